@@ -22,6 +22,7 @@ use quickwit_common::uri::Uri;
 use quickwit_config::{ConfigFormat, SourceConfig, validate_identifier};
 use quickwit_metastore::checkpoint::SourceCheckpoint;
 use quickwit_proto::types::{IndexId, SourceId};
+use quickwit_serve::ReplayDeadLettersQueryParams;
 use quickwit_storage::{StorageResolver, load_file};
 use serde_json::Value as JsonValue;
 use tabled::{Table, Tabled};
@@ -148,6 +149,22 @@ pub fn build_source_command() -> Command {
                         .required(true),
                 ])
             )
+        .subcommand(
+            Command::new("replay-dead-letters")
+                .about("Re-ingests the documents of a source rejected by the doc processor into the index of the source.")
+                .args(&[
+                    arg!(--index <INDEX_ID> "Index ID")
+                        .display_order(1)
+                        .required(true),
+                    arg!(--source <SOURCE_ID> "Source ID")
+                        .display_order(2)
+                        .required(true),
+                    arg!(--"start-timestamp" <TIMESTAMP> "Only replays the documents rejected at or after that timestamp, in seconds.")
+                        .required(false),
+                    arg!(--"end-timestamp" <TIMESTAMP> "Only replays the documents rejected before that timestamp, in seconds.")
+                        .required(false),
+                ])
+            )
         .arg_required_else_help(true)
 }
 
@@ -204,6 +221,15 @@ pub struct ResetCheckpointArgs {
     pub assume_yes: bool,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct ReplayDeadLettersArgs {
+    pub client_args: ClientArgs,
+    pub index_id: IndexId,
+    pub source_id: SourceId,
+    pub start_timestamp: Option<i64>,
+    pub end_timestamp: Option<i64>,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum SourceCliCommand {
     CreateSource(CreateSourceArgs),
@@ -213,6 +239,7 @@ pub enum SourceCliCommand {
     DescribeSource(DescribeSourceArgs),
     ListSources(ListSourcesArgs),
     ResetCheckpoint(ResetCheckpointArgs),
+    ReplayDeadLetters(ReplayDeadLettersArgs),
 }
 
 impl SourceCliCommand {
@@ -225,6 +252,7 @@ impl SourceCliCommand {
             Self::DescribeSource(args) => describe_source_cli(args).await,
             Self::ListSources(args) => list_sources_cli(args).await,
             Self::ResetCheckpoint(args) => reset_checkpoint_cli(args).await,
+            Self::ReplayDeadLetters(args) => replay_dead_letters_cli(args).await,
         }
     }
 
@@ -247,6 +275,9 @@ impl SourceCliCommand {
             "reset-checkpoint" => {
                 Self::parse_reset_checkpoint_args(submatches).map(Self::ResetCheckpoint)
             }
+            "replay-dead-letters" => {
+                Self::parse_replay_dead_letters_args(submatches).map(Self::ReplayDeadLetters)
+            }
             _ => bail!("unknown source subcommand `{subcommand}`"),
         }
     }
@@ -369,6 +400,33 @@ impl SourceCliCommand {
             assume_yes,
         })
     }
+
+    fn parse_replay_dead_letters_args(
+        mut matches: ArgMatches,
+    ) -> anyhow::Result<ReplayDeadLettersArgs> {
+        let client_args = ClientArgs::parse(&mut matches)?;
+        let index_id = matches
+            .remove_one::<String>("index")
+            .expect("`index` should be a required arg.");
+        let source_id = matches
+            .remove_one::<String>("source")
+            .expect("`source` should be a required arg.");
+        let start_timestamp = matches
+            .remove_one::<String>("start-timestamp")
+            .map(|ts| ts.parse())
+            .transpose()?;
+        let end_timestamp = matches
+            .remove_one::<String>("end-timestamp")
+            .map(|ts| ts.parse())
+            .transpose()?;
+        Ok(ReplayDeadLettersArgs {
+            client_args,
+            index_id,
+            source_id,
+            start_timestamp,
+            end_timestamp,
+        })
+    }
 }
 
 async fn create_source_cli(args: CreateSourceArgs) -> anyhow::Result<()> {
@@ -590,6 +648,28 @@ async fn reset_checkpoint_cli(args: ResetCheckpointArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+async fn replay_dead_letters_cli(args: ReplayDeadLettersArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "replay-dead-letters");
+    println!("❯ Replaying dead letters...");
+    let replay_dead_letters_query_params = ReplayDeadLettersQueryParams {
+        start_timestamp: args.start_timestamp,
+        end_timestamp: args.end_timestamp,
+    };
+    let qw_client = args.client_args.client();
+    let replay_dead_letters_response = qw_client
+        .sources(&args.index_id)
+        .replay_dead_letters(&args.source_id, replay_dead_letters_query_params)
+        .await?;
+    println!(
+        "{} {} dead letters successfully replayed ({} rejected, {} kept for the next replay).",
+        "✔".color(GREEN_COLOR),
+        replay_dead_letters_response.num_replayed_docs,
+        replay_dead_letters_response.num_rejected_docs,
+        replay_dead_letters_response.num_kept_docs,
+    );
+    Ok(())
+}
+
 /// Recursively flattens a JSON object into a vector of `(path, value)` tuples where `path`
 /// represents the full path of each property in the original object. For instance, `{"root": true,
 /// "parent": {"child": 0}}` yields `[("root", true), ("parent.child", 0)]`. Arrays are not
@@ -820,6 +900,33 @@ mod tests {
         assert_eq!(command, expected_command);
     }
 
+    #[test]
+    fn test_parse_replay_dead_letters_args() {
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from(vec![
+                "source",
+                "replay-dead-letters",
+                "--index",
+                "hdfs-logs",
+                "--source",
+                "hdfs-logs-source",
+                "--start-timestamp",
+                "1700000000",
+            ])
+            .unwrap();
+        let command = CliCommand::parse_cli_args(matches).unwrap();
+        let expected_command =
+            CliCommand::Source(SourceCliCommand::ReplayDeadLetters(ReplayDeadLettersArgs {
+                client_args: ClientArgs::default(),
+                index_id: "hdfs-logs".to_string(),
+                source_id: "hdfs-logs-source".to_string(),
+                start_timestamp: Some(1_700_000_000),
+                end_timestamp: None,
+            }));
+        assert_eq!(command, expected_command);
+    }
+
     #[test]
     fn test_make_describe_source_tables() {
         assert!(
@@ -840,6 +947,8 @@ mod tests {
             source_params: SourceParams::file_from_str("path/to/file").unwrap(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         }];
         let expected_source = vec![SourceRow {
             source_id: "foo-source".to_string(),
@@ -901,6 +1010,8 @@ mod tests {
                 source_params: SourceParams::stdin(),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                dead_letter: None,
+                input_schema: None,
             },
             SourceConfig {
                 source_id: "bar-source".to_string(),
@@ -909,6 +1020,8 @@ mod tests {
                 source_params: SourceParams::stdin(),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                dead_letter: None,
+                input_schema: None,
             },
         ];
         let expected_sources = [
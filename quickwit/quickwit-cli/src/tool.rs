@@ -420,6 +420,8 @@ pub async fn local_ingest_docs_cli(args: LocalIngestDocsArgs) -> anyhow::Result<
         source_params,
         transform_config,
         input_format: args.input_format,
+        dead_letter: None,
+        input_schema: None,
     };
     run_index_checklist(
         &mut metastore,
@@ -545,6 +547,7 @@ pub async fn local_search_cli(args: LocalSearchArgs) -> anyhow::Result<()> {
         max_hits: args.max_hits as u64,
         search_fields: args.search_fields,
         snippet_fields: args.snippet_fields,
+        source_fields: None,
         start_timestamp: args.start_timestamp,
         end_timestamp: args.end_timestamp,
         aggs,
@@ -552,6 +555,9 @@ pub async fn local_search_cli(args: LocalSearchArgs) -> anyhow::Result<()> {
         sort_by,
         count_all: CountHits::CountAll,
         allow_failed_splits: false,
+        timeout: None,
+        profile: false,
+        knn: None,
     };
     let search_request =
         search_request_from_api_request(vec![args.index_id], search_request_query_string)?;
@@ -610,6 +616,8 @@ pub async fn merge_cli(args: MergeArgs) -> anyhow::Result<()> {
                 source_params: SourceParams::Vec(VecSourceParams::default()),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                dead_letter: None,
+                input_schema: None,
             },
             pipeline_uid: PipelineUid::random(),
         })
@@ -0,0 +1,276 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+
+use anyhow::{bail, ensure};
+use quickwit_proto::types::IndexId;
+use quickwit_query::query_ast::QueryAst;
+use serde::{Deserialize, Serialize};
+
+use crate::validate_identifier;
+
+pub type IndexAliasId = String;
+
+/// An index alias is a stable name pointing to one or more indexes. Searching an alias searches
+/// all its target indexes, and writing to an alias writes to its write index.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IndexAlias {
+    pub alias_id: IndexAliasId,
+    pub targets: Vec<IndexAliasTarget>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IndexAliasTarget {
+    pub index_id: IndexId,
+    /// Query ANDed with the queries of the searches targeting the index through the alias.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<QueryAst>,
+    #[serde(default)]
+    pub is_write_index: bool,
+}
+
+/// An action updating the targets of an index alias. The metastore applies the actions of an
+/// update atomically: either all of them succeed or none of them is applied.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexAliasAction {
+    /// Adds a target to the alias, or replaces the target pointing to the same index. The alias
+    /// is created if it does not exist.
+    Add {
+        alias_id: IndexAliasId,
+        target: IndexAliasTarget,
+    },
+    /// Removes the target of the alias pointing to the index. The alias is deleted once it has no
+    /// targets left.
+    Remove {
+        alias_id: IndexAliasId,
+        index_id: IndexId,
+    },
+}
+
+impl IndexAliasAction {
+    pub fn alias_id(&self) -> &IndexAliasId {
+        match self {
+            IndexAliasAction::Add { alias_id, .. } => alias_id,
+            IndexAliasAction::Remove { alias_id, .. } => alias_id,
+        }
+    }
+}
+
+impl IndexAlias {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        validate_identifier("index alias", &self.alias_id)?;
+
+        ensure!(!self.targets.is_empty(), "`targets` must not be empty");
+
+        let mut index_ids = HashSet::with_capacity(self.targets.len());
+        let mut num_write_indexes = 0;
+
+        for target in &self.targets {
+            validate_identifier("index", &target.index_id)?;
+
+            if !index_ids.insert(&target.index_id) {
+                bail!(
+                    "index `{}` is targeted more than once by alias `{}`",
+                    target.index_id,
+                    self.alias_id
+                );
+            }
+            if target.is_write_index {
+                num_write_indexes += 1;
+            }
+        }
+        ensure!(
+            num_write_indexes <= 1,
+            "alias `{}` must have at most one write index",
+            self.alias_id
+        );
+        Ok(())
+    }
+
+    /// Returns the ID of the index that receives the documents written to the alias: the target
+    /// flagged as write index or, failing that, the only target of the alias.
+    pub fn write_index_id(&self) -> Option<&IndexId> {
+        if let Some(target) = self.targets.iter().find(|target| target.is_write_index) {
+            return Some(&target.index_id);
+        }
+        if let [target] = &self.targets[..] {
+            return Some(&target.index_id);
+        }
+        None
+    }
+
+    /// Applies an action to the alias. Flagging a target as write index unflags the other
+    /// targets. Returns an error if the action removes a target that the alias does not have.
+    pub fn apply_action(&mut self, action: IndexAliasAction) -> anyhow::Result<()> {
+        match action {
+            IndexAliasAction::Add { target, .. } => {
+                if target.is_write_index {
+                    for other_target in &mut self.targets {
+                        other_target.is_write_index = false;
+                    }
+                }
+                if let Some(other_target) = self
+                    .targets
+                    .iter_mut()
+                    .find(|other_target| other_target.index_id == target.index_id)
+                {
+                    *other_target = target;
+                } else {
+                    self.targets.push(target);
+                }
+            }
+            IndexAliasAction::Remove { index_id, .. } => {
+                let num_targets = self.targets.len();
+                self.targets.retain(|target| target.index_id != index_id);
+                ensure!(
+                    self.targets.len() < num_targets,
+                    "alias `{}` does not target index `{index_id}`",
+                    self.alias_id
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(any(test, feature = "testsuite"))]
+    pub fn for_test(alias_id: &str, index_ids: &[&str]) -> Self {
+        let targets = index_ids
+            .iter()
+            .map(|index_id| IndexAliasTarget {
+                index_id: index_id.to_string(),
+                filter: None,
+                is_write_index: false,
+            })
+            .collect();
+        IndexAlias {
+            alias_id: alias_id.to_string(),
+            targets,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_alias_serde() {
+        let index_alias_json = r#"{
+            "alias_id": "logs",
+            "targets": [
+                {
+                    "index_id": "logs-2026-09"
+                },
+                {
+                    "index_id": "logs-2026-10",
+                    "filter": {"type": "term", "field": "tenant", "value": "acme"},
+                    "is_write_index": true
+                }
+            ]
+        }"#;
+        let index_alias: IndexAlias = serde_json::from_str(index_alias_json).unwrap();
+        assert_eq!(index_alias.alias_id, "logs");
+        assert_eq!(index_alias.targets.len(), 2);
+        assert!(index_alias.targets[0].filter.is_none());
+        assert!(!index_alias.targets[0].is_write_index);
+        assert!(matches!(
+            index_alias.targets[1].filter,
+            Some(QueryAst::Term(_))
+        ));
+        assert!(index_alias.targets[1].is_write_index);
+        index_alias.validate().unwrap();
+
+        let index_alias_json = serde_json::to_string(&index_alias).unwrap();
+        let deserialized_index_alias: IndexAlias = serde_json::from_str(&index_alias_json).unwrap();
+        assert_eq!(deserialized_index_alias, index_alias);
+    }
+
+    #[test]
+    fn test_index_alias_validate() {
+        let index_alias = IndexAlias::for_test("logs", &[]);
+        let error = index_alias.validate().unwrap_err();
+        assert_eq!(error.to_string(), "`targets` must not be empty");
+
+        let index_alias = IndexAlias::for_test("logs", &["logs-2026-10", "logs-2026-10"]);
+        let error = index_alias.validate().unwrap_err();
+        assert!(error.to_string().contains("more than once"));
+
+        let mut index_alias = IndexAlias::for_test("logs", &["logs-2026-09", "logs-2026-10"]);
+        index_alias.targets[0].is_write_index = true;
+        index_alias.targets[1].is_write_index = true;
+        let error = index_alias.validate().unwrap_err();
+        assert!(error.to_string().contains("at most one write index"));
+    }
+
+    #[test]
+    fn test_index_alias_apply_action() {
+        let mut index_alias = IndexAlias::for_test("logs", &["logs-2026-09"]);
+
+        let add_action = IndexAliasAction::Add {
+            alias_id: "logs".to_string(),
+            target: IndexAliasTarget {
+                index_id: "logs-2026-10".to_string(),
+                filter: None,
+                is_write_index: true,
+            },
+        };
+        index_alias.apply_action(add_action).unwrap();
+        assert_eq!(index_alias.targets.len(), 2);
+        assert_eq!(index_alias.write_index_id().unwrap(), "logs-2026-10");
+
+        // Flagging another write index unflags the previous one.
+        let add_action = IndexAliasAction::Add {
+            alias_id: "logs".to_string(),
+            target: IndexAliasTarget {
+                index_id: "logs-2026-09".to_string(),
+                filter: Some(QueryAst::MatchAll),
+                is_write_index: true,
+            },
+        };
+        index_alias.apply_action(add_action).unwrap();
+        assert_eq!(index_alias.targets.len(), 2);
+        assert_eq!(index_alias.write_index_id().unwrap(), "logs-2026-09");
+        assert_eq!(index_alias.targets[0].filter, Some(QueryAst::MatchAll));
+
+        let remove_action = IndexAliasAction::Remove {
+            alias_id: "logs".to_string(),
+            index_id: "logs-2026-10".to_string(),
+        };
+        index_alias.apply_action(remove_action.clone()).unwrap();
+        assert_eq!(index_alias.targets.len(), 1);
+
+        let error = index_alias.apply_action(remove_action).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "alias `logs` does not target index `logs-2026-10`"
+        );
+    }
+
+    #[test]
+    fn test_index_alias_write_index_id() {
+        let index_alias = IndexAlias::for_test("logs", &["logs-2026-10"]);
+        assert_eq!(index_alias.write_index_id().unwrap(), "logs-2026-10");
+
+        let mut index_alias = IndexAlias::for_test("logs", &["logs-2026-09", "logs-2026-10"]);
+        assert!(index_alias.write_index_id().is_none());
+
+        index_alias.targets[1].is_write_index = true;
+        assert_eq!(index_alias.write_index_id().unwrap(), "logs-2026-10");
+    }
+}
@@ -14,6 +14,7 @@
 
 pub(crate) mod serialize;
 
+use std::collections::BTreeMap;
 use std::hash::{Hash, Hasher};
 use std::num::NonZeroUsize;
 use std::str::FromStr;
@@ -28,6 +29,7 @@ use humantime::parse_duration;
 use quickwit_common::uri::Uri;
 use quickwit_doc_mapper::{DocMapper, DocMapperBuilder, DocMapping};
 use quickwit_proto::types::IndexId;
+use quickwit_query::query_ast::QueryRewriter;
 use serde::{Deserialize, Serialize};
 pub use serialize::{load_index_config_from_user_config, load_index_config_update};
 use siphasher::sip::SipHasher;
@@ -191,6 +193,36 @@ impl Default for IngestSettings {
 pub struct SearchSettings {
     #[serde(default)]
     pub default_search_fields: Vec<String>,
+    /// Rules rewriting the queries of the index at search time.
+    #[serde(default, skip_serializing_if = "QueryRewrites::is_empty")]
+    pub query_rewrites: QueryRewrites,
+}
+
+/// Rules rewriting the queries of an index at search time, without reindexing.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct QueryRewrites {
+    /// Synonym rules, either sets of equivalent words (`k8s, kubernetes`) or explicit mappings
+    /// (`k8s => kubernetes`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub synonyms: Vec<String>,
+    /// Words removed from the queries.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stop_words: Vec<String>,
+    /// Alternative field names, mapped to the field they refer to.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub field_aliases: BTreeMap<String, String>,
+}
+
+impl QueryRewrites {
+    pub fn is_empty(&self) -> bool {
+        self.synonyms.is_empty() && self.stop_words.is_empty() && self.field_aliases.is_empty()
+    }
+
+    /// Builds the query rewriter applying the rules.
+    pub fn query_rewriter(&self) -> anyhow::Result<QueryRewriter> {
+        QueryRewriter::new(&self.synonyms, &self.stop_words, &self.field_aliases)
+    }
 }
 
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
@@ -379,6 +411,7 @@ impl IndexConfig {
                 r#"attributes.server"#.to_string(),
                 r"attributes.server\.status".to_string(),
             ],
+            ..Default::default()
         };
         IndexConfig {
             index_id: index_id.to_string(),
@@ -454,6 +487,7 @@ impl crate::TestableForRegression for IndexConfig {
                 message_mapping,
             ],
             timestamp_field: Some("timestamp".to_string()),
+            id_field: None,
             tag_fields: BTreeSet::from_iter(["tenant_id".to_string(), "log_level".to_string()]),
             partition_key: Some("tenant_id".to_string()),
             max_num_partitions: NonZeroU32::new(100).unwrap(),
@@ -484,6 +518,7 @@ impl crate::TestableForRegression for IndexConfig {
         };
         let search_settings = SearchSettings {
             default_search_fields: vec!["message".to_string()],
+            ..Default::default()
         };
         let retention_policy_opt = Some(RetentionPolicy {
             retention_period: "90 days".to_string(),
@@ -537,8 +572,18 @@ pub(super) fn validate_index_config(
     // and doc mapper implementations.
     // TODO see if we should store the byproducton the IndexConfig.
     build_doc_mapper(doc_mapping, search_settings)?;
+    search_settings
+        .query_rewrites
+        .query_rewriter()
+        .context("invalid `search_settings.query_rewrites`")?;
 
     indexing_settings.merge_policy.validate()?;
+    ensure!(
+        indexing_settings.merge_policy.time_bucket().is_none()
+            || doc_mapping.timestamp_field.is_some(),
+        "time bucketed merge policy requires a timestamp field, but doc mapping does not declare \
+         one"
+    );
     indexing_settings.resources.validate()?;
 
     if let Some(retention_policy) = retention_policy_opt {
@@ -637,6 +682,7 @@ mod tests {
             index_config.search_settings,
             SearchSettings {
                 default_search_fields: vec!["severity_text".to_string(), "body".to_string()],
+                ..Default::default()
             }
         );
     }
@@ -679,6 +725,7 @@ mod tests {
 
             let expected_search_settings = SearchSettings {
                 default_search_fields: vec!["body".to_string()],
+                ..Default::default()
             };
             assert_eq!(index_config.search_settings, expected_search_settings);
             assert!(index_config.retention_policy_opt.is_none());
@@ -714,6 +761,7 @@ mod tests {
                 index_config.search_settings,
                 SearchSettings {
                     default_search_fields: vec!["body".to_string()],
+                    ..Default::default()
                 }
             );
         }
@@ -956,4 +1004,50 @@ mod tests {
         let error = serde_yaml::from_str::<IngestSettings>(ingest_settings_yaml).unwrap_err();
         assert!(error.to_string().contains("expected a nonzero"));
     }
+
+    #[test]
+    fn test_index_config_query_rewrites() {
+        let config_yaml = r#"
+            version: 0.8
+            index_id: hdfs-logs
+            index_uri: "s3://my-index"
+            doc_mapping: {}
+            search_settings:
+              default_search_fields: [body]
+              query_rewrites:
+                synonyms:
+                  - k8s, kubernetes
+                  - nyc => new york
+                stop_words: [the, a]
+                field_aliases:
+                  msg: body
+        "#;
+        let index_config: IndexConfig = load_index_config_from_user_config(
+            ConfigFormat::Yaml,
+            config_yaml.as_bytes(),
+            &Uri::for_test("s3://my-index"),
+        )
+        .unwrap();
+        let query_rewrites = &index_config.search_settings.query_rewrites;
+        assert_eq!(
+            query_rewrites.synonyms,
+            ["k8s, kubernetes", "nyc => new york"]
+        );
+        assert_eq!(query_rewrites.stop_words, ["the", "a"]);
+        assert_eq!(query_rewrites.field_aliases["msg"], "body");
+
+        // Query rewrites are applied at search time and do not affect the indexing pipelines.
+        let mut index_config_without_rewrites = index_config.clone();
+        index_config_without_rewrites.search_settings.query_rewrites = QueryRewrites::default();
+        assert!(index_config.equals_fingerprint(&index_config_without_rewrites));
+
+        let invalid_config_yaml = config_yaml.replace("nyc => new york", "new york => nyc");
+        let error = load_index_config_from_user_config(
+            ConfigFormat::Yaml,
+            invalid_config_yaml.as_bytes(),
+            &Uri::for_test("s3://my-index"),
+        )
+        .unwrap_err();
+        assert!(format!("{error:#}").contains("invalid `search_settings.query_rewrites`"),);
+    }
 }
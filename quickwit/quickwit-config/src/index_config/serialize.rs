@@ -257,7 +257,9 @@ impl From<IndexConfig> for IndexConfigV0_8 {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::merge_policy_config::{MergePolicyConfig, StableLogMergePolicyConfig};
+    use crate::merge_policy_config::{
+        MergePolicyConfig, StableLogMergePolicyConfig, TimeBucketedMergePolicyConfig,
+    };
 
     fn minimal_index_config_for_serialization() -> IndexConfigForSerialization {
         serde_yaml::from_str(
@@ -301,6 +303,19 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_validate_time_bucketed_merge_policy_requires_timestamp_field() {
+        let mut invalid_index_config: IndexConfigForSerialization =
+            minimal_index_config_for_serialization();
+        invalid_index_config.indexing_settings.merge_policy =
+            MergePolicyConfig::TimeBucketed(TimeBucketedMergePolicyConfig::default());
+        let validation_err = invalid_index_config
+            .build_and_validate(None)
+            .unwrap_err()
+            .to_string();
+        assert!(validation_err.contains("time bucketed merge policy requires a timestamp field"));
+    }
+
     #[test]
     fn test_validate_retention_policy() {
         // Not yet invalid, but we modify it right after this.
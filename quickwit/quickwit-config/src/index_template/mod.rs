@@ -232,6 +232,7 @@ mod tests {
         };
         index_template.search_settings = SearchSettings {
             default_search_fields: vec!["message".to_string()],
+            ..Default::default()
         };
         index_template.retention_policy_opt = Some(RetentionPolicy {
             retention_period: "42 days".to_string(),
@@ -28,6 +28,7 @@ use regex::Regex;
 
 mod cluster_config;
 mod config_value;
+mod index_alias;
 mod index_config;
 mod index_template;
 pub mod merge_policy_config;
@@ -45,8 +46,9 @@ pub use cluster_config::ClusterConfig;
 // See #2048
 use index_config::serialize::{IndexConfigV0_8, VersionedIndexConfig};
 pub use index_config::{
-    IndexConfig, IndexingResources, IndexingSettings, IngestSettings, RetentionPolicy,
-    SearchSettings, build_doc_mapper, load_index_config_from_user_config, load_index_config_update,
+    IndexConfig, IndexingResources, IndexingSettings, IngestSettings, QueryRewrites,
+    RetentionPolicy, SearchSettings, build_doc_mapper, load_index_config_from_user_config,
+    load_index_config_update,
 };
 pub use quickwit_doc_mapper::DocMapping;
 use serde::Serialize;
@@ -55,26 +57,29 @@ use serde_json::Value as JsonValue;
 use siphasher::sip::SipHasher;
 use source_config::FileSourceParamsForSerde;
 pub use source_config::{
-    CLI_SOURCE_ID, FileSourceMessageType, FileSourceNotification, FileSourceParams, FileSourceSqs,
-    INGEST_API_SOURCE_ID, INGEST_V2_SOURCE_ID, KafkaSourceParams, KinesisSourceParams,
+    CLI_SOURCE_ID, DeadLetterConfig, FileSourceMessageType, FileSourceNotification,
+    FileSourceParams, FileSourcePolling, FileSourceSqs, INGEST_API_SOURCE_ID, INGEST_V2_SOURCE_ID,
+    InputSchemaConfig, KafkaSourceParams, KinesisSourceParams, NatsSourceAuth, NatsSourceParams,
     PubSubSourceParams, PulsarSourceAuth, PulsarSourceParams, RegionOrEndpoint, SourceConfig,
     SourceInputFormat, SourceParams, TransformConfig, VecSourceParams, VoidSourceParams,
     load_source_config_from_user_config, load_source_config_update,
 };
 use tracing::warn;
 
+pub use crate::index_alias::{IndexAlias, IndexAliasAction, IndexAliasId, IndexAliasTarget};
 use crate::index_template::IndexTemplateV0_8;
 pub use crate::index_template::{IndexTemplate, IndexTemplateId, VersionedIndexTemplate};
 use crate::merge_policy_config::{
     ConstWriteAmplificationMergePolicyConfig, MergePolicyConfig, StableLogMergePolicyConfig,
+    TimeBucket, TimeBucketedMergePolicyConfig,
 };
 pub use crate::metastore_config::{
     MetastoreBackend, MetastoreConfig, MetastoreConfigs, PostgresMetastoreConfig,
 };
 pub use crate::node_config::{
     DEFAULT_QW_CONFIG_PATH, GrpcConfig, IndexerConfig, IngestApiConfig, JaegerConfig,
-    KeepAliveConfig, NodeConfig, RestConfig, SearcherConfig, SplitCacheLimits,
-    StorageTimeoutPolicy, TlsConfig,
+    KeepAliveConfig, NodeConfig, OTHER_SEARCH_TENANT_ID, RestConfig, RootSearchCacheConfig,
+    SearchTenantLimits, SearcherConfig, SplitCacheLimits, StorageTimeoutPolicy, TlsConfig,
 };
 use crate::source_config::serialize::{SourceConfigV0_7, SourceConfigV0_8, VersionedSourceConfig};
 pub use crate::storage_config::{
@@ -99,22 +104,28 @@ pub fn disable_ingest_v1() -> bool {
 #[derive(utoipa::OpenApi)]
 #[openapi(components(schemas(
     ConstWriteAmplificationMergePolicyConfig,
+    DeadLetterConfig,
     DocMapping,
     FileSourceMessageType,
     FileSourceNotification,
     FileSourceParamsForSerde,
+    FileSourcePolling,
     FileSourceSqs,
     IndexConfigV0_8,
     IndexingResources,
     IndexingSettings,
     IndexTemplateV0_8,
     IngestSettings,
+    InputSchemaConfig,
     KafkaSourceParams,
     KinesisSourceParams,
     MergePolicyConfig,
+    NatsSourceAuth,
+    NatsSourceParams,
     PubSubSourceParams,
     PulsarSourceAuth,
     PulsarSourceParams,
+    QueryRewrites,
     RegionOrEndpoint,
     RetentionPolicy,
     SearchSettings,
@@ -123,6 +134,8 @@ pub fn disable_ingest_v1() -> bool {
     SourceInputFormat,
     SourceParams,
     StableLogMergePolicyConfig,
+    TimeBucket,
+    TimeBucketedMergePolicyConfig,
     TransformConfig,
     VecSourceParams,
     VersionedIndexConfig,
@@ -88,6 +88,64 @@ pub struct StableLogMergePolicyConfig {
     pub maturation_period: Duration,
 }
 
+/// Width of the time windows of the [`TimeBucketedMergePolicyConfig`]. Windows are aligned on
+/// UTC hours or days.
+#[derive(
+    Clone, Copy, Debug, Default, Serialize, Deserialize, Eq, PartialEq, Hash, utoipa::ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeBucket {
+    Hour,
+    #[default]
+    Day,
+}
+
+impl TimeBucket {
+    pub fn duration(&self) -> Duration {
+        match self {
+            TimeBucket::Hour => Duration::from_secs(3600),
+            TimeBucket::Day => Duration::from_secs(24 * 3600),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq, Hash, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TimeBucketedMergePolicyConfig {
+    /// Width of the time windows. Only splits whose time range falls within the same window are
+    /// merged together.
+    #[serde(default)]
+    pub bucket: TimeBucket,
+    /// Number of splits to merge together in a single merge operation.
+    #[serde(default = "default_merge_factor")]
+    pub merge_factor: usize,
+    /// Maximum number of splits that can be merged together in a single merge operation.
+    #[serde(default = "default_max_merge_factor")]
+    pub max_merge_factor: usize,
+    /// Maximum number of merges that a given split should undergo.
+    #[serde(default = "default_max_merge_ops")]
+    pub max_merge_ops: usize,
+    /// Duration relative to `split.created_timestamp` after which a split
+    /// becomes mature.
+    #[schema(value_type = String)]
+    #[serde(default = "default_maturation_period")]
+    #[serde(deserialize_with = "parse_human_duration")]
+    #[serde(serialize_with = "serialize_duration")]
+    pub maturation_period: Duration,
+}
+
+impl Default for TimeBucketedMergePolicyConfig {
+    fn default() -> Self {
+        TimeBucketedMergePolicyConfig {
+            bucket: TimeBucket::default(),
+            merge_factor: default_merge_factor(),
+            max_merge_factor: default_max_merge_factor(),
+            max_merge_ops: default_max_merge_ops(),
+            maturation_period: default_maturation_period(),
+        }
+    }
+}
+
 fn default_merge_factor() -> usize {
     10
 }
@@ -147,6 +205,8 @@ pub enum MergePolicyConfig {
     #[serde(rename = "stable_log")]
     #[serde(alias = "default")]
     StableLog(StableLogMergePolicyConfig),
+    #[serde(rename = "time_bucketed")]
+    TimeBucketed(TimeBucketedMergePolicyConfig),
 }
 
 impl Default for MergePolicyConfig {
@@ -160,6 +220,14 @@ impl MergePolicyConfig {
         MergePolicyConfig::Nop
     }
 
+    /// Returns the time window in which documents must be grouped at indexing time, if any.
+    pub fn time_bucket(&self) -> Option<TimeBucket> {
+        match self {
+            MergePolicyConfig::TimeBucketed(config) => Some(config.bucket),
+            _ => None,
+        }
+    }
+
     pub fn validate(&self) -> anyhow::Result<()> {
         let (merge_factor, max_merge_factor) = match self {
             MergePolicyConfig::Nop => {
@@ -169,6 +237,9 @@ impl MergePolicyConfig {
                 (config.merge_factor, config.max_merge_factor)
             }
             MergePolicyConfig::StableLog(config) => (config.merge_factor, config.max_merge_factor),
+            MergePolicyConfig::TimeBucketed(config) => {
+                (config.merge_factor, config.max_merge_factor)
+            }
         };
         if max_merge_factor < merge_factor {
             anyhow::bail!(
@@ -179,3 +250,39 @@ impl MergePolicyConfig {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_bucketed_merge_policy_config_serde() {
+        let merge_policy_yaml = r#"
+            type: time_bucketed
+            bucket: hour
+            merge_factor: 5
+            maturation_period: 2 days
+        "#;
+        let merge_policy: MergePolicyConfig = serde_yaml::from_str(merge_policy_yaml).unwrap();
+        let expected_merge_policy =
+            MergePolicyConfig::TimeBucketed(TimeBucketedMergePolicyConfig {
+                bucket: TimeBucket::Hour,
+                merge_factor: 5,
+                maturation_period: Duration::from_secs(2 * 24 * 3600),
+                ..Default::default()
+            });
+        assert_eq!(merge_policy, expected_merge_policy);
+        assert_eq!(merge_policy.time_bucket(), Some(TimeBucket::Hour));
+        merge_policy.validate().unwrap();
+
+        let merge_policy_json = serde_json::to_value(&merge_policy).unwrap();
+        let deserialized_merge_policy: MergePolicyConfig =
+            serde_json::from_value(merge_policy_json).unwrap();
+        assert_eq!(deserialized_merge_policy, merge_policy);
+
+        let default_merge_policy: MergePolicyConfig =
+            serde_yaml::from_str("type: time_bucketed").unwrap();
+        assert_eq!(default_merge_policy.time_bucket(), Some(TimeBucket::Day));
+        assert_eq!(MergePolicyConfig::default().time_bucket(), None);
+    }
+}
@@ -43,6 +43,9 @@ use crate::{ConfigFormat, MetastoreConfigs};
 
 pub const DEFAULT_QW_CONFIG_PATH: &str = "config/quickwit.yaml";
 
+/// Search tenant the searches of the tenants without an entry in `tenant_limits` are accounted to.
+pub const OTHER_SEARCH_TENANT_ID: &str = "__other__";
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct RestConfig {
@@ -278,11 +281,101 @@ pub struct SearcherConfig {
     pub split_cache: Option<SplitCacheLimits>,
     #[serde(default = "SearcherConfig::default_request_timeout_secs")]
     request_timeout_secs: NonZeroU64,
+    /// Root searches taking longer than this threshold are logged, along with a breakdown of
+    /// their execution, under the `quickwit_search::slow_query_log` tracing target.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_query_log_threshold_millis: Option<NonZeroU64>,
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub storage_timeout_policy: Option<StorageTimeoutPolicy>,
     pub warmup_memory_budget: ByteSize,
     pub warmup_single_split_initial_allocation: ByteSize,
+    /// Limits applied to the tenants that have no entry in `tenant_limits`.
+    pub default_tenant_limits: SearchTenantLimits,
+    /// Limits applied to the split searches of specific tenants. Searches are accounted to the
+    /// tenant passed in the `x-quickwit-tenant-id` header or, if missing, to the index ID
+    /// patterns they target. Searches of tenants not listed here share the
+    /// [`OTHER_SEARCH_TENANT_ID`] tenant.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tenant_limits: HashMap<String, SearchTenantLimits>,
+    /// Caches the responses of the root searches, for instance to serve the repeated searches of
+    /// dashboards. Disabled if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root_search_cache: Option<RootSearchCacheConfig>,
+}
+
+/// Configuration of the root search cache.
+///
+/// The hits of the cached searches are cached for the part of their time window aligned on
+/// multiples of `time_granularity_secs`, so that the searches over a sliding window, e.g. "last 15
+/// minutes", share their cache entries.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RootSearchCacheConfig {
+    /// Maximum memory used by the cached responses.
+    #[serde(default = "RootSearchCacheConfig::default_capacity")]
+    pub capacity: ByteSize,
+    /// Granularity of the time windows of the cached hits.
+    #[serde(default = "RootSearchCacheConfig::default_time_granularity_secs")]
+    pub time_granularity_secs: NonZeroU64,
+}
+
+impl RootSearchCacheConfig {
+    fn default_capacity() -> ByteSize {
+        ByteSize::mb(64)
+    }
+
+    fn default_time_granularity_secs() -> NonZeroU64 {
+        NonZeroU64::new(60).unwrap()
+    }
+}
+
+impl Default for RootSearchCacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: Self::default_capacity(),
+            time_granularity_secs: Self::default_time_granularity_secs(),
+        }
+    }
+}
+
+/// Admission control limits of a search tenant.
+///
+/// Search permits are handed out to the tenants in proportion to their weight. Within a tenant,
+/// permits are handed out in order.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SearchTenantLimits {
+    /// Share of the search permits the tenant gets when several tenants are waiting for them.
+    #[serde(default = "SearchTenantLimits::default_weight")]
+    pub weight: NonZeroU32,
+    /// Maximum number of concurrent split searches of the tenant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_num_concurrent_split_searches: Option<NonZeroUsize>,
+    /// Maximum amount of warmup memory used by the split searches of the tenant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warmup_memory_budget: Option<ByteSize>,
+    /// Maximum number of split searches of the tenant waiting for a permit. Leaf search requests
+    /// exceeding it are rejected with a `429 Too Many Requests` error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_num_pending_split_searches: Option<NonZeroUsize>,
+}
+
+impl SearchTenantLimits {
+    fn default_weight() -> NonZeroU32 {
+        NonZeroU32::MIN
+    }
+}
+
+impl Default for SearchTenantLimits {
+    fn default() -> Self {
+        Self {
+            weight: Self::default_weight(),
+            max_num_concurrent_split_searches: None,
+            warmup_memory_budget: None,
+            max_num_pending_split_searches: None,
+        }
+    }
 }
 
 /// Configuration controlling how fast a searcher should timeout a `get_slice`
@@ -330,9 +423,13 @@ impl Default for SearcherConfig {
             aggregation_bucket_limit: 65000,
             split_cache: None,
             request_timeout_secs: Self::default_request_timeout_secs(),
+            slow_query_log_threshold_millis: None,
             storage_timeout_policy: None,
             warmup_memory_budget: ByteSize::gb(100),
             warmup_single_split_initial_allocation: ByteSize::gb(1),
+            default_tenant_limits: SearchTenantLimits::default(),
+            tenant_limits: HashMap::new(),
+            root_search_cache: None,
         }
     }
 }
@@ -345,7 +442,27 @@ impl SearcherConfig {
     fn default_request_timeout_secs() -> NonZeroU64 {
         NonZeroU64::new(30).unwrap()
     }
+
+    /// The duration above which a root search is logged as slow, if set.
+    pub fn slow_query_log_threshold(&self) -> Option<Duration> {
+        self.slow_query_log_threshold_millis
+            .map(|threshold_millis| Duration::from_millis(threshold_millis.get()))
+    }
+
+    /// Returns the admission control limits of a search tenant.
+    pub fn tenant_limits(&self, tenant_id: &str) -> &SearchTenantLimits {
+        self.tenant_limits
+            .get(tenant_id)
+            .unwrap_or(&self.default_tenant_limits)
+    }
+
     fn validate(&self) -> anyhow::Result<()> {
+        if self.tenant_limits.contains_key(OTHER_SEARCH_TENANT_ID) {
+            anyhow::bail!(
+                "tenant ID `{OTHER_SEARCH_TENANT_ID}` is reserved, use `default_tenant_limits` to \
+                 configure the limits of the tenants not listed in `tenant_limits`"
+            );
+        }
         if let Some(split_cache_limits) = self.split_cache {
             if self.max_num_concurrent_split_searches
                 > split_cache_limits.max_file_descriptors.get() as usize
@@ -840,4 +957,19 @@ mod tests {
         };
         assert!(grpc_config.validate().is_err());
     }
+
+    #[test]
+    fn test_searcher_config_validate_reserved_tenant_id() {
+        let mut searcher_config = SearcherConfig::default();
+        searcher_config
+            .tenant_limits
+            .insert("tenant-a".to_string(), SearchTenantLimits::default());
+        searcher_config.validate().unwrap();
+
+        searcher_config.tenant_limits.insert(
+            OTHER_SEARCH_TENANT_ID.to_string(),
+            SearchTenantLimits::default(),
+        );
+        searcher_config.validate().unwrap_err();
+    }
 }
@@ -524,7 +524,7 @@ pub fn node_config_for_tests_from_ports(
 mod tests {
     use std::env;
     use std::net::Ipv4Addr;
-    use std::num::{NonZeroU64, NonZeroUsize};
+    use std::num::{NonZeroU32, NonZeroU64, NonZeroUsize};
     use std::path::Path;
 
     use bytesize::ByteSize;
@@ -665,6 +665,7 @@ mod tests {
                 _max_num_concurrent_split_streams: Some(serde::de::IgnoredAny),
                 split_cache: None,
                 request_timeout_secs: NonZeroU64::new(30).unwrap(),
+                slow_query_log_threshold_millis: Some(NonZeroU64::new(5_000).unwrap()),
                 storage_timeout_policy: Some(crate::StorageTimeoutPolicy {
                     min_throughtput_bytes_per_secs: 100_000,
                     timeout_millis: 2_000,
@@ -672,6 +673,20 @@ mod tests {
                 }),
                 warmup_memory_budget: ByteSize::gb(100),
                 warmup_single_split_initial_allocation: ByteSize::gb(1),
+                default_tenant_limits: crate::SearchTenantLimits::default(),
+                tenant_limits: HashMap::from_iter([(
+                    "dashboards".to_string(),
+                    crate::SearchTenantLimits {
+                        weight: NonZeroU32::new(2).unwrap(),
+                        max_num_concurrent_split_searches: Some(NonZeroUsize::new(50).unwrap()),
+                        warmup_memory_budget: Some(ByteSize::gb(10)),
+                        max_num_pending_split_searches: Some(NonZeroUsize::new(1_000).unwrap()),
+                    },
+                )]),
+                root_search_cache: Some(crate::RootSearchCacheConfig {
+                    capacity: ByteSize::mb(256),
+                    time_granularity_secs: NonZeroU64::new(30).unwrap(),
+                }),
             }
         );
         assert_eq!(
@@ -19,12 +19,12 @@ use std::hash::{Hash, Hasher};
 use std::num::NonZeroUsize;
 use std::str::FromStr;
 
-use anyhow::ensure;
+use anyhow::{Context, bail, ensure};
 use bytes::Bytes;
 use quickwit_common::is_false;
 use quickwit_common::uri::Uri;
 use quickwit_proto::metastore::SourceType;
-use quickwit_proto::types::SourceId;
+use quickwit_proto::types::{IndexId, SourceId};
 use regex::Regex;
 use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize};
@@ -34,7 +34,7 @@ use serialize::VersionedSourceConfig;
 pub use serialize::{load_source_config_from_user_config, load_source_config_update};
 use siphasher::sip::SipHasher;
 
-use crate::{disable_ingest_v1, enable_ingest_v2};
+use crate::{disable_ingest_v1, enable_ingest_v2, validate_identifier};
 
 /// Reserved source ID for the `quickwit index ingest` CLI command.
 pub const CLI_SOURCE_ID: &str = "_ingest-cli-source";
@@ -68,6 +68,12 @@ pub struct SourceConfig {
     // Denotes the input data format.
     #[serde(default)]
     pub input_format: SourceInputFormat,
+
+    /// Destination of the documents rejected by the doc processor.
+    pub dead_letter: Option<DeadLetterConfig>,
+
+    /// Schema of the records, for the `avro` and `protobuf` input formats.
+    pub input_schema: Option<InputSchemaConfig>,
 }
 
 impl SourceConfig {
@@ -85,6 +91,7 @@ impl SourceConfig {
             SourceParams::IngestCli => serde_json::to_value(()),
             SourceParams::Kafka(params) => serde_json::to_value(params),
             SourceParams::Kinesis(params) => serde_json::to_value(params),
+            SourceParams::Nats(params) => serde_json::to_value(params),
             SourceParams::Pulsar(params) => serde_json::to_value(params),
             SourceParams::Stdin => serde_json::to_value(()),
             SourceParams::Vec(params) => serde_json::to_value(params),
@@ -102,6 +109,8 @@ impl SourceConfig {
             source_params: SourceParams::IngestCli,
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         }
     }
 
@@ -114,6 +123,8 @@ impl SourceConfig {
             source_params: SourceParams::Ingest,
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         }
     }
 
@@ -126,6 +137,8 @@ impl SourceConfig {
             source_params: SourceParams::IngestApi,
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         }
     }
 
@@ -140,6 +153,12 @@ impl SourceConfig {
         self.num_pipelines.hash(&mut hasher);
         self.source_params.hash(&mut hasher);
         self.transform_config.hash(&mut hasher);
+        if let Some(dead_letter_config) = &self.dead_letter {
+            dead_letter_config.hash(&mut hasher);
+        }
+        if let Some(input_schema_config) = &self.input_schema {
+            input_schema_config.hash(&mut hasher);
+        }
         hasher.finish()
     }
 
@@ -152,6 +171,8 @@ impl SourceConfig {
             source_params,
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         }
     }
 }
@@ -174,6 +195,8 @@ impl crate::TestableForRegression for SourceConfig {
                 timezone: default_timezone(),
             }),
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         }
     }
 
@@ -202,6 +225,17 @@ pub enum SourceInputFormat {
     OtlpTracesProtobuf,
     #[serde(alias = "plain")]
     PlainText,
+    Avro,
+    #[serde(alias = "proto")]
+    Protobuf,
+}
+
+impl SourceInputFormat {
+    /// Returns whether the records of this format are decoded with a schema, configured with
+    /// `input_schema`.
+    pub fn requires_schema(&self) -> bool {
+        matches!(self, SourceInputFormat::Avro | SourceInputFormat::Protobuf)
+    }
 }
 
 impl FromStr for SourceInputFormat {
@@ -228,6 +262,7 @@ pub enum SourceParams {
     IngestCli,
     Kafka(KafkaSourceParams),
     Kinesis(KinesisSourceParams),
+    Nats(NatsSourceParams),
     #[serde(rename = "pubsub")]
     PubSub(PubSubSourceParams),
     Pulsar(PulsarSourceParams),
@@ -261,6 +296,7 @@ impl SourceParams {
             SourceParams::IngestCli => SourceType::Cli,
             SourceParams::Kafka(_) => SourceType::Kafka,
             SourceParams::Kinesis(_) => SourceType::Kinesis,
+            SourceParams::Nats(_) => SourceType::Nats,
             SourceParams::PubSub(_) => SourceType::PubSub,
             SourceParams::Pulsar(_) => SourceType::Pulsar,
             SourceParams::Stdin => SourceType::Stdin,
@@ -281,6 +317,7 @@ impl SourceParams {
             (SourceParams::Kinesis(current), SourceParams::Kinesis(new)) => {
                 current.validate_update(new)
             }
+            (SourceParams::Nats(current), SourceParams::Nats(new)) => current.validate_update(new),
             (SourceParams::PubSub(current), SourceParams::PubSub(new)) => {
                 current.validate_update(new)
             }
@@ -332,19 +369,83 @@ fn default_deduplication_cleanup_interval_secs() -> u32 {
     60
 }
 
+/// Discovers the files to index by periodically listing a prefix, for storages that cannot emit
+/// event notifications.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct FileSourcePolling {
+    /// URI of the prefix under which new files are discovered, e.g. `s3://bucket/logs/`.
+    #[schema(value_type = String)]
+    pub uri: Uri,
+    /// Glob pattern matched against the path of the files relative to `uri`. All the files are
+    /// indexed if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glob: Option<String>,
+    #[serde(default = "default_polling_interval_secs")]
+    pub polling_interval_secs: u32,
+    #[serde(default = "default_polling_deduplication_window_duration_secs")]
+    pub deduplication_window_duration_secs: u32,
+    #[serde(default = "default_deduplication_window_max_files")]
+    pub deduplication_window_max_files: u32,
+    #[serde(default = "default_deduplication_cleanup_interval_secs")]
+    pub deduplication_cleanup_interval_secs: u32,
+}
+
+fn default_polling_interval_secs() -> u32 {
+    60
+}
+
+fn default_polling_deduplication_window_duration_secs() -> u32 {
+    30 * 24 * 3600
+}
+
+fn default_deduplication_window_max_files() -> u32 {
+    100_000
+}
+
+impl FileSourcePolling {
+    fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            self.polling_interval_secs > 0,
+            "`polling_interval_secs` must be strictly positive"
+        );
+        if let Some(glob) = &self.glob {
+            glob::Pattern::new(glob).with_context(|| format!("invalid glob pattern `{glob}`"))?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum FileSourceNotification {
     Sqs(FileSourceSqs),
+    Polling(FileSourcePolling),
 }
 
 impl FileSourceNotification {
+    fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            Self::Sqs(_) => Ok(()),
+            Self::Polling(polling) => polling.validate(),
+        }
+    }
+
     fn validate_update(&self, other: &Self) -> anyhow::Result<()> {
         match (self, other) {
             (Self::Sqs(_), Self::Sqs(_)) => {
                 // changing the queue or the deduplication settings should be fine
                 Ok(())
             }
+            (Self::Polling(current), Self::Polling(new)) => {
+                // The file URIs are used as shard IDs, so the files indexed under the
+                // current prefix would be indexed again under the new one.
+                ensure!(
+                    current.uri == new.uri,
+                    "the URI of a polling file source cannot be updated"
+                );
+                Ok(())
+            }
+            _ => bail!("the notification type of a file source cannot be updated"),
         }
     }
 }
@@ -620,6 +721,162 @@ fn default_consumer_name() -> String {
     "quickwit".to_string()
 }
 
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NatsSourceParams {
+    /// The connection URL of the NATS server, or a comma-separated list of URLs.
+    #[serde(deserialize_with = "nats_url")]
+    pub address: String,
+    /// Name of the JetStream stream that the source consumes.
+    pub stream: String,
+    /// Subjects of the stream that the source consumes. Each subject is a partition of the source,
+    /// consumed with its own durable consumer. Defaults to all the subjects of the stream.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub subjects: Vec<String>,
+    /// Prefix of the names of the durable consumers created by the source.
+    #[schema(default = "quickwit")]
+    #[serde(default = "default_consumer_name")]
+    pub consumer_name: String,
+    /// Authentication for NATS.
+    #[serde(default, with = "serde_yaml::with::singleton_map")]
+    pub authentication: Option<NatsSourceAuth>,
+}
+
+impl NatsSourceParams {
+    fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            !self.stream.is_empty(),
+            "NATS stream name must not be empty"
+        );
+        for (subject_idx, subject) in self.subjects.iter().enumerate() {
+            ensure!(
+                !self.subjects[..subject_idx].contains(subject),
+                "NATS subject `{subject}` is listed more than once"
+            );
+        }
+        Ok(())
+    }
+
+    fn validate_update(&self, other: &Self) -> anyhow::Result<()> {
+        // The subjects are used as metastore checkpoint PartitionId, and the positions are
+        // sequence numbers of the stream.
+        ensure!(self.stream == other.stream, "NATS stream cannot be updated");
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NatsSourceAuth {
+    Token(String),
+    UserPassword {
+        user: String,
+        password: String,
+    },
+    /// Path to a NATS credentials file, holding a JWT and an NKey seed.
+    CredentialsFile(String),
+}
+
+// Deserializing a string into a list of NATS URLs.
+fn nats_url<'de, D>(deserializer: D) -> Result<String, D::Error>
+where D: Deserializer<'de> {
+    let address: String = Deserialize::deserialize(deserializer)?;
+
+    for url in address.split(',') {
+        let url = url.trim();
+        if !(url.starts_with("nats://") || url.starts_with("tls://")) {
+            return Err(Error::custom(format!(
+                "invalid NATS URL provided, must be in the format of `nats://host:port`. got: \
+                 `{url}`"
+            )));
+        }
+    }
+    Ok(address)
+}
+
+/// Destination of the documents rejected by the doc processor of a source, for instance because
+/// they could not be parsed or do not match the doc mapping. Exactly one of `index_id` or
+/// `storage_uri` must be set.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DeadLetterConfig {
+    /// ID of the index the rejected documents are ingested into.
+    #[schema(value_type = String)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_id: Option<IndexId>,
+    /// URI of the storage the rejected documents are written to as NDJSON files.
+    #[schema(value_type = String)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage_uri: Option<Uri>,
+}
+
+impl DeadLetterConfig {
+    pub(super) fn validate(&self) -> anyhow::Result<()> {
+        match (&self.index_id, &self.storage_uri) {
+            (Some(index_id), None) => validate_identifier("dead-letter index", index_id),
+            (None, Some(_)) => Ok(()),
+            _ => bail!("exactly one of `index_id` or `storage_uri` must be set in `dead_letter`"),
+        }
+    }
+}
+
+/// Schema of the records of a source with the `avro` or `protobuf` input format. The schema is
+/// either inline or fetched from a schema registry.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct InputSchemaConfig {
+    /// Inline schema: an Avro schema (the content of an `.avsc` file) or a base64-encoded
+    /// Protobuf `FileDescriptorSet` (as generated by `protoc --descriptor_set_out`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// Fully qualified name of the Protobuf message of the records, required with an inline
+    /// Protobuf schema.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_name: Option<String>,
+    /// URL of a Confluent-compatible schema registry. The records must then be framed with a magic
+    /// byte and the ID of their schema in the registry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_registry_url: Option<String>,
+}
+
+impl InputSchemaConfig {
+    pub(super) fn validate(&self, input_format: SourceInputFormat) -> anyhow::Result<()> {
+        ensure!(
+            input_format.requires_schema(),
+            "`input_schema` can only be set with the `avro` or `protobuf` input formats"
+        );
+        match (&self.schema, &self.schema_registry_url) {
+            (Some(_), None) => {
+                if input_format == SourceInputFormat::Protobuf {
+                    ensure!(
+                        self.message_name.is_some(),
+                        "`message_name` must be set in `input_schema` with an inline Protobuf \
+                         schema"
+                    );
+                }
+            }
+            (None, Some(schema_registry_url)) => {
+                ensure!(
+                    schema_registry_url.starts_with("http://")
+                        || schema_registry_url.starts_with("https://"),
+                    "schema registry URL `{schema_registry_url}` must be an HTTP(S) URL"
+                );
+            }
+            _ => bail!(
+                "exactly one of `schema` or `schema_registry_url` must be set in `input_schema`"
+            ),
+        }
+        if input_format == SourceInputFormat::Avro || self.schema.is_none() {
+            ensure!(
+                self.message_name.is_none(),
+                "`message_name` can only be set in `input_schema` with an inline Protobuf schema"
+            );
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, utoipa::ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct TransformConfig {
@@ -756,6 +1013,8 @@ mod tests {
                 timezone: "local".to_string(),
             }),
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         assert_eq!(source_config, expected_source_config);
         assert_eq!(source_config.num_pipelines.get(), 2);
@@ -851,6 +1110,8 @@ mod tests {
                 timezone: "local".to_string(),
             }),
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         assert_eq!(source_config, expected_source_config);
         assert_eq!(source_config.num_pipelines.get(), 1);
@@ -1039,6 +1300,60 @@ mod tests {
                 }]})
             );
         }
+        {
+            let yaml = r#"
+                notifications:
+                  - type: polling
+                    uri: s3://logs/app/2026/
+                    glob: "**/*.json.gz"
+            "#;
+            let file_params_deserialized = serde_yaml::from_str::<FileSourceParams>(yaml).unwrap();
+            assert_eq!(
+                file_params_deserialized,
+                FileSourceParams::Notifications(FileSourceNotification::Polling(
+                    FileSourcePolling {
+                        uri: Uri::for_test("s3://logs/app/2026/"),
+                        glob: Some("**/*.json.gz".to_string()),
+                        polling_interval_secs: default_polling_interval_secs(),
+                        deduplication_window_duration_secs:
+                            default_polling_deduplication_window_duration_secs(),
+                        deduplication_window_max_files: default_deduplication_window_max_files(),
+                        deduplication_cleanup_interval_secs:
+                            default_deduplication_cleanup_interval_secs(),
+                    }
+                )),
+            );
+            let file_params_reserialized = serde_json::to_value(&file_params_deserialized).unwrap();
+            assert_eq!(
+                file_params_reserialized,
+                json!({"notifications": [{
+                    "type": "polling",
+                    "uri": "s3://logs/app/2026/",
+                    "glob": "**/*.json.gz",
+                    "polling_interval_secs": default_polling_interval_secs(),
+                    "deduplication_window_duration_secs":
+                        default_polling_deduplication_window_duration_secs(),
+                    "deduplication_window_max_files": default_deduplication_window_max_files(),
+                    "deduplication_cleanup_interval_secs": default_deduplication_cleanup_interval_secs(),
+                }]})
+            );
+        }
+        {
+            let file_content = r#"
+                version: 0.8
+                source_id: polling-source
+                source_type: file
+                params:
+                    notifications:
+                      - type: polling
+                        uri: s3://logs/app/2026/
+                        glob: "[*.json"
+            "#;
+            let error =
+                load_source_config_from_user_config(ConfigFormat::Yaml, file_content.as_bytes())
+                    .unwrap_err();
+            assert_eq!(error.to_string(), "invalid glob pattern `[*.json`");
+        }
         {
             let yaml = r#"
                 filepath: source-path.json
@@ -1341,6 +1656,82 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_nats_source_params_deserialization() {
+        {
+            let yaml = r#"
+                    address: nats://localhost:4222
+                    stream: my-stream
+                "#;
+            assert_eq!(
+                serde_yaml::from_str::<NatsSourceParams>(yaml).unwrap(),
+                NatsSourceParams {
+                    address: "nats://localhost:4222".to_string(),
+                    stream: "my-stream".to_string(),
+                    subjects: Vec::new(),
+                    consumer_name: default_consumer_name(),
+                    authentication: None,
+                }
+            );
+        }
+        {
+            let yaml = r#"
+                    address: nats://host-1:4222,tls://host-2:4222
+                    stream: my-stream
+                    subjects:
+                        - events.0.>
+                        - events.1.>
+                    consumer_name: my-consumer
+                    authentication:
+                        user_password:
+                            user: my-user
+                            password: my-password
+                "#;
+            assert_eq!(
+                serde_yaml::from_str::<NatsSourceParams>(yaml).unwrap(),
+                NatsSourceParams {
+                    address: "nats://host-1:4222,tls://host-2:4222".to_string(),
+                    stream: "my-stream".to_string(),
+                    subjects: vec!["events.0.>".to_string(), "events.1.>".to_string()],
+                    consumer_name: "my-consumer".to_string(),
+                    authentication: Some(NatsSourceAuth::UserPassword {
+                        user: "my-user".to_string(),
+                        password: "my-password".to_string(),
+                    }),
+                }
+            );
+        }
+        {
+            let yaml = r#"
+                    address: localhost:4222
+                    stream: my-stream
+                "#;
+            serde_yaml::from_str::<NatsSourceParams>(yaml)
+                .expect_err("NATS config should reject invalid address");
+        }
+        {
+            let file_content = r#"
+                version: 0.8
+                source_id: nats-source
+                source_type: nats
+                num_pipelines: 2
+                params:
+                    address: nats://localhost:4222
+                    stream: my-stream
+                    subjects:
+                        - events.0.>
+                        - events.0.>
+            "#;
+            let error =
+                load_source_config_from_user_config(ConfigFormat::Yaml, file_content.as_bytes())
+                    .unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "NATS subject `events.0.>` is listed more than once"
+            );
+        }
+    }
+
     #[cfg(feature = "vrl")]
     #[tokio::test]
     async fn test_load_ingest_api_source_config() {
@@ -1357,6 +1748,8 @@ mod tests {
                 timezone: default_timezone(),
             }),
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         assert_eq!(source_config, expected_source_config);
         assert_eq!(source_config.num_pipelines.get(), 1);
@@ -1479,6 +1872,158 @@ mod tests {
         assert_eq!(source_config.input_format, SourceInputFormat::PlainText);
     }
 
+    #[test]
+    fn test_source_config_dead_letter() {
+        let file_content = r#"
+            version: 0.8
+            source_id: kafka-source
+            source_type: kafka
+            params:
+              topic: my-topic
+            dead_letter:
+              storage_uri: s3://my-bucket/dead-letters
+        "#;
+        let source_config =
+            load_source_config_from_user_config(ConfigFormat::Yaml, file_content.as_bytes())
+                .unwrap();
+        let expected_dead_letter_config = DeadLetterConfig {
+            index_id: None,
+            storage_uri: Some(Uri::for_test("s3://my-bucket/dead-letters")),
+        };
+        assert_eq!(source_config.dead_letter, Some(expected_dead_letter_config));
+
+        let mut source_config_without_dead_letter = source_config.clone();
+        source_config_without_dead_letter.dead_letter = None;
+        assert_ne!(
+            source_config.indexing_params_fingerprint(),
+            source_config_without_dead_letter.indexing_params_fingerprint()
+        );
+
+        let file_content = r#"
+            version: 0.8
+            source_id: kafka-source
+            source_type: kafka
+            params:
+              topic: my-topic
+            dead_letter:
+              index_id: dead-letters
+              storage_uri: s3://my-bucket/dead-letters
+        "#;
+        let error =
+            load_source_config_from_user_config(ConfigFormat::Yaml, file_content.as_bytes())
+                .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "exactly one of `index_id` or `storage_uri` must be set in `dead_letter`"
+        );
+
+        let file_content = r#"
+            version: 0.8
+            source_id: kafka-source
+            source_type: kafka
+            params:
+              topic: my-topic
+            dead_letter:
+              index_id: "-invalid"
+        "#;
+        load_source_config_from_user_config(ConfigFormat::Yaml, file_content.as_bytes())
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_source_config_input_schema() {
+        let file_content = r#"
+            version: 0.8
+            source_id: kafka-source
+            source_type: kafka
+            params:
+              topic: my-topic
+            input_format: avro
+            input_schema:
+              schema_registry_url: http://localhost:8081
+        "#;
+        let source_config =
+            load_source_config_from_user_config(ConfigFormat::Yaml, file_content.as_bytes())
+                .unwrap();
+        assert_eq!(source_config.input_format, SourceInputFormat::Avro);
+        let expected_input_schema_config = InputSchemaConfig {
+            schema: None,
+            message_name: None,
+            schema_registry_url: Some("http://localhost:8081".to_string()),
+        };
+        assert_eq!(
+            source_config.input_schema,
+            Some(expected_input_schema_config)
+        );
+
+        let file_content = r#"
+            version: 0.8
+            source_id: kafka-source
+            source_type: kafka
+            params:
+              topic: my-topic
+            input_format: proto
+            input_schema:
+              schema: CgR0ZXN0
+              message_name: test.Event
+        "#;
+        let source_config =
+            load_source_config_from_user_config(ConfigFormat::Yaml, file_content.as_bytes())
+                .unwrap();
+        assert_eq!(source_config.input_format, SourceInputFormat::Protobuf);
+
+        let file_content = r#"
+            version: 0.8
+            source_id: kafka-source
+            source_type: kafka
+            params:
+              topic: my-topic
+            input_format: avro
+        "#;
+        let error =
+            load_source_config_from_user_config(ConfigFormat::Yaml, file_content.as_bytes())
+                .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`input_schema` must be set with the `avro` or `protobuf` input formats"
+        );
+
+        let file_content = r#"
+            version: 0.8
+            source_id: kafka-source
+            source_type: kafka
+            params:
+              topic: my-topic
+            input_format: protobuf
+            input_schema:
+              schema: CgR0ZXN0
+        "#;
+        let error =
+            load_source_config_from_user_config(ConfigFormat::Yaml, file_content.as_bytes())
+                .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`message_name` must be set in `input_schema` with an inline Protobuf schema"
+        );
+
+        let file_content = r#"
+            version: 0.8
+            source_id: kafka-source
+            source_type: kafka
+            params:
+              topic: my-topic
+            input_schema:
+              schema_registry_url: http://localhost:8081
+        "#;
+        let error =
+            load_source_config_from_user_config(ConfigFormat::Yaml, file_content.as_bytes())
+                .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`input_schema` can only be set with the `avro` or `protobuf` input formats"
+        );
+    }
+
     #[tokio::test]
     async fn test_update_kafka_source_config() {
         let source_config_filepath = get_source_config_filepath("kafka-source.json");
@@ -1508,6 +2053,8 @@ mod tests {
                     timezone: "local".to_string(),
                 }),
                 input_format: SourceInputFormat::Json,
+                dead_letter: None,
+                input_schema: None,
             };
             assert_eq!(new_source_config, expected_source_config);
             assert_eq!(new_source_config.num_pipelines.get(), 2);
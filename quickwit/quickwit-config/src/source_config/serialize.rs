@@ -18,7 +18,7 @@ use anyhow::{bail, ensure};
 use quickwit_proto::types::SourceId;
 use serde::{Deserialize, Serialize};
 
-use super::{RESERVED_SOURCE_IDS, TransformConfig};
+use super::{DeadLetterConfig, InputSchemaConfig, RESERVED_SOURCE_IDS, TransformConfig};
 use crate::{
     ConfigFormat, FileSourceParams, SourceConfig, SourceInputFormat, SourceParams,
     validate_identifier,
@@ -110,12 +110,16 @@ impl SourceConfigForSerialization {
                      local-ingest`"
                 );
             }
-            SourceParams::File(_)
+            SourceParams::File(FileSourceParams::Notifications(notification)) => {
+                notification.validate()?
+            }
+            SourceParams::File(FileSourceParams::Filepath(_))
             | SourceParams::Kafka(_)
             | SourceParams::Kinesis(_)
             | SourceParams::Pulsar(_) => {
                 // TODO consider any validation opportunity
             }
+            SourceParams::Nats(nats_params) => nats_params.validate()?,
             SourceParams::PubSub(_)
             | SourceParams::Ingest
             | SourceParams::IngestApi
@@ -126,10 +130,11 @@ impl SourceConfigForSerialization {
         match &self.source_params {
             SourceParams::PubSub(_)
             | SourceParams::Kafka(_)
+            | SourceParams::Nats(_)
             | SourceParams::File(FileSourceParams::Notifications(_)) => {}
             _ => {
                 if self.num_pipelines > 1 {
-                    bail!("Quickwit currently supports multiple pipelines only for GCP PubSub or Kafka sources. open an issue https://github.com/quickwit-oss/quickwit/issues if you need the feature for other source types");
+                    bail!("Quickwit currently supports multiple pipelines only for GCP PubSub, Kafka or NATS sources. open an issue https://github.com/quickwit-oss/quickwit/issues if you need the feature for other source types");
                 }
             }
         }
@@ -146,6 +151,14 @@ impl SourceConfigForSerialization {
             }
             transform_config.validate_vrl_script()?;
         }
+        if let Some(dead_letter_config) = &self.dead_letter {
+            dead_letter_config.validate()?;
+        }
+        if let Some(input_schema_config) = &self.input_schema {
+            input_schema_config.validate(self.input_format)?;
+        } else if self.input_format.requires_schema() {
+            bail!("`input_schema` must be set with the `avro` or `protobuf` input formats");
+        }
 
         Ok(SourceConfig {
             source_id: self.source_id,
@@ -154,6 +167,8 @@ impl SourceConfigForSerialization {
             source_params: self.source_params,
             transform_config: self.transform,
             input_format: self.input_format,
+            dead_letter: self.dead_letter,
+            input_schema: self.input_schema,
         })
     }
 }
@@ -167,6 +182,8 @@ impl From<SourceConfig> for SourceConfigV0_8 {
             source_params: source_config.source_params,
             transform: source_config.transform_config,
             input_format: source_config.input_format,
+            dead_letter: source_config.dead_letter,
+            input_schema: source_config.input_schema,
         }
     }
 }
@@ -250,6 +267,12 @@ pub struct SourceConfigV0_8 {
     // Denotes the input data format.
     #[serde(default)]
     pub input_format: SourceInputFormat,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dead_letter: Option<DeadLetterConfig>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<InputSchemaConfig>,
 }
 
 impl From<SourceConfigV0_7> for SourceConfigV0_8 {
@@ -270,6 +293,8 @@ impl From<SourceConfigV0_7> for SourceConfigV0_8 {
             source_params,
             transform,
             input_format,
+            dead_letter: None,
+            input_schema: None,
         }
     }
 }
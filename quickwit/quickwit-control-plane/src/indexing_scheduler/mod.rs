@@ -250,6 +250,7 @@ fn get_sources_to_schedule(model: &ControlPlaneModel) -> Vec<SourceToSchedule> {
             }
             SourceParams::Kafka(_)
             | SourceParams::Kinesis(_)
+            | SourceParams::Nats(_)
             | SourceParams::PubSub(_)
             | SourceParams::Pulsar(_)
             | SourceParams::File(FileSourceParams::Notifications(_)) => {
@@ -886,6 +887,8 @@ mod tests {
                     source_params: SourceParams::Kafka(kafka_source_params.clone()),
                     transform_config: None,
                     input_format: Default::default(),
+                    dead_letter: None,
+                    input_schema: None,
                 },
             )
             .unwrap();
@@ -899,6 +902,8 @@ mod tests {
                     source_params: SourceParams::Kafka(kafka_source_params.clone()),
                     transform_config: None,
                     input_format: Default::default(),
+                    dead_letter: None,
+                    input_schema: None,
                 },
             )
             .unwrap();
@@ -913,6 +918,8 @@ mod tests {
                     source_params: SourceParams::IngestApi,
                     transform_config: None,
                     input_format: Default::default(),
+                    dead_letter: None,
+                    input_schema: None,
                 },
             )
             .unwrap();
@@ -927,6 +934,8 @@ mod tests {
                     source_params: SourceParams::Ingest,
                     transform_config: None,
                     input_format: Default::default(),
+                    dead_letter: None,
+                    input_schema: None,
                 },
             )
             .unwrap();
@@ -942,6 +951,8 @@ mod tests {
                     source_params: SourceParams::Ingest,
                     transform_config: None,
                     input_format: Default::default(),
+                    dead_letter: None,
+                    input_schema: None,
                 },
             )
             .unwrap();
@@ -956,6 +967,8 @@ mod tests {
                     source_params: SourceParams::IngestCli,
                     transform_config: None,
                     input_format: Default::default(),
+                    dead_letter: None,
+                    input_schema: None,
                 },
             )
             .unwrap();
@@ -1111,6 +1124,8 @@ mod tests {
               source_params: kafka_source_params_for_test(),
               transform_config: None,
               input_format: SourceInputFormat::Json,
+              dead_letter: None,
+              input_schema: None,
           })
       }
     }
@@ -57,6 +57,8 @@ fn index_metadata_for_test(index_id: &str, source_id: &str, num_pipelines: usize
         }),
         transform_config: None,
         input_format: SourceInputFormat::Json,
+        dead_letter: None,
+        input_schema: None,
     };
     index_metadata.add_source(kafka_source_config).unwrap();
     index_metadata
@@ -476,6 +476,19 @@ fn list_index_files(index: &Index) -> tantivy::Result<HashSet<PathBuf>> {
 pub fn write_hotcache<D: Directory>(
     directory: D,
     output: &mut dyn io::Write,
+) -> tantivy::Result<()> {
+    write_hotcache_with_sidecar_files(directory, &[], output)
+}
+
+/// Same as [`write_hotcache`], but also registers the sidecar files of the split, i.e. the files
+/// bundled in the split that are not part of the tantivy index, such as dense vector indexes.
+///
+/// None of the content of the sidecar files is cached: only their length is recorded, so that
+/// they can be opened through the [`HotDirectory`].
+pub fn write_hotcache_with_sidecar_files<D: Directory>(
+    directory: D,
+    sidecar_file_paths: &[PathBuf],
+    output: &mut dyn io::Write,
 ) -> tantivy::Result<()> {
     // We use the caching directory here in order to defensively ensure that
     // the content of the directory that will be written in the hotcache is precisely
@@ -534,6 +547,10 @@ pub fn write_hotcache<D: Directory>(
             }
         }
     }
+    for sidecar_file_path in sidecar_file_paths {
+        let file_slice = debug_proxy_directory.open_read(sidecar_file_path)?;
+        cache_builder.add_file(sidecar_file_path, file_slice.len() as u64);
+    }
     cache_builder.write(output)?;
     output.flush()?;
     Ok(())
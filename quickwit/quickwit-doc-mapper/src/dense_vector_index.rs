@@ -0,0 +1,695 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The vectors of a dense vector field are indexed per split in an IVF (inverted file) index,
+//! bundled into the split file next to the tantivy index files.
+//!
+//! The vectors are clustered with k-means, and each vector is stored in the list of its closest
+//! centroid. A search loads the centroids, then only the lists closest to the query vector, which
+//! keeps the number of bytes fetched from the storage low.
+//!
+//! File layout (little-endian):
+//! - header: magic number (u32), version (u8), similarity (u8), padding (u16), number of dimensions
+//!   (u32), number of lists (u32), number of vectors (u64);
+//! - centroids: `num_lists * dims` f32;
+//! - list offsets: `num_lists + 1` u64, expressed in number of vectors;
+//! - lists: for each vector, its segment ordinal (u32), doc ID (u32), and `dims` f32.
+
+use std::cmp::Ordering;
+use std::io;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tantivy::schema::Field;
+use tantivy::{DocAddress, Score};
+
+use crate::doc_mapper::{
+    QuickwitDenseVectorOptions, VectorSimilarity, dot_product, squared_l2_distance,
+};
+
+const MAGIC_NUMBER: u32 = 0x5157_4956;
+
+const FORMAT_VERSION: u8 = 1;
+
+/// Number of bytes of the header of a dense vector index.
+pub const HEADER_NUM_BYTES: usize = 24;
+
+/// Below this number of vectors, the index holds a single list and searches are exhaustive.
+const MIN_NUM_VECTORS_FOR_CLUSTERING: usize = 4_096;
+
+const MAX_NUM_LISTS: usize = 256;
+
+const MAX_NUM_TRAINING_VECTORS_PER_LIST: usize = 64;
+
+const NUM_KMEANS_ITERATIONS: usize = 8;
+
+/// Maximum number of bytes buffered per list before being written.
+const MAX_LIST_BUFFER_NUM_BYTES: usize = 1 << 20;
+
+const RESERVOIR_SAMPLING_SEED: u64 = 42;
+
+/// A dense vector field of a doc mapping.
+#[derive(Clone, Debug)]
+pub struct DenseVectorField {
+    /// Name of the field in the tantivy schema.
+    pub name: String,
+    /// Options of the field.
+    pub options: QuickwitDenseVectorOptions,
+}
+
+/// Returns the path, within the split bundle, of the dense vector index of a field.
+pub fn dense_vector_index_file_path(field: Field) -> PathBuf {
+    PathBuf::from(format!("dense_vector.{}.ivf", field.field_id()))
+}
+
+/// Builds the dense vector index of a field of a split.
+///
+/// The vectors are streamed three times: a first pass draws a uniform sample of the vectors
+/// (reservoir sampling) on which the centroids are trained, a second pass counts the vectors of
+/// each list, and a third pass writes each vector at its position in its list. As a result, the
+/// memory used by the builder does not depend on the number of vectors and stays within the
+/// memory budget it is created with.
+pub struct DenseVectorIndexBuilder {
+    dims: usize,
+    similarity: VectorSimilarity,
+    sample_capacity: usize,
+    list_buffer_num_bytes: usize,
+}
+
+impl DenseVectorIndexBuilder {
+    /// Creates a builder for a field with the given options, using at most `memory_budget`
+    /// bytes, half for the training sample, half for the list write buffers.
+    pub fn new(options: &QuickwitDenseVectorOptions, memory_budget: usize) -> Self {
+        let vector_num_bytes = options.dims * 4;
+        let sample_capacity = (memory_budget / 2 / vector_num_bytes)
+            .clamp(1, MAX_NUM_LISTS * MAX_NUM_TRAINING_VECTORS_PER_LIST);
+        let list_buffer_num_bytes = (memory_budget / 2 / MAX_NUM_LISTS)
+            .clamp(entry_num_bytes(options.dims), MAX_LIST_BUFFER_NUM_BYTES);
+        DenseVectorIndexBuilder {
+            dims: options.dims,
+            similarity: options.similarity,
+            sample_capacity,
+            list_buffer_num_bytes,
+        }
+    }
+
+    /// Returns the maximum number of bytes of memory used to build an index.
+    pub fn memory_usage(&self) -> usize {
+        self.sample_capacity * self.dims * 4 + MAX_NUM_LISTS * self.list_buffer_num_bytes
+    }
+
+    /// Builds the index of the vectors visited by `for_each_vector` and writes it to `output`.
+    ///
+    /// `for_each_vector` is called once per pass and must visit the same vectors, in the same
+    /// order, every time. Vectors with an unexpected number of dimensions are ignored. Returns the
+    /// number of indexed vectors. Nothing is written if there is none.
+    pub fn build<W: io::Write + io::Seek>(
+        &self,
+        mut for_each_vector: impl FnMut(&mut dyn FnMut(DocAddress, Vec<f32>)) -> io::Result<()>,
+        output: &mut W,
+    ) -> io::Result<usize> {
+        // First pass: sample the training vectors.
+        let mut rng = StdRng::seed_from_u64(RESERVOIR_SAMPLING_SEED);
+        let mut sample: Vec<f32> = Vec::new();
+        let mut num_vectors = 0;
+
+        for_each_vector(&mut |_doc_address, vector| {
+            let Some(vector) = self.normalized(vector) else {
+                return;
+            };
+            let sample_len = sample.len() / self.dims;
+
+            if sample_len < self.sample_capacity {
+                sample.extend_from_slice(&vector);
+            } else {
+                let sample_ord = rng.gen_range(0..=num_vectors);
+
+                if sample_ord < self.sample_capacity {
+                    sample[sample_ord * self.dims..(sample_ord + 1) * self.dims]
+                        .copy_from_slice(&vector);
+                }
+            }
+            num_vectors += 1;
+        })?;
+        let num_lists = match num_vectors {
+            0 => return Ok(0),
+            1..MIN_NUM_VECTORS_FOR_CLUSTERING => 1,
+            _ => ((num_vectors as f64).sqrt() as usize)
+                .min(MAX_NUM_LISTS)
+                .min(sample.len() / self.dims),
+        };
+        let centroids = train_centroids(self.similarity, &sample, self.dims, num_lists);
+        drop(sample);
+
+        // Second pass: count the vectors of each list.
+        let mut list_offsets = vec![0u64; num_lists + 1];
+
+        for_each_vector(&mut |_doc_address, vector| {
+            if let Some(vector) = self.normalized(vector) {
+                let list_id = closest_centroid(self.similarity, &centroids, self.dims, &vector);
+                list_offsets[list_id + 1] += 1;
+            }
+        })?;
+        let mut list_offset = 0;
+
+        for offset in &mut list_offsets {
+            list_offset += *offset;
+            *offset = list_offset;
+        }
+        if list_offsets[num_lists] != num_vectors as u64 {
+            return Err(invalid_data(
+                "dense vectors changed while building the index",
+            ));
+        }
+        let mut metadata_bytes = Vec::with_capacity(HEADER_NUM_BYTES + centroids.len() * 4);
+        metadata_bytes.extend_from_slice(&MAGIC_NUMBER.to_le_bytes());
+        metadata_bytes.extend_from_slice(&[FORMAT_VERSION, self.similarity.to_code(), 0, 0]);
+        metadata_bytes.extend_from_slice(&(self.dims as u32).to_le_bytes());
+        metadata_bytes.extend_from_slice(&(num_lists as u32).to_le_bytes());
+        metadata_bytes.extend_from_slice(&(num_vectors as u64).to_le_bytes());
+
+        for value in &centroids {
+            metadata_bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for list_offset in &list_offsets {
+            metadata_bytes.extend_from_slice(&list_offset.to_le_bytes());
+        }
+        let start_position = output.stream_position()?;
+        let lists_start = start_position + metadata_bytes.len() as u64;
+        output.write_all(&metadata_bytes)?;
+
+        // Third pass: write the vectors of each list, through a buffer per list.
+        let entry_num_bytes = entry_num_bytes(self.dims) as u64;
+        let mut list_writers: Vec<ListWriter> = list_offsets[..num_lists]
+            .iter()
+            .zip(&list_offsets[1..])
+            .map(|(start, end)| ListWriter {
+                position: lists_start + start * entry_num_bytes,
+                end: lists_start + end * entry_num_bytes,
+                buffer: Vec::new(),
+            })
+            .collect();
+        let mut write_result = Ok(());
+
+        for_each_vector(&mut |doc_address, vector| {
+            if write_result.is_err() {
+                return;
+            }
+            let Some(vector) = self.normalized(vector) else {
+                return;
+            };
+            let list_id = closest_centroid(self.similarity, &centroids, self.dims, &vector);
+            let list_writer = &mut list_writers[list_id];
+            list_writer
+                .buffer
+                .extend_from_slice(&doc_address.segment_ord.to_le_bytes());
+            list_writer
+                .buffer
+                .extend_from_slice(&doc_address.doc_id.to_le_bytes());
+
+            for value in &vector {
+                list_writer.buffer.extend_from_slice(&value.to_le_bytes());
+            }
+            if list_writer.buffer.len() >= self.list_buffer_num_bytes {
+                write_result = list_writer.flush(output);
+            }
+        })?;
+        write_result?;
+
+        for list_writer in &mut list_writers {
+            list_writer.flush(output)?;
+
+            if list_writer.position != list_writer.end {
+                return Err(invalid_data(
+                    "dense vectors changed while building the index",
+                ));
+            }
+        }
+        output.seek(io::SeekFrom::Start(
+            lists_start + num_vectors as u64 * entry_num_bytes,
+        ))?;
+        Ok(num_vectors)
+    }
+
+    fn normalized(&self, mut vector: Vec<f32>) -> Option<Vec<f32>> {
+        if vector.len() != self.dims {
+            return None;
+        }
+        self.similarity.normalize(&mut vector);
+        Some(vector)
+    }
+}
+
+/// Writes the entries of a list, which are not contiguous in the stream of vectors.
+struct ListWriter {
+    position: u64,
+    end: u64,
+    buffer: Vec<u8>,
+}
+
+impl ListWriter {
+    fn flush<W: io::Write + io::Seek>(&mut self, output: &mut W) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        if self.position + self.buffer.len() as u64 > self.end {
+            return Err(invalid_data(
+                "dense vectors changed while building the index",
+            ));
+        }
+        output.seek(io::SeekFrom::Start(self.position))?;
+        output.write_all(&self.buffer)?;
+        self.position += self.buffer.len() as u64;
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+fn entry_num_bytes(dims: usize) -> usize {
+    8 + dims * 4
+}
+
+/// Picks the initial centroids evenly among the training vectors, then refines them with a few
+/// iterations of k-means, assigning the vectors to their closest centroid for the similarity of
+/// the field.
+fn train_centroids(
+    similarity: VectorSimilarity,
+    training_vectors: &[f32],
+    dims: usize,
+    num_lists: usize,
+) -> Vec<f32> {
+    let num_training_vectors = training_vectors.len() / dims;
+    let mut centroids: Vec<f32> = (0..num_lists)
+        .flat_map(|list_id| {
+            let vector_ord = list_id * num_training_vectors / num_lists;
+            &training_vectors[vector_ord * dims..(vector_ord + 1) * dims]
+        })
+        .copied()
+        .collect();
+
+    if num_lists <= 1 {
+        return centroids;
+    }
+    for _ in 0..NUM_KMEANS_ITERATIONS {
+        let mut sums = vec![0f32; num_lists * dims];
+        let mut counts = vec![0usize; num_lists];
+
+        for vector in training_vectors.chunks_exact(dims) {
+            let list_id = closest_centroid(similarity, &centroids, dims, vector);
+            counts[list_id] += 1;
+
+            for (sum, value) in sums[list_id * dims..(list_id + 1) * dims]
+                .iter_mut()
+                .zip(vector.iter())
+            {
+                *sum += value;
+            }
+        }
+        for (list_id, count) in counts.into_iter().enumerate() {
+            // Centroids without vectors are left unchanged.
+            if count == 0 {
+                continue;
+            }
+            for (centroid_value, sum) in centroids[list_id * dims..(list_id + 1) * dims]
+                .iter_mut()
+                .zip(&sums[list_id * dims..(list_id + 1) * dims])
+            {
+                *centroid_value = sum / count as f32;
+            }
+        }
+    }
+    centroids
+}
+
+/// Returns the distance between a vector and a centroid, the lower the closer.
+fn centroid_distance(similarity: VectorSimilarity, centroid: &[f32], vector: &[f32]) -> f32 {
+    match similarity {
+        VectorSimilarity::DotProduct => -dot_product(centroid, vector),
+        VectorSimilarity::Cosine | VectorSimilarity::L2Norm => {
+            squared_l2_distance(centroid, vector)
+        }
+    }
+}
+
+fn closest_centroid(
+    similarity: VectorSimilarity,
+    centroids: &[f32],
+    dims: usize,
+    vector: &[f32],
+) -> usize {
+    centroids
+        .chunks_exact(dims)
+        .map(|centroid| centroid_distance(similarity, centroid, vector))
+        .enumerate()
+        .min_by(|(_, left), (_, right)| left.total_cmp(right))
+        .map(|(list_id, _)| list_id)
+        .unwrap_or_default()
+}
+
+/// The centroids and list offsets of a dense vector index, from which the lists to load for a
+/// query are determined.
+#[derive(Debug)]
+pub struct DenseVectorIndex {
+    dims: usize,
+    similarity: VectorSimilarity,
+    centroids: Vec<f32>,
+    list_offsets: Vec<u64>,
+    lists_start: usize,
+}
+
+impl DenseVectorIndex {
+    /// Returns the number of bytes to read from the beginning of the file to open the index,
+    /// given the first [`HEADER_NUM_BYTES`] bytes.
+    pub fn metadata_num_bytes(header_bytes: &[u8]) -> io::Result<usize> {
+        let header = Header::parse(header_bytes)?;
+        Ok(header.lists_start())
+    }
+
+    /// Opens the index from the first `metadata_num_bytes` bytes of the file.
+    pub fn open(metadata_bytes: &[u8]) -> io::Result<Self> {
+        let header = Header::parse(metadata_bytes)?;
+        let lists_start = header.lists_start();
+
+        if metadata_bytes.len() < lists_start {
+            return Err(invalid_data("dense vector index metadata is truncated"));
+        }
+        let centroids_end = HEADER_NUM_BYTES + header.num_lists * header.dims * 4;
+        let centroids = metadata_bytes[HEADER_NUM_BYTES..centroids_end]
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        let list_offsets = metadata_bytes[centroids_end..lists_start]
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        Ok(DenseVectorIndex {
+            dims: header.dims,
+            similarity: header.similarity,
+            centroids,
+            list_offsets,
+            lists_start,
+        })
+    }
+
+    /// Returns the number of dimensions of the vectors.
+    pub fn dims(&self) -> usize {
+        self.dims
+    }
+
+    /// Returns the similarity metric of the vectors.
+    pub fn similarity(&self) -> VectorSimilarity {
+        self.similarity
+    }
+
+    /// Returns the byte ranges of the lists to search for a query vector: the lists whose
+    /// centroids are the closest to the query, until they hold at least `num_candidates` vectors.
+    pub fn probe(&self, query: &[f32], num_candidates: usize) -> Vec<Range<usize>> {
+        let mut list_distances: Vec<(usize, f32)> = self
+            .centroids
+            .chunks_exact(self.dims)
+            .map(|centroid| centroid_distance(self.similarity, centroid, query))
+            .enumerate()
+            .collect();
+        list_distances.sort_by(|(_, left), (_, right)| left.total_cmp(right));
+
+        let mut byte_ranges = Vec::new();
+        let mut num_probed_vectors = 0;
+
+        for (list_id, _) in list_distances {
+            if num_probed_vectors >= num_candidates {
+                break;
+            }
+            let start = self.list_offsets[list_id] as usize;
+            let end = self.list_offsets[list_id + 1] as usize;
+
+            if start == end {
+                continue;
+            }
+            num_probed_vectors += end - start;
+            byte_ranges.push(
+                self.lists_start + start * self.entry_num_bytes()
+                    ..self.lists_start + end * self.entry_num_bytes(),
+            );
+        }
+        byte_ranges
+    }
+
+    /// Returns the `k` vectors of the given lists the most similar to the query vector, among
+    /// those of the documents accepted by `filter`, sorted by decreasing score.
+    pub fn top_k(
+        &self,
+        query: &[f32],
+        lists: &[impl AsRef<[u8]>],
+        k: usize,
+        mut filter: impl FnMut(DocAddress) -> bool,
+    ) -> Vec<(Score, DocAddress)> {
+        let mut vector = vec![0f32; self.dims];
+        let mut hits = Vec::new();
+
+        for list in lists {
+            for entry in list.as_ref().chunks_exact(self.entry_num_bytes()) {
+                let segment_ord = u32::from_le_bytes(entry[0..4].try_into().unwrap());
+                let doc_id = u32::from_le_bytes(entry[4..8].try_into().unwrap());
+                let doc_address = DocAddress::new(segment_ord, doc_id);
+
+                if !filter(doc_address) {
+                    continue;
+                }
+                for (value, chunk) in vector.iter_mut().zip(entry[8..].chunks_exact(4)) {
+                    *value = f32::from_le_bytes(chunk.try_into().unwrap());
+                }
+                let score = self.similarity.score(query, &vector);
+                hits.push((score, doc_address));
+            }
+        }
+        hits.sort_by(compare_hits);
+        hits.truncate(k);
+        hits
+    }
+
+    fn entry_num_bytes(&self) -> usize {
+        entry_num_bytes(self.dims)
+    }
+}
+
+struct Header {
+    dims: usize,
+    similarity: VectorSimilarity,
+    num_lists: usize,
+}
+
+impl Header {
+    fn parse(bytes: &[u8]) -> io::Result<Header> {
+        if bytes.len() < HEADER_NUM_BYTES {
+            return Err(invalid_data("dense vector index header is truncated"));
+        }
+        let magic_number = u32::from_le_bytes(bytes[0..4].try_into().unwrap());
+
+        if magic_number != MAGIC_NUMBER {
+            return Err(invalid_data("invalid dense vector index magic number"));
+        }
+        if bytes[4] != FORMAT_VERSION {
+            return Err(invalid_data(&format!(
+                "unsupported dense vector index version `{}`",
+                bytes[4]
+            )));
+        }
+        let similarity = VectorSimilarity::from_code(bytes[5])
+            .ok_or_else(|| invalid_data("invalid dense vector index similarity"))?;
+        let dims = u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+        let num_lists = u32::from_le_bytes(bytes[12..16].try_into().unwrap()) as usize;
+
+        Ok(Header {
+            dims,
+            similarity,
+            num_lists,
+        })
+    }
+
+    fn lists_start(&self) -> usize {
+        HEADER_NUM_BYTES + self.num_lists * self.dims * 4 + (self.num_lists + 1) * 8
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Compares two hits by decreasing score, then by increasing doc address.
+fn compare_hits(left: &(Score, DocAddress), right: &(Score, DocAddress)) -> Ordering {
+    right
+        .0
+        .total_cmp(&left.0)
+        .then_with(|| left.1.cmp(&right.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_index(options: &QuickwitDenseVectorOptions, vectors: &[Vec<f32>]) -> Vec<u8> {
+        build_index_with_memory_budget(options, vectors, 64_000_000)
+    }
+
+    fn build_index_with_memory_budget(
+        options: &QuickwitDenseVectorOptions,
+        vectors: &[Vec<f32>],
+        memory_budget: usize,
+    ) -> Vec<u8> {
+        let builder = DenseVectorIndexBuilder::new(options, memory_budget);
+        let mut output = io::Cursor::new(Vec::new());
+        let num_vectors = builder
+            .build(
+                |visit_vector| {
+                    for (doc_id, vector) in vectors.iter().enumerate() {
+                        visit_vector(DocAddress::new(0, doc_id as u32), vector.clone());
+                    }
+                    Ok(())
+                },
+                &mut output,
+            )
+            .unwrap();
+        assert_eq!(num_vectors, vectors.len());
+        output.into_inner()
+    }
+
+    fn search(index_bytes: &[u8], query: &[f32], num_candidates: usize, k: usize) -> Vec<u32> {
+        let metadata_num_bytes =
+            DenseVectorIndex::metadata_num_bytes(&index_bytes[..HEADER_NUM_BYTES]).unwrap();
+        let index = DenseVectorIndex::open(&index_bytes[..metadata_num_bytes]).unwrap();
+        let mut query = query.to_vec();
+        index.similarity().normalize(&mut query);
+        let lists: Vec<&[u8]> = index
+            .probe(&query, num_candidates)
+            .into_iter()
+            .map(|byte_range| &index_bytes[byte_range])
+            .collect();
+        index
+            .top_k(&query, &lists, k, |doc_address| doc_address.doc_id != 3)
+            .into_iter()
+            .map(|(_, doc_address)| doc_address.doc_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_dense_vector_index_exhaustive() {
+        let options = QuickwitDenseVectorOptions {
+            description: None,
+            dims: 2,
+            similarity: VectorSimilarity::L2Norm,
+            stored: true,
+        };
+        let vectors: Vec<Vec<f32>> = (0..10).map(|i| vec![i as f32, 0.0]).collect();
+        let index_bytes = build_index(&options, &vectors);
+        // The document 3 is filtered out.
+        assert_eq!(search(&index_bytes, &[4.2, 0.0], 1, 3), vec![4, 5, 6]);
+    }
+
+    #[test]
+    fn test_dense_vector_index_clustered() {
+        let options = QuickwitDenseVectorOptions {
+            description: None,
+            dims: 2,
+            similarity: VectorSimilarity::Cosine,
+            stored: true,
+        };
+        // Vectors spread on the unit circle.
+        let vectors: Vec<Vec<f32>> = (0..10_000)
+            .map(|i| {
+                let angle = i as f32 * std::f32::consts::TAU / 10_000.0;
+                vec![angle.cos(), angle.sin()]
+            })
+            .collect();
+        let index_bytes = build_index(&options, &vectors);
+        let metadata_num_bytes =
+            DenseVectorIndex::metadata_num_bytes(&index_bytes[..HEADER_NUM_BYTES]).unwrap();
+        let index = DenseVectorIndex::open(&index_bytes[..metadata_num_bytes]).unwrap();
+        assert_eq!(index.dims(), 2);
+        assert_eq!(index.similarity(), VectorSimilarity::Cosine);
+
+        // Only a fraction of the lists are probed.
+        let byte_ranges = index.probe(&[1.0, 0.0], 100);
+        let num_probed_bytes: usize = byte_ranges.iter().map(|byte_range| byte_range.len()).sum();
+        assert!(num_probed_bytes < index_bytes.len() / 10);
+
+        let doc_ids = search(&index_bytes, &vectors[2_500], 300, 3);
+        assert_eq!(doc_ids[0], 2_500);
+        assert!(doc_ids.contains(&2_499));
+        assert!(doc_ids.contains(&2_501));
+    }
+
+    #[test]
+    fn test_dense_vector_index_dot_product_with_small_memory_budget() {
+        let options = QuickwitDenseVectorOptions {
+            description: None,
+            dims: 2,
+            similarity: VectorSimilarity::DotProduct,
+            stored: true,
+        };
+        let vectors: Vec<Vec<f32>> = (0..10_000)
+            .map(|i| {
+                let angle = i as f32 * std::f32::consts::TAU / 10_000.0;
+                vec![angle.cos(), angle.sin()]
+            })
+            .collect();
+        // The budget fits 500 training vectors and buffers of a single entry per list.
+        let builder = DenseVectorIndexBuilder::new(&options, 8_000);
+        assert_eq!(builder.memory_usage(), 500 * 8 + 256 * 16);
+
+        let index_bytes = build_index_with_memory_budget(&options, &vectors, 8_000);
+        let metadata_num_bytes =
+            DenseVectorIndex::metadata_num_bytes(&index_bytes[..HEADER_NUM_BYTES]).unwrap();
+        let index = DenseVectorIndex::open(&index_bytes[..metadata_num_bytes]).unwrap();
+        assert_eq!(index.similarity(), VectorSimilarity::DotProduct);
+        assert_eq!(
+            index_bytes.len(),
+            metadata_num_bytes + vectors.len() * index.entry_num_bytes()
+        );
+        let doc_ids = search(&index_bytes, &vectors[7_500], 300, 3);
+        assert_eq!(doc_ids[0], 7_500);
+        assert!(doc_ids.contains(&7_499));
+        assert!(doc_ids.contains(&7_501));
+    }
+
+    #[test]
+    fn test_dense_vector_index_ignores_invalid_vectors() {
+        let options = QuickwitDenseVectorOptions {
+            description: None,
+            dims: 2,
+            similarity: VectorSimilarity::L2Norm,
+            stored: true,
+        };
+        let builder = DenseVectorIndexBuilder::new(&options, 1_000_000);
+        let mut output = io::Cursor::new(Vec::new());
+        let num_vectors = builder
+            .build(
+                |visit_vector| {
+                    visit_vector(DocAddress::new(0, 0), vec![1.0, 2.0, 3.0]);
+                    Ok(())
+                },
+                &mut output,
+            )
+            .unwrap();
+        assert_eq!(num_vectors, 0);
+        assert!(output.into_inner().is_empty());
+    }
+
+    #[test]
+    fn test_dense_vector_index_invalid() {
+        DenseVectorIndex::metadata_num_bytes(&[0; HEADER_NUM_BYTES]).unwrap_err();
+        DenseVectorIndex::metadata_num_bytes(&[0; 4]).unwrap_err();
+    }
+}
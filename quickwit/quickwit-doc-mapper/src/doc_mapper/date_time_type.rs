@@ -153,6 +153,13 @@ impl QuickwitDateTimeOptions {
 #[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 pub struct InputFormats(Vec<DateTimeInputFormat>);
 
+impl InputFormats {
+    /// Returns the accepted input formats, in the order they are tried.
+    pub fn iter(&self) -> impl Iterator<Item = &DateTimeInputFormat> {
+        self.0.iter()
+    }
+}
+
 impl Default for InputFormats {
     fn default() -> Self {
         Self(vec![
@@ -0,0 +1,272 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use tantivy::schema::OwnedValue as TantivyValue;
+
+use super::default_as_true;
+
+/// Maximum number of dimensions of a dense vector.
+pub const MAX_DENSE_VECTOR_DIMS: usize = 4096;
+
+/// A struct holding dense vector field options.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuickwitDenseVectorOptions {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Number of dimensions of the vectors.
+    pub dims: usize,
+
+    /// Similarity metric used to rank the vectors.
+    #[serde(default)]
+    pub similarity: VectorSimilarity,
+
+    #[serde(default = "default_as_true")]
+    pub stored: bool,
+}
+
+impl QuickwitDenseVectorOptions {
+    pub(crate) fn validate(&self) -> anyhow::Result<()> {
+        if self.dims == 0 || self.dims > MAX_DENSE_VECTOR_DIMS {
+            anyhow::bail!(
+                "`dims` must be between 1 and {MAX_DENSE_VECTOR_DIMS}, got `{}`",
+                self.dims
+            );
+        }
+        Ok(())
+    }
+
+    pub(crate) fn validate_json(
+        &self,
+        json_value: &serde_json_borrow::Value,
+    ) -> Result<(), String> {
+        let serde_json_borrow::Value::Array(elements) = json_value else {
+            return Err(format!("expected array of numbers, got `{json_value}`"));
+        };
+        if elements.len() != self.dims {
+            return Err(format!(
+                "expected vector with {} dimensions, got {}",
+                self.dims,
+                elements.len()
+            ));
+        }
+        for element in elements {
+            if element.as_f64().is_none() {
+                return Err(format!("expected number, got `{element}`"));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn parse_json(&self, json_value: &JsonValue) -> Result<TantivyValue, String> {
+        let JsonValue::Array(elements) = json_value else {
+            return Err(format!("expected array of numbers, got `{json_value}`"));
+        };
+        if elements.len() != self.dims {
+            return Err(format!(
+                "expected vector with {} dimensions, got {}",
+                self.dims,
+                elements.len()
+            ));
+        }
+        let mut vector = Vec::with_capacity(self.dims);
+
+        for element in elements {
+            let Some(value) = element.as_f64() else {
+                return Err(format!("expected number, got `{element}`"));
+            };
+            vector.push(value as f32);
+        }
+        Ok(TantivyValue::Bytes(encode_dense_vector(&vector)))
+    }
+
+    pub(crate) fn format_to_json(&self, bytes: &[u8]) -> Option<JsonValue> {
+        let vector = decode_dense_vector(bytes)?;
+
+        if vector.len() != self.dims {
+            return None;
+        }
+        let elements = vector
+            .into_iter()
+            .map(|value| {
+                serde_json::Number::from_f64(value as f64)
+                    .map(JsonValue::Number)
+                    .unwrap_or(JsonValue::Null)
+            })
+            .collect();
+        Some(JsonValue::Array(elements))
+    }
+}
+
+/// Similarity metric of a dense vector field.
+///
+/// Scores are mapped to positive values, the greater the more similar, following Elasticsearch.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorSimilarity {
+    /// Cosine of the angle between the vectors.
+    #[default]
+    Cosine,
+    /// Dot product of the vectors.
+    DotProduct,
+    /// Euclidean distance between the vectors.
+    L2Norm,
+}
+
+impl VectorSimilarity {
+    /// Prepares a vector before it is indexed or searched.
+    pub fn normalize(&self, vector: &mut [f32]) {
+        if *self != VectorSimilarity::Cosine {
+            return;
+        }
+        let norm = dot_product(vector, vector).sqrt();
+
+        if norm > 0.0 {
+            for value in vector.iter_mut() {
+                *value /= norm;
+            }
+        }
+    }
+
+    /// Returns the score of a vector for a query. Both must have been normalized.
+    pub fn score(&self, query: &[f32], vector: &[f32]) -> f32 {
+        match self {
+            VectorSimilarity::Cosine | VectorSimilarity::DotProduct => {
+                (1.0 + dot_product(query, vector)) / 2.0
+            }
+            VectorSimilarity::L2Norm => 1.0 / (1.0 + squared_l2_distance(query, vector)),
+        }
+    }
+
+    pub(crate) fn to_code(self) -> u8 {
+        match self {
+            VectorSimilarity::Cosine => 0,
+            VectorSimilarity::DotProduct => 1,
+            VectorSimilarity::L2Norm => 2,
+        }
+    }
+
+    pub(crate) fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(VectorSimilarity::Cosine),
+            1 => Some(VectorSimilarity::DotProduct),
+            2 => Some(VectorSimilarity::L2Norm),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn dot_product(left: &[f32], right: &[f32]) -> f32 {
+    left.iter()
+        .zip(right)
+        .map(|(left, right)| left * right)
+        .sum()
+}
+
+pub(crate) fn squared_l2_distance(left: &[f32], right: &[f32]) -> f32 {
+    left.iter()
+        .zip(right)
+        .map(|(left, right)| (left - right) * (left - right))
+        .sum()
+}
+
+/// Encodes a dense vector as the little-endian bytes of its values.
+pub fn encode_dense_vector(vector: &[f32]) -> Vec<u8> {
+    vector
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+/// Decodes a dense vector encoded with [`encode_dense_vector`].
+pub fn decode_dense_vector(bytes: &[u8]) -> Option<Vec<f32>> {
+    if bytes.len() % 4 != 0 {
+        return None;
+    }
+    let vector = bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect();
+    Some(vector)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_dense_vector_options_deserialization() {
+        let options: QuickwitDenseVectorOptions =
+            serde_json::from_value(json!({"dims": 3})).unwrap();
+        assert_eq!(options.dims, 3);
+        assert_eq!(options.similarity, VectorSimilarity::Cosine);
+        assert!(options.stored);
+
+        let options: QuickwitDenseVectorOptions =
+            serde_json::from_value(json!({"dims": 3, "similarity": "l2_norm", "stored": false}))
+                .unwrap();
+        assert_eq!(options.similarity, VectorSimilarity::L2Norm);
+        assert!(!options.stored);
+
+        let options: QuickwitDenseVectorOptions =
+            serde_json::from_value(json!({"dims": 0})).unwrap();
+        options.validate().unwrap_err();
+    }
+
+    #[test]
+    fn test_dense_vector_parse_and_format_json() {
+        let options: QuickwitDenseVectorOptions =
+            serde_json::from_value(json!({"dims": 3})).unwrap();
+        let TantivyValue::Bytes(bytes) = options.parse_json(&json!([1.0, -0.5, 2])).unwrap() else {
+            panic!("expected bytes");
+        };
+        assert_eq!(bytes.len(), 12);
+        assert_eq!(
+            options.format_to_json(&bytes).unwrap(),
+            json!([1.0, -0.5, 2.0])
+        );
+        let error = options.parse_json(&json!([1.0, 2.0])).unwrap_err();
+        assert_eq!(error, "expected vector with 3 dimensions, got 2");
+
+        let error = options.parse_json(&json!([1.0, "2", 3.0])).unwrap_err();
+        assert_eq!(error, "expected number, got `\"2\"`");
+    }
+
+    #[test]
+    fn test_vector_similarity_score() {
+        let mut query = vec![3.0, 4.0];
+        VectorSimilarity::Cosine.normalize(&mut query);
+        assert_eq!(query, vec![0.6, 0.8]);
+        assert!((VectorSimilarity::Cosine.score(&query, &query) - 1.0).abs() < 1e-6);
+
+        let mut opposite = vec![-3.0, -4.0];
+        VectorSimilarity::Cosine.normalize(&mut opposite);
+        assert!(VectorSimilarity::Cosine.score(&query, &opposite).abs() < 1e-6);
+
+        assert_eq!(
+            VectorSimilarity::L2Norm.score(&[0.0, 0.0], &[1.0, 0.0]),
+            0.5
+        );
+        assert_eq!(
+            VectorSimilarity::DotProduct.score(&[1.0, 0.0], &[0.5, 0.0]),
+            0.75
+        );
+    }
+}
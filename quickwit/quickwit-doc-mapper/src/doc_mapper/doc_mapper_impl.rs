@@ -12,15 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::num::NonZeroU32;
 
 use anyhow::{Context, bail};
 use fnv::FnvHashSet;
 use quickwit_proto::types::DocMappingUid;
-use quickwit_query::create_default_quickwit_tokenizer_manager;
-use quickwit_query::query_ast::QueryAst;
+use quickwit_query::query_ast::{
+    FullTextQuery, QueryAst, QueryAstTransformer, TermQuery, TermSetQuery,
+};
 use quickwit_query::tokenizers::TokenizerManager;
+use quickwit_query::{ID_FIELD_NAME, InvalidQuery, create_default_quickwit_tokenizer_manager};
 use serde::{Deserialize, Serialize};
 use serde_json::{self, Value as JsonValue};
 use serde_json_borrow::Map as BorrowedJsonMap;
@@ -32,6 +34,7 @@ use super::DocMapperBuilder;
 use super::field_mapping_entry::RAW_TOKENIZER_NAME;
 use super::field_presence::populate_field_presence;
 use super::tantivy_val_to_json::tantivy_value_to_json;
+use crate::dense_vector_index::DenseVectorField;
 use crate::doc_mapper::mapping_tree::{
     JsonValueIterator, MappingNode, MappingNodeRoot, build_field_path_from_str, build_mapping_tree,
     map_primitive_json_to_tantivy,
@@ -75,6 +78,8 @@ pub struct DocMapper {
     timestamp_field_name: Option<String>,
     /// Timestamp field path (name parsed)
     timestamp_field_path: Option<Vec<String>>,
+    /// Document id field name.
+    id_field_name: Option<String>,
     /// Root node of the field mapping tree.
     /// See [`MappingNode`].
     field_mappings: MappingNode,
@@ -125,6 +130,77 @@ fn validate_timestamp_field(
     Ok(())
 }
 
+fn validate_id_field(id_field_path: &str, mapping_root_node: &MappingNode) -> anyhow::Result<()> {
+    let Some(id_field_type) = mapping_root_node.find_field_mapping_type(id_field_path) else {
+        bail!("could not find id field `{id_field_path}` in field mappings");
+    };
+    let FieldMappingType::Text(text_options, cardinality) = &id_field_type else {
+        bail!("id field `{id_field_path}` should be a text field");
+    };
+    if cardinality != &Cardinality::SingleValued {
+        bail!("id field `{id_field_path}` should be single-valued");
+    }
+    let Some(indexing_options) = &text_options.indexing_options else {
+        bail!("id field `{id_field_path}` should be indexed");
+    };
+    if indexing_options.tokenizer.name() != RAW_TOKENIZER_NAME {
+        bail!("id field `{id_field_path}` should use the `raw` tokenizer");
+    }
+    if !text_options.fast.is_enabled() {
+        bail!("id field `{id_field_path}` should be a fast field");
+    }
+    Ok(())
+}
+
+/// Rewrites the queries targeting the reserved `_id` field into queries targeting the id field
+/// of the doc mapping.
+struct IdFieldResolver<'a> {
+    id_field_name_opt: Option<&'a str>,
+}
+
+impl IdFieldResolver<'_> {
+    fn resolve_field(&self, field: String) -> Result<String, InvalidQuery> {
+        if field != ID_FIELD_NAME {
+            return Ok(field);
+        }
+        let Some(id_field_name) = self.id_field_name_opt else {
+            return Err(InvalidQuery::SchemaError(format!(
+                "querying `{ID_FIELD_NAME}` requires an `id_field` in the doc mapping"
+            )));
+        };
+        Ok(id_field_name.to_string())
+    }
+}
+
+impl QueryAstTransformer for IdFieldResolver<'_> {
+    type Err = InvalidQuery;
+
+    fn transform_term(&mut self, mut term_query: TermQuery) -> Result<Option<QueryAst>, Self::Err> {
+        term_query.field = self.resolve_field(term_query.field)?;
+        Ok(Some(QueryAst::Term(term_query)))
+    }
+
+    fn transform_term_set(
+        &mut self,
+        term_set_query: TermSetQuery,
+    ) -> Result<Option<QueryAst>, Self::Err> {
+        let mut terms_per_field: HashMap<String, BTreeSet<String>> = HashMap::new();
+        for (field, terms) in term_set_query.terms_per_field {
+            let field = self.resolve_field(field)?;
+            terms_per_field.entry(field).or_default().extend(terms);
+        }
+        Ok(Some(QueryAst::TermSet(TermSetQuery { terms_per_field })))
+    }
+
+    fn transform_full_text(
+        &mut self,
+        mut full_text_query: FullTextQuery,
+    ) -> Result<Option<QueryAst>, Self::Err> {
+        full_text_query.field = self.resolve_field(full_text_query.field)?;
+        Ok(Some(QueryAst::FullText(full_text_query)))
+    }
+}
+
 impl From<DocMapper> for DocMapperBuilder {
     fn from(default_doc_mapper: DocMapper) -> Self {
         let partition_key_str = default_doc_mapper.partition_key.to_string();
@@ -138,6 +214,7 @@ impl From<DocMapper> for DocMapperBuilder {
             mode: default_doc_mapper.mode,
             field_mappings: default_doc_mapper.field_mappings.into(),
             timestamp_field: default_doc_mapper.timestamp_field_name,
+            id_field: default_doc_mapper.id_field_name,
             tag_fields: default_doc_mapper.tag_field_names,
             partition_key: partition_key_opt,
             max_num_partitions: default_doc_mapper.max_num_partitions,
@@ -199,6 +276,9 @@ impl TryFrom<DocMapperBuilder> for DocMapper {
         } else {
             None
         };
+        if let Some(id_field_name) = &doc_mapping.id_field {
+            validate_id_field(id_field_name, &field_mappings)?;
+        }
         let schema = schema_builder.build();
 
         let tokenizer_manager = create_default_quickwit_tokenizer_manager();
@@ -288,6 +368,7 @@ impl TryFrom<DocMapperBuilder> for DocMapper {
             default_search_field_names,
             timestamp_field_name: doc_mapping.timestamp_field,
             timestamp_field_path,
+            id_field_name: doc_mapping.id_field,
             field_mappings,
             concatenate_dynamic_fields,
             tag_field_names,
@@ -423,6 +504,12 @@ fn extract_single_obj(
     }
 }
 
+fn json_bytes_sample(json_doc: &[u8]) -> String {
+    std::str::from_utf8(json_doc)
+        .map(|doc_str| doc_str.chars().take(20).chain("...".chars()).collect())
+        .unwrap_or_else(|_| "document contains some invalid UTF-8 characters".to_string())
+}
+
 impl DocMapper {
     /// Returns the unique identifier of the doc mapping.
     pub fn doc_mapping_uid(&self) -> DocMappingUid {
@@ -464,17 +551,23 @@ impl DocMapper {
         Ok(())
     }
 
+    /// Validates a JSON byte slice according to the doc mapper.
+    pub fn validate_json_bytes(&self, json_doc: &[u8]) -> Result<(), DocParsingError> {
+        let json_value: serde_json_borrow::Value = serde_json::from_slice(json_doc)
+            .map_err(|_| DocParsingError::NotJsonObject(json_bytes_sample(json_doc)))?;
+        let serde_json_borrow::Value::Object(json_obj) = json_value else {
+            return Err(DocParsingError::NotJsonObject(json_bytes_sample(json_doc)));
+        };
+        self.validate_json_obj(&json_obj)
+    }
+
     /// Parses a JSON byte slice into a tantivy [`Document`].
     pub fn doc_from_json_bytes(
         &self,
         json_doc: &[u8],
     ) -> Result<(Partition, Document), DocParsingError> {
-        let json_obj: JsonObject = serde_json::from_slice(json_doc).map_err(|_| {
-            let json_doc_sample: String = std::str::from_utf8(json_doc)
-                .map(|doc_str| doc_str.chars().take(20).chain("...".chars()).collect())
-                .unwrap_or_else(|_| "document contains some invalid UTF-8 characters".to_string());
-            DocParsingError::NotJsonObject(json_doc_sample)
-        })?;
+        let json_obj: JsonObject = serde_json::from_slice(json_doc)
+            .map_err(|_| DocParsingError::NotJsonObject(json_bytes_sample(json_doc)))?;
         self.doc_from_json_obj(json_obj, json_doc.len() as u64)
     }
 
@@ -639,8 +732,14 @@ impl DocMapper {
         query_ast: &QueryAst,
         with_validation: bool,
     ) -> Result<(Box<dyn Query>, WarmupInfo), QueryParserError> {
+        let mut id_field_resolver = IdFieldResolver {
+            id_field_name_opt: self.id_field_name(),
+        };
+        let query_ast = id_field_resolver
+            .transform(query_ast.clone())?
+            .unwrap_or(QueryAst::MatchAll);
         build_query(
-            query_ast,
+            &query_ast,
             split_schema,
             self.tokenizer_manager(),
             &self.default_search_field_names[..],
@@ -667,6 +766,11 @@ impl DocMapper {
         self.timestamp_field_name.as_deref()
     }
 
+    /// Returns the document id field name.
+    pub fn id_field_name(&self) -> Option<&str> {
+        self.id_field_name.as_deref()
+    }
+
     /// Returns the tag `NameField`s on the current schema.
     /// Returns an error if a tag field is not found in this schema.
     pub fn tag_named_fields(&self) -> anyhow::Result<Vec<NamedField>> {
@@ -692,6 +796,20 @@ impl DocMapper {
         self.tag_field_names.clone()
     }
 
+    /// Returns the dense vector fields of the doc mapping, indexed per split in a dense vector
+    /// index.
+    pub fn dense_vector_fields(&self) -> Vec<DenseVectorField> {
+        let schema = &self.schema;
+        self.field_mappings
+            .dense_vector_fields()
+            .into_iter()
+            .map(|(field, options)| DenseVectorField {
+                name: schema.get_field_name(field).to_string(),
+                options,
+            })
+            .collect()
+    }
+
     /// Returns the maximum number of partitions.
     pub fn max_num_partitions(&self) -> NonZeroU32 {
         self.max_num_partitions
@@ -1109,6 +1227,57 @@ mod tests {
         assert_eq!(&builder.try_build().unwrap_err().to_string(), &expected_msg);
     }
 
+    #[test]
+    fn test_build_doc_mapper_with_id_field() {
+        let doc_mapper = r#"{
+            "id_field": "event_id",
+            "field_mappings": [
+                {"name": "event_id", "type": "text", "tokenizer": "raw", "fast": true}
+            ]
+        }"#;
+        let doc_mapper = serde_json::from_str::<DocMapperBuilder>(doc_mapper)
+            .unwrap()
+            .try_build()
+            .unwrap();
+        assert_eq!(doc_mapper.id_field_name(), Some("event_id"));
+    }
+
+    #[test]
+    fn test_fail_to_build_doc_mapper_with_invalid_id_field() {
+        let test_cases = [
+            (
+                r#"{"name": "other_id", "type": "text", "tokenizer": "raw", "fast": true}"#,
+                "could not find id field `event_id` in field mappings",
+            ),
+            (
+                r#"{"name": "event_id", "type": "u64", "fast": true}"#,
+                "id field `event_id` should be a text field",
+            ),
+            (
+                r#"{"name": "event_id", "type": "array<text>", "tokenizer": "raw", "fast": true}"#,
+                "id field `event_id` should be single-valued",
+            ),
+            (
+                r#"{"name": "event_id", "type": "text", "indexed": false, "fast": true}"#,
+                "id field `event_id` should be indexed",
+            ),
+            (
+                r#"{"name": "event_id", "type": "text", "tokenizer": "default", "fast": true}"#,
+                "id field `event_id` should use the `raw` tokenizer",
+            ),
+            (
+                r#"{"name": "event_id", "type": "text", "tokenizer": "raw"}"#,
+                "id field `event_id` should be a fast field",
+            ),
+        ];
+        for (field_mapping, expected_msg) in test_cases {
+            let doc_mapper =
+                format!(r#"{{"id_field": "event_id", "field_mappings": [{field_mapping}]}}"#);
+            let builder = serde_json::from_str::<DocMapperBuilder>(&doc_mapper).unwrap();
+            assert_eq!(builder.try_build().unwrap_err().to_string(), expected_msg);
+        }
+    }
+
     #[test]
     fn test_fail_to_build_doc_mapper_with_duplicate_fields() {
         {
@@ -2073,6 +2242,35 @@ mod tests {
         Ok(format!("{query:?}"))
     }
 
+    #[test]
+    fn test_doc_mapper_query_on_id_field() {
+        let doc_mapper: DocMapper = serde_json::from_str(
+            r#"{
+            "id_field": "event_id",
+            "field_mappings": [
+                {"name": "event_id", "type": "text", "tokenizer": "raw", "fast": true}
+            ]
+        }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            default_doc_mapper_query_aux(&doc_mapper, "_id:abc").unwrap(),
+            default_doc_mapper_query_aux(&doc_mapper, "event_id:abc").unwrap(),
+        );
+
+        let doc_mapper: DocMapper = serde_json::from_str(
+            r#"{
+            "field_mappings": [{"name": "body", "type": "text"}]
+        }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            default_doc_mapper_query_aux(&doc_mapper, "_id:abc").unwrap_err(),
+            "invalid query: query is incompatible with schema. querying `_id` requires an \
+             `id_field` in the doc mapping)"
+        );
+    }
+
     #[test]
     fn test_doc_mapper_sub_field_query_on_non_json_field_should_error() {
         let doc_mapper: DocMapper = serde_json::from_str(
@@ -27,6 +27,7 @@ use tantivy::schema::{
 };
 
 use super::date_time_type::QuickwitDateTimeOptions;
+use super::dense_vector_type::QuickwitDenseVectorOptions;
 use super::{FieldMappingType, default_as_true};
 use crate::doc_mapper::field_mapping_type::QuickwitFieldType;
 use crate::{Cardinality, QW_RESERVED_FIELD_NAMES};
@@ -281,7 +282,8 @@ impl QuickwitTextTokenizer {
     pub const fn from_static(name: &'static str) -> Self {
         Self(Cow::Borrowed(name))
     }
-    pub(crate) fn name(&self) -> &str {
+    /// Returns the name of the tokenizer.
+    pub fn name(&self) -> &str {
         &self.0
     }
     pub fn raw() -> Self {
@@ -472,6 +474,11 @@ impl FastFieldOptions {
             normalizer: QuickwitTextNormalizer::Raw,
         }
     }
+
+    /// Returns true if the field is a fast field.
+    pub fn is_enabled(&self) -> bool {
+        matches!(self, FastFieldOptions::EnabledWithNormalizer { .. })
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -740,6 +747,11 @@ fn deserialize_mapping_type(
             }
             return Ok(FieldMappingType::Concatenate(concatenate_options));
         }
+        QuickwitFieldType::DenseVector => {
+            let dense_vector_options: QuickwitDenseVectorOptions = serde_json::from_value(json)?;
+            dense_vector_options.validate()?;
+            return Ok(FieldMappingType::DenseVector(dense_vector_options));
+        }
     };
     match typ {
         Type::Str => {
@@ -836,6 +848,9 @@ fn typed_mapping_to_json_params(
         FieldMappingType::Concatenate(concatenate_options) => {
             serialize_to_map(&concatenate_options)
         }
+        FieldMappingType::DenseVector(dense_vector_options) => {
+            serialize_to_map(&dense_vector_options)
+        }
     }
     .unwrap()
 }
@@ -921,6 +936,7 @@ mod tests {
 
     use super::*;
     use crate::Cardinality;
+    use crate::doc_mapper::dense_vector_type::VectorSimilarity;
     use crate::doc_mapper::{FastFieldOptions, FieldMappingType};
 
     #[test]
@@ -1078,6 +1094,51 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_deserialize_dense_vector_mapping_entry() {
+        let mapping_entry = serde_json::from_str::<FieldMappingEntry>(
+            r#"
+            {
+                "name": "embedding",
+                "type": "dense_vector",
+                "dims": 384,
+                "similarity": "dot_product"
+            }"#,
+        )
+        .unwrap();
+        let FieldMappingType::DenseVector(options) = &mapping_entry.mapping_type else {
+            panic!("wrong property type");
+        };
+        assert_eq!(options.dims, 384);
+        assert_eq!(options.similarity, VectorSimilarity::DotProduct);
+        assert!(options.stored);
+
+        let mapping_entry_json = serde_json::to_value(&mapping_entry).unwrap();
+        assert_eq!(
+            mapping_entry_json,
+            json!({
+                "name": "embedding",
+                "type": "dense_vector",
+                "dims": 384,
+                "similarity": "dot_product",
+                "stored": true,
+            })
+        );
+        let error = serde_json::from_str::<FieldMappingEntry>(
+            r#"
+            {
+                "name": "embedding",
+                "type": "dense_vector",
+                "dims": 10000
+            }"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "error while parsing field `embedding`: `dims` must be between 1 and 4096, got `10000`"
+        );
+    }
+
     #[test]
     fn test_deserialize_json_mapping_entry_not_indexed_invalid() {
         let result = serde_json::from_str::<FieldMappingEntry>(
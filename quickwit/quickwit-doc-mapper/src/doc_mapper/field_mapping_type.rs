@@ -15,6 +15,7 @@
 use tantivy::schema::Type;
 
 use super::date_time_type::QuickwitDateTimeOptions;
+use super::dense_vector_type::QuickwitDenseVectorOptions;
 use super::field_mapping_entry::QuickwitBoolOptions;
 use crate::Cardinality;
 use crate::doc_mapper::field_mapping_entry::{
@@ -48,6 +49,8 @@ pub enum FieldMappingType {
     Object(QuickwitObjectOptions),
     /// Concatenate field mapping type configuration.
     Concatenate(QuickwitConcatenateOptions),
+    /// Dense vector mapping type configuration.
+    DenseVector(QuickwitDenseVectorOptions),
 }
 
 impl FieldMappingType {
@@ -67,6 +70,7 @@ impl FieldMappingType {
                 return QuickwitFieldType::Object;
             }
             FieldMappingType::Concatenate(_) => return QuickwitFieldType::Concatenate,
+            FieldMappingType::DenseVector(_) => return QuickwitFieldType::DenseVector,
         };
         match cardinality {
             Cardinality::SingleValued => QuickwitFieldType::Simple(primitive_type),
@@ -80,6 +84,7 @@ pub enum QuickwitFieldType {
     Simple(Type),
     Object,
     Concatenate,
+    DenseVector,
     Array(Type),
 }
 
@@ -90,6 +95,7 @@ impl QuickwitFieldType {
             QuickwitFieldType::Object => "object".to_string(),
             QuickwitFieldType::Array(typ) => format!("array<{}>", primitive_type_to_str(typ)),
             QuickwitFieldType::Concatenate => "concatenate".to_string(),
+            QuickwitFieldType::DenseVector => "dense_vector".to_string(),
         }
     }
 
@@ -100,6 +106,9 @@ impl QuickwitFieldType {
         if type_str == "concatenate" {
             return Some(QuickwitFieldType::Concatenate);
         }
+        if type_str == "dense_vector" {
+            return Some(QuickwitFieldType::DenseVector);
+        }
         if type_str.starts_with("array<") && type_str.ends_with('>') {
             let parsed_type_str = parse_primitive_type(&type_str[6..type_str.len() - 1])?;
             return Some(QuickwitFieldType::Array(parsed_type_str));
@@ -163,5 +172,7 @@ mod tests {
         test_parse_type_aux("object2", None);
         test_parse_type_aux("bool", Some(QuickwitFieldType::Simple(Type::Bool)));
         test_parse_type_aux("ip", Some(QuickwitFieldType::Simple(Type::IpAddr)));
+        test_parse_type_aux("dense_vector", Some(QuickwitFieldType::DenseVector));
+        test_parse_type_aux("array<dense_vector>", None);
     }
 }
@@ -28,6 +28,7 @@ use tantivy::schema::{
 };
 
 use super::date_time_type::QuickwitDateTimeOptions;
+use super::dense_vector_type::QuickwitDenseVectorOptions;
 use super::field_mapping_entry::QuickwitBoolOptions;
 use super::tantivy_val_to_json::formatted_tantivy_value_to_json;
 use crate::doc_mapper::field_mapping_entry::{
@@ -42,6 +43,7 @@ pub enum LeafType {
     Bool(QuickwitBoolOptions),
     Bytes(QuickwitBytesOptions),
     DateTime(QuickwitDateTimeOptions),
+    DenseVector(QuickwitDenseVectorOptions),
     F64(QuickwitNumericOptions),
     I64(QuickwitNumericOptions),
     U64(QuickwitNumericOptions),
@@ -209,6 +211,9 @@ impl LeafType {
                     Err(format!("expected object, got `{json_val}`"))
                 }
             }
+            LeafType::DenseVector(dense_vector_options) => {
+                dense_vector_options.validate_json(json_val)
+            }
         }
     }
 
@@ -243,6 +248,9 @@ impl LeafType {
             }
             LeafType::DateTime(date_time_options) => date_time_options.parse_json(&json_val),
             LeafType::Bytes(binary_options) => binary_options.input_format.parse_json(&json_val),
+            LeafType::DenseVector(dense_vector_options) => {
+                dense_vector_options.parse_json(&json_val)
+            }
             LeafType::Json(_) => {
                 if let JsonValue::Object(json_obj) = json_val {
                     Ok(TantivyValue::Object(
@@ -294,6 +302,9 @@ impl LeafType {
                 Err("unsupported concat type: DateTime".to_string())
             }
             LeafType::Bytes(_binary_options) => Err("unsupported concat type: Bytes".to_string()),
+            LeafType::DenseVector(_dense_vector_options) => {
+                Err("unsupported concat type: DenseVector".to_string())
+            }
             LeafType::Json(_) => {
                 if let JsonValue::Object(json_obj) = json_val {
                     Ok(OneOrIter::Iter(
@@ -318,6 +329,7 @@ impl LeafType {
             IpAddr(_),
             // won't be supported
             Bytes(_),
+            DenseVector(_),
         */
     }
 }
@@ -341,6 +353,12 @@ impl MappingLeaf {
             // We just ignore `null`.
             return Ok(());
         }
+        if let LeafType::DenseVector(dense_vector_options) = &self.typ {
+            // The array is the vector itself rather than multiple values.
+            return dense_vector_options
+                .validate_json(json_value)
+                .map_err(|err_msg| DocParsingError::ValueError(path.join("."), err_msg));
+        }
         if let BorrowedJsonValue::Array(els) = json_value {
             if self.cardinality == Cardinality::SingleValued {
                 return Err(DocParsingError::MultiValuesNotSupported(path.join(".")));
@@ -374,6 +392,14 @@ impl MappingLeaf {
             // We just ignore `null`.
             return Ok(());
         }
+        if let LeafType::DenseVector(dense_vector_options) = &self.typ {
+            // The array is the vector itself rather than multiple values.
+            let value = dense_vector_options
+                .parse_json(&json_val)
+                .map_err(|err_msg| DocParsingError::ValueError(path.join("."), err_msg))?;
+            document.add_field_value(self.field, &value);
+            return Ok(());
+        }
         if let JsonValue::Array(els) = json_val {
             if self.cardinality == Cardinality::SingleValued {
                 return Err(DocParsingError::MultiValuesNotSupported(path.join(".")));
@@ -801,6 +827,32 @@ impl MappingNode {
         self.branches.len()
     }
 
+    /// Returns the fields and options of the dense vector leaves of the tree, in mapping order.
+    pub fn dense_vector_fields(&self) -> Vec<(Field, QuickwitDenseVectorOptions)> {
+        let mut dense_vector_fields = Vec::new();
+        self.collect_dense_vector_fields(&mut dense_vector_fields);
+        dense_vector_fields
+    }
+
+    fn collect_dense_vector_fields(
+        &self,
+        dense_vector_fields: &mut Vec<(Field, QuickwitDenseVectorOptions)>,
+    ) {
+        for field_name in &self.branches_order {
+            match self.branches.get(field_name) {
+                Some(MappingTree::Leaf(MappingLeaf {
+                    field,
+                    typ: LeafType::DenseVector(options),
+                    ..
+                })) => dense_vector_fields.push((*field, options.clone())),
+                Some(MappingTree::Node(child_node)) => {
+                    child_node.collect_dense_vector_fields(dense_vector_fields)
+                }
+                _ => {}
+            }
+        }
+    }
+
     pub fn insert(&mut self, path: &str, node: MappingTree) {
         self.branches_order.push(path.to_string());
         self.branches.insert(path.to_string(), node);
@@ -910,6 +962,7 @@ impl From<MappingLeaf> for FieldMappingType {
             LeafType::IpAddr(opt) => FieldMappingType::IpAddr(opt, leaf.cardinality),
             LeafType::DateTime(opt) => FieldMappingType::DateTime(opt, leaf.cardinality),
             LeafType::Bytes(opt) => FieldMappingType::Bytes(opt, leaf.cardinality),
+            LeafType::DenseVector(opt) => FieldMappingType::DenseVector(opt),
             LeafType::Json(opt) => FieldMappingType::Json(opt, leaf.cardinality),
         }
     }
@@ -1129,6 +1182,18 @@ fn get_bytes_options(quickwit_numeric_options: &QuickwitBytesOptions) -> BytesOp
     bytes_options
 }
 
+/// Dense vectors are stored in a bytes fast field, from which the vector index of the split is
+/// built when it is packaged.
+fn get_dense_vector_options(
+    quickwit_dense_vector_options: &QuickwitDenseVectorOptions,
+) -> BytesOptions {
+    let mut bytes_options = BytesOptions::default().set_fast();
+    if quickwit_dense_vector_options.stored {
+        bytes_options = bytes_options.set_stored();
+    }
+    bytes_options
+}
+
 fn get_ip_address_options(quickwit_ip_address_options: &QuickwitIpAddrOptions) -> IpAddrOptions {
     let mut ip_address_options = IpAddrOptions::default();
     if quickwit_ip_address_options.stored {
@@ -1292,6 +1357,17 @@ fn build_mapping_from_field_type<'a>(
             };
             Ok((MappingTree::Leaf(mapping_leaf), Vec::new()))
         }
+        FieldMappingType::DenseVector(options) => {
+            let bytes_options = get_dense_vector_options(options);
+            let field = schema_builder.add_bytes_field(&field_name, bytes_options);
+            let mapping_leaf = MappingLeaf {
+                field,
+                typ: LeafType::DenseVector(options.clone()),
+                cardinality: Cardinality::SingleValued,
+                concatenate: Vec::new(),
+            };
+            Ok((MappingTree::Leaf(mapping_leaf), Vec::new()))
+        }
         FieldMappingType::Json(options, cardinality) => {
             let json_options = JsonObjectOptions::from(options.clone());
             let field = schema_builder.add_json_field(&field_name, json_options);
@@ -1337,12 +1413,15 @@ mod tests {
         JsonValueIterator, LeafType, MapOrArrayIter, MappingLeaf, add_key_to_vec_map,
         extract_val_from_tantivy_val,
     };
-    use crate::Cardinality;
     use crate::doc_mapper::date_time_type::QuickwitDateTimeOptions;
+    use crate::doc_mapper::dense_vector_type::{
+        QuickwitDenseVectorOptions, VectorSimilarity, decode_dense_vector,
+    };
     use crate::doc_mapper::field_mapping_entry::{
         BinaryFormat, QuickwitBoolOptions, QuickwitBytesOptions, QuickwitIpAddrOptions,
         QuickwitNumericOptions, QuickwitTextOptions,
     };
+    use crate::{Cardinality, DocParsingError};
 
     #[test]
     fn test_field_name_from_field_path() {
@@ -1804,6 +1883,42 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_parse_dense_vector() {
+        let typ = LeafType::DenseVector(QuickwitDenseVectorOptions {
+            description: None,
+            dims: 3,
+            similarity: VectorSimilarity::Cosine,
+            stored: true,
+        });
+        let field = Field::from_field_id(10);
+        let leaf_entry = MappingLeaf {
+            field,
+            typ,
+            cardinality: Cardinality::SingleValued,
+            concatenate: Vec::new(),
+        };
+        let mut document = Document::default();
+        let mut path = vec!["embedding".to_string()];
+        leaf_entry
+            .doc_from_json(json!([0.5, 1.0, -2.0]), &mut document, &mut path)
+            .unwrap();
+        assert_eq!(document.len(), 1);
+        let bytes = document.get_first(field).unwrap().as_bytes().unwrap();
+        assert_eq!(decode_dense_vector(bytes).unwrap(), vec![0.5, 1.0, -2.0]);
+
+        let error = leaf_entry
+            .doc_from_json(json!([0.5, 1.0]), &mut document, &mut path)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            DocParsingError::ValueError(
+                "embedding".to_string(),
+                "expected vector with 3 dimensions, got 2".to_string()
+            )
+        );
+    }
+
     #[test]
     fn test_field_path_for_field_name() {
         assert_eq!(super::build_field_path_from_str(""), Vec::<String>::new());
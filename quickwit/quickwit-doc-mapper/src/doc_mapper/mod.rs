@@ -13,6 +13,7 @@
 // limitations under the License.
 
 mod date_time_type;
+mod dense_vector_type;
 mod doc_mapper_builder;
 mod doc_mapper_impl;
 mod field_mapping_entry;
@@ -26,6 +27,11 @@ use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::ops::Bound;
 
+pub use dense_vector_type::{
+    MAX_DENSE_VECTOR_DIMS, QuickwitDenseVectorOptions, VectorSimilarity, decode_dense_vector,
+    encode_dense_vector,
+};
+pub(crate) use dense_vector_type::{dot_product, squared_l2_distance};
 pub use doc_mapper_builder::DocMapperBuilder;
 pub use doc_mapper_impl::DocMapper;
 #[cfg(all(test, feature = "multilang"))]
@@ -86,6 +92,13 @@ pub enum Automaton {
     /// A regex in it's str representation as tantivy_fst::Regex isn't PartialEq, and the path if
     /// inside a json field
     Regex(Option<Vec<u8>>, String),
+    /// The Levenshtein automaton of a fuzzy query, and the path if inside a json field
+    Fuzzy {
+        path: Option<Vec<u8>>,
+        value: String,
+        distance: u8,
+        transpositions: bool,
+    },
     // we could add termset query here, instead of downloading the whole dictionary
 }
 
@@ -340,6 +353,32 @@ mod tests {
         doc_mapper.validate_json_obj(json_obj)
     }
 
+    #[test]
+    fn test_validate_json_bytes() {
+        let doc_mapper: DocMapper = serde_json::from_str(
+            r#"{
+                "timestamp_field": "timestamp",
+                "field_mappings": [{"name": "timestamp", "type": "datetime", "fast": true}]
+            }"#,
+        )
+        .unwrap();
+        doc_mapper
+            .validate_json_bytes(br#"{"timestamp": 1735689600}"#)
+            .unwrap();
+        assert!(matches!(
+            doc_mapper.validate_json_bytes(br#"{"body": "hello"}"#),
+            Err(DocParsingError::RequiredField(_))
+        ));
+        assert!(matches!(
+            doc_mapper.validate_json_bytes(b"[1, 2, 3]"),
+            Err(DocParsingError::NotJsonObject(_))
+        ));
+        assert!(matches!(
+            doc_mapper.validate_json_bytes(b"not json"),
+            Err(DocParsingError::NotJsonObject(_))
+        ));
+    }
+
     #[test]
     fn test_validate_doc() {
         const JSON_CONFIG_VALUE: &str = r#"{
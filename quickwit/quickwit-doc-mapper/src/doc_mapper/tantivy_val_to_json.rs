@@ -255,6 +255,13 @@ pub fn formatted_tantivy_value_to_json(
                 Err(value)
             }
         }
+        LeafType::DenseVector(dense_vector_options) => {
+            if let TantivyValue::Bytes(ref bytes) = value {
+                dense_vector_options.format_to_json(bytes).ok_or(value)
+            } else {
+                Err(value)
+            }
+        }
         LeafType::DateTime(date_time_options) => date_time_options
             .reparse_tantivy_value(&value)
             .map(|date_time| {
@@ -126,6 +126,12 @@ pub struct DocMapping {
     #[serde(default)]
     pub timestamp_field: Option<String>,
 
+    /// Declares the field which uniquely identifies a document. Documents can be fetched by id,
+    /// and documents sharing the same id within a split are deduplicated at indexing time.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_field: Option<String>,
+
     /// Declares the low cardinality fields for which the values ​​are recorded directly in the
     /// splits metadata.
     #[schema(value_type = Vec<String>)]
@@ -213,6 +219,7 @@ mod tests {
                 },
             ],
             timestamp_field: Some("timestamp".to_string()),
+            id_field: None,
             tag_fields: BTreeSet::from_iter(["level".to_string()]),
             partition_key: Some("tenant_id".to_string()),
             max_num_partitions: NonZeroU32::new(100).unwrap(),
@@ -26,13 +26,16 @@ mod error;
 mod query_builder;
 mod routing_expression;
 
+/// Dense vector indexes of splits.
+pub mod dense_vector_index;
 /// Pruning tags manipulation.
 pub mod tag_pruning;
 
 pub use doc_mapper::{
     Automaton, BinaryFormat, DocMapper, DocMapperBuilder, FastFieldWarmupInfo, FieldMappingEntry,
-    FieldMappingType, JsonObject, NamedField, QuickwitBytesOptions, QuickwitJsonOptions, TermRange,
-    TokenizerConfig, TokenizerEntry, WarmupInfo, analyze_text,
+    FieldMappingType, JsonObject, MAX_DENSE_VECTOR_DIMS, NamedField, QuickwitBytesOptions,
+    QuickwitDenseVectorOptions, QuickwitJsonOptions, TermRange, TokenizerConfig, TokenizerEntry,
+    VectorSimilarity, WarmupInfo, analyze_text, decode_dense_vector, encode_dense_vector,
 };
 use doc_mapper::{
     FastFieldOptions, FieldMappingEntryForSerialization, IndexRecordOptionSchema,
@@ -17,8 +17,8 @@ use std::convert::Infallible;
 use std::ops::Bound;
 
 use quickwit_query::query_ast::{
-    FieldPresenceQuery, FullTextQuery, PhrasePrefixQuery, QueryAst, QueryAstVisitor, RangeQuery,
-    RegexQuery, TermSetQuery, WildcardQuery,
+    FieldPresenceQuery, FullTextQuery, FuzzyQuery, PhrasePrefixQuery, QueryAst, QueryAstVisitor,
+    RangeQuery, RegexQuery, TermSetQuery, WildcardQuery,
 };
 use quickwit_query::tokenizers::TokenizerManager;
 use quickwit_query::{InvalidQuery, find_field_or_hit_dynamic};
@@ -383,6 +383,23 @@ impl<'a, 'b: 'a> QueryAstVisitor<'a> for ExtractPrefixTermRanges<'b> {
         self.add_automaton(field, Automaton::Regex(path, regex));
         Ok(())
     }
+
+    fn visit_fuzzy(&mut self, fuzzy_query: &'a FuzzyQuery) -> Result<(), Self::Err> {
+        let (term, path, value) = match fuzzy_query.to_term(self.schema, self.tokenizer_manager) {
+            Ok(res) => res,
+            /* the query will be nullified when casting to a tantivy ast */
+            Err(InvalidQuery::FieldDoesNotExist { .. }) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let automaton = Automaton::Fuzzy {
+            path,
+            value,
+            distance: fuzzy_query.distance,
+            transpositions: fuzzy_query.transpositions,
+        };
+        self.add_automaton(term.field(), automaton);
+        Ok(())
+    }
 }
 
 type TermRangeWarmupInfo = HashMap<Field, HashMap<TermRange, PositionNeeded>>;
@@ -24,12 +24,13 @@ use quickwit_common::metrics::IntCounter;
 use quickwit_common::pretty::PrettySample;
 use quickwit_common::{Progress, rate_limited_info};
 use quickwit_metastore::{
-    ListSplitsQuery, ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, SplitInfo,
+    ListSplitsQuery, ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, Split, SplitInfo,
     SplitMetadata, SplitState,
 };
 use quickwit_proto::metastore::{
-    DeleteSplitsRequest, ListSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError,
-    MetastoreService, MetastoreServiceClient,
+    DeleteSplitsRequest, ListPointInTimeLeasesRequest, ListSplitsRequest,
+    MarkSplitsForDeletionRequest, MetastoreError, MetastoreResult, MetastoreService,
+    MetastoreServiceClient,
 };
 use quickwit_proto::types::{IndexUid, SplitId};
 use quickwit_storage::{BulkDeleteError, Storage};
@@ -40,6 +41,61 @@ use tracing::{error, instrument};
 /// The maximum number of splits that the GC should delete per attempt.
 const DELETE_SPLITS_BATCH_SIZE: usize = 10_000;
 
+/// Margin subtracted from the opening timestamp of points in time to absorb the clock skew between
+/// the searchers opening them and the metastore timestamping the splits updates.
+const POINT_IN_TIME_CLOCK_SKEW_MARGIN_SECS: i64 = 60;
+
+/// Splits marked for deletion that may still be read by a live point in time search.
+///
+/// A point in time pins the splits published when it was opened. Any of them that has been marked
+/// for deletion since then may still be searched, so the splits of the index marked for deletion
+/// after the oldest live point in time was opened are kept, regardless of the deletion grace
+/// period.
+#[derive(Debug, Clone, Default)]
+pub struct PinnedSplits {
+    pinned_since_per_index: HashMap<IndexUid, i64>,
+}
+
+impl PinnedSplits {
+    /// Loads the splits pinned by the point in time leases recorded in the metastore that have not
+    /// expired yet.
+    pub async fn load(metastore: &MetastoreServiceClient) -> MetastoreResult<Self> {
+        let leases = metastore
+            .list_point_in_time_leases(ListPointInTimeLeasesRequest {})
+            .await?
+            .leases;
+        let mut pinned_splits = PinnedSplits::default();
+        for lease in leases {
+            for index_uid in lease.index_uids {
+                pinned_splits.pin_index(index_uid, lease.opened_at);
+            }
+        }
+        Ok(pinned_splits)
+    }
+
+    /// Pins the splits of an index that were published when a point in time was opened at
+    /// `opened_at` (Unix timestamp in seconds).
+    pub fn pin_index(&mut self, index_uid: IndexUid, opened_at: i64) {
+        let pinned_since = opened_at - POINT_IN_TIME_CLOCK_SKEW_MARGIN_SECS;
+        self.pinned_since_per_index
+            .entry(index_uid)
+            .and_modify(|current_pinned_since| {
+                *current_pinned_since = (*current_pinned_since).min(pinned_since)
+            })
+            .or_insert(pinned_since);
+    }
+
+    fn is_pinned(&self, split: &Split) -> bool {
+        let Some(pinned_since) = self
+            .pinned_since_per_index
+            .get(&split.split_metadata.index_uid)
+        else {
+            return false;
+        };
+        split.update_timestamp >= *pinned_since
+    }
+}
+
 pub struct GcMetrics {
     pub deleted_splits: IntCounter,
     pub deleted_bytes: IntCounter,
@@ -102,13 +158,16 @@ pub struct SplitRemovalInfo {
 /// * `deletion_grace_period` -  Threshold period after which a marked as deleted split can be
 ///   safely deleted.
 /// * `dry_run` - Should this only return a list of affected files without performing deletion.
+/// * `pinned_splits` - Splits marked for deletion that live points in time may still read.
 /// * `progress` - For reporting progress (useful when called from within a quickwit actor).
+#[allow(clippy::too_many_arguments)]
 pub async fn run_garbage_collect(
     indexes: HashMap<IndexUid, Arc<dyn Storage>>,
     metastore: MetastoreServiceClient,
     staged_grace_period: Duration,
     deletion_grace_period: Duration,
     dry_run: bool,
+    pinned_splits: &PinnedSplits,
     progress_opt: Option<&Progress>,
     metrics: Option<GcMetrics>,
 ) -> anyhow::Result<SplitRemovalInfo> {
@@ -185,6 +244,7 @@ pub async fn run_garbage_collect(
         updated_before_timestamp,
         metastore,
         indexes,
+        pinned_splits,
         progress_opt,
         metrics,
     )
@@ -269,11 +329,11 @@ async fn delete_splits(
     if error_encountered { Err(()) } else { Ok(()) }
 }
 
-/// Fetch the list metadata from the metastore and returns them as a Vec.
-async fn list_splits_metadata(
+/// Fetch the list of splits from the metastore and returns them as a Vec.
+async fn list_splits(
     metastore: &MetastoreServiceClient,
     query: &ListSplitsQuery,
-) -> anyhow::Result<Vec<SplitMetadata>> {
+) -> anyhow::Result<Vec<Split>> {
     let list_splits_request = ListSplitsRequest::try_from_list_splits_query(query)
         .context("failed to build list splits request")?;
     let splits_to_delete_stream = metastore
@@ -281,7 +341,7 @@ async fn list_splits_metadata(
         .await
         .context("failed to fetch stream splits")?;
     let splits = splits_to_delete_stream
-        .collect_splits_metadata()
+        .collect_splits()
         .await
         .context("failed to collect splits")?;
     Ok(splits)
@@ -306,15 +366,17 @@ fn get_index_gc_concurrency() -> Option<usize> {
 /// Removes any splits marked for deletion which haven't been
 /// updated after `updated_before_timestamp` in batches of 1,000 splits.
 ///
-/// Only splits from index_uids in the `storages` map will be deleted.
+/// Only splits from index_uids in the `storages` map will be deleted. Splits pinned by a live point
+/// in time are skipped.
 ///
 /// The aim of this is to spread the load out across a longer period
 /// rather than short, heavy bursts on the metastore and storage system itself.
-#[instrument(skip(storages, metastore, progress_opt, metrics), fields(num_indexes=%storages.len()))]
+#[instrument(skip(storages, metastore, pinned_splits, progress_opt, metrics), fields(num_indexes=%storages.len()))]
 async fn delete_splits_marked_for_deletion_several_indexes(
     updated_before_timestamp: i64,
     metastore: MetastoreServiceClient,
     storages: HashMap<IndexUid, Arc<dyn Storage>>,
+    pinned_splits: &PinnedSplits,
     progress_opt: Option<&Progress>,
     metrics: Option<GcMetrics>,
 ) -> SplitRemovalInfo {
@@ -345,36 +407,36 @@ async fn delete_splits_marked_for_deletion_several_indexes(
         };
         let sleep_future = tokio::time::sleep(sleep_duration);
 
-        let splits_metadata_to_delete: Vec<SplitMetadata> = match protect_future(
-            progress_opt,
-            list_splits_metadata(&metastore, &list_splits_query),
-        )
-        .await
-        {
-            Ok(splits) => splits,
-            Err(list_splits_err) => {
-                error!(error=?list_splits_err, "failed to list splits");
-                break;
-            }
-        };
+        let splits_to_delete: Vec<Split> =
+            match protect_future(progress_opt, list_splits(&metastore, &list_splits_query)).await {
+                Ok(splits) => splits,
+                Err(list_splits_err) => {
+                    error!(error=?list_splits_err, "failed to list splits");
+                    break;
+                }
+            };
 
         // We page through the list of splits to delete using a limit and a `search_after` trick.
         // To detect if this is the last page, we check if the number of splits is less than the
         // limit.
-        assert!(splits_metadata_to_delete.len() <= DELETE_SPLITS_BATCH_SIZE);
+        assert!(splits_to_delete.len() <= DELETE_SPLITS_BATCH_SIZE);
         let splits_to_delete_possibly_remaining =
-            splits_metadata_to_delete.len() == DELETE_SPLITS_BATCH_SIZE;
+            splits_to_delete.len() == DELETE_SPLITS_BATCH_SIZE;
 
         // set split after which to search for the next loop
-        let Some(last_split_metadata) = splits_metadata_to_delete.last() else {
+        let Some(last_split) = splits_to_delete.last() else {
             break;
         };
-        list_splits_query = list_splits_query.after_split(last_split_metadata);
+        list_splits_query = list_splits_query.after_split(&last_split.split_metadata);
 
         let mut splits_metadata_to_delete_per_index: HashMap<IndexUid, Vec<SplitMetadata>> =
             HashMap::with_capacity(storages.len());
 
-        for meta in splits_metadata_to_delete {
+        for split in splits_to_delete {
+            if pinned_splits.is_pinned(&split) {
+                continue;
+            }
+            let meta = split.split_metadata;
             if !storages.contains_key(&meta.index_uid) {
                 rate_limited_info!(limit_per_min=6, index_uid=?meta.index_uid, "split not listed in storage map: skipping");
                 continue;
@@ -587,6 +649,7 @@ mod tests {
             Duration::from_secs(30),
             Duration::from_secs(30),
             false,
+            &PinnedSplits::default(),
             None,
             None,
         )
@@ -615,6 +678,7 @@ mod tests {
             Duration::from_secs(0),
             Duration::from_secs(30),
             false,
+            &PinnedSplits::default(),
             None,
             None,
         )
@@ -693,6 +757,7 @@ mod tests {
             Duration::from_secs(30),
             Duration::from_secs(30),
             false,
+            &PinnedSplits::default(),
             None,
             None,
         )
@@ -721,6 +786,106 @@ mod tests {
             Duration::from_secs(30),
             Duration::from_secs(0),
             false,
+            &PinnedSplits::default(),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let query = ListSplitsQuery::for_index(index_uid);
+        let list_splits_request = ListSplitsRequest::try_from_list_splits_query(&query).unwrap();
+        assert_eq!(
+            metastore
+                .list_splits(list_splits_request)
+                .await
+                .unwrap()
+                .collect_splits()
+                .await
+                .unwrap()
+                .len(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_gc_skips_splits_pinned_by_point_in_time() {
+        let storage = storage_for_test();
+        let metastore = metastore_for_test();
+
+        let index_id = "test-run-gc-pinned--index";
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(index_id, &index_uri);
+        let create_index_request =
+            CreateIndexRequest::try_from_index_config(&index_config).unwrap();
+        let index_uid: IndexUid = metastore
+            .create_index(create_index_request)
+            .await
+            .unwrap()
+            .index_uid()
+            .clone();
+
+        let split_id = "test-run-gc-pinned--split";
+        let split_metadata = SplitMetadata {
+            split_id: split_id.to_string(),
+            index_uid: index_uid.clone(),
+            ..Default::default()
+        };
+        let stage_splits_request =
+            StageSplitsRequest::try_from_split_metadata(index_uid.clone(), &split_metadata)
+                .unwrap();
+        metastore.stage_splits(stage_splits_request).await.unwrap();
+        let mark_splits_for_deletion_request =
+            MarkSplitsForDeletionRequest::new(index_uid.clone(), vec![split_id.to_string()]);
+        metastore
+            .mark_splits_for_deletion(mark_splits_for_deletion_request)
+            .await
+            .unwrap();
+
+        // A point in time opened before the split was marked for deletion may still read it.
+        let mut pinned_splits = PinnedSplits::default();
+        let opened_at = OffsetDateTime::now_utc().unix_timestamp() - 10;
+        pinned_splits.pin_index(index_uid.clone(), opened_at);
+
+        run_garbage_collect(
+            hashmap(index_uid.clone(), storage.clone()),
+            metastore.clone(),
+            Duration::from_secs(30),
+            Duration::from_secs(0),
+            false,
+            &pinned_splits,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let query = ListSplitsQuery::for_index(index_uid.clone())
+            .with_split_state(SplitState::MarkedForDeletion);
+        let list_splits_request = ListSplitsRequest::try_from_list_splits_query(&query).unwrap();
+        assert_eq!(
+            metastore
+                .list_splits(list_splits_request)
+                .await
+                .unwrap()
+                .collect_splits()
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Pinning other indexes does not prevent the split from being deleted.
+        let mut pinned_splits = PinnedSplits::default();
+        pinned_splits.pin_index(IndexUid::for_test("other-index", 0), opened_at);
+
+        run_garbage_collect(
+            hashmap(index_uid.clone(), storage.clone()),
+            metastore.clone(),
+            Duration::from_secs(30),
+            Duration::from_secs(0),
+            false,
+            &pinned_splits,
             None,
             None,
         )
@@ -742,6 +907,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pinned_splits_keeps_oldest_point_in_time() {
+        let index_uid = IndexUid::for_test("test-index", 0);
+        let mut pinned_splits = PinnedSplits::default();
+        pinned_splits.pin_index(index_uid.clone(), 2_000);
+        pinned_splits.pin_index(index_uid.clone(), 1_000);
+        pinned_splits.pin_index(index_uid.clone(), 3_000);
+
+        let split_updated_at = |update_timestamp: i64| Split {
+            split_metadata: SplitMetadata {
+                index_uid: index_uid.clone(),
+                ..Default::default()
+            },
+            split_state: SplitState::MarkedForDeletion,
+            update_timestamp,
+            publish_timestamp: None,
+        };
+        assert!(!pinned_splits.is_pinned(&split_updated_at(900)));
+        assert!(pinned_splits.is_pinned(&split_updated_at(950)));
+        assert!(pinned_splits.is_pinned(&split_updated_at(2_500)));
+    }
+
     #[tokio::test]
     async fn test_run_gc_deletes_splits_with_no_split() {
         // Test that we make only 2 calls to the metastore.
@@ -760,6 +947,7 @@ mod tests {
             Duration::from_secs(30),
             Duration::from_secs(30),
             false,
+            &PinnedSplits::default(),
             None,
             None,
         )
@@ -42,7 +42,7 @@ use thiserror::Error;
 use tracing::{error, info};
 
 use crate::garbage_collection::{
-    DeleteSplitsError, SplitRemovalInfo, delete_splits_from_storage_and_metastore,
+    DeleteSplitsError, PinnedSplits, SplitRemovalInfo, delete_splits_from_storage_and_metastore,
     run_garbage_collect,
 };
 
@@ -113,6 +113,10 @@ impl IndexService {
         self.metastore.clone()
     }
 
+    pub fn storage_resolver(&self) -> StorageResolver {
+        self.storage_resolver.clone()
+    }
+
     /// Creates an index from `IndexConfig`.
     pub async fn create_index(
         &mut self,
@@ -388,6 +392,7 @@ impl IndexService {
             .storage_resolver
             .resolve(&index_config.index_uri)
             .await?;
+        let pinned_splits = PinnedSplits::load(&self.metastore).await?;
 
         let deleted_entries = run_garbage_collect(
             [(index_uid, storage)].into_iter().collect(),
@@ -397,6 +402,7 @@ impl IndexService {
             // marking to be deleted.
             Duration::ZERO,
             dry_run,
+            &pinned_splits,
             None,
             None,
         )
@@ -15,5 +15,5 @@
 mod garbage_collection;
 mod index;
 
-pub use garbage_collection::{GcMetrics, run_garbage_collect};
+pub use garbage_collection::{GcMetrics, PinnedSplits, run_garbage_collect};
 pub use index::{IndexService, IndexServiceError, clear_cache_directory, validate_storage_uri};
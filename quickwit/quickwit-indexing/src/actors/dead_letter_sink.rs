@@ -0,0 +1,206 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, bail, ensure};
+use quickwit_actors::Mailbox;
+use quickwit_config::DeadLetterConfig;
+use quickwit_ingest::{
+    CommitType, CreateQueueIfNotExistsRequest, DocBatchBuilder, IngestApiService, IngestRequest,
+    get_ingest_api_service,
+};
+use quickwit_proto::types::IndexId;
+use quickwit_storage::{Storage, StorageResolver};
+
+use crate::models::{DeadLetter, dead_letters_prefix, serialize_dead_letters};
+
+/// Writes the documents rejected by the doc processor of a source to the dead-letter destination
+/// configured for the source.
+pub(crate) enum DeadLetterSink {
+    /// Dead letters are appended to the ingest API queue of the dead-letter index.
+    Index {
+        index_id: IndexId,
+        ingest_api_service: Mailbox<IngestApiService>,
+    },
+    /// Dead letters are written to the storage as NDJSON files, one file per batch.
+    Storage {
+        storage: Arc<dyn Storage>,
+        prefix: PathBuf,
+    },
+}
+
+impl DeadLetterSink {
+    pub async fn new(
+        index_id: &str,
+        source_id: &str,
+        dead_letter_config: &DeadLetterConfig,
+        queues_dir_path: &Path,
+        storage_resolver: &StorageResolver,
+    ) -> anyhow::Result<Self> {
+        match (
+            &dead_letter_config.index_id,
+            &dead_letter_config.storage_uri,
+        ) {
+            (Some(dead_letter_index_id), None) => {
+                ensure!(
+                    dead_letter_index_id != index_id,
+                    "the dead-letter index of source `{source_id}` cannot be the index of the \
+                     source"
+                );
+                let ingest_api_service = get_ingest_api_service(queues_dir_path)
+                    .await
+                    .context("dead-letter indexes require the ingest API")?;
+                let create_queue_request = CreateQueueIfNotExistsRequest {
+                    queue_id: dead_letter_index_id.clone(),
+                };
+                ingest_api_service.ask_for_res(create_queue_request).await?;
+
+                Ok(DeadLetterSink::Index {
+                    index_id: dead_letter_index_id.clone(),
+                    ingest_api_service,
+                })
+            }
+            (None, Some(storage_uri)) => {
+                let storage = storage_resolver.resolve(storage_uri).await?;
+                Ok(DeadLetterSink::Storage {
+                    storage,
+                    prefix: dead_letters_prefix(index_id, source_id),
+                })
+            }
+            _ => bail!("exactly one of `index_id` or `storage_uri` must be set in `dead_letter`"),
+        }
+    }
+
+    pub async fn write(&self, dead_letters: &[DeadLetter]) -> anyhow::Result<()> {
+        match self {
+            DeadLetterSink::Index {
+                index_id,
+                ingest_api_service,
+            } => {
+                let mut doc_batch_builder = DocBatchBuilder::new(index_id.clone()).json_writer();
+
+                for dead_letter in dead_letters {
+                    doc_batch_builder.ingest_doc(dead_letter)?;
+                }
+                let ingest_request = IngestRequest {
+                    doc_batches: vec![doc_batch_builder.into_inner().build()],
+                    commit: CommitType::Auto.into(),
+                };
+                ingest_api_service
+                    .ask_for_res(ingest_request)
+                    .await
+                    .with_context(|| {
+                        format!("failed to ingest dead letters into index `{index_id}`")
+                    })?;
+            }
+            DeadLetterSink::Storage { storage, prefix } => {
+                let payload = serialize_dead_letters(dead_letters);
+                // ULIDs are sorted by creation time, and so are the files.
+                let filepath = prefix.join(format!("{}.ndjson", ulid::Ulid::new()));
+                storage
+                    .put(&filepath, Box::new(payload))
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "failed to write dead letters to `{}/{}`",
+                            storage.uri(),
+                            filepath.display()
+                        )
+                    })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use quickwit_common::uri::Uri;
+    use quickwit_metastore::checkpoint::SourceCheckpointDelta;
+
+    use super::*;
+    use crate::models::deserialize_dead_letters;
+
+    #[tokio::test]
+    async fn test_dead_letter_sink_storage() {
+        let storage_resolver = StorageResolver::for_test();
+        let dead_letter_config = DeadLetterConfig {
+            index_id: None,
+            storage_uri: Some(Uri::for_test("ram:///dead-letters")),
+        };
+        let dead_letter_sink = DeadLetterSink::new(
+            "test-index",
+            "test-source",
+            &dead_letter_config,
+            Path::new("/unused"),
+            &storage_resolver,
+        )
+        .await
+        .unwrap();
+
+        let dead_letters: Vec<DeadLetter> = (0..2)
+            .map(|doc_id| {
+                DeadLetter::new(
+                    1_700_000_000,
+                    "test-index".to_string(),
+                    "test-source".to_string(),
+                    "doc_mapper_error",
+                    "doc mapper parse error".to_string(),
+                    &SourceCheckpointDelta::from_range(0..2),
+                    Bytes::from(format!("{{\"id\": {doc_id}}}")),
+                )
+            })
+            .collect();
+        dead_letter_sink.write(&dead_letters).await.unwrap();
+
+        let storage = storage_resolver
+            .resolve(&Uri::for_test("ram:///dead-letters"))
+            .await
+            .unwrap();
+        let filepaths = storage
+            .list_files_with_prefix(Path::new("test-index/test-source/"))
+            .await
+            .unwrap();
+        assert_eq!(filepaths.len(), 1);
+
+        let payload = storage.get_all(&filepaths[0]).await.unwrap();
+        let written_dead_letters = deserialize_dead_letters(&payload).unwrap();
+        assert_eq!(written_dead_letters, dead_letters);
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_sink_rejects_own_index() {
+        let dead_letter_config = DeadLetterConfig {
+            index_id: Some("test-index".to_string()),
+            storage_uri: None,
+        };
+        let error = DeadLetterSink::new(
+            "test-index",
+            "test-source",
+            &dead_letter_config,
+            Path::new("/unused"),
+            &StorageResolver::for_test(),
+        )
+        .await
+        .err()
+        .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "the dead-letter index of source `test-source` cannot be the index of the source"
+        );
+    }
+}
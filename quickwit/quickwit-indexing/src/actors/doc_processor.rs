@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::hash::{Hash, Hasher};
 use std::string::FromUtf8Error;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -23,6 +24,7 @@ use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox, Qu
 use quickwit_common::metrics::IntCounter;
 use quickwit_common::rate_limited_tracing::rate_limited_warn;
 use quickwit_common::runtimes::RuntimeType;
+use quickwit_config::merge_policy_config::TimeBucket;
 use quickwit_config::{SourceInputFormat, TransformConfig};
 use quickwit_doc_mapper::{DocMapper, DocParsingError, JsonObject};
 use quickwit_opentelemetry::otlp::{
@@ -32,16 +34,21 @@ use quickwit_opentelemetry::otlp::{
 use quickwit_proto::types::{IndexId, SourceId};
 use serde::Serialize;
 use serde_json::Value as JsonValue;
+use siphasher::sip::SipHasher;
 use tantivy::schema::{Field, Value};
 use tantivy::{DateTime, TantivyDocument};
 use thiserror::Error;
+use time::OffsetDateTime;
 use tokio::runtime::Handle;
 
 #[cfg(feature = "vrl")]
 use super::vrl_processing::*;
 use crate::actors::Indexer;
+use crate::actors::dead_letter_sink::DeadLetterSink;
+use crate::actors::schema_decoder::SchemaDecoder;
 use crate::models::{
-    NewPublishLock, NewPublishToken, ProcessedDoc, ProcessedDocBatch, PublishLock, RawDocBatch,
+    DeadLetter, NewPublishLock, NewPublishToken, ProcessedDoc, ProcessedDocBatch, PublishLock,
+    RawDocBatch,
 };
 
 const PLAIN_TEXT: &str = "plain_text";
@@ -89,11 +96,29 @@ pub enum DocProcessorError {
     OltpLogsParsing(OtlpLogsError),
     #[error("OLTP traces parse error: {0}")]
     OltpTracesParsing(OtlpTracesError),
+    #[error("schema decoding error: {0}")]
+    SchemaDecoding(String),
     #[cfg(feature = "vrl")]
     #[error("VRL transform error: {0}")]
     Transform(VrlTerminate),
 }
 
+impl DocProcessorError {
+    /// Returns the kind of the error, as reported in the doc processor metrics and dead letters.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DocProcessorError::DocMapperParsing(_) => "doc_mapper_error",
+            DocProcessorError::JsonParsing(_) => "json_parse_error",
+            DocProcessorError::OltpLogsParsing(_) | DocProcessorError::OltpTracesParsing(_) => {
+                "otlp_parse_error"
+            }
+            DocProcessorError::SchemaDecoding(_) => "schema_decode_error",
+            #[cfg(feature = "vrl")]
+            DocProcessorError::Transform(_) => "transform_error",
+        }
+    }
+}
+
 impl From<OtlpLogsError> for DocProcessorError {
     fn from(error: OtlpLogsError) -> Self {
         Self::OltpLogsParsing(error)
@@ -145,6 +170,9 @@ fn try_into_vrl_doc(
         | SourceInputFormat::OtlpTracesProtobuf => {
             panic!("OTP logs or traces do not support VRL transforms")
         }
+        SourceInputFormat::Avro | SourceInputFormat::Protobuf => {
+            panic!("Avro and Protobuf records must be decoded into JSON documents beforehand")
+        }
     };
     let vrl_doc = VrlDoc::new(vrl_value, num_bytes);
     Ok(vrl_doc)
@@ -186,6 +214,9 @@ fn try_into_json_docs(
             });
             JsonDocIterator::from(json_doc_result)
         }
+        SourceInputFormat::Avro | SourceInputFormat::Protobuf => {
+            panic!("Avro and Protobuf records must be decoded into JSON documents beforehand")
+        }
     }
 }
 
@@ -318,6 +349,7 @@ pub struct DocProcessorCounters {
     /// - valid documents
     /// - number of docs that could not be parsed.
     /// - number of docs that were not valid json.
+    /// - number of docs that could not be decoded with their Avro or Protobuf schema.
     /// - number of docs that could not be transformed.
     /// - number of docs for which the doc mapper returned an error.
     /// - number of valid docs.
@@ -326,12 +358,16 @@ pub struct DocProcessorCounters {
     pub transform_errors: DocProcessorCounter,
     pub json_parse_errors: DocProcessorCounter,
     pub otlp_parse_errors: DocProcessorCounter,
+    pub schema_decode_errors: DocProcessorCounter,
 
     /// Number of bytes that went through the indexer
     /// during its entire lifetime.
     ///
     /// Includes both valid and invalid documents.
     pub num_bytes_total: AtomicU64,
+
+    /// Number of invalid docs written to the dead-letter sink of the source.
+    pub num_dead_letters: AtomicU64,
 }
 
 impl DocProcessorCounters {
@@ -346,6 +382,10 @@ impl DocProcessorCounters {
             DocProcessorCounter::for_index_and_doc_processor_outcome(&index_id, "json_parse_error");
         let otlp_parse_errors =
             DocProcessorCounter::for_index_and_doc_processor_outcome(&index_id, "otlp_parse_error");
+        let schema_decode_errors = DocProcessorCounter::for_index_and_doc_processor_outcome(
+            &index_id,
+            "schema_decode_error",
+        );
         DocProcessorCounters {
             index_id,
             source_id,
@@ -355,7 +395,9 @@ impl DocProcessorCounters {
             transform_errors,
             json_parse_errors,
             otlp_parse_errors,
+            schema_decode_errors,
             num_bytes_total: Default::default(),
+            num_dead_letters: Default::default(),
         }
     }
 
@@ -365,6 +407,7 @@ impl DocProcessorCounters {
             + self.doc_mapper_errors.get_num_docs()
             + self.json_parse_errors.get_num_docs()
             + self.otlp_parse_errors.get_num_docs()
+            + self.schema_decode_errors.get_num_docs()
             + self.transform_errors.get_num_docs()
     }
 
@@ -375,6 +418,7 @@ impl DocProcessorCounters {
         self.doc_mapper_errors.get_num_docs()
             + self.json_parse_errors.get_num_docs()
             + self.otlp_parse_errors.get_num_docs()
+            + self.schema_decode_errors.get_num_docs()
             + self.transform_errors.get_num_docs()
     }
 
@@ -395,6 +439,9 @@ impl DocProcessorCounters {
             DocProcessorError::OltpLogsParsing(_) | DocProcessorError::OltpTracesParsing(_) => {
                 self.otlp_parse_errors.record_doc(num_bytes);
             }
+            DocProcessorError::SchemaDecoding(_) => {
+                self.schema_decode_errors.record_doc(num_bytes);
+            }
             #[cfg(feature = "vrl")]
             DocProcessorError::Transform(_) => {
                 self.transform_errors.record_doc(num_bytes);
@@ -403,6 +450,13 @@ impl DocProcessorCounters {
     }
 }
 
+/// A raw document rejected by the doc processor, waiting to be written to the dead-letter sink.
+struct RejectedDoc {
+    raw_doc: Bytes,
+    error_kind: &'static str,
+    error_message: String,
+}
+
 pub struct DocProcessor {
     doc_mapper: Arc<DocMapper>,
     indexer_mailbox: Mailbox<Indexer>,
@@ -412,6 +466,9 @@ pub struct DocProcessor {
     #[cfg(feature = "vrl")]
     transform_opt: Option<VrlProgram>,
     input_format: SourceInputFormat,
+    dead_letter_sink_opt: Option<DeadLetterSink>,
+    schema_decoder_opt: Option<SchemaDecoder>,
+    time_bucket_secs_opt: Option<i64>,
 }
 
 impl DocProcessor {
@@ -438,9 +495,28 @@ impl DocProcessor {
                 .map(VrlProgram::try_from_transform_config)
                 .transpose()?,
             input_format,
+            dead_letter_sink_opt: None,
+            schema_decoder_opt: None,
+            time_bucket_secs_opt: None,
         })
     }
 
+    /// Routes the rejected documents to a dead-letter sink instead of dropping them.
+    pub(crate) fn set_dead_letter_sink(&mut self, dead_letter_sink: DeadLetterSink) {
+        self.dead_letter_sink_opt = Some(dead_letter_sink);
+    }
+
+    /// Decodes the Avro or Protobuf records into JSON documents before parsing them.
+    pub(crate) fn set_schema_decoder(&mut self, schema_decoder: SchemaDecoder) {
+        self.schema_decoder_opt = Some(schema_decoder);
+    }
+
+    /// Routes the documents to different partitions, and therefore different splits, depending on
+    /// the time window their timestamp falls in.
+    pub(crate) fn set_time_bucket(&mut self, time_bucket: TimeBucket) {
+        self.time_bucket_secs_opt = Some(time_bucket.duration().as_secs() as i64);
+    }
+
     // Extract a timestamp from a tantivy document.
     //
     // If the timestamp is set up in the docmapper and the timestamp is missing,
@@ -461,15 +537,38 @@ impl DocProcessor {
         Ok(Some(timestamp))
     }
 
-    fn process_raw_doc(&mut self, raw_doc: Bytes, processed_docs: &mut Vec<ProcessedDoc>) {
+    fn process_raw_doc(
+        &mut self,
+        raw_doc: Bytes,
+        processed_docs: &mut Vec<ProcessedDoc>,
+        rejected_docs: &mut Vec<RejectedDoc>,
+    ) {
         let num_bytes = raw_doc.len();
+        // A raw document can yield several documents (e.g. OTLP payloads), but it is only
+        // dead-lettered once, on its first error.
+        let mut raw_doc_for_dead_letter_opt = if self.dead_letter_sink_opt.is_some() {
+            Some(raw_doc.clone())
+        } else {
+            None
+        };
 
         #[cfg(feature = "vrl")]
         let transform_opt = self.transform_opt.as_mut();
         #[cfg(not(feature = "vrl"))]
         let transform_opt: Option<&mut VrlProgram> = None;
 
-        for json_doc_result in parse_raw_doc(self.input_format, raw_doc, num_bytes, transform_opt) {
+        let json_docs = match &self.schema_decoder_opt {
+            Some(schema_decoder) => match schema_decoder.decode(&raw_doc) {
+                Ok(json_doc) => {
+                    parse_raw_doc(SourceInputFormat::Json, json_doc, num_bytes, transform_opt)
+                }
+                Err(error) => {
+                    JsonDocIterator::One(Some(Err(DocProcessorError::SchemaDecoding(error))))
+                }
+            },
+            None => parse_raw_doc(self.input_format, raw_doc, num_bytes, transform_opt),
+        };
+        for json_doc_result in json_docs {
             let processed_doc_result =
                 json_doc_result.and_then(|json_doc| self.process_json_doc(json_doc));
 
@@ -485,6 +584,13 @@ impl DocProcessor {
                         source_id = self.counters.source_id,
                         "{error}",
                     );
+                    if let Some(raw_doc) = raw_doc_for_dead_letter_opt.take() {
+                        rejected_docs.push(RejectedDoc {
+                            raw_doc,
+                            error_kind: error.kind(),
+                            error_message: error.to_string(),
+                        });
+                    }
                     self.counters.record_error(error, num_bytes as u64);
                 }
             }
@@ -494,10 +600,17 @@ impl DocProcessor {
     fn process_json_doc(&self, json_doc: JsonDoc) -> Result<ProcessedDoc, DocProcessorError> {
         let num_bytes = json_doc.num_bytes;
 
-        let (partition, doc) = self
+        let (mut partition, doc) = self
             .doc_mapper
             .doc_from_json_obj(json_doc.json_obj, json_doc.num_bytes as u64)?;
         let timestamp_opt = self.extract_timestamp(&doc)?;
+
+        if let Some(time_bucket_secs) = self.time_bucket_secs_opt
+            && let Some(timestamp) = timestamp_opt
+        {
+            let time_bucket = timestamp.into_timestamp_secs().div_euclid(time_bucket_secs);
+            partition = time_bucketed_partition(partition, time_bucket);
+        }
         Ok(ProcessedDoc {
             doc,
             timestamp_opt,
@@ -507,6 +620,15 @@ impl DocProcessor {
     }
 }
 
+/// Combines the partition computed from the partition key with the time window of the document.
+/// Partition IDs are persisted in the split metadata, so the hash must be stable.
+fn time_bucketed_partition(partition: u64, time_bucket: i64) -> u64 {
+    let mut hasher = SipHasher::new();
+    partition.hash(&mut hasher);
+    time_bucket.hash(&mut hasher);
+    hasher.finish()
+}
+
 fn extract_timestamp_field(doc_mapper: &DocMapper) -> anyhow::Result<Option<Field>> {
     let schema = doc_mapper.schema();
     let Some(timestamp_field_name) = doc_mapper.timestamp_field_name() else {
@@ -573,12 +695,50 @@ impl Handler<RawDocBatch> for DocProcessor {
             return Ok(());
         }
         let mut processed_docs: Vec<ProcessedDoc> = Vec::with_capacity(raw_doc_batch.docs.len());
-
+        let mut rejected_docs: Vec<RejectedDoc> = Vec::new();
+
+        if let Some(schema_decoder) = &mut self.schema_decoder_opt {
+            // On failure, the pipeline restarts from the last published checkpoint.
+            ctx.protect_future(schema_decoder.fetch_schemas(&raw_doc_batch.docs))
+                .await
+                .map_err(|error| {
+                    ActorExitStatus::from(error.context("failed to fetch record schemas"))
+                })?;
+        }
         for raw_doc in raw_doc_batch.docs {
             let _protected_zone_guard = ctx.protect_zone();
-            self.process_raw_doc(raw_doc, &mut processed_docs);
+            self.process_raw_doc(raw_doc, &mut processed_docs, &mut rejected_docs);
             ctx.record_progress();
         }
+        if let Some(dead_letter_sink) = &self.dead_letter_sink_opt
+            && !rejected_docs.is_empty()
+        {
+            let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+            let dead_letters: Vec<DeadLetter> = rejected_docs
+                .into_iter()
+                .map(|rejected_doc| {
+                    DeadLetter::new(
+                        timestamp,
+                        self.counters.index_id.clone(),
+                        self.counters.source_id.clone(),
+                        rejected_doc.error_kind,
+                        rejected_doc.error_message,
+                        &raw_doc_batch.checkpoint_delta,
+                        rejected_doc.raw_doc,
+                    )
+                })
+                .collect();
+            // The batch is only forwarded once its dead letters are written, so that they are
+            // never lost: on failure, the pipeline restarts from the last published checkpoint.
+            ctx.protect_future(dead_letter_sink.write(&dead_letters))
+                .await
+                .map_err(|error| {
+                    ActorExitStatus::from(error.context("failed to write dead letters"))
+                })?;
+            self.counters
+                .num_dead_letters
+                .fetch_add(dead_letters.len() as u64, Ordering::Relaxed);
+        }
         let processed_doc_batch = ProcessedDocBatch::new(
             processed_docs,
             raw_doc_batch.checkpoint_delta,
@@ -622,12 +782,13 @@ impl Handler<NewPublishToken> for DocProcessor {
 
 #[cfg(test)]
 mod tests {
+    use std::path::Path;
     use std::sync::Arc;
 
     use prost::Message;
     use quickwit_actors::Universe;
     use quickwit_common::uri::Uri;
-    use quickwit_config::{SearchSettings, build_doc_mapper};
+    use quickwit_config::{DeadLetterConfig, InputSchemaConfig, SearchSettings, build_doc_mapper};
     use quickwit_doc_mapper::{DocMapper, default_doc_mapper_for_test};
     use quickwit_metastore::checkpoint::SourceCheckpointDelta;
     use quickwit_opentelemetry::otlp::{OtlpGrpcLogsService, OtlpGrpcTracesService};
@@ -637,6 +798,7 @@ mod tests {
     use quickwit_proto::opentelemetry::proto::common::v1::any_value::Value as OtlpAnyValueValue;
     use quickwit_proto::opentelemetry::proto::logs::v1::{LogRecord, ResourceLogs, ScopeLogs};
     use quickwit_proto::opentelemetry::proto::trace::v1::{ResourceSpans, ScopeSpans, Span};
+    use quickwit_storage::StorageResolver;
     use serde_json::Value as JsonValue;
     use tantivy::Document;
     use tantivy::schema::NamedFieldDocument;
@@ -722,6 +884,166 @@ mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_doc_processor_dead_letters() {
+        let universe = Universe::with_accelerated_time();
+        let doc_mapper = Arc::new(default_doc_mapper_for_test());
+        let (indexer_mailbox, indexer_inbox) = universe.create_test_mailbox();
+        let mut doc_processor = DocProcessor::try_new(
+            "my-index".to_string(),
+            "my-source".to_string(),
+            doc_mapper,
+            indexer_mailbox,
+            None,
+            SourceInputFormat::Json,
+        )
+        .unwrap();
+        let storage_resolver = StorageResolver::for_test();
+        let dead_letter_config = DeadLetterConfig {
+            index_id: None,
+            storage_uri: Some(Uri::for_test("ram:///dead-letters")),
+        };
+        let dead_letter_sink = DeadLetterSink::new(
+            "my-index",
+            "my-source",
+            &dead_letter_config,
+            Path::new("/unused"),
+            &storage_resolver,
+        )
+        .await
+        .unwrap();
+        doc_processor.set_dead_letter_sink(dead_letter_sink);
+
+        let (doc_processor_mailbox, doc_processor_handle) =
+            universe.spawn_builder().spawn(doc_processor);
+        doc_processor_mailbox
+            .send_message(RawDocBatch::for_test(
+                &[
+                    br#"{"body": "happy", "timestamp": 1628837062, "response_date": "2021-12-19T16:39:59+00:00", "response_time": 2, "response_payload": "YWJj"}"#, // ok
+                    br#"{"body": "happy"}"#, // missing timestamp
+                    b"{", // invalid json
+                ],
+                0..3,
+            ))
+            .await
+            .unwrap();
+
+        let counters = doc_processor_handle
+            .process_pending_and_observe()
+            .await
+            .state;
+        assert_eq!(counters.valid.get_num_docs(), 1);
+        assert_eq!(counters.num_dead_letters.load(Ordering::Relaxed), 2);
+
+        let processed_doc_batches: Vec<ProcessedDocBatch> = indexer_inbox.drain_for_test_typed();
+        assert_eq!(processed_doc_batches.len(), 1);
+        assert_eq!(processed_doc_batches[0].docs.len(), 1);
+
+        let storage = storage_resolver
+            .resolve(&Uri::for_test("ram:///dead-letters"))
+            .await
+            .unwrap();
+        let filepaths = storage
+            .list_files_with_prefix(Path::new("my-index/my-source/"))
+            .await
+            .unwrap();
+        assert_eq!(filepaths.len(), 1);
+
+        let payload = storage.get_all(&filepaths[0]).await.unwrap();
+        let dead_letters: Vec<DeadLetter> = payload
+            .as_slice()
+            .split(|byte| *byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+        assert_eq!(dead_letters.len(), 2);
+        assert_eq!(dead_letters[0].error_kind, "doc_mapper_error");
+        assert_eq!(dead_letters[0].raw_doc, r#"{"body": "happy"}"#);
+        assert_eq!(dead_letters[1].error_kind, "json_parse_error");
+        assert_eq!(dead_letters[1].raw_doc, "{");
+        // Dead letters carry the checkpoint delta of their batch.
+        let expected_dead_letter = DeadLetter {
+            id: dead_letters[1].id.clone(),
+            ..DeadLetter::new(
+                dead_letters[1].timestamp,
+                "my-index".to_string(),
+                "my-source".to_string(),
+                "json_parse_error",
+                dead_letters[1].error_message.clone(),
+                &processed_doc_batches[0].checkpoint_delta,
+                Bytes::from_static(b"{"),
+            )
+        };
+        assert_eq!(dead_letters[1], expected_dead_letter);
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_doc_processor_avro_input_format() {
+        let avro_schema_str = r#"{
+            "type": "record",
+            "name": "Event",
+            "fields": [
+                {"name": "body", "type": "string"},
+                {"name": "timestamp", "type": "long"}
+            ]
+        }"#;
+        let avro_schema = apache_avro::Schema::parse_str(avro_schema_str).unwrap();
+        let mut avro_record = apache_avro::types::Record::new(&avro_schema).unwrap();
+        avro_record.put("body", "happy");
+        avro_record.put("timestamp", 1628837062i64);
+        let avro_datum = apache_avro::to_avro_datum(&avro_schema, avro_record).unwrap();
+
+        let universe = Universe::with_accelerated_time();
+        let doc_mapper = Arc::new(default_doc_mapper_for_test());
+        let (indexer_mailbox, indexer_inbox) = universe.create_test_mailbox();
+        let mut doc_processor = DocProcessor::try_new(
+            "my-index".to_string(),
+            "my-source".to_string(),
+            doc_mapper.clone(),
+            indexer_mailbox,
+            None,
+            SourceInputFormat::Avro,
+        )
+        .unwrap();
+        let input_schema_config = InputSchemaConfig {
+            schema: Some(avro_schema_str.to_string()),
+            message_name: None,
+            schema_registry_url: None,
+        };
+        let schema_decoder =
+            SchemaDecoder::try_new(SourceInputFormat::Avro, &input_schema_config).unwrap();
+        doc_processor.set_schema_decoder(schema_decoder);
+
+        let (doc_processor_mailbox, doc_processor_handle) =
+            universe.spawn_builder().spawn(doc_processor);
+        doc_processor_mailbox
+            .send_message(RawDocBatch::for_test(
+                &[avro_datum.as_slice(), b"\xff"],
+                0..2,
+            ))
+            .await
+            .unwrap();
+
+        let counters = doc_processor_handle
+            .process_pending_and_observe()
+            .await
+            .state;
+        assert_eq!(counters.valid.get_num_docs(), 1);
+        assert_eq!(counters.schema_decode_errors.get_num_docs(), 1);
+        assert_eq!(counters.num_invalid_docs(), 1);
+
+        let processed_doc_batches: Vec<ProcessedDocBatch> = indexer_inbox.drain_for_test_typed();
+        assert_eq!(processed_doc_batches.len(), 1);
+        let schema = doc_mapper.schema();
+        let NamedFieldDocument(named_field_doc_map) =
+            processed_doc_batches[0].docs[0].doc.to_named_doc(&schema);
+        let doc_json = JsonValue::Object(doc_mapper.doc_to_json(named_field_doc_map).unwrap());
+        assert_eq!(doc_json["body"], "happy");
+        assert_eq!(doc_json["timestamp"], 1628837062);
+        universe.assert_quit().await;
+    }
+
     const DOCMAPPER_WITH_PARTITION_JSON: &str = r#"
         {
             "tag_fields": ["tenant"],
@@ -781,6 +1103,55 @@ mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_doc_processor_time_bucket_partitioning() {
+        let doc_mapper = Arc::new(default_doc_mapper_for_test());
+        let universe = Universe::with_accelerated_time();
+        let (indexer_mailbox, indexer_inbox) = universe.create_test_mailbox();
+        let mut doc_processor = DocProcessor::try_new(
+            "my-index".to_string(),
+            "my-source".to_string(),
+            doc_mapper,
+            indexer_mailbox,
+            None,
+            SourceInputFormat::Json,
+        )
+        .unwrap();
+        doc_processor.set_time_bucket(TimeBucket::Hour);
+        let (doc_processor_mailbox, doc_processor_handle) =
+            universe.spawn_builder().spawn(doc_processor);
+        doc_processor_mailbox
+            .send_message(RawDocBatch::for_test(
+                &[
+                    br#"{"body": "first doc of hour 0", "timestamp": 1700002800}"#,
+                    br#"{"body": "second doc of hour 0", "timestamp": 1700002810}"#,
+                    br#"{"body": "first doc of hour 1", "timestamp": 1700006400}"#,
+                    br#"{"body": "out-of-order doc of hour 0", "timestamp": 1700006399}"#,
+                ],
+                0..4,
+            ))
+            .await
+            .unwrap();
+
+        universe
+            .send_exit_with_success(&doc_processor_mailbox)
+            .await
+            .unwrap();
+        let (exit_status, _) = doc_processor_handle.join().await;
+        assert!(matches!(exit_status, ActorExitStatus::Success));
+        let processed_doc_batches: Vec<ProcessedDocBatch> = indexer_inbox.drain_for_test_typed();
+        assert_eq!(processed_doc_batches.len(), 1);
+        let partition_ids: Vec<u64> = processed_doc_batches[0]
+            .docs
+            .iter()
+            .map(|doc| doc.partition)
+            .collect();
+        assert_eq!(partition_ids[0], partition_ids[1]);
+        assert_eq!(partition_ids[0], partition_ids[3]);
+        assert_ne!(partition_ids[0], partition_ids[2]);
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_doc_processor_forward_publish_lock() {
         let doc_mapper = Arc::new(default_doc_mapper_for_test());
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::collections::hash_map::Entry;
+use std::hash::Hasher;
 use std::num::NonZeroU32;
 use std::ops::RangeInclusive;
 use std::sync::Arc;
@@ -40,7 +41,8 @@ use quickwit_proto::metastore::{
 use quickwit_proto::types::{DocMappingUid, PublishToken};
 use quickwit_query::get_quickwit_fastfield_normalizer_manager;
 use serde::Serialize;
-use tantivy::schema::Schema;
+use siphasher::sip128::{Hasher128, SipHasher13};
+use tantivy::schema::{Field, Schema, Value};
 use tantivy::store::{Compressor, ZstdCompressor};
 use tantivy::tokenizer::TokenizerManager;
 use tantivy::{DateTime, IndexBuilder, IndexSettings};
@@ -59,6 +61,9 @@ use crate::models::{
 // Random partition ID used to gather partitions exceeding the maximum number of partitions.
 const OTHER_PARTITION_ID: u64 = 3264326757911759461u64;
 
+// Memory used by a slot of the doc id hash set: the hash itself and its control byte.
+const DOC_ID_HASH_SLOT_NUM_BYTES: usize = std::mem::size_of::<u128>() + 1;
+
 #[derive(Debug)]
 struct CommitTimeout {
     workbench_id: Ulid,
@@ -93,9 +98,12 @@ struct IndexerState {
     publish_lock: PublishLock,
     publish_token_opt: Option<PublishToken>,
     schema: Schema,
+    id_field_opt: Option<Field>,
     doc_mapping_uid: DocMappingUid,
     tokenizer_manager: TokenizerManager,
     max_num_partitions: NonZeroU32,
+    // Width of the time windows of the time bucketed merge policy, if any.
+    time_bucket_secs_opt: Option<i64>,
     index_settings: IndexSettings,
     cooperative_indexing_opt: Option<CooperativeIndexingCycle>,
 }
@@ -139,12 +147,14 @@ impl IndexerState {
         Ok(indexed_split)
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn get_or_create_indexed_split<'a>(
         &self,
         partition_id: u64,
+        timestamp_opt: Option<DateTime>,
         last_delete_opstamp: u64,
         splits: &'a mut FnvHashMap<u64, IndexedSplitBuilder>,
-        other_split_opt: &'a mut Option<IndexedSplitBuilder>,
+        other_splits: &'a mut FnvHashMap<Option<i64>, IndexedSplitBuilder>,
         counter: &'a mut IndexerCounters,
         ctx: &ActorContext<Indexer>,
     ) -> anyhow::Result<(&'a mut IndexedSplitBuilder, bool)> {
@@ -154,21 +164,31 @@ impl IndexerState {
             Entry::Vacant(vacant_entry) => {
                 if num_splits as u32 >= self.max_num_partitions.get() {
                     // In order to avoid exceeding max_num_partitions, we map the document to the
-                    // `OTHER` special partition.
-                    if other_split_opt.is_none() {
+                    // `OTHER` special partition. With the time bucketed merge policy, there is one
+                    // `OTHER` split per time window so that it can still be merged.
+                    if other_splits.is_empty() {
                         warn!(
                             num_docs_in_workbench = counter.num_docs_in_workbench,
                             max_num_partition = self.max_num_partitions.get(),
                             "Exceeding max_num_partition"
                         );
-                        let new_other_split = self.create_indexed_split_builder(
-                            OTHER_PARTITION_ID,
-                            last_delete_opstamp,
-                            ctx,
-                        )?;
-                        *other_split_opt = Some(new_other_split);
                     }
-                    Ok((other_split_opt.as_mut().unwrap(), true))
+                    let time_bucket_opt = self.time_bucket_secs_opt.zip(timestamp_opt).map(
+                        |(time_bucket_secs, timestamp)| {
+                            timestamp.into_timestamp_secs().div_euclid(time_bucket_secs)
+                        },
+                    );
+                    match other_splits.entry(time_bucket_opt) {
+                        Entry::Occupied(other_split) => Ok((other_split.into_mut(), false)),
+                        Entry::Vacant(vacant_entry) => {
+                            let new_other_split = self.create_indexed_split_builder(
+                                OTHER_PARTITION_ID,
+                                last_delete_opstamp,
+                                ctx,
+                            )?;
+                            Ok((vacant_entry.insert(new_other_split), true))
+                        }
+                    }
                 } else {
                     let indexed_split =
                         self.create_indexed_split_builder(partition_id, last_delete_opstamp, ctx)?;
@@ -228,7 +248,7 @@ impl IndexerState {
             batch_parent_span,
             _indexing_span: indexing_span,
             indexed_splits: FnvHashMap::with_capacity_and_hasher(250, Default::default()),
-            other_indexed_split_opt: None,
+            other_indexed_splits: FnvHashMap::default(),
             checkpoint_delta,
             publish_lock,
             publish_token_opt,
@@ -280,7 +300,7 @@ impl IndexerState {
         let IndexingWorkbench {
             checkpoint_delta,
             indexed_splits,
-            other_indexed_split_opt,
+            other_indexed_splits,
             publish_lock,
             last_delete_opstamp,
             memory_usage,
@@ -306,12 +326,12 @@ impl IndexerState {
                 partition,
                 num_bytes,
             } = doc;
-            counters.num_docs_in_workbench += 1;
             let (indexed_split, split_created) = self.get_or_create_indexed_split(
                 partition,
+                timestamp_opt,
                 *last_delete_opstamp,
                 indexed_splits,
-                other_indexed_split_opt,
+                other_indexed_splits,
                 counters,
                 ctx,
             )?;
@@ -321,6 +341,26 @@ impl IndexerState {
                 // memory usage.
                 memory_usage_delta += mem_usage_before as i64;
             }
+            // Deduplication is best-effort: documents sharing the same id are only detected
+            // within a split, which is enough to make the replay of an ingest retry idempotent.
+            if let Some(id_field) = self.id_field_opt
+                && let Some(doc_id) = doc.get_first(id_field).and_then(|value| value.as_str())
+            {
+                let doc_id_hashes = &mut indexed_split.doc_id_hashes;
+                let capacity_before = doc_id_hashes.capacity();
+                let is_duplicate = !doc_id_hashes.insert(doc_id_hash(doc_id));
+                let capacity_delta = doc_id_hashes.capacity() - capacity_before;
+                memory_usage_delta += (capacity_delta * DOC_ID_HASH_SLOT_NUM_BYTES) as i64;
+
+                if is_duplicate {
+                    crate::metrics::INDEXER_METRICS
+                        .duplicate_docs_total
+                        .with_label_values([self.pipeline_id.index_uid.index_id.as_str()])
+                        .inc();
+                    continue;
+                }
+            }
+            counters.num_docs_in_workbench += 1;
             indexed_split.split_attrs.uncompressed_docs_size_in_bytes += num_bytes as u64;
             indexed_split.split_attrs.num_docs += 1;
             if let Some(timestamp) = timestamp_opt {
@@ -340,6 +380,14 @@ impl IndexerState {
     }
 }
 
+/// Hashes a document id on 128 bits, which makes collisions between distinct ids practically
+/// impossible while keeping the memory footprint of the deduplication set constant per document.
+fn doc_id_hash(doc_id: &str) -> u128 {
+    let mut hasher = SipHasher13::new();
+    hasher.write(doc_id.as_bytes());
+    hasher.finish128().as_u128()
+}
+
 /// A workbench hosts the set of `IndexedSplit` that are being built.
 struct IndexingWorkbench {
     workbench_id: Ulid,
@@ -349,7 +397,8 @@ struct IndexingWorkbench {
     _indexing_span: Span,
 
     indexed_splits: FnvHashMap<u64, IndexedSplitBuilder>,
-    other_indexed_split_opt: Option<IndexedSplitBuilder>,
+    // Splits of the partitions exceeding `max_num_partitions`, by time window.
+    other_indexed_splits: FnvHashMap<Option<i64>, IndexedSplitBuilder>,
 
     checkpoint_delta: IndexCheckpointDelta,
     publish_lock: PublishLock,
@@ -543,6 +592,9 @@ impl Indexer {
         index_serializer_mailbox: Mailbox<IndexSerializer>,
     ) -> Self {
         let schema = doc_mapper.schema();
+        let id_field_opt = doc_mapper
+            .id_field_name()
+            .and_then(|id_field_name| schema.get_field(id_field_name).ok());
         let tokenizer_manager = doc_mapper.tokenizer_manager().clone();
         let docstore_compression = Compressor::Zstd(ZstdCompressor {
             compression_level: Some(indexing_settings.docstore_compression_level),
@@ -552,6 +604,10 @@ impl Indexer {
             docstore_compression,
             docstore_compress_dedicated_thread: true,
         };
+        let time_bucket_secs_opt = indexing_settings
+            .merge_policy
+            .time_bucket()
+            .map(|time_bucket| time_bucket.duration().as_secs() as i64);
         let cooperative_indexing_opt: Option<CooperativeIndexingCycle> =
             cooperative_indexing_permits_opt.map(|cooperative_indexing_permits| {
                 CooperativeIndexingCycle::new(
@@ -569,10 +625,12 @@ impl Indexer {
                 publish_lock: PublishLock::default(),
                 publish_token_opt: None,
                 schema,
+                id_field_opt,
                 doc_mapping_uid: doc_mapper.doc_mapping_uid(),
                 tokenizer_manager: tokenizer_manager.tantivy_manager().clone(),
                 index_settings,
                 max_num_partitions: doc_mapper.max_num_partitions(),
+                time_bucket_secs_opt,
                 cooperative_indexing_opt,
             },
             index_serializer_mailbox,
@@ -631,7 +689,7 @@ impl Indexer {
     ) -> anyhow::Result<()> {
         let Some(IndexingWorkbench {
             indexed_splits,
-            other_indexed_split_opt,
+            other_indexed_splits,
             checkpoint_delta,
             publish_lock,
             publish_token_opt,
@@ -646,9 +704,7 @@ impl Indexer {
 
         let mut splits: Vec<IndexedSplitBuilder> = indexed_splits.into_values().collect();
 
-        if let Some(other_split) = other_indexed_split_opt {
-            splits.push(other_split)
-        }
+        splits.extend(other_indexed_splits.into_values());
 
         // Avoid producing empty split, but still update the checkpoint if it is not empty to avoid
         // reprocessing the same faulty documents.
@@ -707,6 +763,7 @@ mod tests {
     use std::time::Duration;
 
     use quickwit_actors::Universe;
+    use quickwit_config::{MergePolicyConfig, TimeBucket, TimeBucketedMergePolicyConfig};
     use quickwit_doc_mapper::{DocMapper, default_doc_mapper_for_test};
     use quickwit_metastore::checkpoint::SourceCheckpointDelta;
     use quickwit_proto::metastore::{
@@ -1204,6 +1261,89 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_indexer_deduplicates_docs_by_id() -> anyhow::Result<()> {
+        let universe = Universe::with_accelerated_time();
+        let pipeline_id = IndexingPipelineId {
+            index_uid: IndexUid::new_with_random_ulid("test-index"),
+            source_id: "test-source".to_string(),
+            node_id: NodeId::from("test-node"),
+            pipeline_uid: PipelineUid::default(),
+        };
+        let doc_mapper: Arc<DocMapper> = Arc::new(
+            serde_json::from_str::<DocMapper>(
+                r#"{
+                    "id_field": "event_id",
+                    "field_mappings": [
+                        { "name": "event_id", "type": "text", "tokenizer": "raw", "fast": true },
+                        { "name": "body", "type": "text" }
+                    ]
+                }"#,
+            )
+            .unwrap(),
+        );
+        let schema = doc_mapper.schema();
+        let event_id_field = schema.get_field("event_id").unwrap();
+        let body_field = schema.get_field("body").unwrap();
+        let indexing_directory = TempDirectory::for_test();
+        let indexing_settings = IndexingSettings::for_test();
+        let (index_serializer_mailbox, index_serializer_inbox) = universe.create_test_mailbox();
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore
+            .expect_last_delete_opstamp()
+            .once()
+            .returning(move |_last_delete_opstamp_request| Ok(LastDeleteOpstampResponse::new(10)));
+        mock_metastore.expect_publish_splits().never();
+        let indexer = Indexer::new(
+            pipeline_id,
+            doc_mapper,
+            MetastoreServiceClient::from_mock(mock_metastore),
+            indexing_directory,
+            indexing_settings,
+            None,
+            index_serializer_mailbox,
+        );
+        let (indexer_mailbox, indexer_handle) = universe.spawn_builder().spawn(indexer);
+        let processed_doc = |event_id: &str, body: &str| ProcessedDoc {
+            doc: doc!(event_id_field=>event_id, body_field=>body),
+            timestamp_opt: None,
+            partition: 0,
+            num_bytes: 30,
+        };
+        indexer_mailbox
+            .send_message(ProcessedDocBatch::new(
+                vec![
+                    processed_doc("event-1", "first attempt"),
+                    processed_doc("event-2", "first attempt"),
+                ],
+                SourceCheckpointDelta::from_range(0..2),
+                false,
+            ))
+            .await?;
+        indexer_mailbox
+            .send_message(ProcessedDocBatch::new(
+                vec![
+                    processed_doc("event-2", "retry"),
+                    processed_doc("event-3", "first attempt"),
+                ],
+                SourceCheckpointDelta::from_range(2..4),
+                false,
+            ))
+            .await?;
+        let indexer_counters = indexer_handle.process_pending_and_observe().await.state;
+        assert_eq!(indexer_counters.num_docs_in_workbench, 3);
+
+        universe.send_exit_with_success(&indexer_mailbox).await?;
+        let (exit_status, _indexer_counters) = indexer_handle.join().await;
+        assert!(exit_status.is_success());
+        let output_messages: Vec<IndexedSplitBatchBuilder> =
+            index_serializer_inbox.drain_for_test_typed();
+        assert_eq!(output_messages.len(), 1);
+        assert_eq!(output_messages[0].splits[0].split_attrs.num_docs, 3);
+        universe.assert_quit().await;
+        Ok(())
+    }
+
     const DOCMAPPER_WITH_PARTITION_JSON: &str = r#"{
         "tag_fields": ["tenant"],
         "partition_key": "tenant",
@@ -1384,6 +1524,97 @@ mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_indexer_exceeding_max_num_partitions_with_time_buckets() {
+        let universe = Universe::with_accelerated_time();
+        let pipeline_id = IndexingPipelineId {
+            index_uid: IndexUid::new_with_random_ulid("test-index"),
+            source_id: "test-source".to_string(),
+            node_id: NodeId::from("test-node"),
+            pipeline_uid: PipelineUid::default(),
+        };
+        let doc_mapper: Arc<DocMapper> =
+            Arc::new(serde_json::from_str::<DocMapper>(DOCMAPPER_SIMPLE_JSON).unwrap());
+        let body_field = doc_mapper.schema().get_field("body").unwrap();
+        let indexing_directory = TempDirectory::for_test();
+        let mut indexing_settings = IndexingSettings::for_test();
+        indexing_settings.resources.heap_size = ByteSize::gb(5);
+        indexing_settings.merge_policy =
+            MergePolicyConfig::TimeBucketed(TimeBucketedMergePolicyConfig {
+                bucket: TimeBucket::Hour,
+                ..Default::default()
+            });
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore
+            .expect_last_delete_opstamp()
+            .times(1)
+            .returning(move |_last_delete_opstamp_request| Ok(LastDeleteOpstampResponse::new(10)));
+        mock_metastore.expect_publish_splits().never();
+        let (index_serializer_mailbox, index_serializer_inbox) = universe.create_test_mailbox();
+        let indexer = Indexer::new(
+            pipeline_id,
+            doc_mapper,
+            MetastoreServiceClient::from_mock(mock_metastore),
+            indexing_directory,
+            indexing_settings,
+            None,
+            index_serializer_mailbox,
+        );
+        let (indexer_mailbox, indexer_handle) = universe.spawn_builder().spawn(indexer);
+
+        // A backfill of 100 partitions spread over 20 hourly windows.
+        let hour_start = 1_700_002_800;
+        for partition in 0..100 {
+            let timestamp = hour_start + (partition as i64 % 20) * 3600 + 60;
+            indexer_mailbox
+                .send_message(ProcessedDocBatch::new(
+                    vec![ProcessedDoc {
+                        doc: doc!(body_field=>"doc {i}"),
+                        timestamp_opt: Some(DateTime::from_timestamp_secs(timestamp)),
+                        partition,
+                        num_bytes: 30,
+                    }],
+                    SourceCheckpointDelta::from_range(partition..partition + 1),
+                    false,
+                ))
+                .await
+                .unwrap();
+        }
+        universe
+            .send_exit_with_success(&indexer_mailbox)
+            .await
+            .unwrap();
+
+        let (exit_status, _indexer_counters) = indexer_handle.join().await;
+        assert!(matches!(exit_status, ActorExitStatus::Success));
+
+        let index_serializer_msgs: Vec<IndexedSplitBatchBuilder> =
+            index_serializer_inbox.drain_for_test_typed();
+        assert_eq!(index_serializer_msgs.len(), 1);
+        let msg = index_serializer_msgs.into_iter().next().unwrap();
+        // 10 partitions and 1 `OTHER` split for each of the 20 windows.
+        assert_eq!(msg.splits.len(), 30);
+
+        for split in msg.splits {
+            let time_range = split.split_attrs.time_range.clone().unwrap();
+            let start_hour = time_range.start().into_timestamp_secs().div_euclid(3600);
+            let end_hour = time_range.end().into_timestamp_secs().div_euclid(3600);
+            assert_eq!(start_hour, end_hour);
+
+            if split.split_attrs.partition_id == OTHER_PARTITION_ID {
+                let expected_num_docs = if start_hour - hour_start / 3600 < 10 {
+                    4
+                } else {
+                    5
+                };
+                assert_eq!(split.split_attrs.num_docs, expected_num_docs);
+            } else {
+                assert_eq!(split.split_attrs.num_docs, 1);
+            }
+        }
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_indexer_propagates_publish_lock() {
         let universe = Universe::with_accelerated_time();
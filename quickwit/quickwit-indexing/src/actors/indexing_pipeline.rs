@@ -22,6 +22,7 @@ use quickwit_actors::{
     Actor, ActorContext, ActorExitStatus, ActorHandle, HEARTBEAT, Handler, Health, Mailbox,
     QueueCapacity, Supervisable,
 };
+use quickwit_cluster::Cluster;
 use quickwit_common::KillSwitch;
 use quickwit_common::metrics::OwnedGaugeGuard;
 use quickwit_common::pubsub::EventBroker;
@@ -38,9 +39,11 @@ use tracing::{debug, error, info, instrument};
 
 use super::MergePlanner;
 use crate::SplitsUpdateMailbox;
+use crate::actors::dead_letter_sink::DeadLetterSink;
 use crate::actors::doc_processor::DocProcessor;
 use crate::actors::index_serializer::IndexSerializer;
 use crate::actors::publisher::PublisherType;
+use crate::actors::schema_decoder::SchemaDecoder;
 use crate::actors::sequencer::Sequencer;
 use crate::actors::uploader::UploaderType;
 use crate::actors::{Indexer, Packager, Publisher, Uploader};
@@ -387,7 +390,14 @@ impl IndexingPipeline {
 
         // Packager
         let tag_fields = self.params.doc_mapper.tag_named_fields()?;
-        let packager = Packager::new("Packager", tag_fields, uploader_mailbox);
+        let dense_vector_fields = self.params.doc_mapper.dense_vector_fields();
+        let packager = Packager::new(
+            "Packager",
+            tag_fields,
+            dense_vector_fields,
+            self.params.indexing_settings.resources.heap_size,
+            uploader_mailbox,
+        );
         let (packager_mailbox, packager_handle) = ctx
             .spawn_actor()
             .set_kill_switch(self.kill_switch.clone())
@@ -420,7 +430,7 @@ impl IndexingPipeline {
             .set_kill_switch(self.kill_switch.clone())
             .spawn(indexer);
 
-        let doc_processor = DocProcessor::try_new(
+        let mut doc_processor = DocProcessor::try_new(
             index_id.to_string(),
             source_id.to_string(),
             self.params.doc_mapper.clone(),
@@ -428,6 +438,27 @@ impl IndexingPipeline {
             self.params.source_config.transform_config.clone(),
             self.params.source_config.input_format,
         )?;
+        if let Some(dead_letter_config) = &self.params.source_config.dead_letter {
+            let dead_letter_sink = DeadLetterSink::new(
+                index_id,
+                source_id,
+                dead_letter_config,
+                &self.params.queues_dir_path,
+                &self.params.source_storage_resolver,
+            )
+            .await?;
+            doc_processor.set_dead_letter_sink(dead_letter_sink);
+        }
+        if let Some(input_schema_config) = &self.params.source_config.input_schema {
+            let schema_decoder = SchemaDecoder::try_new(
+                self.params.source_config.input_format,
+                input_schema_config,
+            )?;
+            doc_processor.set_schema_decoder(schema_decoder);
+        }
+        if let Some(time_bucket) = self.params.indexing_settings.merge_policy.time_bucket() {
+            doc_processor.set_time_bucket(time_bucket);
+        }
         let (doc_processor_mailbox, doc_processor_handle) = ctx
             .spawn_actor()
             .set_backpressure_micros_counter(
@@ -446,6 +477,7 @@ impl IndexingPipeline {
             storage_resolver: self.params.source_storage_resolver.clone(),
             event_broker: self.params.event_broker.clone(),
             indexing_setting: self.params.indexing_settings.clone(),
+            cluster_opt: self.params.cluster_opt.clone(),
         };
         let source = ctx
             .protect_future(quickwit_supported_sources().load_source(source_runtime))
@@ -598,6 +630,7 @@ pub struct IndexingPipelineParams {
     pub ingester_pool: IngesterPool,
     pub queues_dir_path: PathBuf,
     pub params_fingerprint: u64,
+    pub cluster_opt: Option<Cluster>,
 
     pub event_broker: EventBroker,
 }
@@ -654,6 +687,8 @@ mod tests {
             source_params: SourceParams::file_from_str(test_file).unwrap(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         let source_config_clone = source_config.clone();
 
@@ -729,6 +764,7 @@ mod tests {
             merge_planner_mailbox,
             event_broker: EventBroker::default(),
             params_fingerprint: 42u64,
+            cluster_opt: None,
         };
         let pipeline = IndexingPipeline::new(pipeline_params);
         let (_pipeline_mailbox, pipeline_handle) = universe.spawn_builder().spawn(pipeline);
@@ -775,6 +811,8 @@ mod tests {
             source_params: SourceParams::file_from_str(test_file).unwrap(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         let source_config_clone = source_config.clone();
 
@@ -843,6 +881,7 @@ mod tests {
             merge_planner_mailbox,
             event_broker: Default::default(),
             params_fingerprint: 42u64,
+            cluster_opt: None,
         };
         let pipeline = IndexingPipeline::new(pipeline_params);
         let (_pipeline_mailbox, pipeline_handler) = universe.spawn_builder().spawn(pipeline);
@@ -881,6 +920,8 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         let source_config_clone = source_config.clone();
 
@@ -918,6 +959,7 @@ mod tests {
             max_concurrent_split_uploads: 2,
             merge_io_throughput_limiter_opt: None,
             merge_scheduler_service: universe.get_or_spawn_one(),
+            heap_size: IndexingSettings::for_test().resources.heap_size,
             event_broker: Default::default(),
         };
         let merge_pipeline = MergePipeline::new(merge_pipeline_params, None, universe.spawn_ctx());
@@ -944,6 +986,7 @@ mod tests {
             merge_planner_mailbox: merge_planner_mailbox.clone(),
             event_broker: Default::default(),
             params_fingerprint: 42u64,
+            cluster_opt: None,
         };
         let indexing_pipeline = IndexingPipeline::new(indexing_pipeline_params);
         let (_indexing_pipeline_mailbox, indexing_pipeline_handler) =
@@ -987,6 +1030,8 @@ mod tests {
             source_params: SourceParams::file_from_str(test_file).unwrap(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         let source_config_clone = source_config.clone();
 
@@ -1071,6 +1116,7 @@ mod tests {
             cooperative_indexing_permits: None,
             merge_planner_mailbox,
             params_fingerprint: 42u64,
+            cluster_opt: None,
             event_broker: Default::default(),
         };
         let pipeline = IndexingPipeline::new(pipeline_params);
@@ -309,6 +309,7 @@ impl IndexingService {
             retention_policy: retention_policy.clone(),
             merge_io_throughput_limiter_opt: self.merge_io_throughput_limiter_opt.clone(),
             max_concurrent_split_uploads: self.max_concurrent_split_uploads,
+            heap_size: index_config.indexing_settings.resources.heap_size,
             event_broker: self.event_broker.clone(),
         };
         let merge_planner_mailbox =
@@ -362,6 +363,7 @@ impl IndexingService {
             queues_dir_path: self.queue_dir_path.clone(),
             source_storage_resolver: self.storage_resolver.clone(),
             params_fingerprint,
+            cluster_opt: Some(self.cluster.clone()),
 
             event_broker: self.event_broker.clone(),
         };
@@ -1108,6 +1110,8 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         let spawn_pipeline_msg = SpawnPipeline {
             index_id: index_id.clone(),
@@ -1189,6 +1193,8 @@ mod tests {
             }),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         let create_index_request = CreateIndexRequest::try_from_index_and_source_configs(
             &index_config,
@@ -1276,6 +1282,8 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         {
             // Assign 2 indexing tasks
@@ -1325,6 +1333,8 @@ mod tests {
             source_params: SourceParams::Kafka(kafka_params),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         {
             // Assign 2 more indexing tasks (1 new source + activate ingest API source)
@@ -1532,6 +1542,8 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         let create_index_request =
             CreateIndexRequest::try_from_index_config(&index_config).unwrap();
@@ -1661,6 +1673,8 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         index_metadata
             .sources
@@ -16,6 +16,7 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
+use bytesize::ByteSize;
 use quickwit_actors::{
     Actor, ActorContext, ActorExitStatus, ActorHandle, HEARTBEAT, Handler, Health, Inbox, Mailbox,
     SpawnContext, Supervisable,
@@ -297,7 +298,14 @@ impl MergePipeline {
 
         // Merge Packager
         let tag_fields = self.params.doc_mapper.tag_named_fields()?;
-        let merge_packager = Packager::new("MergePackager", tag_fields, merge_uploader_mailbox);
+        let dense_vector_fields = self.params.doc_mapper.dense_vector_fields();
+        let merge_packager = Packager::new(
+            "MergePackager",
+            tag_fields,
+            dense_vector_fields,
+            self.params.heap_size,
+            merge_uploader_mailbox,
+        );
         let (merge_packager_mailbox, merge_packager_handle) = ctx
             .spawn_actor()
             .set_kill_switch(self.kill_switch.clone())
@@ -574,6 +582,9 @@ pub struct MergePipelineParams {
     pub retention_policy: Option<RetentionPolicy>,
     pub max_concurrent_split_uploads: usize, //< TODO share with the indexing pipeline.
     pub merge_io_throughput_limiter_opt: Option<Limiter>,
+    /// Memory budget of the pipeline, which bounds the memory used to build the dense vector
+    /// indexes of the merged splits.
+    pub heap_size: ByteSize,
     pub event_broker: EventBroker,
 }
 
@@ -582,6 +593,7 @@ mod tests {
     use std::ops::Bound;
     use std::sync::Arc;
 
+    use bytesize::ByteSize;
     use quickwit_actors::{ActorExitStatus, Universe};
     use quickwit_common::ServiceStream;
     use quickwit_common::temp_dir::TempDirectory;
@@ -638,6 +650,7 @@ mod tests {
             retention_policy: None,
             max_concurrent_split_uploads: 2,
             merge_io_throughput_limiter_opt: None,
+            heap_size: ByteSize::mb(16),
             event_broker: Default::default(),
         };
         let pipeline = MergePipeline::new(pipeline_params, None, universe.spawn_ctx());
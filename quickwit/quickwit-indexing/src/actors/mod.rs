@@ -13,6 +13,7 @@
 // limitations under the License.
 
 mod cooperative_indexing;
+mod dead_letter_sink;
 mod doc_processor;
 mod index_serializer;
 mod indexer;
@@ -25,6 +26,7 @@ mod merge_scheduler_service;
 mod merge_split_downloader;
 mod packager;
 mod publisher;
+mod schema_decoder;
 mod sequencer;
 mod uploader;
 #[cfg(feature = "vrl")]
@@ -13,26 +13,32 @@
 // limitations under the License.
 
 use std::collections::BTreeSet;
+use std::fs::File;
 use std::io;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::{Context, bail};
 use async_trait::async_trait;
+use bytesize::ByteSize;
 use fail::fail_point;
 use itertools::Itertools;
 use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox, QueueCapacity};
+use quickwit_common::metrics::{GaugeGuard, MEMORY_METRICS};
 use quickwit_common::runtimes::RuntimeType;
 use quickwit_common::temp_dir::TempDirectory;
-use quickwit_directories::write_hotcache;
-use quickwit_doc_mapper::NamedField;
+use quickwit_directories::write_hotcache_with_sidecar_files;
+use quickwit_doc_mapper::dense_vector_index::{
+    DenseVectorField, DenseVectorIndexBuilder, dense_vector_index_file_path,
+};
 use quickwit_doc_mapper::tag_pruning::append_to_tag_set;
+use quickwit_doc_mapper::{NamedField, decode_dense_vector};
 use quickwit_proto::search::{
     ListFieldType, ListFields, ListFieldsEntryResponse, serialize_split_fields,
 };
 use tantivy::index::FieldMetadata;
 use tantivy::schema::{FieldType, Type};
-use tantivy::{InvertedIndexReader, ReloadPolicy, SegmentMeta};
+use tantivy::{DocAddress, IndexReader, InvertedIndexReader, ReloadPolicy, SegmentMeta};
 use tokio::runtime::Handle;
 use tracing::{debug, info, instrument, warn};
 
@@ -54,6 +60,7 @@ use crate::models::{
 /// This includes the following steps:
 /// - commit: this step is CPU heavy
 /// - identifying the list of tags for the splits, and labelling it accordingly
+/// - building the dense vector indexes
 /// - creating a bundle file
 /// - computing the hotcache
 /// - appending it to the split file.
@@ -65,18 +72,26 @@ pub struct Packager {
     uploader_mailbox: Mailbox<Uploader>,
     /// List of tag fields ([`Vec<NamedField>`]) defined in the index config.
     tag_fields: Vec<NamedField>,
+    /// List of dense vector fields defined in the index config.
+    dense_vector_fields: Vec<DenseVectorField>,
+    /// Memory budget for building the dense vector index of a field.
+    dense_vector_memory_budget: ByteSize,
 }
 
 impl Packager {
     pub fn new(
         actor_name: &'static str,
         tag_fields: Vec<NamedField>,
+        dense_vector_fields: Vec<DenseVectorField>,
+        dense_vector_memory_budget: ByteSize,
         uploader_mailbox: Mailbox<Uploader>,
     ) -> Packager {
         Packager {
             actor_name,
             uploader_mailbox,
             tag_fields,
+            dense_vector_fields,
+            dense_vector_memory_budget,
         }
     }
 
@@ -87,8 +102,14 @@ impl Packager {
     ) -> anyhow::Result<PackagedSplit> {
         let segment_metas = split.index.searchable_segment_metas()?;
         assert_eq!(segment_metas.len(), 1);
-        let packaged_split =
-            create_packaged_split(&segment_metas[..], split, &self.tag_fields, ctx)?;
+        let packaged_split = create_packaged_split(
+            &segment_metas[..],
+            split,
+            &self.tag_fields,
+            &self.dense_vector_fields,
+            self.dense_vector_memory_budget,
+            ctx,
+        )?;
         Ok(packaged_split)
     }
 }
@@ -207,12 +228,81 @@ fn list_split_files(
     Ok(index_files)
 }
 
-fn build_hotcache<W: io::Write>(split_path: &Path, out: &mut W) -> anyhow::Result<()> {
+fn build_hotcache<W: io::Write>(
+    split_path: &Path,
+    sidecar_file_paths: &[PathBuf],
+    out: &mut W,
+) -> anyhow::Result<()> {
     let mmap_directory = tantivy::directory::MmapDirectory::open(split_path)?;
-    write_hotcache(mmap_directory, out)?;
+    write_hotcache_with_sidecar_files(mmap_directory, sidecar_file_paths, out)?;
     Ok(())
 }
 
+/// Builds the dense vector index of a field from the vectors stored in its fast column, and
+/// writes it in the split scratch directory.
+///
+/// The vectors are streamed from the fast column, so the memory used to build the index is bounded
+/// by `memory_budget`. It is accounted for in the in-flight data metrics.
+///
+/// Returns the path of the index file relative to the split scratch directory, or `None` if no
+/// document of the split has a vector.
+fn build_dense_vector_index(
+    dense_vector_field: &DenseVectorField,
+    index_reader: &IndexReader,
+    split_path: &Path,
+    memory_budget: ByteSize,
+) -> anyhow::Result<Option<PathBuf>> {
+    let searcher = index_reader.searcher();
+    let Ok(field) = searcher.schema().get_field(&dense_vector_field.name) else {
+        return Ok(None);
+    };
+    let mut bytes_columns = Vec::new();
+
+    for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+        if let Some(bytes_column) = segment_reader
+            .fast_fields()
+            .bytes(&dense_vector_field.name)?
+        {
+            bytes_columns.push((segment_ord as u32, segment_reader, bytes_column));
+        }
+    }
+    let dense_vector_index_builder =
+        DenseVectorIndexBuilder::new(&dense_vector_field.options, memory_budget.as_u64() as usize);
+    let mut memory_usage_guard =
+        GaugeGuard::from_gauge(&MEMORY_METRICS.in_flight.dense_vector_index);
+    memory_usage_guard.add(dense_vector_index_builder.memory_usage() as i64);
+
+    let for_each_vector = |visit_vector: &mut dyn FnMut(DocAddress, Vec<f32>)| -> io::Result<()> {
+        let mut buffer = Vec::new();
+
+        for (segment_ord, segment_reader, bytes_column) in &bytes_columns {
+            for doc_id in segment_reader.doc_ids_alive() {
+                let Some(term_ord) = bytes_column.term_ords(doc_id).next() else {
+                    continue;
+                };
+                buffer.clear();
+                bytes_column.ord_to_bytes(term_ord, &mut buffer)?;
+
+                if let Some(vector) = decode_dense_vector(&buffer) {
+                    visit_vector(DocAddress::new(*segment_ord, doc_id), vector);
+                }
+            }
+        }
+        Ok(())
+    };
+    let file_path = dense_vector_index_file_path(field);
+    let file_full_path = split_path.join(&file_path);
+    let mut file = File::create(&file_full_path)?;
+    let num_vectors = dense_vector_index_builder.build(for_each_vector, &mut file)?;
+
+    if num_vectors == 0 {
+        drop(file);
+        std::fs::remove_file(&file_full_path)?;
+        return Ok(None);
+    }
+    Ok(Some(file_path))
+}
+
 /// Attempts to exhaustively extract the list of terms in a
 /// field term dictionary.
 ///
@@ -272,15 +362,17 @@ fn create_packaged_split(
     segment_metas: &[SegmentMeta],
     split: IndexedSplit,
     tag_fields: &[NamedField],
+    dense_vector_fields: &[DenseVectorField],
+    dense_vector_memory_budget: ByteSize,
     ctx: &ActorContext<Packager>,
 ) -> anyhow::Result<PackagedSplit> {
     debug!(split_id = split.split_id(), "create-packaged-split");
-    let split_files = list_split_files(segment_metas, &split.split_scratch_directory)?;
+    let mut split_files = list_split_files(segment_metas, &split.split_scratch_directory)?;
 
     // Extracts tag values from inverted indexes only when a field cardinality is less
     // than `MAX_VALUES_PER_TAG_FIELD`.
     debug!(split_id = split.split_id(), tag_fields =? tag_fields, "extract-tags-values");
-    let index_reader = split
+    let index_reader: IndexReader = split
         .index
         .reader_builder()
         .reload_policy(ReloadPolicy::Manual)
@@ -309,9 +401,30 @@ fn create_packaged_split(
 
     ctx.record_progress();
 
+    let split_path = split.split_scratch_directory.path();
+    let mut sidecar_file_paths = Vec::new();
+
+    for dense_vector_field in dense_vector_fields {
+        debug!(
+            split_id = split.split_id(),
+            field = dense_vector_field.name,
+            "build-dense-vector-index"
+        );
+        if let Some(file_path) = build_dense_vector_index(
+            dense_vector_field,
+            &index_reader,
+            split_path,
+            dense_vector_memory_budget,
+        )? {
+            split_files.push(split_path.join(&file_path));
+            sidecar_file_paths.push(file_path);
+        }
+        ctx.record_progress();
+    }
+
     debug!(split_id = split.split_id(), "build-hotcache");
     let mut hotcache_bytes = Vec::new();
-    build_hotcache(split.split_scratch_directory.path(), &mut hotcache_bytes)?;
+    build_hotcache(split_path, &sidecar_file_paths, &mut hotcache_bytes)?;
     ctx.record_progress();
 
     let serialized_split_fields = serialize_field_metadata(&fields_metadata);
@@ -381,6 +494,7 @@ mod tests {
     use std::ops::RangeInclusive;
 
     use quickwit_actors::{ObservationType, Universe};
+    use quickwit_doc_mapper::encode_dense_vector;
     use quickwit_metastore::checkpoint::IndexCheckpointDelta;
     use quickwit_proto::search::{ListFieldsEntryResponse, deserialize_split_fields};
     use quickwit_proto::types::{DocMappingUid, IndexUid, NodeId};
@@ -463,6 +577,7 @@ mod tests {
             schema_builder.add_f64_field("tag_f64", NumericOptions::default().set_indexed());
         let tag_bool =
             schema_builder.add_bool_field("tag_bool", NumericOptions::default().set_indexed());
+        let embedding = schema_builder.add_bytes_field("embedding", FAST);
         let schema = schema_builder.build();
         let index_builder = IndexBuilder::new()
             .settings(IndexSettings::default())
@@ -493,6 +608,7 @@ mod tests {
                     tag_i64 => -42i64,
                     tag_f64 => -42.02f64,
                     tag_bool => true,
+                    embedding => encode_dense_vector(&[num as f32, 1.0]),
                 );
                 index_writer.add_document(doc)?;
                 num_docs += 1;
@@ -567,7 +683,17 @@ mod tests {
                 "tag_str", "tag_many", "tag_u64", "tag_i64", "tag_f64", "tag_bool",
             ],
         );
-        let packager = Packager::new("TestPackager", tag_fields, mailbox);
+        let dense_vector_fields = vec![DenseVectorField {
+            name: "embedding".to_string(),
+            options: serde_json::from_value(serde_json::json!({"dims": 2}))?,
+        }];
+        let packager = Packager::new(
+            "TestPackager",
+            tag_fields,
+            dense_vector_fields,
+            ByteSize::mb(16),
+            mailbox,
+        );
         let (packager_mailbox, packager_handle) = universe.spawn_builder().spawn(packager);
         packager_mailbox
             .send_message(IndexedSplitBatch {
@@ -611,6 +737,11 @@ mod tests {
                     ..=DateTime::from_timestamp_secs(1628203640)
             )
         );
+        let dense_vector_index_path = split
+            .split_scratch_directory
+            .path()
+            .join("dense_vector.8.ivf");
+        assert!(split.split_files.contains(&dense_vector_index_path));
         universe.assert_quit().await;
         Ok(())
     }
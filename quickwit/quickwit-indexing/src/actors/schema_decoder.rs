@@ -0,0 +1,989 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use anyhow::{Context, bail};
+use apache_avro::Schema as AvroSchema;
+use base64::prelude::{BASE64_STANDARD, Engine};
+use bytes::Bytes;
+use prost::Message;
+use prost_reflect::prost_types::FileDescriptorProto;
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, FileDescriptor, MessageDescriptor, SerializeOptions,
+};
+use quickwit_config::{InputSchemaConfig, SourceInputFormat};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use tracing::info;
+
+/// Magic byte prefixing the records framed with the ID of their schema in a schema registry.
+const SCHEMA_REGISTRY_MAGIC_BYTE: u8 = 0;
+
+const SCHEMA_REGISTRY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximum number of schemas referenced by a schema of a schema registry, transitively.
+const MAX_NUM_SCHEMA_REFERENCES: usize = 100;
+
+/// Decodes the Avro or Protobuf records of a source into JSON documents.
+pub(crate) struct SchemaDecoder {
+    input_format: SourceInputFormat,
+    schemas: Schemas,
+}
+
+enum Schemas {
+    /// The schema is inline in the source config and the records are not framed.
+    Inline(Schema),
+    /// The schemas are fetched from a schema registry and cached by ID. Schemas that cannot be
+    /// fetched or parsed are cached as well, along with the reason why.
+    Registry {
+        schema_registry_client: SchemaRegistryClient,
+        schema_cache: HashMap<u32, Result<Schema, String>>,
+    },
+}
+
+enum Schema {
+    /// An Avro schema, along with the named schemas it references.
+    Avro {
+        schema: AvroSchema,
+        referenced_schemas: Vec<AvroSchema>,
+    },
+    /// A Protobuf message descriptor, from an inline schema.
+    ProtobufMessage(MessageDescriptor),
+    /// A Protobuf file descriptor, from a schema registry. The message is identified by the
+    /// message indexes of the record framing.
+    ProtobufFile(FileDescriptor),
+}
+
+impl SchemaDecoder {
+    pub fn try_new(
+        input_format: SourceInputFormat,
+        input_schema_config: &InputSchemaConfig,
+    ) -> anyhow::Result<Self> {
+        let schemas = match (
+            &input_schema_config.schema,
+            &input_schema_config.schema_registry_url,
+        ) {
+            (Some(schema), None) => {
+                let schema = parse_inline_schema(
+                    input_format,
+                    schema,
+                    input_schema_config.message_name.as_deref(),
+                )?;
+                Schemas::Inline(schema)
+            }
+            (None, Some(schema_registry_url)) => {
+                let schema_registry_client = SchemaRegistryClient::try_new(schema_registry_url)?;
+                Schemas::Registry {
+                    schema_registry_client,
+                    schema_cache: HashMap::new(),
+                }
+            }
+            _ => bail!(
+                "exactly one of `schema` or `schema_registry_url` must be set in `input_schema`"
+            ),
+        };
+        Ok(SchemaDecoder {
+            input_format,
+            schemas,
+        })
+    }
+
+    /// Fetches the schemas of the records that are missing from the schema cache. Schemas that do
+    /// not exist or cannot be parsed are cached as invalid, but an error is returned if the
+    /// schema registry cannot be reached.
+    pub async fn fetch_schemas(&mut self, raw_docs: &[Bytes]) -> anyhow::Result<()> {
+        let Schemas::Registry {
+            schema_registry_client,
+            schema_cache,
+        } = &mut self.schemas
+        else {
+            return Ok(());
+        };
+        for raw_doc in raw_docs {
+            let Ok((schema_id, _)) = split_schema_id(raw_doc) else {
+                continue;
+            };
+            if schema_cache.contains_key(&schema_id) {
+                continue;
+            }
+            let schema_result = schema_registry_client
+                .fetch_schema(self.input_format, schema_id)
+                .await?;
+            schema_cache.insert(schema_id, schema_result);
+        }
+        Ok(())
+    }
+
+    /// Decodes a record into a JSON document. With a schema registry, the schema of the record
+    /// must have been fetched beforehand with [`SchemaDecoder::fetch_schemas`].
+    pub fn decode(&self, raw_doc: &[u8]) -> Result<Bytes, String> {
+        match &self.schemas {
+            Schemas::Inline(schema) => decode_record(schema, raw_doc, &[]),
+            Schemas::Registry { schema_cache, .. } => {
+                let (schema_id, payload) = split_schema_id(raw_doc)?;
+                let schema = match schema_cache.get(&schema_id) {
+                    Some(Ok(schema)) => schema,
+                    Some(Err(error)) => return Err(error.clone()),
+                    None => return Err(format!("schema `{schema_id}` was not fetched")),
+                };
+                if self.input_format == SourceInputFormat::Protobuf {
+                    let (message_indexes, payload) = split_message_indexes(payload)?;
+                    decode_record(schema, payload, &message_indexes)
+                } else {
+                    decode_record(schema, payload, &[])
+                }
+            }
+        }
+    }
+}
+
+fn parse_inline_schema(
+    input_format: SourceInputFormat,
+    schema: &str,
+    message_name_opt: Option<&str>,
+) -> anyhow::Result<Schema> {
+    match input_format {
+        SourceInputFormat::Avro => {
+            let avro_schema =
+                AvroSchema::parse_str(schema).context("failed to parse inline Avro schema")?;
+            Ok(Schema::Avro {
+                schema: avro_schema,
+                referenced_schemas: Vec::new(),
+            })
+        }
+        SourceInputFormat::Protobuf => {
+            let file_descriptor_set = BASE64_STANDARD
+                .decode(schema.trim())
+                .context("failed to decode base64 inline Protobuf schema")?;
+            // The well-known types can be imported without being part of the file descriptor set.
+            let mut descriptor_pool = DescriptorPool::global();
+            descriptor_pool
+                .decode_file_descriptor_set(file_descriptor_set.as_slice())
+                .context("failed to parse inline Protobuf schema")?;
+            let message_name =
+                message_name_opt.context("`message_name` must be set in `input_schema`")?;
+            let message_descriptor = descriptor_pool
+                .get_message_by_name(message_name)
+                .with_context(|| {
+                    format!("message `{message_name}` not found in inline Protobuf schema")
+                })?;
+            Ok(Schema::ProtobufMessage(message_descriptor))
+        }
+        _ => bail!("input format `{input_format:?}` does not support schemas"),
+    }
+}
+
+/// Splits a record framed for a schema registry into its schema ID and its payload.
+fn split_schema_id(raw_doc: &[u8]) -> Result<(u32, &[u8]), String> {
+    match raw_doc {
+        [SCHEMA_REGISTRY_MAGIC_BYTE, b0, b1, b2, b3, payload @ ..] => {
+            Ok((u32::from_be_bytes([*b0, *b1, *b2, *b3]), payload))
+        }
+        _ => {
+            Err("record is not framed with a schema registry magic byte and schema ID".to_string())
+        }
+    }
+}
+
+/// Splits the payload of a Protobuf record framed for a schema registry into its message indexes
+/// and the message. The indexes are a zigzag-encoded varint array identifying the message in its
+/// file: the first index is the index of a top-level message, the next ones are the indexes of
+/// nested messages. An empty array stands for the first message of the file.
+fn split_message_indexes(mut payload: &[u8]) -> Result<(Vec<i64>, &[u8]), String> {
+    let num_indexes = decode_zigzag_varint(&mut payload)?;
+
+    if num_indexes == 0 {
+        return Ok((vec![0], payload));
+    }
+    if num_indexes < 0 || num_indexes as usize > payload.len() {
+        return Err(format!("invalid number of message indexes: {num_indexes}"));
+    }
+    let mut message_indexes = Vec::with_capacity(num_indexes as usize);
+
+    for _ in 0..num_indexes {
+        message_indexes.push(decode_zigzag_varint(&mut payload)?);
+    }
+    Ok((message_indexes, payload))
+}
+
+fn decode_zigzag_varint(buffer: &mut &[u8]) -> Result<i64, String> {
+    let value = prost::encoding::decode_varint(buffer)
+        .map_err(|error| format!("invalid message indexes: {error}"))?;
+    Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+}
+
+fn decode_record(
+    schema: &Schema,
+    payload: &[u8],
+    message_indexes: &[i64],
+) -> Result<Bytes, String> {
+    match schema {
+        Schema::Avro {
+            schema,
+            referenced_schemas,
+        } => {
+            let avro_value_result = if referenced_schemas.is_empty() {
+                apache_avro::from_avro_datum(schema, &mut &payload[..], None)
+            } else {
+                let schemata = referenced_schemas.iter().chain([schema]).collect();
+                apache_avro::from_avro_datum_schemata(schema, schemata, &mut &payload[..], None)
+            };
+            let avro_value = avro_value_result
+                .map_err(|error| format!("failed to decode Avro record: {error}"))?;
+            let json_value = JsonValue::try_from(avro_value)
+                .map_err(|error| format!("failed to convert Avro record to JSON: {error}"))?;
+            let json_doc = serde_json::to_vec(&json_value)
+                .map_err(|error| format!("failed to serialize Avro record: {error}"))?;
+            Ok(Bytes::from(json_doc))
+        }
+        Schema::ProtobufMessage(message_descriptor) => {
+            decode_protobuf_message(message_descriptor.clone(), payload)
+        }
+        Schema::ProtobufFile(file_descriptor) => {
+            let message_descriptor =
+                resolve_message(file_descriptor, message_indexes).ok_or_else(|| {
+                    format!(
+                        "message indexes {message_indexes:?} not found in Protobuf schema `{}`",
+                        file_descriptor.name()
+                    )
+                })?;
+            decode_protobuf_message(message_descriptor, payload)
+        }
+    }
+}
+
+fn decode_protobuf_message(
+    message_descriptor: MessageDescriptor,
+    payload: &[u8],
+) -> Result<Bytes, String> {
+    let message = DynamicMessage::decode(message_descriptor, payload)
+        .map_err(|error| format!("failed to decode Protobuf record: {error}"))?;
+    // Fields are serialized with their Protobuf name and default values, so that the documents
+    // match the doc mapping regardless of which fields were set by the producer.
+    let serialize_options = SerializeOptions::new()
+        .stringify_64_bit_integers(false)
+        .use_proto_field_name(true)
+        .skip_default_fields(false);
+    let mut json_doc = Vec::with_capacity(payload.len() * 2);
+    let mut serializer = serde_json::Serializer::new(&mut json_doc);
+    message
+        .serialize_with_options(&mut serializer, &serialize_options)
+        .map_err(|error| format!("failed to serialize Protobuf record: {error}"))?;
+    Ok(Bytes::from(json_doc))
+}
+
+fn resolve_message(
+    file_descriptor: &FileDescriptor,
+    message_indexes: &[i64],
+) -> Option<MessageDescriptor> {
+    let (first_index, nested_indexes) = message_indexes.split_first()?;
+    let mut message_descriptor = file_descriptor
+        .messages()
+        .nth(usize::try_from(*first_index).ok()?)?;
+
+    for nested_index in nested_indexes {
+        message_descriptor = message_descriptor
+            .child_messages()
+            .nth(usize::try_from(*nested_index).ok()?)?;
+    }
+    Some(message_descriptor)
+}
+
+/// A client for the subset of the Confluent schema registry API needed to decode records.
+struct SchemaRegistryClient {
+    client: reqwest::Client,
+    schema_registry_url: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SchemaResponse {
+    /// Absent for Avro schemas.
+    #[serde(default)]
+    schema_type: Option<String>,
+    schema: String,
+    #[serde(default)]
+    references: Vec<SchemaReference>,
+}
+
+/// Reference of a schema to another schema of the schema registry, e.g. a Protobuf import.
+#[derive(Clone, Deserialize)]
+struct SchemaReference {
+    /// Name of the referenced schema: the imported file for Protobuf, the full name of the
+    /// referenced type for Avro.
+    name: String,
+    subject: String,
+    version: i32,
+}
+
+impl SchemaRegistryClient {
+    fn try_new(schema_registry_url: &str) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(SCHEMA_REGISTRY_TIMEOUT)
+            .build()
+            .context("failed to build schema registry client")?;
+        Ok(SchemaRegistryClient {
+            client,
+            schema_registry_url: schema_registry_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    async fn fetch_schema(
+        &self,
+        input_format: SourceInputFormat,
+        schema_id: u32,
+    ) -> anyhow::Result<Result<Schema, String>> {
+        let url = format!("{}/schemas/ids/{schema_id}", self.schema_registry_url);
+        let schema_name = format!("schema `{schema_id}`");
+
+        let Some(schema_response) = self.get_schema(input_format, url, &schema_name).await? else {
+            return Ok(Err(format!(
+                "schema `{schema_id}` not found in schema registry"
+            )));
+        };
+        info!(schema_id, "fetched schema from schema registry");
+
+        let referenced_schemas = match self
+            .fetch_references(input_format, schema_id, &schema_response.references)
+            .await?
+        {
+            Ok(referenced_schemas) => referenced_schemas,
+            Err(error) => return Ok(Err(error)),
+        };
+        Ok(parse_registry_schema(
+            input_format,
+            schema_id,
+            schema_response,
+            referenced_schemas,
+        ))
+    }
+
+    /// Fetches the schemas referenced by a schema, transitively, through the subject versions
+    /// they point to. The Protobuf well-known types are not fetched since they are part of the
+    /// descriptor pool of every Protobuf schema.
+    async fn fetch_references(
+        &self,
+        input_format: SourceInputFormat,
+        schema_id: u32,
+        references: &[SchemaReference],
+    ) -> anyhow::Result<Result<Vec<String>, String>> {
+        let well_known_types = DescriptorPool::global();
+        let mut referenced_schemas = Vec::new();
+        let mut visited_references: HashSet<(String, i32)> = HashSet::new();
+        let mut pending_references = references.to_vec();
+
+        while let Some(reference) = pending_references.pop() {
+            if input_format == SourceInputFormat::Protobuf
+                && well_known_types.get_file_by_name(&reference.name).is_some()
+            {
+                continue;
+            }
+            if !visited_references.insert((reference.subject.clone(), reference.version)) {
+                continue;
+            }
+            if visited_references.len() > MAX_NUM_SCHEMA_REFERENCES {
+                return Ok(Err(format!(
+                    "schema `{schema_id}` has more than {MAX_NUM_SCHEMA_REFERENCES} references"
+                )));
+            }
+            let mut url = reqwest::Url::parse(&self.schema_registry_url)
+                .context("failed to parse schema registry URL")?;
+            url.path_segments_mut()
+                .map_err(|_| anyhow::anyhow!("schema registry URL cannot be a base"))?
+                .pop_if_empty()
+                .extend([
+                    "subjects",
+                    &reference.subject,
+                    "versions",
+                    &reference.version.to_string(),
+                ]);
+            let reference_name = format!(
+                "subject `{}` version `{}`",
+                reference.subject, reference.version
+            );
+            let Some(schema_response) = self
+                .get_schema(input_format, url.to_string(), &reference_name)
+                .await?
+            else {
+                return Ok(Err(format!(
+                    "schema `{schema_id}` references {reference_name}, which was not found in \
+                     schema registry"
+                )));
+            };
+            pending_references.extend(schema_response.references);
+            referenced_schemas.push(schema_response.schema);
+        }
+        Ok(Ok(referenced_schemas))
+    }
+
+    /// Gets a schema from the schema registry, or `None` if it does not exist.
+    async fn get_schema(
+        &self,
+        input_format: SourceInputFormat,
+        mut url: String,
+        schema_name: &str,
+    ) -> anyhow::Result<Option<SchemaResponse>> {
+        if input_format == SourceInputFormat::Protobuf {
+            // Protobuf schemas are returned as `.proto` files unless they are requested as
+            // serialized file descriptors.
+            url.push_str("?format=serialized");
+        }
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .with_context(|| format!("failed to fetch {schema_name} from schema registry"))?;
+        let status = response.status();
+
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            bail!("failed to fetch {schema_name} from schema registry: status {status}");
+        }
+        let schema_response: SchemaResponse = response
+            .json()
+            .await
+            .with_context(|| format!("failed to read {schema_name} from schema registry"))?;
+        Ok(Some(schema_response))
+    }
+}
+
+fn parse_registry_schema(
+    input_format: SourceInputFormat,
+    schema_id: u32,
+    schema_response: SchemaResponse,
+    referenced_schemas: Vec<String>,
+) -> Result<Schema, String> {
+    let schema_type = schema_response.schema_type.as_deref().unwrap_or("AVRO");
+
+    match (input_format, schema_type) {
+        (SourceInputFormat::Avro, "AVRO") if referenced_schemas.is_empty() => {
+            AvroSchema::parse_str(&schema_response.schema)
+                .map(|schema| Schema::Avro {
+                    schema,
+                    referenced_schemas: Vec::new(),
+                })
+                .map_err(|error| format!("failed to parse Avro schema `{schema_id}`: {error}"))
+        }
+        (SourceInputFormat::Avro, "AVRO") => {
+            let schema_strs: Vec<&str> = referenced_schemas
+                .iter()
+                .chain([&schema_response.schema])
+                .map(String::as_str)
+                .collect();
+            let mut referenced_schemas = AvroSchema::parse_list(&schema_strs)
+                .map_err(|error| format!("failed to parse Avro schema `{schema_id}`: {error}"))?;
+            let schema = referenced_schemas
+                .pop()
+                .expect("the list should contain the schema");
+            Ok(Schema::Avro {
+                schema,
+                referenced_schemas,
+            })
+        }
+        (SourceInputFormat::Protobuf, "PROTOBUF") => {
+            let decode_file_descriptor_proto = |schema: &str| {
+                let file_descriptor_proto_bytes =
+                    BASE64_STANDARD.decode(schema).map_err(|error| {
+                        format!("failed to decode Protobuf schema `{schema_id}`: {error}")
+                    })?;
+                FileDescriptorProto::decode(file_descriptor_proto_bytes.as_slice()).map_err(
+                    |error| format!("failed to decode Protobuf schema `{schema_id}`: {error}"),
+                )
+            };
+            let file_descriptor_proto = decode_file_descriptor_proto(&schema_response.schema)?;
+            let file_name = file_descriptor_proto.name().to_string();
+            let mut file_descriptor_protos = referenced_schemas
+                .iter()
+                .map(|referenced_schema| decode_file_descriptor_proto(referenced_schema))
+                .collect::<Result<Vec<FileDescriptorProto>, String>>()?;
+            file_descriptor_protos.push(file_descriptor_proto);
+
+            // The well-known types are seldom registered in schema registries, so the pool
+            // starts with them.
+            let mut descriptor_pool = DescriptorPool::global();
+            descriptor_pool
+                .add_file_descriptor_protos(file_descriptor_protos)
+                .map_err(|error| {
+                    format!("failed to parse Protobuf schema `{schema_id}`: {error}")
+                })?;
+            let file_descriptor = descriptor_pool
+                .get_file_by_name(&file_name)
+                .ok_or_else(|| format!("Protobuf schema `{schema_id}` is empty"))?;
+            Ok(Schema::ProtobufFile(file_descriptor))
+        }
+        _ => Err(format!(
+            "schema `{schema_id}` is of type `{schema_type}`, which does not match input format \
+             `{input_format:?}`"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use apache_avro::types::Record as AvroRecord;
+    use prost::Message;
+    use prost_reflect::Value as ProtobufValue;
+    use prost_types::field_descriptor_proto::{Label, Type};
+    use prost_types::{
+        DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+    };
+    use serde_json::json;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    const AVRO_SCHEMA: &str = r#"{
+        "type": "record",
+        "name": "Event",
+        "fields": [
+            {"name": "body", "type": "string"},
+            {"name": "severity", "type": ["null", "long"], "default": null}
+        ]
+    }"#;
+
+    fn encode_avro_record(body: &str, severity: Option<i64>) -> Vec<u8> {
+        let avro_schema = AvroSchema::parse_str(AVRO_SCHEMA).unwrap();
+        let mut avro_record = AvroRecord::new(&avro_schema).unwrap();
+        avro_record.put("body", body);
+        avro_record.put("severity", severity);
+        apache_avro::to_avro_datum(&avro_schema, avro_record).unwrap()
+    }
+
+    fn protobuf_field(name: &str, number: i32, field_type: Type) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(Label::Optional as i32),
+            r#type: Some(field_type as i32),
+            ..Default::default()
+        }
+    }
+
+    /// Returns a Protobuf file with a `Header` message and an `Event` message nesting an
+    /// `Attribute` message.
+    fn protobuf_file_for_test() -> FileDescriptorProto {
+        FileDescriptorProto {
+            name: Some("event.proto".to_string()),
+            package: Some("test".to_string()),
+            message_type: vec![
+                DescriptorProto {
+                    name: Some("Header".to_string()),
+                    field: vec![protobuf_field("trace_id", 1, Type::String)],
+                    ..Default::default()
+                },
+                DescriptorProto {
+                    name: Some("Event".to_string()),
+                    field: vec![
+                        protobuf_field("body", 1, Type::String),
+                        protobuf_field("severity_number", 2, Type::Int64),
+                    ],
+                    nested_type: vec![DescriptorProto {
+                        name: Some("Attribute".to_string()),
+                        field: vec![protobuf_field("key", 1, Type::String)],
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                },
+            ],
+            syntax: Some("proto3".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn encode_protobuf_event(body: &str, severity_number: i64) -> Vec<u8> {
+        let file_descriptor_set = FileDescriptorSet {
+            file: vec![protobuf_file_for_test()],
+        };
+        let descriptor_pool =
+            DescriptorPool::from_file_descriptor_set(file_descriptor_set).unwrap();
+        let message_descriptor = descriptor_pool.get_message_by_name("test.Event").unwrap();
+        let mut message = DynamicMessage::new(message_descriptor);
+        message.set_field_by_name("body", ProtobufValue::String(body.to_string()));
+        message.set_field_by_name("severity_number", ProtobufValue::I64(severity_number));
+        message.encode_to_vec()
+    }
+
+    fn frame_record(schema_id: u32, message_indexes_opt: Option<&[u8]>, payload: &[u8]) -> Bytes {
+        let mut raw_doc = vec![SCHEMA_REGISTRY_MAGIC_BYTE];
+        raw_doc.extend_from_slice(&schema_id.to_be_bytes());
+
+        if let Some(message_indexes) = message_indexes_opt {
+            raw_doc.extend_from_slice(message_indexes);
+        }
+        raw_doc.extend_from_slice(payload);
+        Bytes::from(raw_doc)
+    }
+
+    fn decode_to_json(schema_decoder: &SchemaDecoder, raw_doc: &[u8]) -> JsonValue {
+        let json_doc = schema_decoder.decode(raw_doc).unwrap();
+        serde_json::from_slice(&json_doc).unwrap()
+    }
+
+    #[test]
+    fn test_schema_decoder_avro_inline() {
+        let input_schema_config = InputSchemaConfig {
+            schema: Some(AVRO_SCHEMA.to_string()),
+            message_name: None,
+            schema_registry_url: None,
+        };
+        let schema_decoder =
+            SchemaDecoder::try_new(SourceInputFormat::Avro, &input_schema_config).unwrap();
+
+        let raw_doc = encode_avro_record("foo", Some(9));
+        assert_eq!(
+            decode_to_json(&schema_decoder, &raw_doc),
+            json!({"body": "foo", "severity": 9})
+        );
+        let raw_doc = encode_avro_record("bar", None);
+        assert_eq!(
+            decode_to_json(&schema_decoder, &raw_doc),
+            json!({"body": "bar", "severity": null})
+        );
+        let error = schema_decoder.decode(&[0x80]).unwrap_err();
+        assert!(error.starts_with("failed to decode Avro record"));
+    }
+
+    #[test]
+    fn test_schema_decoder_avro_invalid_inline_schema() {
+        let input_schema_config = InputSchemaConfig {
+            schema: Some("{\"type\": \"record\"}".to_string()),
+            message_name: None,
+            schema_registry_url: None,
+        };
+        SchemaDecoder::try_new(SourceInputFormat::Avro, &input_schema_config).unwrap_err();
+    }
+
+    #[test]
+    fn test_schema_decoder_protobuf_inline() {
+        let file_descriptor_set = FileDescriptorSet {
+            file: vec![protobuf_file_for_test()],
+        };
+        let mut input_schema_config = InputSchemaConfig {
+            schema: Some(BASE64_STANDARD.encode(file_descriptor_set.encode_to_vec())),
+            message_name: Some("test.Event".to_string()),
+            schema_registry_url: None,
+        };
+        let schema_decoder =
+            SchemaDecoder::try_new(SourceInputFormat::Protobuf, &input_schema_config).unwrap();
+
+        let raw_doc = encode_protobuf_event("foo", 9);
+        assert_eq!(
+            decode_to_json(&schema_decoder, &raw_doc),
+            json!({"body": "foo", "severity_number": 9})
+        );
+        // Default values are serialized too.
+        assert_eq!(
+            decode_to_json(&schema_decoder, &[]),
+            json!({"body": "", "severity_number": 0})
+        );
+        input_schema_config.message_name = Some("test.Unknown".to_string());
+        let error = SchemaDecoder::try_new(SourceInputFormat::Protobuf, &input_schema_config)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "message `test.Unknown` not found in inline Protobuf schema"
+        );
+    }
+
+    #[test]
+    fn test_split_message_indexes() {
+        // An empty array stands for the first message.
+        let (message_indexes, payload) = split_message_indexes(&[0, 42]).unwrap();
+        assert_eq!(message_indexes, [0]);
+        assert_eq!(payload, [42]);
+
+        // Zigzag encoding: 2 -> 1, 4 -> 2.
+        let (message_indexes, payload) = split_message_indexes(&[4, 2, 0, 42]).unwrap();
+        assert_eq!(message_indexes, [1, 0]);
+        assert_eq!(payload, [42]);
+
+        split_message_indexes(&[]).unwrap_err();
+        split_message_indexes(&[1]).unwrap_err();
+        split_message_indexes(&[8, 2]).unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_schema_decoder_avro_registry() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/schemas/ids/1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "schema": AVRO_SCHEMA,
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/schemas/ids/2"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+                "error_code": 40403,
+                "message": "Schema not found",
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let input_schema_config = InputSchemaConfig {
+            schema: None,
+            message_name: None,
+            schema_registry_url: Some(format!("{}/", mock_server.uri())),
+        };
+        let mut schema_decoder =
+            SchemaDecoder::try_new(SourceInputFormat::Avro, &input_schema_config).unwrap();
+
+        let raw_docs = vec![
+            frame_record(1, None, &encode_avro_record("foo", Some(9))),
+            frame_record(1, None, &encode_avro_record("bar", None)),
+            frame_record(2, None, &encode_avro_record("baz", None)),
+            Bytes::from_static(b"{\"body\": \"not framed\"}"),
+        ];
+        schema_decoder.fetch_schemas(&raw_docs).await.unwrap();
+        // Schemas are cached, including the missing ones.
+        schema_decoder.fetch_schemas(&raw_docs).await.unwrap();
+
+        assert_eq!(
+            decode_to_json(&schema_decoder, &raw_docs[0]),
+            json!({"body": "foo", "severity": 9})
+        );
+        assert_eq!(
+            decode_to_json(&schema_decoder, &raw_docs[1]),
+            json!({"body": "bar", "severity": null})
+        );
+        assert_eq!(
+            schema_decoder.decode(&raw_docs[2]).unwrap_err(),
+            "schema `2` not found in schema registry"
+        );
+        assert_eq!(
+            schema_decoder.decode(&raw_docs[3]).unwrap_err(),
+            "record is not framed with a schema registry magic byte and schema ID"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_schema_decoder_protobuf_registry() {
+        let mock_server = MockServer::start().await;
+        let file_descriptor_proto = protobuf_file_for_test().encode_to_vec();
+        Mock::given(method("GET"))
+            .and(path("/schemas/ids/7"))
+            .and(query_param("format", "serialized"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "schemaType": "PROTOBUF",
+                "schema": BASE64_STANDARD.encode(file_descriptor_proto),
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let input_schema_config = InputSchemaConfig {
+            schema: None,
+            message_name: None,
+            schema_registry_url: Some(mock_server.uri()),
+        };
+        let mut schema_decoder =
+            SchemaDecoder::try_new(SourceInputFormat::Protobuf, &input_schema_config).unwrap();
+
+        // `Event` is the second message of the file: message indexes `[1]`.
+        let raw_doc = frame_record(7, Some(&[2, 2]), &encode_protobuf_event("foo", 9));
+        schema_decoder
+            .fetch_schemas(std::slice::from_ref(&raw_doc))
+            .await
+            .unwrap();
+        assert_eq!(
+            decode_to_json(&schema_decoder, &raw_doc),
+            json!({"body": "foo", "severity_number": 9})
+        );
+        // `Header` is the first message of the file: empty message indexes.
+        let raw_doc = frame_record(7, Some(&[0]), &[]);
+        assert_eq!(
+            decode_to_json(&schema_decoder, &raw_doc),
+            json!({"trace_id": ""})
+        );
+        // `Event.Attribute`: message indexes `[1, 0]`.
+        let raw_doc = frame_record(7, Some(&[4, 2, 0]), &[]);
+        assert_eq!(
+            decode_to_json(&schema_decoder, &raw_doc),
+            json!({"key": ""})
+        );
+
+        let raw_doc = frame_record(7, Some(&[2, 6]), &[]);
+        assert_eq!(
+            schema_decoder.decode(&raw_doc).unwrap_err(),
+            "message indexes [3] not found in Protobuf schema `event.proto`"
+        );
+    }
+
+    /// Returns a Protobuf file with a `Resource` message, imported by
+    /// [`protobuf_file_with_imports_for_test`].
+    fn protobuf_imported_file_for_test() -> FileDescriptorProto {
+        FileDescriptorProto {
+            name: Some("resource.proto".to_string()),
+            package: Some("test.common".to_string()),
+            message_type: vec![DescriptorProto {
+                name: Some("Resource".to_string()),
+                field: vec![protobuf_field("service_name", 1, Type::String)],
+                ..Default::default()
+            }],
+            syntax: Some("proto3".to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Returns a Protobuf file with a `Span` message referencing a type of an imported file and a
+    /// well-known type.
+    fn protobuf_file_with_imports_for_test() -> FileDescriptorProto {
+        FileDescriptorProto {
+            name: Some("span.proto".to_string()),
+            package: Some("test".to_string()),
+            dependency: vec![
+                "resource.proto".to_string(),
+                "google/protobuf/timestamp.proto".to_string(),
+            ],
+            message_type: vec![DescriptorProto {
+                name: Some("Span".to_string()),
+                field: vec![
+                    FieldDescriptorProto {
+                        type_name: Some(".test.common.Resource".to_string()),
+                        ..protobuf_field("resource", 1, Type::Message)
+                    },
+                    FieldDescriptorProto {
+                        type_name: Some(".google.protobuf.Timestamp".to_string()),
+                        ..protobuf_field("start_time", 2, Type::Message)
+                    },
+                ],
+                ..Default::default()
+            }],
+            syntax: Some("proto3".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_schema_decoder_protobuf_registry_with_references() {
+        let mock_server = MockServer::start().await;
+        let file_descriptor_proto = protobuf_file_with_imports_for_test().encode_to_vec();
+        Mock::given(method("GET"))
+            .and(path("/schemas/ids/8"))
+            .and(query_param("format", "serialized"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "schemaType": "PROTOBUF",
+                "schema": BASE64_STANDARD.encode(file_descriptor_proto),
+                "references": [
+                    {
+                        "name": "resource.proto",
+                        "subject": "resource.proto",
+                        "version": 3,
+                    },
+                    {
+                        "name": "google/protobuf/timestamp.proto",
+                        "subject": "google/protobuf/timestamp.proto",
+                        "version": 1,
+                    },
+                ],
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let imported_file_descriptor_proto = protobuf_imported_file_for_test().encode_to_vec();
+        Mock::given(method("GET"))
+            .and(path("/subjects/resource.proto/versions/3"))
+            .and(query_param("format", "serialized"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "subject": "resource.proto",
+                "version": 3,
+                "id": 5,
+                "schemaType": "PROTOBUF",
+                "schema": BASE64_STANDARD.encode(imported_file_descriptor_proto),
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let input_schema_config = InputSchemaConfig {
+            schema: None,
+            message_name: None,
+            schema_registry_url: Some(mock_server.uri()),
+        };
+        let mut schema_decoder =
+            SchemaDecoder::try_new(SourceInputFormat::Protobuf, &input_schema_config).unwrap();
+
+        let mut descriptor_pool = DescriptorPool::global();
+        descriptor_pool
+            .add_file_descriptor_protos([
+                protobuf_imported_file_for_test(),
+                protobuf_file_with_imports_for_test(),
+            ])
+            .unwrap();
+        let span_descriptor = descriptor_pool.get_message_by_name("test.Span").unwrap();
+        let mut resource = DynamicMessage::new(
+            descriptor_pool
+                .get_message_by_name("test.common.Resource")
+                .unwrap(),
+        );
+        resource.set_field_by_name("service_name", ProtobufValue::String("api".to_string()));
+        let mut start_time = DynamicMessage::new(
+            descriptor_pool
+                .get_message_by_name("google.protobuf.Timestamp")
+                .unwrap(),
+        );
+        start_time.set_field_by_name("seconds", ProtobufValue::I64(1_700_000_000));
+        let mut span = DynamicMessage::new(span_descriptor);
+        span.set_field_by_name("resource", ProtobufValue::Message(resource));
+        span.set_field_by_name("start_time", ProtobufValue::Message(start_time));
+
+        let raw_doc = frame_record(8, Some(&[0]), &span.encode_to_vec());
+        schema_decoder
+            .fetch_schemas(std::slice::from_ref(&raw_doc))
+            .await
+            .unwrap();
+        assert_eq!(
+            decode_to_json(&schema_decoder, &raw_doc),
+            json!({
+                "resource": {"service_name": "api"},
+                "start_time": "2023-11-14T22:13:20Z",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_schema_decoder_registry_unavailable() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&mock_server)
+            .await;
+
+        let input_schema_config = InputSchemaConfig {
+            schema: None,
+            message_name: None,
+            schema_registry_url: Some(mock_server.uri()),
+        };
+        let mut schema_decoder =
+            SchemaDecoder::try_new(SourceInputFormat::Avro, &input_schema_config).unwrap();
+
+        let raw_doc = frame_record(1, None, &encode_avro_record("foo", None));
+        let error = schema_decoder.fetch_schemas(&[raw_doc]).await.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "failed to fetch schema `1` from schema registry: status 503 Service Unavailable"
+        );
+    }
+}
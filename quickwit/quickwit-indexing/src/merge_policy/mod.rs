@@ -15,6 +15,7 @@
 mod const_write_amplification;
 mod nop_merge_policy;
 mod stable_log_merge_policy;
+mod time_bucketed_merge_policy;
 
 use std::fmt;
 use std::ops::Deref;
@@ -30,6 +31,7 @@ use quickwit_proto::types::SplitId;
 use serde::Serialize;
 pub(crate) use stable_log_merge_policy::StableLogMergePolicy;
 use tantivy::TrackedObject;
+pub(crate) use time_bucketed_merge_policy::TimeBucketedMergePolicy;
 use tracing::{Span, info_span};
 
 use crate::actors::MergePermit;
@@ -184,6 +186,10 @@ pub fn merge_policy_from_settings(settings: &IndexingSettings) -> Arc<dyn MergeP
             let merge_policy = StableLogMergePolicy::new(config, settings.split_num_docs_target);
             Arc::new(merge_policy)
         }
+        MergePolicyConfig::TimeBucketed(config) => {
+            let merge_policy = TimeBucketedMergePolicy::new(config, settings.split_num_docs_target);
+            Arc::new(merge_policy)
+        }
     }
 }
 
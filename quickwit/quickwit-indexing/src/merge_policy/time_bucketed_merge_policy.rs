@@ -0,0 +1,217 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use quickwit_config::merge_policy_config::{
+    ConstWriteAmplificationMergePolicyConfig, TimeBucketedMergePolicyConfig,
+};
+use quickwit_metastore::{SplitMaturity, SplitMetadata};
+
+use super::{ConstWriteAmplificationMergePolicy, MergeOperation, MergePolicy};
+
+/// The `TimeBucketedMergePolicy` only merges splits whose time range falls
+/// within the same time window (UTC hour or day). As a result, the splits of
+/// a mature index never overlap more than one window, which makes time range
+/// pruning and time-based retention very effective.
+///
+/// Splits that span several windows, for instance splits indexed before the
+/// policy was enabled, are never merged. New splits do not span several
+/// windows because the doc processor routes documents to different splits
+/// depending on their window, and the indexer keeps one split per window for
+/// the partitions exceeding `max_num_partitions`.
+///
+/// Within a window, splits are merged by the [`ConstWriteAmplificationMergePolicy`].
+#[derive(Debug, Clone)]
+pub struct TimeBucketedMergePolicy {
+    bucket_secs: i64,
+    inner: ConstWriteAmplificationMergePolicy,
+}
+
+impl TimeBucketedMergePolicy {
+    pub fn new(config: TimeBucketedMergePolicyConfig, split_num_docs_target: usize) -> Self {
+        let inner_config = ConstWriteAmplificationMergePolicyConfig {
+            merge_factor: config.merge_factor,
+            max_merge_factor: config.max_merge_factor,
+            max_merge_ops: config.max_merge_ops,
+            maturation_period: config.maturation_period,
+            max_finalize_merge_operations: 0,
+            max_finalize_split_num_docs: None,
+        };
+        TimeBucketedMergePolicy {
+            bucket_secs: config.bucket.duration().as_secs() as i64,
+            inner: ConstWriteAmplificationMergePolicy::new(inner_config, split_num_docs_target),
+        }
+    }
+
+    /// Returns the window of the split, or `None` if its time range spans several windows or is
+    /// unknown.
+    fn time_bucket(&self, split: &SplitMetadata) -> Option<i64> {
+        let time_range = split.time_range.as_ref()?;
+        let start_bucket = time_range.start().div_euclid(self.bucket_secs);
+        let end_bucket = time_range.end().div_euclid(self.bucket_secs);
+
+        if start_bucket == end_bucket {
+            Some(start_bucket)
+        } else {
+            None
+        }
+    }
+}
+
+impl MergePolicy for TimeBucketedMergePolicy {
+    fn operations(&self, splits: &mut Vec<SplitMetadata>) -> Vec<MergeOperation> {
+        let mut group_by_time_bucket: HashMap<i64, Vec<SplitMetadata>> = HashMap::default();
+        let mut unbucketed_splits = Vec::new();
+
+        for split in splits.drain(..) {
+            if let Some(time_bucket) = self.time_bucket(&split) {
+                group_by_time_bucket
+                    .entry(time_bucket)
+                    .or_default()
+                    .push(split);
+            } else {
+                unbucketed_splits.push(split);
+            }
+        }
+        splits.extend(unbucketed_splits);
+        let mut merge_operations = Vec::new();
+        for splits_in_bucket in group_by_time_bucket.values_mut() {
+            let merge_ops = self.inner.operations(splits_in_bucket);
+            merge_operations.extend(merge_ops);
+            // we readd the splits that are not used in a merge operation into the splits vector.
+            splits.append(splits_in_bucket);
+        }
+        merge_operations
+    }
+
+    fn split_maturity(&self, split_num_docs: usize, split_num_merge_ops: usize) -> SplitMaturity {
+        self.inner
+            .split_maturity(split_num_docs, split_num_merge_ops)
+    }
+
+    #[cfg(test)]
+    fn check_is_valid(&self, merge_op: &MergeOperation, remaining_splits: &[SplitMetadata]) {
+        use std::collections::HashSet;
+        let time_buckets: HashSet<Option<i64>> = merge_op
+            .splits_as_slice()
+            .iter()
+            .map(|split| self.time_bucket(split))
+            .collect();
+        assert_eq!(time_buckets.len(), 1);
+        assert!(time_buckets.into_iter().next().unwrap().is_some());
+        self.inner.check_is_valid(merge_op, remaining_splits);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use quickwit_config::merge_policy_config::TimeBucket;
+    use time::OffsetDateTime;
+
+    use super::*;
+
+    fn time_bucketed_merge_policy_for_test() -> TimeBucketedMergePolicy {
+        let config = TimeBucketedMergePolicyConfig {
+            bucket: TimeBucket::Hour,
+            merge_factor: 3,
+            max_merge_factor: 5,
+            max_merge_ops: 3,
+            maturation_period: Duration::from_secs(3600),
+        };
+        TimeBucketedMergePolicy::new(config, 10_000_000)
+    }
+
+    fn split_for_test(
+        merge_policy: &TimeBucketedMergePolicy,
+        split_id: &str,
+        start_timestamp: i64,
+        end_timestamp: i64,
+    ) -> SplitMetadata {
+        SplitMetadata {
+            split_id: split_id.to_string(),
+            num_docs: 1_000,
+            time_range: Some(start_timestamp..=end_timestamp),
+            create_timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+            maturity: merge_policy.split_maturity(1_000, 0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_time_bucketed_merge_policy_merges_within_bucket() {
+        let merge_policy = time_bucketed_merge_policy_for_test();
+        let hour_start = 1_700_002_800;
+        let mut splits = vec![
+            split_for_test(&merge_policy, "hour-0-a", hour_start, hour_start + 10),
+            split_for_test(&merge_policy, "hour-0-b", hour_start + 20, hour_start + 30),
+            split_for_test(
+                &merge_policy,
+                "hour-1-a",
+                hour_start + 3600,
+                hour_start + 3610,
+            ),
+            split_for_test(
+                &merge_policy,
+                "hour-0-c",
+                hour_start + 3500,
+                hour_start + 3599,
+            ),
+            split_for_test(
+                &merge_policy,
+                "hour-1-b",
+                hour_start + 3700,
+                hour_start + 3710,
+            ),
+        ];
+        let operations = merge_policy.operations(&mut splits);
+        assert_eq!(operations.len(), 1);
+
+        let mut merged_split_ids: Vec<&str> = operations[0]
+            .splits_as_slice()
+            .iter()
+            .map(|split| split.split_id())
+            .collect();
+        merged_split_ids.sort();
+        assert_eq!(merged_split_ids, ["hour-0-a", "hour-0-b", "hour-0-c"]);
+        assert_eq!(splits.len(), 2);
+    }
+
+    #[test]
+    fn test_time_bucketed_merge_policy_ignores_splits_spanning_several_buckets() {
+        let merge_policy = time_bucketed_merge_policy_for_test();
+        let hour_start = 1_700_002_800;
+        let mut splits = vec![
+            split_for_test(&merge_policy, "split-0", hour_start, hour_start + 10),
+            split_for_test(&merge_policy, "split-1", hour_start + 20, hour_start + 30),
+            split_for_test(&merge_policy, "split-2", hour_start + 40, hour_start + 3610),
+        ];
+        let mut split_without_time_range =
+            split_for_test(&merge_policy, "split-3", hour_start, hour_start);
+        split_without_time_range.time_range = None;
+        splits.push(split_without_time_range);
+
+        let operations = merge_policy.operations(&mut splits);
+        assert!(operations.is_empty());
+        assert_eq!(splits.len(), 4);
+    }
+
+    #[test]
+    fn test_time_bucketed_merge_policy_proptest() {
+        let merge_policy = time_bucketed_merge_policy_for_test();
+        crate::merge_policy::tests::proptest_merge_policy(&merge_policy);
+    }
+}
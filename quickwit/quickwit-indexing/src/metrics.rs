@@ -21,6 +21,7 @@ use quickwit_common::metrics::{
 pub struct IndexerMetrics {
     pub processed_docs_total: IntCounterVec<2>,
     pub processed_bytes: IntCounterVec<2>,
+    pub duplicate_docs_total: IntCounterVec<1>,
     pub indexing_pipelines: IntGaugeVec<1>,
     pub backpressure_micros: IntCounterVec<1>,
     pub available_concurrent_upload_permits: IntGaugeVec<1>,
@@ -52,6 +53,14 @@ impl Default for IndexerMetrics {
                 &[],
                 ["index", "docs_processed_status"],
             ),
+            duplicate_docs_total: new_counter_vec(
+                "duplicate_docs_total",
+                "Number of docs dropped by index because a doc with the same id was already \
+                 indexed in the same split",
+                "indexing",
+                &[],
+                ["index"],
+            ),
             indexing_pipelines: new_gauge_vec(
                 "indexing_pipelines",
                 "Number of running indexing pipelines",
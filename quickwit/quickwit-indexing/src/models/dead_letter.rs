@@ -0,0 +1,189 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use base64::prelude::{BASE64_STANDARD, Engine};
+use bytes::Bytes;
+use quickwit_common::is_false;
+use quickwit_metastore::checkpoint::{PartitionDelta, SourceCheckpointDelta};
+use quickwit_proto::types::{IndexId, SourceId};
+use serde::{Deserialize, Serialize};
+
+/// A document rejected by the doc processor, as written to the dead-letter destination of its
+/// source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// Unique ID of the dead letter, a ULID. Replays use it to delete the dead letters they
+    /// replayed from a dead-letter index.
+    pub id: String,
+    /// Unix timestamp, in seconds, at which the document was rejected.
+    pub timestamp: i64,
+    pub index_id: IndexId,
+    pub source_id: SourceId,
+    /// Kind of the error that caused the rejection, e.g. `doc_mapper_error`.
+    pub error_kind: String,
+    pub error_message: String,
+    /// Checkpoint delta of the batch the document belongs to, per partition.
+    pub checkpoint_delta: BTreeMap<String, PartitionDelta>,
+    /// The raw document. Raw documents that are not valid UTF-8 are base64 encoded.
+    pub raw_doc: String,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub raw_doc_base64: bool,
+}
+
+impl DeadLetter {
+    pub fn new(
+        timestamp: i64,
+        index_id: IndexId,
+        source_id: SourceId,
+        error_kind: &str,
+        error_message: String,
+        checkpoint_delta: &SourceCheckpointDelta,
+        raw_doc: Bytes,
+    ) -> Self {
+        let checkpoint_delta = checkpoint_delta
+            .iter()
+            .map(|(partition_id, partition_delta)| (partition_id.to_string(), partition_delta))
+            .collect();
+        let (raw_doc, raw_doc_base64) = match String::from_utf8(raw_doc.to_vec()) {
+            Ok(raw_doc) => (raw_doc, false),
+            Err(_) => (BASE64_STANDARD.encode(&raw_doc), true),
+        };
+        DeadLetter {
+            id: ulid::Ulid::new().to_string(),
+            timestamp,
+            index_id,
+            source_id,
+            error_kind: error_kind.to_string(),
+            error_message,
+            checkpoint_delta,
+            raw_doc,
+            raw_doc_base64,
+        }
+    }
+
+    /// Returns the raw document, as it was received by the doc processor.
+    pub fn raw_doc_bytes(&self) -> anyhow::Result<Bytes> {
+        if !self.raw_doc_base64 {
+            return Ok(Bytes::from(self.raw_doc.clone()));
+        }
+        let raw_doc = BASE64_STANDARD
+            .decode(&self.raw_doc)
+            .context("failed to decode base64 raw document")?;
+        Ok(Bytes::from(raw_doc))
+    }
+}
+
+/// Returns the prefix of the NDJSON files holding the dead letters of a source in a dead-letter
+/// storage.
+pub fn dead_letters_prefix(index_id: &str, source_id: &str) -> PathBuf {
+    PathBuf::from(index_id).join(source_id)
+}
+
+/// Serializes dead letters as an NDJSON payload, the format of the dead-letter files.
+pub fn serialize_dead_letters(dead_letters: &[DeadLetter]) -> Vec<u8> {
+    let mut payload = Vec::new();
+
+    for dead_letter in dead_letters {
+        serde_json::to_writer(&mut payload, dead_letter)
+            .expect("dead letter should be JSON serializable");
+        payload.push(b'\n');
+    }
+    payload
+}
+
+/// Deserializes the dead letters of an NDJSON payload written by [`serialize_dead_letters`].
+pub fn deserialize_dead_letters(payload: &[u8]) -> anyhow::Result<Vec<DeadLetter>> {
+    payload
+        .split(|byte| *byte == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| serde_json::from_slice(line).context("failed to deserialize dead letter"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_proto::types::Position;
+
+    use super::*;
+
+    #[test]
+    fn test_dead_letter_serde() {
+        let checkpoint_delta = SourceCheckpointDelta::from_partition_delta(
+            "partition-0".into(),
+            Position::offset(10u64),
+            Position::offset(20u64),
+        )
+        .unwrap();
+        let dead_letter = DeadLetter::new(
+            1_700_000_000,
+            "test-index".to_string(),
+            "test-source".to_string(),
+            "json_parse_error",
+            "JSON parse error: document is not an object".to_string(),
+            &checkpoint_delta,
+            Bytes::from_static(b"[1, 2]"),
+        );
+        assert!(!dead_letter.raw_doc_base64);
+
+        let dead_letter_json = serde_json::to_value(&dead_letter).unwrap();
+        assert_eq!(
+            dead_letter_json,
+            serde_json::json!({
+                "id": dead_letter.id,
+                "timestamp": 1_700_000_000,
+                "index_id": "test-index",
+                "source_id": "test-source",
+                "error_kind": "json_parse_error",
+                "error_message": "JSON parse error: document is not an object",
+                "checkpoint_delta": {
+                    "partition-0": {
+                        "from": "00000000000000000010",
+                        "to": "00000000000000000020",
+                    }
+                },
+                "raw_doc": "[1, 2]",
+            })
+        );
+        let deserialized_dead_letter: DeadLetter =
+            serde_json::from_value(dead_letter_json).unwrap();
+        assert_eq!(deserialized_dead_letter, dead_letter);
+        assert_eq!(
+            deserialized_dead_letter.raw_doc_bytes().unwrap(),
+            Bytes::from_static(b"[1, 2]")
+        );
+    }
+
+    #[test]
+    fn test_dead_letter_non_utf8_raw_doc() {
+        let dead_letter = DeadLetter::new(
+            1_700_000_000,
+            "test-index".to_string(),
+            "test-source".to_string(),
+            "otlp_parse_error",
+            "OLTP log records parse error".to_string(),
+            &SourceCheckpointDelta::from_range(0..1),
+            Bytes::from_static(&[0xff, 0xfe, 0x00]),
+        );
+        assert!(dead_letter.raw_doc_base64);
+        assert_eq!(dead_letter.raw_doc, "//4A");
+        assert_eq!(
+            dead_letter.raw_doc_bytes().unwrap(),
+            Bytes::from_static(&[0xff, 0xfe, 0x00])
+        );
+    }
+}
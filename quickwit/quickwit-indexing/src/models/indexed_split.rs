@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
 use std::fmt;
 use std::path::Path;
 
@@ -35,6 +36,10 @@ pub struct IndexedSplitBuilder {
     pub index_writer: tantivy::SingleSegmentIndexWriter,
     pub split_scratch_directory: TempDirectory,
     pub controlled_directory_opt: Option<ControlledDirectory>,
+    /// 128-bit hashes of the ids of the documents added to the split, used to deduplicate
+    /// documents when the doc mapping declares an id field. Their memory footprint is accounted
+    /// for by the indexer along with the index writer's.
+    pub doc_id_hashes: HashSet<u128>,
 }
 
 pub struct IndexedSplit {
@@ -114,6 +119,7 @@ impl IndexedSplitBuilder {
             index_writer,
             split_scratch_directory,
             controlled_directory_opt: Some(controlled_directory),
+            doc_id_hashes: HashSet::new(),
         })
     }
 
@@ -14,6 +14,7 @@
 
 #![allow(rustdoc::invalid_html_tags)]
 
+mod dead_letter;
 mod indexed_split;
 mod indexing_service_message;
 mod indexing_statistics;
@@ -28,6 +29,9 @@ mod raw_doc_batch;
 mod shard_positions;
 mod split_attrs;
 
+pub use dead_letter::{
+    DeadLetter, dead_letters_prefix, deserialize_dead_letters, serialize_dead_letters,
+};
 pub use indexed_split::{
     CommitTrigger, EmptySplit, IndexedSplit, IndexedSplitBatch, IndexedSplitBatchBuilder,
     IndexedSplitBuilder,
@@ -186,6 +186,21 @@ impl TypedSourceFactory for FileSourceFactory {
             FileSourceParams::Notifications(quickwit_config::FileSourceNotification::Sqs(_)) => {
                 anyhow::bail!("Quickwit was compiled without the `sqs` feature")
             }
+            #[cfg(feature = "queue-sources")]
+            FileSourceParams::Notifications(quickwit_config::FileSourceNotification::Polling(
+                polling_config,
+            )) => {
+                let coordinator =
+                    QueueCoordinator::try_from_polling_config(polling_config, source_runtime)
+                        .await?;
+                FileSourceState::Notification(Box::new(coordinator))
+            }
+            #[cfg(not(feature = "queue-sources"))]
+            FileSourceParams::Notifications(quickwit_config::FileSourceNotification::Polling(
+                _,
+            )) => {
+                anyhow::bail!("Quickwit was compiled without the `queue-sources` feature")
+            }
         };
 
         Ok(FileSource {
@@ -237,6 +252,8 @@ mod tests {
             source_params: SourceParams::File(params.clone()),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         let index_uid = IndexUid::new_with_random_ulid("test-index");
         let source_runtime = SourceRuntimeBuilder::new(index_uid, source_config).build();
@@ -289,6 +306,8 @@ mod tests {
             source_params: SourceParams::File(params.clone()),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         let index_uid = IndexUid::new_with_random_ulid("test-index");
         let source_runtime = SourceRuntimeBuilder::new(index_uid, source_config).build();
@@ -353,6 +372,8 @@ mod tests {
             source_params: SourceParams::File(params.clone()),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         let partition_id = PartitionId::from(uri.as_str());
         let source_checkpoint_delta = SourceCheckpointDelta::from_partition_delta(
@@ -317,6 +317,8 @@ mod gcp_pubsub_emulator_tests {
             }),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         }
     }
 
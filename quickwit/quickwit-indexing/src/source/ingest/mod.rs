@@ -944,6 +944,7 @@ mod tests {
             storage_resolver: StorageResolver::for_test(),
             event_broker,
             indexing_setting: IndexingSettings::default(),
+            cluster_opt: None,
         };
         let retry_params = RetryParams::no_retries();
         let mut source = IngestSource::try_new(source_runtime, retry_params)
@@ -1148,6 +1149,7 @@ mod tests {
             storage_resolver: StorageResolver::for_test(),
             event_broker,
             indexing_setting: IndexingSettings::default(),
+            cluster_opt: None,
         };
         let retry_params = RetryParams::for_test();
         let mut source = IngestSource::try_new(source_runtime, retry_params)
@@ -1313,6 +1315,7 @@ mod tests {
             storage_resolver: StorageResolver::for_test(),
             event_broker,
             indexing_setting: IndexingSettings::default(),
+            cluster_opt: None,
         };
         let retry_params = RetryParams::for_test();
         let mut source = IngestSource::try_new(source_runtime, retry_params)
@@ -1379,6 +1382,7 @@ mod tests {
             storage_resolver: StorageResolver::for_test(),
             event_broker,
             indexing_setting: IndexingSettings::default(),
+            cluster_opt: None,
         };
         let retry_params = RetryParams::for_test();
         let mut source = IngestSource::try_new(source_runtime, retry_params)
@@ -1613,6 +1617,7 @@ mod tests {
             storage_resolver: StorageResolver::for_test(),
             event_broker,
             indexing_setting: IndexingSettings::default(),
+            cluster_opt: None,
         };
         let retry_params = RetryParams::for_test();
         let mut source = IngestSource::try_new(source_runtime, retry_params)
@@ -1768,6 +1773,7 @@ mod tests {
             storage_resolver: StorageResolver::for_test(),
             event_broker,
             indexing_setting: IndexingSettings::default(),
+            cluster_opt: None,
         };
         let retry_params = RetryParams::for_test();
         let mut source = IngestSource::try_new(source_runtime, retry_params)
@@ -1900,6 +1906,7 @@ mod tests {
             storage_resolver: StorageResolver::for_test(),
             event_broker: event_broker.clone(),
             indexing_setting: IndexingSettings::default(),
+            cluster_opt: None,
         };
         let retry_params = RetryParams::for_test();
         let mut source = IngestSource::try_new(source_runtime, retry_params)
@@ -887,6 +887,8 @@ mod kafka_broker_tests {
             }),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         (source_id, source_config)
     }
@@ -62,6 +62,8 @@ mod ingest_api_source;
 mod kafka_source;
 #[cfg(feature = "kinesis")]
 mod kinesis;
+#[cfg(feature = "nats")]
+mod nats_source;
 #[cfg(feature = "pulsar")]
 mod pulsar_source;
 #[cfg(feature = "queue-sources")]
@@ -85,12 +87,15 @@ pub use gcp_pubsub_source::{GcpPubSubSource, GcpPubSubSourceFactory};
 pub use kafka_source::{KafkaSource, KafkaSourceFactory};
 #[cfg(feature = "kinesis")]
 pub use kinesis::kinesis_source::{KinesisSource, KinesisSourceFactory};
+#[cfg(feature = "nats")]
+pub use nats_source::{NatsSource, NatsSourceFactory};
 use once_cell::sync::{Lazy, OnceCell};
 #[cfg(feature = "pulsar")]
 pub use pulsar_source::{PulsarSource, PulsarSourceFactory};
 #[cfg(feature = "sqs")]
 pub use queue_sources::sqs_queue;
 use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox};
+use quickwit_cluster::Cluster;
 use quickwit_common::metrics::{GaugeGuard, MEMORY_METRICS};
 use quickwit_common::pubsub::EventBroker;
 use quickwit_common::runtimes::RuntimeType;
@@ -160,6 +165,7 @@ pub struct SourceRuntime {
     pub storage_resolver: StorageResolver,
     pub event_broker: EventBroker,
     pub indexing_setting: IndexingSettings,
+    pub cluster_opt: Option<Cluster>,
 }
 
 impl SourceRuntime {
@@ -183,6 +189,27 @@ impl SourceRuntime {
         self.pipeline_id.pipeline_uid
     }
 
+    /// Returns the UIDs of the pipelines of the source running in the cluster, including this
+    /// one, in sorted order.
+    pub async fn source_pipeline_uids(&self) -> Vec<PipelineUid> {
+        let mut pipeline_uids = vec![self.pipeline_uid()];
+
+        if let Some(cluster) = &self.cluster_opt {
+            for member in cluster.ready_members().await {
+                for indexing_task in &member.indexing_tasks {
+                    if indexing_task.index_uid() == self.index_uid()
+                        && indexing_task.source_id == self.source_id()
+                    {
+                        pipeline_uids.push(indexing_task.pipeline_uid());
+                    }
+                }
+            }
+        }
+        pipeline_uids.sort();
+        pipeline_uids.dedup();
+        pipeline_uids
+    }
+
     pub async fn fetch_checkpoint(&self) -> MetastoreResult<SourceCheckpoint> {
         let index_uid = self.index_uid().clone();
         let request = IndexMetadataRequest::for_index_uid(index_uid);
@@ -414,6 +441,8 @@ pub fn quickwit_supported_sources() -> &'static SourceLoader {
         source_factory.add_source(SourceType::Kafka, KafkaSourceFactory);
         #[cfg(feature = "kinesis")]
         source_factory.add_source(SourceType::Kinesis, KinesisSourceFactory);
+        #[cfg(feature = "nats")]
+        source_factory.add_source(SourceType::Nats, NatsSourceFactory);
         #[cfg(feature = "pulsar")]
         source_factory.add_source(SourceType::Pulsar, PulsarSourceFactory);
         source_factory.add_source(SourceType::Stdin, StdinSourceFactory);
@@ -447,6 +476,13 @@ pub async fn check_source_connectivity(
                 Ok(())
             }
         }
+        SourceParams::File(FileSourceParams::Notifications(FileSourceNotification::Polling(
+            polling_config,
+        ))) => {
+            let storage = storage_resolver.resolve(&polling_config.uri).await?;
+            storage.check_connectivity().await?;
+            Ok(())
+        }
         #[allow(unused_variables)]
         SourceParams::Kafka(params) => {
             #[cfg(not(feature = "kafka"))]
@@ -470,6 +506,17 @@ pub async fn check_source_connectivity(
             }
         }
         #[allow(unused_variables)]
+        SourceParams::Nats(params) => {
+            #[cfg(not(feature = "nats"))]
+            anyhow::bail!("Quickwit was compiled without the `nats` feature");
+
+            #[cfg(feature = "nats")]
+            {
+                nats_source::check_connectivity(params).await?;
+                Ok(())
+            }
+        }
+        #[allow(unused_variables)]
         SourceParams::Pulsar(params) => {
             #[cfg(not(feature = "pulsar"))]
             anyhow::bail!("Quickwit was compiled without the `pulsar` feature");
@@ -528,6 +575,7 @@ impl BatchBuilder {
             SourceType::IngestV2 => MEMORY_METRICS.in_flight.ingest(),
             SourceType::Kafka => MEMORY_METRICS.in_flight.kafka(),
             SourceType::Kinesis => MEMORY_METRICS.in_flight.kinesis(),
+            SourceType::Nats => MEMORY_METRICS.in_flight.nats(),
             SourceType::PubSub => MEMORY_METRICS.in_flight.pubsub(),
             SourceType::Pulsar => MEMORY_METRICS.in_flight.pulsar(),
             _ => MEMORY_METRICS.in_flight.other(),
@@ -585,6 +633,8 @@ mod tests {
         source_config: SourceConfig,
         metastore_opt: Option<MetastoreServiceClient>,
         queues_dir_path_opt: Option<PathBuf>,
+        pipeline_uid: PipelineUid,
+        cluster_opt: Option<Cluster>,
     }
 
     impl SourceRuntimeBuilder {
@@ -594,6 +644,8 @@ mod tests {
                 source_config,
                 metastore_opt: None,
                 queues_dir_path_opt: None,
+                pipeline_uid: PipelineUid::for_test(0u128),
+                cluster_opt: None,
             }
         }
 
@@ -612,7 +664,7 @@ mod tests {
                     node_id: NodeId::from("test-node"),
                     index_uid: self.index_uid,
                     source_id: self.source_config.source_id.clone(),
-                    pipeline_uid: PipelineUid::for_test(0u128),
+                    pipeline_uid: self.pipeline_uid,
                 },
                 metastore,
                 ingester_pool: IngesterPool::default(),
@@ -621,6 +673,7 @@ mod tests {
                 storage_resolver: StorageResolver::for_test(),
                 event_broker: EventBroker::default(),
                 indexing_setting: IndexingSettings::default(),
+                cluster_opt: self.cluster_opt,
             }
         }
 
@@ -633,6 +686,18 @@ mod tests {
             self
         }
 
+        #[cfg(all(test, feature = "nats-broker-tests"))]
+        pub fn with_pipeline_uid(mut self, pipeline_uid: PipelineUid) -> Self {
+            self.pipeline_uid = pipeline_uid;
+            self
+        }
+
+        #[cfg(all(test, feature = "nats-broker-tests"))]
+        pub fn with_cluster(mut self, cluster: Cluster) -> Self {
+            self.cluster_opt = Some(cluster);
+            self
+        }
+
         pub fn with_mock_metastore(
             mut self,
             source_checkpoint_delta_opt: Option<SourceCheckpointDelta>,
@@ -690,6 +755,8 @@ mod tests {
                 source_params: SourceParams::void(),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                dead_letter: None,
+                input_schema: None,
             };
             check_source_connectivity(&StorageResolver::for_test(), &source_config).await?;
         }
@@ -701,6 +768,8 @@ mod tests {
                 source_params: SourceParams::Vec(VecSourceParams::default()),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                dead_letter: None,
+                input_schema: None,
             };
             check_source_connectivity(&StorageResolver::for_test(), &source_config).await?;
         }
@@ -712,6 +781,8 @@ mod tests {
                 source_params: SourceParams::file_from_str("file-does-not-exist.json").unwrap(),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                dead_letter: None,
+                input_schema: None,
             };
             assert!(
                 check_source_connectivity(&StorageResolver::for_test(), &source_config)
@@ -727,6 +798,8 @@ mod tests {
                 source_params: SourceParams::file_from_str("data/test_corpus.json").unwrap(),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                dead_letter: None,
+                input_schema: None,
             };
             assert!(
                 check_source_connectivity(&StorageResolver::for_test(), &source_config)
@@ -0,0 +1,957 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, anyhow};
+use async_nats::jetstream::consumer::pull::Config as PullConsumerConfig;
+use async_nats::jetstream::consumer::{AckPolicy, DeliverPolicy, PullConsumer};
+use async_nats::jetstream::message::Acker;
+use async_nats::jetstream::stream::Stream as JetStream;
+use async_nats::jetstream::{self, Message};
+use async_nats::{ConnectOptions, ServerAddr};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::StreamExt;
+use futures::stream::{self, AbortHandle, BoxStream, SelectAll};
+use quickwit_actors::{ActorContext, ActorExitStatus, Mailbox};
+use quickwit_common::rendezvous_hasher::node_affinity;
+use quickwit_config::{NatsSourceAuth, NatsSourceParams};
+use quickwit_metastore::checkpoint::{PartitionId, SourceCheckpoint};
+use quickwit_proto::metastore::SourceType;
+use quickwit_proto::types::{PipelineUid, Position};
+use serde_json::{Value as JsonValue, json};
+use tokio::time;
+use tracing::{debug, info, warn};
+
+use crate::actors::DocProcessor;
+use crate::source::{
+    BATCH_NUM_BYTES_LIMIT, BatchBuilder, EMIT_BATCHES_TIMEOUT, Source, SourceActor, SourceContext,
+    SourceRuntime, TypedSourceFactory,
+};
+
+/// Interval at which a pipeline checks which partitions of the source are assigned to it.
+const ASSIGNMENT_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Time after which the NATS server deletes the consumer of a pipeline that stopped pulling
+/// messages, for instance because the pipeline was killed.
+const CONSUMER_INACTIVE_THRESHOLD: Duration = Duration::from_secs(3_600);
+
+/// Minimum time the NATS server waits for an acknowledgment before redelivering a message.
+const MIN_ACK_WAIT: Duration = Duration::from_secs(60);
+
+type NatsMessageStream = SelectAll<BoxStream<'static, (PartitionId, anyhow::Result<Message>)>>;
+
+pub struct NatsSourceFactory;
+
+#[async_trait]
+impl TypedSourceFactory for NatsSourceFactory {
+    type Source = NatsSource;
+    type Params = NatsSourceParams;
+
+    async fn typed_create_source(
+        source_runtime: SourceRuntime,
+        source_params: NatsSourceParams,
+    ) -> anyhow::Result<Self::Source> {
+        NatsSource::try_new(source_runtime, source_params).await
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct NatsSourceState {
+    /// Number of bytes processed by the source.
+    pub num_bytes_processed: u64,
+    /// Number of messages processed by the source (including invalid messages).
+    pub num_messages_processed: u64,
+    /// Number of invalid messages, i.e., that were empty or could not be parsed.
+    pub num_invalid_messages: u64,
+    /// The number of messages that were skipped due to the message being older
+    /// than the current checkpoint position
+    pub num_skipped_messages: u64,
+}
+
+/// Consumes the partitions of a NATS source assigned to this pipeline.
+///
+/// Each partition is assigned to the pipeline of the source with the highest rendezvous hash
+/// affinity for it. The pipelines of the source are discovered through the cluster, and the
+/// assignment is refreshed periodically, so the partitions are rebalanced when pipelines come and
+/// go. Each pipeline consumes its partitions with its own durable consumers, so it never deletes
+/// a consumer used by another pipeline. While the pipelines have different views of the cluster,
+/// ownership of a partition is arbitrated by the checkpoint of the source.
+pub struct NatsSource {
+    source_runtime: SourceRuntime,
+    source_params: NatsSourceParams,
+    stream: JetStream,
+    /// Partitions of the source, i.e. subjects of the stream, or the stream itself when no
+    /// subjects are configured.
+    partitions: Vec<PartitionId>,
+    /// Partitions consumed by this pipeline, with the handles stopping their message streams.
+    assigned_partitions: BTreeMap<PartitionId, AbortHandle>,
+    next_assignment_refresh_at: Instant,
+    ack_wait: Duration,
+    messages: NatsMessageStream,
+    current_positions: BTreeMap<PartitionId, Position>,
+    /// Ackers of the last message of each emitted batch, per partition and stream sequence. They
+    /// are used to acknowledge the messages once their checkpoint is published.
+    pending_acks: BTreeMap<PartitionId, BTreeMap<u64, Acker>>,
+    state: NatsSourceState,
+}
+
+impl fmt::Debug for NatsSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NatsSource")
+            .field("index_uid", self.source_runtime.index_uid())
+            .field("source_id", &self.source_runtime.source_id())
+            .field("stream", &self.source_params.stream)
+            .field("partitions", &self.assigned_partitions.keys())
+            .finish()
+    }
+}
+
+impl NatsSource {
+    pub async fn try_new(
+        source_runtime: SourceRuntime,
+        source_params: NatsSourceParams,
+    ) -> anyhow::Result<Self> {
+        info!(
+            index_id=%source_runtime.index_id(),
+            source_id=%source_runtime.source_id(),
+            stream=%source_params.stream,
+            subjects=?source_params.subjects,
+            "Create NATS source."
+        );
+        let jetstream = connect_jetstream(&source_params).await?;
+        let stream = jetstream
+            .get_stream(&source_params.stream)
+            .await
+            .with_context(|| format!("failed to get NATS stream `{}`", source_params.stream))?;
+
+        let partitions = partitions(&source_params);
+        let ack_wait =
+            Duration::from_secs(3 * source_runtime.indexing_setting.commit_timeout_secs as u64)
+                .max(MIN_ACK_WAIT);
+
+        let mut nats_source = Self {
+            source_runtime,
+            source_params,
+            stream,
+            partitions,
+            assigned_partitions: BTreeMap::new(),
+            next_assignment_refresh_at: Instant::now(),
+            ack_wait,
+            messages: SelectAll::new(),
+            current_positions: BTreeMap::new(),
+            pending_acks: BTreeMap::new(),
+            state: NatsSourceState::default(),
+        };
+        nats_source.refresh_assignment().await?;
+        Ok(nats_source)
+    }
+
+    /// Starts consuming the partitions newly assigned to this pipeline and stops consuming the
+    /// partitions assigned to other pipelines.
+    async fn refresh_assignment(&mut self) -> anyhow::Result<()> {
+        let pipeline_uids = self.source_runtime.source_pipeline_uids().await;
+        let pipeline_uid = self.source_runtime.pipeline_uid();
+
+        let mut acquired_partition_idxs = Vec::new();
+        let mut released_partition_idxs = Vec::new();
+
+        for (partition_idx, partition_id) in self.partitions.iter().enumerate() {
+            let is_assigned = is_assigned_to(partition_id, &pipeline_uids, pipeline_uid);
+            let is_consumed = self.assigned_partitions.contains_key(partition_id);
+
+            if is_assigned && !is_consumed {
+                acquired_partition_idxs.push(partition_idx);
+            } else if !is_assigned && is_consumed {
+                released_partition_idxs.push(partition_idx);
+            }
+        }
+        for partition_idx in released_partition_idxs {
+            info!(
+                partition=%self.partitions[partition_idx],
+                "partition was reassigned to another pipeline"
+            );
+            self.stop_consuming(partition_idx).await;
+        }
+        if !acquired_partition_idxs.is_empty() {
+            let checkpoint = self.source_runtime.fetch_checkpoint().await?;
+
+            for partition_idx in acquired_partition_idxs {
+                self.start_consuming(partition_idx, &checkpoint).await?;
+            }
+        }
+        self.next_assignment_refresh_at = Instant::now() + ASSIGNMENT_REFRESH_INTERVAL;
+        Ok(())
+    }
+
+    async fn start_consuming(
+        &mut self,
+        partition_idx: usize,
+        checkpoint: &SourceCheckpoint,
+    ) -> anyhow::Result<()> {
+        let partition_id = self.partitions[partition_idx].clone();
+        let position_opt = checkpoint.position_for_partition(&partition_id).cloned();
+
+        let durable_name = durable_consumer_name(
+            &self.source_params.consumer_name,
+            &self.source_runtime,
+            partition_idx,
+        );
+        let filter_subject = if self.source_params.subjects.is_empty() {
+            String::new()
+        } else {
+            partition_id.0.to_string()
+        };
+        let deliver_policy = match position_opt.as_ref().and_then(Position::as_u64) {
+            Some(stream_sequence) => DeliverPolicy::ByStartSequence {
+                start_sequence: stream_sequence + 1,
+            },
+            None => DeliverPolicy::All,
+        };
+        info!(
+            partition=%partition_id,
+            position=?position_opt,
+            durable_name=%durable_name,
+            "seeking to last checkpoint position"
+        );
+
+        // The start sequence of a durable consumer cannot be updated, so the consumer is
+        // recreated from the checkpoint every time the pipeline starts consuming the partition.
+        // The consumer is named after the pipeline, so no other pipeline uses it.
+        if let Err(error) = self.stream.delete_consumer(&durable_name).await {
+            debug!(%error, durable_name=%durable_name, "failed to delete NATS consumer");
+        }
+        let consumer: PullConsumer = self
+            .stream
+            .create_consumer(PullConsumerConfig {
+                durable_name: Some(durable_name.clone()),
+                filter_subject,
+                deliver_policy,
+                ack_policy: AckPolicy::All,
+                ack_wait: self.ack_wait,
+                max_ack_pending: -1,
+                inactive_threshold: CONSUMER_INACTIVE_THRESHOLD,
+                ..Default::default()
+            })
+            .await
+            .with_context(|| format!("failed to create NATS consumer `{durable_name}`"))?;
+        let consumer_messages = consumer.messages().await?;
+
+        let stream_partition_id = partition_id.clone();
+        let closed_partition_id = partition_id.clone();
+        // The message stream of a consumer only ends when the pipeline stops consuming the
+        // partition, otherwise the consumer was closed and the pipeline fails.
+        let (consumer_messages, abort_handle) = stream::abortable(
+            consumer_messages
+                .map(move |message_res| {
+                    (
+                        stream_partition_id.clone(),
+                        message_res.map_err(anyhow::Error::from),
+                    )
+                })
+                .chain(stream::once(async move {
+                    let error = anyhow!("NATS consumer `{durable_name}` was closed");
+                    (closed_partition_id, Err(error))
+                })),
+        );
+        self.messages.push(consumer_messages.boxed());
+
+        match position_opt {
+            Some(position) => {
+                self.current_positions
+                    .insert(partition_id.clone(), position);
+            }
+            None => {
+                self.current_positions.remove(&partition_id);
+            }
+        }
+        self.pending_acks.remove(&partition_id);
+        self.assigned_partitions.insert(partition_id, abort_handle);
+        Ok(())
+    }
+
+    async fn stop_consuming(&mut self, partition_idx: usize) {
+        let partition_id = &self.partitions[partition_idx];
+
+        if let Some(abort_handle) = self.assigned_partitions.remove(partition_id) {
+            abort_handle.abort();
+        }
+        self.current_positions.remove(partition_id);
+        self.pending_acks.remove(partition_id);
+
+        let durable_name = durable_consumer_name(
+            &self.source_params.consumer_name,
+            &self.source_runtime,
+            partition_idx,
+        );
+        if let Err(error) = self.stream.delete_consumer(&durable_name).await {
+            debug!(%error, durable_name=%durable_name, "failed to delete NATS consumer");
+        }
+    }
+
+    fn process_message(
+        &mut self,
+        partition_id: PartitionId,
+        message: Message,
+        batch: &mut BatchBuilder,
+        batch_acks: &mut BTreeMap<PartitionId, (u64, Acker)>,
+    ) -> anyhow::Result<()> {
+        let stream_sequence = message
+            .info()
+            .map_err(|error| anyhow!("failed to parse NATS message metadata: {error}"))?
+            .stream_sequence;
+        let (message, acker) = message.split();
+        self.add_doc_to_batch(
+            partition_id.clone(),
+            stream_sequence,
+            message.payload,
+            batch,
+        )?;
+        batch_acks.insert(partition_id, (stream_sequence, acker));
+        Ok(())
+    }
+
+    fn add_doc_to_batch(
+        &mut self,
+        partition: PartitionId,
+        stream_sequence: u64,
+        doc: Bytes,
+        batch: &mut BatchBuilder,
+    ) -> anyhow::Result<()> {
+        if doc.is_empty() {
+            warn!("message received from stream was empty");
+            self.state.num_invalid_messages += 1;
+            return Ok(());
+        }
+        let msg_position = Position::offset(stream_sequence);
+        let num_bytes = doc.len() as u64;
+
+        if let Some(current_position) = self.current_positions.get(&partition) {
+            // We skip messages older or equal to the current recorded position. NATS redelivers
+            // the messages that have not been acknowledged in time, which can happen when the
+            // checkpoint takes longer than `ack_wait` to be published.
+            if &msg_position <= current_position {
+                self.state.num_skipped_messages += 1;
+                return Ok(());
+            }
+        }
+        let current_position = self
+            .current_positions
+            .insert(partition.clone(), msg_position.clone())
+            .unwrap_or(Position::Beginning);
+
+        batch
+            .checkpoint_delta
+            .record_partition_delta(partition, current_position, msg_position)
+            .context("failed to record partition delta")?;
+        batch.add_doc(doc);
+
+        self.state.num_bytes_processed += num_bytes;
+        self.state.num_messages_processed += 1;
+
+        Ok(())
+    }
+
+    async fn try_ack_messages(&mut self, checkpoint: SourceCheckpoint) -> anyhow::Result<()> {
+        debug!(ckpt = ?checkpoint, "truncating message queue");
+        for (partition, position) in checkpoint.iter() {
+            let Some(stream_sequence) = position.as_u64() else {
+                continue;
+            };
+            let Some(partition_acks) = self.pending_acks.get_mut(&partition) else {
+                continue;
+            };
+            let remaining_acks = partition_acks.split_off(&(stream_sequence + 1));
+            let published_acks = std::mem::replace(partition_acks, remaining_acks);
+
+            // The consumers use the `all` ack policy, so acknowledging the last published message
+            // acknowledges all the messages before it.
+            if let Some((_, acker)) = published_acks.into_iter().next_back() {
+                acker
+                    .ack()
+                    .await
+                    .map_err(|error| anyhow!("failed to ack NATS message: {error}"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Source for NatsSource {
+    async fn emit_batches(
+        &mut self,
+        doc_processor_mailbox: &Mailbox<DocProcessor>,
+        ctx: &SourceContext,
+    ) -> Result<Duration, ActorExitStatus> {
+        if Instant::now() >= self.next_assignment_refresh_at {
+            self.refresh_assignment().await?;
+        }
+        let now = Instant::now();
+        let mut batch_builder = BatchBuilder::new(SourceType::Nats);
+        let mut batch_acks = BTreeMap::new();
+        let deadline = time::sleep(*EMIT_BATCHES_TIMEOUT);
+        tokio::pin!(deadline);
+
+        loop {
+            tokio::select! {
+                // A pipeline may have no partition assigned when the source has more pipelines
+                // than subjects.
+                message_opt = self.messages.next(), if !self.messages.is_empty() => {
+                    // The message streams of the released partitions end without item.
+                    let Some((partition_id, message)) = message_opt else {
+                        continue;
+                    };
+                    let message = message
+                        .map_err(|error| ActorExitStatus::from(anyhow!("failed to get message from consumer: {error:?}")))?;
+
+                    self.process_message(partition_id, message, &mut batch_builder, &mut batch_acks)
+                        .map_err(ActorExitStatus::from)?;
+
+                    if batch_builder.num_bytes >= BATCH_NUM_BYTES_LIMIT {
+                        break;
+                    }
+                }
+                _ = &mut deadline => {
+                    break;
+                }
+            }
+            ctx.record_progress();
+        }
+        for (partition_id, (stream_sequence, acker)) in batch_acks {
+            self.pending_acks
+                .entry(partition_id)
+                .or_default()
+                .insert(stream_sequence, acker);
+        }
+        if !batch_builder.checkpoint_delta.is_empty() {
+            debug!(
+                num_docs=%batch_builder.docs.len(),
+                num_bytes=%batch_builder.num_bytes,
+                num_millis=%now.elapsed().as_millis(),
+                "sending doc batch to indexer"
+            );
+            let message = batch_builder.build();
+            ctx.send_message(doc_processor_mailbox, message).await?;
+        }
+        Ok(Duration::default())
+    }
+
+    async fn suggest_truncate(
+        &mut self,
+        checkpoint: SourceCheckpoint,
+        _ctx: &ActorContext<SourceActor>,
+    ) -> anyhow::Result<()> {
+        self.try_ack_messages(checkpoint).await
+    }
+
+    fn name(&self) -> String {
+        format!("{self:?}")
+    }
+
+    async fn finalize(
+        &mut self,
+        _exit_status: &ActorExitStatus,
+        _ctx: &SourceContext,
+    ) -> anyhow::Result<()> {
+        for partition_idx in 0..self.partitions.len() {
+            if self
+                .assigned_partitions
+                .contains_key(&self.partitions[partition_idx])
+            {
+                self.stop_consuming(partition_idx).await;
+            }
+        }
+        Ok(())
+    }
+
+    fn observable_state(&self) -> JsonValue {
+        json!({
+            "index_id": self.source_runtime.index_id(),
+            "source_id": self.source_runtime.source_id(),
+            "stream": self.source_params.stream,
+            "partitions": self.assigned_partitions.keys().collect::<Vec<_>>(),
+            "num_bytes_processed": self.state.num_bytes_processed,
+            "num_messages_processed": self.state.num_messages_processed,
+            "num_invalid_messages": self.state.num_invalid_messages,
+            "num_skipped_messages": self.state.num_skipped_messages,
+        })
+    }
+}
+
+/// Returns the partitions of the source: its subjects, or the stream itself when no subjects are
+/// configured.
+fn partitions(source_params: &NatsSourceParams) -> Vec<PartitionId> {
+    if source_params.subjects.is_empty() {
+        return vec![PartitionId::from(source_params.stream.as_str())];
+    }
+    source_params
+        .subjects
+        .iter()
+        .map(|subject| PartitionId::from(subject.as_str()))
+        .collect()
+}
+
+/// Returns whether the partition is assigned to `pipeline_uid`, i.e. whether it has the highest
+/// rendezvous hash affinity for the partition among the pipelines of the source.
+fn is_assigned_to(
+    partition_id: &PartitionId,
+    pipeline_uids: &[PipelineUid],
+    pipeline_uid: PipelineUid,
+) -> bool {
+    pipeline_uids
+        .iter()
+        .max_by_key(|candidate_uid| node_affinity(**candidate_uid, partition_id))
+        == Some(&pipeline_uid)
+}
+
+fn durable_consumer_name(
+    consumer_name: &str,
+    source_runtime: &SourceRuntime,
+    partition_idx: usize,
+) -> String {
+    sanitize_name(&format!(
+        "{consumer_name}-{}-{}-{}-{partition_idx}",
+        source_runtime.index_uid(),
+        source_runtime.source_id(),
+        source_runtime.pipeline_uid()
+    ))
+}
+
+/// Replaces the characters that are not allowed in NATS consumer names.
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() || character == '-' || character == '_' {
+                character
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+async fn connect_jetstream(params: &NatsSourceParams) -> anyhow::Result<jetstream::Context> {
+    let server_addrs = params
+        .address
+        .split(',')
+        .map(|url| ServerAddr::from_str(url.trim()))
+        .collect::<Result<Vec<_>, _>>()
+        .context("failed to parse NATS address")?;
+
+    let connect_options = match params.authentication.clone() {
+        None => ConnectOptions::new(),
+        Some(NatsSourceAuth::Token(token)) => ConnectOptions::with_token(token),
+        Some(NatsSourceAuth::UserPassword { user, password }) => {
+            ConnectOptions::with_user_and_password(user, password)
+        }
+        Some(NatsSourceAuth::CredentialsFile(credentials_filepath)) => {
+            ConnectOptions::with_credentials_file(&credentials_filepath)
+                .await
+                .with_context(|| {
+                    format!("failed to read NATS credentials file `{credentials_filepath}`")
+                })?
+        }
+    };
+    let client = connect_options
+        .connect(server_addrs.as_slice())
+        .await
+        .with_context(|| format!("failed to connect to NATS server `{}`", params.address))?;
+    Ok(jetstream::new(client))
+}
+
+/// Checks whether we can establish a connection to the NATS server and that the stream exists.
+pub(crate) async fn check_connectivity(params: &NatsSourceParams) -> anyhow::Result<()> {
+    let jetstream = connect_jetstream(params).await?;
+    jetstream
+        .get_stream(&params.stream)
+        .await
+        .with_context(|| format!("failed to get NATS stream `{}`", params.stream))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nats_source_is_assigned_to() {
+        let pipeline_uids = [PipelineUid::for_test(0), PipelineUid::for_test(1)];
+        let mut num_assigned_partitions = [0; 2];
+
+        for subject_idx in 0..100 {
+            let partition_id = PartitionId::from(format!("events.{subject_idx}.>"));
+            let assigned_pipelines: Vec<usize> = (0..2)
+                .filter(|pipeline_idx| {
+                    is_assigned_to(&partition_id, &pipeline_uids, pipeline_uids[*pipeline_idx])
+                })
+                .collect();
+            assert_eq!(assigned_pipelines.len(), 1);
+            num_assigned_partitions[assigned_pipelines[0]] += 1;
+        }
+        assert!(num_assigned_partitions[0] > 0);
+        assert!(num_assigned_partitions[1] > 0);
+
+        // A pipeline alone is assigned all the partitions.
+        assert!(is_assigned_to(
+            &PartitionId::from("events.0.>"),
+            &[PipelineUid::for_test(1)],
+            PipelineUid::for_test(1)
+        ));
+    }
+
+    #[test]
+    fn test_nats_source_partitions() {
+        let mut source_params = NatsSourceParams {
+            address: "nats://localhost:4222".to_string(),
+            stream: "my-stream".to_string(),
+            subjects: Vec::new(),
+            consumer_name: "quickwit".to_string(),
+            authentication: None,
+        };
+        assert_eq!(
+            partitions(&source_params),
+            vec![PartitionId::from("my-stream")]
+        );
+        source_params.subjects = vec!["events.0.>".to_string(), "events.1.>".to_string()];
+        assert_eq!(
+            partitions(&source_params),
+            vec![
+                PartitionId::from("events.0.>"),
+                PartitionId::from("events.1.>")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nats_source_sanitize_name() {
+        assert_eq!(
+            sanitize_name("quickwit-my-index:01H0-source_1.>"),
+            "quickwit-my-index_01H0-source_1__"
+        );
+    }
+}
+
+#[cfg(all(test, feature = "nats-broker-tests"))]
+mod nats_broker_tests {
+    use std::num::NonZeroUsize;
+
+    use async_nats::jetstream::stream::Config as StreamConfig;
+    use quickwit_actors::{ActorHandle, Inbox, Universe};
+    use quickwit_cluster::{ChannelTransport, create_cluster_for_test};
+    use quickwit_common::rand::append_random_suffix;
+    use quickwit_config::{SourceConfig, SourceInputFormat, SourceParams};
+    use quickwit_metastore::checkpoint::SourceCheckpointDelta;
+    use quickwit_proto::indexing::IndexingTask;
+    use quickwit_proto::types::IndexUid;
+
+    use super::*;
+    use crate::source::tests::SourceRuntimeBuilder;
+    use crate::source::{RawDocBatch, SuggestTruncate, quickwit_supported_sources};
+
+    static NATS_URI: &str = "nats://localhost:4222";
+
+    fn get_source_config(
+        stream: &str,
+        subjects: &[String],
+        num_pipelines: usize,
+    ) -> (String, SourceConfig) {
+        let source_id = append_random_suffix("test-nats-source--source");
+        let source_config = SourceConfig {
+            source_id: source_id.clone(),
+            num_pipelines: NonZeroUsize::new(num_pipelines).unwrap(),
+            enabled: true,
+            source_params: SourceParams::Nats(NatsSourceParams {
+                address: NATS_URI.to_string(),
+                stream: stream.to_string(),
+                subjects: subjects.to_vec(),
+                consumer_name: "quickwit-tester".to_string(),
+                authentication: None,
+            }),
+            transform_config: None,
+            input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
+        };
+        (source_id, source_config)
+    }
+
+    /// Creates a stream capturing the subjects `{stream}.>` and publishes `num_messages` messages
+    /// to each of the given subjects. Returns the stream sequence of the last message published to
+    /// each subject.
+    async fn populate_stream(
+        stream: &str,
+        subjects: &[String],
+        num_messages: usize,
+    ) -> anyhow::Result<Vec<u64>> {
+        let client = async_nats::connect(NATS_URI).await?;
+        let jetstream = jetstream::new(client);
+        jetstream
+            .create_stream(StreamConfig {
+                name: stream.to_string(),
+                subjects: vec![format!("{stream}.>")],
+                ..Default::default()
+            })
+            .await?;
+
+        let mut last_stream_sequences = Vec::with_capacity(subjects.len());
+        for subject in subjects {
+            let mut last_stream_sequence = 0;
+            for id in 0..num_messages {
+                let message = json!({
+                    "id": id.to_string(),
+                    "subject": subject,
+                    "body": "Hello, world! This is some test data.",
+                });
+                last_stream_sequence = jetstream
+                    .publish(subject.clone(), message.to_string().into())
+                    .await?
+                    .await?
+                    .sequence;
+            }
+            last_stream_sequences.push(last_stream_sequence);
+        }
+        Ok(last_stream_sequences)
+    }
+
+    async fn create_source(
+        universe: &Universe,
+        source_config: SourceConfig,
+        checkpoint_delta_opt: Option<SourceCheckpointDelta>,
+    ) -> anyhow::Result<(ActorHandle<SourceActor>, Inbox<DocProcessor>)> {
+        let index_uid = IndexUid::for_test("test-nats-source-index", 0);
+        let source_runtime = SourceRuntimeBuilder::new(index_uid, source_config)
+            .with_mock_metastore(checkpoint_delta_opt)
+            .build();
+        spawn_source(universe, source_runtime).await
+    }
+
+    async fn spawn_source(
+        universe: &Universe,
+        source_runtime: SourceRuntime,
+    ) -> anyhow::Result<(ActorHandle<SourceActor>, Inbox<DocProcessor>)> {
+        let source = quickwit_supported_sources()
+            .load_source(source_runtime)
+            .await?;
+        let (doc_processor_mailbox, doc_processor_inbox) = universe.create_test_mailbox();
+        let source_actor = SourceActor {
+            source,
+            doc_processor_mailbox,
+        };
+        let (_source_mailbox, source_handle) = universe.spawn_builder().spawn(source_actor);
+        Ok((source_handle, doc_processor_inbox))
+    }
+
+    async fn wait_for_messages(source_handle: &ActorHandle<SourceActor>, num_expected: u64) {
+        loop {
+            let observation = source_handle.observe().await;
+            let num_messages_processed = observation.state["num_messages_processed"]
+                .as_u64()
+                .unwrap();
+            if num_messages_processed >= num_expected {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    fn merge_doc_batches(batches: Vec<RawDocBatch>) -> RawDocBatch {
+        let mut merged_batch = RawDocBatch::default();
+        for batch in batches {
+            merged_batch.docs.extend(batch.docs);
+            merged_batch
+                .checkpoint_delta
+                .extend(batch.checkpoint_delta)
+                .unwrap();
+        }
+        merged_batch
+    }
+
+    #[tokio::test]
+    async fn test_nats_source_emits_and_acks_messages() {
+        let universe = Universe::with_accelerated_time();
+        let stream = append_random_suffix("test-nats-stream");
+        let subjects = vec![format!("{stream}.a"), format!("{stream}.b")];
+        let last_stream_sequences = populate_stream(&stream, &subjects, 10).await.unwrap();
+
+        let (_source_id, source_config) = get_source_config(&stream, &subjects, 1);
+        let (source_handle, doc_processor_inbox) =
+            create_source(&universe, source_config, None).await.unwrap();
+        wait_for_messages(&source_handle, 20).await;
+
+        let batch = merge_doc_batches(doc_processor_inbox.drain_for_test_typed::<RawDocBatch>());
+        assert_eq!(batch.docs.len(), 20);
+
+        let mut expected_checkpoint_delta = SourceCheckpointDelta::default();
+        for (subject, last_stream_sequence) in subjects.iter().zip(&last_stream_sequences) {
+            expected_checkpoint_delta
+                .record_partition_delta(
+                    PartitionId::from(subject.as_str()),
+                    Position::Beginning,
+                    Position::offset(*last_stream_sequence),
+                )
+                .unwrap();
+        }
+        assert_eq!(batch.checkpoint_delta, expected_checkpoint_delta);
+
+        let mut checkpoint = SourceCheckpoint::default();
+        checkpoint
+            .try_apply_delta(expected_checkpoint_delta)
+            .unwrap();
+        source_handle
+            .mailbox()
+            .send_message(SuggestTruncate(checkpoint))
+            .await
+            .unwrap();
+        let observation = source_handle.process_pending_and_observe().await;
+        assert_eq!(observation.state["num_invalid_messages"], 0);
+
+        let client = async_nats::connect(NATS_URI).await.unwrap();
+        let nats_stream = jetstream::new(client).get_stream(&stream).await.unwrap();
+
+        for (partition_idx, last_stream_sequence) in last_stream_sequences.iter().enumerate() {
+            let durable_name = sanitize_name(&format!(
+                "quickwit-tester-{}-{}-{}-{partition_idx}",
+                IndexUid::for_test("test-nats-source-index", 0),
+                observation.state["source_id"].as_str().unwrap(),
+                PipelineUid::for_test(0),
+            ));
+            let mut consumer: PullConsumer = nats_stream.get_consumer(&durable_name).await.unwrap();
+            let consumer_info = consumer.info().await.unwrap();
+            assert_eq!(
+                consumer_info.ack_floor.stream_sequence,
+                *last_stream_sequence
+            );
+        }
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_nats_source_resumes_from_checkpoint() {
+        let universe = Universe::with_accelerated_time();
+        let stream = append_random_suffix("test-nats-stream");
+        let subjects = vec![format!("{stream}.a")];
+        let last_stream_sequences = populate_stream(&stream, &subjects, 10).await.unwrap();
+
+        let mut checkpoint_delta = SourceCheckpointDelta::default();
+        checkpoint_delta
+            .record_partition_delta(
+                PartitionId::from(subjects[0].as_str()),
+                Position::Beginning,
+                Position::offset(last_stream_sequences[0] - 4),
+            )
+            .unwrap();
+        let (_source_id, source_config) = get_source_config(&stream, &subjects, 1);
+        let (source_handle, doc_processor_inbox) =
+            create_source(&universe, source_config, Some(checkpoint_delta))
+                .await
+                .unwrap();
+        wait_for_messages(&source_handle, 4).await;
+
+        let batch = merge_doc_batches(doc_processor_inbox.drain_for_test_typed::<RawDocBatch>());
+        assert_eq!(batch.docs.len(), 4);
+
+        let expected_checkpoint_delta = SourceCheckpointDelta::from_partition_delta(
+            PartitionId::from(subjects[0].as_str()),
+            Position::offset(last_stream_sequences[0] - 4),
+            Position::offset(last_stream_sequences[0]),
+        )
+        .unwrap();
+        assert_eq!(batch.checkpoint_delta, expected_checkpoint_delta);
+
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_nats_source_multiple_pipelines() {
+        let universe = Universe::with_accelerated_time();
+        let stream = append_random_suffix("test-nats-stream");
+        let pipeline_uids = [PipelineUid::for_test(0), PipelineUid::for_test(1)];
+
+        // Picks a subject assigned to each pipeline.
+        let subjects: Vec<String> = pipeline_uids
+            .iter()
+            .map(|pipeline_uid| {
+                (0..)
+                    .map(|subject_idx| format!("{stream}.{subject_idx}"))
+                    .find(|subject| {
+                        let partition_id = PartitionId::from(subject.as_str());
+                        is_assigned_to(&partition_id, &pipeline_uids, *pipeline_uid)
+                    })
+                    .unwrap()
+            })
+            .collect();
+        populate_stream(&stream, &subjects, 10).await.unwrap();
+
+        let (source_id, source_config) = get_source_config(&stream, &subjects, 2);
+        let index_uid = IndexUid::for_test("test-nats-source-index", 0);
+
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["indexer"], &transport, true)
+            .await
+            .unwrap();
+        let indexing_tasks: Vec<IndexingTask> = pipeline_uids
+            .iter()
+            .map(|pipeline_uid| IndexingTask {
+                index_uid: Some(index_uid.clone()),
+                source_id: source_id.clone(),
+                pipeline_uid: Some(*pipeline_uid),
+                shard_ids: Vec::new(),
+                params_fingerprint: 0,
+            })
+            .collect();
+        cluster
+            .update_self_node_indexing_tasks(&indexing_tasks)
+            .await;
+        cluster
+            .wait_for_ready_members(
+                |members| {
+                    members
+                        .iter()
+                        .any(|member| member.indexing_tasks.len() == indexing_tasks.len())
+                },
+                Duration::from_secs(5),
+            )
+            .await
+            .unwrap();
+
+        for (pipeline_uid, subject) in pipeline_uids.into_iter().zip(&subjects) {
+            let source_runtime =
+                SourceRuntimeBuilder::new(index_uid.clone(), source_config.clone())
+                    .with_mock_metastore(None)
+                    .with_pipeline_uid(pipeline_uid)
+                    .with_cluster(cluster.clone())
+                    .build();
+            let (source_handle, doc_processor_inbox) =
+                spawn_source(&universe, source_runtime).await.unwrap();
+            wait_for_messages(&source_handle, 10).await;
+
+            let batch =
+                merge_doc_batches(doc_processor_inbox.drain_for_test_typed::<RawDocBatch>());
+            assert_eq!(batch.docs.len(), 10);
+
+            let partition_ids: Vec<PartitionId> = batch
+                .checkpoint_delta
+                .iter()
+                .map(|(partition_id, _)| partition_id)
+                .collect();
+            assert_eq!(partition_ids, [PartitionId::from(subject.as_str())]);
+        }
+        universe.assert_quit().await;
+    }
+}
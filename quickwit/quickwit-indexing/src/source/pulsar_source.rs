@@ -503,6 +503,8 @@ mod pulsar_broker_tests {
             }),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         (source_id, source_config)
     }
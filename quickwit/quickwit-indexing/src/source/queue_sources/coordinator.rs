@@ -19,7 +19,7 @@ use std::time::Duration;
 use itertools::Itertools;
 use quickwit_actors::{ActorExitStatus, Mailbox};
 use quickwit_common::rate_limited_error;
-use quickwit_config::{FileSourceMessageType, FileSourceSqs};
+use quickwit_config::{FileSourceMessageType, FileSourcePolling, FileSourceSqs};
 use quickwit_metastore::checkpoint::SourceCheckpoint;
 use quickwit_proto::indexing::IndexingPipelineId;
 use quickwit_proto::metastore::SourceType;
@@ -146,6 +146,36 @@ impl QueueCoordinator {
         ))
     }
 
+    /// The files before the start-after marker of the polling queue are not
+    /// listed anymore, so their shards can be pruned as with the SQS queue.
+    pub async fn try_from_polling_config(
+        config: FileSourcePolling,
+        source_runtime: SourceRuntime,
+    ) -> anyhow::Result<Self> {
+        use super::polling_queue::PollingQueue;
+        let storage = source_runtime.storage_resolver.resolve(&config.uri).await?;
+        let queue = PollingQueue::try_new(
+            storage,
+            &config,
+            SourceUid {
+                index_uid: source_runtime.pipeline_id.index_uid.clone(),
+                source_id: source_runtime.pipeline_id.source_id.clone(),
+            },
+            source_runtime.pipeline_uid(),
+            source_runtime.metastore.clone(),
+            source_runtime.cluster_opt.clone(),
+        )?;
+        let shard_max_age = Duration::from_secs(config.deduplication_window_duration_secs as u64);
+        Ok(QueueCoordinator::new(
+            source_runtime,
+            Arc::new(queue),
+            MessageType::RawUri,
+            Some(shard_max_age),
+            Some(config.deduplication_window_max_files),
+            Duration::from_secs(config.deduplication_cleanup_interval_secs as u64),
+        ))
+    }
+
     pub async fn initialize(
         &mut self,
         doc_processor_mailbox: &Mailbox<DocProcessor>,
@@ -18,6 +18,7 @@ mod local_state;
 #[cfg(test)]
 mod memory_queue;
 mod message;
+mod polling_queue;
 mod shared_state;
 #[cfg(feature = "sqs")]
 pub mod sqs_queue;
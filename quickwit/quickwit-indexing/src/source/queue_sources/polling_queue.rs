@@ -0,0 +1,500 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use glob::Pattern as GlobPattern;
+use quickwit_cluster::Cluster;
+use quickwit_common::rendezvous_hasher::node_affinity;
+use quickwit_common::uri::Uri;
+use quickwit_config::FileSourcePolling;
+use quickwit_proto::metastore::{
+    ListShardsRequest, ListShardsSubrequest, MetastoreService, MetastoreServiceClient,
+};
+use quickwit_proto::types::{PipelineUid, Position, SourceUid};
+use quickwit_storage::{OwnedBytes, Storage};
+use tracing::debug;
+
+use super::Queue;
+use super::message::{MessageMetadata, RawMessage};
+
+#[derive(Default)]
+struct PollingQueueState {
+    /// URIs of the files discovered by the last listing that were not received yet
+    pending: VecDeque<Uri>,
+    /// Path of the last file of the longest run of fully indexed files, in key
+    /// order. The listings start after it.
+    start_after_opt: Option<PathBuf>,
+    next_listing_at: Option<tokio::time::Instant>,
+}
+
+/// A [`Queue`] that discovers files by periodically listing a prefix of a
+/// storage. Each listed file is a message with the file URI as payload and ack
+/// id.
+///
+/// The files are assumed to be created in increasing key order. The queue
+/// keeps a start-after marker on the last file of the run of fully indexed
+/// files, so that each listing only returns the files after it. The files that
+/// are fully indexed are the ones whose shard reached EOF, so the marker
+/// advances over the files indexed by the other pipelines of the source too.
+/// After a restart, the marker starts from the oldest file still tracked by a
+/// shard, if it is fully indexed.
+///
+/// Each file is assigned to the pipeline of the source with the highest
+/// rendezvous hash affinity, so the pipelines receive disjoint sets of files.
+/// The pipelines of the source are discovered through the cluster, and
+/// ownership is still arbitrated by the shared state while the pipelines have
+/// different views of the cluster.
+pub struct PollingQueue {
+    storage: Arc<dyn Storage>,
+    glob_opt: Option<GlobPattern>,
+    polling_interval: Duration,
+    source_uid: SourceUid,
+    pipeline_uid: PipelineUid,
+    metastore: MetastoreServiceClient,
+    cluster_opt: Option<Cluster>,
+    state: Mutex<PollingQueueState>,
+}
+
+impl fmt::Debug for PollingQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PollingQueue")
+            .field("uri", self.storage.uri())
+            .field("glob", &self.glob_opt.as_ref().map(GlobPattern::as_str))
+            .finish()
+    }
+}
+
+impl PollingQueue {
+    pub fn try_new(
+        storage: Arc<dyn Storage>,
+        config: &FileSourcePolling,
+        source_uid: SourceUid,
+        pipeline_uid: PipelineUid,
+        metastore: MetastoreServiceClient,
+        cluster_opt: Option<Cluster>,
+    ) -> anyhow::Result<Self> {
+        let glob_opt = config
+            .glob
+            .as_deref()
+            .map(GlobPattern::new)
+            .transpose()
+            .context("invalid glob pattern")?;
+        Ok(Self {
+            storage,
+            glob_opt,
+            polling_interval: Duration::from_secs(config.polling_interval_secs as u64),
+            source_uid,
+            pipeline_uid,
+            metastore,
+            cluster_opt,
+            state: Mutex::default(),
+        })
+    }
+
+    /// Returns the URIs of the files tracked by the shards of the source and
+    /// whether they are fully indexed.
+    async fn fetch_tracked_files(&self) -> anyhow::Result<BTreeMap<String, bool>> {
+        let list_shards_request = ListShardsRequest {
+            subrequests: vec![ListShardsSubrequest {
+                index_uid: Some(self.source_uid.index_uid.clone()),
+                source_id: self.source_uid.source_id.clone(),
+                shard_state: None,
+            }],
+        };
+        let list_shards_response = self.metastore.list_shards(list_shards_request).await?;
+        let mut tracked_files = BTreeMap::new();
+
+        for list_shards_subresponse in list_shards_response.subresponses {
+            for shard in list_shards_subresponse.shards {
+                let is_completed =
+                    matches!(shard.publish_position_inclusive, Some(Position::Eof(_)));
+                tracked_files.insert(shard.shard_id().as_str().to_string(), is_completed);
+            }
+        }
+        Ok(tracked_files)
+    }
+
+    /// Returns the UIDs of the pipelines of the source, including this one.
+    async fn source_pipeline_uids(&self) -> Vec<PipelineUid> {
+        let mut pipeline_uids = vec![self.pipeline_uid];
+
+        if let Some(cluster) = &self.cluster_opt {
+            for member in cluster.ready_members().await {
+                for indexing_task in &member.indexing_tasks {
+                    if indexing_task.index_uid() == &self.source_uid.index_uid
+                        && indexing_task.source_id == self.source_uid.source_id
+                    {
+                        pipeline_uids.push(indexing_task.pipeline_uid());
+                    }
+                }
+            }
+        }
+        pipeline_uids.sort();
+        pipeline_uids.dedup();
+        pipeline_uids
+    }
+
+    /// Converts the URI of a tracked file back into a path relative to the
+    /// storage.
+    fn relative_path(&self, uri: &str) -> Option<PathBuf> {
+        let relative_path = uri
+            .strip_prefix(self.storage.uri().as_str())?
+            .trim_start_matches('/');
+        Some(PathBuf::from(relative_path))
+    }
+
+    /// Lists the files after the start-after marker that match the glob
+    /// pattern, are not fully indexed yet and are assigned to this pipeline.
+    /// Advances the marker over the files that are fully indexed.
+    async fn list_files(&self) -> anyhow::Result<Vec<Uri>> {
+        let tracked_files = self.fetch_tracked_files().await?;
+        let mut start_after_opt = {
+            let state = self.state.lock().unwrap();
+
+            if state.next_listing_at.is_none() {
+                // First listing: resume after the oldest tracked file if it is
+                // fully indexed.
+                tracked_files
+                    .first_key_value()
+                    .filter(|(_, is_completed)| **is_completed)
+                    .and_then(|(uri, _)| self.relative_path(uri))
+            } else {
+                state.start_after_opt.clone()
+            }
+        };
+        let mut filepaths = if let Some(start_after) = &start_after_opt {
+            self.storage
+                .list_files_with_prefix_after(Path::new(""), start_after)
+                .await?
+        } else {
+            self.storage.list_files_with_prefix(Path::new("")).await?
+        };
+        // The start-after marker compares keys in byte order.
+        filepaths.sort_by(|left, right| left.as_os_str().cmp(right.as_os_str()));
+
+        let pipeline_uids = self.source_pipeline_uids().await;
+        let mut is_completed_run = true;
+        let mut uris = Vec::new();
+
+        for filepath in filepaths {
+            if let Some(glob) = &self.glob_opt
+                && !glob.matches_path(&filepath)
+            {
+                if is_completed_run {
+                    start_after_opt = Some(filepath);
+                }
+                continue;
+            }
+            let uri = self.storage.uri().join(&filepath)?;
+
+            if tracked_files.get(uri.as_str()) == Some(&true) {
+                if is_completed_run {
+                    start_after_opt = Some(filepath);
+                }
+                continue;
+            }
+            is_completed_run = false;
+
+            if is_assigned_to(&uri, &pipeline_uids, self.pipeline_uid) {
+                uris.push(uri);
+            }
+        }
+        self.state.lock().unwrap().start_after_opt = start_after_opt;
+        Ok(uris)
+    }
+}
+
+/// Returns whether the file is assigned to `pipeline_uid`, i.e. whether it has
+/// the highest rendezvous hash affinity for the file among the pipelines of the
+/// source.
+fn is_assigned_to(uri: &Uri, pipeline_uids: &[PipelineUid], pipeline_uid: PipelineUid) -> bool {
+    pipeline_uids
+        .iter()
+        .max_by_key(|candidate_uid| node_affinity(**candidate_uid, &uri.as_str()))
+        == Some(&pipeline_uid)
+}
+
+#[async_trait]
+impl Queue for PollingQueue {
+    async fn receive(
+        self: Arc<Self>,
+        max_messages: usize,
+        suggested_deadline: Duration,
+    ) -> anyhow::Result<Vec<RawMessage>> {
+        loop {
+            let next_listing_at = {
+                let mut state = self.state.lock().unwrap();
+                if !state.pending.is_empty() {
+                    let num_messages = max_messages.min(state.pending.len());
+                    let initial_deadline = Instant::now() + suggested_deadline;
+                    let messages = state
+                        .pending
+                        .drain(..num_messages)
+                        .map(|uri| RawMessage {
+                            metadata: MessageMetadata {
+                                ack_id: uri.to_string(),
+                                message_id: uri.to_string(),
+                                delivery_attempts: 1,
+                                initial_deadline,
+                            },
+                            payload: OwnedBytes::new(uri.into_string().into_bytes()),
+                        })
+                        .collect();
+                    return Ok(messages);
+                }
+                state.next_listing_at
+            };
+            if let Some(next_listing_at) = next_listing_at {
+                tokio::time::sleep_until(next_listing_at).await;
+            }
+            let uris = self.list_files().await?;
+            debug!(num_files = uris.len(), "listed files to index");
+
+            let mut state = self.state.lock().unwrap();
+            state.pending = uris.into();
+            state.next_listing_at = Some(tokio::time::Instant::now() + self.polling_interval);
+        }
+    }
+
+    async fn acknowledge(&self, _ack_ids: &[String]) -> anyhow::Result<()> {
+        // The fully indexed files are tracked by the shards of the source.
+        Ok(())
+    }
+
+    async fn modify_deadlines(
+        &self,
+        _ack_id: &str,
+        suggested_deadline: Duration,
+    ) -> anyhow::Result<Instant> {
+        // Listed files are never hidden from the other pipelines.
+        Ok(Instant::now() + suggested_deadline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use quickwit_proto::ingest::{Shard, ShardState};
+    use quickwit_proto::metastore::{
+        ListShardsResponse, ListShardsSubresponse, MockMetastoreService,
+    };
+    use quickwit_proto::types::{IndexUid, ShardId};
+    use quickwit_storage::StorageResolver;
+
+    use super::*;
+
+    type ShardPositions = Arc<Mutex<Vec<(&'static str, Position)>>>;
+
+    async fn polling_queue_for_test(
+        glob_opt: Option<&str>,
+        polling_interval_secs: u32,
+        shard_positions: ShardPositions,
+    ) -> Arc<PollingQueue> {
+        let uri = Uri::for_test("ram:///polling-queue");
+        let storage = StorageResolver::for_test().resolve(&uri).await.unwrap();
+        for filepath in ["app/1.json", "app/2.json", "app/3.ndjson"] {
+            storage
+                .put(Path::new(filepath), Box::new(b"{}".to_vec()))
+                .await
+                .unwrap();
+        }
+        let config = FileSourcePolling {
+            uri,
+            glob: glob_opt.map(str::to_string),
+            polling_interval_secs,
+            deduplication_window_duration_secs: 3600,
+            deduplication_window_max_files: 1000,
+            deduplication_cleanup_interval_secs: 60,
+        };
+        let source_uid = SourceUid {
+            index_uid: IndexUid::for_test("test-index", 0),
+            source_id: "test-source".to_string(),
+        };
+        let mut mock_metastore = MockMetastoreService::new();
+        let index_uid = source_uid.index_uid.clone();
+        mock_metastore.expect_list_shards().returning(move |_| {
+            let shards = shard_positions
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(filepath, position)| Shard {
+                    index_uid: Some(index_uid.clone()),
+                    source_id: "test-source".to_string(),
+                    shard_id: Some(ShardId::from(format!("ram:///polling-queue/{filepath}"))),
+                    shard_state: ShardState::Open as i32,
+                    publish_position_inclusive: Some(position.clone()),
+                    ..Default::default()
+                })
+                .collect();
+            Ok(ListShardsResponse {
+                subresponses: vec![ListShardsSubresponse {
+                    index_uid: Some(index_uid.clone()),
+                    source_id: "test-source".to_string(),
+                    shards,
+                }],
+            })
+        });
+        let queue = PollingQueue::try_new(
+            storage,
+            &config,
+            source_uid,
+            PipelineUid::for_test(0),
+            MetastoreServiceClient::from_mock(mock_metastore),
+            None,
+        )
+        .unwrap();
+        Arc::new(queue)
+    }
+
+    fn payloads(messages: &[RawMessage]) -> Vec<String> {
+        let mut payloads: Vec<String> = messages
+            .iter()
+            .map(|message| String::from_utf8(message.payload.to_vec()).unwrap())
+            .collect();
+        payloads.sort();
+        payloads
+    }
+
+    fn start_after(queue: &PollingQueue) -> Option<PathBuf> {
+        queue.state.lock().unwrap().start_after_opt.clone()
+    }
+
+    #[tokio::test]
+    async fn test_polling_queue_receive() {
+        let queue = polling_queue_for_test(Some("**/*.json"), 60, ShardPositions::default()).await;
+        let messages = queue
+            .clone()
+            .receive(10, Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            payloads(&messages),
+            [
+                "ram:///polling-queue/app/1.json",
+                "ram:///polling-queue/app/2.json"
+            ]
+        );
+        assert_eq!(messages[0].metadata.ack_id, payloads(&messages[..1])[0]);
+
+        // The next listing happens after the polling interval.
+        let receive_res = tokio::time::timeout(
+            Duration::from_millis(100),
+            queue.clone().receive(10, Duration::from_secs(1)),
+        )
+        .await;
+        assert!(receive_res.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_polling_queue_lists_files_after_completed_files() {
+        let shard_positions = ShardPositions::default();
+        shard_positions
+            .lock()
+            .unwrap()
+            .push(("app/1.json", Position::eof(2u64)));
+        let queue = polling_queue_for_test(None, 0, shard_positions.clone()).await;
+
+        // The first listing resumes after the oldest tracked file.
+        let messages = queue
+            .clone()
+            .receive(10, Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            payloads(&messages),
+            [
+                "ram:///polling-queue/app/2.json",
+                "ram:///polling-queue/app/3.ndjson"
+            ]
+        );
+        assert_eq!(start_after(&queue), Some(PathBuf::from("app/1.json")));
+
+        // The files that are not fully indexed are listed again, but the
+        // marker only advances over the run of fully indexed files.
+        shard_positions.lock().unwrap().extend([
+            ("app/2.json", Position::eof(2u64)),
+            ("app/3.ndjson", Position::offset(1u64)),
+        ]);
+        let messages = queue
+            .clone()
+            .receive(10, Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(payloads(&messages), ["ram:///polling-queue/app/3.ndjson"]);
+        assert_eq!(start_after(&queue), Some(PathBuf::from("app/2.json")));
+
+        shard_positions.lock().unwrap()[2].1 = Position::eof(2u64);
+        let uris = queue.list_files().await.unwrap();
+        assert!(uris.is_empty());
+        assert_eq!(start_after(&queue), Some(PathBuf::from("app/3.ndjson")));
+    }
+
+    #[tokio::test]
+    async fn test_polling_queue_does_not_resume_after_incomplete_file() {
+        let shard_positions = ShardPositions::default();
+        shard_positions.lock().unwrap().extend([
+            ("app/1.json", Position::offset(1u64)),
+            ("app/2.json", Position::eof(2u64)),
+        ]);
+        let queue = polling_queue_for_test(None, 60, shard_positions).await;
+        let messages = queue
+            .clone()
+            .receive(10, Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            payloads(&messages),
+            [
+                "ram:///polling-queue/app/1.json",
+                "ram:///polling-queue/app/3.ndjson"
+            ]
+        );
+        assert_eq!(start_after(&queue), None);
+    }
+
+    #[test]
+    fn test_is_assigned_to() {
+        let pipeline_uids = [PipelineUid::for_test(0), PipelineUid::for_test(1)];
+        let mut num_assigned_files = [0; 2];
+
+        for file_idx in 0..100 {
+            let uri = Uri::from_str(&format!("s3://bucket/app/{file_idx}.json")).unwrap();
+            let assigned_pipelines: Vec<usize> = (0..2)
+                .filter(|pipeline_idx| {
+                    is_assigned_to(&uri, &pipeline_uids, pipeline_uids[*pipeline_idx])
+                })
+                .collect();
+            assert_eq!(assigned_pipelines.len(), 1);
+            num_assigned_files[assigned_pipelines[0]] += 1;
+        }
+        assert!(num_assigned_files[0] > 0);
+        assert!(num_assigned_files[1] > 0);
+
+        // A pipeline alone is assigned all the files.
+        let uri = Uri::for_test("s3://bucket/app/0.json");
+        assert!(is_assigned_to(
+            &uri,
+            &[PipelineUid::for_test(1)],
+            PipelineUid::for_test(1)
+        ));
+    }
+}
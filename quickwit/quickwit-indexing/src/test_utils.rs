@@ -171,6 +171,8 @@ impl TestSandbox {
             }),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         let pipeline_id = self
             .indexing_service
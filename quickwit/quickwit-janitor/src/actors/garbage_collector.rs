@@ -21,7 +21,7 @@ use async_trait::async_trait;
 use futures::{StreamExt, stream};
 use quickwit_actors::{Actor, ActorContext, Handler};
 use quickwit_common::shared_consts::split_deletion_grace_period;
-use quickwit_index_management::{GcMetrics, run_garbage_collect};
+use quickwit_index_management::{GcMetrics, PinnedSplits, run_garbage_collect};
 use quickwit_metastore::ListIndexesMetadataResponseExt;
 use quickwit_proto::metastore::{
     ListIndexesMetadataRequest, MetastoreService, MetastoreServiceClient,
@@ -128,6 +128,14 @@ impl GarbageCollector {
         if index_storages.is_empty() {
             return;
         }
+        // Without the point in time leases, we cannot tell which splits are still searched.
+        let pinned_splits = match PinnedSplits::load(&self.metastore).await {
+            Ok(pinned_splits) => pinned_splits,
+            Err(error) => {
+                error!(%error, "failed to list point in time leases from the metastore");
+                return;
+            }
+        };
 
         let gc_res = run_garbage_collect(
             index_storages,
@@ -135,6 +143,7 @@ impl GarbageCollector {
             STAGED_GRACE_PERIOD,
             split_deletion_grace_period(),
             false,
+            &pinned_splits,
             Some(ctx.progress()),
             Some(GcMetrics {
                 deleted_splits: JANITOR_METRICS
@@ -238,8 +247,8 @@ mod tests {
         SplitState,
     };
     use quickwit_proto::metastore::{
-        EmptyResponse, ListIndexesMetadataResponse, ListSplitsResponse, MetastoreError,
-        MockMetastoreService,
+        EmptyResponse, ListIndexesMetadataResponse, ListPointInTimeLeasesResponse,
+        ListSplitsResponse, MetastoreError, MockMetastoreService, PointInTimeLease,
     };
     use quickwit_proto::types::IndexUid;
     use quickwit_storage::MockStorage;
@@ -366,6 +375,7 @@ mod tests {
             STAGED_GRACE_PERIOD,
             split_deletion_grace_period(),
             false,
+            &PinnedSplits::default(),
             None,
             None,
         )
@@ -438,6 +448,9 @@ mod tests {
                 Ok(EmptyResponse {})
             });
 
+        mock_metastore
+            .expect_list_point_in_time_leases()
+            .returning(|_list_leases_request| Ok(ListPointInTimeLeasesResponse::default()));
         let garbage_collect_actor = GarbageCollector::new(
             MetastoreServiceClient::from_mock(mock_metastore),
             storage_resolver,
@@ -453,6 +466,68 @@ mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_garbage_collect_skips_splits_pinned_by_point_in_time() {
+        let storage_resolver = StorageResolver::unconfigured();
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .times(1)
+            .returning(|_list_indexes_request| {
+                let indexes_metadata = vec![IndexMetadata::for_test(
+                    "test-index",
+                    "ram://indexes/test-index",
+                )];
+                Ok(ListIndexesMetadataResponse::for_test(indexes_metadata))
+            });
+        mock_metastore
+            .expect_list_splits()
+            .times(2)
+            .returning(|list_splits_request| {
+                let query = list_splits_request.deserialize_list_splits_query().unwrap();
+                let splits = match query.split_states[0] {
+                    SplitState::Staged => Vec::new(),
+                    SplitState::MarkedForDeletion => make_splits(
+                        "test-index",
+                        &["a", "b", "c"],
+                        SplitState::MarkedForDeletion,
+                    ),
+                    _ => panic!("only Staged and MarkedForDeletion expected."),
+                };
+                let splits = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits)]))
+            });
+        mock_metastore.expect_delete_splits().never();
+
+        // The splits were marked for deletion after the point in time was opened.
+        mock_metastore
+            .expect_list_point_in_time_leases()
+            .times(1)
+            .returning(|_list_leases_request| {
+                let lease = PointInTimeLease {
+                    lease_id: "test-lease".to_string(),
+                    index_uids: vec![IndexUid::for_test("test-index", 0)],
+                    opened_at: 0,
+                    expires_at: OffsetDateTime::now_utc().unix_timestamp() + 3_600,
+                };
+                Ok(ListPointInTimeLeasesResponse {
+                    leases: vec![lease],
+                })
+            });
+        let garbage_collect_actor = GarbageCollector::new(
+            MetastoreServiceClient::from_mock(mock_metastore),
+            storage_resolver,
+        );
+        let universe = Universe::with_accelerated_time();
+        let (_mailbox, handler) = universe.spawn_builder().spawn(garbage_collect_actor);
+
+        let state_after_initialization = handler.process_pending_and_observe().await.state;
+        assert_eq!(state_after_initialization.num_passes, 1);
+        assert_eq!(state_after_initialization.num_deleted_files, 0);
+        assert_eq!(state_after_initialization.num_failed_splits, 0);
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_garbage_collect_get_calls_repeatedly() {
         let storage_resolver = StorageResolver::unconfigured();
@@ -514,6 +589,9 @@ mod tests {
                 Ok(EmptyResponse {})
             });
 
+        mock_metastore
+            .expect_list_point_in_time_leases()
+            .returning(|_list_leases_request| Ok(ListPointInTimeLeasesResponse::default()));
         let garbage_collect_actor = GarbageCollector::new(
             MetastoreServiceClient::from_mock(mock_metastore),
             storage_resolver,
@@ -739,6 +817,9 @@ mod tests {
                 }
             });
 
+        mock_metastore
+            .expect_list_point_in_time_leases()
+            .returning(|_list_leases_request| Ok(ListPointInTimeLeasesResponse::default()));
         let garbage_collect_actor = GarbageCollector::new(
             MetastoreServiceClient::from_mock(mock_metastore),
             storage_resolver,
@@ -0,0 +1 @@
+DROP TABLE point_in_time_leases;
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS point_in_time_leases (
+    lease_id VARCHAR(255) NOT NULL,
+    lease_json TEXT NOT NULL,
+    expires_at BIGINT NOT NULL,
+    PRIMARY KEY (lease_id)
+);
@@ -18,21 +18,24 @@ use async_trait::async_trait;
 use quickwit_common::uri::Uri;
 use quickwit_proto::control_plane::{ControlPlaneService, ControlPlaneServiceClient};
 use quickwit_proto::metastore::{
-    AcquireShardsRequest, AcquireShardsResponse, AddSourceRequest, CreateIndexRequest,
-    CreateIndexResponse, CreateIndexTemplateRequest, DeleteIndexRequest,
-    DeleteIndexTemplatesRequest, DeleteQuery, DeleteShardsRequest, DeleteShardsResponse,
-    DeleteSourceRequest, DeleteSplitsRequest, DeleteTask, EmptyResponse,
-    FindIndexTemplateMatchesRequest, FindIndexTemplateMatchesResponse, GetClusterIdentityRequest,
-    GetClusterIdentityResponse, GetIndexTemplateRequest, GetIndexTemplateResponse,
-    IndexMetadataRequest, IndexMetadataResponse, IndexesMetadataRequest, IndexesMetadataResponse,
-    LastDeleteOpstampRequest, LastDeleteOpstampResponse, ListDeleteTasksRequest,
-    ListDeleteTasksResponse, ListIndexTemplatesRequest, ListIndexTemplatesResponse,
-    ListIndexesMetadataRequest, ListIndexesMetadataResponse, ListShardsRequest, ListShardsResponse,
-    ListSplitsRequest, ListSplitsResponse, ListStaleSplitsRequest, MarkSplitsForDeletionRequest,
-    MetastoreResult, MetastoreService, MetastoreServiceClient, MetastoreServiceStream,
-    OpenShardsRequest, OpenShardsResponse, PruneShardsRequest, PublishSplitsRequest,
-    ResetSourceCheckpointRequest, StageSplitsRequest, ToggleSourceRequest, UpdateIndexRequest,
-    UpdateSourceRequest, UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse,
+    AcquireShardsRequest, AcquireShardsResponse, AddSourceRequest, CreateIndexAliasRequest,
+    CreateIndexRequest, CreateIndexResponse, CreateIndexTemplateRequest, DeleteIndexAliasesRequest,
+    DeleteIndexRequest, DeleteIndexTemplatesRequest, DeletePointInTimeLeaseRequest, DeleteQuery,
+    DeleteShardsRequest, DeleteShardsResponse, DeleteSourceRequest, DeleteSplitsRequest,
+    DeleteTask, EmptyResponse, FindIndexTemplateMatchesRequest, FindIndexTemplateMatchesResponse,
+    GetClusterIdentityRequest, GetClusterIdentityResponse, GetIndexTemplateRequest,
+    GetIndexTemplateResponse, IndexMetadataRequest, IndexMetadataResponse, IndexesMetadataRequest,
+    IndexesMetadataResponse, LastDeleteOpstampRequest, LastDeleteOpstampResponse,
+    ListDeleteTasksRequest, ListDeleteTasksResponse, ListIndexAliasesRequest,
+    ListIndexAliasesResponse, ListIndexTemplatesRequest, ListIndexTemplatesResponse,
+    ListIndexesMetadataRequest, ListIndexesMetadataResponse, ListPointInTimeLeasesRequest,
+    ListPointInTimeLeasesResponse, ListShardsRequest, ListShardsResponse, ListSplitsRequest,
+    ListSplitsResponse, ListStaleSplitsRequest, MarkSplitsForDeletionRequest, MetastoreResult,
+    MetastoreService, MetastoreServiceClient, MetastoreServiceStream, OpenShardsRequest,
+    OpenShardsResponse, PruneShardsRequest, PublishSplitsRequest, PutPointInTimeLeaseRequest,
+    ResetSourceCheckpointRequest, StageSplitsRequest, ToggleSourceRequest,
+    UpdateIndexAliasesRequest, UpdateIndexRequest, UpdateSourceRequest,
+    UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse,
 };
 
 /// A [`MetastoreService`] implementation that proxies some requests to the control plane so it can
@@ -275,6 +278,57 @@ impl MetastoreService for ControlPlaneMetastore {
         self.metastore.delete_index_templates(request).await
     }
 
+    // Index Alias API
+
+    async fn create_index_alias(
+        &self,
+        request: CreateIndexAliasRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        self.metastore.create_index_alias(request).await
+    }
+
+    async fn list_index_aliases(
+        &self,
+        request: ListIndexAliasesRequest,
+    ) -> MetastoreResult<ListIndexAliasesResponse> {
+        self.metastore.list_index_aliases(request).await
+    }
+
+    async fn delete_index_aliases(
+        &self,
+        request: DeleteIndexAliasesRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        self.metastore.delete_index_aliases(request).await
+    }
+
+    async fn update_index_aliases(
+        &self,
+        request: UpdateIndexAliasesRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        self.metastore.update_index_aliases(request).await
+    }
+
+    async fn put_point_in_time_lease(
+        &self,
+        request: PutPointInTimeLeaseRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        self.metastore.put_point_in_time_lease(request).await
+    }
+
+    async fn list_point_in_time_leases(
+        &self,
+        request: ListPointInTimeLeasesRequest,
+    ) -> MetastoreResult<ListPointInTimeLeasesResponse> {
+        self.metastore.list_point_in_time_leases(request).await
+    }
+
+    async fn delete_point_in_time_lease(
+        &self,
+        request: DeletePointInTimeLeaseRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        self.metastore.delete_point_in_time_lease(request).await
+    }
+
     async fn get_cluster_identity(
         &self,
         request: GetClusterIdentityRequest,
@@ -17,8 +17,8 @@ use std::path::Path;
 
 use itertools::Itertools;
 use quickwit_common::uri::Uri;
-use quickwit_config::{IndexTemplate, IndexTemplateId};
-use quickwit_proto::metastore::{MetastoreError, MetastoreResult, serde_utils};
+use quickwit_config::{IndexAlias, IndexAliasId, IndexTemplate, IndexTemplateId};
+use quickwit_proto::metastore::{MetastoreError, MetastoreResult, PointInTimeLease, serde_utils};
 use quickwit_proto::types::{DocMappingUid, IndexId};
 use quickwit_storage::{OwnedBytes, Storage, StorageError, StorageErrorKind, StorageResult};
 use serde::{Deserialize, Serialize};
@@ -41,6 +41,8 @@ impl LegacyManifest {
         Manifest {
             indexes: self.indexes,
             templates: HashMap::new(),
+            aliases: HashMap::new(),
+            point_in_time_leases: HashMap::new(),
             identity: Uuid::nil(),
         }
     }
@@ -66,6 +68,10 @@ pub(crate) struct Manifest {
     // The templates are serialized as a sorted `Vec<IndexTemplate>` so the btree map is
     // unnecessary here and we can pass the hash map as is to the `MetastoreState`
     pub templates: HashMap<IndexTemplateId, IndexTemplate>,
+    // Same as above, the aliases are serialized as a sorted `Vec<IndexAlias>`.
+    pub aliases: HashMap<IndexAliasId, IndexAlias>,
+    // Same as above, the leases are serialized as a sorted `Vec<PointInTimeLease>`.
+    pub point_in_time_leases: HashMap<String, PointInTimeLease>,
     pub identity: Uuid,
 }
 
@@ -106,6 +112,10 @@ impl From<VersionedManifest> for Manifest {
 struct ManifestV0_8 {
     indexes: BTreeMap<IndexId, IndexStatus>,
     templates: Vec<IndexTemplate>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<IndexAlias>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    point_in_time_leases: Vec<PointInTimeLease>,
     #[serde(default, skip_serializing_if = "Uuid::is_nil")]
     identity: Uuid,
 }
@@ -117,9 +127,21 @@ impl From<Manifest> for ManifestV0_8 {
             .into_values()
             .sorted_unstable_by(|left, right| left.template_id.cmp(&right.template_id))
             .collect();
+        let aliases = manifest
+            .aliases
+            .into_values()
+            .sorted_unstable_by(|left, right| left.alias_id.cmp(&right.alias_id))
+            .collect();
+        let point_in_time_leases = manifest
+            .point_in_time_leases
+            .into_values()
+            .sorted_unstable_by(|left, right| left.lease_id.cmp(&right.lease_id))
+            .collect();
         ManifestV0_8 {
             indexes: manifest.indexes,
             templates,
+            aliases,
+            point_in_time_leases,
             identity: manifest.identity,
         }
     }
@@ -133,9 +155,21 @@ impl From<ManifestV0_8> for Manifest {
             .into_iter()
             .map(|template| (template.template_id.clone(), template))
             .collect();
+        let aliases = manifest
+            .aliases
+            .into_iter()
+            .map(|alias| (alias.alias_id.clone(), alias))
+            .collect();
+        let point_in_time_leases = manifest
+            .point_in_time_leases
+            .into_iter()
+            .map(|lease| (lease.lease_id.clone(), lease))
+            .collect();
         Manifest {
             indexes,
             templates,
+            aliases,
+            point_in_time_leases,
             identity: manifest.identity,
         }
     }
@@ -157,6 +191,8 @@ impl quickwit_config::TestableForRegression for Manifest {
         Manifest {
             indexes,
             templates,
+            aliases: HashMap::new(),
+            point_in_time_leases: HashMap::new(),
             identity: Uuid::nil(),
         }
     }
@@ -258,6 +294,7 @@ fn into_metastore_error(
 
 #[cfg(test)]
 mod tests {
+    use quickwit_proto::types::IndexUid;
     use serde_json::json;
 
     use super::*;
@@ -334,9 +371,24 @@ mod tests {
                 IndexTemplate::for_test("test-template-2", &["test-index-bar*"], 200),
             ),
         ]);
+        let aliases = HashMap::from_iter([(
+            "test-alias".to_string(),
+            IndexAlias::for_test("test-alias", &["test-index-1", "test-index-2"]),
+        )]);
+        let point_in_time_leases = HashMap::from_iter([(
+            "test-lease".to_string(),
+            PointInTimeLease {
+                lease_id: "test-lease".to_string(),
+                index_uids: vec![IndexUid::for_test("test-index-1", 0)],
+                opened_at: 1_700_000_000,
+                expires_at: 1_700_000_060,
+            },
+        )]);
         let manifest = Manifest {
             indexes,
             templates,
+            aliases,
+            point_in_time_leases,
             identity: Uuid::nil(),
         };
         let manifest_json = serde_json::to_string_pretty(&manifest).unwrap();
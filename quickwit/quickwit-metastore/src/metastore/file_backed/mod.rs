@@ -38,25 +38,28 @@ use futures::future::try_join_all;
 use futures::stream::FuturesUnordered;
 use itertools::Itertools;
 use quickwit_common::ServiceStream;
-use quickwit_config::IndexTemplate;
+use quickwit_config::{IndexAlias, IndexTemplate};
 use quickwit_proto::metastore::{
-    AcquireShardsRequest, AcquireShardsResponse, AddSourceRequest, CreateIndexRequest,
-    CreateIndexResponse, CreateIndexTemplateRequest, DeleteIndexRequest,
-    DeleteIndexTemplatesRequest, DeleteQuery, DeleteShardsRequest, DeleteShardsResponse,
-    DeleteSourceRequest, DeleteSplitsRequest, DeleteTask, EmptyResponse, EntityKind,
-    FindIndexTemplateMatchesRequest, FindIndexTemplateMatchesResponse, GetClusterIdentityRequest,
-    GetClusterIdentityResponse, GetIndexTemplateRequest, GetIndexTemplateResponse,
-    IndexMetadataFailure, IndexMetadataFailureReason, IndexMetadataRequest, IndexMetadataResponse,
-    IndexTemplateMatch, IndexesMetadataRequest, IndexesMetadataResponse, LastDeleteOpstampRequest,
+    AcquireShardsRequest, AcquireShardsResponse, AddSourceRequest, CreateIndexAliasRequest,
+    CreateIndexRequest, CreateIndexResponse, CreateIndexTemplateRequest, DeleteIndexAliasesRequest,
+    DeleteIndexRequest, DeleteIndexTemplatesRequest, DeletePointInTimeLeaseRequest, DeleteQuery,
+    DeleteShardsRequest, DeleteShardsResponse, DeleteSourceRequest, DeleteSplitsRequest,
+    DeleteTask, EmptyResponse, EntityKind, FindIndexTemplateMatchesRequest,
+    FindIndexTemplateMatchesResponse, GetClusterIdentityRequest, GetClusterIdentityResponse,
+    GetIndexTemplateRequest, GetIndexTemplateResponse, IndexMetadataFailure,
+    IndexMetadataFailureReason, IndexMetadataRequest, IndexMetadataResponse, IndexTemplateMatch,
+    IndexesMetadataRequest, IndexesMetadataResponse, LastDeleteOpstampRequest,
     LastDeleteOpstampResponse, ListDeleteTasksRequest, ListDeleteTasksResponse,
-    ListIndexTemplatesRequest, ListIndexTemplatesResponse, ListIndexesMetadataRequest,
-    ListIndexesMetadataResponse, ListShardsRequest, ListShardsResponse, ListSplitsRequest,
-    ListSplitsResponse, ListStaleSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError,
-    MetastoreResult, MetastoreService, MetastoreServiceStream, OpenShardSubrequest,
-    OpenShardsRequest, OpenShardsResponse, PruneShardsRequest, PublishSplitsRequest,
-    ResetSourceCheckpointRequest, StageSplitsRequest, ToggleSourceRequest, UpdateIndexRequest,
-    UpdateSourceRequest, UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse,
-    serde_utils,
+    ListIndexAliasesRequest, ListIndexAliasesResponse, ListIndexTemplatesRequest,
+    ListIndexTemplatesResponse, ListIndexesMetadataRequest, ListIndexesMetadataResponse,
+    ListPointInTimeLeasesRequest, ListPointInTimeLeasesResponse, ListShardsRequest,
+    ListShardsResponse, ListSplitsRequest, ListSplitsResponse, ListStaleSplitsRequest,
+    MarkSplitsForDeletionRequest, MetastoreError, MetastoreResult, MetastoreService,
+    MetastoreServiceStream, OpenShardSubrequest, OpenShardsRequest, OpenShardsResponse,
+    PruneShardsRequest, PublishSplitsRequest, PutPointInTimeLeaseRequest,
+    ResetSourceCheckpointRequest, StageSplitsRequest, ToggleSourceRequest,
+    UpdateIndexAliasesRequest, UpdateIndexRequest, UpdateSourceRequest,
+    UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse, serde_utils,
 };
 use quickwit_proto::types::{IndexId, IndexUid};
 use quickwit_storage::Storage;
@@ -76,7 +79,8 @@ use super::{
     AddSourceRequestExt, CreateIndexRequestExt, IndexMetadataResponseExt,
     IndexesMetadataResponseExt, ListIndexesMetadataResponseExt, ListSplitsRequestExt,
     ListSplitsResponseExt, PublishSplitsRequestExt, STREAM_SPLITS_CHUNK_SIZE,
-    StageSplitsRequestExt, UpdateIndexRequestExt, UpdateSourceRequestExt,
+    StageSplitsRequestExt, UpdateIndexAliasesRequestExt, UpdateIndexRequestExt,
+    UpdateSourceRequestExt, apply_index_alias_actions,
 };
 use crate::checkpoint::IndexCheckpointDelta;
 use crate::{IndexMetadata, ListSplitsQuery, MetastoreServiceExt, Split, SplitState};
@@ -496,6 +500,10 @@ impl MetastoreService for FileBackedMetastore {
 
         let mut state_wlock_guard = self.state.write().await;
 
+        if state_wlock_guard.aliases.contains_key(index_id) {
+            let message = format!("an index alias with ID `{index_id}` already exists");
+            return Err(MetastoreError::InvalidArgument { message });
+        }
         // Checking if index already exists is a bit tedious:
         // - first we check the index state: if it's `Active`, return `IndexAlreadyExists` error,
         //   and if it's `Creating` or `Deleting`, it's ok to override them as these are
@@ -627,12 +635,26 @@ impl MetastoreService for FileBackedMetastore {
             Ok(()) | Err(MetastoreError::NotFound(EntityKind::Index { .. }))
         ) {
             state_wlock_guard.indexes.remove(index_id);
+
+            // The aliases targeting the index are pruned and deleted if they have no targets left.
+            let previous_aliases = state_wlock_guard.aliases.clone();
+
+            for index_alias in state_wlock_guard.aliases.values_mut() {
+                index_alias
+                    .targets
+                    .retain(|target| target.index_id != *index_id);
+            }
+            state_wlock_guard
+                .aliases
+                .retain(|_, index_alias| !index_alias.targets.is_empty());
+
             let manifest = state_wlock_guard.as_manifest();
 
             if let Err(error) = save_manifest(&*self.storage, &manifest).await {
                 state_wlock_guard
                     .indexes
                     .insert(index_id.to_string(), LazyIndexStatus::Deleting);
+                state_wlock_guard.aliases = previous_aliases;
                 return Err(error);
             }
         }
@@ -1205,6 +1227,252 @@ impl MetastoreService for FileBackedMetastore {
         Ok(EmptyResponse {})
     }
 
+    // Index Alias API
+
+    async fn create_index_alias(
+        &self,
+        request: CreateIndexAliasRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let index_alias: IndexAlias = serde_utils::from_json_str(&request.index_alias_json)?;
+        let alias_id = index_alias.alias_id.clone();
+
+        index_alias
+            .validate()
+            .map_err(|error| MetastoreError::InvalidArgument {
+                message: format!("invalid index alias `{alias_id}`: `{error}`"),
+            })?;
+
+        let mut state_wlock_guard = self.state.write().await;
+
+        if state_wlock_guard.indexes.contains_key(&alias_id) {
+            let message = format!("an index with ID `{alias_id}` already exists");
+            return Err(MetastoreError::InvalidArgument { message });
+        }
+        for target in &index_alias.targets {
+            if !matches!(
+                state_wlock_guard.indexes.get(&target.index_id),
+                Some(LazyIndexStatus::Active(_))
+            ) {
+                return Err(MetastoreError::NotFound(EntityKind::Index {
+                    index_id: target.index_id.clone(),
+                }));
+            }
+        }
+        let evicted_alias_opt = match state_wlock_guard.aliases.entry(alias_id.clone()) {
+            Entry::Vacant(entry) => {
+                entry.insert(index_alias);
+                None
+            }
+            Entry::Occupied(mut entry) if request.overwrite => Some(entry.insert(index_alias)),
+            Entry::Occupied(_) => {
+                return Err(MetastoreError::AlreadyExists(EntityKind::IndexAlias {
+                    alias_id,
+                }));
+            }
+        };
+        let manifest = state_wlock_guard.as_manifest();
+        let save_result = save_manifest(&*self.storage, &manifest).await;
+
+        // Rollback on error.
+        if let Err(error) = save_result {
+            if let Some(evicted_alias) = evicted_alias_opt {
+                state_wlock_guard.aliases.insert(alias_id, evicted_alias);
+            } else {
+                state_wlock_guard.aliases.remove(&alias_id);
+            }
+            return Err(error);
+        }
+        Ok(EmptyResponse {})
+    }
+
+    async fn list_index_aliases(
+        &self,
+        request: ListIndexAliasesRequest,
+    ) -> MetastoreResult<ListIndexAliasesResponse> {
+        let inner_rlock_guard = self.state.read().await;
+
+        let index_aliases_json: Vec<String> = if request.alias_ids.is_empty() {
+            inner_rlock_guard
+                .aliases
+                .values()
+                .sorted_unstable_by(|left, right| left.alias_id.cmp(&right.alias_id))
+                .map(serde_utils::to_json_str)
+                .collect::<MetastoreResult<_>>()?
+        } else {
+            request
+                .alias_ids
+                .iter()
+                .filter_map(|alias_id| inner_rlock_guard.aliases.get(alias_id))
+                .map(serde_utils::to_json_str)
+                .collect::<MetastoreResult<_>>()?
+        };
+        let response = ListIndexAliasesResponse { index_aliases_json };
+        Ok(response)
+    }
+
+    async fn delete_index_aliases(
+        &self,
+        request: DeleteIndexAliasesRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let mut evicted_aliases = Vec::with_capacity(request.alias_ids.len());
+        let mut state_wlock_guard = self.state.write().await;
+
+        for alias_id in &request.alias_ids {
+            if let Some(evicted_alias) = state_wlock_guard.aliases.remove(alias_id) {
+                evicted_aliases.push(evicted_alias);
+            }
+        }
+        if evicted_aliases.is_empty() {
+            return Ok(EmptyResponse {});
+        }
+        let manifest = state_wlock_guard.as_manifest();
+        let save_result = save_manifest(&*self.storage, &manifest).await;
+
+        // Rollback on error.
+        if let Err(error) = save_result {
+            for evicted_alias in evicted_aliases {
+                state_wlock_guard
+                    .aliases
+                    .insert(evicted_alias.alias_id.clone(), evicted_alias);
+            }
+            return Err(error);
+        }
+        Ok(EmptyResponse {})
+    }
+
+    async fn update_index_aliases(
+        &self,
+        request: UpdateIndexAliasesRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let alias_actions = request.deserialize_alias_actions()?;
+
+        let mut state_wlock_guard = self.state.write().await;
+
+        let mut index_aliases: HashMap<String, IndexAlias> = alias_actions
+            .iter()
+            .filter_map(|alias_action| state_wlock_guard.aliases.get(alias_action.alias_id()))
+            .map(|index_alias| (index_alias.alias_id.clone(), index_alias.clone()))
+            .collect();
+        apply_index_alias_actions(&mut index_aliases, alias_actions)?;
+
+        for index_alias in index_aliases.values() {
+            if index_alias.targets.is_empty() {
+                continue;
+            }
+            if state_wlock_guard
+                .indexes
+                .contains_key(&index_alias.alias_id)
+            {
+                let message = format!("an index with ID `{}` already exists", index_alias.alias_id);
+                return Err(MetastoreError::InvalidArgument { message });
+            }
+            for target in &index_alias.targets {
+                if !matches!(
+                    state_wlock_guard.indexes.get(&target.index_id),
+                    Some(LazyIndexStatus::Active(_))
+                ) {
+                    return Err(MetastoreError::NotFound(EntityKind::Index {
+                        index_id: target.index_id.clone(),
+                    }));
+                }
+            }
+        }
+        let previous_aliases = state_wlock_guard.aliases.clone();
+
+        for (alias_id, index_alias) in index_aliases {
+            if index_alias.targets.is_empty() {
+                state_wlock_guard.aliases.remove(&alias_id);
+            } else {
+                state_wlock_guard.aliases.insert(alias_id, index_alias);
+            }
+        }
+        let manifest = state_wlock_guard.as_manifest();
+        let save_result = save_manifest(&*self.storage, &manifest).await;
+
+        // Rollback on error.
+        if let Err(error) = save_result {
+            state_wlock_guard.aliases = previous_aliases;
+            return Err(error);
+        }
+        Ok(EmptyResponse {})
+    }
+
+    // Point In Time Lease API
+
+    async fn put_point_in_time_lease(
+        &self,
+        request: PutPointInTimeLeaseRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let Some(lease) = request.lease else {
+            let message = "missing point in time lease".to_string();
+            return Err(MetastoreError::InvalidArgument { message });
+        };
+        let mut state_wlock_guard = self.state.write().await;
+        let previous_leases = state_wlock_guard.point_in_time_leases.clone();
+
+        // Expired leases are pruned along the way.
+        let current_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+        state_wlock_guard
+            .point_in_time_leases
+            .retain(|_, other_lease| other_lease.expires_at > current_timestamp);
+        state_wlock_guard
+            .point_in_time_leases
+            .insert(lease.lease_id.clone(), lease);
+
+        let manifest = state_wlock_guard.as_manifest();
+        let save_result = save_manifest(&*self.storage, &manifest).await;
+
+        // Rollback on error.
+        if let Err(error) = save_result {
+            state_wlock_guard.point_in_time_leases = previous_leases;
+            return Err(error);
+        }
+        Ok(EmptyResponse {})
+    }
+
+    async fn list_point_in_time_leases(
+        &self,
+        _request: ListPointInTimeLeasesRequest,
+    ) -> MetastoreResult<ListPointInTimeLeasesResponse> {
+        let inner_rlock_guard = self.state.read().await;
+
+        let current_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+        let leases = inner_rlock_guard
+            .point_in_time_leases
+            .values()
+            .filter(|lease| lease.expires_at > current_timestamp)
+            .sorted_unstable_by(|left, right| left.lease_id.cmp(&right.lease_id))
+            .cloned()
+            .collect();
+        let response = ListPointInTimeLeasesResponse { leases };
+        Ok(response)
+    }
+
+    async fn delete_point_in_time_lease(
+        &self,
+        request: DeletePointInTimeLeaseRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let mut state_wlock_guard = self.state.write().await;
+
+        let Some(evicted_lease) = state_wlock_guard
+            .point_in_time_leases
+            .remove(&request.lease_id)
+        else {
+            return Ok(EmptyResponse {});
+        };
+        let manifest = state_wlock_guard.as_manifest();
+        let save_result = save_manifest(&*self.storage, &manifest).await;
+
+        // Rollback on error.
+        if let Err(error) = save_result {
+            state_wlock_guard
+                .point_in_time_leases
+                .insert(evicted_lease.lease_id.clone(), evicted_lease);
+            return Err(error);
+        }
+        Ok(EmptyResponse {})
+    }
+
     // Get cluster identity api
 
     // this returns a constant uuid. on first call, it generate said uuid if it doesn't already
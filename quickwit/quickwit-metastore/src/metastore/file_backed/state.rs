@@ -16,8 +16,8 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
-use quickwit_config::{IndexTemplate, IndexTemplateId};
-use quickwit_proto::metastore::MetastoreResult;
+use quickwit_config::{IndexAlias, IndexAliasId, IndexTemplate, IndexTemplateId};
+use quickwit_proto::metastore::{MetastoreResult, PointInTimeLease};
 use quickwit_proto::types::IndexId;
 use quickwit_storage::Storage;
 use uuid::Uuid;
@@ -32,6 +32,8 @@ pub(super) struct MetastoreState {
     pub indexes: HashMap<IndexId, LazyIndexStatus>,
     pub templates: HashMap<IndexTemplateId, IndexTemplate>,
     pub template_matcher: IndexTemplateMatcher,
+    pub aliases: HashMap<IndexAliasId, IndexAlias>,
+    pub point_in_time_leases: HashMap<String, PointInTimeLease>,
     pub identity: Uuid,
 }
 
@@ -66,6 +68,8 @@ impl MetastoreState {
             indexes,
             templates: manifest.templates,
             template_matcher,
+            aliases: manifest.aliases,
+            point_in_time_leases: manifest.point_in_time_leases,
             identity: manifest.identity,
         };
         Ok(state)
@@ -85,9 +89,13 @@ impl MetastoreState {
             })
             .collect();
         let templates = self.templates.clone();
+        let aliases = self.aliases.clone();
+        let point_in_time_leases = self.point_in_time_leases.clone();
         Manifest {
             indexes,
             templates,
+            aliases,
+            point_in_time_leases,
             identity: self.identity,
         }
     }
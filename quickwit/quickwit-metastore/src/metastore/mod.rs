@@ -20,6 +20,7 @@ pub mod postgres;
 pub mod control_plane_metastore;
 
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::ops::{Bound, RangeInclusive};
 
 use async_trait::async_trait;
@@ -29,16 +30,17 @@ pub use index_metadata::IndexMetadata;
 use itertools::Itertools;
 use quickwit_common::thread_pool::run_cpu_intensive;
 use quickwit_config::{
-    DocMapping, FileSourceParams, IndexConfig, IndexingSettings, IngestSettings, RetentionPolicy,
-    SearchSettings, SourceConfig, SourceParams,
+    DocMapping, FileSourceParams, IndexAlias, IndexAliasAction, IndexAliasId, IndexConfig,
+    IndexingSettings, IngestSettings, RetentionPolicy, SearchSettings, SourceConfig, SourceParams,
 };
 use quickwit_doc_mapper::tag_pruning::TagFilterAst;
 use quickwit_proto::metastore::{
-    AddSourceRequest, CreateIndexRequest, CreateIndexResponse, DeleteTask, IndexMetadataFailure,
-    IndexMetadataRequest, IndexMetadataResponse, IndexesMetadataResponse,
-    ListIndexesMetadataResponse, ListSplitsRequest, ListSplitsResponse, MetastoreError,
-    MetastoreResult, MetastoreService, MetastoreServiceClient, MetastoreServiceStream,
-    PublishSplitsRequest, StageSplitsRequest, UpdateIndexRequest, UpdateSourceRequest, serde_utils,
+    AddSourceRequest, CreateIndexAliasRequest, CreateIndexRequest, CreateIndexResponse, DeleteTask,
+    EntityKind, IndexMetadataFailure, IndexMetadataRequest, IndexMetadataResponse,
+    IndexesMetadataResponse, ListIndexAliasesResponse, ListIndexesMetadataResponse,
+    ListSplitsRequest, ListSplitsResponse, MetastoreError, MetastoreResult, MetastoreService,
+    MetastoreServiceClient, MetastoreServiceStream, PublishSplitsRequest, StageSplitsRequest,
+    UpdateIndexAliasesRequest, UpdateIndexRequest, UpdateSourceRequest, serde_utils,
 };
 use quickwit_proto::types::{IndexUid, NodeId, SplitId};
 use time::OffsetDateTime;
@@ -401,6 +403,85 @@ impl ListIndexesMetadataResponseExt for ListIndexesMetadataResponse {
     }
 }
 
+/// Helper trait to build a [`CreateIndexAliasRequest`] and deserialize its payload.
+pub trait CreateIndexAliasRequestExt {
+    /// Creates a new [`CreateIndexAliasRequest`] from an [`IndexAlias`].
+    fn try_from_index_alias(
+        index_alias: &IndexAlias,
+        overwrite: bool,
+    ) -> MetastoreResult<CreateIndexAliasRequest>;
+
+    /// Deserializes the `index_alias_json` field of a [`CreateIndexAliasRequest`] into an
+    /// [`IndexAlias`].
+    fn deserialize_index_alias(&self) -> MetastoreResult<IndexAlias>;
+}
+
+impl CreateIndexAliasRequestExt for CreateIndexAliasRequest {
+    fn try_from_index_alias(
+        index_alias: &IndexAlias,
+        overwrite: bool,
+    ) -> MetastoreResult<CreateIndexAliasRequest> {
+        let index_alias_json = serde_utils::to_json_str(index_alias)?;
+        let request = Self {
+            index_alias_json,
+            overwrite,
+        };
+        Ok(request)
+    }
+
+    fn deserialize_index_alias(&self) -> MetastoreResult<IndexAlias> {
+        serde_utils::from_json_str(&self.index_alias_json)
+    }
+}
+
+/// Helper trait to deserialize the payload of a [`ListIndexAliasesResponse`].
+pub trait ListIndexAliasesResponseExt {
+    /// Deserializes the `index_aliases_json` field of a [`ListIndexAliasesResponse`] into a `Vec`
+    /// of [`IndexAlias`].
+    fn deserialize_index_aliases(&self) -> MetastoreResult<Vec<IndexAlias>>;
+}
+
+impl ListIndexAliasesResponseExt for ListIndexAliasesResponse {
+    fn deserialize_index_aliases(&self) -> MetastoreResult<Vec<IndexAlias>> {
+        self.index_aliases_json
+            .iter()
+            .map(|index_alias_json| serde_utils::from_json_str(index_alias_json))
+            .collect()
+    }
+}
+
+/// Helper trait to build a [`UpdateIndexAliasesRequest`] and deserialize its payload.
+pub trait UpdateIndexAliasesRequestExt {
+    /// Creates a new [`UpdateIndexAliasesRequest`] from a batch of [`IndexAliasAction`].
+    fn try_from_alias_actions(
+        alias_actions: &[IndexAliasAction],
+    ) -> MetastoreResult<UpdateIndexAliasesRequest>;
+
+    /// Deserializes the `alias_actions_json` field of a [`UpdateIndexAliasesRequest`] into a
+    /// `Vec` of [`IndexAliasAction`].
+    fn deserialize_alias_actions(&self) -> MetastoreResult<Vec<IndexAliasAction>>;
+}
+
+impl UpdateIndexAliasesRequestExt for UpdateIndexAliasesRequest {
+    fn try_from_alias_actions(
+        alias_actions: &[IndexAliasAction],
+    ) -> MetastoreResult<UpdateIndexAliasesRequest> {
+        let alias_actions_json = alias_actions
+            .iter()
+            .map(serde_utils::to_json_str)
+            .collect::<MetastoreResult<_>>()?;
+        let request = Self { alias_actions_json };
+        Ok(request)
+    }
+
+    fn deserialize_alias_actions(&self) -> MetastoreResult<Vec<IndexAliasAction>> {
+        self.alias_actions_json
+            .iter()
+            .map(|alias_action_json| serde_utils::from_json_str(alias_action_json))
+            .collect()
+    }
+}
+
 /// Helper trait to build a [`AddSourceRequest`] and deserialize its payload.
 pub trait AddSourceRequestExt {
     /// Creates a new [`AddSourceRequest`] from a [`SourceConfig`].
@@ -1036,6 +1117,49 @@ impl<T> Default for FilterRange<T> {
     }
 }
 
+/// Applies the alias actions, in order, to the aliases they reference, which must be loaded in
+/// `index_aliases` beforehand, and validates the updated aliases. The aliases left without targets
+/// must be deleted.
+fn apply_index_alias_actions(
+    index_aliases: &mut HashMap<IndexAliasId, IndexAlias>,
+    alias_actions: Vec<IndexAliasAction>,
+) -> MetastoreResult<()> {
+    for alias_action in alias_actions {
+        let alias_id = alias_action.alias_id().clone();
+        let alias_not_found_error = || {
+            MetastoreError::NotFound(EntityKind::IndexAlias {
+                alias_id: alias_id.clone(),
+            })
+        };
+        let index_alias = if let IndexAliasAction::Add { .. } = alias_action {
+            index_aliases
+                .entry(alias_id.clone())
+                .or_insert_with(|| IndexAlias {
+                    alias_id: alias_id.clone(),
+                    targets: Vec::new(),
+                })
+        } else if let Some(index_alias) = index_aliases.get_mut(&alias_id) {
+            index_alias
+        } else {
+            return Err(alias_not_found_error());
+        };
+        index_alias
+            .apply_action(alias_action)
+            .map_err(|_| alias_not_found_error())?;
+    }
+    for index_alias in index_aliases.values() {
+        if index_alias.targets.is_empty() {
+            continue;
+        }
+        index_alias
+            .validate()
+            .map_err(|error| MetastoreError::InvalidArgument {
+                message: format!("invalid index alias `{}`: `{error}`", index_alias.alias_id),
+            })?;
+    }
+    Ok(())
+}
+
 /// Maps the given source params to whether checkpoints should be stored in the index metadata
 /// (false) or the shard table (true)
 fn use_shard_api(params: &SourceParams) -> bool {
@@ -1047,6 +1171,7 @@ fn use_shard_api(params: &SourceParams) -> bool {
         SourceParams::IngestCli => false,
         SourceParams::Kafka(_) => false,
         SourceParams::Kinesis(_) => false,
+        SourceParams::Nats(_) => false,
         SourceParams::PubSub(_) => false,
         SourceParams::Pulsar(_) => false,
         SourceParams::Stdin => panic!("stdin cannot be checkpointed"),
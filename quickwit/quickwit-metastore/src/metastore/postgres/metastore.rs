@@ -23,27 +23,31 @@ use quickwit_common::pretty::PrettySample;
 use quickwit_common::uri::Uri;
 use quickwit_common::{ServiceStream, get_bool_from_env, rate_limited_error};
 use quickwit_config::{
-    IndexTemplate, IndexTemplateId, PostgresMetastoreConfig, validate_index_id_pattern,
+    IndexAlias, IndexTemplate, IndexTemplateId, PostgresMetastoreConfig, validate_index_id_pattern,
 };
 use quickwit_proto::ingest::{Shard, ShardState};
 use quickwit_proto::metastore::{
-    AcquireShardsRequest, AcquireShardsResponse, AddSourceRequest, CreateIndexRequest,
-    CreateIndexResponse, CreateIndexTemplateRequest, DeleteIndexRequest,
-    DeleteIndexTemplatesRequest, DeleteQuery, DeleteShardsRequest, DeleteShardsResponse,
-    DeleteSourceRequest, DeleteSplitsRequest, DeleteTask, EmptyResponse, EntityKind,
-    FindIndexTemplateMatchesRequest, FindIndexTemplateMatchesResponse, GetClusterIdentityRequest,
-    GetClusterIdentityResponse, GetIndexTemplateRequest, GetIndexTemplateResponse,
-    IndexMetadataFailure, IndexMetadataFailureReason, IndexMetadataRequest, IndexMetadataResponse,
-    IndexTemplateMatch, IndexesMetadataRequest, IndexesMetadataResponse, LastDeleteOpstampRequest,
+    AcquireShardsRequest, AcquireShardsResponse, AddSourceRequest, CreateIndexAliasRequest,
+    CreateIndexRequest, CreateIndexResponse, CreateIndexTemplateRequest, DeleteIndexAliasesRequest,
+    DeleteIndexRequest, DeleteIndexTemplatesRequest, DeletePointInTimeLeaseRequest, DeleteQuery,
+    DeleteShardsRequest, DeleteShardsResponse, DeleteSourceRequest, DeleteSplitsRequest,
+    DeleteTask, EmptyResponse, EntityKind, FindIndexTemplateMatchesRequest,
+    FindIndexTemplateMatchesResponse, GetClusterIdentityRequest, GetClusterIdentityResponse,
+    GetIndexTemplateRequest, GetIndexTemplateResponse, IndexMetadataFailure,
+    IndexMetadataFailureReason, IndexMetadataRequest, IndexMetadataResponse, IndexTemplateMatch,
+    IndexesMetadataRequest, IndexesMetadataResponse, LastDeleteOpstampRequest,
     LastDeleteOpstampResponse, ListDeleteTasksRequest, ListDeleteTasksResponse,
-    ListIndexTemplatesRequest, ListIndexTemplatesResponse, ListIndexesMetadataRequest,
-    ListIndexesMetadataResponse, ListShardsRequest, ListShardsResponse, ListShardsSubresponse,
-    ListSplitsRequest, ListSplitsResponse, ListStaleSplitsRequest, MarkSplitsForDeletionRequest,
-    MetastoreError, MetastoreResult, MetastoreService, MetastoreServiceStream, OpenShardSubrequest,
-    OpenShardSubresponse, OpenShardsRequest, OpenShardsResponse, PruneShardsRequest,
-    PublishSplitsRequest, ResetSourceCheckpointRequest, StageSplitsRequest, ToggleSourceRequest,
-    UpdateIndexRequest, UpdateSourceRequest, UpdateSplitsDeleteOpstampRequest,
-    UpdateSplitsDeleteOpstampResponse, serde_utils,
+    ListIndexAliasesRequest, ListIndexAliasesResponse, ListIndexTemplatesRequest,
+    ListIndexTemplatesResponse, ListIndexesMetadataRequest, ListIndexesMetadataResponse,
+    ListPointInTimeLeasesRequest, ListPointInTimeLeasesResponse, ListShardsRequest,
+    ListShardsResponse, ListShardsSubresponse, ListSplitsRequest, ListSplitsResponse,
+    ListStaleSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError, MetastoreResult,
+    MetastoreService, MetastoreServiceStream, OpenShardSubrequest, OpenShardSubresponse,
+    OpenShardsRequest, OpenShardsResponse, PointInTimeLease, PruneShardsRequest,
+    PublishSplitsRequest, PutPointInTimeLeaseRequest, ResetSourceCheckpointRequest,
+    StageSplitsRequest, ToggleSourceRequest, UpdateIndexAliasesRequest, UpdateIndexRequest,
+    UpdateSourceRequest, UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse,
+    serde_utils,
 };
 use quickwit_proto::types::{IndexId, IndexUid, Position, PublishToken, ShardId, SourceId};
 use sea_query::{Alias, Asterisk, Expr, Func, PostgresQueryBuilder, Query, UnionType};
@@ -71,7 +75,7 @@ use crate::metastore::postgres::model::Shards;
 use crate::metastore::postgres::utils::split_maturity_timestamp;
 use crate::metastore::{
     IndexesMetadataResponseExt, PublishSplitsRequestExt, STREAM_SPLITS_CHUNK_SIZE,
-    UpdateSourceRequestExt, use_shard_api,
+    UpdateIndexAliasesRequestExt, UpdateSourceRequestExt, apply_index_alias_actions, use_shard_api,
 };
 use crate::{
     AddSourceRequestExt, CreateIndexRequestExt, IndexMetadata, IndexMetadataResponseExt,
@@ -395,8 +399,13 @@ impl MetastoreService for PostgresqlMetastore {
         }
         let index_metadata_json = serde_utils::to_json_str(&index_metadata)?;
 
-        sqlx::query(
-            "INSERT INTO indexes (index_uid, index_id, index_metadata_json) VALUES ($1, $2, $3)",
+        // The index is not inserted if an index alias with the same ID exists.
+        let pg_query_result = sqlx::query(
+            r#"
+            INSERT INTO indexes (index_uid, index_id, index_metadata_json)
+                SELECT $1, $2, $3
+                WHERE NOT EXISTS (SELECT 1 FROM index_aliases WHERE alias_id = $2)
+            "#,
         )
         .bind(index_metadata.index_uid.to_string())
         .bind(&index_metadata.index_uid.index_id)
@@ -405,6 +414,14 @@ impl MetastoreService for PostgresqlMetastore {
         .await
         .map_err(|sqlx_error| convert_sqlx_err(index_metadata.index_id(), sqlx_error))?;
 
+        if pg_query_result.rows_affected() == 0 {
+            let message = format!(
+                "an index alias with ID `{}` already exists",
+                index_metadata.index_id()
+            );
+            return Err(MetastoreError::InvalidArgument { message });
+        }
+
         let response = CreateIndexResponse {
             index_uid: index_metadata.index_uid.into(),
             index_metadata_json,
@@ -566,19 +583,58 @@ impl MetastoreService for PostgresqlMetastore {
 
     #[instrument(skip_all, fields(index_id=%request.index_uid()))]
     async fn delete_index(&self, request: DeleteIndexRequest) -> MetastoreResult<EmptyResponse> {
+        const UPSERT_INDEX_ALIAS_QUERY: &str = include_str!("queries/index_aliases/upsert.sql");
+
         let index_uid: IndexUid = request.index_uid().clone();
-        let delete_result = sqlx::query("DELETE FROM indexes WHERE index_uid = $1")
-            .bind(&index_uid)
-            .execute(&self.connection_pool)
+
+        run_with_tx!(self.connection_pool, tx, "delete index", {
+            let delete_result = sqlx::query("DELETE FROM indexes WHERE index_uid = $1")
+                .bind(&index_uid)
+                .execute(tx.as_mut())
+                .await?;
+            // FIXME: This is not idempotent.
+            if delete_result.rows_affected() == 0 {
+                return Err(MetastoreError::NotFound(EntityKind::Index {
+                    index_id: index_uid.index_id.clone(),
+                }));
+            }
+            // The aliases targeting the index are pruned and deleted if they have no targets left.
+            let pg_index_aliases_json: Vec<(String,)> = sqlx::query_as(
+                r#"
+                SELECT index_alias_json
+                FROM index_aliases
+                WHERE (index_alias_json::jsonb -> 'targets')
+                    @> jsonb_build_array(jsonb_build_object('index_id', $1::text))
+                FOR UPDATE
+                "#,
+            )
+            .bind(&index_uid.index_id)
+            .fetch_all(tx.as_mut())
             .await?;
-        // FIXME: This is not idempotent.
-        if delete_result.rows_affected() == 0 {
-            return Err(MetastoreError::NotFound(EntityKind::Index {
-                index_id: index_uid.index_id,
-            }));
-        }
-        info!(index_id = index_uid.index_id, "deleted index successfully");
-        Ok(EmptyResponse {})
+
+            for (index_alias_json,) in pg_index_aliases_json {
+                let mut index_alias: IndexAlias = serde_utils::from_json_str(&index_alias_json)?;
+                index_alias
+                    .targets
+                    .retain(|target| target.index_id != index_uid.index_id);
+
+                if index_alias.targets.is_empty() {
+                    sqlx::query("DELETE FROM index_aliases WHERE alias_id = $1")
+                        .bind(&index_alias.alias_id)
+                        .execute(tx.as_mut())
+                        .await?;
+                } else {
+                    let index_alias_json = serde_utils::to_json_str(&index_alias)?;
+                    sqlx::query(UPSERT_INDEX_ALIAS_QUERY)
+                        .bind(&index_alias.alias_id)
+                        .bind(&index_alias_json)
+                        .execute(tx.as_mut())
+                        .await?;
+                }
+            }
+            info!(index_id = index_uid.index_id, "deleted index successfully");
+            Ok(EmptyResponse {})
+        })
     }
 
     #[instrument(skip_all, fields(split_ids))]
@@ -1666,6 +1722,259 @@ impl MetastoreService for PostgresqlMetastore {
         Ok(EmptyResponse {})
     }
 
+    // Index Alias API
+
+    async fn create_index_alias(
+        &self,
+        request: CreateIndexAliasRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        const INSERT_INDEX_ALIAS_QUERY: &str = include_str!("queries/index_aliases/insert.sql");
+        const UPSERT_INDEX_ALIAS_QUERY: &str = include_str!("queries/index_aliases/upsert.sql");
+
+        let index_alias: IndexAlias = serde_utils::from_json_str(&request.index_alias_json)?;
+
+        index_alias
+            .validate()
+            .map_err(|error| MetastoreError::InvalidArgument {
+                message: format!("invalid index alias `{}`: `{error}`", index_alias.alias_id),
+            })?;
+
+        let target_index_ids: Vec<&str> = index_alias
+            .targets
+            .iter()
+            .map(|target| target.index_id.as_str())
+            .collect();
+        let existing_index_ids: Vec<(IndexId,)> =
+            sqlx::query_as("SELECT index_id FROM indexes WHERE index_id = ANY($1)")
+                .bind(&target_index_ids)
+                .fetch_all(&self.connection_pool)
+                .await?;
+
+        for target_index_id in target_index_ids {
+            if !existing_index_ids
+                .iter()
+                .any(|(index_id,)| index_id == target_index_id)
+            {
+                return Err(MetastoreError::NotFound(EntityKind::Index {
+                    index_id: target_index_id.to_string(),
+                }));
+            }
+        }
+        let (alias_is_index_id,): (bool,) =
+            sqlx::query_as("SELECT EXISTS(SELECT 1 FROM indexes WHERE index_id = $1)")
+                .bind(&index_alias.alias_id)
+                .fetch_one(&self.connection_pool)
+                .await?;
+
+        if alias_is_index_id {
+            let message = format!("an index with ID `{}` already exists", index_alias.alias_id);
+            return Err(MetastoreError::InvalidArgument { message });
+        }
+        if request.overwrite {
+            sqlx::query(UPSERT_INDEX_ALIAS_QUERY)
+                .bind(&index_alias.alias_id)
+                .bind(&request.index_alias_json)
+                .execute(&self.connection_pool)
+                .await?;
+
+            return Ok(EmptyResponse {});
+        }
+        let pg_query_result = sqlx::query(INSERT_INDEX_ALIAS_QUERY)
+            .bind(&index_alias.alias_id)
+            .bind(&request.index_alias_json)
+            .execute(&self.connection_pool)
+            .await?;
+
+        if pg_query_result.rows_affected() == 0 {
+            return Err(MetastoreError::AlreadyExists(EntityKind::IndexAlias {
+                alias_id: index_alias.alias_id,
+            }));
+        }
+        Ok(EmptyResponse {})
+    }
+
+    async fn list_index_aliases(
+        &self,
+        request: ListIndexAliasesRequest,
+    ) -> MetastoreResult<ListIndexAliasesResponse> {
+        let pg_index_aliases_json: Vec<(String,)> = if request.alias_ids.is_empty() {
+            sqlx::query_as("SELECT index_alias_json FROM index_aliases ORDER BY alias_id ASC")
+                .fetch_all(&self.connection_pool)
+                .await?
+        } else {
+            sqlx::query_as(
+                "SELECT index_alias_json FROM index_aliases WHERE alias_id = ANY($1) ORDER BY \
+                 alias_id ASC",
+            )
+            .bind(&request.alias_ids)
+            .fetch_all(&self.connection_pool)
+            .await?
+        };
+        let index_aliases_json: Vec<String> = pg_index_aliases_json
+            .into_iter()
+            .map(|(index_alias_json,)| index_alias_json)
+            .collect();
+        let response = ListIndexAliasesResponse { index_aliases_json };
+        Ok(response)
+    }
+
+    async fn delete_index_aliases(
+        &self,
+        request: DeleteIndexAliasesRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        sqlx::query("DELETE FROM index_aliases WHERE alias_id = ANY($1)")
+            .bind(&request.alias_ids)
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(EmptyResponse {})
+    }
+
+    async fn update_index_aliases(
+        &self,
+        request: UpdateIndexAliasesRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        const UPSERT_INDEX_ALIAS_QUERY: &str = include_str!("queries/index_aliases/upsert.sql");
+
+        let alias_actions = request.deserialize_alias_actions()?;
+        let alias_ids: Vec<String> = alias_actions
+            .iter()
+            .map(|alias_action| alias_action.alias_id().clone())
+            .unique()
+            .collect();
+
+        run_with_tx!(self.connection_pool, tx, "update index aliases", {
+            let pg_index_aliases_json: Vec<(String,)> = sqlx::query_as(
+                "SELECT index_alias_json FROM index_aliases WHERE alias_id = ANY($1) FOR UPDATE",
+            )
+            .bind(&alias_ids)
+            .fetch_all(tx.as_mut())
+            .await?;
+
+            let mut index_aliases: HashMap<String, IndexAlias> = HashMap::new();
+
+            for (index_alias_json,) in pg_index_aliases_json {
+                let index_alias: IndexAlias = serde_utils::from_json_str(&index_alias_json)?;
+                index_aliases.insert(index_alias.alias_id.clone(), index_alias);
+            }
+            apply_index_alias_actions(&mut index_aliases, alias_actions)?;
+
+            let target_index_ids: Vec<&str> = index_aliases
+                .values()
+                .flat_map(|index_alias| &index_alias.targets)
+                .map(|target| target.index_id.as_str())
+                .unique()
+                .collect();
+            let existing_index_ids: Vec<(IndexId,)> = sqlx::query_as(
+                "SELECT index_id FROM indexes WHERE index_id = ANY($1) OR index_id = ANY($2)",
+            )
+            .bind(&target_index_ids)
+            .bind(&alias_ids)
+            .fetch_all(tx.as_mut())
+            .await?;
+
+            for target_index_id in target_index_ids {
+                if !existing_index_ids
+                    .iter()
+                    .any(|(index_id,)| index_id == target_index_id)
+                {
+                    return Err(MetastoreError::NotFound(EntityKind::Index {
+                        index_id: target_index_id.to_string(),
+                    }));
+                }
+            }
+            for (alias_id, index_alias) in index_aliases {
+                if index_alias.targets.is_empty() {
+                    sqlx::query("DELETE FROM index_aliases WHERE alias_id = $1")
+                        .bind(&alias_id)
+                        .execute(tx.as_mut())
+                        .await?;
+                    continue;
+                }
+                if existing_index_ids
+                    .iter()
+                    .any(|(index_id,)| *index_id == alias_id)
+                {
+                    let message = format!("an index with ID `{alias_id}` already exists");
+                    return Err(MetastoreError::InvalidArgument { message });
+                }
+                let index_alias_json = serde_utils::to_json_str(&index_alias)?;
+                sqlx::query(UPSERT_INDEX_ALIAS_QUERY)
+                    .bind(&alias_id)
+                    .bind(&index_alias_json)
+                    .execute(tx.as_mut())
+                    .await?;
+            }
+            Ok(())
+        })?;
+        Ok(EmptyResponse {})
+    }
+
+    // Point In Time Lease API
+
+    async fn put_point_in_time_lease(
+        &self,
+        request: PutPointInTimeLeaseRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        const UPSERT_POINT_IN_TIME_LEASE_QUERY: &str =
+            include_str!("queries/point_in_time_leases/upsert.sql");
+
+        let Some(lease) = request.lease else {
+            let message = "missing point in time lease".to_string();
+            return Err(MetastoreError::InvalidArgument { message });
+        };
+        let lease_json = serde_utils::to_json_str(&lease)?;
+        let current_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+
+        run_with_tx!(self.connection_pool, tx, "put point in time lease", {
+            // Expired leases are pruned along the way.
+            sqlx::query("DELETE FROM point_in_time_leases WHERE expires_at <= $1")
+                .bind(current_timestamp)
+                .execute(tx.as_mut())
+                .await?;
+            sqlx::query(UPSERT_POINT_IN_TIME_LEASE_QUERY)
+                .bind(&lease.lease_id)
+                .bind(&lease_json)
+                .bind(lease.expires_at)
+                .execute(tx.as_mut())
+                .await?;
+            Ok(())
+        })?;
+        Ok(EmptyResponse {})
+    }
+
+    async fn list_point_in_time_leases(
+        &self,
+        _request: ListPointInTimeLeasesRequest,
+    ) -> MetastoreResult<ListPointInTimeLeasesResponse> {
+        let current_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+
+        let pg_leases_json: Vec<(String,)> = sqlx::query_as(
+            "SELECT lease_json FROM point_in_time_leases WHERE expires_at > $1 ORDER BY lease_id \
+             ASC",
+        )
+        .bind(current_timestamp)
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        let leases: Vec<PointInTimeLease> = pg_leases_json
+            .into_iter()
+            .map(|(lease_json,)| serde_utils::from_json_str(&lease_json))
+            .collect::<MetastoreResult<_>>()?;
+        let response = ListPointInTimeLeasesResponse { leases };
+        Ok(response)
+    }
+
+    async fn delete_point_in_time_lease(
+        &self,
+        request: DeletePointInTimeLeaseRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        sqlx::query("DELETE FROM point_in_time_leases WHERE lease_id = $1")
+            .bind(&request.lease_id)
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(EmptyResponse {})
+    }
+
     async fn get_cluster_identity(
         &self,
         _: GetClusterIdentityRequest,
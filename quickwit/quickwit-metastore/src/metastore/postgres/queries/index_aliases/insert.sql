@@ -0,0 +1,4 @@
+INSERT INTO index_aliases(alias_id, index_alias_json)
+    VALUES ($1, $2)
+ON CONFLICT (alias_id)
+    DO NOTHING
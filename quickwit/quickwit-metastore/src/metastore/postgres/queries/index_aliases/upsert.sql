@@ -0,0 +1,5 @@
+INSERT INTO index_aliases(alias_id, index_alias_json)
+    VALUES ($1, $2)
+ON CONFLICT (alias_id)
+    DO UPDATE SET
+        index_alias_json = $2
@@ -0,0 +1,6 @@
+INSERT INTO point_in_time_leases(lease_id, lease_json, expires_at)
+    VALUES ($1, $2, $3)
+ON CONFLICT (lease_id)
+    DO UPDATE SET
+        lease_json = $2,
+        expires_at = $3
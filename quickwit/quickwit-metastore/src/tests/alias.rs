@@ -0,0 +1,389 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use quickwit_common::rand::append_random_suffix;
+use quickwit_config::{IndexAlias, IndexAliasAction, IndexAliasTarget, IndexConfig};
+use quickwit_proto::metastore::{
+    CreateIndexAliasRequest, CreateIndexRequest, DeleteIndexAliasesRequest, EntityKind,
+    ListIndexAliasesRequest, MetastoreError, MetastoreResult, MetastoreService,
+    UpdateIndexAliasesRequest,
+};
+use quickwit_proto::types::IndexUid;
+
+use super::DefaultForTest;
+use crate::tests::cleanup_index;
+use crate::{
+    CreateIndexAliasRequestExt, CreateIndexRequestExt, ListIndexAliasesResponseExt,
+    MetastoreServiceExt, UpdateIndexAliasesRequestExt,
+};
+
+async fn list_index_aliases(
+    metastore: &mut dyn MetastoreService,
+    alias_ids: Vec<String>,
+) -> MetastoreResult<Vec<IndexAlias>> {
+    let list_index_aliases_request = ListIndexAliasesRequest { alias_ids };
+    metastore
+        .list_index_aliases(list_index_aliases_request)
+        .await?
+        .deserialize_index_aliases()
+}
+
+async fn cleanup_aliases(metastore: &mut dyn MetastoreService) {
+    let alias_ids = list_index_aliases(metastore, Vec::new())
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|index_alias| index_alias.alias_id)
+        .collect::<Vec<_>>();
+
+    let delete_index_aliases_request = DeleteIndexAliasesRequest { alias_ids };
+    metastore
+        .delete_index_aliases(delete_index_aliases_request)
+        .await
+        .unwrap();
+}
+
+async fn create_index(metastore: &mut dyn MetastoreService, index_id: &str) -> IndexUid {
+    let index_uri = format!("ram:///indexes/{index_id}");
+    let index_config = IndexConfig::for_test(index_id, &index_uri);
+    let create_index_request = CreateIndexRequest::try_from_index_config(&index_config).unwrap();
+    metastore
+        .create_index(create_index_request)
+        .await
+        .unwrap()
+        .index_uid()
+        .clone()
+}
+
+fn create_index_alias_request(
+    index_alias: &IndexAlias,
+    overwrite: bool,
+) -> CreateIndexAliasRequest {
+    CreateIndexAliasRequest::try_from_index_alias(index_alias, overwrite).unwrap()
+}
+
+fn update_index_aliases_request(alias_actions: &[IndexAliasAction]) -> UpdateIndexAliasesRequest {
+    UpdateIndexAliasesRequest::try_from_alias_actions(alias_actions).unwrap()
+}
+
+pub async fn test_metastore_create_index_alias<
+    MetastoreUnderTest: MetastoreService + MetastoreServiceExt + DefaultForTest,
+>() {
+    let mut metastore = MetastoreUnderTest::default_for_test().await;
+    cleanup_aliases(&mut metastore).await;
+
+    let index_id_1 = append_random_suffix("test-create-alias-index");
+    let index_uid_1 = create_index(&mut metastore, &index_id_1).await;
+
+    let index_id_2 = append_random_suffix("test-create-alias-index");
+    let index_uid_2 = create_index(&mut metastore, &index_id_2).await;
+
+    let alias_id = append_random_suffix("test-create-alias");
+    let index_alias = IndexAlias::for_test(&alias_id, &[&index_id_1]);
+    metastore
+        .create_index_alias(create_index_alias_request(&index_alias, false))
+        .await
+        .unwrap();
+
+    let index_aliases = list_index_aliases(&mut metastore, Vec::new())
+        .await
+        .unwrap();
+    assert_eq!(index_aliases, [index_alias.clone()]);
+
+    let error = metastore
+        .create_index_alias(create_index_alias_request(&index_alias, false))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, MetastoreError::AlreadyExists(EntityKind::IndexAlias { alias_id: error_alias_id }) if error_alias_id == alias_id)
+    );
+
+    let mut index_alias = IndexAlias::for_test(&alias_id, &[&index_id_1, &index_id_2]);
+    index_alias.targets[1].is_write_index = true;
+    metastore
+        .create_index_alias(create_index_alias_request(&index_alias, true))
+        .await
+        .unwrap();
+
+    let index_aliases = list_index_aliases(&mut metastore, vec![alias_id.clone()])
+        .await
+        .unwrap();
+    assert_eq!(index_aliases, [index_alias]);
+
+    let index_alias = IndexAlias::for_test(&alias_id, &["test-alias-index-does-not-exist"]);
+    let error = metastore
+        .create_index_alias(create_index_alias_request(&index_alias, true))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        MetastoreError::NotFound(EntityKind::Index { .. })
+    ));
+
+    let index_alias = IndexAlias::for_test(&index_id_2, &[&index_id_1]);
+    let error = metastore
+        .create_index_alias(create_index_alias_request(&index_alias, false))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, MetastoreError::InvalidArgument { .. }));
+
+    cleanup_aliases(&mut metastore).await;
+    cleanup_index(&mut metastore, index_uid_1).await;
+    cleanup_index(&mut metastore, index_uid_2).await;
+}
+
+pub async fn test_metastore_list_index_aliases<
+    MetastoreUnderTest: MetastoreService + MetastoreServiceExt + DefaultForTest,
+>() {
+    let mut metastore = MetastoreUnderTest::default_for_test().await;
+    cleanup_aliases(&mut metastore).await;
+
+    let index_id = append_random_suffix("test-list-aliases-index");
+    let index_uid = create_index(&mut metastore, &index_id).await;
+
+    let alias_id_1 = append_random_suffix("test-list-aliases-1");
+    let index_alias_1 = IndexAlias::for_test(&alias_id_1, &[&index_id]);
+    metastore
+        .create_index_alias(create_index_alias_request(&index_alias_1, false))
+        .await
+        .unwrap();
+
+    let alias_id_2 = append_random_suffix("test-list-aliases-2");
+    let index_alias_2 = IndexAlias::for_test(&alias_id_2, &[&index_id]);
+    metastore
+        .create_index_alias(create_index_alias_request(&index_alias_2, false))
+        .await
+        .unwrap();
+
+    let index_aliases = list_index_aliases(&mut metastore, Vec::new())
+        .await
+        .unwrap();
+    assert_eq!(index_aliases.len(), 2);
+
+    let index_aliases = list_index_aliases(
+        &mut metastore,
+        vec![
+            alias_id_2.clone(),
+            "test-list-aliases-does-not-exist".to_string(),
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(index_aliases, [index_alias_2]);
+
+    cleanup_aliases(&mut metastore).await;
+    cleanup_index(&mut metastore, index_uid).await;
+}
+
+pub async fn test_metastore_delete_index_aliases<
+    MetastoreUnderTest: MetastoreService + MetastoreServiceExt + DefaultForTest,
+>() {
+    let mut metastore = MetastoreUnderTest::default_for_test().await;
+    cleanup_aliases(&mut metastore).await;
+
+    let index_id = append_random_suffix("test-delete-aliases-index");
+    let index_uid = create_index(&mut metastore, &index_id).await;
+
+    let alias_id_1 = append_random_suffix("test-delete-aliases-1");
+    let index_alias_1 = IndexAlias::for_test(&alias_id_1, &[&index_id]);
+    metastore
+        .create_index_alias(create_index_alias_request(&index_alias_1, false))
+        .await
+        .unwrap();
+
+    let alias_id_2 = append_random_suffix("test-delete-aliases-2");
+    let index_alias_2 = IndexAlias::for_test(&alias_id_2, &[&index_id]);
+    metastore
+        .create_index_alias(create_index_alias_request(&index_alias_2, false))
+        .await
+        .unwrap();
+
+    let delete_index_aliases_request = DeleteIndexAliasesRequest {
+        alias_ids: vec![
+            alias_id_1.clone(),
+            "test-delete-aliases-does-not-exist".to_string(),
+        ],
+    };
+    metastore
+        .delete_index_aliases(delete_index_aliases_request)
+        .await
+        .unwrap();
+
+    let index_aliases = list_index_aliases(&mut metastore, Vec::new())
+        .await
+        .unwrap();
+    assert_eq!(index_aliases, [index_alias_2]);
+
+    cleanup_aliases(&mut metastore).await;
+    cleanup_index(&mut metastore, index_uid).await;
+}
+
+pub async fn test_metastore_update_index_aliases<
+    MetastoreUnderTest: MetastoreService + MetastoreServiceExt + DefaultForTest,
+>() {
+    let mut metastore = MetastoreUnderTest::default_for_test().await;
+    cleanup_aliases(&mut metastore).await;
+
+    let index_id_1 = append_random_suffix("test-update-aliases-index");
+    let index_uid_1 = create_index(&mut metastore, &index_id_1).await;
+
+    let index_id_2 = append_random_suffix("test-update-aliases-index");
+    let index_uid_2 = create_index(&mut metastore, &index_id_2).await;
+
+    let alias_id_1 = append_random_suffix("test-update-aliases-1");
+    let alias_id_2 = append_random_suffix("test-update-aliases-2");
+
+    let add_action = |alias_id: &str, index_id: &str, is_write_index: bool| IndexAliasAction::Add {
+        alias_id: alias_id.to_string(),
+        target: IndexAliasTarget {
+            index_id: index_id.to_string(),
+            filter: None,
+            is_write_index,
+        },
+    };
+    let remove_action = |alias_id: &str, index_id: &str| IndexAliasAction::Remove {
+        alias_id: alias_id.to_string(),
+        index_id: index_id.to_string(),
+    };
+    let alias_actions = [
+        add_action(&alias_id_1, &index_id_1, false),
+        add_action(&alias_id_1, &index_id_2, true),
+        add_action(&alias_id_2, &index_id_1, false),
+    ];
+    metastore
+        .update_index_aliases(update_index_aliases_request(&alias_actions))
+        .await
+        .unwrap();
+
+    let mut expected_index_alias_1 = IndexAlias::for_test(&alias_id_1, &[&index_id_1, &index_id_2]);
+    expected_index_alias_1.targets[1].is_write_index = true;
+    let expected_index_alias_2 = IndexAlias::for_test(&alias_id_2, &[&index_id_1]);
+
+    let index_aliases = list_index_aliases(&mut metastore, Vec::new())
+        .await
+        .unwrap();
+    assert_eq!(index_aliases.len(), 2);
+    assert!(index_aliases.contains(&expected_index_alias_1));
+    assert!(index_aliases.contains(&expected_index_alias_2));
+
+    // The actions are applied atomically: the failed removal cancels the other actions.
+    let alias_actions = [
+        remove_action(&alias_id_2, &index_id_1),
+        remove_action(&alias_id_1, "test-update-aliases-index-does-not-exist"),
+    ];
+    let error = metastore
+        .update_index_aliases(update_index_aliases_request(&alias_actions))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, MetastoreError::NotFound(EntityKind::IndexAlias { alias_id: error_alias_id }) if error_alias_id == alias_id_1)
+    );
+    let index_aliases = list_index_aliases(&mut metastore, vec![alias_id_2.clone()])
+        .await
+        .unwrap();
+    assert_eq!(index_aliases, [expected_index_alias_2]);
+
+    let alias_actions = [add_action(
+        &alias_id_2,
+        "test-update-aliases-index-does-not-exist",
+        false,
+    )];
+    let error = metastore
+        .update_index_aliases(update_index_aliases_request(&alias_actions))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        MetastoreError::NotFound(EntityKind::Index { .. })
+    ));
+
+    let alias_actions = [add_action(&index_id_2, &index_id_1, false)];
+    let error = metastore
+        .update_index_aliases(update_index_aliases_request(&alias_actions))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, MetastoreError::InvalidArgument { .. }));
+
+    // Removing the last target of an alias deletes it.
+    let alias_actions = [
+        remove_action(&alias_id_1, &index_id_2),
+        remove_action(&alias_id_2, &index_id_1),
+    ];
+    metastore
+        .update_index_aliases(update_index_aliases_request(&alias_actions))
+        .await
+        .unwrap();
+
+    let index_aliases = list_index_aliases(&mut metastore, Vec::new())
+        .await
+        .unwrap();
+    assert_eq!(
+        index_aliases,
+        [IndexAlias::for_test(&alias_id_1, &[&index_id_1])]
+    );
+
+    cleanup_aliases(&mut metastore).await;
+    cleanup_index(&mut metastore, index_uid_1).await;
+    cleanup_index(&mut metastore, index_uid_2).await;
+}
+
+pub async fn test_metastore_index_and_alias_lifecycles<
+    MetastoreUnderTest: MetastoreService + MetastoreServiceExt + DefaultForTest,
+>() {
+    let mut metastore = MetastoreUnderTest::default_for_test().await;
+    cleanup_aliases(&mut metastore).await;
+
+    let index_id_1 = append_random_suffix("test-alias-lifecycle-index");
+    let index_uid_1 = create_index(&mut metastore, &index_id_1).await;
+
+    let index_id_2 = append_random_suffix("test-alias-lifecycle-index");
+    let index_uid_2 = create_index(&mut metastore, &index_id_2).await;
+
+    let alias_id_1 = append_random_suffix("test-alias-lifecycle-1");
+    let index_alias_1 = IndexAlias::for_test(&alias_id_1, &[&index_id_1, &index_id_2]);
+    metastore
+        .create_index_alias(create_index_alias_request(&index_alias_1, false))
+        .await
+        .unwrap();
+
+    let alias_id_2 = append_random_suffix("test-alias-lifecycle-2");
+    let index_alias_2 = IndexAlias::for_test(&alias_id_2, &[&index_id_1]);
+    metastore
+        .create_index_alias(create_index_alias_request(&index_alias_2, false))
+        .await
+        .unwrap();
+
+    // An index cannot be created with the ID of an alias.
+    let index_config = IndexConfig::for_test(&alias_id_1, "ram:///indexes/test-alias-lifecycle");
+    let create_index_request = CreateIndexRequest::try_from_index_config(&index_config).unwrap();
+    let error = metastore
+        .create_index(create_index_request)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, MetastoreError::InvalidArgument { .. }));
+
+    // Deleting an index prunes the aliases targeting it.
+    cleanup_index(&mut metastore, index_uid_1).await;
+
+    let index_aliases = list_index_aliases(&mut metastore, Vec::new())
+        .await
+        .unwrap();
+    assert_eq!(
+        index_aliases,
+        [IndexAlias::for_test(&alias_id_1, &[&index_id_2])]
+    );
+
+    cleanup_aliases(&mut metastore).await;
+    cleanup_index(&mut metastore, index_uid_2).await;
+}
@@ -215,6 +215,7 @@ pub async fn test_metastore_update_search_settings<
     ] {
         let search_settings = SearchSettings {
             default_search_fields: default_search_fields.clone(),
+            ..Default::default()
         };
         let index_update = UpdateIndexRequest::try_from_updates(
             index_uid.clone(),
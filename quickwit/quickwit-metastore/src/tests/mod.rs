@@ -25,10 +25,12 @@ use quickwit_proto::metastore::{
 use quickwit_proto::tonic::transport::Channel;
 use quickwit_proto::types::IndexUid;
 
+pub(crate) mod alias;
 pub(crate) mod delete_task;
 pub(crate) mod get_identity;
 pub(crate) mod index;
 pub(crate) mod list_splits;
+pub(crate) mod point_in_time_lease;
 pub(crate) mod shard;
 pub(crate) mod source;
 pub(crate) mod split;
@@ -554,6 +556,52 @@ macro_rules! metastore_test_suite {
                 $crate::tests::template::test_metastore_delete_index_templates::<$metastore_type>().await;
             }
 
+            /// Index Alias API tests
+
+            #[tokio::test]
+            #[serial_test::file_serial]
+            async fn test_metastore_create_index_alias() {
+                $crate::tests::alias::test_metastore_create_index_alias::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            #[serial_test::file_serial]
+            async fn test_metastore_list_index_aliases() {
+                $crate::tests::alias::test_metastore_list_index_aliases::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            #[serial_test::file_serial]
+            async fn test_metastore_delete_index_aliases() {
+                $crate::tests::alias::test_metastore_delete_index_aliases::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            #[serial_test::file_serial]
+            async fn test_metastore_update_index_aliases() {
+                $crate::tests::alias::test_metastore_update_index_aliases::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            #[serial_test::file_serial]
+            async fn test_metastore_index_and_alias_lifecycles() {
+                $crate::tests::alias::test_metastore_index_and_alias_lifecycles::<$metastore_type>().await;
+            }
+
+            /// Point In Time Lease API tests
+
+            #[tokio::test]
+            #[serial_test::file_serial]
+            async fn test_metastore_put_point_in_time_lease() {
+                $crate::tests::point_in_time_lease::test_metastore_put_point_in_time_lease::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            #[serial_test::file_serial]
+            async fn test_metastore_delete_point_in_time_lease() {
+                $crate::tests::point_in_time_lease::test_metastore_delete_point_in_time_lease::<$metastore_type>().await;
+            }
+
             #[tokio::test]
             #[serial_test::file_serial]
             async fn test_metastore_get_identity() {
@@ -0,0 +1,130 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use quickwit_proto::metastore::{
+    DeletePointInTimeLeaseRequest, ListPointInTimeLeasesRequest, MetastoreService,
+    PointInTimeLease, PutPointInTimeLeaseRequest,
+};
+use quickwit_proto::types::IndexUid;
+use time::OffsetDateTime;
+
+use super::DefaultForTest;
+use crate::MetastoreServiceExt;
+
+async fn list_point_in_time_leases(metastore: &mut dyn MetastoreService) -> Vec<PointInTimeLease> {
+    metastore
+        .list_point_in_time_leases(ListPointInTimeLeasesRequest {})
+        .await
+        .unwrap()
+        .leases
+}
+
+async fn put_point_in_time_lease(metastore: &mut dyn MetastoreService, lease: &PointInTimeLease) {
+    let put_lease_request = PutPointInTimeLeaseRequest {
+        lease: Some(lease.clone()),
+    };
+    metastore
+        .put_point_in_time_lease(put_lease_request)
+        .await
+        .unwrap();
+}
+
+async fn cleanup_point_in_time_leases(metastore: &mut dyn MetastoreService) {
+    for lease in list_point_in_time_leases(metastore).await {
+        let delete_lease_request = DeletePointInTimeLeaseRequest {
+            lease_id: lease.lease_id,
+        };
+        metastore
+            .delete_point_in_time_lease(delete_lease_request)
+            .await
+            .unwrap();
+    }
+}
+
+fn point_in_time_lease_for_test(lease_id: &str, expires_at: i64) -> PointInTimeLease {
+    PointInTimeLease {
+        lease_id: lease_id.to_string(),
+        index_uids: vec![IndexUid::for_test("test-index", 0)],
+        opened_at: expires_at - 60,
+        expires_at,
+    }
+}
+
+pub async fn test_metastore_put_point_in_time_lease<
+    MetastoreUnderTest: MetastoreService + MetastoreServiceExt + DefaultForTest,
+>() {
+    let mut metastore = MetastoreUnderTest::default_for_test().await;
+    cleanup_point_in_time_leases(&mut metastore).await;
+
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+
+    let lease_foo = point_in_time_lease_for_test("test-lease-foo", now + 60);
+    put_point_in_time_lease(&mut metastore, &lease_foo).await;
+
+    let leases = list_point_in_time_leases(&mut metastore).await;
+    assert_eq!(leases, [lease_foo.clone()]);
+
+    // Putting a lease again extends it.
+    let extended_lease_foo = point_in_time_lease_for_test("test-lease-foo", now + 120);
+    put_point_in_time_lease(&mut metastore, &extended_lease_foo).await;
+
+    let leases = list_point_in_time_leases(&mut metastore).await;
+    assert_eq!(leases, [extended_lease_foo.clone()]);
+
+    // Expired leases are not listed.
+    let expired_lease_bar = point_in_time_lease_for_test("test-lease-bar", now - 1);
+    put_point_in_time_lease(&mut metastore, &expired_lease_bar).await;
+
+    let leases = list_point_in_time_leases(&mut metastore).await;
+    assert_eq!(leases, [extended_lease_foo]);
+
+    cleanup_point_in_time_leases(&mut metastore).await;
+}
+
+pub async fn test_metastore_delete_point_in_time_lease<
+    MetastoreUnderTest: MetastoreService + MetastoreServiceExt + DefaultForTest,
+>() {
+    let mut metastore = MetastoreUnderTest::default_for_test().await;
+    cleanup_point_in_time_leases(&mut metastore).await;
+
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+
+    let lease_foo = point_in_time_lease_for_test("test-lease-foo", now + 60);
+    put_point_in_time_lease(&mut metastore, &lease_foo).await;
+
+    let lease_bar = point_in_time_lease_for_test("test-lease-bar", now + 60);
+    put_point_in_time_lease(&mut metastore, &lease_bar).await;
+
+    let delete_lease_request = DeletePointInTimeLeaseRequest {
+        lease_id: "test-lease-foo".to_string(),
+    };
+    metastore
+        .delete_point_in_time_lease(delete_lease_request)
+        .await
+        .unwrap();
+
+    let leases = list_point_in_time_leases(&mut metastore).await;
+    assert_eq!(leases, [lease_bar]);
+
+    // Deleting a lease that does not exist is a no-op.
+    let delete_lease_request = DeletePointInTimeLeaseRequest {
+        lease_id: "test-lease-foo".to_string(),
+    };
+    metastore
+        .delete_point_in_time_lease(delete_lease_request)
+        .await
+        .unwrap();
+
+    cleanup_point_in_time_leases(&mut metastore).await;
+}
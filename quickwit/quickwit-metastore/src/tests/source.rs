@@ -58,6 +58,8 @@ pub async fn test_metastore_add_source<MetastoreToTest: MetastoreServiceExt + De
         source_params: SourceParams::void(),
         transform_config: None,
         input_format: SourceInputFormat::Json,
+        dead_letter: None,
+        input_schema: None,
     };
 
     assert_eq!(
@@ -158,6 +160,8 @@ pub async fn test_metastore_update_source<MetastoreToTest: MetastoreServiceExt +
         source_params: SourceParams::void(),
         transform_config: None,
         input_format: SourceInputFormat::Json,
+        dead_letter: None,
+        input_schema: None,
     };
 
     assert_eq!(
@@ -263,6 +267,8 @@ pub async fn test_metastore_toggle_source<MetastoreToTest: MetastoreServiceExt +
         source_params: SourceParams::void(),
         transform_config: None,
         input_format: SourceInputFormat::Json,
+        dead_letter: None,
+        input_schema: None,
     };
     let add_source_request =
         AddSourceRequest::try_from_source_config(index_uid.clone(), &source).unwrap();
@@ -329,6 +335,8 @@ pub async fn test_metastore_delete_source<MetastoreToTest: MetastoreServiceExt +
         source_params: SourceParams::void(),
         transform_config: None,
         input_format: SourceInputFormat::Json,
+        dead_letter: None,
+        input_schema: None,
     };
 
     let index_config = IndexConfig::for_test(&index_id, index_uri.as_str());
@@ -450,6 +458,8 @@ pub async fn test_metastore_reset_checkpoint<
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            dead_letter: None,
+            input_schema: None,
         };
         metastore
             .add_source(
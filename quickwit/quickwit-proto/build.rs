@@ -109,6 +109,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .field_attribute("DeleteQuery.index_uid", "#[schema(value_type = String)]")
         .field_attribute("DeleteQuery.index_uid", "#[serde(alias = \"index_id\")]")
         .field_attribute("DeleteQuery.query_ast", "#[serde(alias = \"query\")]")
+        .field_attribute(
+            "PointInTimeLease.index_uids",
+            "#[schema(value_type = Vec<String>)]",
+        )
         .field_attribute(
             "DeleteQuery.start_timestamp",
             "#[serde(skip_serializing_if = \"Option::is_none\")]",
@@ -199,9 +203,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .type_attribute("PartialHit", "#[derive(Eq, Hash)]")
         .type_attribute("PartialHit.sort_value", "#[derive(Copy)]")
         .type_attribute("SearchRequest", "#[derive(Eq, Hash)]")
+        .type_attribute("SnippetOptions", "#[derive(Eq, Hash)]")
+        .type_attribute("SourceFilter", "#[derive(Eq, Hash)]")
+        .type_attribute("PointInTime", "#[derive(Eq, Hash)]")
         .type_attribute("ListFieldSerialized", "#[derive(Eq)]")
         .type_attribute("SortByValue", "#[derive(Ord, PartialOrd)]")
         .type_attribute("SortField", "#[derive(Eq, Hash)]")
+        .type_attribute("CollapseRequest", "#[derive(Eq, Hash)]")
         .out_dir("src/codegen/quickwit")
         .compile_protos_with_config(prost_config, &["protos/quickwit/search.proto"], &["protos"])?;
 
@@ -488,6 +488,76 @@ pub struct DeleteIndexTemplatesRequest {
     pub template_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateIndexAliasRequest {
+    #[prost(string, tag = "1")]
+    pub index_alias_json: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub overwrite: bool,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListIndexAliasesRequest {
+    /// Lists all the index aliases when empty.
+    #[prost(string, repeated, tag = "1")]
+    pub alias_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListIndexAliasesResponse {
+    #[prost(string, repeated, tag = "1")]
+    pub index_aliases_json: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteIndexAliasesRequest {
+    #[prost(string, repeated, tag = "1")]
+    pub alias_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateIndexAliasesRequest {
+    /// Applied in order.
+    #[prost(string, repeated, tag = "1")]
+    pub alias_actions_json: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PointInTimeLease {
+    #[prost(string, tag = "1")]
+    pub lease_id: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "2")]
+    #[schema(value_type = Vec<String>)]
+    pub index_uids: ::prost::alloc::vec::Vec<crate::types::IndexUid>,
+    /// Unix timestamp, in seconds, at which the point in time was opened.
+    #[prost(int64, tag = "3")]
+    pub opened_at: i64,
+    /// Unix timestamp, in seconds, after which the point in time can no longer be searched.
+    #[prost(int64, tag = "4")]
+    pub expires_at: i64,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PutPointInTimeLeaseRequest {
+    #[prost(message, optional, tag = "1")]
+    pub lease: ::core::option::Option<PointInTimeLease>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ListPointInTimeLeasesRequest {}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListPointInTimeLeasesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub leases: ::prost::alloc::vec::Vec<PointInTimeLease>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeletePointInTimeLeaseRequest {
+    #[prost(string, tag = "1")]
+    pub lease_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct GetClusterIdentityRequest {}
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
@@ -752,6 +822,41 @@ impl RpcName for DeleteIndexTemplatesRequest {
         "delete_index_templates"
     }
 }
+impl RpcName for CreateIndexAliasRequest {
+    fn rpc_name() -> &'static str {
+        "create_index_alias"
+    }
+}
+impl RpcName for ListIndexAliasesRequest {
+    fn rpc_name() -> &'static str {
+        "list_index_aliases"
+    }
+}
+impl RpcName for DeleteIndexAliasesRequest {
+    fn rpc_name() -> &'static str {
+        "delete_index_aliases"
+    }
+}
+impl RpcName for UpdateIndexAliasesRequest {
+    fn rpc_name() -> &'static str {
+        "update_index_aliases"
+    }
+}
+impl RpcName for PutPointInTimeLeaseRequest {
+    fn rpc_name() -> &'static str {
+        "put_point_in_time_lease"
+    }
+}
+impl RpcName for ListPointInTimeLeasesRequest {
+    fn rpc_name() -> &'static str {
+        "list_point_in_time_leases"
+    }
+}
+impl RpcName for DeletePointInTimeLeaseRequest {
+    fn rpc_name() -> &'static str {
+        "delete_point_in_time_lease"
+    }
+}
 impl RpcName for GetClusterIdentityRequest {
     fn rpc_name() -> &'static str {
         "get_cluster_identity"
@@ -934,6 +1039,41 @@ pub trait MetastoreService: std::fmt::Debug + Send + Sync + 'static {
         &self,
         request: DeleteIndexTemplatesRequest,
     ) -> crate::metastore::MetastoreResult<EmptyResponse>;
+    /// Creates or overwrites an index alias.
+    async fn create_index_alias(
+        &self,
+        request: CreateIndexAliasRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse>;
+    /// Returns the index aliases with the given IDs, or all the index aliases if none is specified.
+    async fn list_index_aliases(
+        &self,
+        request: ListIndexAliasesRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexAliasesResponse>;
+    /// Deletes index aliases.
+    async fn delete_index_aliases(
+        &self,
+        request: DeleteIndexAliasesRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse>;
+    /// Applies a batch of index alias actions atomically. Aliases left without targets are deleted.
+    async fn update_index_aliases(
+        &self,
+        request: UpdateIndexAliasesRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse>;
+    /// Creates or extends a point in time lease. The expired leases are removed along the way.
+    async fn put_point_in_time_lease(
+        &self,
+        request: PutPointInTimeLeaseRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse>;
+    /// Returns the point in time leases that have not expired yet.
+    async fn list_point_in_time_leases(
+        &self,
+        request: ListPointInTimeLeasesRequest,
+    ) -> crate::metastore::MetastoreResult<ListPointInTimeLeasesResponse>;
+    /// Deletes a point in time lease.
+    async fn delete_point_in_time_lease(
+        &self,
+        request: DeletePointInTimeLeaseRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse>;
     /// Get cluster identity
     async fn get_cluster_identity(
         &self,
@@ -1235,6 +1375,48 @@ impl MetastoreService for MetastoreServiceClient {
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.inner.0.delete_index_templates(request).await
     }
+    async fn create_index_alias(
+        &self,
+        request: CreateIndexAliasRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner.0.create_index_alias(request).await
+    }
+    async fn list_index_aliases(
+        &self,
+        request: ListIndexAliasesRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexAliasesResponse> {
+        self.inner.0.list_index_aliases(request).await
+    }
+    async fn delete_index_aliases(
+        &self,
+        request: DeleteIndexAliasesRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner.0.delete_index_aliases(request).await
+    }
+    async fn update_index_aliases(
+        &self,
+        request: UpdateIndexAliasesRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner.0.update_index_aliases(request).await
+    }
+    async fn put_point_in_time_lease(
+        &self,
+        request: PutPointInTimeLeaseRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner.0.put_point_in_time_lease(request).await
+    }
+    async fn list_point_in_time_leases(
+        &self,
+        request: ListPointInTimeLeasesRequest,
+    ) -> crate::metastore::MetastoreResult<ListPointInTimeLeasesResponse> {
+        self.inner.0.list_point_in_time_leases(request).await
+    }
+    async fn delete_point_in_time_lease(
+        &self,
+        request: DeletePointInTimeLeaseRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner.0.delete_point_in_time_lease(request).await
+    }
     async fn get_cluster_identity(
         &self,
         request: GetClusterIdentityRequest,
@@ -1447,6 +1629,48 @@ pub mod mock_metastore_service {
         ) -> crate::metastore::MetastoreResult<super::EmptyResponse> {
             self.inner.lock().await.delete_index_templates(request).await
         }
+        async fn create_index_alias(
+            &self,
+            request: super::CreateIndexAliasRequest,
+        ) -> crate::metastore::MetastoreResult<super::EmptyResponse> {
+            self.inner.lock().await.create_index_alias(request).await
+        }
+        async fn list_index_aliases(
+            &self,
+            request: super::ListIndexAliasesRequest,
+        ) -> crate::metastore::MetastoreResult<super::ListIndexAliasesResponse> {
+            self.inner.lock().await.list_index_aliases(request).await
+        }
+        async fn delete_index_aliases(
+            &self,
+            request: super::DeleteIndexAliasesRequest,
+        ) -> crate::metastore::MetastoreResult<super::EmptyResponse> {
+            self.inner.lock().await.delete_index_aliases(request).await
+        }
+        async fn update_index_aliases(
+            &self,
+            request: super::UpdateIndexAliasesRequest,
+        ) -> crate::metastore::MetastoreResult<super::EmptyResponse> {
+            self.inner.lock().await.update_index_aliases(request).await
+        }
+        async fn put_point_in_time_lease(
+            &self,
+            request: super::PutPointInTimeLeaseRequest,
+        ) -> crate::metastore::MetastoreResult<super::EmptyResponse> {
+            self.inner.lock().await.put_point_in_time_lease(request).await
+        }
+        async fn list_point_in_time_leases(
+            &self,
+            request: super::ListPointInTimeLeasesRequest,
+        ) -> crate::metastore::MetastoreResult<super::ListPointInTimeLeasesResponse> {
+            self.inner.lock().await.list_point_in_time_leases(request).await
+        }
+        async fn delete_point_in_time_lease(
+            &self,
+            request: super::DeletePointInTimeLeaseRequest,
+        ) -> crate::metastore::MetastoreResult<super::EmptyResponse> {
+            self.inner.lock().await.delete_point_in_time_lease(request).await
+        }
         async fn get_cluster_identity(
             &self,
             request: super::GetClusterIdentityRequest,
@@ -1960,6 +2184,118 @@ impl tower::Service<DeleteIndexTemplatesRequest> for InnerMetastoreServiceClient
         Box::pin(fut)
     }
 }
+impl tower::Service<CreateIndexAliasRequest> for InnerMetastoreServiceClient {
+    type Response = EmptyResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: CreateIndexAliasRequest) -> Self::Future {
+        let svc = self.clone();
+        let fut = async move { svc.0.create_index_alias(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<ListIndexAliasesRequest> for InnerMetastoreServiceClient {
+    type Response = ListIndexAliasesResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: ListIndexAliasesRequest) -> Self::Future {
+        let svc = self.clone();
+        let fut = async move { svc.0.list_index_aliases(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<DeleteIndexAliasesRequest> for InnerMetastoreServiceClient {
+    type Response = EmptyResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: DeleteIndexAliasesRequest) -> Self::Future {
+        let svc = self.clone();
+        let fut = async move { svc.0.delete_index_aliases(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<UpdateIndexAliasesRequest> for InnerMetastoreServiceClient {
+    type Response = EmptyResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: UpdateIndexAliasesRequest) -> Self::Future {
+        let svc = self.clone();
+        let fut = async move { svc.0.update_index_aliases(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<PutPointInTimeLeaseRequest> for InnerMetastoreServiceClient {
+    type Response = EmptyResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: PutPointInTimeLeaseRequest) -> Self::Future {
+        let svc = self.clone();
+        let fut = async move { svc.0.put_point_in_time_lease(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<ListPointInTimeLeasesRequest> for InnerMetastoreServiceClient {
+    type Response = ListPointInTimeLeasesResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: ListPointInTimeLeasesRequest) -> Self::Future {
+        let svc = self.clone();
+        let fut = async move { svc.0.list_point_in_time_leases(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<DeletePointInTimeLeaseRequest> for InnerMetastoreServiceClient {
+    type Response = EmptyResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: DeletePointInTimeLeaseRequest) -> Self::Future {
+        let svc = self.clone();
+        let fut = async move { svc.0.delete_point_in_time_lease(request).await };
+        Box::pin(fut)
+    }
+}
 impl tower::Service<GetClusterIdentityRequest> for InnerMetastoreServiceClient {
     type Response = GetClusterIdentityResponse;
     type Error = crate::metastore::MetastoreError;
@@ -2136,6 +2472,41 @@ struct MetastoreServiceTowerServiceStack {
         EmptyResponse,
         crate::metastore::MetastoreError,
     >,
+    create_index_alias_svc: quickwit_common::tower::BoxService<
+        CreateIndexAliasRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
+    list_index_aliases_svc: quickwit_common::tower::BoxService<
+        ListIndexAliasesRequest,
+        ListIndexAliasesResponse,
+        crate::metastore::MetastoreError,
+    >,
+    delete_index_aliases_svc: quickwit_common::tower::BoxService<
+        DeleteIndexAliasesRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
+    update_index_aliases_svc: quickwit_common::tower::BoxService<
+        UpdateIndexAliasesRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
+    put_point_in_time_lease_svc: quickwit_common::tower::BoxService<
+        PutPointInTimeLeaseRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
+    list_point_in_time_leases_svc: quickwit_common::tower::BoxService<
+        ListPointInTimeLeasesRequest,
+        ListPointInTimeLeasesResponse,
+        crate::metastore::MetastoreError,
+    >,
+    delete_point_in_time_lease_svc: quickwit_common::tower::BoxService<
+        DeletePointInTimeLeaseRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
     get_cluster_identity_svc: quickwit_common::tower::BoxService<
         GetClusterIdentityRequest,
         GetClusterIdentityResponse,
@@ -2330,6 +2701,48 @@ impl MetastoreService for MetastoreServiceTowerServiceStack {
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.delete_index_templates_svc.clone().ready().await?.call(request).await
     }
+    async fn create_index_alias(
+        &self,
+        request: CreateIndexAliasRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.create_index_alias_svc.clone().ready().await?.call(request).await
+    }
+    async fn list_index_aliases(
+        &self,
+        request: ListIndexAliasesRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexAliasesResponse> {
+        self.list_index_aliases_svc.clone().ready().await?.call(request).await
+    }
+    async fn delete_index_aliases(
+        &self,
+        request: DeleteIndexAliasesRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.delete_index_aliases_svc.clone().ready().await?.call(request).await
+    }
+    async fn update_index_aliases(
+        &self,
+        request: UpdateIndexAliasesRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.update_index_aliases_svc.clone().ready().await?.call(request).await
+    }
+    async fn put_point_in_time_lease(
+        &self,
+        request: PutPointInTimeLeaseRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.put_point_in_time_lease_svc.clone().ready().await?.call(request).await
+    }
+    async fn list_point_in_time_leases(
+        &self,
+        request: ListPointInTimeLeasesRequest,
+    ) -> crate::metastore::MetastoreResult<ListPointInTimeLeasesResponse> {
+        self.list_point_in_time_leases_svc.clone().ready().await?.call(request).await
+    }
+    async fn delete_point_in_time_lease(
+        &self,
+        request: DeletePointInTimeLeaseRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.delete_point_in_time_lease_svc.clone().ready().await?.call(request).await
+    }
     async fn get_cluster_identity(
         &self,
         request: GetClusterIdentityRequest,
@@ -2653,26 +3066,96 @@ type DeleteIndexTemplatesLayer = quickwit_common::tower::BoxLayer<
     EmptyResponse,
     crate::metastore::MetastoreError,
 >;
-type GetClusterIdentityLayer = quickwit_common::tower::BoxLayer<
+type CreateIndexAliasLayer = quickwit_common::tower::BoxLayer<
     quickwit_common::tower::BoxService<
-        GetClusterIdentityRequest,
-        GetClusterIdentityResponse,
+        CreateIndexAliasRequest,
+        EmptyResponse,
         crate::metastore::MetastoreError,
     >,
-    GetClusterIdentityRequest,
-    GetClusterIdentityResponse,
+    CreateIndexAliasRequest,
+    EmptyResponse,
     crate::metastore::MetastoreError,
 >;
-#[derive(Debug, Default)]
-pub struct MetastoreServiceTowerLayerStack {
-    create_index_layers: Vec<CreateIndexLayer>,
-    update_index_layers: Vec<UpdateIndexLayer>,
-    index_metadata_layers: Vec<IndexMetadataLayer>,
-    indexes_metadata_layers: Vec<IndexesMetadataLayer>,
-    list_indexes_metadata_layers: Vec<ListIndexesMetadataLayer>,
-    delete_index_layers: Vec<DeleteIndexLayer>,
-    list_splits_layers: Vec<ListSplitsLayer>,
-    stage_splits_layers: Vec<StageSplitsLayer>,
+type ListIndexAliasesLayer = quickwit_common::tower::BoxLayer<
+    quickwit_common::tower::BoxService<
+        ListIndexAliasesRequest,
+        ListIndexAliasesResponse,
+        crate::metastore::MetastoreError,
+    >,
+    ListIndexAliasesRequest,
+    ListIndexAliasesResponse,
+    crate::metastore::MetastoreError,
+>;
+type DeleteIndexAliasesLayer = quickwit_common::tower::BoxLayer<
+    quickwit_common::tower::BoxService<
+        DeleteIndexAliasesRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
+    DeleteIndexAliasesRequest,
+    EmptyResponse,
+    crate::metastore::MetastoreError,
+>;
+type UpdateIndexAliasesLayer = quickwit_common::tower::BoxLayer<
+    quickwit_common::tower::BoxService<
+        UpdateIndexAliasesRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
+    UpdateIndexAliasesRequest,
+    EmptyResponse,
+    crate::metastore::MetastoreError,
+>;
+type PutPointInTimeLeaseLayer = quickwit_common::tower::BoxLayer<
+    quickwit_common::tower::BoxService<
+        PutPointInTimeLeaseRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
+    PutPointInTimeLeaseRequest,
+    EmptyResponse,
+    crate::metastore::MetastoreError,
+>;
+type ListPointInTimeLeasesLayer = quickwit_common::tower::BoxLayer<
+    quickwit_common::tower::BoxService<
+        ListPointInTimeLeasesRequest,
+        ListPointInTimeLeasesResponse,
+        crate::metastore::MetastoreError,
+    >,
+    ListPointInTimeLeasesRequest,
+    ListPointInTimeLeasesResponse,
+    crate::metastore::MetastoreError,
+>;
+type DeletePointInTimeLeaseLayer = quickwit_common::tower::BoxLayer<
+    quickwit_common::tower::BoxService<
+        DeletePointInTimeLeaseRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
+    DeletePointInTimeLeaseRequest,
+    EmptyResponse,
+    crate::metastore::MetastoreError,
+>;
+type GetClusterIdentityLayer = quickwit_common::tower::BoxLayer<
+    quickwit_common::tower::BoxService<
+        GetClusterIdentityRequest,
+        GetClusterIdentityResponse,
+        crate::metastore::MetastoreError,
+    >,
+    GetClusterIdentityRequest,
+    GetClusterIdentityResponse,
+    crate::metastore::MetastoreError,
+>;
+#[derive(Debug, Default)]
+pub struct MetastoreServiceTowerLayerStack {
+    create_index_layers: Vec<CreateIndexLayer>,
+    update_index_layers: Vec<UpdateIndexLayer>,
+    index_metadata_layers: Vec<IndexMetadataLayer>,
+    indexes_metadata_layers: Vec<IndexesMetadataLayer>,
+    list_indexes_metadata_layers: Vec<ListIndexesMetadataLayer>,
+    delete_index_layers: Vec<DeleteIndexLayer>,
+    list_splits_layers: Vec<ListSplitsLayer>,
+    stage_splits_layers: Vec<StageSplitsLayer>,
     publish_splits_layers: Vec<PublishSplitsLayer>,
     mark_splits_for_deletion_layers: Vec<MarkSplitsForDeletionLayer>,
     delete_splits_layers: Vec<DeleteSplitsLayer>,
@@ -2696,6 +3179,13 @@ pub struct MetastoreServiceTowerLayerStack {
     find_index_template_matches_layers: Vec<FindIndexTemplateMatchesLayer>,
     list_index_templates_layers: Vec<ListIndexTemplatesLayer>,
     delete_index_templates_layers: Vec<DeleteIndexTemplatesLayer>,
+    create_index_alias_layers: Vec<CreateIndexAliasLayer>,
+    list_index_aliases_layers: Vec<ListIndexAliasesLayer>,
+    delete_index_aliases_layers: Vec<DeleteIndexAliasesLayer>,
+    update_index_aliases_layers: Vec<UpdateIndexAliasesLayer>,
+    put_point_in_time_lease_layers: Vec<PutPointInTimeLeaseLayer>,
+    list_point_in_time_leases_layers: Vec<ListPointInTimeLeasesLayer>,
+    delete_point_in_time_lease_layers: Vec<DeletePointInTimeLeaseLayer>,
     get_cluster_identity_layers: Vec<GetClusterIdentityLayer>,
 }
 impl MetastoreServiceTowerLayerStack {
@@ -3492,6 +3982,191 @@ impl MetastoreServiceTowerLayerStack {
         >>::Service as tower::Service<
             DeleteIndexTemplatesRequest,
         >>::Future: Send + 'static,
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    CreateIndexAliasRequest,
+                    EmptyResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Clone + Send + Sync + 'static,
+        <L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                CreateIndexAliasRequest,
+                EmptyResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service: tower::Service<
+                CreateIndexAliasRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <<L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                CreateIndexAliasRequest,
+                EmptyResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service as tower::Service<CreateIndexAliasRequest>>::Future: Send + 'static,
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    ListIndexAliasesRequest,
+                    ListIndexAliasesResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Clone + Send + Sync + 'static,
+        <L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                ListIndexAliasesRequest,
+                ListIndexAliasesResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service: tower::Service<
+                ListIndexAliasesRequest,
+                Response = ListIndexAliasesResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <<L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                ListIndexAliasesRequest,
+                ListIndexAliasesResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service as tower::Service<ListIndexAliasesRequest>>::Future: Send + 'static,
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    DeleteIndexAliasesRequest,
+                    EmptyResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Clone + Send + Sync + 'static,
+        <L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                DeleteIndexAliasesRequest,
+                EmptyResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service: tower::Service<
+                DeleteIndexAliasesRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <<L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                DeleteIndexAliasesRequest,
+                EmptyResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service as tower::Service<
+            DeleteIndexAliasesRequest,
+        >>::Future: Send + 'static,
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    UpdateIndexAliasesRequest,
+                    EmptyResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Clone + Send + Sync + 'static,
+        <L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                UpdateIndexAliasesRequest,
+                EmptyResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service: tower::Service<
+                UpdateIndexAliasesRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <<L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                UpdateIndexAliasesRequest,
+                EmptyResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service as tower::Service<
+            UpdateIndexAliasesRequest,
+        >>::Future: Send + 'static,
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    PutPointInTimeLeaseRequest,
+                    EmptyResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Clone + Send + Sync + 'static,
+        <L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                PutPointInTimeLeaseRequest,
+                EmptyResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service: tower::Service<
+                PutPointInTimeLeaseRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <<L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                PutPointInTimeLeaseRequest,
+                EmptyResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service as tower::Service<
+            PutPointInTimeLeaseRequest,
+        >>::Future: Send + 'static,
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    ListPointInTimeLeasesRequest,
+                    ListPointInTimeLeasesResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Clone + Send + Sync + 'static,
+        <L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                ListPointInTimeLeasesRequest,
+                ListPointInTimeLeasesResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service: tower::Service<
+                ListPointInTimeLeasesRequest,
+                Response = ListPointInTimeLeasesResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <<L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                ListPointInTimeLeasesRequest,
+                ListPointInTimeLeasesResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service as tower::Service<
+            ListPointInTimeLeasesRequest,
+        >>::Future: Send + 'static,
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    DeletePointInTimeLeaseRequest,
+                    EmptyResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Clone + Send + Sync + 'static,
+        <L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                DeletePointInTimeLeaseRequest,
+                EmptyResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service: tower::Service<
+                DeletePointInTimeLeaseRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <<L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                DeletePointInTimeLeaseRequest,
+                EmptyResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service as tower::Service<
+            DeletePointInTimeLeaseRequest,
+        >>::Future: Send + 'static,
         L: tower::Layer<
                 quickwit_common::tower::BoxService<
                     GetClusterIdentityRequest,
@@ -3582,6 +4257,20 @@ impl MetastoreServiceTowerLayerStack {
             .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
         self.delete_index_templates_layers
             .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.create_index_alias_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.list_index_aliases_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.delete_index_aliases_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.update_index_aliases_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.put_point_in_time_lease_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.list_point_in_time_leases_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.delete_point_in_time_lease_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
         self.get_cluster_identity_layers
             .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
         self
@@ -4202,59 +4891,209 @@ impl MetastoreServiceTowerLayerStack {
             .push(quickwit_common::tower::BoxLayer::new(layer));
         self
     }
-    pub fn stack_get_cluster_identity_layer<L>(mut self, layer: L) -> Self
+    pub fn stack_create_index_alias_layer<L>(mut self, layer: L) -> Self
     where
         L: tower::Layer<
                 quickwit_common::tower::BoxService<
-                    GetClusterIdentityRequest,
-                    GetClusterIdentityResponse,
+                    CreateIndexAliasRequest,
+                    EmptyResponse,
                     crate::metastore::MetastoreError,
                 >,
             > + Send + Sync + 'static,
         L::Service: tower::Service<
-                GetClusterIdentityRequest,
-                Response = GetClusterIdentityResponse,
+                CreateIndexAliasRequest,
+                Response = EmptyResponse,
                 Error = crate::metastore::MetastoreError,
             > + Clone + Send + Sync + 'static,
-        <L::Service as tower::Service<
-            GetClusterIdentityRequest,
-        >>::Future: Send + 'static,
+        <L::Service as tower::Service<CreateIndexAliasRequest>>::Future: Send + 'static,
     {
-        self.get_cluster_identity_layers
+        self.create_index_alias_layers
             .push(quickwit_common::tower::BoxLayer::new(layer));
         self
     }
-    pub fn build<T>(self, instance: T) -> MetastoreServiceClient
+    pub fn stack_list_index_aliases_layer<L>(mut self, layer: L) -> Self
     where
-        T: MetastoreService,
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    ListIndexAliasesRequest,
+                    ListIndexAliasesResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Send + Sync + 'static,
+        L::Service: tower::Service<
+                ListIndexAliasesRequest,
+                Response = ListIndexAliasesResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<ListIndexAliasesRequest>>::Future: Send + 'static,
     {
-        let inner_client = InnerMetastoreServiceClient(std::sync::Arc::new(instance));
-        self.build_from_inner_client(inner_client)
-    }
-    pub fn build_from_channel(
-        self,
-        addr: std::net::SocketAddr,
-        channel: tonic::transport::Channel,
-        max_message_size: bytesize::ByteSize,
-        compression_encoding_opt: Option<tonic::codec::CompressionEncoding>,
-    ) -> MetastoreServiceClient {
-        let client = MetastoreServiceClient::from_channel(
-            addr,
-            channel,
-            max_message_size,
-            compression_encoding_opt,
-        );
-        let inner_client = client.inner;
-        self.build_from_inner_client(inner_client)
+        self.list_index_aliases_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer));
+        self
     }
-    pub fn build_from_balance_channel(
-        self,
-        balance_channel: quickwit_common::tower::BalanceChannel<std::net::SocketAddr>,
-        max_message_size: bytesize::ByteSize,
-        compression_encoding_opt: Option<tonic::codec::CompressionEncoding>,
-    ) -> MetastoreServiceClient {
-        let client = MetastoreServiceClient::from_balance_channel(
-            balance_channel,
+    pub fn stack_delete_index_aliases_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    DeleteIndexAliasesRequest,
+                    EmptyResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Send + Sync + 'static,
+        L::Service: tower::Service<
+                DeleteIndexAliasesRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<
+            DeleteIndexAliasesRequest,
+        >>::Future: Send + 'static,
+    {
+        self.delete_index_aliases_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn stack_update_index_aliases_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    UpdateIndexAliasesRequest,
+                    EmptyResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Send + Sync + 'static,
+        L::Service: tower::Service<
+                UpdateIndexAliasesRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<
+            UpdateIndexAliasesRequest,
+        >>::Future: Send + 'static,
+    {
+        self.update_index_aliases_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn stack_put_point_in_time_lease_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    PutPointInTimeLeaseRequest,
+                    EmptyResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Send + Sync + 'static,
+        L::Service: tower::Service<
+                PutPointInTimeLeaseRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<
+            PutPointInTimeLeaseRequest,
+        >>::Future: Send + 'static,
+    {
+        self.put_point_in_time_lease_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn stack_list_point_in_time_leases_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    ListPointInTimeLeasesRequest,
+                    ListPointInTimeLeasesResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Send + Sync + 'static,
+        L::Service: tower::Service<
+                ListPointInTimeLeasesRequest,
+                Response = ListPointInTimeLeasesResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<
+            ListPointInTimeLeasesRequest,
+        >>::Future: Send + 'static,
+    {
+        self.list_point_in_time_leases_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn stack_delete_point_in_time_lease_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    DeletePointInTimeLeaseRequest,
+                    EmptyResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Send + Sync + 'static,
+        L::Service: tower::Service<
+                DeletePointInTimeLeaseRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<
+            DeletePointInTimeLeaseRequest,
+        >>::Future: Send + 'static,
+    {
+        self.delete_point_in_time_lease_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn stack_get_cluster_identity_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    GetClusterIdentityRequest,
+                    GetClusterIdentityResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Send + Sync + 'static,
+        L::Service: tower::Service<
+                GetClusterIdentityRequest,
+                Response = GetClusterIdentityResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<
+            GetClusterIdentityRequest,
+        >>::Future: Send + 'static,
+    {
+        self.get_cluster_identity_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn build<T>(self, instance: T) -> MetastoreServiceClient
+    where
+        T: MetastoreService,
+    {
+        let inner_client = InnerMetastoreServiceClient(std::sync::Arc::new(instance));
+        self.build_from_inner_client(inner_client)
+    }
+    pub fn build_from_channel(
+        self,
+        addr: std::net::SocketAddr,
+        channel: tonic::transport::Channel,
+        max_message_size: bytesize::ByteSize,
+        compression_encoding_opt: Option<tonic::codec::CompressionEncoding>,
+    ) -> MetastoreServiceClient {
+        let client = MetastoreServiceClient::from_channel(
+            addr,
+            channel,
+            max_message_size,
+            compression_encoding_opt,
+        );
+        let inner_client = client.inner;
+        self.build_from_inner_client(inner_client)
+    }
+    pub fn build_from_balance_channel(
+        self,
+        balance_channel: quickwit_common::tower::BalanceChannel<std::net::SocketAddr>,
+        max_message_size: bytesize::ByteSize,
+        compression_encoding_opt: Option<tonic::codec::CompressionEncoding>,
+    ) -> MetastoreServiceClient {
+        let client = MetastoreServiceClient::from_balance_channel(
+            balance_channel,
             max_message_size,
             compression_encoding_opt,
         );
@@ -4532,6 +5371,62 @@ impl MetastoreServiceTowerLayerStack {
                 quickwit_common::tower::BoxService::new(inner_client.clone()),
                 |svc, layer| layer.layer(svc),
             );
+        let create_index_alias_svc = self
+            .create_index_alias_layers
+            .into_iter()
+            .rev()
+            .fold(
+                quickwit_common::tower::BoxService::new(inner_client.clone()),
+                |svc, layer| layer.layer(svc),
+            );
+        let list_index_aliases_svc = self
+            .list_index_aliases_layers
+            .into_iter()
+            .rev()
+            .fold(
+                quickwit_common::tower::BoxService::new(inner_client.clone()),
+                |svc, layer| layer.layer(svc),
+            );
+        let delete_index_aliases_svc = self
+            .delete_index_aliases_layers
+            .into_iter()
+            .rev()
+            .fold(
+                quickwit_common::tower::BoxService::new(inner_client.clone()),
+                |svc, layer| layer.layer(svc),
+            );
+        let update_index_aliases_svc = self
+            .update_index_aliases_layers
+            .into_iter()
+            .rev()
+            .fold(
+                quickwit_common::tower::BoxService::new(inner_client.clone()),
+                |svc, layer| layer.layer(svc),
+            );
+        let put_point_in_time_lease_svc = self
+            .put_point_in_time_lease_layers
+            .into_iter()
+            .rev()
+            .fold(
+                quickwit_common::tower::BoxService::new(inner_client.clone()),
+                |svc, layer| layer.layer(svc),
+            );
+        let list_point_in_time_leases_svc = self
+            .list_point_in_time_leases_layers
+            .into_iter()
+            .rev()
+            .fold(
+                quickwit_common::tower::BoxService::new(inner_client.clone()),
+                |svc, layer| layer.layer(svc),
+            );
+        let delete_point_in_time_lease_svc = self
+            .delete_point_in_time_lease_layers
+            .into_iter()
+            .rev()
+            .fold(
+                quickwit_common::tower::BoxService::new(inner_client.clone()),
+                |svc, layer| layer.layer(svc),
+            );
         let get_cluster_identity_svc = self
             .get_cluster_identity_layers
             .into_iter()
@@ -4573,6 +5468,13 @@ impl MetastoreServiceTowerLayerStack {
             find_index_template_matches_svc,
             list_index_templates_svc,
             delete_index_templates_svc,
+            create_index_alias_svc,
+            list_index_aliases_svc,
+            delete_index_aliases_svc,
+            update_index_aliases_svc,
+            put_point_in_time_lease_svc,
+            list_point_in_time_leases_svc,
+            delete_point_in_time_lease_svc,
             get_cluster_identity_svc,
         };
         MetastoreServiceClient::new(tower_svc_stack)
@@ -4857,6 +5759,54 @@ where
             Error = crate::metastore::MetastoreError,
             Future = BoxFuture<EmptyResponse, crate::metastore::MetastoreError>,
         >
+        + tower::Service<
+            CreateIndexAliasRequest,
+            Response = EmptyResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<EmptyResponse, crate::metastore::MetastoreError>,
+        >
+        + tower::Service<
+            ListIndexAliasesRequest,
+            Response = ListIndexAliasesResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<
+                ListIndexAliasesResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >
+        + tower::Service<
+            DeleteIndexAliasesRequest,
+            Response = EmptyResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<EmptyResponse, crate::metastore::MetastoreError>,
+        >
+        + tower::Service<
+            UpdateIndexAliasesRequest,
+            Response = EmptyResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<EmptyResponse, crate::metastore::MetastoreError>,
+        >
+        + tower::Service<
+            PutPointInTimeLeaseRequest,
+            Response = EmptyResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<EmptyResponse, crate::metastore::MetastoreError>,
+        >
+        + tower::Service<
+            ListPointInTimeLeasesRequest,
+            Response = ListPointInTimeLeasesResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<
+                ListPointInTimeLeasesResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >
+        + tower::Service<
+            DeletePointInTimeLeaseRequest,
+            Response = EmptyResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<EmptyResponse, crate::metastore::MetastoreError>,
+        >
         + tower::Service<
             GetClusterIdentityRequest,
             Response = GetClusterIdentityResponse,
@@ -5053,6 +6003,48 @@ where
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.clone().call(request).await
     }
+    async fn create_index_alias(
+        &self,
+        request: CreateIndexAliasRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.clone().call(request).await
+    }
+    async fn list_index_aliases(
+        &self,
+        request: ListIndexAliasesRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexAliasesResponse> {
+        self.clone().call(request).await
+    }
+    async fn delete_index_aliases(
+        &self,
+        request: DeleteIndexAliasesRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.clone().call(request).await
+    }
+    async fn update_index_aliases(
+        &self,
+        request: UpdateIndexAliasesRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.clone().call(request).await
+    }
+    async fn put_point_in_time_lease(
+        &self,
+        request: PutPointInTimeLeaseRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.clone().call(request).await
+    }
+    async fn list_point_in_time_leases(
+        &self,
+        request: ListPointInTimeLeasesRequest,
+    ) -> crate::metastore::MetastoreResult<ListPointInTimeLeasesResponse> {
+        self.clone().call(request).await
+    }
+    async fn delete_point_in_time_lease(
+        &self,
+        request: DeletePointInTimeLeaseRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.clone().call(request).await
+    }
     async fn get_cluster_identity(
         &self,
         request: GetClusterIdentityRequest,
@@ -5548,29 +6540,127 @@ where
                 DeleteIndexTemplatesRequest::rpc_name(),
             ))
     }
-    async fn get_cluster_identity(
+    async fn create_index_alias(
         &self,
-        request: GetClusterIdentityRequest,
-    ) -> crate::metastore::MetastoreResult<GetClusterIdentityResponse> {
+        request: CreateIndexAliasRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.inner
             .clone()
-            .get_cluster_identity(request)
+            .create_index_alias(request)
             .await
             .map(|response| response.into_inner())
             .map_err(|status| crate::error::grpc_status_to_service_error(
                 status,
-                GetClusterIdentityRequest::rpc_name(),
+                CreateIndexAliasRequest::rpc_name(),
             ))
     }
-    async fn check_connectivity(&self) -> anyhow::Result<()> {
-        if self.connection_addrs_rx.borrow().is_empty() {
-            anyhow::bail!("no server currently available")
-        }
-        Ok(())
-    }
-    fn endpoints(&self) -> Vec<quickwit_common::uri::Uri> {
-        self.connection_addrs_rx
-            .borrow()
+    async fn list_index_aliases(
+        &self,
+        request: ListIndexAliasesRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexAliasesResponse> {
+        self.inner
+            .clone()
+            .list_index_aliases(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|status| crate::error::grpc_status_to_service_error(
+                status,
+                ListIndexAliasesRequest::rpc_name(),
+            ))
+    }
+    async fn delete_index_aliases(
+        &self,
+        request: DeleteIndexAliasesRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner
+            .clone()
+            .delete_index_aliases(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|status| crate::error::grpc_status_to_service_error(
+                status,
+                DeleteIndexAliasesRequest::rpc_name(),
+            ))
+    }
+    async fn update_index_aliases(
+        &self,
+        request: UpdateIndexAliasesRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner
+            .clone()
+            .update_index_aliases(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|status| crate::error::grpc_status_to_service_error(
+                status,
+                UpdateIndexAliasesRequest::rpc_name(),
+            ))
+    }
+    async fn put_point_in_time_lease(
+        &self,
+        request: PutPointInTimeLeaseRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner
+            .clone()
+            .put_point_in_time_lease(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|status| crate::error::grpc_status_to_service_error(
+                status,
+                PutPointInTimeLeaseRequest::rpc_name(),
+            ))
+    }
+    async fn list_point_in_time_leases(
+        &self,
+        request: ListPointInTimeLeasesRequest,
+    ) -> crate::metastore::MetastoreResult<ListPointInTimeLeasesResponse> {
+        self.inner
+            .clone()
+            .list_point_in_time_leases(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|status| crate::error::grpc_status_to_service_error(
+                status,
+                ListPointInTimeLeasesRequest::rpc_name(),
+            ))
+    }
+    async fn delete_point_in_time_lease(
+        &self,
+        request: DeletePointInTimeLeaseRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner
+            .clone()
+            .delete_point_in_time_lease(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|status| crate::error::grpc_status_to_service_error(
+                status,
+                DeletePointInTimeLeaseRequest::rpc_name(),
+            ))
+    }
+    async fn get_cluster_identity(
+        &self,
+        request: GetClusterIdentityRequest,
+    ) -> crate::metastore::MetastoreResult<GetClusterIdentityResponse> {
+        self.inner
+            .clone()
+            .get_cluster_identity(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|status| crate::error::grpc_status_to_service_error(
+                status,
+                GetClusterIdentityRequest::rpc_name(),
+            ))
+    }
+    async fn check_connectivity(&self) -> anyhow::Result<()> {
+        if self.connection_addrs_rx.borrow().is_empty() {
+            anyhow::bail!("no server currently available")
+        }
+        Ok(())
+    }
+    fn endpoints(&self) -> Vec<quickwit_common::uri::Uri> {
+        self.connection_addrs_rx
+            .borrow()
             .iter()
             .flat_map(|addr| quickwit_common::uri::Uri::from_str(
                 &format!("grpc://{addr}/{}.{}", "quickwit.metastore", "MetastoreService"),
@@ -5941,6 +7031,83 @@ for MetastoreServiceGrpcServerAdapter {
             .map(tonic::Response::new)
             .map_err(crate::error::grpc_error_to_grpc_status)
     }
+    async fn create_index_alias(
+        &self,
+        request: tonic::Request<CreateIndexAliasRequest>,
+    ) -> Result<tonic::Response<EmptyResponse>, tonic::Status> {
+        self.inner
+            .0
+            .create_index_alias(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(crate::error::grpc_error_to_grpc_status)
+    }
+    async fn list_index_aliases(
+        &self,
+        request: tonic::Request<ListIndexAliasesRequest>,
+    ) -> Result<tonic::Response<ListIndexAliasesResponse>, tonic::Status> {
+        self.inner
+            .0
+            .list_index_aliases(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(crate::error::grpc_error_to_grpc_status)
+    }
+    async fn delete_index_aliases(
+        &self,
+        request: tonic::Request<DeleteIndexAliasesRequest>,
+    ) -> Result<tonic::Response<EmptyResponse>, tonic::Status> {
+        self.inner
+            .0
+            .delete_index_aliases(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(crate::error::grpc_error_to_grpc_status)
+    }
+    async fn update_index_aliases(
+        &self,
+        request: tonic::Request<UpdateIndexAliasesRequest>,
+    ) -> Result<tonic::Response<EmptyResponse>, tonic::Status> {
+        self.inner
+            .0
+            .update_index_aliases(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(crate::error::grpc_error_to_grpc_status)
+    }
+    async fn put_point_in_time_lease(
+        &self,
+        request: tonic::Request<PutPointInTimeLeaseRequest>,
+    ) -> Result<tonic::Response<EmptyResponse>, tonic::Status> {
+        self.inner
+            .0
+            .put_point_in_time_lease(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(crate::error::grpc_error_to_grpc_status)
+    }
+    async fn list_point_in_time_leases(
+        &self,
+        request: tonic::Request<ListPointInTimeLeasesRequest>,
+    ) -> Result<tonic::Response<ListPointInTimeLeasesResponse>, tonic::Status> {
+        self.inner
+            .0
+            .list_point_in_time_leases(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(crate::error::grpc_error_to_grpc_status)
+    }
+    async fn delete_point_in_time_lease(
+        &self,
+        request: tonic::Request<DeletePointInTimeLeaseRequest>,
+    ) -> Result<tonic::Response<EmptyResponse>, tonic::Status> {
+        self.inner
+            .0
+            .delete_point_in_time_lease(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(crate::error::grpc_error_to_grpc_status)
+    }
     async fn get_cluster_identity(
         &self,
         request: tonic::Request<GetClusterIdentityRequest>,
@@ -6967,12 +8134,39 @@ pub mod metastore_service_grpc_client {
                 );
             self.inner.unary(req, path, codec).await
         }
-        /// Get cluster identity
-        pub async fn get_cluster_identity(
+        /// Creates or overwrites an index alias.
+        pub async fn create_index_alias(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetClusterIdentityRequest>,
+            request: impl tonic::IntoRequest<super::CreateIndexAliasRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/CreateIndexAlias",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "CreateIndexAlias",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Returns the index aliases with the given IDs, or all the index aliases if none is specified.
+        pub async fn list_index_aliases(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListIndexAliasesRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetClusterIdentityResponse>,
+            tonic::Response<super::ListIndexAliasesResponse>,
             tonic::Status,
         > {
             self.inner
@@ -6985,46 +8179,214 @@ pub mod metastore_service_grpc_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/quickwit.metastore.MetastoreService/GetClusterIdentity",
+                "/quickwit.metastore.MetastoreService/ListIndexAliases",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "quickwit.metastore.MetastoreService",
-                        "GetClusterIdentity",
+                        "ListIndexAliases",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-    }
-}
-/// Generated server implementations.
-pub mod metastore_service_grpc_server {
-    #![allow(
-        unused_variables,
-        dead_code,
-        missing_docs,
-        clippy::wildcard_imports,
-        clippy::let_unit_value,
-    )]
-    use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with MetastoreServiceGrpcServer.
-    #[async_trait]
-    pub trait MetastoreServiceGrpc: std::marker::Send + std::marker::Sync + 'static {
-        /// Creates an index.
-        ///
-        /// This API creates a new index in the metastore.
-        /// An error will occur if an index that already exists in the storage is specified.
-        async fn create_index(
-            &self,
-            request: tonic::Request<super::CreateIndexRequest>,
+        /// Deletes index aliases.
+        pub async fn delete_index_aliases(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteIndexAliasesRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/DeleteIndexAliases",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "DeleteIndexAliases",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Applies a batch of index alias actions atomically. Aliases left without targets are deleted.
+        pub async fn update_index_aliases(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateIndexAliasesRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/UpdateIndexAliases",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "UpdateIndexAliases",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Creates or extends a point in time lease. The expired leases are removed along the way.
+        pub async fn put_point_in_time_lease(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PutPointInTimeLeaseRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/PutPointInTimeLease",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "PutPointInTimeLease",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Returns the point in time leases that have not expired yet.
+        pub async fn list_point_in_time_leases(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListPointInTimeLeasesRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::CreateIndexResponse>,
+            tonic::Response<super::ListPointInTimeLeasesResponse>,
             tonic::Status,
-        >;
-        /// Update an index.
-        async fn update_index(
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/ListPointInTimeLeases",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "ListPointInTimeLeases",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Deletes a point in time lease.
+        pub async fn delete_point_in_time_lease(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeletePointInTimeLeaseRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/DeletePointInTimeLease",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "DeletePointInTimeLease",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Get cluster identity
+        pub async fn get_cluster_identity(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetClusterIdentityRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetClusterIdentityResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/GetClusterIdentity",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "GetClusterIdentity",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod metastore_service_grpc_server {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with MetastoreServiceGrpcServer.
+    #[async_trait]
+    pub trait MetastoreServiceGrpc: std::marker::Send + std::marker::Sync + 'static {
+        /// Creates an index.
+        ///
+        /// This API creates a new index in the metastore.
+        /// An error will occur if an index that already exists in the storage is specified.
+        async fn create_index(
+            &self,
+            request: tonic::Request<super::CreateIndexRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateIndexResponse>,
+            tonic::Status,
+        >;
+        /// Update an index.
+        async fn update_index(
             &self,
             request: tonic::Request<super::UpdateIndexRequest>,
         ) -> std::result::Result<
@@ -7237,6 +8599,47 @@ pub mod metastore_service_grpc_server {
             &self,
             request: tonic::Request<super::DeleteIndexTemplatesRequest>,
         ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status>;
+        /// Creates or overwrites an index alias.
+        async fn create_index_alias(
+            &self,
+            request: tonic::Request<super::CreateIndexAliasRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status>;
+        /// Returns the index aliases with the given IDs, or all the index aliases if none is specified.
+        async fn list_index_aliases(
+            &self,
+            request: tonic::Request<super::ListIndexAliasesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListIndexAliasesResponse>,
+            tonic::Status,
+        >;
+        /// Deletes index aliases.
+        async fn delete_index_aliases(
+            &self,
+            request: tonic::Request<super::DeleteIndexAliasesRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status>;
+        /// Applies a batch of index alias actions atomically. Aliases left without targets are deleted.
+        async fn update_index_aliases(
+            &self,
+            request: tonic::Request<super::UpdateIndexAliasesRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status>;
+        /// Creates or extends a point in time lease. The expired leases are removed along the way.
+        async fn put_point_in_time_lease(
+            &self,
+            request: tonic::Request<super::PutPointInTimeLeaseRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status>;
+        /// Returns the point in time leases that have not expired yet.
+        async fn list_point_in_time_leases(
+            &self,
+            request: tonic::Request<super::ListPointInTimeLeasesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListPointInTimeLeasesResponse>,
+            tonic::Status,
+        >;
+        /// Deletes a point in time lease.
+        async fn delete_point_in_time_lease(
+            &self,
+            request: tonic::Request<super::DeletePointInTimeLeaseRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status>;
         /// Get cluster identity
         async fn get_cluster_identity(
             &self,
@@ -8847,6 +10250,351 @@ pub mod metastore_service_grpc_server {
                     };
                     Box::pin(fut)
                 }
+                "/quickwit.metastore.MetastoreService/CreateIndexAlias" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateIndexAliasSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::CreateIndexAliasRequest>
+                    for CreateIndexAliasSvc<T> {
+                        type Response = super::EmptyResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateIndexAliasRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as MetastoreServiceGrpc>::create_index_alias(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CreateIndexAliasSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.metastore.MetastoreService/ListIndexAliases" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListIndexAliasesSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::ListIndexAliasesRequest>
+                    for ListIndexAliasesSvc<T> {
+                        type Response = super::ListIndexAliasesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListIndexAliasesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as MetastoreServiceGrpc>::list_index_aliases(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListIndexAliasesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.metastore.MetastoreService/DeleteIndexAliases" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteIndexAliasesSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::DeleteIndexAliasesRequest>
+                    for DeleteIndexAliasesSvc<T> {
+                        type Response = super::EmptyResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteIndexAliasesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as MetastoreServiceGrpc>::delete_index_aliases(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = DeleteIndexAliasesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.metastore.MetastoreService/UpdateIndexAliases" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdateIndexAliasesSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::UpdateIndexAliasesRequest>
+                    for UpdateIndexAliasesSvc<T> {
+                        type Response = super::EmptyResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateIndexAliasesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as MetastoreServiceGrpc>::update_index_aliases(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = UpdateIndexAliasesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.metastore.MetastoreService/PutPointInTimeLease" => {
+                    #[allow(non_camel_case_types)]
+                    struct PutPointInTimeLeaseSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::PutPointInTimeLeaseRequest>
+                    for PutPointInTimeLeaseSvc<T> {
+                        type Response = super::EmptyResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PutPointInTimeLeaseRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as MetastoreServiceGrpc>::put_point_in_time_lease(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = PutPointInTimeLeaseSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.metastore.MetastoreService/ListPointInTimeLeases" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListPointInTimeLeasesSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::ListPointInTimeLeasesRequest>
+                    for ListPointInTimeLeasesSvc<T> {
+                        type Response = super::ListPointInTimeLeasesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListPointInTimeLeasesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as MetastoreServiceGrpc>::list_point_in_time_leases(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListPointInTimeLeasesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.metastore.MetastoreService/DeletePointInTimeLease" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeletePointInTimeLeaseSvc<T: MetastoreServiceGrpc>(
+                        pub Arc<T>,
+                    );
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::DeletePointInTimeLeaseRequest>
+                    for DeletePointInTimeLeaseSvc<T> {
+                        type Response = super::EmptyResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeletePointInTimeLeaseRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as MetastoreServiceGrpc>::delete_point_in_time_lease(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = DeletePointInTimeLeaseSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/quickwit.metastore.MetastoreService/GetClusterIdentity" => {
                     #[allow(non_camel_case_types)]
                     struct GetClusterIdentitySvc<T: MetastoreServiceGrpc>(pub Arc<T>);
@@ -11,6 +11,151 @@ pub struct ScrollRequest {
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OpenPointInTimeRequest {
+    /// Index ID patterns of the indexes the point in time is opened on.
+    #[prost(string, repeated, tag = "1")]
+    pub index_id_patterns: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Duration during which the point in time can be searched, renewed by every
+    /// search carrying it.
+    #[prost(uint32, tag = "2")]
+    pub keep_alive_secs: u32,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OpenPointInTimeResponse {
+    /// Opaque point in time ID to pass in the search requests.
+    #[prost(string, tag = "1")]
+    pub pit_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClosePointInTimeRequest {
+    #[prost(string, tag = "1")]
+    pub pit_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ClosePointInTimeResponse {
+    /// Number of point in time contexts that were released: 0 if the point in time
+    /// had already expired or been closed.
+    #[prost(uint32, tag = "1")]
+    pub num_freed: u32,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubmitAsyncSearchRequest {
+    #[prost(message, optional, tag = "1")]
+    pub search_request: ::core::option::Option<SearchRequest>,
+    /// Duration during which the search and its results are kept around.
+    #[prost(uint32, tag = "2")]
+    pub keep_alive_secs: u32,
+    /// Duration to wait for the search to complete before returning its partial
+    /// results.
+    #[prost(uint64, tag = "3")]
+    pub wait_for_completion_timeout_millis: u64,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetAsyncSearchRequest {
+    #[prost(string, tag = "1")]
+    pub async_search_id: ::prost::alloc::string::String,
+    /// If set, extends the lifetime of the async search.
+    #[prost(uint32, optional, tag = "2")]
+    pub keep_alive_secs: ::core::option::Option<u32>,
+    /// Duration to wait for the search to complete before returning its partial
+    /// results.
+    #[prost(uint64, tag = "3")]
+    pub wait_for_completion_timeout_millis: u64,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AsyncSearchResponse {
+    #[prost(string, tag = "1")]
+    pub async_search_id: ::prost::alloc::string::String,
+    /// Whether the search is still running.
+    #[prost(bool, tag = "2")]
+    pub is_running: bool,
+    /// Whether the response only accounts for a subset of the targeted splits,
+    /// because the search is still running or failed.
+    #[prost(bool, tag = "3")]
+    pub is_partial: bool,
+    /// Unix timestamps in milliseconds.
+    #[prost(int64, tag = "4")]
+    pub start_time_millis: i64,
+    #[prost(int64, tag = "5")]
+    pub expiration_time_millis: i64,
+    /// Total number of splits targeted by the search.
+    #[prost(uint64, tag = "6")]
+    pub num_targeted_splits: u64,
+    /// Response of the search, or the partial response gathered so far while the
+    /// search is running. Partial responses carry the hit count and aggregations
+    /// but no hits.
+    #[prost(message, optional, tag = "7")]
+    pub response: ::core::option::Option<SearchResponse>,
+    /// Error that caused the search to fail.
+    #[prost(string, optional, tag = "8")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteAsyncSearchRequest {
+    #[prost(string, tag = "1")]
+    pub async_search_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct DeleteAsyncSearchResponse {
+    /// Number of async searches that were deleted: 0 if the async search had
+    /// already expired or been deleted.
+    #[prost(uint32, tag = "1")]
+    pub num_freed: u32,
+}
+/// Root search running on a searcher.
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchTask {
+    #[prost(string, tag = "1")]
+    pub search_task_id: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "2")]
+    pub index_id_patterns: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "3")]
+    pub query_ast: ::prost::alloc::string::String,
+    #[prost(int64, tag = "4")]
+    pub start_time_millis: i64,
+    #[prost(uint64, optional, tag = "5")]
+    pub timeout_millis: ::core::option::Option<u64>,
+    /// gRPC address of the searcher running the root search. Set when listing the
+    /// search tasks of the whole cluster.
+    #[prost(string, tag = "6")]
+    pub searcher_grpc_addr: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ListSearchTasksRequest {}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListSearchTasksResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub search_tasks: ::prost::alloc::vec::Vec<SearchTask>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelSearchTaskRequest {
+    #[prost(string, tag = "1")]
+    pub search_task_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelSearchTaskResponse {
+    /// Root searches that were cancelled.
+    #[prost(message, repeated, tag = "1")]
+    pub cancelled_search_tasks: ::prost::alloc::vec::Vec<SearchTask>,
+    /// Number of leaf searches that were cancelled.
+    #[prost(uint32, tag = "2")]
+    pub num_cancelled_leaf_searches: u32,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PutKvRequest {
     #[prost(bytes = "vec", tag = "1")]
     pub key: ::prost::alloc::vec::Vec<u8>,
@@ -18,6 +163,11 @@ pub struct PutKvRequest {
     pub payload: ::prost::alloc::vec::Vec<u8>,
     #[prost(uint32, tag = "3")]
     pub ttl_secs: u32,
+    #[prost(enumeration = "KvStore", tag = "4")]
+    pub store: i32,
+    /// If set, the payload does not replace a stored payload with a higher version.
+    #[prost(uint64, optional, tag = "5")]
+    pub version: ::core::option::Option<u64>,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
@@ -27,6 +177,8 @@ pub struct PutKvResponse {}
 pub struct GetKvRequest {
     #[prost(bytes = "vec", tag = "1")]
     pub key: ::prost::alloc::vec::Vec<u8>,
+    #[prost(enumeration = "KvStore", tag = "2")]
+    pub store: i32,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -178,6 +330,106 @@ pub struct SearchRequest {
     pub search_after: ::core::option::Option<PartialHit>,
     #[prost(enumeration = "CountHits", tag = "17")]
     pub count_hits: i32,
+    /// Options used to render the snippets of the `snippet_fields`.
+    #[prost(message, optional, tag = "18")]
+    pub snippet_options: ::core::option::Option<SnippetOptions>,
+    /// Restricts the fields of the documents returned in the hits.
+    #[prost(message, optional, tag = "19")]
+    pub source_filter: ::core::option::Option<SourceFilter>,
+    /// Runs the search on the splits pinned by a point in time instead of the
+    /// splits currently published.
+    #[prost(message, optional, tag = "20")]
+    pub point_in_time: ::core::option::Option<PointInTime>,
+    /// Maximum duration of the search. Once it has elapsed, the splits that have
+    /// not been searched yet are skipped and the partial results are returned,
+    /// flagged as timed out.
+    #[prost(uint64, optional, tag = "21")]
+    pub timeout_millis: ::core::option::Option<u64>,
+    /// Tenant the search is accounted to by the searcher admission control. If
+    /// not set, the search is accounted to the index ID patterns it targets.
+    #[prost(string, optional, tag = "22")]
+    pub tenant_id: ::core::option::Option<::prost::alloc::string::String>,
+    /// Collapses the hits on the value of a fast field, returning only the best
+    /// hit of each group.
+    #[prost(message, optional, tag = "23")]
+    pub collapse: ::core::option::Option<CollapseRequest>,
+    /// If set, the search response includes a breakdown of the execution of the
+    /// search.
+    #[prost(bool, tag = "24")]
+    pub profile: bool,
+    /// JSON-serialized k-nearest neighbors search over a dense vector field. See
+    /// `quickwit_search::KnnQuery` for its format.
+    #[prost(string, optional, tag = "25")]
+    pub knn: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Eq, Hash)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CollapseRequest {
+    /// Fast field the hits are grouped by. Documents without a value for this
+    /// field are grouped together.
+    #[prost(string, tag = "1")]
+    pub field: ::prost::alloc::string::String,
+    /// Number of best hits of each group returned as inner hits. No inner hits
+    /// are returned if 0.
+    #[prost(uint32, tag = "2")]
+    pub inner_hits_size: u32,
+    /// Number of best hits kept for each group. If set, `max_hits` bounds the
+    /// total number of hits and the number of hits of each group is returned
+    /// in `LeafSearchResponse.collapse_groups`. Only the best hit of each group
+    /// is kept if 0. This is used by the root to search the inner hits of all
+    /// the collapsed hits at once.
+    #[prost(uint32, tag = "3")]
+    pub group_size: u32,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Eq, Hash)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SnippetOptions {
+    /// Tag inserted before each highlighted term. Defaults to `<b>`.
+    #[prost(string, optional, tag = "1")]
+    pub pre_tag: ::core::option::Option<::prost::alloc::string::String>,
+    /// Tag inserted after each highlighted term. Defaults to `</b>`.
+    #[prost(string, optional, tag = "2")]
+    pub post_tag: ::core::option::Option<::prost::alloc::string::String>,
+    /// Maximum number of characters of a snippet. Defaults to 150.
+    #[prost(uint32, optional, tag = "3")]
+    pub fragment_size: ::core::option::Option<u32>,
+    /// Maximum number of fragments returned per field.
+    /// If set to 0, the whole field values are highlighted without fragmentation.
+    #[prost(uint32, optional, tag = "4")]
+    pub number_of_fragments: ::core::option::Option<u32>,
+    /// If true, the snippet fields may contain `*` wildcards, which are expanded against the doc
+    /// mapping, and the fields that cannot be highlighted are ignored instead of failing the search.
+    #[prost(bool, tag = "5")]
+    pub lenient_fields: bool,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Eq, Hash)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SourceFilter {
+    /// Field path patterns to include. Patterns may contain `*` wildcards.
+    /// If empty, all the fields are included.
+    #[prost(string, repeated, tag = "1")]
+    pub includes: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Field path patterns to exclude. Patterns may contain `*` wildcards.
+    /// Exclusion takes precedence over inclusion.
+    #[prost(string, repeated, tag = "2")]
+    pub excludes: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// If true, the documents are not returned at all, as opposed to returned as empty objects.
+    #[prost(bool, tag = "3")]
+    pub disabled: bool,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Eq, Hash)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PointInTime {
+    /// ID returned when opening the point in time.
+    #[prost(string, tag = "1")]
+    pub pit_id: ::prost::alloc::string::String,
+    /// If set, extends the lifetime of the point in time.
+    #[prost(uint32, optional, tag = "2")]
+    pub keep_alive_secs: ::core::option::Option<u32>,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[derive(Eq, Hash)]
@@ -226,6 +478,81 @@ pub struct SearchResponse {
     /// Total number of successful splits searched.
     #[prost(uint64, tag = "8")]
     pub num_successful_splits: u64,
+    /// Whether the search timed out before all the splits were searched.
+    #[prost(bool, tag = "10")]
+    pub timed_out: bool,
+    /// Breakdown of the execution of the search, only set if `profile` was set
+    /// on the request.
+    #[prost(message, optional, tag = "11")]
+    pub profile: ::core::option::Option<SearchProfile>,
+}
+/// Breakdown of the execution of a root search.
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchProfile {
+    /// Time spent resolving the indexes and listing the splits to search, in
+    /// microseconds.
+    #[prost(uint64, tag = "1")]
+    pub plan_micros: u64,
+    /// Number of splits matching the indexes, time range, and tags of the
+    /// request.
+    #[prost(uint64, tag = "2")]
+    pub num_targeted_splits: u64,
+    /// Number of targeted splits that were not searched, because the root
+    /// answered them from the split metadata or its cache, because they could
+    /// not contain better hits than the splits already searched, or because the
+    /// search timed out.
+    #[prost(uint64, tag = "3")]
+    pub num_pruned_splits: u64,
+    /// Time spent searching the splits, merging their results, and fetching
+    /// the documents, in microseconds.
+    #[prost(uint64, tag = "4")]
+    pub exec_micros: u64,
+    /// Breakdown of the work done by each searcher.
+    #[prost(message, repeated, tag = "5")]
+    pub leaves: ::prost::alloc::vec::Vec<LeafSearchProfile>,
+}
+/// Breakdown of the work done by a searcher for a root search. Durations are
+/// summed over the splits the searcher searched.
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LeafSearchProfile {
+    /// gRPC address of the searcher.
+    #[prost(string, tag = "1")]
+    pub leaf_addr: ::prost::alloc::string::String,
+    /// Number of splits sent to the searcher.
+    #[prost(uint64, tag = "2")]
+    pub num_splits: u64,
+    /// Time between sending the leaf search requests and receiving their
+    /// responses, as measured by the root, in microseconds.
+    #[prost(uint64, tag = "3")]
+    pub leaf_search_micros: u64,
+    /// Time spent downloading the data required to search the splits, in
+    /// microseconds.
+    #[prost(uint64, tag = "4")]
+    pub warmup_micros: u64,
+    /// Time spent waiting for a thread of the search thread pool, in
+    /// microseconds.
+    #[prost(uint64, tag = "5")]
+    pub cpu_thread_pool_wait_micros: u64,
+    /// Time spent running the queries and collecting the hits, in microseconds.
+    #[prost(uint64, tag = "6")]
+    pub search_micros: u64,
+    /// Time between sending the fetch docs requests and receiving their
+    /// responses, as measured by the root, in microseconds.
+    #[prost(uint64, tag = "7")]
+    pub fetch_docs_micros: u64,
+    /// Number of bytes downloaded from the index storage.
+    #[prost(uint64, tag = "8")]
+    pub downloaded_num_bytes: u64,
+    /// Share of the splits whose results were served by the leaf search cache.
+    #[prost(double, tag = "9")]
+    pub leaf_search_cache_hit_ratio: f64,
+    /// Share of the bytes read by the warmup that were served by the fast field
+    /// cache and the split cache rather than downloaded, or 0 if the warmup did
+    /// not read anything.
+    #[prost(double, tag = "10")]
+    pub storage_cache_hit_ratio: f64,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -267,6 +594,13 @@ pub struct LeafSearchRequest {
     /// split files.
     #[prost(string, repeated, tag = "9")]
     pub index_uris: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Time left to perform the leaf search, derived by the root from the timeout
+    /// of the search request.
+    #[prost(uint64, optional, tag = "10")]
+    pub timeout_millis: ::core::option::Option<u64>,
+    /// ID of the search task this leaf search is part of, used to cancel it.
+    #[prost(string, tag = "11")]
+    pub search_task_id: ::prost::alloc::string::String,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
@@ -281,6 +615,16 @@ pub struct ResourceStats {
     pub cpu_thread_pool_wait_microsecs: u64,
     #[prost(uint64, tag = "5")]
     pub cpu_microsecs: u64,
+    /// Number of bytes downloaded from the index storage, excluding the reads
+    /// served by the searcher caches.
+    #[prost(uint64, tag = "6")]
+    pub downloaded_num_bytes: u64,
+    /// Number of bytes downloaded from the index storage during the warmup.
+    #[prost(uint64, tag = "7")]
+    pub warmup_downloaded_num_bytes: u64,
+    /// Number of splits whose results were served by the leaf search cache.
+    #[prost(uint64, tag = "8")]
+    pub num_leaf_search_cache_hits: u64,
 }
 /// / LeafRequestRef references data in LeafSearchRequest to deduplicate data.
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
@@ -345,6 +689,9 @@ pub struct LeafHit {
     /// A snippet of the matching content
     #[prost(string, optional, tag = "3")]
     pub leaf_snippet_json: ::core::option::Option<::prost::alloc::string::String>,
+    /// If true, the source of the document was disabled and `leaf_json` is empty.
+    #[prost(bool, tag = "4")]
+    pub source_disabled: bool,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -361,6 +708,22 @@ pub struct Hit {
     /// The index id of the hit
     #[prost(string, tag = "4")]
     pub index_id: ::prost::alloc::string::String,
+    /// The best hits of the group of the hit, if the search collapses hits and
+    /// requests inner hits.
+    #[prost(message, optional, tag = "5")]
+    pub inner_hits: ::core::option::Option<InnerHits>,
+    /// If true, the source of the document was disabled and `json` is empty.
+    #[prost(bool, tag = "6")]
+    pub source_disabled: bool,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InnerHits {
+    /// Number of hits of the group.
+    #[prost(uint64, tag = "1")]
+    pub num_hits: u64,
+    #[prost(message, repeated, tag = "2")]
+    pub hits: ::prost::alloc::vec::Vec<Hit>,
 }
 /// A partial hit, is a hit for which we have not fetch the content yet.
 /// Instead, it holds a document_uri which is enough information to
@@ -394,6 +757,10 @@ pub struct PartialHit {
     /// The DocId identifies a unique document at the scale of a tantivy segment.
     #[prost(uint32, tag = "4")]
     pub doc_id: u32,
+    /// Value of the collapse field for the given document, if the search
+    /// collapses hits. Not set for documents without a value for this field.
+    #[prost(string, optional, tag = "5")]
+    pub collapse_key: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[derive(Ord, PartialOrd)]
@@ -447,6 +814,23 @@ pub struct LeafSearchResponse {
     >,
     #[prost(message, optional, tag = "8")]
     pub resource_stats: ::core::option::Option<ResourceStats>,
+    /// Whether the leaf search timed out before all the splits were searched.
+    #[prost(bool, tag = "9")]
+    pub timed_out: bool,
+    /// Number of hits of each group, if the search collapses hits with a group
+    /// size.
+    #[prost(message, repeated, tag = "10")]
+    pub collapse_groups: ::prost::alloc::vec::Vec<CollapseGroup>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CollapseGroup {
+    /// Value of the collapse field shared by the hits of the group. Not set for
+    /// the group of documents without a value for this field.
+    #[prost(string, optional, tag = "1")]
+    pub collapse_key: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, tag = "2")]
+    pub num_hits: u64,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -455,6 +839,8 @@ pub struct SnippetRequest {
     pub snippet_fields: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
     #[prost(string, tag = "2")]
     pub query_ast_resolved: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "3")]
+    pub snippet_options: ::core::option::Option<SnippetOptions>,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -476,6 +862,9 @@ pub struct FetchDocsRequest {
     /// `DocMapper` as json serialized trait.
     #[prost(string, tag = "6")]
     pub doc_mapper: ::prost::alloc::string::String,
+    /// Restricts the fields of the fetched documents.
+    #[prost(message, optional, tag = "8")]
+    pub source_filter: ::core::option::Option<SourceFilter>,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -556,6 +945,85 @@ pub struct LeafListTermsResponse {
     pub num_attempted_splits: u64,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LeafExportRequest {
+    /// Search request selecting the documents to export. Only the query, the time range and the
+    /// source filter are taken into account.
+    #[prost(message, optional, tag = "1")]
+    pub search_request: ::core::option::Option<SearchRequest>,
+    /// `DocMapper` as json serialized trait.
+    #[prost(string, tag = "2")]
+    pub doc_mapper: ::prost::alloc::string::String,
+    /// Index URI. The index URI defines the location of the storage that contains the
+    /// split files.
+    #[prost(string, tag = "3")]
+    pub index_uri: ::prost::alloc::string::String,
+    /// Split to export the documents from.
+    #[prost(message, optional, tag = "4")]
+    pub split_offsets: ::core::option::Option<SplitIdAndFooterOffsets>,
+    /// Address of the first document to consider. Documents are exported in
+    /// (segment_ord, doc_id) order.
+    #[prost(uint32, tag = "5")]
+    pub start_segment_ord: u32,
+    #[prost(uint32, tag = "6")]
+    pub start_doc_id: u32,
+    /// Maximum number of documents of each page of the stream.
+    #[prost(uint32, tag = "7")]
+    pub max_docs_per_page: u32,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExportedDoc {
+    /// The content of the document, as a json object.
+    #[prost(string, tag = "1")]
+    pub json: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "2")]
+    pub segment_ord: u32,
+    #[prost(uint32, tag = "3")]
+    pub doc_id: u32,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LeafExportResponse {
+    #[prost(string, tag = "1")]
+    pub split_id: ::prost::alloc::string::String,
+    /// Documents matching the query, in doc address order.
+    #[prost(message, repeated, tag = "2")]
+    pub docs: ::prost::alloc::vec::Vec<ExportedDoc>,
+}
+/// Key-value stores of the searchers. Each store has its own capacity, so that
+/// its use cases do not compete with the others.
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum KvStore {
+    /// Scroll and point in time contexts.
+    SearchContext = 0,
+    /// State of the async searches.
+    AsyncSearch = 1,
+}
+impl KvStore {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::SearchContext => "KV_STORE_SEARCH_CONTEXT",
+            Self::AsyncSearch => "KV_STORE_ASYNC_SEARCH",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "KV_STORE_SEARCH_CONTEXT" => Some(Self::SearchContext),
+            "KV_STORE_ASYNC_SEARCH" => Some(Self::AsyncSearch),
+            _ => None,
+        }
+    }
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "snake_case")]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -1107,6 +1575,88 @@ pub mod search_service_client {
                 .insert(GrpcMethod::new("quickwit.search.SearchService", "SearchPlan"));
             self.inner.unary(req, path, codec).await
         }
+        /// Lists the root searches running on the targeted node.
+        pub async fn leaf_list_search_tasks(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListSearchTasksRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListSearchTasksResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.search.SearchService/LeafListSearchTasks",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("quickwit.search.SearchService", "LeafListSearchTasks"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Cancels the root and leaf searches of a search task running on the targeted node.
+        pub async fn leaf_cancel_search_task(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CancelSearchTaskRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CancelSearchTaskResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.search.SearchService/LeafCancelSearchTask",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.search.SearchService",
+                        "LeafCancelSearchTask",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Streams the documents of a split matching a query, page by page, in doc address order.
+        pub async fn leaf_export(
+            &mut self,
+            request: impl tonic::IntoRequest<super::LeafExportRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::LeafExportResponse>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.search.SearchService/LeafExport",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("quickwit.search.SearchService", "LeafExport"));
+            self.inner.server_streaming(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -1225,6 +1775,33 @@ pub mod search_service_server {
             tonic::Response<super::SearchPlanResponse>,
             tonic::Status,
         >;
+        /// Lists the root searches running on the targeted node.
+        async fn leaf_list_search_tasks(
+            &self,
+            request: tonic::Request<super::ListSearchTasksRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListSearchTasksResponse>,
+            tonic::Status,
+        >;
+        /// Cancels the root and leaf searches of a search task running on the targeted node.
+        async fn leaf_cancel_search_task(
+            &self,
+            request: tonic::Request<super::CancelSearchTaskRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CancelSearchTaskResponse>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the LeafExport method.
+        type LeafExportStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::LeafExportResponse, tonic::Status>,
+            >
+            + std::marker::Send
+            + 'static;
+        /// Streams the documents of a split matching a query, page by page, in doc address order.
+        async fn leaf_export(
+            &self,
+            request: tonic::Request<super::LeafExportRequest>,
+        ) -> std::result::Result<tonic::Response<Self::LeafExportStream>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct SearchServiceServer<T> {
@@ -1841,6 +2418,150 @@ pub mod search_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/quickwit.search.SearchService/LeafListSearchTasks" => {
+                    #[allow(non_camel_case_types)]
+                    struct LeafListSearchTasksSvc<T: SearchService>(pub Arc<T>);
+                    impl<
+                        T: SearchService,
+                    > tonic::server::UnaryService<super::ListSearchTasksRequest>
+                    for LeafListSearchTasksSvc<T> {
+                        type Response = super::ListSearchTasksResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListSearchTasksRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as SearchService>::leaf_list_search_tasks(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = LeafListSearchTasksSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.search.SearchService/LeafCancelSearchTask" => {
+                    #[allow(non_camel_case_types)]
+                    struct LeafCancelSearchTaskSvc<T: SearchService>(pub Arc<T>);
+                    impl<
+                        T: SearchService,
+                    > tonic::server::UnaryService<super::CancelSearchTaskRequest>
+                    for LeafCancelSearchTaskSvc<T> {
+                        type Response = super::CancelSearchTaskResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CancelSearchTaskRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as SearchService>::leaf_cancel_search_task(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = LeafCancelSearchTaskSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.search.SearchService/LeafExport" => {
+                    #[allow(non_camel_case_types)]
+                    struct LeafExportSvc<T: SearchService>(pub Arc<T>);
+                    impl<
+                        T: SearchService,
+                    > tonic::server::ServerStreamingService<super::LeafExportRequest>
+                    for LeafExportSvc<T> {
+                        type Response = super::LeafExportResponse;
+                        type ResponseStream = T::LeafExportStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::LeafExportRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as SearchService>::leaf_export(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = LeafExportSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         let mut response = http::Response::new(
@@ -79,6 +79,11 @@ pub enum EntityKind {
         /// Index template ID.
         template_id: String,
     },
+    /// An index alias.
+    IndexAlias {
+        /// Index alias ID.
+        alias_id: String,
+    },
 }
 
 impl fmt::Display for EntityKind {
@@ -100,6 +105,7 @@ impl fmt::Display for EntityKind {
             EntityKind::IndexTemplate { template_id } => {
                 write!(f, "index template `{template_id}`")
             }
+            EntityKind::IndexAlias { alias_id } => write!(f, "index alias `{alias_id}`"),
         }
     }
 }
@@ -40,8 +40,16 @@ impl From<TantivyAggregationResults> for AggregationResults {
 }
 
 impl From<AggregationResults> for TantivyAggregationResults {
+    /// Composite results have no tantivy counterpart and are skipped. They are only ever
+    /// top-level results and must be handled before converting.
     fn from(value: AggregationResults) -> TantivyAggregationResults {
-        TantivyAggregationResults(value.0.into_iter().map(|(k, v)| (k, v.into())).collect())
+        TantivyAggregationResults(
+            value
+                .0
+                .into_iter()
+                .filter_map(|(k, v)| Some((k, v.try_into().ok()?)))
+                .collect(),
+        )
     }
 }
 
@@ -52,6 +60,8 @@ pub enum AggregationResult {
     BucketResult(BucketResult),
     /// Metric result variant.
     MetricResult(MetricResult),
+    /// Composite aggregation result variant.
+    CompositeResult(CompositeResult),
 }
 
 impl From<TantivyAggregationResult> for AggregationResult {
@@ -67,19 +77,62 @@ impl From<TantivyAggregationResult> for AggregationResult {
     }
 }
 
-impl From<AggregationResult> for TantivyAggregationResult {
-    fn from(value: AggregationResult) -> TantivyAggregationResult {
+impl TryFrom<AggregationResult> for TantivyAggregationResult {
+    type Error = CompositeResult;
+
+    fn try_from(value: AggregationResult) -> Result<TantivyAggregationResult, CompositeResult> {
         match value {
             AggregationResult::BucketResult(bucket) => {
-                TantivyAggregationResult::BucketResult(bucket.into())
+                Ok(TantivyAggregationResult::BucketResult(bucket.into()))
             }
             AggregationResult::MetricResult(metric) => {
-                TantivyAggregationResult::MetricResult(metric.into())
+                Ok(TantivyAggregationResult::MetricResult(metric.into()))
             }
+            AggregationResult::CompositeResult(composite) => Err(composite),
         }
     }
 }
 
+/// The result of a composite aggregation, which tantivy doesn't support.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompositeResult {
+    /// The key of the last bucket, to pass as `after` to fetch the next page of buckets. `None`
+    /// when there are no buckets left.
+    pub after_key: Option<CompositeKey>,
+    /// The buckets, sorted by key.
+    pub buckets: Vec<CompositeBucketEntry>,
+}
+
+/// The key of a composite bucket: the name of each source and its value, `None` when the
+/// documents of the bucket have no value for the source.
+pub type CompositeKey = Vec<(String, Option<CompositeKeyValue>)>;
+
+/// A value of a composite bucket key.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CompositeKeyValue {
+    /// `bool` value
+    Bool(bool),
+    /// `u64` value
+    U64(u64),
+    /// `i64` value, also used for dates as milliseconds since the epoch
+    I64(i64),
+    /// `f64` value
+    F64(f64),
+    /// String value
+    Str(String),
+}
+
+/// A bucket of a composite aggregation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompositeBucketEntry {
+    /// The identifier of the bucket.
+    pub key: CompositeKey,
+    /// Number of documents in the bucket.
+    pub doc_count: u64,
+    /// Sub-aggregations in this bucket.
+    pub sub_aggregation: AggregationResults,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 /// MetricResult
 pub enum MetricResult {
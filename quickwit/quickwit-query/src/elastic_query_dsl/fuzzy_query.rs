@@ -0,0 +1,262 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::str::FromStr;
+
+use anyhow::bail;
+use serde::{Deserialize, Deserializer};
+
+use crate::NotNaNf32;
+use crate::elastic_query_dsl::one_field_map::OneFieldMap;
+use crate::elastic_query_dsl::{
+    ConvertibleToQueryAst, ElasticQueryDslInner, StringOrStructForSerialization,
+    default_max_expansions,
+};
+use crate::query_ast::{self, MAX_FUZZY_DISTANCE, QueryAst};
+
+/// `FuzzyQuery` as defined in
+/// <https://www.elastic.co/guide/en/elasticsearch/reference/current/query-dsl-fuzzy-query.html>
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(from = "OneFieldMap<StringOrStructForSerialization<FuzzyQueryParams>>")]
+pub(crate) struct FuzzyQuery {
+    pub(crate) field: String,
+    pub(crate) params: FuzzyQueryParams,
+}
+
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct FuzzyQueryParams {
+    pub(crate) value: String,
+    #[serde(default)]
+    pub(crate) fuzziness: Fuzziness,
+    #[serde(default = "default_transpositions")]
+    pub(crate) transpositions: bool,
+    #[serde(default)]
+    pub(crate) prefix_length: u32,
+    // The number of matched terms is not bounded: the maximum number of expansions is ignored.
+    #[serde(default = "default_max_expansions", rename = "max_expansions")]
+    _max_expansions: u32,
+    #[serde(default)]
+    pub(crate) boost: Option<NotNaNf32>,
+    // Multi-term queries are never rewritten: the rewrite method is ignored.
+    #[serde(default, rename = "rewrite")]
+    _rewrite: Option<String>,
+}
+
+fn default_transpositions() -> bool {
+    true
+}
+
+impl From<String> for FuzzyQueryParams {
+    fn from(value: String) -> Self {
+        FuzzyQueryParams {
+            value,
+            fuzziness: Fuzziness::default(),
+            transpositions: default_transpositions(),
+            prefix_length: 0,
+            _max_expansions: default_max_expansions(),
+            boost: None,
+            _rewrite: None,
+        }
+    }
+}
+
+/// Maximum edit distance allowed for a match, either fixed or depending on the length of the
+/// value.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum Fuzziness {
+    Fixed(u8),
+    /// Exact matches for values shorter than `low` characters, one edit for values shorter than
+    /// `high` characters, two edits otherwise.
+    Auto {
+        low: usize,
+        high: usize,
+    },
+}
+
+impl Default for Fuzziness {
+    fn default() -> Self {
+        Fuzziness::Auto { low: 3, high: 6 }
+    }
+}
+
+impl Fuzziness {
+    fn distance(&self, value: &str) -> u8 {
+        match *self {
+            Fuzziness::Fixed(distance) => distance,
+            Fuzziness::Auto { low, high } => {
+                let num_chars = value.chars().count();
+                if num_chars < low {
+                    0
+                } else if num_chars < high {
+                    1
+                } else {
+                    2
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Fuzziness {
+    type Err = String;
+
+    fn from_str(fuzziness_str: &str) -> Result<Self, Self::Err> {
+        let invalid_fuzziness = || {
+            format!(
+                "invalid fuzziness `{fuzziness_str}`, expected `AUTO`, `AUTO:[low],[high]` or an \
+                 edit distance between 0 and {MAX_FUZZY_DISTANCE}"
+            )
+        };
+        if let Some(auto_str) = fuzziness_str.strip_prefix("AUTO") {
+            if auto_str.is_empty() {
+                return Ok(Fuzziness::default());
+            }
+            let (low_str, high_str) = auto_str
+                .strip_prefix(':')
+                .and_then(|bounds_str| bounds_str.split_once(','))
+                .ok_or_else(invalid_fuzziness)?;
+            let low: usize = low_str.trim().parse().map_err(|_| invalid_fuzziness())?;
+            let high: usize = high_str.trim().parse().map_err(|_| invalid_fuzziness())?;
+            if low > high {
+                return Err(invalid_fuzziness());
+            }
+            return Ok(Fuzziness::Auto { low, high });
+        }
+        let distance: u8 = fuzziness_str.parse().map_err(|_| invalid_fuzziness())?;
+        if distance > MAX_FUZZY_DISTANCE {
+            return Err(invalid_fuzziness());
+        }
+        Ok(Fuzziness::Fixed(distance))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FuzzinessForDeser {
+    Int(u64),
+    Str(String),
+}
+
+impl<'de> Deserialize<'de> for Fuzziness {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        let fuzziness_str = match FuzzinessForDeser::deserialize(deserializer)? {
+            FuzzinessForDeser::Int(distance) => distance.to_string(),
+            FuzzinessForDeser::Str(fuzziness_str) => fuzziness_str,
+        };
+        fuzziness_str.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<OneFieldMap<StringOrStructForSerialization<FuzzyQueryParams>>> for FuzzyQuery {
+    fn from(one_field_map: OneFieldMap<StringOrStructForSerialization<FuzzyQueryParams>>) -> Self {
+        FuzzyQuery {
+            field: one_field_map.field,
+            params: one_field_map.value.inner,
+        }
+    }
+}
+
+impl From<FuzzyQuery> for ElasticQueryDslInner {
+    fn from(fuzzy_query: FuzzyQuery) -> Self {
+        Self::Fuzzy(fuzzy_query)
+    }
+}
+
+impl ConvertibleToQueryAst for FuzzyQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        if self.params.prefix_length > 0 {
+            bail!("fuzzy queries with a non-zero `prefix_length` are not supported");
+        }
+        let distance = self.params.fuzziness.distance(&self.params.value);
+        let fuzzy_ast: QueryAst = query_ast::FuzzyQuery {
+            field: self.field,
+            value: self.params.value,
+            distance,
+            transpositions: self.params.transpositions,
+        }
+        .into();
+        Ok(fuzzy_ast.boost(self.params.boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fuzzy_query_ast(fuzzy_query_json: &str) -> anyhow::Result<QueryAst> {
+        let fuzzy_query: FuzzyQuery = serde_json::from_str(fuzzy_query_json)?;
+        fuzzy_query.convert_to_query_ast()
+    }
+
+    #[test]
+    fn test_fuzzy_query_convert_to_query_ast() {
+        let query_ast = fuzzy_query_ast(
+            r#"{"user.id": {"value": "ki", "fuzziness": 1, "transpositions": false}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            query_ast,
+            QueryAst::Fuzzy(query_ast::FuzzyQuery {
+                field: "user.id".to_string(),
+                value: "ki".to_string(),
+                distance: 1,
+                transpositions: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_query_auto_fuzziness() {
+        for (value, expected_distance) in [("ki", 0), ("kim", 1), ("kimch", 1), ("kimchy", 2)] {
+            let query_ast = fuzzy_query_ast(&format!(r#"{{"user.id": "{value}"}}"#)).unwrap();
+            let QueryAst::Fuzzy(fuzzy_query) = query_ast else {
+                panic!("expected a fuzzy query, got {query_ast:?}");
+            };
+            assert_eq!(fuzzy_query.distance, expected_distance);
+            assert!(fuzzy_query.transpositions);
+        }
+        let query_ast =
+            fuzzy_query_ast(r#"{"user.id": {"value": "kimchy", "fuzziness": "AUTO:2,7"}}"#)
+                .unwrap();
+        let QueryAst::Fuzzy(fuzzy_query) = query_ast else {
+            panic!("expected a fuzzy query, got {query_ast:?}");
+        };
+        assert_eq!(fuzzy_query.distance, 1);
+    }
+
+    #[test]
+    fn test_fuzziness_from_str() {
+        assert_eq!(
+            "AUTO".parse::<Fuzziness>().unwrap(),
+            Fuzziness::Auto { low: 3, high: 6 }
+        );
+        assert_eq!(
+            "AUTO:1,4".parse::<Fuzziness>().unwrap(),
+            Fuzziness::Auto { low: 1, high: 4 }
+        );
+        assert_eq!("2".parse::<Fuzziness>().unwrap(), Fuzziness::Fixed(2));
+        assert!("3".parse::<Fuzziness>().is_err());
+        assert!("AUTO:4,1".parse::<Fuzziness>().is_err());
+        assert!("AUTO:4".parse::<Fuzziness>().is_err());
+        assert!("auto".parse::<Fuzziness>().is_err());
+    }
+
+    #[test]
+    fn test_fuzzy_query_unsupported_prefix_length() {
+        fuzzy_query_ast(r#"{"user.id": {"value": "kimchy", "prefix_length": 2}}"#).unwrap_err();
+        fuzzy_query_ast(r#"{"user.id": {"value": "kimchy", "prefix_length": 0}}"#).unwrap();
+    }
+}
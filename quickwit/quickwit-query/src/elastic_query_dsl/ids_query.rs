@@ -0,0 +1,83 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::{BTreeSet, HashMap};
+
+use serde::Deserialize;
+
+use crate::elastic_query_dsl::{ConvertibleToQueryAst, ElasticQueryDslInner};
+use crate::query_ast::{QueryAst, TermSetQuery};
+use crate::{ID_FIELD_NAME, NotNaNf32};
+
+/// `IdsQuery` as defined in
+/// <https://www.elastic.co/guide/en/elasticsearch/reference/current/query-dsl-ids-query.html>
+///
+/// Ids are matched against the `id_field` of the doc mapping of the targeted indexes.
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct IdsQuery {
+    pub(crate) values: Vec<String>,
+    #[serde(default)]
+    pub(crate) boost: Option<NotNaNf32>,
+}
+
+impl From<IdsQuery> for ElasticQueryDslInner {
+    fn from(ids_query: IdsQuery) -> Self {
+        Self::Ids(ids_query)
+    }
+}
+
+impl ConvertibleToQueryAst for IdsQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        if self.values.is_empty() {
+            return Ok(QueryAst::MatchNone);
+        }
+        let ids: BTreeSet<String> = self.values.into_iter().collect();
+        let terms_per_field = HashMap::from([(ID_FIELD_NAME.to_string(), ids)]);
+        let ids_ast: QueryAst = TermSetQuery { terms_per_field }.into();
+        Ok(ids_ast.boost(self.boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_query_convert_to_query_ast() {
+        let ids_query: IdsQuery = serde_json::from_str(r#"{"values": ["1", "4", "100"]}"#).unwrap();
+        let query_ast = ids_query.convert_to_query_ast().unwrap();
+        let QueryAst::TermSet(term_set_query) = query_ast else {
+            panic!("expected a term set query, got {query_ast:?}");
+        };
+        let ids: Vec<&str> = term_set_query.terms_per_field[ID_FIELD_NAME]
+            .iter()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(ids, ["1", "100", "4"]);
+    }
+
+    #[test]
+    fn test_ids_query_empty_matches_none() {
+        let ids_query: IdsQuery = serde_json::from_str(r#"{"values": []}"#).unwrap();
+        assert_eq!(
+            ids_query.convert_to_query_ast().unwrap(),
+            QueryAst::MatchNone
+        );
+    }
+
+    #[test]
+    fn test_ids_query_deny_unknown_fields() {
+        serde_json::from_str::<IdsQuery>(r#"{"values": ["1"], "type": "_doc"}"#).unwrap_err();
+    }
+}
@@ -16,18 +16,22 @@ use serde::{Deserialize, Serialize};
 
 mod bool_query;
 mod exists_query;
+mod fuzzy_query;
+mod ids_query;
 mod match_bool_prefix;
 mod match_phrase_query;
 mod match_query;
 mod multi_match;
 mod one_field_map;
 mod phrase_prefix_query;
+mod prefix_query;
 mod query_string_query;
 mod range_query;
 mod regex_query;
 mod string_or_struct;
 mod term_query;
 mod terms_query;
+mod wildcard_query;
 
 use bool_query::BoolQuery;
 pub use one_field_map::OneFieldMap;
@@ -38,12 +42,16 @@ pub(crate) use string_or_struct::StringOrStructForSerialization;
 use term_query::TermQuery;
 
 use crate::elastic_query_dsl::exists_query::ExistsQuery;
+use crate::elastic_query_dsl::fuzzy_query::FuzzyQuery;
+use crate::elastic_query_dsl::ids_query::IdsQuery;
 use crate::elastic_query_dsl::match_bool_prefix::MatchBoolPrefixQuery;
 use crate::elastic_query_dsl::match_phrase_query::MatchPhraseQuery;
 use crate::elastic_query_dsl::match_query::MatchQuery;
 use crate::elastic_query_dsl::multi_match::MultiMatchQuery;
+use crate::elastic_query_dsl::prefix_query::PrefixQuery;
 use crate::elastic_query_dsl::regex_query::RegexQuery;
 use crate::elastic_query_dsl::terms_query::TermsQuery;
+use crate::elastic_query_dsl::wildcard_query::WildcardQuery;
 use crate::not_nan_f32::NotNaNf32;
 use crate::query_ast::QueryAst;
 
@@ -85,6 +93,10 @@ pub(crate) enum ElasticQueryDslInner {
     Range(RangeQuery),
     Exists(ExistsQuery),
     Regexp(RegexQuery),
+    Wildcard(WildcardQuery),
+    Prefix(PrefixQuery),
+    Fuzzy(FuzzyQuery),
+    Ids(IdsQuery),
 }
 
 #[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
@@ -133,6 +145,10 @@ impl ConvertibleToQueryAst for ElasticQueryDslInner {
             Self::Exists(exists_query) => exists_query.convert_to_query_ast(),
             Self::MultiMatch(multi_match_query) => multi_match_query.convert_to_query_ast(),
             Self::Regexp(regex_query) => regex_query.convert_to_query_ast(),
+            Self::Wildcard(wildcard_query) => wildcard_query.convert_to_query_ast(),
+            Self::Prefix(prefix_query) => prefix_query.convert_to_query_ast(),
+            Self::Fuzzy(fuzzy_query) => fuzzy_query.convert_to_query_ast(),
+            Self::Ids(ids_query) => ids_query.convert_to_query_ast(),
         }
     }
 }
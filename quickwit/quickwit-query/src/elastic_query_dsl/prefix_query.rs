@@ -0,0 +1,130 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+
+use crate::NotNaNf32;
+use crate::elastic_query_dsl::one_field_map::OneFieldMap;
+use crate::elastic_query_dsl::{
+    ConvertibleToQueryAst, ElasticQueryDslInner, StringOrStructForSerialization,
+};
+use crate::query_ast::{self, QueryAst};
+
+/// `PrefixQuery` as defined in
+/// <https://www.elastic.co/guide/en/elasticsearch/reference/current/query-dsl-prefix-query.html>
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(from = "OneFieldMap<StringOrStructForSerialization<PrefixQueryParams>>")]
+pub(crate) struct PrefixQuery {
+    pub(crate) field: String,
+    pub(crate) params: PrefixQueryParams,
+}
+
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PrefixQueryParams {
+    pub(crate) value: String,
+    #[serde(default)]
+    pub(crate) case_insensitive: bool,
+    #[serde(default)]
+    pub(crate) boost: Option<NotNaNf32>,
+    // Multi-term queries are never rewritten: the rewrite method is ignored.
+    #[serde(default, rename = "rewrite")]
+    _rewrite: Option<String>,
+}
+
+impl From<String> for PrefixQueryParams {
+    fn from(value: String) -> Self {
+        PrefixQueryParams {
+            value,
+            case_insensitive: false,
+            boost: None,
+            _rewrite: None,
+        }
+    }
+}
+
+impl From<OneFieldMap<StringOrStructForSerialization<PrefixQueryParams>>> for PrefixQuery {
+    fn from(one_field_map: OneFieldMap<StringOrStructForSerialization<PrefixQueryParams>>) -> Self {
+        PrefixQuery {
+            field: one_field_map.field,
+            params: one_field_map.value.inner,
+        }
+    }
+}
+
+impl From<PrefixQuery> for ElasticQueryDslInner {
+    fn from(prefix_query: PrefixQuery) -> Self {
+        Self::Prefix(prefix_query)
+    }
+}
+
+/// Escapes the characters interpreted by wildcard queries.
+fn escape_wildcard(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 1);
+    for chr in value.chars() {
+        if matches!(chr, '*' | '?' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(chr);
+    }
+    escaped
+}
+
+impl ConvertibleToQueryAst for PrefixQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        // A prefix query is a wildcard query with a single trailing `*`.
+        let mut value = escape_wildcard(&self.params.value);
+        value.push('*');
+        let wildcard_ast: QueryAst = query_ast::WildcardQuery {
+            field: self.field,
+            value,
+            case_insensitive: self.params.case_insensitive,
+            lenient: false,
+        }
+        .into();
+        Ok(wildcard_ast.boost(self.params.boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_query_convert_to_query_ast() {
+        let prefix_query_json = r#"{"user.id": {"value": "k?*", "case_insensitive": true}}"#;
+        let prefix_query: PrefixQuery = serde_json::from_str(prefix_query_json).unwrap();
+        let query_ast = prefix_query.convert_to_query_ast().unwrap();
+        assert_eq!(
+            query_ast,
+            QueryAst::Wildcard(query_ast::WildcardQuery {
+                field: "user.id".to_string(),
+                value: r"k\?\**".to_string(),
+                case_insensitive: true,
+                lenient: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_prefix_query_deserialization_in_short_format() {
+        let prefix_query: PrefixQuery = serde_json::from_str(r#"{"user.id": "ki"}"#).unwrap();
+        let query_ast = prefix_query.convert_to_query_ast().unwrap();
+        let QueryAst::Wildcard(wildcard_query) = query_ast else {
+            panic!("expected a wildcard query, got {query_ast:?}");
+        };
+        assert_eq!(wildcard_query.value, "ki*");
+        assert!(!wildcard_query.case_insensitive);
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+
+use crate::NotNaNf32;
+use crate::elastic_query_dsl::one_field_map::OneFieldMap;
+use crate::elastic_query_dsl::{
+    ConvertibleToQueryAst, ElasticQueryDslInner, StringOrStructForSerialization,
+};
+use crate::query_ast::{self, QueryAst};
+
+/// `WildcardQuery` as defined in
+/// <https://www.elastic.co/guide/en/elasticsearch/reference/current/query-dsl-wildcard-query.html>
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(from = "OneFieldMap<StringOrStructForSerialization<WildcardQueryParams>>")]
+pub(crate) struct WildcardQuery {
+    pub(crate) field: String,
+    pub(crate) params: WildcardQueryParams,
+}
+
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct WildcardQueryParams {
+    #[serde(alias = "wildcard")]
+    pub(crate) value: String,
+    #[serde(default)]
+    pub(crate) case_insensitive: bool,
+    #[serde(default)]
+    pub(crate) boost: Option<NotNaNf32>,
+    // Multi-term queries are never rewritten: the rewrite method is ignored.
+    #[serde(default, rename = "rewrite")]
+    _rewrite: Option<String>,
+}
+
+impl From<String> for WildcardQueryParams {
+    fn from(value: String) -> Self {
+        WildcardQueryParams {
+            value,
+            case_insensitive: false,
+            boost: None,
+            _rewrite: None,
+        }
+    }
+}
+
+impl From<OneFieldMap<StringOrStructForSerialization<WildcardQueryParams>>> for WildcardQuery {
+    fn from(
+        one_field_map: OneFieldMap<StringOrStructForSerialization<WildcardQueryParams>>,
+    ) -> Self {
+        WildcardQuery {
+            field: one_field_map.field,
+            params: one_field_map.value.inner,
+        }
+    }
+}
+
+impl From<WildcardQuery> for ElasticQueryDslInner {
+    fn from(wildcard_query: WildcardQuery) -> Self {
+        Self::Wildcard(wildcard_query)
+    }
+}
+
+impl ConvertibleToQueryAst for WildcardQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let wildcard_ast: QueryAst = query_ast::WildcardQuery {
+            field: self.field,
+            value: self.params.value,
+            case_insensitive: self.params.case_insensitive,
+            lenient: false,
+        }
+        .into();
+        Ok(wildcard_ast.boost(self.params.boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_query_convert_to_query_ast() {
+        let wildcard_query_json = r#"{
+            "user.id": {
+                "value": "ki*y",
+                "case_insensitive": true,
+                "rewrite": "constant_score_blended"
+            }
+        }"#;
+        let wildcard_query: WildcardQuery = serde_json::from_str(wildcard_query_json).unwrap();
+        let query_ast = wildcard_query.convert_to_query_ast().unwrap();
+        assert_eq!(
+            query_ast,
+            QueryAst::Wildcard(query_ast::WildcardQuery {
+                field: "user.id".to_string(),
+                value: "ki*y".to_string(),
+                case_insensitive: true,
+                lenient: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_wildcard_query_deserialization_formats() {
+        let wildcard_query: WildcardQuery = serde_json::from_str(r#"{"user.id": "ki*y"}"#).unwrap();
+        assert_eq!(wildcard_query.params.value, "ki*y");
+        assert!(!wildcard_query.params.case_insensitive);
+
+        let wildcard_query: WildcardQuery =
+            serde_json::from_str(r#"{"user.id": {"wildcard": "ki*y", "boost": 2.0}}"#).unwrap();
+        assert_eq!(wildcard_query.params.value, "ki*y");
+        assert!(wildcard_query.params.boost.is_some());
+
+        serde_json::from_str::<WildcardQuery>(r#"{"user.id": {"value": "ki*y", "foo": 1}}"#)
+            .unwrap_err();
+    }
+}
@@ -45,6 +45,10 @@ pub use tokenizers::{
     get_quickwit_fastfield_normalizer_manager,
 };
 
+/// Field name reserved for referring to the document id of an index, as declared by the
+/// `id_field` of its doc mapping.
+pub const ID_FIELD_NAME: &str = "_id";
+
 #[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, Eq, PartialEq)]
 pub enum BooleanOperand {
     #[serde(alias = "AND")]
@@ -0,0 +1,281 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::Context;
+use levenshtein_automata::{DFA, Distance, LevenshteinAutomatonBuilder, SINK_STATE};
+use serde::{Deserialize, Serialize};
+use tantivy::Term;
+use tantivy::query::FuzzyTermQuery;
+use tantivy::schema::{FieldType, Schema as TantivySchema};
+
+use super::{BuildTantivyAst, QueryAst};
+use crate::query_ast::TantivyQueryAst;
+use crate::tokenizers::TokenizerManager;
+use crate::{InvalidQuery, find_field_or_hit_dynamic};
+
+/// Maximum edit distance supported by fuzzy queries.
+pub const MAX_FUZZY_DISTANCE: u8 = 2;
+
+/// A Fuzzy query matches the terms within a given Levenshtein distance of the value, e.g. 'bond'
+/// with 'bonf'.
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone)]
+pub struct FuzzyQuery {
+    pub field: String,
+    pub value: String,
+    /// Maximum number of edits allowed.
+    pub distance: u8,
+    /// Counts the transposition of two adjacent characters as a single edit.
+    pub transpositions: bool,
+}
+
+impl From<FuzzyQuery> for QueryAst {
+    fn from(fuzzy_query: FuzzyQuery) -> Self {
+        Self::Fuzzy(fuzzy_query)
+    }
+}
+
+fn normalize_value(
+    value: &str,
+    tokenizer_name: &str,
+    tokenizer_manager: &TokenizerManager,
+) -> anyhow::Result<String> {
+    let mut normalizer = tokenizer_manager
+        .get_normalizer(tokenizer_name)
+        .with_context(|| format!("no tokenizer named `{tokenizer_name}` is registered"))?;
+    let mut token_stream = normalizer.token_stream(value);
+    let normalized_value = token_stream
+        .next()
+        .context("normalizer generated no content")?
+        .text
+        .clone();
+    if token_stream.next().is_some() {
+        anyhow::bail!("normalizer generated multiple tokens");
+    }
+    Ok(normalized_value)
+}
+
+impl FuzzyQuery {
+    /// Returns the term to match, the JSON path prefix of the terms if the field is a JSON field,
+    /// and the normalized value.
+    pub fn to_term(
+        &self,
+        schema: &TantivySchema,
+        tokenizer_manager: &TokenizerManager,
+    ) -> Result<(Term, Option<Vec<u8>>, String), InvalidQuery> {
+        if self.distance > MAX_FUZZY_DISTANCE {
+            return Err(InvalidQuery::Other(anyhow::anyhow!(
+                "fuzzy query distance must be at most {MAX_FUZZY_DISTANCE}, got {}",
+                self.distance
+            )));
+        }
+        let Some((field, field_entry, json_path)) = find_field_or_hit_dynamic(&self.field, schema)
+        else {
+            return Err(InvalidQuery::FieldDoesNotExist {
+                full_path: self.field.clone(),
+            });
+        };
+        match field_entry.field_type() {
+            FieldType::Str(text_options) => {
+                let text_field_indexing = text_options.get_indexing_options().ok_or_else(|| {
+                    InvalidQuery::SchemaError(format!(
+                        "field {} is not full-text searchable",
+                        field_entry.name()
+                    ))
+                })?;
+                let normalized_value = normalize_value(
+                    &self.value,
+                    text_field_indexing.tokenizer(),
+                    tokenizer_manager,
+                )?;
+                let term = Term::from_field_text(field, &normalized_value);
+                Ok((term, None, normalized_value))
+            }
+            FieldType::JsonObject(json_options) => {
+                let text_field_indexing =
+                    json_options.get_text_indexing_options().ok_or_else(|| {
+                        InvalidQuery::SchemaError(format!(
+                            "field {} is not full-text searchable",
+                            field_entry.name()
+                        ))
+                    })?;
+                let normalized_value = normalize_value(
+                    &self.value,
+                    text_field_indexing.tokenizer(),
+                    tokenizer_manager,
+                )?;
+                let mut term_for_path = Term::from_field_json_path(
+                    field,
+                    json_path,
+                    json_options.is_expand_dots_enabled(),
+                );
+                term_for_path.append_type_and_str("");
+                // We skip the 1st byte which is a marker to tell this is json. This isn't present
+                // in the dictionary
+                let byte_path_prefix = term_for_path.value().as_serialized()[1..].to_owned();
+
+                let mut term = Term::from_field_json_path(
+                    field,
+                    json_path,
+                    json_options.is_expand_dots_enabled(),
+                );
+                term.append_type_and_str(&normalized_value);
+                Ok((term, Some(byte_path_prefix), normalized_value))
+            }
+            _ => Err(InvalidQuery::SchemaError(
+                "trying to run a fuzzy query on a non-text field".to_string(),
+            )),
+        }
+    }
+}
+
+impl BuildTantivyAst for FuzzyQuery {
+    fn build_tantivy_ast_impl(
+        &self,
+        schema: &TantivySchema,
+        tokenizer_manager: &TokenizerManager,
+        _search_fields: &[String],
+        _with_validation: bool,
+    ) -> Result<TantivyQueryAst, InvalidQuery> {
+        let (term, _path, _normalized_value) = self.to_term(schema, tokenizer_manager)?;
+        let fuzzy_term_query = FuzzyTermQuery::new(term, self.distance, self.transpositions);
+        Ok(fuzzy_term_query.into())
+    }
+}
+
+/// The Levenshtein automaton walked by a fuzzy query over the term dictionary.
+///
+/// It is only used to warm up the terms matched by fuzzy queries: the query itself relies on
+/// tantivy's `FuzzyTermQuery`, which builds the same automaton.
+pub struct LevenshteinAutomaton(DFA);
+
+impl LevenshteinAutomaton {
+    pub fn new(value: &str, distance: u8, transpositions: bool) -> Self {
+        let dfa = LevenshteinAutomatonBuilder::new(distance, transpositions).build_dfa(value);
+        LevenshteinAutomaton(dfa)
+    }
+}
+
+impl tantivy_fst::Automaton for LevenshteinAutomaton {
+    type State = u32;
+
+    fn start(&self) -> u32 {
+        self.0.initial_state()
+    }
+
+    fn is_match(&self, state: &u32) -> bool {
+        matches!(self.0.distance(*state), Distance::Exact(_))
+    }
+
+    fn can_match(&self, state: &u32) -> bool {
+        *state != SINK_STATE
+    }
+
+    fn accept(&self, state: &u32, byte: u8) -> u32 {
+        self.0.transition(*state, byte)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::schema::{TextFieldIndexing, TextOptions};
+    use tantivy_fst::Automaton;
+
+    use super::*;
+    use crate::create_default_quickwit_tokenizer_manager;
+
+    fn fuzzy_query(field: &str, value: &str, distance: u8) -> FuzzyQuery {
+        FuzzyQuery {
+            field: field.to_string(),
+            value: value.to_string(),
+            distance,
+            transpositions: true,
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_query_to_term_on_text() {
+        let mut schema_builder = TantivySchema::builder();
+        let text_options = TextOptions::default()
+            .set_indexing_options(TextFieldIndexing::default().set_tokenizer("default"));
+        let field = schema_builder.add_text_field("text_field", text_options);
+        let schema = schema_builder.build();
+        let tokenizer_manager = create_default_quickwit_tokenizer_manager();
+
+        let (term, path, normalized_value) = fuzzy_query("text_field", "Quickwit", 1)
+            .to_term(&schema, &tokenizer_manager)
+            .unwrap();
+        assert_eq!(term, Term::from_field_text(field, "quickwit"));
+        assert!(path.is_none());
+        assert_eq!(normalized_value, "quickwit");
+    }
+
+    #[test]
+    fn test_fuzzy_query_to_term_on_json() {
+        let mut schema_builder = TantivySchema::builder();
+        let text_options = TextOptions::default()
+            .set_indexing_options(TextFieldIndexing::default().set_tokenizer("raw"));
+        schema_builder.add_json_field("json_field", text_options);
+        let schema = schema_builder.build();
+        let tokenizer_manager = create_default_quickwit_tokenizer_manager();
+
+        let (_term, path, normalized_value) = fuzzy_query("json_field.Inner", "Quickwit", 1)
+            .to_term(&schema, &tokenizer_manager)
+            .unwrap();
+        assert_eq!(path.unwrap(), "Inner\0s".as_bytes());
+        assert_eq!(normalized_value, "Quickwit");
+    }
+
+    #[test]
+    fn test_fuzzy_query_invalid() {
+        let mut schema_builder = TantivySchema::builder();
+        schema_builder.add_u64_field("u64_field", tantivy::schema::INDEXED);
+        let schema = schema_builder.build();
+        let tokenizer_manager = create_default_quickwit_tokenizer_manager();
+
+        let err = fuzzy_query("missing_field", "quickwit", 1)
+            .to_term(&schema, &tokenizer_manager)
+            .unwrap_err();
+        assert!(matches!(err, InvalidQuery::FieldDoesNotExist { .. }));
+
+        let err = fuzzy_query("u64_field", "quickwit", 1)
+            .to_term(&schema, &tokenizer_manager)
+            .unwrap_err();
+        assert!(matches!(err, InvalidQuery::SchemaError(_)));
+
+        let err = fuzzy_query("u64_field", "quickwit", 3)
+            .to_term(&schema, &tokenizer_manager)
+            .unwrap_err();
+        assert!(matches!(err, InvalidQuery::Other(_)));
+    }
+
+    fn automaton_matches(automaton: &LevenshteinAutomaton, text: &str) -> bool {
+        let mut state = automaton.start();
+        for byte in text.as_bytes() {
+            state = automaton.accept(&state, *byte);
+        }
+        automaton.is_match(&state)
+    }
+
+    #[test]
+    fn test_levenshtein_automaton() {
+        let automaton = LevenshteinAutomaton::new("quickwit", 1, true);
+        assert!(automaton_matches(&automaton, "quickwit"));
+        assert!(automaton_matches(&automaton, "quikcwit"));
+        assert!(automaton_matches(&automaton, "quickwt"));
+        assert!(!automaton_matches(&automaton, "quikwt"));
+
+        let automaton = LevenshteinAutomaton::new("quickwit", 1, false);
+        assert!(!automaton_matches(&automaton, "quikcwit"));
+    }
+}
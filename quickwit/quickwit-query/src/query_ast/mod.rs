@@ -21,6 +21,7 @@ use crate::tokenizers::TokenizerManager;
 mod bool_query;
 mod field_presence;
 mod full_text_query;
+mod fuzzy_query;
 mod phrase_prefix_query;
 mod range_query;
 mod regex_query;
@@ -35,6 +36,7 @@ mod wildcard_query;
 pub use bool_query::BoolQuery;
 pub use field_presence::FieldPresenceQuery;
 pub use full_text_query::{FullTextMode, FullTextParams, FullTextQuery};
+pub use fuzzy_query::{FuzzyQuery, LevenshteinAutomaton, MAX_FUZZY_DISTANCE};
 pub use phrase_prefix_query::PhrasePrefixQuery;
 pub use range_query::RangeQuery;
 pub use regex_query::{AutomatonQuery, JsonPathPrefix, RegexQuery};
@@ -42,7 +44,7 @@ use tantivy_query_ast::TantivyQueryAst;
 pub use term_query::TermQuery;
 pub use term_set_query::TermSetQuery;
 pub use user_input_query::UserInputQuery;
-pub use visitor::{QueryAstTransformer, QueryAstVisitor};
+pub use visitor::{QueryAstTransformer, QueryAstVisitor, QueryRewriter};
 pub use wildcard_query::WildcardQuery;
 
 use crate::{BooleanOperand, InvalidQuery, NotNaNf32};
@@ -61,6 +63,7 @@ pub enum QueryAst {
     UserInput(UserInputQuery),
     Wildcard(WildcardQuery),
     Regex(RegexQuery),
+    Fuzzy(FuzzyQuery),
     MatchAll,
     MatchNone,
     Boost {
@@ -104,7 +107,8 @@ impl QueryAst {
             | ast @ QueryAst::FieldPresence(_)
             | ast @ QueryAst::Range(_)
             | ast @ QueryAst::Wildcard(_)
-            | ast @ QueryAst::Regex(_) => Ok(ast),
+            | ast @ QueryAst::Regex(_)
+            | ast @ QueryAst::Fuzzy(_) => Ok(ast),
             QueryAst::UserInput(user_text_query) => {
                 user_text_query.parse_user_query(default_search_fields)
             }
@@ -254,6 +258,12 @@ impl BuildTantivyAst for QueryAst {
                 search_fields,
                 with_validation,
             ),
+            QueryAst::Fuzzy(fuzzy) => fuzzy.build_tantivy_ast_call(
+                schema,
+                tokenizer_manager,
+                search_fields,
+                with_validation,
+            ),
         }
     }
 }
@@ -277,6 +277,7 @@ fn convert_user_input_literal(
                 query_ast::WildcardQuery {
                     field: field_name,
                     value: phrase.clone(),
+                    case_insensitive: false,
                     lenient,
                 }
                 .into()
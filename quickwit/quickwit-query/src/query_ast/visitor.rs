@@ -12,13 +12,26 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::convert::Infallible;
+
+use anyhow::{bail, ensure};
+use tantivy::schema::{FieldType, Schema as TantivySchema};
+
 use crate::not_nan_f32::NotNaNf32;
 use crate::query_ast::field_presence::FieldPresenceQuery;
 use crate::query_ast::user_input_query::UserInputQuery;
+use crate::query_ast::utils::find_field_or_hit_dynamic;
 use crate::query_ast::{
-    BoolQuery, FullTextQuery, PhrasePrefixQuery, QueryAst, RangeQuery, RegexQuery, TermQuery,
-    TermSetQuery, WildcardQuery,
+    BoolQuery, FullTextMode, FullTextParams, FullTextQuery, FuzzyQuery, PhrasePrefixQuery,
+    QueryAst, RangeQuery, RegexQuery, TermQuery, TermSetQuery, WildcardQuery,
 };
+use crate::tokenizers::RAW_TOKENIZER_NAME;
+use crate::{BooleanOperand, ID_FIELD_NAME};
+
+/// Tokenizers emitting the whole value as a single token. The fields using them are keyword
+/// fields, to which the synonyms and stop words of a [`QueryRewriter`] do not apply.
+const KEYWORD_TOKENIZER_NAMES: [&str; 3] = [RAW_TOKENIZER_NAME, "raw_lowercase", "lowercase"];
 
 /// Simple trait to implement a Visitor over the QueryAst.
 pub trait QueryAstVisitor<'a> {
@@ -41,6 +54,7 @@ pub trait QueryAstVisitor<'a> {
             QueryAst::FieldPresence(exists) => self.visit_exists(exists),
             QueryAst::Wildcard(wildcard) => self.visit_wildcard(wildcard),
             QueryAst::Regex(regex) => self.visit_regex(regex),
+            QueryAst::Fuzzy(fuzzy) => self.visit_fuzzy(fuzzy),
         }
     }
 
@@ -111,6 +125,10 @@ pub trait QueryAstVisitor<'a> {
     fn visit_regex(&mut self, _regex_query: &'a RegexQuery) -> Result<(), Self::Err> {
         Ok(())
     }
+
+    fn visit_fuzzy(&mut self, _fuzzy_query: &'a FuzzyQuery) -> Result<(), Self::Err> {
+        Ok(())
+    }
 }
 
 /// Simple trait to implement a Visitor over the QueryAst.
@@ -134,6 +152,7 @@ pub trait QueryAstTransformer {
             QueryAst::FieldPresence(exists) => self.transform_exists(exists),
             QueryAst::Wildcard(wildcard) => self.transform_wildcard(wildcard),
             QueryAst::Regex(regex) => self.transform_regex(regex),
+            QueryAst::Fuzzy(fuzzy) => self.transform_fuzzy(fuzzy),
         }
     }
 
@@ -236,4 +255,608 @@ pub trait QueryAstTransformer {
     fn transform_regex(&mut self, regex_query: RegexQuery) -> Result<Option<QueryAst>, Self::Err> {
         Ok(Some(QueryAst::Regex(regex_query)))
     }
+
+    fn transform_fuzzy(&mut self, fuzzy_query: FuzzyQuery) -> Result<Option<QueryAst>, Self::Err> {
+        Ok(Some(QueryAst::Fuzzy(fuzzy_query)))
+    }
+}
+
+/// Rewrites queries according to the query rewrite rules of an index: field aliases are resolved
+/// to their target field, stop words are removed and words with synonyms are expanded into a
+/// disjunction of their synonyms.
+///
+/// Stop words and synonyms are matched case-insensitively against the whitespace-separated words
+/// of full-text queries and against the values of term queries. They only apply to text fields,
+/// that is fields whose tokenizer splits values into words: the values of keyword fields, e.g.
+/// identifiers or status codes, are matched as is. Multi-word phrase queries are left untouched, as
+/// removing or replacing words would break their positions.
+#[derive(Debug, Default)]
+pub struct QueryRewriter {
+    field_aliases: HashMap<String, String>,
+    stop_words: HashSet<String>,
+    synonyms: HashMap<String, Vec<String>>,
+}
+
+impl QueryRewriter {
+    /// Creates a query rewriter.
+    ///
+    /// A synonym rule is either a comma-separated set of equivalent words, e.g. `k8s, kubernetes`,
+    /// or an explicit mapping replacing the words on its left side with the words or phrases on
+    /// its right side, e.g. `k8s, kube => kubernetes`.
+    pub fn new(
+        synonym_rules: &[String],
+        stop_words: &[String],
+        field_aliases: &BTreeMap<String, String>,
+    ) -> anyhow::Result<QueryRewriter> {
+        let mut synonyms: HashMap<String, Vec<String>> = HashMap::new();
+
+        for synonym_rule in synonym_rules {
+            let (words, replacements) = parse_synonym_rule(synonym_rule)?;
+            for word in words {
+                let word_synonyms = synonyms.entry(word.to_lowercase()).or_default();
+                for replacement in &replacements {
+                    if !word_synonyms.contains(replacement) {
+                        word_synonyms.push(replacement.clone());
+                    }
+                }
+            }
+        }
+        let stop_words = stop_words
+            .iter()
+            .map(|stop_word| {
+                let stop_word = stop_word.trim();
+                ensure!(
+                    !stop_word.is_empty() && !stop_word.contains(char::is_whitespace),
+                    "stop word `{stop_word}` must be a single word"
+                );
+                Ok(stop_word.to_lowercase())
+            })
+            .collect::<anyhow::Result<HashSet<String>>>()?;
+
+        for (alias, target_field) in field_aliases {
+            ensure!(
+                !alias.is_empty() && !target_field.is_empty(),
+                "field alias `{alias}` and its target field `{target_field}` must not be empty"
+            );
+            ensure!(
+                !field_aliases.contains_key(target_field),
+                "field alias `{alias}` must not target another alias"
+            );
+        }
+        Ok(QueryRewriter {
+            field_aliases: field_aliases.clone().into_iter().collect(),
+            stop_words,
+            synonyms,
+        })
+    }
+
+    /// Rewrites a query against the schema of an index. Queries made only of stop words match no
+    /// documents.
+    pub fn rewrite(&self, query_ast: QueryAst, schema: &TantivySchema) -> QueryAst {
+        let mut schema_query_rewriter = SchemaQueryRewriter {
+            query_rewriter: self,
+            schema,
+        };
+        let Ok(query_ast_opt) = schema_query_rewriter.transform(query_ast);
+        query_ast_opt.unwrap_or(QueryAst::MatchNone)
+    }
+
+    fn resolve_field(&self, field: String) -> String {
+        if let Some(target_field) = self.field_aliases.get(&field) {
+            return target_field.clone();
+        }
+        // The alias of an object field also applies to its subfields.
+        for (dot_pos, _) in field.match_indices('.').rev() {
+            if let Some(target_field) = self.field_aliases.get(&field[..dot_pos]) {
+                return format!("{target_field}{}", &field[dot_pos..]);
+            }
+        }
+        field
+    }
+
+    fn is_rewritten_word(&self, word: &str) -> bool {
+        let word_lowercase = word.to_lowercase();
+        self.stop_words.contains(&word_lowercase) || self.synonyms.contains_key(&word_lowercase)
+    }
+
+    /// Rewrites the full-text query of a single word. Returns `None` for stop words.
+    fn rewrite_word(&self, full_text_query: &FullTextQuery, word: &str) -> Option<QueryAst> {
+        let word_lowercase = word.to_lowercase();
+
+        if self.stop_words.contains(&word_lowercase) {
+            return None;
+        }
+        let Some(word_synonyms) = self.synonyms.get(&word_lowercase) else {
+            return Some(
+                FullTextQuery {
+                    text: word.to_string(),
+                    ..full_text_query.clone()
+                }
+                .into(),
+            );
+        };
+        let mut synonym_queries: Vec<QueryAst> = word_synonyms
+            .iter()
+            .map(|synonym| {
+                let params = if synonym.contains(char::is_whitespace) {
+                    FullTextParams {
+                        mode: FullTextMode::Phrase { slop: 0 },
+                        ..full_text_query.params.clone()
+                    }
+                } else {
+                    full_text_query.params.clone()
+                };
+                FullTextQuery {
+                    field: full_text_query.field.clone(),
+                    text: synonym.clone(),
+                    params,
+                    lenient: full_text_query.lenient,
+                }
+                .into()
+            })
+            .collect();
+        if synonym_queries.len() == 1 {
+            return synonym_queries.pop();
+        }
+        Some(
+            BoolQuery {
+                should: synonym_queries,
+                ..Default::default()
+            }
+            .into(),
+        )
+    }
+}
+
+/// Parses a synonym rule into the words it applies to and their replacements.
+fn parse_synonym_rule(synonym_rule: &str) -> anyhow::Result<(Vec<String>, Vec<String>)> {
+    fn split_words(words: &str) -> Vec<String> {
+        words
+            .split(',')
+            .map(str::trim)
+            .filter(|word| !word.is_empty())
+            .map(ToString::to_string)
+            .collect()
+    }
+    let (words, replacements) = match synonym_rule.split_once("=>") {
+        Some((words, replacements)) => (split_words(words), split_words(replacements)),
+        None => {
+            let words = split_words(synonym_rule);
+            (words.clone(), words)
+        }
+    };
+    if words.is_empty() || replacements.is_empty() {
+        bail!("synonym rule `{synonym_rule}` must have words on both sides of `=>`");
+    }
+    if let Some(word) = words.iter().find(|word| word.contains(char::is_whitespace)) {
+        bail!(
+            "synonym rule `{synonym_rule}` is invalid: `{word}` must be a single word, only \
+             replacements can be phrases"
+        );
+    }
+    Ok((words, replacements))
+}
+
+/// Applies the rules of a [`QueryRewriter`] to the queries of an index, whose schema tells the
+/// text fields apart.
+struct SchemaQueryRewriter<'a> {
+    query_rewriter: &'a QueryRewriter,
+    schema: &'a TantivySchema,
+}
+
+impl SchemaQueryRewriter<'_> {
+    fn resolve_field(&self, field: String) -> String {
+        self.query_rewriter.resolve_field(field)
+    }
+
+    /// Returns whether a field is a text field, to which synonyms and stop words apply.
+    fn is_text_field(&self, field: &str) -> bool {
+        if field == ID_FIELD_NAME {
+            return false;
+        }
+        let Some((_, field_entry, _)) = find_field_or_hit_dynamic(field, self.schema) else {
+            return false;
+        };
+        let text_indexing_opt = match field_entry.field_type() {
+            FieldType::Str(text_options) => text_options.get_indexing_options(),
+            FieldType::JsonObject(json_options) => json_options.get_text_indexing_options(),
+            _ => None,
+        };
+        if let Some(text_indexing) = text_indexing_opt {
+            !KEYWORD_TOKENIZER_NAMES.contains(&text_indexing.tokenizer())
+        } else {
+            false
+        }
+    }
+}
+
+impl QueryAstTransformer for SchemaQueryRewriter<'_> {
+    type Err = Infallible;
+
+    fn transform_term(
+        &mut self,
+        mut term_query: TermQuery,
+    ) -> Result<Option<QueryAst>, Infallible> {
+        term_query.field = self.resolve_field(term_query.field);
+
+        if !self.is_text_field(&term_query.field) {
+            return Ok(Some(term_query.into()));
+        }
+        let Some(word_synonyms) = self
+            .query_rewriter
+            .synonyms
+            .get(&term_query.value.to_lowercase())
+        else {
+            return Ok(Some(term_query.into()));
+        };
+        let term_set_query = TermSetQuery {
+            terms_per_field: HashMap::from([(
+                term_query.field,
+                word_synonyms.iter().cloned().collect(),
+            )]),
+        };
+        Ok(Some(term_set_query.into()))
+    }
+
+    fn transform_term_set(
+        &mut self,
+        term_set_query: TermSetQuery,
+    ) -> Result<Option<QueryAst>, Infallible> {
+        let terms_per_field = term_set_query
+            .terms_per_field
+            .into_iter()
+            .map(|(field, terms)| {
+                let field = self.resolve_field(field);
+
+                if !self.is_text_field(&field) {
+                    return (field, terms);
+                }
+                let terms: BTreeSet<String> = terms
+                    .into_iter()
+                    .flat_map(
+                        |term| match self.query_rewriter.synonyms.get(&term.to_lowercase()) {
+                            Some(word_synonyms) => word_synonyms.clone(),
+                            None => vec![term],
+                        },
+                    )
+                    .collect();
+                (field, terms)
+            })
+            .collect();
+        Ok(Some(TermSetQuery { terms_per_field }.into()))
+    }
+
+    fn transform_full_text(
+        &mut self,
+        mut full_text_query: FullTextQuery,
+    ) -> Result<Option<QueryAst>, Infallible> {
+        full_text_query.field = self.resolve_field(full_text_query.field);
+
+        if !self.is_text_field(&full_text_query.field) {
+            return Ok(Some(full_text_query.into()));
+        }
+        let words: Vec<&str> = full_text_query.text.split_whitespace().collect();
+        if words.len() == 1 {
+            return Ok(self.query_rewriter.rewrite_word(&full_text_query, words[0]));
+        }
+        let FullTextMode::Bool { operator } = full_text_query.params.mode else {
+            return Ok(Some(full_text_query.into()));
+        };
+        if !words
+            .iter()
+            .any(|word| self.query_rewriter.is_rewritten_word(word))
+        {
+            return Ok(Some(full_text_query.into()));
+        }
+        let word_queries: Vec<QueryAst> = words
+            .iter()
+            .filter_map(|word| self.query_rewriter.rewrite_word(&full_text_query, word))
+            .collect();
+        if word_queries.is_empty() {
+            return Ok(None);
+        }
+        let bool_query = match operator {
+            BooleanOperand::And => BoolQuery {
+                must: word_queries,
+                ..Default::default()
+            },
+            BooleanOperand::Or => BoolQuery {
+                should: word_queries,
+                ..Default::default()
+            },
+        };
+        Ok(Some(bool_query.into()))
+    }
+
+    fn transform_phrase_prefix(
+        &mut self,
+        mut phrase_prefix_query: PhrasePrefixQuery,
+    ) -> Result<Option<QueryAst>, Infallible> {
+        phrase_prefix_query.field = self.resolve_field(phrase_prefix_query.field);
+        Ok(Some(phrase_prefix_query.into()))
+    }
+
+    fn transform_range(
+        &mut self,
+        mut range_query: RangeQuery,
+    ) -> Result<Option<QueryAst>, Infallible> {
+        range_query.field = self.resolve_field(range_query.field);
+        Ok(Some(range_query.into()))
+    }
+
+    fn transform_exists(
+        &mut self,
+        mut exists_query: FieldPresenceQuery,
+    ) -> Result<Option<QueryAst>, Infallible> {
+        exists_query.field = self.resolve_field(exists_query.field);
+        Ok(Some(exists_query.into()))
+    }
+
+    fn transform_wildcard(
+        &mut self,
+        mut wildcard_query: WildcardQuery,
+    ) -> Result<Option<QueryAst>, Infallible> {
+        wildcard_query.field = self.resolve_field(wildcard_query.field);
+        Ok(Some(wildcard_query.into()))
+    }
+
+    fn transform_regex(
+        &mut self,
+        mut regex_query: RegexQuery,
+    ) -> Result<Option<QueryAst>, Infallible> {
+        regex_query.field = self.resolve_field(regex_query.field);
+        Ok(Some(regex_query.into()))
+    }
+
+    fn transform_fuzzy(
+        &mut self,
+        mut fuzzy_query: FuzzyQuery,
+    ) -> Result<Option<QueryAst>, Infallible> {
+        fuzzy_query.field = self.resolve_field(fuzzy_query.field);
+        Ok(Some(fuzzy_query.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::schema::{STRING, TEXT};
+
+    use super::*;
+    use crate::MatchAllOrNone;
+
+    fn schema_for_test() -> TantivySchema {
+        let mut schema_builder = TantivySchema::builder();
+        schema_builder.add_text_field("body", TEXT);
+        schema_builder.add_text_field("status", STRING);
+        schema_builder.add_json_field("resource", TEXT);
+        schema_builder.build()
+    }
+
+    fn full_text_query(field: &str, text: &str, mode: FullTextMode) -> FullTextQuery {
+        FullTextQuery {
+            field: field.to_string(),
+            text: text.to_string(),
+            params: FullTextParams {
+                tokenizer: None,
+                mode,
+                zero_terms_query: MatchAllOrNone::MatchNone,
+            },
+            lenient: false,
+        }
+    }
+
+    fn query_rewriter_for_test() -> QueryRewriter {
+        QueryRewriter::new(
+            &["k8s, kubernetes".to_string(), "nyc => new york".to_string()],
+            &["the".to_string(), "A".to_string()],
+            &BTreeMap::from([
+                ("msg".to_string(), "body".to_string()),
+                ("svc".to_string(), "resource.service".to_string()),
+            ]),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_query_rewriter_synonyms() {
+        let query_rewriter = query_rewriter_for_test();
+        let schema = schema_for_test();
+        let mode = FullTextMode::PhraseFallbackToIntersection;
+
+        let query_ast =
+            query_rewriter.rewrite(full_text_query("body", "K8s", mode).into(), &schema);
+        let expected_query_ast: QueryAst = BoolQuery {
+            should: vec![
+                full_text_query("body", "k8s", mode).into(),
+                full_text_query("body", "kubernetes", mode).into(),
+            ],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(query_ast, expected_query_ast);
+
+        let query_ast =
+            query_rewriter.rewrite(full_text_query("body", "nyc", mode).into(), &schema);
+        let expected_query_ast: QueryAst =
+            full_text_query("body", "new york", FullTextMode::Phrase { slop: 0 }).into();
+        assert_eq!(query_ast, expected_query_ast);
+
+        let query_ast = query_rewriter.rewrite(
+            TermQuery {
+                field: "body".to_string(),
+                value: "k8s".to_string(),
+            }
+            .into(),
+            &schema,
+        );
+        let expected_query_ast: QueryAst = TermSetQuery {
+            terms_per_field: HashMap::from([(
+                "body".to_string(),
+                BTreeSet::from(["k8s".to_string(), "kubernetes".to_string()]),
+            )]),
+        }
+        .into();
+        assert_eq!(query_ast, expected_query_ast);
+    }
+
+    #[test]
+    fn test_query_rewriter_stop_words() {
+        let query_rewriter = query_rewriter_for_test();
+        let schema = schema_for_test();
+        let mode: FullTextMode = BooleanOperand::And.into();
+
+        let query_ast = query_rewriter.rewrite(
+            full_text_query("body", "the printer is a jam", mode).into(),
+            &schema,
+        );
+        let expected_query_ast: QueryAst = BoolQuery {
+            must: vec![
+                full_text_query("body", "printer", mode).into(),
+                full_text_query("body", "is", mode).into(),
+                full_text_query("body", "jam", mode).into(),
+            ],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(query_ast, expected_query_ast);
+
+        // Stop words are removed from boolean queries.
+        let query_ast = query_rewriter.rewrite(
+            BoolQuery {
+                must: vec![
+                    full_text_query("body", "the", mode).into(),
+                    full_text_query("body", "printer", mode).into(),
+                ],
+                ..Default::default()
+            }
+            .into(),
+            &schema,
+        );
+        let expected_query_ast: QueryAst = BoolQuery {
+            must: vec![full_text_query("body", "printer", mode).into()],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(query_ast, expected_query_ast);
+
+        let query_ast =
+            query_rewriter.rewrite(full_text_query("body", "the", mode).into(), &schema);
+        assert_eq!(query_ast, QueryAst::MatchNone);
+
+        // Phrases are left untouched.
+        let phrase_query = full_text_query("body", "the k8s", FullTextMode::Phrase { slop: 0 });
+        let query_ast = query_rewriter.rewrite(phrase_query.clone().into(), &schema);
+        assert_eq!(query_ast, phrase_query.into());
+    }
+
+    #[test]
+    fn test_query_rewriter_field_aliases() {
+        let query_rewriter = query_rewriter_for_test();
+        let schema = schema_for_test();
+        let mode = FullTextMode::PhraseFallbackToIntersection;
+
+        let query_ast =
+            query_rewriter.rewrite(full_text_query("msg", "printer", mode).into(), &schema);
+        assert_eq!(query_ast, full_text_query("body", "printer", mode).into());
+        let query_ast = query_rewriter.rewrite(
+            FieldPresenceQuery {
+                field: "svc.name".to_string(),
+            }
+            .into(),
+            &schema,
+        );
+        assert_eq!(
+            query_ast,
+            FieldPresenceQuery {
+                field: "resource.service.name".to_string(),
+            }
+            .into()
+        );
+        let query_ast =
+            query_rewriter.rewrite(full_text_query("svcs", "printer", mode).into(), &schema);
+        assert_eq!(query_ast, full_text_query("svcs", "printer", mode).into());
+    }
+
+    #[test]
+    fn test_query_rewriter_ignores_keyword_fields() {
+        let query_rewriter = query_rewriter_for_test();
+        let schema = schema_for_test();
+        let mode: FullTextMode = BooleanOperand::And.into();
+
+        for field in ["status", ID_FIELD_NAME, "unknown"] {
+            let term_query: QueryAst = TermQuery {
+                field: field.to_string(),
+                value: "k8s".to_string(),
+            }
+            .into();
+            let query_ast = query_rewriter.rewrite(term_query.clone(), &schema);
+            assert_eq!(query_ast, term_query);
+
+            let term_set_query: QueryAst = TermSetQuery {
+                terms_per_field: HashMap::from([(
+                    field.to_string(),
+                    BTreeSet::from(["k8s".to_string()]),
+                )]),
+            }
+            .into();
+            let query_ast = query_rewriter.rewrite(term_set_query.clone(), &schema);
+            assert_eq!(query_ast, term_set_query);
+
+            let full_text_query_ast: QueryAst = full_text_query(field, "the k8s", mode).into();
+            let query_ast = query_rewriter.rewrite(full_text_query_ast.clone(), &schema);
+            assert_eq!(query_ast, full_text_query_ast);
+        }
+        // The text subfields of a JSON field are text fields.
+        let query_ast = query_rewriter.rewrite(
+            TermQuery {
+                field: "svc.name".to_string(),
+                value: "k8s".to_string(),
+            }
+            .into(),
+            &schema,
+        );
+        let expected_query_ast: QueryAst = TermSetQuery {
+            terms_per_field: HashMap::from([(
+                "resource.service.name".to_string(),
+                BTreeSet::from(["k8s".to_string(), "kubernetes".to_string()]),
+            )]),
+        }
+        .into();
+        assert_eq!(query_ast, expected_query_ast);
+    }
+
+    #[test]
+    fn test_query_rewriter_invalid_rules() {
+        let no_field_aliases = BTreeMap::new();
+        let error =
+            QueryRewriter::new(&["k8s =>".to_string()], &[], &no_field_aliases).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "synonym rule `k8s =>` must have words on both sides of `=>`"
+        );
+        let error = QueryRewriter::new(&["new york => nyc".to_string()], &[], &no_field_aliases)
+            .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("`new york` must be a single word")
+        );
+
+        let error =
+            QueryRewriter::new(&[], &["in the".to_string()], &no_field_aliases).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "stop word `in the` must be a single word"
+        );
+
+        let chained_field_aliases = BTreeMap::from([
+            ("a".to_string(), "b".to_string()),
+            ("b".to_string(), "c".to_string()),
+        ]);
+        let error = QueryRewriter::new(&[], &[], &chained_field_aliases).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "field alias `a` must not target another alias"
+        );
+    }
 }
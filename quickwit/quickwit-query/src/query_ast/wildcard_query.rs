@@ -30,6 +30,9 @@ use crate::{InvalidQuery, find_field_or_hit_dynamic};
 pub struct WildcardQuery {
     pub field: String,
     pub value: String,
+    /// Matches the value regardless of the case of the indexed terms.
+    #[serde(default)]
+    pub case_insensitive: bool,
     /// Support missing fields
     pub lenient: bool,
 }
@@ -121,6 +124,7 @@ impl WildcardQuery {
         let field_type = field_entry.field_type();
 
         let sub_query_parts = parse_wildcard_query(&self.value);
+        let regex_flags = if self.case_insensitive { "(?i)" } else { "" };
 
         match field_type {
             FieldType::Str(text_options) => {
@@ -134,7 +138,7 @@ impl WildcardQuery {
                 let regex =
                     sub_query_parts_to_regex(sub_query_parts, tokenizer_name, tokenizer_manager)?;
 
-                Ok((field, None, regex))
+                Ok((field, None, format!("{regex_flags}{regex}")))
             }
             FieldType::JsonObject(json_options) => {
                 let text_field_indexing =
@@ -160,7 +164,11 @@ impl WildcardQuery {
                 // in the dictionary
                 let byte_path_prefix = value.as_serialized()[1..].to_owned();
 
-                Ok((field, Some(byte_path_prefix), regex))
+                Ok((
+                    field,
+                    Some(byte_path_prefix),
+                    format!("{regex_flags}{regex}"),
+                ))
             }
             _ => Err(InvalidQuery::SchemaError(
                 "trying to run a Wildcard query on a non-text field".to_string(),
@@ -218,6 +226,7 @@ mod tests {
         let query = WildcardQuery {
             field: "text_field".to_string(),
             value: "MyString Wh1ch?a.nOrMal Tokenizer would*cut".to_string(),
+            case_insensitive: false,
             lenient: false,
         };
 
@@ -262,6 +271,7 @@ mod tests {
             // keep the case, but sanitize special chars
             field: "json_field.Inner.Fie*ld".to_string(),
             value: "MyString Wh1ch?a.nOrMal Tokenizer would*cut".to_string(),
+            case_insensitive: false,
             lenient: false,
         };
 
@@ -299,11 +309,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_wildcard_query_to_regex_case_insensitive() {
+        let query = WildcardQuery {
+            field: "text_field".to_string(),
+            value: "MyString*".to_string(),
+            case_insensitive: true,
+            lenient: false,
+        };
+        let tokenizer_manager = create_default_quickwit_tokenizer_manager();
+        let schema = single_text_field_schema("text_field", "raw");
+        let (_field, path, regex) = query.to_regex(&schema, &tokenizer_manager).unwrap();
+        assert_eq!(regex, "(?i)MyString.*");
+        assert!(path.is_none());
+        tantivy_fst::Regex::new(&regex).unwrap();
+    }
+
     #[test]
     fn test_extract_regex_wildcard_missing_field() {
         let query = WildcardQuery {
             field: "my_missing_field".to_string(),
             value: "My query value*".to_string(),
+            case_insensitive: false,
             lenient: false,
         };
         let tokenizer_manager = create_default_quickwit_tokenizer_manager();
@@ -23,7 +23,8 @@ pub use quickwit_ingest::CommitType;
 use quickwit_metastore::{IndexMetadata, Split, SplitInfo};
 use quickwit_proto::ingest::Shard;
 use quickwit_serve::{
-    ListSplitsQueryParams, ListSplitsResponse, RestIngestResponse, SearchRequestQueryString,
+    ListSplitsQueryParams, ListSplitsResponse, ReplayDeadLettersQueryParams,
+    ReplayDeadLettersResponse, RestIngestResponse, SearchRequestQueryString,
 };
 use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
 use reqwest::tls::Certificate;
@@ -662,6 +663,30 @@ impl<'a> SourceClient<'a> {
         Ok(())
     }
 
+    pub async fn replay_dead_letters(
+        &self,
+        source_id: &str,
+        replay_dead_letters_query_params: ReplayDeadLettersQueryParams,
+    ) -> Result<ReplayDeadLettersResponse, Error> {
+        let path = format!(
+            "{}/{source_id}/dead-letters/replay",
+            self.sources_root_url()
+        );
+        let response = self
+            .transport
+            .send(
+                Method::POST,
+                &path,
+                None,
+                Some(&replay_dead_letters_query_params),
+                None,
+                self.timeout,
+            )
+            .await?;
+        let replay_dead_letters_response = response.deserialize().await?;
+        Ok(replay_dead_letters_response)
+    }
+
     pub async fn list(&self) -> Result<Vec<SourceConfig>, Error> {
         let response = self
             .transport
@@ -802,7 +827,8 @@ mod test {
     use quickwit_ingest::CommitType;
     use quickwit_metastore::IndexMetadata;
     use quickwit_serve::{
-        ListSplitsQueryParams, ListSplitsResponse, RestIngestResponse, SearchRequestQueryString,
+        ListSplitsQueryParams, ListSplitsResponse, ReplayDeadLettersQueryParams,
+        ReplayDeadLettersResponse, RestIngestResponse, SearchRequestQueryString,
     };
     use reqwest::Url;
     use reqwest::header::CONTENT_TYPE;
@@ -1306,6 +1332,38 @@ mod test {
             .await
             .unwrap_err();
 
+        // POST replay dead letters
+        Mock::given(method("POST"))
+            .and(path(
+                "/api/v1/indexes/my-index/sources/my-source/dead-letters/replay",
+            ))
+            .and(query_param("start_timestamp", "1700000000"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(json!({
+                "num_replayed_docs": 3,
+                "num_rejected_docs": 1,
+                "num_kept_docs": 3,
+            })))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        let replay_dead_letters_query_params = ReplayDeadLettersQueryParams {
+            start_timestamp: Some(1_700_000_000),
+            end_timestamp: None,
+        };
+        let replay_dead_letters_response = qw_client
+            .sources("my-index")
+            .replay_dead_letters("my-source", replay_dead_letters_query_params)
+            .await
+            .unwrap();
+        assert_eq!(
+            replay_dead_letters_response,
+            ReplayDeadLettersResponse {
+                num_replayed_docs: 3,
+                num_rejected_docs: 1,
+                num_kept_docs: 3,
+            }
+        );
+
         // DELETE source
         Mock::given(method("DELETE"))
             .and(path("/api/v1/indexes/my-index/sources/my-source"))
@@ -0,0 +1,698 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use futures::StreamExt;
+use futures::stream::FuturesUnordered;
+use prost::Message;
+use quickwit_metastore::SplitMetadata;
+use quickwit_proto::metastore::MetastoreServiceClient;
+use quickwit_proto::search::{
+    AsyncSearchResponse, DeleteAsyncSearchRequest, DeleteAsyncSearchResponse,
+    GetAsyncSearchRequest, KvStore, LeafSearchRequest, LeafSearchResponse, SearchRequest,
+    SearchResponse, SplitSearchError, SubmitAsyncSearchRequest,
+};
+use tracing::{debug, warn};
+use ulid::Ulid;
+
+use crate::collector::{IncrementalCollector, make_merge_collector};
+use crate::root::{
+    IndexesMetasForLeafSearch, SearchJob, fetch_docs_phase, finalize_aggregation_if_any,
+    get_count_from_metadata, is_metadata_count_request, jobs_to_leaf_request,
+    plan_splits_for_root_search,
+};
+use crate::service::SearcherContext;
+use crate::{ClusterClient, SearchError};
+
+/// Maximum accepted async search keep alive.
+const MAX_ASYNC_SEARCH_KEEP_ALIVE: Duration = Duration::from_secs(5 * 24 * 3_600);
+
+/// Lifetime of the empty payload overwriting the state of a deleted async search.
+const DELETED_ASYNC_SEARCH_TTL: Duration = Duration::from_secs(60);
+
+/// Version of the states of the completed async searches in the KV store.
+const COMPLETED_ASYNC_SEARCH_VERSION: u64 = u64::MAX - 1;
+
+/// Version of the empty payload of a deleted async search, which no state can replace.
+const DELETED_ASYNC_SEARCH_VERSION: u64 = u64::MAX;
+
+/// Minimum interval between two updates of the partial response stored in the KV store.
+const PARTIAL_RESPONSE_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Interval at which the KV store is polled while waiting for an async search to complete.
+const WAIT_FOR_COMPLETION_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+pub(crate) fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time should be after the Unix epoch")
+        .as_millis() as i64
+}
+
+fn get_keep_alive_duration(keep_alive_secs: u32) -> crate::Result<Duration> {
+    let keep_alive = Duration::from_secs(keep_alive_secs as u64);
+    if keep_alive.is_zero() || keep_alive > MAX_ASYNC_SEARCH_KEEP_ALIVE {
+        return Err(SearchError::InvalidArgument(format!(
+            "async search keep alive must be between 1 and {} secs",
+            MAX_ASYNC_SEARCH_KEEP_ALIVE.as_secs()
+        )));
+    }
+    Ok(keep_alive)
+}
+
+fn parse_async_search_id(async_search_id: &str) -> crate::Result<Ulid> {
+    Ulid::from_string(async_search_id)
+        .map_err(|_| SearchError::InvalidArgument("async search id is malformed".to_string()))
+}
+
+fn async_search_key(async_search_ulid: Ulid) -> [u8; 16] {
+    u128::from(async_search_ulid).to_le_bytes()
+}
+
+fn async_search_not_found_error(async_search_id: &str) -> SearchError {
+    SearchError::NotFound(format!(
+        "async search `{async_search_id}` not found or expired"
+    ))
+}
+
+/// Returns the version of the state of an async search in the KV store. The version grows with
+/// the progress of the search, so that a state never replaces a more advanced one, and the state
+/// of a deleted async search is never replaced.
+fn async_search_version(async_search_response: &AsyncSearchResponse) -> u64 {
+    if !async_search_response.is_running {
+        return COMPLETED_ASYNC_SEARCH_VERSION;
+    }
+    async_search_response
+        .response
+        .as_ref()
+        .map(|search_response| {
+            search_response.num_successful_splits + search_response.failed_splits.len() as u64
+        })
+        .unwrap_or_default()
+}
+
+/// Loads the state of an async search from the KV store. Returns `None` if the async search
+/// expired or was deleted.
+async fn load_async_search_response(
+    async_search_key: &[u8],
+    cluster_client: &ClusterClient,
+) -> crate::Result<Option<AsyncSearchResponse>> {
+    let Some(payload) = cluster_client
+        .get_kv_from_store(KvStore::AsyncSearch, async_search_key)
+        .await
+        .filter(|payload| !payload.is_empty())
+    else {
+        return Ok(None);
+    };
+    let async_search_response = AsyncSearchResponse::decode(&payload[..])
+        .map_err(|_| SearchError::Internal("corrupted async search state".to_string()))?;
+    Ok(Some(async_search_response))
+}
+
+/// Stores the state of an async search in the KV store until it expires, unless a more advanced
+/// state is already stored or the async search was deleted.
+async fn store_async_search_response(
+    async_search_key: &[u8],
+    async_search_response: &AsyncSearchResponse,
+    cluster_client: &ClusterClient,
+) {
+    let ttl_millis = async_search_response.expiration_time_millis - now_millis();
+
+    if ttl_millis <= 0 {
+        return;
+    }
+    let payload = async_search_response.encode_to_vec();
+    let ttl = Duration::from_millis(ttl_millis as u64).max(Duration::from_secs(1));
+    let version = async_search_version(async_search_response);
+    cluster_client
+        .put_kv_in_store(
+            KvStore::AsyncSearch,
+            async_search_key,
+            &payload,
+            ttl,
+            Some(version),
+        )
+        .await;
+}
+
+/// Submits an async search: the splits are planned right away, so that invalid requests are
+/// rejected upfront, then the search runs in the background on this searcher, publishing its
+/// progress in the KV store so that any searcher can serve it.
+pub(crate) async fn root_submit_async_search(
+    submit_request: SubmitAsyncSearchRequest,
+    searcher_context: Arc<SearcherContext>,
+    mut metastore: MetastoreServiceClient,
+    cluster_client: &ClusterClient,
+) -> crate::Result<AsyncSearchResponse> {
+    let keep_alive = get_keep_alive_duration(submit_request.keep_alive_secs)?;
+    let Some(mut search_request) = submit_request.search_request else {
+        return Err(SearchError::InvalidArgument(
+            "async search request must contain a search request".to_string(),
+        ));
+    };
+    if search_request.scroll_ttl_secs.is_some() {
+        return Err(SearchError::InvalidArgument(
+            "scroll is not supported by async search".to_string(),
+        ));
+    }
+    let (split_metadatas, indexes_metas_for_leaf_search) =
+        plan_splits_for_root_search(&mut search_request, &mut metastore, cluster_client).await?;
+
+    let async_search_ulid = Ulid::new();
+    let start_time_millis = now_millis();
+    let async_search_response = AsyncSearchResponse {
+        async_search_id: async_search_ulid.to_string(),
+        is_running: true,
+        is_partial: true,
+        start_time_millis,
+        expiration_time_millis: start_time_millis + keep_alive.as_millis() as i64,
+        num_targeted_splits: split_metadatas.len() as u64,
+        response: None,
+        error: None,
+    };
+    let async_search_key = async_search_key(async_search_ulid);
+    store_async_search_response(&async_search_key, &async_search_response, cluster_client).await;
+
+    let async_search_task = AsyncSearchTask {
+        async_search_key,
+        async_search_response: async_search_response.clone(),
+        searcher_context,
+        cluster_client: cluster_client.clone(),
+        search_request,
+        split_metadatas,
+        indexes_metas_for_leaf_search,
+    };
+    let mut async_search_handle = tokio::spawn(async_search_task.run());
+
+    let wait_for_completion_timeout =
+        Duration::from_millis(submit_request.wait_for_completion_timeout_millis);
+    // The search keeps running in the background if the timeout elapses.
+    if let Ok(join_result) =
+        tokio::time::timeout(wait_for_completion_timeout, &mut async_search_handle).await
+    {
+        return Ok(join_result?);
+    }
+    let latest_async_search_response =
+        load_async_search_response(&async_search_key, cluster_client)
+            .await?
+            .unwrap_or(async_search_response);
+    Ok(latest_async_search_response)
+}
+
+/// Returns the state of an async search, waiting for it to complete up to the requested timeout.
+pub(crate) async fn root_get_async_search(
+    get_request: GetAsyncSearchRequest,
+    cluster_client: &ClusterClient,
+) -> crate::Result<AsyncSearchResponse> {
+    let async_search_ulid = parse_async_search_id(&get_request.async_search_id)?;
+    let keep_alive_opt = get_request
+        .keep_alive_secs
+        .map(get_keep_alive_duration)
+        .transpose()?;
+    let async_search_key = async_search_key(async_search_ulid);
+    let wait_deadline =
+        Instant::now() + Duration::from_millis(get_request.wait_for_completion_timeout_millis);
+
+    loop {
+        let mut async_search_response =
+            load_async_search_response(&async_search_key, cluster_client)
+                .await?
+                .ok_or_else(|| async_search_not_found_error(&get_request.async_search_id))?;
+        let now = Instant::now();
+
+        if !async_search_response.is_running || now >= wait_deadline {
+            if let Some(keep_alive) = keep_alive_opt {
+                async_search_response.expiration_time_millis =
+                    now_millis() + keep_alive.as_millis() as i64;
+                store_async_search_response(
+                    &async_search_key,
+                    &async_search_response,
+                    cluster_client,
+                )
+                .await;
+            }
+            return Ok(async_search_response);
+        }
+        tokio::time::sleep(WAIT_FOR_COMPLETION_POLL_INTERVAL.min(wait_deadline - now)).await;
+    }
+}
+
+/// Deletes an async search, cancelling it if it is still running.
+///
+/// The KV store has no delete operation, so the state is overwritten with an empty payload that
+/// expires shortly after. Its version prevents the searcher running the search from overwriting
+/// it, and the searcher notices it on its next update and stops.
+pub(crate) async fn root_delete_async_search(
+    delete_request: DeleteAsyncSearchRequest,
+    cluster_client: &ClusterClient,
+) -> crate::Result<DeleteAsyncSearchResponse> {
+    let async_search_ulid = parse_async_search_id(&delete_request.async_search_id)?;
+    let async_search_key = async_search_key(async_search_ulid);
+
+    if load_async_search_response(&async_search_key, cluster_client)
+        .await?
+        .is_none()
+    {
+        return Ok(DeleteAsyncSearchResponse { num_freed: 0 });
+    }
+    cluster_client
+        .put_kv_in_store(
+            KvStore::AsyncSearch,
+            &async_search_key,
+            &[],
+            DELETED_ASYNC_SEARCH_TTL,
+            Some(DELETED_ASYNC_SEARCH_VERSION),
+        )
+        .await;
+    Ok(DeleteAsyncSearchResponse { num_freed: 1 })
+}
+
+/// Search running in the background on behalf of an async search.
+struct AsyncSearchTask {
+    async_search_key: [u8; 16],
+    async_search_response: AsyncSearchResponse,
+    searcher_context: Arc<SearcherContext>,
+    cluster_client: ClusterClient,
+    search_request: SearchRequest,
+    split_metadatas: Vec<SplitMetadata>,
+    indexes_metas_for_leaf_search: IndexesMetasForLeafSearch,
+}
+
+impl AsyncSearchTask {
+    async fn run(mut self) -> AsyncSearchResponse {
+        let search_result = self.search().await;
+        self.async_search_response.is_running = false;
+
+        match search_result {
+            Ok(Some(search_response)) => {
+                self.async_search_response.is_partial = false;
+                self.async_search_response.response = Some(search_response);
+            }
+            Ok(None) => {
+                debug!(
+                    async_search_id = %self.async_search_response.async_search_id,
+                    "async search was deleted or expired"
+                );
+                return self.async_search_response;
+            }
+            Err(search_error) => {
+                warn!(
+                    async_search_id = %self.async_search_response.async_search_id,
+                    %search_error,
+                    "async search failed"
+                );
+                self.async_search_response.error = Some(search_error.to_string());
+            }
+        }
+        // Whether the async search was deleted in the meantime is irrelevant at this point.
+        let _ = self.update_async_search_response().await;
+        self.async_search_response
+    }
+
+    /// Stores the current state of the async search, unless it was deleted or expired in the
+    /// meantime, in which case `false` is returned. A deletion occurring between the load and the
+    /// store of the state is preserved by the version of its empty payload.
+    async fn update_async_search_response(&mut self) -> bool {
+        // Get requests may have extended the lifetime of the async search.
+        match load_async_search_response(&self.async_search_key, &self.cluster_client).await {
+            Ok(Some(stored_async_search_response)) => {
+                self.async_search_response.expiration_time_millis =
+                    stored_async_search_response.expiration_time_millis;
+            }
+            Ok(None) => return false,
+            Err(error) => {
+                warn!(%error, "failed to load async search state");
+            }
+        }
+        store_async_search_response(
+            &self.async_search_key,
+            &self.async_search_response,
+            &self.cluster_client,
+        )
+        .await;
+        true
+    }
+
+    /// Runs the search, merging the leaf responses as they arrive and periodically publishing
+    /// the partial response. Returns `None` if the async search was deleted or expired before
+    /// completing.
+    async fn search(&mut self) -> crate::Result<Option<SearchResponse>> {
+        let merge_collector = make_merge_collector(
+            &self.search_request,
+            &self.searcher_context.get_aggregation_limits(),
+        )?;
+        let mut incremental_collector = IncrementalCollector::new(merge_collector);
+
+        if is_metadata_count_request(&self.search_request) {
+            for leaf_search_response in get_count_from_metadata(&self.split_metadatas) {
+                incremental_collector.add_result(leaf_search_response)?;
+            }
+        } else {
+            let jobs: Vec<SearchJob> = self.split_metadatas.iter().map(SearchJob::from).collect();
+            let assigned_leaf_search_jobs = self
+                .cluster_client
+                .search_job_placer
+                .assign_jobs(jobs, &HashSet::default())
+                .await?;
+            let cluster_client = self.cluster_client.clone();
+            let mut leaf_search_futures = FuturesUnordered::new();
+
+            for (client, client_jobs) in assigned_leaf_search_jobs {
+                let leaf_request = jobs_to_leaf_request(
+                    &self.search_request,
+                    &self.indexes_metas_for_leaf_search,
+                    client_jobs,
+                )?;
+                let split_ids = leaf_request_split_ids(&leaf_request);
+                let leaf_search_future = cluster_client.leaf_search(leaf_request, client);
+                // Like root searches, the leaf searches are retried once by the cluster client.
+                // The splits of the leaf searches that still fail are reported as failed splits.
+                leaf_search_futures.push(async move {
+                    leaf_search_future.await.unwrap_or_else(|search_error| {
+                        failed_leaf_search_response(split_ids, &search_error)
+                    })
+                });
+            }
+            let mut last_update = Instant::now();
+
+            while let Some(leaf_search_response) = leaf_search_futures.next().await {
+                incremental_collector =
+                    merge_leaf_search_response(incremental_collector, leaf_search_response).await?;
+
+                if leaf_search_futures.is_empty()
+                    || last_update.elapsed() < PARTIAL_RESPONSE_UPDATE_INTERVAL
+                {
+                    continue;
+                }
+                let partial_leaf_search_response = incremental_collector.clone().finalize()?;
+                let partial_search_response =
+                    self.build_partial_search_response(partial_leaf_search_response)?;
+                self.async_search_response.response = Some(partial_search_response);
+
+                if !self.update_async_search_response().await {
+                    // Dropping the futures cancels the in-flight leaf requests.
+                    return Ok(None);
+                }
+                last_update = Instant::now();
+            }
+        }
+        let leaf_search_response = incremental_collector.finalize()?;
+
+        let hits = fetch_docs_phase(
+            &self.indexes_metas_for_leaf_search,
+            &leaf_search_response.partial_hits,
+            &self.split_metadatas,
+            &self.search_request,
+            &self.cluster_client,
+            None,
+        )
+        .await?;
+        let mut search_response = self.build_partial_search_response(leaf_search_response)?;
+        search_response.hits = hits;
+        search_response.elapsed_time_micros =
+            (now_millis() - self.async_search_response.start_time_millis) as u64 * 1_000;
+        Ok(Some(search_response))
+    }
+
+    /// Builds a search response, without hits, from the leaf responses merged so far.
+    fn build_partial_search_response(
+        &self,
+        leaf_search_response: LeafSearchResponse,
+    ) -> crate::Result<SearchResponse> {
+        // In case there is no index, we don't want the response to contain any aggregation
+        // structure.
+        let aggregation_postcard = if self.indexes_metas_for_leaf_search.is_empty() {
+            None
+        } else {
+            finalize_aggregation_if_any(
+                &self.search_request,
+                leaf_search_response.intermediate_aggregation_result,
+                &self.searcher_context,
+            )?
+        };
+        Ok(SearchResponse {
+            num_hits: leaf_search_response.num_hits,
+            hits: Vec::new(),
+            elapsed_time_micros: 0,
+            errors: Vec::new(),
+            aggregation_postcard,
+            scroll_id: None,
+            failed_splits: leaf_search_response.failed_splits,
+            num_successful_splits: leaf_search_response.num_successful_splits,
+            timed_out: false,
+            profile: None,
+        })
+    }
+}
+
+fn leaf_request_split_ids(leaf_request: &LeafSearchRequest) -> Vec<String> {
+    leaf_request
+        .leaf_requests
+        .iter()
+        .flat_map(|leaf_request_ref| leaf_request_ref.split_offsets.iter())
+        .map(|split_offsets| split_offsets.split_id.clone())
+        .collect()
+}
+
+/// Builds the response of a leaf search that failed altogether, in which all its splits failed.
+fn failed_leaf_search_response(
+    split_ids: Vec<String>,
+    search_error: &SearchError,
+) -> LeafSearchResponse {
+    warn!(%search_error, num_splits = split_ids.len(), "async leaf search failed");
+    let failed_splits = split_ids
+        .into_iter()
+        .map(|split_id| SplitSearchError {
+            error: search_error.to_string(),
+            split_id,
+            retryable_error: true,
+        })
+        .collect::<Vec<_>>();
+    LeafSearchResponse {
+        num_attempted_splits: failed_splits.len() as u64,
+        failed_splits,
+        ..Default::default()
+    }
+}
+
+/// Merges a leaf response into the collector. Merging aggregations is a cpu-bound task, so it is
+/// executed by the search thread pool.
+async fn merge_leaf_search_response(
+    mut incremental_collector: IncrementalCollector,
+    leaf_search_response: LeafSearchResponse,
+) -> crate::Result<IncrementalCollector> {
+    crate::search_thread_pool()
+        .run_cpu_intensive(move || {
+            incremental_collector.add_result(leaf_search_response)?;
+            Ok(incremental_collector)
+        })
+        .await
+        .map_err(|_| SearchError::Internal("failed to merge leaf search response".to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::RwLock;
+
+    use quickwit_common::ServiceStream;
+    use quickwit_indexing::MockSplitBuilder;
+    use quickwit_metastore::{IndexMetadata, ListSplitsResponseExt};
+    use quickwit_proto::metastore::{
+        ListIndexesMetadataResponse, ListSplitsResponse, MockMetastoreService,
+    };
+    use quickwit_query::query_ast::qast_json_helper;
+
+    use super::*;
+    use crate::{MockSearchService, SearchJobPlacer, searcher_pool_for_test};
+
+    fn mock_metastore() -> MockMetastoreService {
+        let mut mock_metastore = MockMetastoreService::new();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        mock_metastore.expect_list_indexes_metadata().returning(
+            move |_list_indexes_metadata_request| {
+                Ok(ListIndexesMetadataResponse::for_test(vec![
+                    index_metadata.clone(),
+                ]))
+            },
+        );
+        mock_metastore
+            .expect_list_splits()
+            .returning(move |_list_splits_request| {
+                let splits = vec![
+                    MockSplitBuilder::new("split-1")
+                        .with_index_uid(&index_uid)
+                        .build(),
+                    MockSplitBuilder::new("split-2")
+                        .with_index_uid(&index_uid)
+                        .build(),
+                ];
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        mock_metastore
+    }
+
+    fn mock_search_service_with_kv() -> MockSearchService {
+        let mut mock_search_service = MockSearchService::new();
+        let kv: Arc<RwLock<HashMap<Vec<u8>, Vec<u8>>>> = Default::default();
+        let kv_clone = kv.clone();
+        mock_search_service
+            .expect_put_kv()
+            .returning(move |put_kv_request| {
+                kv_clone
+                    .write()
+                    .unwrap()
+                    .insert(put_kv_request.key, put_kv_request.payload);
+            });
+        mock_search_service
+            .expect_get_kv()
+            .returning(move |get_kv_request| kv.read().unwrap().get(&get_kv_request.key).cloned());
+        mock_search_service
+    }
+
+    #[test]
+    fn test_get_keep_alive_duration() {
+        assert_eq!(
+            get_keep_alive_duration(60).unwrap(),
+            Duration::from_secs(60)
+        );
+        assert!(get_keep_alive_duration(0).is_err());
+        assert!(get_keep_alive_duration(6 * 24 * 3_600).is_err());
+    }
+
+    #[test]
+    fn test_parse_async_search_id() {
+        let async_search_ulid = Ulid::new();
+        assert_eq!(
+            parse_async_search_id(&async_search_ulid.to_string()).unwrap(),
+            async_search_ulid
+        );
+        assert!(parse_async_search_id("not-an-async-search-id").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_async_search_lifecycle() {
+        let mut mock_search_service = mock_search_service_with_kv();
+        mock_search_service
+            .expect_leaf_search()
+            .returning(|leaf_search_request| {
+                let num_splits = leaf_search_request.leaf_requests[0].split_offsets.len() as u64;
+                Ok(LeafSearchResponse {
+                    num_hits: 3 * num_splits,
+                    num_attempted_splits: num_splits,
+                    num_successful_splits: num_splits,
+                    ..Default::default()
+                })
+            });
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let cluster_client = ClusterClient::new(SearchJobPlacer::new(searcher_pool));
+        let searcher_context = Arc::new(SearcherContext::for_test());
+        let metastore = MetastoreServiceClient::from_mock(mock_metastore());
+
+        let submit_request = SubmitAsyncSearchRequest {
+            search_request: Some(SearchRequest {
+                index_id_patterns: vec!["test-index".to_string()],
+                query_ast: qast_json_helper("test", &["body"]),
+                max_hits: 0,
+                ..Default::default()
+            }),
+            keep_alive_secs: 60,
+            wait_for_completion_timeout_millis: 10_000,
+        };
+        let submit_response =
+            root_submit_async_search(submit_request, searcher_context, metastore, &cluster_client)
+                .await
+                .unwrap();
+        assert!(!submit_response.is_running);
+        assert!(!submit_response.is_partial);
+        assert_eq!(submit_response.num_targeted_splits, 2);
+        assert_eq!(submit_response.response.as_ref().unwrap().num_hits, 6);
+
+        let get_request = GetAsyncSearchRequest {
+            async_search_id: submit_response.async_search_id.clone(),
+            keep_alive_secs: Some(120),
+            wait_for_completion_timeout_millis: 0,
+        };
+        let get_response = root_get_async_search(get_request.clone(), &cluster_client)
+            .await
+            .unwrap();
+        assert_eq!(get_response.response, submit_response.response);
+        assert!(get_response.expiration_time_millis > submit_response.expiration_time_millis);
+
+        let delete_request = DeleteAsyncSearchRequest {
+            async_search_id: submit_response.async_search_id.clone(),
+        };
+        let delete_response = root_delete_async_search(delete_request.clone(), &cluster_client)
+            .await
+            .unwrap();
+        assert_eq!(delete_response.num_freed, 1);
+
+        let delete_response = root_delete_async_search(delete_request, &cluster_client)
+            .await
+            .unwrap();
+        assert_eq!(delete_response.num_freed, 0);
+
+        let error = root_get_async_search(get_request, &cluster_client)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, SearchError::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn test_async_search_reports_failed_splits() {
+        let mut mock_search_service = mock_search_service_with_kv();
+        mock_search_service
+            .expect_leaf_search()
+            .returning(|_leaf_search_request| {
+                Err(SearchError::Internal("leaf search failed".to_string()))
+            });
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let cluster_client = ClusterClient::new(SearchJobPlacer::new(searcher_pool));
+        let searcher_context = Arc::new(SearcherContext::for_test());
+        let metastore = MetastoreServiceClient::from_mock(mock_metastore());
+
+        let submit_request = SubmitAsyncSearchRequest {
+            search_request: Some(SearchRequest {
+                index_id_patterns: vec!["test-index".to_string()],
+                query_ast: qast_json_helper("test", &["body"]),
+                max_hits: 0,
+                ..Default::default()
+            }),
+            keep_alive_secs: 60,
+            wait_for_completion_timeout_millis: 10_000,
+        };
+        let submit_response =
+            root_submit_async_search(submit_request, searcher_context, metastore, &cluster_client)
+                .await
+                .unwrap();
+        assert!(!submit_response.is_running);
+        assert!(submit_response.error.is_none());
+
+        let search_response = submit_response.response.unwrap();
+        assert_eq!(search_response.num_hits, 0);
+        assert_eq!(search_response.num_successful_splits, 0);
+        let mut failed_split_ids: Vec<&str> = search_response
+            .failed_splits
+            .iter()
+            .map(|failed_split| failed_split.split_id.as_str())
+            .collect();
+        failed_split_ids.sort_unstable();
+        assert_eq!(failed_split_ids, ["split-1", "split-2"]);
+    }
+}
@@ -18,6 +18,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use bytesize::ByteSize;
+use futures::{StreamExt, TryStreamExt};
 use http::Uri;
 use quickwit_proto::search::{GetKvRequest, PutKvRequest, ReportSplitsRequest};
 use quickwit_proto::tonic::Request;
@@ -27,8 +28,8 @@ use quickwit_proto::{SpanContextInterceptor, tonic};
 use tower::timeout::Timeout;
 use tracing::warn;
 
-use crate::SearchService;
 use crate::error::parse_grpc_error;
+use crate::{LeafExportStream, SearchService};
 
 /// Impl is an enumeration that meant to manage Quickwit's search service client types.
 #[derive(Clone)]
@@ -180,6 +181,68 @@ impl SearchServiceClient {
         }
     }
 
+    /// Lists the root searches running on the targeted node.
+    pub async fn leaf_list_search_tasks(
+        &mut self,
+        request: quickwit_proto::search::ListSearchTasksRequest,
+    ) -> crate::Result<quickwit_proto::search::ListSearchTasksResponse> {
+        match &mut self.client_impl {
+            SearchServiceClientImpl::Grpc(grpc_client) => {
+                let tonic_request = Request::new(request);
+                let tonic_response = grpc_client
+                    .leaf_list_search_tasks(tonic_request)
+                    .await
+                    .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+                Ok(tonic_response.into_inner())
+            }
+            SearchServiceClientImpl::Local(service) => {
+                service.leaf_list_search_tasks(request).await
+            }
+        }
+    }
+
+    /// Cancels the root and leaf searches of a search task running on the targeted node.
+    pub async fn leaf_cancel_search_task(
+        &mut self,
+        request: quickwit_proto::search::CancelSearchTaskRequest,
+    ) -> crate::Result<quickwit_proto::search::CancelSearchTaskResponse> {
+        match &mut self.client_impl {
+            SearchServiceClientImpl::Grpc(grpc_client) => {
+                let tonic_request = Request::new(request);
+                let tonic_response = grpc_client
+                    .leaf_cancel_search_task(tonic_request)
+                    .await
+                    .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+                Ok(tonic_response.into_inner())
+            }
+            SearchServiceClientImpl::Local(service) => {
+                service.leaf_cancel_search_task(request).await
+            }
+        }
+    }
+
+    /// Streams the documents of a split matching a query, page by page.
+    pub async fn leaf_export(
+        &mut self,
+        request: quickwit_proto::search::LeafExportRequest,
+    ) -> crate::Result<LeafExportStream> {
+        match &mut self.client_impl {
+            SearchServiceClientImpl::Grpc(grpc_client) => {
+                let tonic_request = Request::new(request);
+                let tonic_response = grpc_client
+                    .leaf_export(tonic_request)
+                    .await
+                    .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+                let export_stream = tonic_response
+                    .into_inner()
+                    .map_err(|tonic_error| parse_grpc_error(&tonic_error))
+                    .boxed();
+                Ok(export_stream)
+            }
+            SearchServiceClientImpl::Local(service) => service.leaf_export(request).await,
+        }
+    }
+
     /// Gets the value associated to a key stored locally in the targeted node.
     /// This call is not "distributed".
     /// If the key is not present on the targeted search `None` is simply returned.
@@ -15,18 +15,22 @@
 use std::time::Duration;
 
 use base64::Engine;
-use futures::future::ready;
+use futures::future::{join_all, ready};
 use futures::{Future, StreamExt};
 use quickwit_proto::search::{
-    FetchDocsRequest, FetchDocsResponse, GetKvRequest, LeafListFieldsRequest, LeafListTermsRequest,
-    LeafListTermsResponse, LeafSearchRequest, LeafSearchResponse, ListFieldsResponse, PutKvRequest,
+    CancelSearchTaskRequest, CancelSearchTaskResponse, FetchDocsRequest, FetchDocsResponse,
+    GetKvRequest, KvStore, LeafExportRequest, LeafListFieldsRequest, LeafListTermsRequest,
+    LeafListTermsResponse, LeafSearchRequest, LeafSearchResponse, ListFieldsResponse,
+    ListSearchTasksRequest, ListSearchTasksResponse, PutKvRequest,
 };
-use tantivy::aggregation::intermediate_agg_result::IntermediateAggregationResults;
 use tracing::{debug, error, info, warn};
 
+use crate::collector::{
+    QuickwitAggregations, merge_collapse_groups, merge_intermediate_aggregation_result,
+};
 use crate::retry::search::LeafSearchRetryPolicy;
 use crate::retry::{DefaultRetryPolicy, RetryPolicy, retry_client};
-use crate::{SearchJobPlacer, SearchServiceClient, merge_resource_stats_it};
+use crate::{LeafExportStream, SearchJobPlacer, SearchServiceClient, merge_resource_stats_it};
 
 /// Maximum number of put requests emitted to perform a replicated given PUT KV.
 const MAX_PUT_KV_ATTEMPTS: usize = 6;
@@ -112,11 +116,50 @@ impl ClusterClient {
             "Leaf search response error: `{:?}`. Retry once to execute {:?} with {:?}",
             response_res, retry_request, client
         );
+        let aggregations_opt: Option<QuickwitAggregations> = retry_request
+            .search_request
+            .as_ref()
+            .and_then(|search_request| search_request.aggregation_request.as_ref())
+            .map(|aggregation_json| serde_json::from_str(aggregation_json))
+            .transpose()?;
         let retry_result = client.leaf_search(retry_request).await;
-        response_res = merge_original_with_retry_leaf_search_results(response_res, retry_result);
+        response_res = merge_original_with_retry_leaf_search_results(
+            &aggregations_opt,
+            response_res,
+            retry_result,
+        );
         response_res
     }
 
+    /// Leaf export with retry on another node client.
+    ///
+    /// Only the opening of the stream is retried: the caller is in charge of resuming a stream
+    /// that fails midway.
+    pub async fn leaf_export(
+        &self,
+        request: LeafExportRequest,
+        mut client: SearchServiceClient,
+    ) -> crate::Result<LeafExportStream> {
+        let export_stream_res = client.leaf_export(request.clone()).await;
+        let retry_policy = DefaultRetryPolicy {};
+        let Some(retry_request) = retry_policy.retry_request(request, &export_stream_res) else {
+            return export_stream_res;
+        };
+        let split_id = retry_request
+            .split_offsets
+            .as_ref()
+            .map(|split_offsets| split_offsets.split_id.as_str())
+            .unwrap_or_default();
+        client = retry_client(&self.search_job_placer, client.grpc_addr(), split_id).await?;
+        debug!(
+            "Leaf export response error: `{:?}`. Retry once to execute {:?} with {:?}",
+            export_stream_res.err(),
+            retry_request,
+            client
+        );
+        client.leaf_export(retry_request).await
+    }
+
     /// Leaf search with retry on another node client.
     pub async fn leaf_list_fields(
         &self,
@@ -136,6 +179,82 @@ impl ClusterClient {
         client.leaf_list_terms(request.clone()).await
     }
 
+    /// Lists the root searches running on all the search nodes of the cluster.
+    ///
+    /// Nodes that fail to answer are skipped.
+    pub async fn list_search_tasks(&self) -> ListSearchTasksResponse {
+        let list_search_tasks_futures =
+            self.search_job_placer
+                .all_nodes()
+                .into_iter()
+                .map(|mut client| async move {
+                    let grpc_addr = client.grpc_addr();
+                    match client
+                        .leaf_list_search_tasks(ListSearchTasksRequest {})
+                        .await
+                    {
+                        Ok(list_search_tasks_response) => list_search_tasks_response
+                            .search_tasks
+                            .into_iter()
+                            .map(|mut search_task| {
+                                search_task.searcher_grpc_addr = grpc_addr.to_string();
+                                search_task
+                            })
+                            .collect(),
+                        Err(error) => {
+                            warn!(%error, %grpc_addr, "failed to list search tasks");
+                            Vec::new()
+                        }
+                    }
+                });
+        let search_tasks = join_all(list_search_tasks_futures)
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+        ListSearchTasksResponse { search_tasks }
+    }
+
+    /// Cancels the root and leaf searches of a search task on all the search nodes of the
+    /// cluster.
+    pub async fn cancel_search_task(&self, search_task_id: &str) -> CancelSearchTaskResponse {
+        let cancel_request = CancelSearchTaskRequest {
+            search_task_id: search_task_id.to_string(),
+        };
+        let cancel_futures = self
+            .search_job_placer
+            .all_nodes()
+            .into_iter()
+            .map(|mut client| {
+                let cancel_request = cancel_request.clone();
+                async move {
+                    let grpc_addr = client.grpc_addr();
+                    match client.leaf_cancel_search_task(cancel_request).await {
+                        Ok(mut cancel_response) => {
+                            for search_task in &mut cancel_response.cancelled_search_tasks {
+                                search_task.searcher_grpc_addr = grpc_addr.to_string();
+                            }
+                            Some(cancel_response)
+                        }
+                        Err(error) => {
+                            warn!(%error, %grpc_addr, "failed to cancel search task");
+                            None
+                        }
+                    }
+                }
+            });
+        let mut merged_cancel_response = CancelSearchTaskResponse::default();
+
+        for cancel_response in join_all(cancel_futures).await.into_iter().flatten() {
+            merged_cancel_response
+                .cancelled_search_tasks
+                .extend(cancel_response.cancelled_search_tasks);
+            merged_cancel_response.num_cancelled_leaf_searches +=
+                cancel_response.num_cancelled_leaf_searches;
+        }
+        merged_cancel_response
+    }
+
     /// Attempts to store a given key value pair within the cluster.
     ///
     /// Tries to replicate the pair to [`TARGET_NUM_REPLICATION`] nodes, but this function may fail
@@ -143,6 +262,20 @@ impl ClusterClient {
     /// persistent. For instance during a rolling upgrade, all replicas will be lost as there is no
     /// mechanism to maintain the replication count.
     pub async fn put_kv(&self, key: &[u8], payload: &[u8], ttl: Duration) {
+        self.put_kv_in_store(KvStore::SearchContext, key, payload, ttl, None)
+            .await
+    }
+
+    /// Same as [`ClusterClient::put_kv`], targeting a specific store. If a version is specified,
+    /// the replicas do not replace the payloads stored with a higher version.
+    pub async fn put_kv_in_store(
+        &self,
+        store: KvStore,
+        key: &[u8],
+        payload: &[u8],
+        ttl: Duration,
+        version_opt: Option<u64>,
+    ) {
         let clients: Vec<SearchServiceClient> = self
             .search_job_placer
             .best_nodes_per_affinity(key)
@@ -166,9 +299,9 @@ impl ClusterClient {
         //
         // The requests are made in a concurrent manner, up to TARGET_NUM_REPLICATION at a time. As
         // soon as TARGET_NUM_REPLICATION requests are successful, we stop.
-        let put_kv_futs = clients
-            .into_iter()
-            .map(|client| replicate_kv_to_one_server(client, key, payload, ttl));
+        let put_kv_futs = clients.into_iter().map(|client| {
+            replicate_kv_to_one_server(client, store, key, payload, ttl, version_opt)
+        });
         let successful_replication = futures::stream::iter(put_kv_futs)
             .buffer_unordered(TARGET_NUM_REPLICATION)
             .filter(|put_kv_successful| ready(*put_kv_successful))
@@ -183,10 +316,18 @@ impl ClusterClient {
 
     /// Returns a search_after context
     pub async fn get_kv(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.get_kv_from_store(KvStore::SearchContext, key).await
+    }
+
+    /// Same as [`ClusterClient::get_kv`], targeting a specific store.
+    pub async fn get_kv_from_store(&self, store: KvStore, key: &[u8]) -> Option<Vec<u8>> {
         let clients = self.search_job_placer.best_nodes_per_affinity(key).await;
         // On the read side, we attempt to contact up to 6 nodes.
         for mut client in clients.take(MAX_GET_KV_ATTEMPTS) {
-            let get_request = GetKvRequest { key: key.to_vec() };
+            let get_request = GetKvRequest {
+                key: key.to_vec(),
+                store: store as i32,
+            };
             if let Ok(Some(search_after_resp)) = client.get_kv(get_request.clone()).await {
                 return Some(search_after_resp);
             } else {
@@ -200,14 +341,18 @@ impl ClusterClient {
 
 fn replicate_kv_to_one_server(
     mut client: SearchServiceClient,
+    store: KvStore,
     key: &[u8],
     payload: &[u8],
     ttl: Duration,
+    version_opt: Option<u64>,
 ) -> impl Future<Output = bool> {
     let put_kv_request = PutKvRequest {
         key: key.to_vec(),
         payload: payload.to_vec(),
         ttl_secs: ttl.as_secs() as u32,
+        store: store as i32,
+        version: version_opt,
     };
     let base64_key: String = base64::prelude::BASE64_STANDARD.encode(key);
     async move {
@@ -220,18 +365,6 @@ fn replicate_kv_to_one_server(
     }
 }
 
-/// Takes two intermediate aggregation results serialized using postcard,
-/// merge them and returns the merged serialized result.
-fn merge_intermediate_aggregation(left: &[u8], right: &[u8]) -> crate::Result<Vec<u8>> {
-    let mut intermediate_aggregation_results_left: IntermediateAggregationResults =
-        postcard::from_bytes(left)?;
-    let intermediate_aggregation_results_right: IntermediateAggregationResults =
-        postcard::from_bytes(right)?;
-    intermediate_aggregation_results_left.merge_fruits(intermediate_aggregation_results_right)?;
-    let serialized = postcard::to_allocvec(&intermediate_aggregation_results_left)?;
-    Ok(serialized)
-}
-
 /// Merge two leaf search response.
 ///
 /// # Quirk
@@ -241,6 +374,7 @@ fn merge_intermediate_aggregation(left: &[u8], right: &[u8]) -> crate::Result<Ve
 /// list of the left one, so that the list of the overal failed splits is the list of splits on the
 /// `right_response`.
 fn merge_original_with_retry_leaf_search_response(
+    aggregations_opt: &Option<QuickwitAggregations>,
     mut original_response: LeafSearchResponse,
     retry_response: LeafSearchResponse,
 ) -> crate::Result<LeafSearchResponse> {
@@ -251,11 +385,10 @@ fn merge_original_with_retry_leaf_search_response(
         original_response.intermediate_aggregation_result,
         retry_response.intermediate_aggregation_result,
     ) {
-        (Some(left_agg_bytes), Some(right_agg_bytes)) => {
-            let intermediate_aggregation_bytes: Vec<u8> =
-                merge_intermediate_aggregation(&left_agg_bytes[..], &right_agg_bytes[..])?;
-            Some(intermediate_aggregation_bytes)
-        }
+        (Some(left_agg_bytes), Some(right_agg_bytes)) => merge_intermediate_aggregation_result(
+            aggregations_opt,
+            [&left_agg_bytes[..], &right_agg_bytes[..]].into_iter(),
+        )?,
         (None, Some(right)) => Some(right),
         (Some(left), None) => Some(left),
         (None, None) => None,
@@ -274,18 +407,28 @@ fn merge_original_with_retry_leaf_search_response(
         num_successful_splits: original_response.num_successful_splits
             + retry_response.num_successful_splits,
         resource_stats,
+        timed_out: original_response.timed_out || retry_response.timed_out,
+        collapse_groups: merge_collapse_groups(
+            original_response
+                .collapse_groups
+                .into_iter()
+                .chain(retry_response.collapse_groups),
+        ),
     })
 }
 
 // Merge initial leaf search results with results obtained from a retry.
 fn merge_original_with_retry_leaf_search_results(
+    aggregations_opt: &Option<QuickwitAggregations>,
     left_search_response_result: crate::Result<LeafSearchResponse>,
     right_search_response_result: crate::Result<LeafSearchResponse>,
 ) -> crate::Result<LeafSearchResponse> {
     match (left_search_response_result, right_search_response_result) {
-        (Ok(left_response), Ok(right_response)) => {
-            merge_original_with_retry_leaf_search_response(left_response, right_response)
-        }
+        (Ok(left_response), Ok(right_response)) => merge_original_with_retry_leaf_search_response(
+            aggregations_opt,
+            left_response,
+            right_response,
+        ),
         (Ok(single_valid_response), Err(_)) => Ok(single_valid_response),
         (Err(_), Ok(single_valid_response)) => Ok(single_valid_response),
         (Err(error), Err(_)) => Err(error),
@@ -314,6 +457,7 @@ mod tests {
             split_id: split_id.to_string(),
             segment_ord: 1,
             doc_id,
+            collapse_key: None,
         }
     }
 
@@ -366,6 +510,8 @@ mod tests {
                     },
                 ],
             }],
+            timeout_millis: None,
+            search_task_id: String::new(),
         }
     }
 
@@ -538,6 +684,7 @@ mod tests {
             ..Default::default()
         };
         let merged_leaf_search_response = merge_original_with_retry_leaf_search_results(
+            &None,
             Ok(leaf_response),
             Ok(leaf_response_retry),
         )
@@ -564,6 +711,7 @@ mod tests {
             ..Default::default()
         };
         let merged_result = merge_original_with_retry_leaf_search_results(
+            &None,
             Err(SearchError::Internal("error".to_string())),
             Ok(leaf_response),
         )
@@ -578,6 +726,7 @@ mod tests {
     #[test]
     fn test_merge_leaf_search_retry_error_on_error() -> anyhow::Result<()> {
         let merge_error = merge_original_with_retry_leaf_search_results(
+            &None,
             Err(SearchError::Internal("error".to_string())),
             Err(SearchError::Internal("retry error".to_string())),
         )
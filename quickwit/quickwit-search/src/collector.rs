@@ -14,14 +14,15 @@
 
 use std::borrow::Cow;
 use std::cmp::Ordering;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::Hash;
 
 use itertools::Itertools;
 use quickwit_common::binary_heap::{SortKeyMapper, TopK};
 use quickwit_doc_mapper::{FastFieldWarmupInfo, WarmupInfo};
 use quickwit_proto::search::{
-    LeafSearchResponse, PartialHit, ResourceStats, SearchRequest, SortByValue, SortOrder,
-    SortValue, SplitSearchError,
+    CollapseGroup, CollapseRequest, LeafSearchResponse, PartialHit, ResourceStats, SearchRequest,
+    SortByValue, SortOrder, SortValue, SplitSearchError,
 };
 use quickwit_proto::types::SplitId;
 use serde::Deserialize;
@@ -33,8 +34,13 @@ use tantivy::columnar::{ColumnType, MonotonicallyMappableToU64};
 use tantivy::fastfield::Column;
 use tantivy::{DateTime, DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
 
+use crate::composite_aggregation::{
+    CompositeAggregation, CompositeIntermediateResult, CompositeSegmentCollector,
+};
 use crate::find_trace_ids_collector::{FindTraceIdsCollector, FindTraceIdsSegmentCollector, Span};
-use crate::top_k_collector::{QuickwitSegmentTopKCollector, specialized_top_k_segment_collector};
+use crate::top_k_collector::{
+    CollapseSegmentTopKCollector, QuickwitSegmentTopKCollector, specialized_top_k_segment_collector,
+};
 use crate::{GlobalDocAddress, merge_resource_stats, merge_resource_stats_it};
 
 #[derive(Clone, Debug)]
@@ -468,6 +474,7 @@ fn get_score_extractor(
 #[allow(clippy::large_enum_variant)]
 enum AggregationSegmentCollectors {
     FindTraceIdsSegmentCollector(Box<FindTraceIdsSegmentCollector>),
+    CompositeAggregationSegmentCollector(Box<CompositeSegmentCollector>),
     TantivyAggregationSegmentCollector(AggregationSegmentCollector),
 }
 
@@ -516,6 +523,7 @@ impl SegmentPartialHit {
             doc_id: self.doc_id,
             split_id,
             segment_ord,
+            collapse_key: None,
         }
     }
 }
@@ -536,6 +544,9 @@ impl SegmentCollector for QuickwitSegmentCollector {
             Some(AggregationSegmentCollectors::FindTraceIdsSegmentCollector(collector)) => {
                 collector.collect_block(filtered_docs)
             }
+            Some(AggregationSegmentCollectors::CompositeAggregationSegmentCollector(collector)) => {
+                collector.collect_block(filtered_docs)
+            }
             Some(AggregationSegmentCollectors::TantivyAggregationSegmentCollector(collector)) => {
                 collector.collect_block(filtered_docs)
             }
@@ -554,6 +565,9 @@ impl SegmentCollector for QuickwitSegmentCollector {
             Some(AggregationSegmentCollectors::FindTraceIdsSegmentCollector(collector)) => {
                 collector.collect(doc_id, score)
             }
+            Some(AggregationSegmentCollectors::CompositeAggregationSegmentCollector(collector)) => {
+                collector.collect(doc_id, score)
+            }
             Some(AggregationSegmentCollectors::TantivyAggregationSegmentCollector(collector)) => {
                 collector.collect(doc_id, score)
             }
@@ -563,8 +577,10 @@ impl SegmentCollector for QuickwitSegmentCollector {
 
     fn harvest(self) -> Self::Fruit {
         let mut partial_hits: Vec<PartialHit> = Vec::new();
+        let mut collapse_groups: Vec<CollapseGroup> = Vec::new();
         if let Some(segment_top_k_collector) = self.segment_top_k_collector {
             partial_hits = segment_top_k_collector.get_top_k();
+            collapse_groups = segment_top_k_collector.get_collapse_groups();
         }
 
         let intermediate_aggregation_result = match self.aggregation {
@@ -574,6 +590,12 @@ impl SegmentCollector for QuickwitSegmentCollector {
                     postcard::to_allocvec(&fruit).expect("Collector fruit should be serializable.");
                 Some(serialized)
             }
+            Some(AggregationSegmentCollectors::CompositeAggregationSegmentCollector(collector)) => {
+                let fruit: CompositeIntermediateResult = collector.harvest();
+                let serialized =
+                    postcard::to_allocvec(&fruit).expect("Collector fruit should be serializable.");
+                Some(serialized)
+            }
             Some(AggregationSegmentCollectors::TantivyAggregationSegmentCollector(collector)) => {
                 let serialized = postcard::to_allocvec(&collector.harvest()?)
                     .expect("Collector fruit should be serializable.");
@@ -590,6 +612,8 @@ impl SegmentCollector for QuickwitSegmentCollector {
             num_attempted_splits: 1,
             num_successful_splits: 1,
             resource_stats: None,
+            timed_out: false,
+            collapse_groups,
         })
     }
 }
@@ -601,6 +625,8 @@ pub enum QuickwitAggregations {
     /// Aggregation used by the Jaeger service to find trace IDs that match a
     /// [`quickwit_proto::jaeger::storage::v1::FindTraceIDsRequest`].
     FindTraceIdsAggregation(FindTraceIdsCollector),
+    /// Elasticsearch composite aggregation, which tantivy does not support.
+    CompositeAggregation(CompositeAggregation),
     /// Your classic Tantivy aggregation.
     TantivyAggregations(Aggregations),
 }
@@ -612,6 +638,9 @@ impl QuickwitAggregations {
             QuickwitAggregations::FindTraceIdsAggregation(collector) => {
                 collector.fast_field_names()
             }
+            QuickwitAggregations::CompositeAggregation(aggregation) => {
+                aggregation.fast_field_names()
+            }
             QuickwitAggregations::TantivyAggregations(aggregations) => {
                 get_fast_field_names(aggregations)
             }
@@ -623,6 +652,9 @@ impl QuickwitAggregations {
             QuickwitAggregations::FindTraceIdsAggregation(aggreg) => {
                 QuickwitIncrementalAggregations::FindTraceIdsAggregation(aggreg.clone(), Vec::new())
             }
+            QuickwitAggregations::CompositeAggregation(aggreg) => {
+                QuickwitIncrementalAggregations::CompositeAggregation(aggreg.clone(), Vec::new())
+            }
             QuickwitAggregations::TantivyAggregations(aggreg) => {
                 QuickwitIncrementalAggregations::TantivyAggregations(aggreg.clone(), Vec::new())
             }
@@ -633,6 +665,7 @@ impl QuickwitAggregations {
 #[derive(Clone)]
 enum QuickwitIncrementalAggregations {
     FindTraceIdsAggregation(FindTraceIdsCollector, Vec<Vec<Span>>),
+    CompositeAggregation(CompositeAggregation, Vec<CompositeIntermediateResult>),
     TantivyAggregations(Aggregations, Vec<Vec<u8>>),
     NoAggregation,
 }
@@ -649,6 +682,15 @@ impl QuickwitIncrementalAggregations {
                     state.push(new_state);
                 }
             }
+            QuickwitIncrementalAggregations::CompositeAggregation(aggregation, state) => {
+                let fruit: CompositeIntermediateResult =
+                    postcard::from_bytes(&intermediate_result).map_err(map_error)?;
+                state.push(fruit);
+                if state.len() > 1 {
+                    let new_state = aggregation.merge_results(std::mem::take(state));
+                    state.push(new_state);
+                }
+            }
             QuickwitIncrementalAggregations::TantivyAggregations(_, state) => {
                 state.push(intermediate_result);
             }
@@ -673,10 +715,12 @@ impl QuickwitIncrementalAggregations {
                         split_id: SplitId::new(),
                         segment_ord: 0,
                         doc_id: 0,
+                        collapse_key: None,
                     });
                 }
                 None
             }
+            QuickwitIncrementalAggregations::CompositeAggregation(_, _) => None,
             QuickwitIncrementalAggregations::TantivyAggregations(_, _) => None,
             QuickwitIncrementalAggregations::NoAggregation => None,
         }
@@ -693,6 +737,11 @@ impl QuickwitIncrementalAggregations {
                 let serialized = postcard::to_allocvec(&merged_fruit).map_err(map_error)?;
                 Ok(Some(serialized))
             }
+            QuickwitIncrementalAggregations::CompositeAggregation(_, mut state) => {
+                let merged_fruit = state.pop().unwrap_or_default();
+                let serialized = postcard::to_allocvec(&merged_fruit).map_err(map_error)?;
+                Ok(Some(serialized))
+            }
             QuickwitIncrementalAggregations::TantivyAggregations(aggregation, state) => {
                 merge_intermediate_aggregation_result(
                     &Some(QuickwitAggregations::TantivyAggregations(aggregation)),
@@ -717,6 +766,8 @@ pub(crate) struct QuickwitCollector {
     pub aggregation: Option<QuickwitAggregations>,
     pub aggregation_limits: AggregationLimitsGuard,
     search_after: Option<PartialHit>,
+    /// Collapses hits on the value of a fast field, keeping only the best hits of each value.
+    pub collapse: Option<CollapseRequest>,
 }
 
 impl QuickwitCollector {
@@ -731,6 +782,7 @@ impl QuickwitCollector {
         self.max_hits = search_request.max_hits as usize;
         self.start_offset = search_request.start_offset as usize;
         self.search_after.clone_from(&search_request.search_after);
+        self.collapse.clone_from(&search_request.collapse);
     }
     pub fn fast_field_names(&self) -> HashSet<String> {
         let mut fast_field_names = HashSet::default();
@@ -741,6 +793,9 @@ impl QuickwitCollector {
         if let Some(aggregations) = &self.aggregation {
             fast_field_names.extend(aggregations.fast_field_names());
         }
+        if let Some(collapse) = &self.collapse {
+            fast_field_names.insert(collapse.field.clone());
+        }
         fast_field_names
     }
 
@@ -779,6 +834,11 @@ impl Collector for QuickwitCollector {
                     Box::new(collector.for_segment(0, segment_reader)?),
                 ))
             }
+            Some(QuickwitAggregations::CompositeAggregation(aggregation)) => Some(
+                AggregationSegmentCollectors::CompositeAggregationSegmentCollector(Box::new(
+                    aggregation.for_segment(0, segment_reader)?,
+                )),
+            ),
             Some(QuickwitAggregations::TantivyAggregations(aggs)) => Some(
                 AggregationSegmentCollectors::TantivyAggregationSegmentCollector(
                     AggregationSegmentCollector::from_agg_req_and_reader(
@@ -796,6 +856,19 @@ impl Collector for QuickwitCollector {
 
         let segment_top_k_collector = if leaf_max_hits == 0 {
             None
+        } else if let Some(collapse) = &self.collapse {
+            let coll: Box<dyn QuickwitSegmentTopKCollector> =
+                Box::new(CollapseSegmentTopKCollector::new(
+                    self.split_id.clone(),
+                    score_extractor,
+                    collapse,
+                    segment_reader,
+                    leaf_max_hits,
+                    segment_ord,
+                    order1,
+                    order2,
+                )?);
+            Some(coll)
         } else {
             let coll: Box<dyn QuickwitSegmentTopKCollector> = specialized_top_k_segment_collector(
                 self.split_id.clone(),
@@ -846,6 +919,7 @@ impl Collector for QuickwitCollector {
             sort_order1,
             sort_order2,
             num_hits,
+            self.collapse.as_ref(),
         )?;
         // ... and drop the first [..start_offsets) hits.
         // note that self.start_offset is 0 when merging from leaf_search, and is only set when
@@ -867,7 +941,7 @@ fn map_error(error: postcard::Error) -> TantivyError {
 }
 
 /// Merges a set of Leaf Results.
-fn merge_intermediate_aggregation_result<'a>(
+pub(crate) fn merge_intermediate_aggregation_result<'a>(
     aggregations_opt: &Option<QuickwitAggregations>,
     intermediate_aggregation_results: impl Iterator<Item = &'a [u8]>,
 ) -> tantivy::Result<Option<Vec<u8>>> {
@@ -884,6 +958,16 @@ fn merge_intermediate_aggregation_result<'a>(
             let serialized = postcard::to_allocvec(&merged_fruit).map_err(map_error)?;
             Some(serialized)
         }
+        Some(QuickwitAggregations::CompositeAggregation(aggregation)) => {
+            let fruits: Vec<CompositeIntermediateResult> = intermediate_aggregation_results
+                .map(|intermediate_aggregation_result| {
+                    postcard::from_bytes(intermediate_aggregation_result).map_err(map_error)
+                })
+                .collect::<Result<_, _>>()?;
+            let merged_fruit = aggregation.merge_fruits(fruits)?;
+            let serialized = postcard::to_allocvec(&merged_fruit).map_err(map_error)?;
+            Some(serialized)
+        }
         Some(QuickwitAggregations::TantivyAggregations(_)) => {
             let merged_opt = intermediate_aggregation_results
                 .map(|bytes| postcard::from_bytes(bytes).map_err(map_error))
@@ -917,6 +1001,7 @@ fn merge_leaf_responses(
     sort_order1: SortOrder,
     sort_order2: SortOrder,
     max_hits: usize,
+    collapse_opt: Option<&CollapseRequest>,
 ) -> tantivy::Result<LeafSearchResponse> {
     // Optimization: No merging needed if there is only one result.
     if leaf_responses.len() == 1 {
@@ -952,16 +1037,34 @@ fn merge_leaf_responses(
         .flat_map(|leaf_response| leaf_response.failed_splits.iter())
         .cloned()
         .collect_vec();
+    let timed_out = leaf_responses
+        .iter()
+        .any(|leaf_response| leaf_response.timed_out);
+    let collapse_groups = merge_collapse_groups(
+        leaf_responses
+            .iter_mut()
+            .flat_map(|leaf_response| std::mem::take(&mut leaf_response.collapse_groups)),
+    );
     let all_partial_hits: Vec<PartialHit> = leaf_responses
         .into_iter()
         .flat_map(|leaf_response| leaf_response.partial_hits)
         .collect();
-    let top_k_partial_hits: Vec<PartialHit> = top_k_partial_hits(
-        all_partial_hits.into_iter(),
-        sort_order1,
-        sort_order2,
-        max_hits,
-    );
+    let top_k_partial_hits: Vec<PartialHit> = if let Some(collapse) = collapse_opt {
+        top_k_collapsed_partial_hits(
+            all_partial_hits.into_iter(),
+            sort_order1,
+            sort_order2,
+            max_hits,
+            collapse_group_size(collapse),
+        )
+    } else {
+        top_k_partial_hits(
+            all_partial_hits.into_iter(),
+            sort_order1,
+            sort_order2,
+            max_hits,
+        )
+    };
     Ok(LeafSearchResponse {
         intermediate_aggregation_result: merged_intermediate_aggregation_result,
         num_hits,
@@ -970,6 +1073,8 @@ fn merge_leaf_responses(
         num_attempted_splits,
         num_successful_splits,
         resource_stats: merged_resource_stats,
+        timed_out,
+        collapse_groups,
     })
 }
 
@@ -991,6 +1096,53 @@ fn top_k_partial_hits(
     top_k_hits.finalize()
 }
 
+/// Same as [`top_k_partial_hits`], but only keeps the best `group_size` hits for each collapse
+/// key.
+fn top_k_collapsed_partial_hits(
+    partial_hits: impl Iterator<Item = PartialHit>,
+    order1: SortOrder,
+    order2: SortOrder,
+    num_hits: usize,
+    group_size: usize,
+) -> Vec<PartialHit> {
+    let sort_key_mapper = HitSortingMapper { order1, order2 };
+    let mut top_k_groups = TopKGroups::new(num_hits, group_size, sort_key_mapper);
+
+    for partial_hit in partial_hits {
+        top_k_groups.add_entry(partial_hit.collapse_key.clone(), partial_hit);
+    }
+    top_k_groups
+        .finalize()
+        .into_iter()
+        .map(|(_, partial_hit)| partial_hit)
+        .collect()
+}
+
+/// Returns the number of best hits kept for each group of a collapsed search.
+pub(crate) fn collapse_group_size(collapse: &CollapseRequest) -> usize {
+    (collapse.group_size as usize).max(1)
+}
+
+/// Sums the number of hits of the groups sharing the same collapse key.
+pub(crate) fn merge_collapse_groups(
+    collapse_groups: impl Iterator<Item = CollapseGroup>,
+) -> Vec<CollapseGroup> {
+    let mut num_hits_per_collapse_key: BTreeMap<Option<String>, u64> = BTreeMap::new();
+
+    for collapse_group in collapse_groups {
+        *num_hits_per_collapse_key
+            .entry(collapse_group.collapse_key)
+            .or_default() += collapse_group.num_hits;
+    }
+    num_hits_per_collapse_key
+        .into_iter()
+        .map(|(collapse_key, num_hits)| CollapseGroup {
+            collapse_key,
+            num_hits,
+        })
+        .collect()
+}
+
 pub(crate) fn sort_by_from_request(search_request: &SearchRequest) -> SortByPair {
     let to_sort_by_component = |field_name: &str, order| {
         if field_name == "_score" {
@@ -1048,6 +1200,7 @@ pub(crate) fn make_collector_for_split(
         aggregation,
         aggregation_limits,
         search_after: search_request.search_after.clone(),
+        collapse: search_request.collapse.clone(),
     })
 }
 
@@ -1069,6 +1222,7 @@ pub(crate) fn make_merge_collector(
         aggregation,
         aggregation_limits: aggregation_limits.clone(),
         search_after: search_request.search_after.clone(),
+        collapse: search_request.collapse.clone(),
     })
 }
 
@@ -1183,10 +1337,178 @@ impl SortKeyMapper<SegmentPartialHit> for HitSortingMapper {
     }
 }
 
+/// Top-K of groups of entries, where each group keeps its best `group_size` entries and groups
+/// are ranked by their best entry. This is used to collapse hits on the value of a field.
+///
+/// `k` bounds the total number of entries: at most `k / group_size` groups are kept.
+#[derive(Clone)]
+pub(crate) struct TopKGroups<G, T, O, S> {
+    /// Entries of each group, sorted from best to worst.
+    groups: HashMap<G, Vec<(O, T)>>,
+    ranked_groups: BTreeSet<(O, G)>,
+    pub sort_key_mapper: S,
+    k: usize,
+    num_groups: usize,
+    group_size: usize,
+}
+
+impl<G, T, O, S> TopKGroups<G, T, O, S>
+where
+    G: Clone + Eq + Hash + Ord,
+    O: Clone + Ord,
+    S: SortKeyMapper<T, Key = O>,
+{
+    pub fn new(k: usize, group_size: usize, sort_key_mapper: S) -> Self {
+        let group_size = group_size.max(1);
+        TopKGroups {
+            groups: HashMap::new(),
+            ranked_groups: BTreeSet::new(),
+            sort_key_mapper,
+            k,
+            num_groups: k.div_ceil(group_size),
+            group_size,
+        }
+    }
+
+    /// Whether all the groups are full already, in which case entries worse than
+    /// [`Self::peek_worst`] can be discarded.
+    pub fn at_capacity(&self) -> bool {
+        self.groups.len() >= self.num_groups
+            && self
+                .groups
+                .values()
+                .all(|entries| entries.len() >= self.group_size)
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.k
+    }
+
+    /// Adds an entry to its group if it is among the best `group_size` entries of the group so
+    /// far. Once the maximum number of groups is reached, the entries of a new group are only kept
+    /// if they are better than the best entry of the worst group, which is then evicted.
+    pub fn add_entry(&mut self, group: G, item: T) {
+        if self.num_groups == 0 {
+            return;
+        }
+        let order = self.sort_key_mapper.get_sort_key(&item);
+
+        if let Some(entries) = self.groups.get_mut(&group) {
+            if entries.len() >= self.group_size
+                && let Some((worst_order, _)) = entries.last()
+                && *worst_order >= order
+            {
+                return;
+            }
+            let position = entries.partition_point(|(entry_order, _)| *entry_order >= order);
+            if position == 0 {
+                let (best_order, _) = &entries[0];
+                self.ranked_groups
+                    .remove(&(best_order.clone(), group.clone()));
+                self.ranked_groups.insert((order.clone(), group));
+            }
+            entries.insert(position, (order, item));
+            entries.truncate(self.group_size);
+            return;
+        }
+        if self.groups.len() >= self.num_groups {
+            let Some((worst_order, _)) = self.ranked_groups.first() else {
+                return;
+            };
+            if order <= *worst_order {
+                return;
+            }
+            if let Some((_, worst_group)) = self.ranked_groups.pop_first() {
+                self.groups.remove(&worst_group);
+            }
+        }
+        self.ranked_groups.insert((order.clone(), group.clone()));
+        self.groups.insert(group, vec![(order, item)]);
+    }
+
+    /// Returns the worst entry kept across all groups.
+    pub fn peek_worst(&self) -> Option<&T> {
+        self.groups
+            .values()
+            .filter_map(|entries| entries.last())
+            .min_by(|(left_order, _), (right_order, _)| left_order.cmp(right_order))
+            .map(|(_, item)| item)
+    }
+
+    /// Returns the entries of the groups, sorted from the best group to the worst group and from
+    /// the best entry to the worst entry within a group.
+    pub fn finalize(mut self) -> Vec<(G, T)> {
+        self.ranked_groups
+            .into_iter()
+            .rev()
+            .filter_map(|(_, group)| {
+                let entries = self.groups.remove(&group)?;
+                Some((group, entries))
+            })
+            .flat_map(|(group, entries)| {
+                entries
+                    .into_iter()
+                    .map(move |(_, item)| (group.clone(), item))
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone)]
+enum IncrementalTopKHits {
+    TopK(TopK<PartialHit, PartialHitSortingKey, HitSortingMapper>),
+    Collapsed(TopKGroups<Option<String>, PartialHit, PartialHitSortingKey, HitSortingMapper>),
+}
+
+impl IncrementalTopKHits {
+    fn add_entries(&mut self, partial_hits: impl Iterator<Item = PartialHit>) {
+        match self {
+            IncrementalTopKHits::TopK(top_k_hits) => top_k_hits.add_entries(partial_hits),
+            IncrementalTopKHits::Collapsed(top_k_groups) => {
+                for partial_hit in partial_hits {
+                    top_k_groups.add_entry(partial_hit.collapse_key.clone(), partial_hit);
+                }
+            }
+        }
+    }
+
+    fn max_len(&self) -> usize {
+        match self {
+            IncrementalTopKHits::TopK(top_k_hits) => top_k_hits.max_len(),
+            IncrementalTopKHits::Collapsed(top_k_groups) => top_k_groups.max_len(),
+        }
+    }
+
+    fn at_capacity(&self) -> bool {
+        match self {
+            IncrementalTopKHits::TopK(top_k_hits) => top_k_hits.at_capacity(),
+            IncrementalTopKHits::Collapsed(top_k_groups) => top_k_groups.at_capacity(),
+        }
+    }
+
+    fn peek_worst(&self) -> Option<&PartialHit> {
+        match self {
+            IncrementalTopKHits::TopK(top_k_hits) => top_k_hits.peek_worst(),
+            IncrementalTopKHits::Collapsed(top_k_groups) => top_k_groups.peek_worst(),
+        }
+    }
+
+    fn finalize(self) -> Vec<PartialHit> {
+        match self {
+            IncrementalTopKHits::TopK(top_k_hits) => top_k_hits.finalize(),
+            IncrementalTopKHits::Collapsed(top_k_groups) => top_k_groups
+                .finalize()
+                .into_iter()
+                .map(|(_, partial_hit)| partial_hit)
+                .collect(),
+        }
+    }
+}
+
 /// Incrementally merge segment results.
 #[derive(Clone)]
 pub(crate) struct IncrementalCollector {
-    top_k_hits: TopK<PartialHit, PartialHitSortingKey, HitSortingMapper>,
+    top_k_hits: IncrementalTopKHits,
     incremental_aggregation: QuickwitIncrementalAggregations,
     num_hits: u64,
     failed_splits: Vec<SplitSearchError>,
@@ -1194,6 +1516,8 @@ pub(crate) struct IncrementalCollector {
     num_successful_splits: u64,
     start_offset: usize,
     resource_stats: Option<ResourceStats>,
+    timed_out: bool,
+    collapse_groups: Vec<CollapseGroup>,
 }
 
 impl IncrementalCollector {
@@ -1206,8 +1530,18 @@ impl IncrementalCollector {
             .unwrap_or(QuickwitIncrementalAggregations::NoAggregation);
         let (order1, order2) = collector.sort_by.sort_orders();
         let sort_key_mapper = HitSortingMapper { order1, order2 };
+        let num_hits = collector.max_hits + collector.start_offset;
+        let top_k_hits = if let Some(collapse) = &collector.collapse {
+            IncrementalTopKHits::Collapsed(TopKGroups::new(
+                num_hits,
+                collapse_group_size(collapse),
+                sort_key_mapper,
+            ))
+        } else {
+            IncrementalTopKHits::TopK(TopK::new(num_hits, sort_key_mapper))
+        };
         IncrementalCollector {
-            top_k_hits: TopK::new(collector.max_hits + collector.start_offset, sort_key_mapper),
+            top_k_hits,
             start_offset: collector.start_offset,
             incremental_aggregation,
             num_hits: 0,
@@ -1215,6 +1549,8 @@ impl IncrementalCollector {
             num_attempted_splits: 0,
             num_successful_splits: 0,
             resource_stats: None,
+            timed_out: false,
+            collapse_groups: Vec::new(),
         }
     }
 
@@ -1228,6 +1564,8 @@ impl IncrementalCollector {
             intermediate_aggregation_result,
             num_successful_splits,
             resource_stats,
+            timed_out,
+            collapse_groups,
         } = leaf_response;
 
         merge_resource_stats(&resource_stats, &mut self.resource_stats);
@@ -1237,6 +1575,14 @@ impl IncrementalCollector {
         self.failed_splits.extend(failed_splits);
         self.num_attempted_splits += num_attempted_splits;
         self.num_successful_splits += num_successful_splits;
+        self.timed_out |= timed_out;
+        if !collapse_groups.is_empty() {
+            self.collapse_groups = merge_collapse_groups(
+                std::mem::take(&mut self.collapse_groups)
+                    .into_iter()
+                    .chain(collapse_groups),
+            );
+        }
         if let Some(intermediate_aggregation_result) = intermediate_aggregation_result {
             self.incremental_aggregation
                 .add(intermediate_aggregation_result)?;
@@ -1244,6 +1590,11 @@ impl IncrementalCollector {
         Ok(())
     }
 
+    /// Flags the search as timed out: some splits were not searched.
+    pub(crate) fn set_timed_out(&mut self) {
+        self.timed_out = true;
+    }
+
     /// Add a failed split to the state
     pub(crate) fn add_failed_split(&mut self, split_error: SplitSearchError) {
         self.failed_splits.push(split_error)
@@ -1282,6 +1633,8 @@ impl IncrementalCollector {
             num_successful_splits: self.num_successful_splits,
             intermediate_aggregation_result,
             resource_stats: self.resource_stats,
+            timed_out: self.timed_out,
+            collapse_groups: self.collapse_groups,
         })
     }
 }
@@ -1291,8 +1644,8 @@ mod tests {
     use std::cmp::Ordering;
 
     use quickwit_proto::search::{
-        LeafSearchResponse, PartialHit, ResourceStats, SearchRequest, SortByValue, SortField,
-        SortOrder, SortValue, SplitSearchError,
+        CollapseRequest, LeafSearchResponse, PartialHit, ResourceStats, SearchRequest, SortByValue,
+        SortField, SortOrder, SortValue, SplitSearchError,
     };
     use tantivy::TantivyDocument;
     use tantivy::aggregation::agg_req::Aggregations;
@@ -1301,7 +1654,9 @@ mod tests {
 
     use super::{IncrementalCollector, make_merge_collector};
     use crate::QuickwitAggregations;
-    use crate::collector::{merge_intermediate_aggregation_result, top_k_partial_hits};
+    use crate::collector::{
+        merge_intermediate_aggregation_result, top_k_collapsed_partial_hits, top_k_partial_hits,
+    };
 
     #[test]
     fn test_merge_partial_hits_no_tie() {
@@ -1311,6 +1666,7 @@ mod tests {
             split_id: "split1".to_string(),
             segment_ord: 0u32,
             doc_id: 0u32,
+            collapse_key: None,
         };
         assert_eq!(
             top_k_partial_hits(
@@ -1331,6 +1687,7 @@ mod tests {
             split_id: format!("split_{split_id}"),
             segment_ord: 0u32,
             doc_id: 0u32,
+            collapse_key: None,
         };
         assert_eq!(
             &top_k_partial_hits(
@@ -1362,6 +1719,68 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_merge_collapsed_partial_hits() {
+        let make_hit = |split_id: &str, sort_value: u64, collapse_key: Option<&str>| PartialHit {
+            sort_value: Some(SortValue::U64(sort_value).into()),
+            sort_value2: None,
+            split_id: split_id.to_string(),
+            segment_ord: 0u32,
+            doc_id: sort_value as u32,
+            collapse_key: collapse_key.map(ToString::to_string),
+        };
+        let partial_hits = vec![
+            make_hit("split_1", 5, Some("host-1")),
+            make_hit("split_1", 4, Some("host-2")),
+            make_hit("split_2", 7, Some("host-2")),
+            make_hit("split_2", 3, None),
+            make_hit("split_2", 2, Some("host-3")),
+            make_hit("split_3", 1, None),
+        ];
+        assert_eq!(
+            top_k_collapsed_partial_hits(
+                partial_hits.clone().into_iter(),
+                SortOrder::Desc,
+                SortOrder::Desc,
+                3,
+                1
+            ),
+            vec![
+                make_hit("split_2", 7, Some("host-2")),
+                make_hit("split_1", 5, Some("host-1")),
+                make_hit("split_2", 3, None),
+            ]
+        );
+        assert_eq!(
+            top_k_collapsed_partial_hits(
+                partial_hits.clone().into_iter(),
+                SortOrder::Asc,
+                SortOrder::Asc,
+                2,
+                1
+            ),
+            vec![
+                make_hit("split_3", 1, None),
+                make_hit("split_2", 2, Some("host-3")),
+            ]
+        );
+        // With a group size of 2, `num_hits` bounds the total number of hits: 2 groups are kept.
+        assert_eq!(
+            top_k_collapsed_partial_hits(
+                partial_hits.into_iter(),
+                SortOrder::Desc,
+                SortOrder::Desc,
+                4,
+                2
+            ),
+            vec![
+                make_hit("split_2", 7, Some("host-2")),
+                make_hit("split_1", 4, Some("host-2")),
+                make_hit("split_1", 5, Some("host-1")),
+            ]
+        );
+    }
+
     fn sort_dataset() -> Vec<(Option<u64>, Option<u64>)> {
         // every combination of 0..=2 + None, in random order.
         // (2, 1) is duplicated to allow testing for DocId sorting with two sort fields
@@ -1610,6 +2029,111 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_single_split_collapse() {
+        let index = make_index();
+
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+
+        let mut search_request = make_request(10, "sort1");
+        search_request.collapse = Some(CollapseRequest {
+            field: "sort2".to_string(),
+            inner_hits_size: 0,
+            group_size: 0,
+        });
+        let collector = super::make_collector_for_split(
+            "fake_split_id".to_string(),
+            &search_request,
+            Default::default(),
+        )
+        .unwrap();
+        let res = searcher
+            .search(&tantivy::query::AllQuery, &collector)
+            .unwrap();
+        assert_eq!(res.num_hits, 17);
+
+        // The best hit of each group has the highest sort1 value and then the highest doc id.
+        let doc_ids_and_collapse_keys: Vec<(u32, Option<&str>)> = res
+            .partial_hits
+            .iter()
+            .map(|hit| (hit.doc_id, hit.collapse_key.as_deref()))
+            .collect();
+        assert_eq!(
+            doc_ids_and_collapse_keys,
+            [(13, None), (11, Some("2")), (10, Some("0")), (6, Some("1"))]
+        );
+
+        search_request.max_hits = 2;
+        let collector = super::make_collector_for_split(
+            "fake_split_id".to_string(),
+            &search_request,
+            Default::default(),
+        )
+        .unwrap();
+        let res = searcher
+            .search(&tantivy::query::AllQuery, &collector)
+            .unwrap();
+        let doc_ids: Vec<u32> = res.partial_hits.iter().map(|hit| hit.doc_id).collect();
+        assert_eq!(doc_ids, [13, 11]);
+        assert!(res.collapse_groups.is_empty());
+    }
+
+    #[test]
+    fn test_single_split_collapse_with_group_size() {
+        let index = make_index();
+
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+
+        let mut search_request = make_request(8, "sort1");
+        search_request.collapse = Some(CollapseRequest {
+            field: "sort2".to_string(),
+            inner_hits_size: 0,
+            group_size: 2,
+        });
+        let collector = super::make_collector_for_split(
+            "fake_split_id".to_string(),
+            &search_request,
+            Default::default(),
+        )
+        .unwrap();
+        let res = searcher
+            .search(&tantivy::query::AllQuery, &collector)
+            .unwrap();
+        assert_eq!(res.num_hits, 17);
+
+        // The groups are ranked by their best hit, and the hits of a group from best to worst.
+        let doc_ids_and_collapse_keys: Vec<(u32, Option<&str>)> = res
+            .partial_hits
+            .iter()
+            .map(|hit| (hit.doc_id, hit.collapse_key.as_deref()))
+            .collect();
+        assert_eq!(
+            doc_ids_and_collapse_keys,
+            [
+                (13, None),
+                (16, None),
+                (11, Some("2")),
+                (7, Some("2")),
+                (10, Some("0")),
+                (15, Some("0")),
+                (6, Some("1")),
+                (0, Some("1")),
+            ]
+        );
+        let mut collapse_groups: Vec<(Option<&str>, u64)> = res
+            .collapse_groups
+            .iter()
+            .map(|group| (group.collapse_key.as_deref(), group.num_hits))
+            .collect();
+        collapse_groups.sort();
+        assert_eq!(
+            collapse_groups,
+            [(None, 4), (Some("0"), 4), (Some("1"), 5), (Some("2"), 4)]
+        );
+    }
+
     #[test]
     fn test_search_after() {
         let index = make_index();
@@ -1642,6 +2166,7 @@ mod tests {
                 sort_value2: Some(SortByValue {
                     sort_value: val2.map(SortValue::U64),
                 }),
+                collapse_key: None,
             })
             .collect::<Vec<_>>();
         // we eliminate based on sort value
@@ -1689,6 +2214,7 @@ mod tests {
                 doc_id: 5,
                 sort_value: None,
                 sort_value2: None,
+                collapse_key: None,
             };
             let request = SearchRequest {
                 max_hits: 1000,
@@ -1786,12 +2312,15 @@ mod tests {
                     doc_id: 123,
                     sort_value: Some(SortValue::I64(1234).into()),
                     sort_value2: None,
+                    collapse_key: None,
                 }],
                 failed_splits: Vec::new(),
                 num_attempted_splits: 3,
                 num_successful_splits: 3,
                 intermediate_aggregation_result: None,
                 resource_stats: None,
+                timed_out: false,
+                collapse_groups: Vec::new(),
             }],
         );
 
@@ -1805,12 +2334,15 @@ mod tests {
                     doc_id: 123,
                     sort_value: Some(SortValue::I64(1234).into()),
                     sort_value2: None,
+                    collapse_key: None,
                 }],
                 failed_splits: Vec::new(),
                 num_attempted_splits: 3,
                 num_successful_splits: 3,
                 intermediate_aggregation_result: None,
                 resource_stats: None,
+                timed_out: false,
+                collapse_groups: Vec::new(),
             }
         );
 
@@ -1836,6 +2368,7 @@ mod tests {
                             doc_id: 123,
                             sort_value: Some(SortValue::I64(1234).into()),
                             sort_value2: None,
+                            collapse_key: None,
                         },
                         PartialHit {
                             split_id: "1".to_string(),
@@ -1843,6 +2376,7 @@ mod tests {
                             doc_id: 125,
                             sort_value: Some(SortValue::I64(1236).into()),
                             sort_value2: None,
+                            collapse_key: None,
                         },
                     ],
                     failed_splits: Vec::new(),
@@ -1850,6 +2384,8 @@ mod tests {
                     num_successful_splits: 3,
                     intermediate_aggregation_result: None,
                     resource_stats: None,
+                    timed_out: false,
+                    collapse_groups: Vec::new(),
                 },
                 LeafSearchResponse {
                     num_hits: 10,
@@ -1859,6 +2395,7 @@ mod tests {
                         doc_id: 3,
                         sort_value: Some(SortValue::I64(1235).into()),
                         sort_value2: None,
+                        collapse_key: None,
                     }],
                     failed_splits: vec![SplitSearchError {
                         error: "fake error".to_string(),
@@ -1869,6 +2406,8 @@ mod tests {
                     num_successful_splits: 1,
                     intermediate_aggregation_result: None,
                     resource_stats: None,
+                    timed_out: false,
+                    collapse_groups: Vec::new(),
                 },
             ],
         );
@@ -1884,6 +2423,7 @@ mod tests {
                         doc_id: 125,
                         sort_value: Some(SortValue::I64(1236).into()),
                         sort_value2: None,
+                        collapse_key: None,
                     },
                     PartialHit {
                         split_id: "2".to_string(),
@@ -1891,6 +2431,7 @@ mod tests {
                         doc_id: 3,
                         sort_value: Some(SortValue::I64(1235).into()),
                         sort_value2: None,
+                        collapse_key: None,
                     },
                 ],
                 failed_splits: vec![SplitSearchError {
@@ -1902,6 +2443,8 @@ mod tests {
                 num_successful_splits: 4,
                 intermediate_aggregation_result: None,
                 resource_stats: None,
+                timed_out: false,
+                collapse_groups: Vec::new(),
             }
         );
 
@@ -1928,6 +2471,7 @@ mod tests {
                             doc_id: 123,
                             sort_value: Some(SortValue::I64(1234).into()),
                             sort_value2: None,
+                            collapse_key: None,
                         },
                         PartialHit {
                             split_id: "1".to_string(),
@@ -1935,6 +2479,7 @@ mod tests {
                             doc_id: 125,
                             sort_value: Some(SortValue::I64(1236).into()),
                             sort_value2: None,
+                            collapse_key: None,
                         },
                     ],
                     failed_splits: Vec::new(),
@@ -1945,6 +2490,8 @@ mod tests {
                         cpu_microsecs: 100,
                         ..Default::default()
                     }),
+                    timed_out: false,
+                    collapse_groups: Vec::new(),
                 },
                 LeafSearchResponse {
                     num_hits: 10,
@@ -1954,6 +2501,7 @@ mod tests {
                         doc_id: 3,
                         sort_value: Some(SortValue::I64(1235).into()),
                         sort_value2: None,
+                        collapse_key: None,
                     }],
                     failed_splits: vec![SplitSearchError {
                         error: "fake error".to_string(),
@@ -1967,6 +2515,8 @@ mod tests {
                         cpu_microsecs: 50,
                         ..Default::default()
                     }),
+                    timed_out: false,
+                    collapse_groups: Vec::new(),
                 },
             ],
         );
@@ -1982,6 +2532,7 @@ mod tests {
                         doc_id: 123,
                         sort_value: Some(SortValue::I64(1234).into()),
                         sort_value2: None,
+                        collapse_key: None,
                     },
                     PartialHit {
                         split_id: "2".to_string(),
@@ -1989,6 +2540,7 @@ mod tests {
                         doc_id: 3,
                         sort_value: Some(SortValue::I64(1235).into()),
                         sort_value2: None,
+                        collapse_key: None,
                     },
                 ],
                 failed_splits: vec![SplitSearchError {
@@ -2003,6 +2555,8 @@ mod tests {
                     cpu_microsecs: 150,
                     ..Default::default()
                 }),
+                timed_out: false,
+                collapse_groups: Vec::new(),
             }
         );
         // TODO would be nice to test aggregation too.
@@ -0,0 +1,1249 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io;
+
+use quickwit_query::aggregations::{
+    AggregationResult, AggregationResults, CompositeBucketEntry, CompositeKeyValue,
+    CompositeResult, MetricResult,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use tantivy::aggregation::metric::SingleMetricResult;
+use tantivy::collector::{Collector, SegmentCollector};
+use tantivy::columnar::{ColumnType, MonotonicallyMappableToU64, StrColumn};
+use tantivy::fastfield::Column;
+use tantivy::{DateTime, DocId, Score, SegmentReader};
+
+/// Same default as Elasticsearch.
+const DEFAULT_COMPOSITE_SIZE: usize = 10;
+
+/// Same as the default `aggregation_bucket_limit`.
+const MAX_COMPOSITE_SIZE: usize = 65_000;
+
+/// Value of a composite bucket key for one source, `None` for documents without a value.
+type CompositeKeyPart = Option<CompositeKeyValue>;
+
+/// Sort order of the buckets of a composite source.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum CompositeOrder {
+    /// Ascending order, documents without a value come first.
+    #[default]
+    Asc,
+    /// Descending order, documents without a value come last.
+    Desc,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CompositeSourceKind {
+    Terms,
+    DateHistogram { interval_millis: i64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct CompositeSource {
+    name: String,
+    field: String,
+    kind: CompositeSourceKind,
+    missing_bucket: bool,
+    order: CompositeOrder,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MetricFieldParams {
+    field: String,
+}
+
+/// Metric sub-aggregation computed for each bucket of a composite aggregation.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CompositeMetric {
+    Avg(MetricFieldParams),
+    Max(MetricFieldParams),
+    Min(MetricFieldParams),
+    Sum(MetricFieldParams),
+    ValueCount(MetricFieldParams),
+}
+
+impl CompositeMetric {
+    fn field(&self) -> &str {
+        match self {
+            CompositeMetric::Avg(params)
+            | CompositeMetric::Max(params)
+            | CompositeMetric::Min(params)
+            | CompositeMetric::Sum(params)
+            | CompositeMetric::ValueCount(params) => &params.field,
+        }
+    }
+
+    fn final_result(&self, metric_state: &MetricState) -> MetricResult {
+        let value_opt = |value: f64| (metric_state.count > 0).then_some(value);
+        match self {
+            CompositeMetric::Avg(_) => MetricResult::Average(SingleMetricResult {
+                value: value_opt(metric_state.sum / metric_state.count as f64),
+            }),
+            CompositeMetric::Max(_) => MetricResult::Max(SingleMetricResult {
+                value: value_opt(metric_state.max),
+            }),
+            CompositeMetric::Min(_) => MetricResult::Min(SingleMetricResult {
+                value: value_opt(metric_state.min),
+            }),
+            CompositeMetric::Sum(_) => MetricResult::Sum(SingleMetricResult {
+                value: Some(metric_state.sum),
+            }),
+            CompositeMetric::ValueCount(_) => MetricResult::Count(SingleMetricResult {
+                value: Some(metric_state.count as f64),
+            }),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TermsSourceParams {
+    field: String,
+    #[serde(default)]
+    missing_bucket: bool,
+    #[serde(default)]
+    order: CompositeOrder,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DateHistogramSourceParams {
+    field: String,
+    #[serde(default)]
+    fixed_interval: Option<String>,
+    #[serde(default)]
+    calendar_interval: Option<String>,
+    #[serde(default)]
+    missing_bucket: bool,
+    #[serde(default)]
+    order: CompositeOrder,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CompositeSourceParams {
+    Terms(TermsSourceParams),
+    DateHistogram(DateHistogramSourceParams),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CompositeParams {
+    #[serde(default)]
+    size: Option<usize>,
+    sources: Vec<HashMap<String, CompositeSourceParams>>,
+    #[serde(default)]
+    after: Option<JsonMap<String, JsonValue>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CompositeAggregationParams {
+    composite: CompositeParams,
+    #[serde(default, alias = "aggregations")]
+    aggs: BTreeMap<String, CompositeMetric>,
+}
+
+/// Elasticsearch `composite` aggregation, which returns every bucket of a multi-source
+/// aggregation, one page at a time.
+///
+/// Composite aggregations support `terms` and `date_histogram` sources and metric
+/// sub-aggregations. They must be the only top-level aggregation of the request. Only the first
+/// value of multi-valued fields is taken into account.
+///
+/// Each split returns its first `size` buckets after the `after` key, so merging the results of
+/// the splits is exact.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "HashMap<String, CompositeAggregationParams>")]
+pub struct CompositeAggregation {
+    name: String,
+    size: usize,
+    sources: Vec<CompositeSource>,
+    after_opt: Option<Vec<CompositeKeyPart>>,
+    metrics: Vec<(String, CompositeMetric)>,
+}
+
+impl TryFrom<HashMap<String, CompositeAggregationParams>> for CompositeAggregation {
+    type Error = String;
+
+    fn try_from(
+        aggregations: HashMap<String, CompositeAggregationParams>,
+    ) -> Result<Self, Self::Error> {
+        if aggregations.len() != 1 {
+            return Err("composite aggregation must be the only top-level aggregation".to_string());
+        }
+        let (name, params) = aggregations.into_iter().next().expect("one aggregation");
+        let size = params.composite.size.unwrap_or(DEFAULT_COMPOSITE_SIZE);
+
+        if size == 0 || size > MAX_COMPOSITE_SIZE {
+            return Err(format!(
+                "composite aggregation size must be between 1 and {MAX_COMPOSITE_SIZE}, got {size}"
+            ));
+        }
+        if params.composite.sources.is_empty() {
+            return Err("composite aggregation must have at least one source".to_string());
+        }
+        let mut sources = Vec::with_capacity(params.composite.sources.len());
+        let mut source_names = HashSet::new();
+
+        for source_params in params.composite.sources {
+            if source_params.len() != 1 {
+                return Err(
+                    "each source of a composite aggregation must have exactly one name".to_string(),
+                );
+            }
+            let (source_name, source_params) =
+                source_params.into_iter().next().expect("one source");
+
+            if !source_names.insert(source_name.clone()) {
+                return Err(format!(
+                    "duplicate source `{source_name}` in composite aggregation"
+                ));
+            }
+            let source = match source_params {
+                CompositeSourceParams::Terms(terms) => CompositeSource {
+                    name: source_name,
+                    field: terms.field,
+                    kind: CompositeSourceKind::Terms,
+                    missing_bucket: terms.missing_bucket,
+                    order: terms.order,
+                },
+                CompositeSourceParams::DateHistogram(date_histogram) => {
+                    let interval_millis = match (
+                        &date_histogram.fixed_interval,
+                        &date_histogram.calendar_interval,
+                    ) {
+                        (Some(fixed_interval), None) => parse_fixed_interval(fixed_interval)?,
+                        (None, Some(calendar_interval)) => {
+                            parse_calendar_interval(calendar_interval)?
+                        }
+                        _ => {
+                            return Err(format!(
+                                "date histogram source `{source_name}` must have either a \
+                                 `fixed_interval` or a `calendar_interval`"
+                            ));
+                        }
+                    };
+                    CompositeSource {
+                        name: source_name,
+                        field: date_histogram.field,
+                        kind: CompositeSourceKind::DateHistogram { interval_millis },
+                        missing_bucket: date_histogram.missing_bucket,
+                        order: date_histogram.order,
+                    }
+                }
+            };
+            sources.push(source);
+        }
+        let after_opt = params
+            .composite
+            .after
+            .map(|mut after| {
+                sources
+                    .iter()
+                    .map(|source| {
+                        let value_json = after.remove(&source.name).ok_or_else(|| {
+                            format!(
+                                "`after` key is missing a value for source `{}`",
+                                source.name
+                            )
+                        })?;
+                        parse_after_value(value_json)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?;
+
+        Ok(CompositeAggregation {
+            name,
+            size,
+            sources,
+            after_opt,
+            metrics: params.aggs.into_iter().collect(),
+        })
+    }
+}
+
+/// Returns whether the aggregation request contains a composite aggregation, so that its parsing
+/// errors can be reported instead of tantivy's.
+pub(crate) fn is_composite_aggregation_request(aggregation_json: &str) -> bool {
+    let Ok(aggregations) = serde_json::from_str::<HashMap<String, JsonValue>>(aggregation_json)
+    else {
+        return false;
+    };
+    aggregations
+        .values()
+        .any(|aggregation| aggregation.get("composite").is_some())
+}
+
+fn parse_fixed_interval(interval: &str) -> Result<i64, String> {
+    let unit_pos = interval
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(interval.len());
+    let (value_str, unit) = interval.split_at(unit_pos);
+    let unit_millis = match unit {
+        "ms" => 1,
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        "d" => 86_400_000,
+        _ => return Err(format!("invalid fixed interval `{interval}`")),
+    };
+    match value_str.parse::<i64>() {
+        Ok(value) if value > 0 => value
+            .checked_mul(unit_millis)
+            .ok_or_else(|| format!("fixed interval `{interval}` is too large")),
+        _ => Err(format!("invalid fixed interval `{interval}`")),
+    }
+}
+
+/// Only calendar intervals with a fixed duration in UTC are supported.
+fn parse_calendar_interval(interval: &str) -> Result<i64, String> {
+    match interval {
+        "minute" | "1m" => Ok(60_000),
+        "hour" | "1h" => Ok(3_600_000),
+        "day" | "1d" => Ok(86_400_000),
+        _ => Err(format!(
+            "unsupported calendar interval `{interval}`, expected `minute`, `hour` or `day`"
+        )),
+    }
+}
+
+fn parse_after_value(value_json: JsonValue) -> Result<CompositeKeyPart, String> {
+    let value = match value_json {
+        JsonValue::Null => return Ok(None),
+        JsonValue::Bool(value) => CompositeKeyValue::Bool(value),
+        JsonValue::String(value) => CompositeKeyValue::Str(value),
+        JsonValue::Number(number) => {
+            if let Some(value) = number.as_u64() {
+                CompositeKeyValue::U64(value)
+            } else if let Some(value) = number.as_i64() {
+                CompositeKeyValue::I64(value)
+            } else {
+                CompositeKeyValue::F64(number.as_f64().unwrap_or(f64::NAN))
+            }
+        }
+        _ => return Err(format!("invalid `after` key value `{value_json}`")),
+    };
+    Ok(Some(value))
+}
+
+/// Compares key values of possibly different types. Booleans come first, then numbers, then
+/// strings.
+fn cmp_key_values(left: &CompositeKeyValue, right: &CompositeKeyValue) -> Ordering {
+    use CompositeKeyValue::*;
+
+    fn type_rank(value: &CompositeKeyValue) -> u8 {
+        match value {
+            Bool(_) => 0,
+            U64(_) | I64(_) | F64(_) => 1,
+            Str(_) => 2,
+        }
+    }
+    match (left, right) {
+        (Bool(left), Bool(right)) => left.cmp(right),
+        (U64(left), U64(right)) => left.cmp(right),
+        (I64(left), I64(right)) => left.cmp(right),
+        (U64(left), I64(right)) => (*left as i128).cmp(&(*right as i128)),
+        (I64(left), U64(right)) => (*left as i128).cmp(&(*right as i128)),
+        (F64(left), F64(right)) => left.total_cmp(right),
+        (F64(left), U64(right)) => left.total_cmp(&(*right as f64)),
+        (F64(left), I64(right)) => left.total_cmp(&(*right as f64)),
+        (U64(left), F64(right)) => (*left as f64).total_cmp(right),
+        (I64(left), F64(right)) => (*left as f64).total_cmp(right),
+        (Str(left), Str(right)) => left.cmp(right),
+        _ => type_rank(left).cmp(&type_rank(right)),
+    }
+}
+
+impl CompositeSource {
+    fn cmp_key_parts(&self, left: &CompositeKeyPart, right: &CompositeKeyPart) -> Ordering {
+        let ordering = match (left, right) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(left), Some(right)) => cmp_key_values(left, right),
+        };
+        match self.order {
+            CompositeOrder::Asc => ordering,
+            CompositeOrder::Desc => ordering.reverse(),
+        }
+    }
+}
+
+impl CompositeAggregation {
+    /// The names of the fast fields accessed by this aggregation.
+    pub fn fast_field_names(&self) -> HashSet<String> {
+        self.sources
+            .iter()
+            .map(|source| source.field.clone())
+            .chain(
+                self.metrics
+                    .iter()
+                    .map(|(_, metric)| metric.field().to_string()),
+            )
+            .collect()
+    }
+
+    fn cmp_keys(&self, left: &[CompositeKeyPart], right: &[CompositeKeyPart]) -> Ordering {
+        for ((source, left_part), right_part) in self.sources.iter().zip(left).zip(right) {
+            let ordering = source.cmp_key_parts(left_part, right_part);
+
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    }
+
+    /// Merges intermediate results, keeping the first `size` buckets.
+    pub fn merge_results(
+        &self,
+        intermediate_results: impl IntoIterator<Item = CompositeIntermediateResult>,
+    ) -> CompositeIntermediateResult {
+        let mut buckets: Vec<CompositeIntermediateBucket> = intermediate_results
+            .into_iter()
+            .flat_map(|intermediate_result| intermediate_result.buckets)
+            .collect();
+        buckets.sort_by(|left, right| self.cmp_keys(&left.key, &right.key));
+
+        let mut merged_buckets: Vec<CompositeIntermediateBucket> =
+            Vec::with_capacity(self.size.min(buckets.len()));
+
+        for bucket in buckets {
+            if let Some(last_bucket) = merged_buckets.last_mut()
+                && self.cmp_keys(&last_bucket.key, &bucket.key) == Ordering::Equal
+            {
+                last_bucket.state.merge(&bucket.state);
+                continue;
+            }
+            if merged_buckets.len() == self.size {
+                break;
+            }
+            merged_buckets.push(bucket);
+        }
+        CompositeIntermediateResult {
+            buckets: merged_buckets,
+        }
+    }
+
+    /// Converts the merged intermediate result into the final aggregation results. The
+    /// `after_key` is the key of the last bucket.
+    pub fn finalize(&self, intermediate_result: CompositeIntermediateResult) -> AggregationResults {
+        let buckets: Vec<CompositeBucketEntry> = intermediate_result
+            .buckets
+            .into_iter()
+            .map(|bucket| {
+                let key = self
+                    .sources
+                    .iter()
+                    .map(|source| source.name.clone())
+                    .zip(bucket.key)
+                    .collect();
+                let sub_aggregation = self
+                    .metrics
+                    .iter()
+                    .zip(&bucket.state.metrics)
+                    .map(|((metric_name, metric), metric_state)| {
+                        let metric_result = metric.final_result(metric_state);
+                        (
+                            metric_name.clone(),
+                            AggregationResult::MetricResult(metric_result),
+                        )
+                    })
+                    .collect();
+                CompositeBucketEntry {
+                    key,
+                    doc_count: bucket.state.doc_count,
+                    sub_aggregation: AggregationResults(sub_aggregation),
+                }
+            })
+            .collect();
+        let after_key = buckets.last().map(|bucket| bucket.key.clone());
+        let composite_result = CompositeResult { after_key, buckets };
+        AggregationResults(vec![(
+            self.name.clone(),
+            AggregationResult::CompositeResult(composite_result),
+        )])
+    }
+}
+
+impl Collector for CompositeAggregation {
+    type Fruit = CompositeIntermediateResult;
+    type Child = CompositeSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: u32,
+        segment_reader: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        let sources: Vec<SegmentSource> = self
+            .sources
+            .iter()
+            .map(|source| SegmentSource::open(source, segment_reader))
+            .collect::<tantivy::Result<_>>()?;
+        let after_bounds_opt = self
+            .after_opt
+            .as_ref()
+            .map(|after| {
+                sources
+                    .iter()
+                    .zip(after)
+                    .map(|(source, after_part)| source.after_bound(after_part.as_ref()))
+                    .collect::<io::Result<Vec<_>>>()
+            })
+            .transpose()?;
+        let metric_columns: Vec<MetricColumn> = self
+            .metrics
+            .iter()
+            .map(|(_, metric)| MetricColumn::open(metric, segment_reader))
+            .collect::<tantivy::Result<_>>()?;
+        Ok(CompositeSegmentCollector {
+            size: self.size,
+            sources,
+            after_bounds_opt,
+            metric_columns,
+            buckets: BTreeMap::new(),
+            key_buffer: Vec::with_capacity(self.sources.len()),
+        })
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
+    ) -> tantivy::Result<Self::Fruit> {
+        Ok(self.merge_results(segment_fruits))
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+/// Running state of a metric sub-aggregation, from which all the supported metrics can be
+/// computed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct MetricState {
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for MetricState {
+    fn default() -> Self {
+        MetricState {
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl MetricState {
+    fn collect(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    fn merge(&mut self, other: &MetricState) {
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct BucketState {
+    doc_count: u64,
+    metrics: Vec<MetricState>,
+}
+
+impl BucketState {
+    fn new(num_metrics: usize) -> Self {
+        BucketState {
+            doc_count: 0,
+            metrics: vec![MetricState::default(); num_metrics],
+        }
+    }
+
+    fn collect(&mut self, doc: DocId, metric_columns: &[MetricColumn]) {
+        self.doc_count += 1;
+
+        for (metric_column, metric_state) in metric_columns.iter().zip(&mut self.metrics) {
+            metric_column.collect(doc, metric_state);
+        }
+    }
+
+    fn merge(&mut self, other: &BucketState) {
+        self.doc_count += other.doc_count;
+
+        for (metric_state, other_metric_state) in self.metrics.iter_mut().zip(&other.metrics) {
+            metric_state.merge(other_metric_state);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CompositeIntermediateBucket {
+    key: Vec<CompositeKeyPart>,
+    state: BucketState,
+}
+
+/// Intermediate result of a composite aggregation: the first `size` buckets after the `after`
+/// key, sorted by key.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompositeIntermediateResult {
+    buckets: Vec<CompositeIntermediateBucket>,
+}
+
+enum SourceColumn {
+    /// Values are term ordinals, which sort like the terms.
+    Str(StrColumn),
+    /// Values are the monotonic `u64` mapping of the column values.
+    Numeric {
+        column: Column<u64>,
+        column_type: ColumnType,
+    },
+    /// Values are the monotonic `u64` mapping of the start of the buckets, in milliseconds
+    /// since the epoch.
+    Date {
+        column: Column<DateTime>,
+        interval_millis: i64,
+    },
+}
+
+impl SourceColumn {
+    fn open(source: &CompositeSource, segment_reader: &SegmentReader) -> tantivy::Result<Self> {
+        let fast_fields = segment_reader.fast_fields();
+
+        let interval_millis = match source.kind {
+            CompositeSourceKind::Terms => {
+                if let Some(str_column) = fast_fields.str(&source.field)? {
+                    return Ok(SourceColumn::Str(str_column));
+                }
+                let column_opt: Option<(Column<u64>, ColumnType)> =
+                    fast_fields.u64_lenient(&source.field)?;
+                match column_opt {
+                    Some((_, ColumnType::DateTime)) => 1,
+                    Some((column, column_type)) => {
+                        return Ok(SourceColumn::Numeric {
+                            column,
+                            column_type,
+                        });
+                    }
+                    None => {
+                        return Ok(SourceColumn::Numeric {
+                            column: Column::build_empty_column(segment_reader.max_doc()),
+                            column_type: ColumnType::U64,
+                        });
+                    }
+                }
+            }
+            CompositeSourceKind::DateHistogram { interval_millis } => interval_millis,
+        };
+        let column = fast_fields
+            .column_opt::<DateTime>(&source.field)?
+            .unwrap_or_else(|| Column::build_empty_column(segment_reader.max_doc()));
+        Ok(SourceColumn::Date {
+            column,
+            interval_millis,
+        })
+    }
+
+    #[inline]
+    fn value(&self, doc: DocId) -> Option<u64> {
+        match self {
+            SourceColumn::Str(str_column) => str_column.ords().first(doc),
+            SourceColumn::Numeric { column, .. } => column.first(doc),
+            SourceColumn::Date {
+                column,
+                interval_millis,
+            } => column.first(doc).map(|date_time| {
+                let timestamp_millis = date_time.into_timestamp_millis();
+                let bucket_millis =
+                    timestamp_millis - timestamp_millis.rem_euclid(*interval_millis);
+                bucket_millis.to_u64()
+            }),
+        }
+    }
+
+    fn max_value(&self) -> Option<u64> {
+        match self {
+            SourceColumn::Str(str_column) => (str_column.num_terms() as u64).checked_sub(1),
+            SourceColumn::Numeric { .. } | SourceColumn::Date { .. } => Some(u64::MAX),
+        }
+    }
+
+    fn key_value(&self, value: u64) -> io::Result<CompositeKeyValue> {
+        let key_value = match self {
+            SourceColumn::Str(str_column) => {
+                let mut term = String::new();
+                str_column.ord_to_str(value, &mut term)?;
+                CompositeKeyValue::Str(term)
+            }
+            SourceColumn::Numeric { column_type, .. } => match column_type {
+                ColumnType::I64 => CompositeKeyValue::I64(i64::from_u64(value)),
+                ColumnType::F64 => CompositeKeyValue::F64(f64::from_u64(value)),
+                ColumnType::Bool => CompositeKeyValue::Bool(value != 0),
+                _ => CompositeKeyValue::U64(value),
+            },
+            SourceColumn::Date { .. } => CompositeKeyValue::I64(i64::from_u64(value)),
+        };
+        Ok(key_value)
+    }
+
+    /// Returns the smallest value whose key is greater than or equal to `key_value`, if any.
+    fn lower_bound(&self, key_value: &CompositeKeyValue) -> io::Result<Option<u64>> {
+        let Some(max_value) = self.max_value() else {
+            return Ok(None);
+        };
+        if cmp_key_values(&self.key_value(max_value)?, key_value) == Ordering::Less {
+            return Ok(None);
+        }
+        let mut low = 0;
+        let mut high = max_value;
+
+        while low < high {
+            let mid = low + (high - low) / 2;
+
+            if cmp_key_values(&self.key_value(mid)?, key_value) == Ordering::Less {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        Ok(Some(low))
+    }
+}
+
+/// Segment-local encoding of a key part, which sorts in the order of the buckets: a flag
+/// placing documents without a value first or last, and the value of the source column.
+type SegmentKeyPart = (u8, u64);
+
+const MISSING_FIRST: u8 = 0;
+const PRESENT: u8 = 1;
+const MISSING_LAST: u8 = 2;
+
+/// Position of the `after` key part of a source relative to the key parts of a segment.
+#[derive(Debug, Clone, Copy)]
+enum AfterBound {
+    /// The `after` key part exists in the segment.
+    Exact(SegmentKeyPart),
+    /// The `after` key part does not exist in the segment. The key parts greater than or equal
+    /// to this one sort after it, the others before it.
+    Inexact(SegmentKeyPart),
+}
+
+impl AfterBound {
+    #[inline]
+    fn cmp_key_part(&self, key_part: SegmentKeyPart) -> Ordering {
+        match self {
+            AfterBound::Exact(after_key_part) => key_part.cmp(after_key_part),
+            AfterBound::Inexact(after_key_part) if key_part >= *after_key_part => Ordering::Greater,
+            AfterBound::Inexact(_) => Ordering::Less,
+        }
+    }
+}
+
+struct SegmentSource {
+    column: SourceColumn,
+    missing_bucket: bool,
+    order: CompositeOrder,
+}
+
+impl SegmentSource {
+    fn open(source: &CompositeSource, segment_reader: &SegmentReader) -> tantivy::Result<Self> {
+        Ok(SegmentSource {
+            column: SourceColumn::open(source, segment_reader)?,
+            missing_bucket: source.missing_bucket,
+            order: source.order,
+        })
+    }
+
+    fn missing_key_part(&self) -> SegmentKeyPart {
+        match self.order {
+            CompositeOrder::Asc => (MISSING_FIRST, 0),
+            CompositeOrder::Desc => (MISSING_LAST, 0),
+        }
+    }
+
+    /// Returns the key part of the document, or `None` if the document does not belong to any
+    /// bucket.
+    #[inline]
+    fn key_part(&self, doc: DocId) -> Option<SegmentKeyPart> {
+        match (self.column.value(doc), self.order) {
+            (Some(value), CompositeOrder::Asc) => Some((PRESENT, value)),
+            (Some(value), CompositeOrder::Desc) => Some((PRESENT, !value)),
+            (None, _) if self.missing_bucket => Some(self.missing_key_part()),
+            (None, _) => None,
+        }
+    }
+
+    fn key_value(&self, key_part: SegmentKeyPart) -> io::Result<CompositeKeyPart> {
+        if key_part.0 != PRESENT {
+            return Ok(None);
+        }
+        let value = match self.order {
+            CompositeOrder::Asc => key_part.1,
+            CompositeOrder::Desc => !key_part.1,
+        };
+        self.column.key_value(value).map(Some)
+    }
+
+    fn after_bound(
+        &self,
+        after_key_value_opt: Option<&CompositeKeyValue>,
+    ) -> io::Result<AfterBound> {
+        let Some(after_key_value) = after_key_value_opt else {
+            return Ok(AfterBound::Exact(self.missing_key_part()));
+        };
+        let lower_bound_opt = self.column.lower_bound(after_key_value)?;
+        let is_exact = if let Some(lower_bound) = lower_bound_opt {
+            cmp_key_values(&self.column.key_value(lower_bound)?, after_key_value) == Ordering::Equal
+        } else {
+            false
+        };
+        let after_bound = match (self.order, lower_bound_opt) {
+            (CompositeOrder::Asc, Some(lower_bound)) if is_exact => {
+                AfterBound::Exact((PRESENT, lower_bound))
+            }
+            (CompositeOrder::Asc, Some(lower_bound)) => AfterBound::Inexact((PRESENT, lower_bound)),
+            // All the values sort before the `after` key part.
+            (CompositeOrder::Asc, None) => AfterBound::Inexact((MISSING_LAST, 0)),
+            (CompositeOrder::Desc, Some(lower_bound)) if is_exact => {
+                AfterBound::Exact((PRESENT, !lower_bound))
+            }
+            // Only the documents without a value sort after the `after` key part.
+            (CompositeOrder::Desc, Some(0)) => AfterBound::Inexact((MISSING_LAST, 0)),
+            (CompositeOrder::Desc, Some(lower_bound)) => {
+                AfterBound::Inexact((PRESENT, !(lower_bound - 1)))
+            }
+            // All the values sort after the `after` key part.
+            (CompositeOrder::Desc, None) => AfterBound::Inexact((PRESENT, 0)),
+        };
+        Ok(after_bound)
+    }
+}
+
+struct MetricColumn {
+    column: Column<u64>,
+    column_type: ColumnType,
+}
+
+impl MetricColumn {
+    fn open(metric: &CompositeMetric, segment_reader: &SegmentReader) -> tantivy::Result<Self> {
+        let fast_fields = segment_reader.fast_fields();
+
+        if let Some((column, column_type)) = fast_fields.u64_lenient(metric.field())? {
+            return Ok(MetricColumn {
+                column,
+                column_type,
+            });
+        }
+        // Values of string fields can be counted but have no numeric value.
+        if let CompositeMetric::ValueCount(_) = metric
+            && let Some(str_column) = fast_fields.str(metric.field())?
+        {
+            return Ok(MetricColumn {
+                column: str_column.ords().clone(),
+                column_type: ColumnType::Str,
+            });
+        }
+        Ok(MetricColumn {
+            column: Column::build_empty_column(segment_reader.max_doc()),
+            column_type: ColumnType::U64,
+        })
+    }
+
+    #[inline]
+    fn collect(&self, doc: DocId, metric_state: &mut MetricState) {
+        for value in self.column.values_for_doc(doc) {
+            let value_f64 = match self.column_type {
+                ColumnType::I64 => i64::from_u64(value) as f64,
+                ColumnType::F64 => f64::from_u64(value),
+                // Dates are aggregated in milliseconds, like in Elasticsearch.
+                ColumnType::DateTime => DateTime::from_u64(value).into_timestamp_millis() as f64,
+                ColumnType::Str => f64::NAN,
+                _ => value as f64,
+            };
+            metric_state.collect(value_f64);
+        }
+    }
+}
+
+pub struct CompositeSegmentCollector {
+    size: usize,
+    sources: Vec<SegmentSource>,
+    after_bounds_opt: Option<Vec<AfterBound>>,
+    metric_columns: Vec<MetricColumn>,
+    /// The first `size` buckets after the `after` key seen so far.
+    buckets: BTreeMap<Vec<SegmentKeyPart>, BucketState>,
+    key_buffer: Vec<SegmentKeyPart>,
+}
+
+impl CompositeSegmentCollector {
+    fn is_after(&self, key: &[SegmentKeyPart]) -> bool {
+        let Some(after_bounds) = &self.after_bounds_opt else {
+            return true;
+        };
+        for (key_part, after_bound) in key.iter().zip(after_bounds) {
+            match after_bound.cmp_key_part(*key_part) {
+                Ordering::Greater => return true,
+                Ordering::Less => return false,
+                Ordering::Equal => {}
+            }
+        }
+        false
+    }
+}
+
+impl SegmentCollector for CompositeSegmentCollector {
+    type Fruit = CompositeIntermediateResult;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        self.key_buffer.clear();
+
+        for source in &self.sources {
+            let Some(key_part) = source.key_part(doc) else {
+                return;
+            };
+            self.key_buffer.push(key_part);
+        }
+        if !self.is_after(&self.key_buffer) {
+            return;
+        }
+        if let Some(bucket_state) = self.buckets.get_mut(&self.key_buffer) {
+            bucket_state.collect(doc, &self.metric_columns);
+            return;
+        }
+        if self.buckets.len() == self.size {
+            match self.buckets.last_key_value() {
+                Some((last_key, _)) if self.key_buffer > *last_key => return,
+                _ => {
+                    self.buckets.pop_last();
+                }
+            }
+        }
+        let mut bucket_state = BucketState::new(self.metric_columns.len());
+        bucket_state.collect(doc, &self.metric_columns);
+        self.buckets.insert(self.key_buffer.clone(), bucket_state);
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        let buckets = self
+            .buckets
+            .into_iter()
+            .map(|(segment_key, state)| {
+                let key = self
+                    .sources
+                    .iter()
+                    .zip(segment_key)
+                    .map(|(source, key_part)| {
+                        source
+                            .key_value(key_part)
+                            .expect("Failed to lookup term in the column term dictionary")
+                    })
+                    .collect();
+                CompositeIntermediateBucket { key, state }
+            })
+            .collect();
+        CompositeIntermediateResult { buckets }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::query::AllQuery;
+    use tantivy::schema::{FAST, STRING, Schema};
+    use tantivy::{Index, IndexWriter, doc};
+
+    use super::*;
+
+    const DAY_MILLIS: i64 = 86_400_000;
+
+    fn make_index() -> Index {
+        let mut schema_builder = Schema::builder();
+        let customer_field = schema_builder.add_text_field("customer", STRING | FAST);
+        let timestamp_field = schema_builder.add_date_field("timestamp", FAST);
+        let bytes_field = schema_builder.add_u64_field("bytes", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000).unwrap();
+
+        let docs = [
+            (Some("acme"), 0, 10),
+            (Some("acme"), DAY_MILLIS + 1, 20),
+            (Some("globex"), 5, 30),
+            (Some("acme"), 7, 40),
+            (None, 2 * DAY_MILLIS, 50),
+            (Some("initech"), DAY_MILLIS, 60),
+        ];
+        for (i, (customer_opt, timestamp_millis, bytes)) in docs.into_iter().enumerate() {
+            let mut doc = doc!(
+                timestamp_field => DateTime::from_timestamp_millis(timestamp_millis),
+                bytes_field => bytes as u64,
+            );
+            if let Some(customer) = customer_opt {
+                doc.add_text(customer_field, customer);
+            }
+            index_writer.add_document(doc).unwrap();
+
+            // Spread the documents over two segments.
+            if i == 2 {
+                index_writer.commit().unwrap();
+            }
+        }
+        index_writer.commit().unwrap();
+        index
+    }
+
+    fn composite_aggregation(aggregation_json: JsonValue) -> CompositeAggregation {
+        serde_json::from_value(aggregation_json).unwrap()
+    }
+
+    fn bucket_keys_and_doc_counts(
+        intermediate_result: &CompositeIntermediateResult,
+    ) -> Vec<(Vec<CompositeKeyPart>, u64)> {
+        intermediate_result
+            .buckets
+            .iter()
+            .map(|bucket| (bucket.key.clone(), bucket.state.doc_count))
+            .collect()
+    }
+
+    fn str_key(value: &str) -> CompositeKeyPart {
+        Some(CompositeKeyValue::Str(value.to_string()))
+    }
+
+    fn date_key(millis: i64) -> CompositeKeyPart {
+        Some(CompositeKeyValue::I64(millis))
+    }
+
+    #[test]
+    fn test_composite_aggregation_deser() {
+        let aggregation = composite_aggregation(serde_json::json!({
+            "usage": {
+                "composite": {
+                    "size": 2,
+                    "sources": [
+                        {"customer": {"terms": {"field": "customer", "order": "desc"}}},
+                        {"day": {"date_histogram": {"field": "timestamp", "calendar_interval": "day"}}}
+                    ],
+                    "after": {"customer": "acme", "day": 0}
+                },
+                "aggs": {"total_bytes": {"sum": {"field": "bytes"}}}
+            }
+        }));
+        assert_eq!(aggregation.name, "usage");
+        assert_eq!(aggregation.size, 2);
+        assert_eq!(aggregation.sources[0].order, CompositeOrder::Desc);
+        assert_eq!(
+            aggregation.sources[1].kind,
+            CompositeSourceKind::DateHistogram {
+                interval_millis: DAY_MILLIS
+            }
+        );
+        assert_eq!(
+            aggregation.after_opt.unwrap(),
+            [str_key("acme"), Some(CompositeKeyValue::U64(0))]
+        );
+        assert_eq!(
+            aggregation.fast_field_names(),
+            HashSet::from_iter([
+                "customer".to_string(),
+                "timestamp".to_string(),
+                "bytes".to_string()
+            ])
+        );
+
+        let invalid_aggregations = [
+            serde_json::json!({
+                "usage": {"composite": {"sources": [{"customer": {"terms": {"field": "customer"}}}]}},
+                "other": {"composite": {"sources": [{"customer": {"terms": {"field": "customer"}}}]}}
+            }),
+            serde_json::json!({"usage": {"composite": {"sources": []}}}),
+            serde_json::json!({
+                "usage": {"composite": {"sources": [{"day": {"date_histogram": {"field": "timestamp", "calendar_interval": "month"}}}]}}
+            }),
+            serde_json::json!({
+                "usage": {"composite": {
+                    "sources": [{"customer": {"terms": {"field": "customer"}}}],
+                    "after": {"day": 0}
+                }}
+            }),
+            serde_json::json!({
+                "usage": {
+                    "composite": {"sources": [{"customer": {"terms": {"field": "customer"}}}]},
+                    "aggs": {"top": {"terms": {"field": "bytes"}}}
+                }
+            }),
+        ];
+        for invalid_aggregation in invalid_aggregations {
+            serde_json::from_value::<CompositeAggregation>(invalid_aggregation).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn test_parse_intervals() {
+        assert_eq!(parse_fixed_interval("30s").unwrap(), 30_000);
+        assert_eq!(parse_fixed_interval("12h").unwrap(), 12 * 3_600_000);
+        assert_eq!(parse_fixed_interval("1d").unwrap(), DAY_MILLIS);
+        parse_fixed_interval("0d").unwrap_err();
+        parse_fixed_interval("1w").unwrap_err();
+        parse_fixed_interval("9223372036854775807d").unwrap_err();
+        assert_eq!(parse_calendar_interval("hour").unwrap(), 3_600_000);
+        parse_calendar_interval("year").unwrap_err();
+    }
+
+    #[test]
+    fn test_composite_aggregation_pagination() {
+        let index = make_index();
+        let searcher = index.reader().unwrap().searcher();
+
+        let mut aggregation = composite_aggregation(serde_json::json!({
+            "usage": {
+                "composite": {
+                    "size": 2,
+                    "sources": [
+                        {"customer": {"terms": {"field": "customer", "missing_bucket": true}}},
+                        {"day": {"date_histogram": {"field": "timestamp", "fixed_interval": "1d"}}}
+                    ]
+                },
+                "aggs": {"total_bytes": {"sum": {"field": "bytes"}}}
+            }
+        }));
+        let mut pages = Vec::new();
+
+        loop {
+            let intermediate_result = searcher.search(&AllQuery, &aggregation).unwrap();
+            if intermediate_result.buckets.is_empty() {
+                break;
+            }
+            aggregation.after_opt = Some(intermediate_result.buckets.last().unwrap().key.clone());
+            pages.push(bucket_keys_and_doc_counts(&intermediate_result));
+        }
+        assert_eq!(
+            pages,
+            [
+                vec![
+                    (vec![None, date_key(2 * DAY_MILLIS)], 1),
+                    (vec![str_key("acme"), date_key(0)], 2),
+                ],
+                vec![
+                    (vec![str_key("acme"), date_key(DAY_MILLIS)], 1),
+                    (vec![str_key("globex"), date_key(0)], 1),
+                ],
+                vec![(vec![str_key("initech"), date_key(DAY_MILLIS)], 1)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_composite_aggregation_desc_with_inexact_after() {
+        let index = make_index();
+        let searcher = index.reader().unwrap().searcher();
+
+        let aggregation = composite_aggregation(serde_json::json!({
+            "usage": {
+                "composite": {
+                    "sources": [{"customer": {"terms": {"field": "customer", "order": "desc"}}}],
+                    "after": {"customer": "foo"}
+                }
+            }
+        }));
+        let intermediate_result = searcher.search(&AllQuery, &aggregation).unwrap();
+        assert_eq!(
+            bucket_keys_and_doc_counts(&intermediate_result),
+            [(vec![str_key("acme")], 3)]
+        );
+    }
+
+    #[test]
+    fn test_composite_aggregation_merge_and_finalize() {
+        let aggregation = composite_aggregation(serde_json::json!({
+            "usage": {
+                "composite": {
+                    "size": 2,
+                    "sources": [{"customer": {"terms": {"field": "customer"}}}]
+                },
+                "aggs": {
+                    "avg_bytes": {"avg": {"field": "bytes"}},
+                    "max_bytes": {"max": {"field": "bytes"}}
+                }
+            }
+        }));
+        let bucket = |customer: &str, values: &[f64]| {
+            let mut metric_state = MetricState::default();
+            for value in values {
+                metric_state.collect(*value);
+            }
+            CompositeIntermediateBucket {
+                key: vec![str_key(customer)],
+                state: BucketState {
+                    doc_count: values.len() as u64,
+                    metrics: vec![metric_state; 2],
+                },
+            }
+        };
+        let left = CompositeIntermediateResult {
+            buckets: vec![bucket("acme", &[10.0]), bucket("globex", &[30.0])],
+        };
+        let right = CompositeIntermediateResult {
+            buckets: vec![bucket("acme", &[20.0, 40.0]), bucket("initech", &[60.0])],
+        };
+        let merged_result = aggregation.merge_results([left, right]);
+        assert_eq!(
+            bucket_keys_and_doc_counts(&merged_result),
+            [(vec![str_key("acme")], 3), (vec![str_key("globex")], 1)]
+        );
+
+        let final_result = aggregation.finalize(merged_result);
+        let AggregationResult::CompositeResult(composite_result) = &final_result.0[0].1 else {
+            panic!("expected a composite result");
+        };
+        assert_eq!(
+            composite_result.after_key,
+            Some(vec![("customer".to_string(), str_key("globex"))])
+        );
+        let acme_bucket = &composite_result.buckets[0];
+        assert_eq!(acme_bucket.doc_count, 3);
+
+        let metric_values: Vec<(&str, Option<f64>)> = acme_bucket
+            .sub_aggregation
+            .0
+            .iter()
+            .map(|(name, result)| match result {
+                AggregationResult::MetricResult(
+                    MetricResult::Average(value) | MetricResult::Max(value),
+                ) => (name.as_str(), value.value),
+                _ => panic!("unexpected metric result"),
+            })
+            .collect();
+        assert_eq!(
+            metric_values,
+            [("avg_bytes", Some(70.0 / 3.0)), ("max_bytes", Some(40.0))]
+        );
+    }
+}
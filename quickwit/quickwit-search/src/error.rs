@@ -36,10 +36,14 @@ pub enum SearchError {
     Internal(String),
     #[error("invalid aggregation request: {0}")]
     InvalidAggregationRequest(String),
+    #[error("cancelled: {0}")]
+    Cancelled(String),
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
     #[error("{0}")]
     InvalidQuery(String),
+    #[error("{0}")]
+    NotFound(String),
     #[error("storage not found: `{0}`)")]
     StorageResolver(#[from] StorageResolverError),
     #[error("request timed out: {0}")]
@@ -71,6 +75,7 @@ impl ServiceError for SearchError {
     fn error_code(&self) -> ServiceErrorCode {
         match self {
             Self::IndexesNotFound { .. } => ServiceErrorCode::NotFound,
+            Self::Cancelled(_) => ServiceErrorCode::BadRequest,
             Self::Internal(error_msg) => {
                 rate_limited_error!(limit_per_min = 6, "search internal error: {error_msg}");
                 ServiceErrorCode::Internal
@@ -78,6 +83,7 @@ impl ServiceError for SearchError {
             Self::InvalidAggregationRequest(_) => ServiceErrorCode::BadRequest,
             Self::InvalidArgument(_) => ServiceErrorCode::BadRequest,
             Self::InvalidQuery(_) => ServiceErrorCode::BadRequest,
+            Self::NotFound(_) => ServiceErrorCode::NotFound,
             Self::StorageResolver(storage_err) => {
                 rate_limited_error!(
                     limit_per_min = 6,
@@ -0,0 +1,618 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use quickwit_doc_mapper::DocMapper;
+use quickwit_metastore::SplitMetadata;
+use quickwit_proto::metastore::MetastoreServiceClient;
+use quickwit_proto::search::{
+    ClosePointInTimeRequest, ExportedDoc, LeafExportRequest, LeafExportResponse, PointInTime,
+    SearchRequest, SourceFilter, SplitIdAndFooterOffsets,
+};
+use quickwit_proto::types::IndexUid;
+use quickwit_query::query_ast::QueryAst;
+use quickwit_storage::Storage;
+use tantivy::query::{EnableScoring, Weight};
+use tantivy::schema::TantivyDocument;
+use tantivy::{DocAddress, DocId, DocSet, ReloadPolicy, Searcher, TERMINATED};
+use tracing::{Instrument, info_span, warn};
+
+use crate::leaf::{compute_index_size, open_index_with_caches, rewrite_request, warmup};
+use crate::point_in_time::{
+    load_point_in_time_context, open_point_in_time_on_splits, root_close_point_in_time,
+};
+use crate::root::{IndexesMetasForLeafSearch, SearchJob, plan_splits_for_root_search};
+use crate::search_permit_provider::{SearchPermit, compute_initial_memory_allocation};
+use crate::service::SearcherContext;
+use crate::{ClusterClient, SearchError, convert_document_to_json_string};
+
+/// Maximum number of documents of a page of a leaf export stream.
+const EXPORT_PAGE_NUM_DOCS: u32 = 1_000;
+
+/// Number of documents fetched concurrently from the doc store of a split.
+const NUM_CONCURRENT_DOC_FETCHES: usize = 30;
+
+/// Keep alive of the point in time pinning the splits of an export. The lease is extended while
+/// the export runs and whenever it is resumed.
+const EXPORT_POINT_IN_TIME_KEEP_ALIVE: Duration = Duration::from_secs(3_600);
+
+/// Stream of the pages of documents of an export, split by split.
+pub type ExportStream = BoxStream<'static, crate::Result<ExportPage>>;
+
+/// Stream of the pages of documents of a split, returned by the leaves.
+pub type LeafExportStream = BoxStream<'static, crate::Result<LeafExportResponse>>;
+
+/// Request to export all the documents matching a search request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportRequest {
+    /// Only the indexes, the query, the time range and the source filter of the search request
+    /// are taken into account.
+    pub search_request: SearchRequest,
+    /// Resumes the export right after the document designated by the cursor.
+    pub cursor_opt: Option<ExportCursor>,
+}
+
+/// Page of documents of an export, all coming from the same split.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportPage {
+    /// ID of the point in time pinning the splits of the export.
+    pub pit_id: String,
+    pub split_id: String,
+    /// Documents of the page, in doc address order.
+    pub docs: Vec<ExportedDoc>,
+}
+
+impl ExportPage {
+    /// Returns the cursor resuming the export right after the given document of the page.
+    pub fn doc_cursor(&self, doc: &ExportedDoc) -> ExportCursor {
+        ExportCursor {
+            pit_id: self.pit_id.clone(),
+            split_id: self.split_id.clone(),
+            segment_ord: doc.segment_ord,
+            doc_id: doc.doc_id,
+        }
+    }
+}
+
+/// Position of a document within an export.
+///
+/// Exports go through the splits in split ID order and through the documents of a split in doc
+/// address order, so an export can be resumed after any of the documents it returned. The splits
+/// are pinned by a point in time when the export starts, and a resumed export goes through the
+/// same splits, even if they were merged in the meantime. A cursor becomes invalid once the point
+/// in time expires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportCursor {
+    pub pit_id: String,
+    pub split_id: String,
+    pub segment_ord: u32,
+    pub doc_id: DocId,
+}
+
+impl fmt::Display for ExportCursor {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{}:{}:{}:{}",
+            self.pit_id, self.split_id, self.segment_ord, self.doc_id
+        )
+    }
+}
+
+impl FromStr for ExportCursor {
+    type Err = SearchError;
+
+    fn from_str(cursor_str: &str) -> crate::Result<Self> {
+        let invalid_cursor_error =
+            || SearchError::InvalidArgument(format!("invalid export cursor `{cursor_str}`"));
+        let mut parts = cursor_str.rsplitn(4, ':');
+        let doc_id: DocId = parts
+            .next()
+            .and_then(|doc_id_str| doc_id_str.parse().ok())
+            .ok_or_else(invalid_cursor_error)?;
+        let segment_ord: u32 = parts
+            .next()
+            .and_then(|segment_ord_str| segment_ord_str.parse().ok())
+            .ok_or_else(invalid_cursor_error)?;
+        let split_id = parts
+            .next()
+            .filter(|split_id| !split_id.is_empty())
+            .ok_or_else(invalid_cursor_error)?;
+        let pit_id = parts
+            .next()
+            .filter(|pit_id| !pit_id.is_empty())
+            .ok_or_else(invalid_cursor_error)?;
+        Ok(ExportCursor {
+            pit_id: pit_id.to_string(),
+            split_id: split_id.to_string(),
+            segment_ord,
+            doc_id,
+        })
+    }
+}
+
+fn export_point_in_time(pit_id: String) -> PointInTime {
+    PointInTime {
+        pit_id,
+        keep_alive_secs: Some(EXPORT_POINT_IN_TIME_KEEP_ALIVE.as_secs() as u32),
+    }
+}
+
+/// Exports the documents matching a search request.
+///
+/// The splits are exported one after the other, one page of documents at a time, so the
+/// memory used by an export does not depend on the number of documents it returns. The splits
+/// are pinned by a point in time for the whole export, which is closed once the export completes.
+pub async fn root_export(
+    export_request: ExportRequest,
+    mut metastore: MetastoreServiceClient,
+    cluster_client: &ClusterClient,
+) -> crate::Result<ExportStream> {
+    let mut search_request = export_request.search_request;
+    search_request.max_hits = 0;
+    search_request.start_offset = 0;
+    search_request.sort_fields.clear();
+    search_request.aggregation_request = None;
+    search_request.point_in_time = None;
+
+    let (mut split_metadatas, indexes_meta_for_leaf_search) =
+        plan_splits_for_root_search(&mut search_request, &mut metastore, cluster_client).await?;
+    let index_uids: Vec<IndexUid> = indexes_meta_for_leaf_search.keys().cloned().collect();
+
+    let point_in_time = if let Some(cursor) = &export_request.cursor_opt {
+        // A resumed export goes through the splits pinned when it started rather than through
+        // the current ones: the merges that happened in the meantime would otherwise make it
+        // return documents twice.
+        let point_in_time = export_point_in_time(cursor.pit_id.clone());
+        let point_in_time_context =
+            load_point_in_time_context(&point_in_time, &mut metastore, cluster_client)
+                .await
+                .map_err(|search_error| match search_error {
+                    SearchError::InvalidArgument(_) => SearchError::InvalidArgument(
+                        "the splits pinned by the export cursor are no longer available: the \
+                         export must be restarted"
+                            .to_string(),
+                    ),
+                    _ => search_error,
+                })?;
+        split_metadatas = point_in_time_context.into_relevant_splits(
+            &index_uids,
+            search_request.start_timestamp,
+            search_request.end_timestamp,
+        );
+        point_in_time
+    } else {
+        let pit_id = open_point_in_time_on_splits(
+            index_uids,
+            split_metadatas.clone(),
+            EXPORT_POINT_IN_TIME_KEEP_ALIVE,
+            &mut metastore,
+            cluster_client,
+        )
+        .await?;
+        export_point_in_time(pit_id)
+    };
+    split_metadatas.sort_by(|left, right| left.split_id.cmp(&right.split_id));
+
+    let mut export_state = ExportState {
+        search_request,
+        indexes_meta_for_leaf_search,
+        split_metadatas: VecDeque::from(split_metadatas),
+        split_export_stream_opt: None,
+        start_segment_ord: 0,
+        start_doc_id: 0,
+        point_in_time,
+        lease_renewed_at: Instant::now(),
+        metastore,
+        cluster_client: cluster_client.clone(),
+    };
+    if let Some(cursor) = export_request.cursor_opt {
+        export_state.skip_to_cursor(&cursor)?;
+    }
+    let export_stream = futures::stream::try_unfold(export_state, |mut export_state| async move {
+        let page_opt = export_state.next_page().await?;
+        Ok(page_opt.map(|page| (page, export_state)))
+    });
+    Ok(export_stream.boxed())
+}
+
+struct ExportState {
+    search_request: SearchRequest,
+    indexes_meta_for_leaf_search: IndexesMetasForLeafSearch,
+    // The split being exported comes first.
+    split_metadatas: VecDeque<SplitMetadata>,
+    // Pages of the split being exported, once its leaf export started.
+    split_export_stream_opt: Option<LeafExportStream>,
+    start_segment_ord: u32,
+    start_doc_id: DocId,
+    point_in_time: PointInTime,
+    lease_renewed_at: Instant,
+    metastore: MetastoreServiceClient,
+    cluster_client: ClusterClient,
+}
+
+impl ExportState {
+    fn skip_to_cursor(&mut self, cursor: &ExportCursor) -> crate::Result<()> {
+        let Some(split_position) = self
+            .split_metadatas
+            .iter()
+            .position(|split_metadata| split_metadata.split_id == cursor.split_id)
+        else {
+            return Err(SearchError::InvalidArgument(format!(
+                "split `{}` of the export cursor is not pinned by the export: the export must be \
+                 restarted",
+                cursor.split_id
+            )));
+        };
+        self.split_metadatas.drain(..split_position);
+        self.start_segment_ord = cursor.segment_ord;
+        self.start_doc_id = cursor.doc_id.saturating_add(1);
+        Ok(())
+    }
+
+    /// Returns the next non-empty page of documents, or `None` once all the splits have been
+    /// exported.
+    async fn next_page(&mut self) -> crate::Result<Option<ExportPage>> {
+        loop {
+            let Some(split_export_stream) = &mut self.split_export_stream_opt else {
+                self.renew_lease_if_needed().await?;
+
+                let Some(split_metadata) = self.split_metadatas.front() else {
+                    self.close_point_in_time().await;
+                    return Ok(None);
+                };
+                let split_export_stream = self.open_split_export_stream(split_metadata).await?;
+                self.split_export_stream_opt = Some(split_export_stream);
+                continue;
+            };
+            let Some(leaf_export_response) = split_export_stream.try_next().await? else {
+                // All the documents of the split have been exported.
+                self.split_export_stream_opt = None;
+                self.split_metadatas.pop_front();
+                self.start_segment_ord = 0;
+                self.start_doc_id = 0;
+                continue;
+            };
+            if let Some(last_doc) = leaf_export_response.docs.last() {
+                self.start_segment_ord = last_doc.segment_ord;
+                self.start_doc_id = last_doc.doc_id + 1;
+
+                let page = ExportPage {
+                    pit_id: self.point_in_time.pit_id.clone(),
+                    split_id: leaf_export_response.split_id,
+                    docs: leaf_export_response.docs,
+                };
+                return Ok(Some(page));
+            }
+        }
+    }
+
+    async fn open_split_export_stream(
+        &self,
+        split_metadata: &SplitMetadata,
+    ) -> crate::Result<LeafExportStream> {
+        let search_job = SearchJob::from(split_metadata);
+        let index_meta = self
+            .indexes_meta_for_leaf_search
+            .get(&split_metadata.index_uid)
+            .ok_or_else(|| {
+                SearchError::Internal(format!(
+                    "received export job for an unknown index {}. it should never happen",
+                    split_metadata.index_uid
+                ))
+            })?;
+        let leaf_export_request = LeafExportRequest {
+            search_request: Some(self.search_request.clone()),
+            doc_mapper: index_meta.doc_mapper_str.clone(),
+            index_uri: index_meta.index_uri.to_string(),
+            split_offsets: Some(search_job.offsets.clone()),
+            start_segment_ord: self.start_segment_ord,
+            start_doc_id: self.start_doc_id,
+            max_docs_per_page: EXPORT_PAGE_NUM_DOCS,
+        };
+        let client = self
+            .cluster_client
+            .search_job_placer
+            .assign_job(search_job, &HashSet::new())
+            .await?;
+        self.cluster_client
+            .leaf_export(leaf_export_request, client)
+            .await
+    }
+
+    /// Extends the lease of the point in time pinning the splits once half of its keep alive has
+    /// elapsed.
+    async fn renew_lease_if_needed(&mut self) -> crate::Result<()> {
+        if self.lease_renewed_at.elapsed() < EXPORT_POINT_IN_TIME_KEEP_ALIVE / 2 {
+            return Ok(());
+        }
+        load_point_in_time_context(
+            &self.point_in_time,
+            &mut self.metastore,
+            &self.cluster_client,
+        )
+        .await?;
+        self.lease_renewed_at = Instant::now();
+        Ok(())
+    }
+
+    /// Releases the splits pinned by the export once it is complete.
+    async fn close_point_in_time(&self) {
+        let close_request = ClosePointInTimeRequest {
+            pit_id: self.point_in_time.pit_id.clone(),
+        };
+        if let Err(search_error) =
+            root_close_point_in_time(close_request, self.metastore.clone(), &self.cluster_client)
+                .await
+        {
+            warn!(error=%search_error, "failed to close the point in time of the export");
+        }
+    }
+}
+
+/// Streams the documents of a split matching the query of a leaf export request, page by page,
+/// starting from the document address of the request.
+///
+/// The split is opened and warmed up once for the whole stream, which holds a search permit
+/// until it is dropped.
+pub(crate) async fn leaf_export(
+    searcher_context: Arc<SearcherContext>,
+    leaf_export_request: LeafExportRequest,
+    index_storage: Arc<dyn Storage>,
+    doc_mapper: Arc<DocMapper>,
+) -> crate::Result<LeafExportStream> {
+    let mut search_request = leaf_export_request
+        .search_request
+        .ok_or_else(|| SearchError::Internal("no search request".to_string()))?;
+    let split: SplitIdAndFooterOffsets = leaf_export_request
+        .split_offsets
+        .ok_or_else(|| SearchError::Internal("no split".to_string()))?;
+    rewrite_request(
+        &mut search_request,
+        &split,
+        doc_mapper.timestamp_field_name(),
+    );
+    let query_ast: QueryAst = serde_json::from_str(&search_request.query_ast)
+        .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
+
+    let tenant_id = search_request
+        .tenant_id
+        .clone()
+        .unwrap_or_else(|| search_request.index_id_patterns.join(","));
+    let permit_size = compute_initial_memory_allocation(
+        &split,
+        searcher_context
+            .searcher_config
+            .warmup_single_split_initial_allocation,
+    );
+    let permit_future = searcher_context
+        .search_permit_provider
+        .get_permits(&tenant_id, [permit_size])
+        .await?
+        .pop()
+        .expect("one permit should be returned per split");
+    let mut search_permit = permit_future
+        .instrument(info_span!("waiting_for_leaf_export_split_semaphore"))
+        .await;
+
+    let (index, hot_directory) = open_index_with_caches(
+        &searcher_context,
+        index_storage,
+        &split,
+        Some(doc_mapper.tokenizer_manager()),
+        None,
+    )
+    .await?;
+    let index_size = compute_index_size(&hot_directory);
+    if index_size < search_permit.memory_allocation() {
+        search_permit.update_memory_usage(index_size);
+    }
+    let index_reader = index
+        .reader_builder()
+        // the docs are fetched in doc address order
+        .doc_store_cache_num_blocks(NUM_CONCURRENT_DOC_FETCHES)
+        .reload_policy(ReloadPolicy::Manual)
+        .try_into()?;
+    let searcher = Arc::new(index_reader.searcher());
+
+    let (query, mut warmup_info) = doc_mapper.query(index.schema(), &query_ast, false)?;
+    warmup_info.simplify();
+    warmup(&searcher, &warmup_info).await?;
+    search_permit.free_warmup_slot();
+
+    let weight: Arc<dyn Weight> =
+        Arc::from(query.weight(EnableScoring::disabled_from_searcher(&searcher))?);
+    let leaf_export_state = LeafExportState {
+        split_id: split.split_id,
+        searcher,
+        weight,
+        doc_mapper,
+        source_filter_opt: search_request.source_filter,
+        next_doc_addr_opt: Some(DocAddress::new(
+            leaf_export_request.start_segment_ord,
+            leaf_export_request.start_doc_id,
+        )),
+        max_docs_per_page: leaf_export_request.max_docs_per_page.max(1) as usize,
+        _search_permit: search_permit,
+    };
+    let leaf_export_stream =
+        futures::stream::try_unfold(leaf_export_state, |mut leaf_export_state| async move {
+            let page_opt = leaf_export_state.next_page().await?;
+            Ok(page_opt.map(|page| (page, leaf_export_state)))
+        });
+    Ok(leaf_export_stream.boxed())
+}
+
+struct LeafExportState {
+    split_id: String,
+    searcher: Arc<Searcher>,
+    weight: Arc<dyn Weight>,
+    doc_mapper: Arc<DocMapper>,
+    source_filter_opt: Option<SourceFilter>,
+    // Address of the next document to consider, or `None` once the split is exhausted.
+    next_doc_addr_opt: Option<DocAddress>,
+    max_docs_per_page: usize,
+    _search_permit: SearchPermit,
+}
+
+impl LeafExportState {
+    /// Returns the next page of documents, or `None` once all the documents of the split
+    /// matching the query have been returned.
+    async fn next_page(&mut self) -> crate::Result<Option<LeafExportResponse>> {
+        let Some(start_doc_addr) = self.next_doc_addr_opt else {
+            return Ok(None);
+        };
+        let (doc_addrs, next_doc_addr_opt) = {
+            let searcher = self.searcher.clone();
+            let weight = self.weight.clone();
+            let max_docs = self.max_docs_per_page;
+            crate::search_thread_pool()
+                .run_cpu_intensive(move || {
+                    collect_doc_addrs(&searcher, &*weight, start_doc_addr, max_docs)
+                })
+                .await
+                .map_err(|_| {
+                    SearchError::Internal(format!("leaf export panicked. split={}", self.split_id))
+                })??
+        };
+        self.next_doc_addr_opt = next_doc_addr_opt;
+
+        if doc_addrs.is_empty() {
+            return Ok(None);
+        }
+        let source_filter_opt = self.source_filter_opt.as_ref();
+        let doc_futures = doc_addrs.into_iter().map(|doc_addr| {
+            let searcher = self.searcher.clone();
+            let doc_mapper = self.doc_mapper.clone();
+            async move {
+                let doc: TantivyDocument = searcher.doc_async(doc_addr).await?;
+                let named_field_doc = doc.to_named_doc(searcher.schema());
+                let json = convert_document_to_json_string(
+                    named_field_doc,
+                    &doc_mapper,
+                    source_filter_opt,
+                )?;
+                crate::Result::Ok(ExportedDoc {
+                    json,
+                    segment_ord: doc_addr.segment_ord,
+                    doc_id: doc_addr.doc_id,
+                })
+            }
+            .in_current_span()
+        });
+        let docs: Vec<ExportedDoc> = futures::stream::iter(doc_futures)
+            .buffered(NUM_CONCURRENT_DOC_FETCHES)
+            .try_collect()
+            .await?;
+
+        Ok(Some(LeafExportResponse {
+            split_id: self.split_id.clone(),
+            docs,
+        }))
+    }
+}
+
+/// Returns the addresses of at most `max_docs` documents matching the query, starting from
+/// `start_doc_addr`, along with the address to resume from, or `None` if the split is exhausted.
+fn collect_doc_addrs(
+    searcher: &Searcher,
+    weight: &dyn Weight,
+    start_doc_addr: DocAddress,
+    max_docs: usize,
+) -> crate::Result<(Vec<DocAddress>, Option<DocAddress>)> {
+    let mut doc_addrs: Vec<DocAddress> = Vec::new();
+
+    for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+        let segment_ord = segment_ord as u32;
+        if segment_ord < start_doc_addr.segment_ord {
+            continue;
+        }
+        let mut scorer = weight.scorer(segment_reader, 1.0)?;
+        let mut doc_id = scorer.doc();
+
+        if segment_ord == start_doc_addr.segment_ord && doc_id < start_doc_addr.doc_id {
+            doc_id = scorer.seek(start_doc_addr.doc_id);
+        }
+        let alive_bitset_opt = segment_reader.alive_bitset();
+
+        while doc_id != TERMINATED {
+            if doc_addrs.len() == max_docs {
+                return Ok((doc_addrs, Some(DocAddress::new(segment_ord, doc_id))));
+            }
+            let is_alive = alive_bitset_opt
+                .map(|alive_bitset| alive_bitset.is_alive(doc_id))
+                .unwrap_or(true);
+            if is_alive {
+                doc_addrs.push(DocAddress::new(segment_ord, doc_id));
+            }
+            doc_id = scorer.advance();
+        }
+    }
+    Ok((doc_addrs, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_cursor_round_trip() {
+        let cursor = ExportCursor {
+            pit_id: "eyJwaXQiOjF9".to_string(),
+            split_id: "01HQ2ZWXG7J8R5WAYN0C5KMV3E".to_string(),
+            segment_ord: 1,
+            doc_id: 42,
+        };
+        let cursor_str = cursor.to_string();
+        assert_eq!(cursor_str, "eyJwaXQiOjF9:01HQ2ZWXG7J8R5WAYN0C5KMV3E:1:42");
+        assert_eq!(cursor_str.parse::<ExportCursor>().unwrap(), cursor);
+    }
+
+    #[test]
+    fn test_export_cursor_invalid() {
+        for cursor_str in [
+            "",
+            "split:1:2",
+            "pit:split:1",
+            ":split:1:2",
+            "pit::1:2",
+            "pit:split:a:2",
+            "pit:split:1:-2",
+        ] {
+            let error = cursor_str.parse::<ExportCursor>().unwrap_err();
+            assert!(matches!(error, SearchError::InvalidArgument(_)));
+        }
+    }
+
+    #[test]
+    fn test_export_page_doc_cursor() {
+        let page = ExportPage {
+            pit_id: "pit".to_string(),
+            split_id: "split".to_string(),
+            docs: vec![ExportedDoc {
+                json: "{}".to_string(),
+                segment_ord: 1,
+                doc_id: 3,
+            }],
+        };
+        assert_eq!(page.doc_cursor(&page.docs[0]).to_string(), "pit:split:1:3");
+    }
+}
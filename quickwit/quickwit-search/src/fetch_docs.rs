@@ -20,21 +20,27 @@ use futures::{StreamExt, TryStreamExt};
 use itertools::Itertools;
 use quickwit_doc_mapper::DocMapper;
 use quickwit_proto::search::{
-    FetchDocsResponse, PartialHit, SnippetRequest, SplitIdAndFooterOffsets,
+    FetchDocsResponse, PartialHit, SnippetOptions, SnippetRequest, SourceFilter,
+    SplitIdAndFooterOffsets,
 };
 use quickwit_storage::Storage;
 use tantivy::query::Query;
 use tantivy::schema::document::CompactDocValue;
-use tantivy::schema::{Document as DocumentTrait, Field, TantivyDocument, Value};
+use tantivy::schema::{
+    Document as DocumentTrait, Field, FieldEntry, FieldType, TantivyDocument, Value,
+};
 use tantivy::snippet::SnippetGenerator;
 use tantivy::{ReloadPolicy, Score, Searcher, Term};
 use tracing::{Instrument, error};
 
 use crate::leaf::open_index_with_caches;
 use crate::service::SearcherContext;
+use crate::source_filter::wildcard_match;
 use crate::{GlobalDocAddress, convert_document_to_json_string};
 
 const SNIPPET_MAX_NUM_CHARS: usize = 150;
+const SNIPPET_DEFAULT_PRE_TAG: &str = "<b>";
+const SNIPPET_DEFAULT_POST_TAG: &str = "</b>";
 
 /// Given a list of global doc address, fetches all the documents and
 /// returns them as a hashmap.
@@ -45,6 +51,7 @@ async fn fetch_docs_to_map(
     splits: &[SplitIdAndFooterOffsets],
     doc_mapper: Arc<DocMapper>,
     snippet_request_opt: Option<&SnippetRequest>,
+    source_filter_opt: Option<&SourceFilter>,
 ) -> anyhow::Result<HashMap<GlobalDocAddress, Document>> {
     let mut split_fetch_docs_futures = Vec::new();
 
@@ -72,6 +79,7 @@ async fn fetch_docs_to_map(
             split_and_offset,
             doc_mapper.clone(),
             snippet_request_opt,
+            source_filter_opt,
         ));
     }
 
@@ -112,6 +120,7 @@ pub async fn fetch_docs(
     splits: &[SplitIdAndFooterOffsets],
     doc_mapper: Arc<DocMapper>,
     snippet_request_opt: Option<&SnippetRequest>,
+    source_filter_opt: Option<&SourceFilter>,
 ) -> anyhow::Result<FetchDocsResponse> {
     let global_doc_addrs: Vec<GlobalDocAddress> = partial_hits
         .iter()
@@ -125,6 +134,7 @@ pub async fn fetch_docs(
         splits,
         doc_mapper,
         snippet_request_opt,
+        source_filter_opt,
     )
     .await?;
 
@@ -135,7 +145,8 @@ pub async fn fetch_docs(
             if let Some((_, document)) = global_doc_addr_to_doc_json.remove_entry(&global_doc_addr)
             {
                 Some(quickwit_proto::search::LeafHit {
-                    leaf_json: document.content_json,
+                    source_disabled: document.content_json_opt.is_none(),
+                    leaf_json: document.content_json_opt.unwrap_or_default(),
                     partial_hit: Some(partial_hit),
                     leaf_snippet_json: document.snippet_json,
                 })
@@ -153,7 +164,8 @@ const NUM_CONCURRENT_REQUESTS: usize = 30;
 /// A struct for holding a fetched document's content and snippet.
 #[derive(Debug)]
 struct Document {
-    content_json: String,
+    /// `None` if the source of the document is disabled.
+    content_json_opt: Option<String>,
     snippet_json: Option<String>,
 }
 
@@ -165,6 +177,7 @@ async fn fetch_docs_in_split(
     split: &SplitIdAndFooterOffsets,
     doc_mapper: Arc<DocMapper>,
     snippet_request_opt: Option<&SnippetRequest>,
+    source_filter_opt: Option<&SourceFilter>,
 ) -> anyhow::Result<Vec<(GlobalDocAddress, Document)>> {
     global_doc_addrs.sort_by_key(|doc| doc.doc_addr);
     // Opens the index without the ephemeral unbounded cache, this cache is indeed not useful
@@ -197,6 +210,9 @@ async fn fetch_docs_in_split(
         None
     };
 
+    let is_source_disabled = source_filter_opt
+        .map(|source_filter| source_filter.disabled)
+        .unwrap_or_default();
     let doc_futures = global_doc_addrs.into_iter().map(|global_doc_addr| {
         let moved_searcher = searcher.clone();
         let moved_doc_mapper = doc_mapper.clone();
@@ -207,13 +223,22 @@ async fn fetch_docs_in_split(
                 .await
                 .context("searcher-doc-async")?;
 
-            let named_field_doc = doc.to_named_doc(moved_searcher.schema());
-            let content_json = convert_document_to_json_string(named_field_doc, &moved_doc_mapper)?;
+            let content_json_opt = if is_source_disabled {
+                None
+            } else {
+                let named_field_doc = doc.to_named_doc(moved_searcher.schema());
+                let content_json = convert_document_to_json_string(
+                    named_field_doc,
+                    &moved_doc_mapper,
+                    source_filter_opt,
+                )?;
+                Some(content_json)
+            };
             if fields_snippet_generator_opt_clone.is_none() {
                 return Ok((
                     global_doc_addr,
                     Document {
-                        content_json,
+                        content_json_opt,
                         snippet_json: None,
                     },
                 ));
@@ -224,7 +249,7 @@ async fn fetch_docs_in_split(
                 return Ok((
                     global_doc_addr,
                     Document {
-                        content_json,
+                        content_json_opt,
                         snippet_json: None,
                     },
                 ));
@@ -243,7 +268,7 @@ async fn fetch_docs_in_split(
             Ok((
                 global_doc_addr,
                 Document {
-                    content_json,
+                    content_json_opt,
                     snippet_json: Some(snippet_json),
                 },
             ))
@@ -262,6 +287,8 @@ async fn fetch_docs_in_split(
 #[derive(Clone)]
 struct FieldsSnippetGenerator {
     field_generators: Arc<HashMap<String, SnippetGenerator>>,
+    snippet_options: Arc<SnippetOptions>,
+    fragment_size: usize,
 }
 
 impl FieldsSnippetGenerator {
@@ -271,23 +298,54 @@ impl FieldsSnippetGenerator {
         field_name: &str,
         field_values: Vec<CompactDocValue<'_>>,
     ) -> Option<Vec<String>> {
-        if let Some(snippet_generator) = self.field_generators.get(field_name) {
-            let values = field_values
-                .into_iter()
-                .filter_map(|value| {
-                    value.as_str().and_then(|text| {
-                        let snippet = snippet_generator.snippet(text);
-                        match snippet.is_empty() {
-                            false => Some(snippet.to_html()),
-                            _ => None,
+        let snippet_generator = self.field_generators.get(field_name)?;
+        let texts = field_values.into_iter().filter_map(|value| value.as_str());
+        let mut snippets = Vec::new();
+        match self.snippet_options.number_of_fragments {
+            // Without `number_of_fragments`, we return the best fragment of each value. A
+            // `number_of_fragments` of 0 means that the whole value is highlighted: the snippet
+            // generator is then configured with an unbounded number of chars.
+            None | Some(0) => {
+                for text in texts {
+                    if let Some(snippet) = self.highlight(snippet_generator, text) {
+                        snippets.push(snippet);
+                    }
+                }
+            }
+            Some(number_of_fragments) => {
+                let max_num_fragments = number_of_fragments as usize;
+                'values: for text in texts {
+                    for fragment in split_into_fragments(text, self.fragment_size) {
+                        if snippets.len() >= max_num_fragments {
+                            break 'values;
                         }
-                    })
-                })
-                .collect();
-            Some(values)
-        } else {
-            None
+                        if let Some(snippet) = self.highlight(snippet_generator, fragment) {
+                            snippets.push(snippet);
+                        }
+                    }
+                }
+            }
+        }
+        Some(snippets)
+    }
+
+    // Returns the highlighted snippet of the text, or `None` if the text has no match.
+    fn highlight(&self, snippet_generator: &SnippetGenerator, text: &str) -> Option<String> {
+        let mut snippet = snippet_generator.snippet(text);
+        if snippet.is_empty() {
+            return None;
         }
+        snippet.set_snippet_prefix_postfix(
+            self.snippet_options
+                .pre_tag
+                .as_deref()
+                .unwrap_or(SNIPPET_DEFAULT_PRE_TAG),
+            self.snippet_options
+                .post_tag
+                .as_deref()
+                .unwrap_or(SNIPPET_DEFAULT_POST_TAG),
+        );
+        Some(snippet.to_html())
     }
 
     fn is_empty(&self) -> bool {
@@ -305,23 +363,91 @@ async fn create_fields_snippet_generator(
     let query_ast_resolved = serde_json::from_str(&snippet_request.query_ast_resolved)
         .context("failed to deserialize QueryAst")?;
     let (query, _) = doc_mapper.query(schema.clone(), &query_ast_resolved, false)?;
+    let snippet_options = snippet_request.snippet_options.clone().unwrap_or_default();
+    let max_num_chars = snippet_max_num_chars(&snippet_options);
     let mut snippet_generators = HashMap::new();
     for field_name in &snippet_request.snippet_fields {
+        if snippet_options.lenient_fields {
+            // Like Elasticsearch, field patterns are expanded and the fields that cannot be
+            // highlighted are skipped.
+            for (field, field_entry) in schema.fields() {
+                if is_highlightable(field_entry)
+                    && wildcard_match(field_name.as_bytes(), field_entry.name().as_bytes())
+                    && !snippet_generators.contains_key(field_entry.name())
+                {
+                    let snippet_generator =
+                        create_snippet_generator(searcher, &query, field, max_num_chars).await?;
+                    snippet_generators.insert(field_entry.name().to_string(), snippet_generator);
+                }
+            }
+            continue;
+        }
         let field = schema.get_field(field_name)?;
-        let snippet_generator = create_snippet_generator(searcher, &query, field).await?;
+        let snippet_generator =
+            create_snippet_generator(searcher, &query, field, max_num_chars).await?;
         snippet_generators.insert(field_name.clone(), snippet_generator);
     }
 
     Ok(FieldsSnippetGenerator {
         field_generators: Arc::new(snippet_generators),
+        snippet_options: Arc::new(snippet_options),
+        fragment_size: max_num_chars,
     })
 }
 
+// Returns whether snippets can be generated for the field, i.e. whether it is a stored text field.
+fn is_highlightable(field_entry: &FieldEntry) -> bool {
+    matches!(field_entry.field_type(), FieldType::Str(text_options) if text_options.is_stored())
+}
+
+// Splits a text into consecutive fragments of at most `fragment_size` chars. Fragments are cut on
+// whitespaces whenever possible so that words are not split across fragments.
+fn split_into_fragments(text: &str, fragment_size: usize) -> Vec<&str> {
+    let fragment_size = fragment_size.max(1);
+    let mut fragments = Vec::new();
+    let mut fragment_start = 0;
+    let mut last_whitespace_opt: Option<usize> = None;
+    let mut num_chars = 0;
+
+    for (offset, ch) in text.char_indices() {
+        if num_chars == fragment_size {
+            let fragment_end = last_whitespace_opt.unwrap_or(offset);
+            fragments.push(&text[fragment_start..fragment_end]);
+            fragment_start = fragment_end;
+            num_chars = text[fragment_start..offset].chars().count();
+            last_whitespace_opt = None;
+        }
+        if ch.is_whitespace() && offset > fragment_start {
+            last_whitespace_opt = Some(offset);
+        }
+        num_chars += 1;
+    }
+    fragments.push(&text[fragment_start..]);
+    fragments
+        .into_iter()
+        .map(str::trim)
+        .filter(|fragment| !fragment.is_empty())
+        .collect()
+}
+
+// Returns the maximum number of chars of a snippet. When `number_of_fragments` is 0, the snippet
+// spans the whole field value.
+fn snippet_max_num_chars(snippet_options: &SnippetOptions) -> usize {
+    if snippet_options.number_of_fragments == Some(0) {
+        return usize::MAX;
+    }
+    snippet_options
+        .fragment_size
+        .map(|fragment_size| fragment_size as usize)
+        .unwrap_or(SNIPPET_MAX_NUM_CHARS)
+}
+
 // Creates a snippet generator associated to a field.
 async fn create_snippet_generator(
     searcher: &Searcher,
     query: &dyn Query,
     field: Field,
+    max_num_chars: usize,
 ) -> anyhow::Result<SnippetGenerator> {
     let mut terms: Vec<&Term> = Vec::new();
     // TODO ok with termset?
@@ -347,6 +473,6 @@ async fn create_snippet_generator(
         terms_text,
         tokenizer,
         field,
-        SNIPPET_MAX_NUM_CHARS,
+        max_num_chars,
     ))
 }
@@ -0,0 +1,518 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use anyhow::Context;
+use futures::future::try_join_all;
+use quickwit_doc_mapper::DocMapper;
+use quickwit_doc_mapper::dense_vector_index::{
+    DenseVectorIndex, HEADER_NUM_BYTES, dense_vector_index_file_path,
+};
+use quickwit_proto::search::{
+    LeafSearchResponse, PartialHit, SearchRequest, SortField, SortOrder, SortValue,
+};
+use quickwit_query::query_ast::{BoolQuery, QueryAst};
+use serde::{Deserialize, Serialize};
+use tantivy::directory::OwnedBytes;
+use tantivy::directory::error::OpenReadError;
+use tantivy::query::{EnableScoring, Query};
+use tantivy::{DocAddress, DocSet, Index, Searcher, TERMINATED};
+
+use crate::SearchError;
+
+/// Maximum number of nearest neighbors a kNN search can return.
+const MAX_K: u64 = 10_000;
+
+const DEFAULT_RRF_RANK_CONSTANT: u64 = 60;
+
+/// A k-nearest neighbors search over a dense vector field, serialized as JSON in the `knn` field
+/// of a [`SearchRequest`].
+///
+/// Without `rrf`, the search returns the `k` documents matching both the query and the filter
+/// whose vectors are the most similar to the query vector, ranked by similarity. With `rrf`, the
+/// hits of the query and the `k` nearest neighbors matching the filter are combined with
+/// reciprocal rank fusion.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KnnQuery {
+    /// Name of the dense vector field.
+    pub field: String,
+    /// Vector whose nearest neighbors are searched.
+    pub query_vector: Vec<f32>,
+    /// Number of nearest neighbors to return.
+    pub k: u64,
+    /// Number of vectors compared with the query vector in each split. Defaults to
+    /// `max(10 * k, 100)`.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_candidates: Option<u64>,
+    /// Only the documents matching the filter are candidate neighbors.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<QueryAst>,
+    /// Combines the query and kNN hits with reciprocal rank fusion.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rrf: Option<RrfParams>,
+}
+
+/// Reciprocal rank fusion parameters.
+///
+/// The score of a hit is the sum, over the rankings it appears in, of
+/// `1 / (rank_constant + rank)`, ranks starting at 1.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RrfParams {
+    /// Number of query hits fused with the kNN hits. Defaults to `start_offset + max_hits`.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_size: Option<u64>,
+    /// Damps the weight of the top ranked hits. Defaults to 60.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank_constant: Option<u64>,
+}
+
+impl KnnQuery {
+    pub(crate) fn parse(knn_json: &str) -> crate::Result<KnnQuery> {
+        serde_json::from_str(knn_json)
+            .map_err(|err| SearchError::InvalidArgument(format!("invalid kNN search: {err}")))
+    }
+
+    pub(crate) fn num_candidates(&self) -> usize {
+        self.num_candidates.unwrap_or((10 * self.k).max(100)) as usize
+    }
+
+    fn rank_constant(&self) -> u64 {
+        self.rrf
+            .as_ref()
+            .and_then(|rrf| rrf.rank_constant)
+            .unwrap_or(DEFAULT_RRF_RANK_CONSTANT)
+    }
+
+    /// Validates the kNN search against the doc mapping of an index and the rest of the search
+    /// request.
+    pub(crate) fn validate(
+        &self,
+        doc_mapper: &DocMapper,
+        search_request: &SearchRequest,
+    ) -> crate::Result<()> {
+        let invalid_argument = |message: String| Err(SearchError::InvalidArgument(message));
+
+        if self.k == 0 || self.k > MAX_K {
+            return invalid_argument(format!(
+                "kNN `k` must be between 1 and {MAX_K}, got `{}`",
+                self.k
+            ));
+        }
+        if let Some(num_candidates) = self.num_candidates
+            && (num_candidates < self.k || num_candidates > MAX_K)
+        {
+            return invalid_argument(format!(
+                "kNN `num_candidates` must be between `k` and {MAX_K}, got `{num_candidates}`"
+            ));
+        }
+        let Some(dense_vector_field) = doc_mapper
+            .dense_vector_fields()
+            .into_iter()
+            .find(|dense_vector_field| dense_vector_field.name == self.field)
+        else {
+            return invalid_argument(format!(
+                "kNN field `{}` is not a dense vector field",
+                self.field
+            ));
+        };
+        if self.query_vector.len() != dense_vector_field.options.dims {
+            return invalid_argument(format!(
+                "kNN query vector has {} dimensions, but field `{}` has {}",
+                self.query_vector.len(),
+                self.field,
+                dense_vector_field.options.dims
+            ));
+        }
+        if search_request.aggregation_request.is_some() {
+            return invalid_argument("kNN search cannot be used with aggregations".to_string());
+        }
+        if search_request.scroll_ttl_secs.is_some() {
+            return invalid_argument("kNN search cannot be used with scroll".to_string());
+        }
+        if search_request.search_after.is_some() {
+            return invalid_argument("kNN search cannot be used with search after".to_string());
+        }
+        if search_request.collapse.is_some() {
+            return invalid_argument("kNN search cannot be used with collapse".to_string());
+        }
+        let is_sorted_by_score = match &search_request.sort_fields[..] {
+            [] => true,
+            [sort_field] => {
+                sort_field.field_name == "_score" && sort_field.sort_order() == SortOrder::Desc
+            }
+            _ => false,
+        };
+        if !is_sorted_by_score {
+            return invalid_argument("kNN search hits can only be sorted by score".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn sort_by_score() -> Vec<SortField> {
+    vec![SortField {
+        field_name: "_score".to_string(),
+        sort_order: SortOrder::Desc as i32,
+        sort_datetime_format: None,
+    }]
+}
+
+/// Returns the request searching the `k` nearest neighbors among the documents matching
+/// `query_ast`, sent to the leaves.
+pub(crate) fn knn_phase_request(
+    search_request: &SearchRequest,
+    knn_query: &KnnQuery,
+    query_ast: QueryAst,
+    start_offset: u64,
+    max_hits: u64,
+) -> crate::Result<SearchRequest> {
+    let leaf_knn_query = KnnQuery {
+        // The filter is applied through the query of the request.
+        filter: None,
+        rrf: None,
+        ..knn_query.clone()
+    };
+    Ok(SearchRequest {
+        query_ast: serde_json::to_string(&query_ast)?,
+        sort_fields: sort_by_score(),
+        start_offset,
+        max_hits: max_hits.min(knn_query.k.saturating_sub(start_offset)),
+        knn: Some(serde_json::to_string(&leaf_knn_query)?),
+        ..search_request.clone()
+    })
+}
+
+/// Returns the conjunction of the query of the request and of the kNN filter, if any.
+pub(crate) fn filtered_query_ast(query_ast: QueryAst, knn_query: &KnnQuery) -> QueryAst {
+    let Some(filter) = knn_query.filter.clone() else {
+        return query_ast;
+    };
+    if query_ast == QueryAst::MatchAll {
+        return filter;
+    }
+    QueryAst::Bool(BoolQuery {
+        must: vec![query_ast],
+        filter: vec![filter],
+        ..Default::default()
+    })
+}
+
+/// Returns the request searching the hits of the query fused with the kNN hits.
+pub(crate) fn rrf_query_phase_request(
+    search_request: &SearchRequest,
+    window_size: u64,
+) -> SearchRequest {
+    SearchRequest {
+        sort_fields: sort_by_score(),
+        start_offset: 0,
+        max_hits: window_size,
+        knn: None,
+        ..search_request.clone()
+    }
+}
+
+/// Returns the number of hits of each ranking fused by reciprocal rank fusion.
+pub(crate) fn rrf_window_size(search_request: &SearchRequest, knn_query: &KnnQuery) -> u64 {
+    knn_query
+        .rrf
+        .as_ref()
+        .and_then(|rrf| rrf.window_size)
+        .unwrap_or(search_request.start_offset + search_request.max_hits)
+}
+
+/// Fuses rankings of hits with reciprocal rank fusion.
+///
+/// The score of the fused hits is stored as their sort value, and the number of hits is the
+/// number of distinct hits of the rankings.
+pub(crate) fn reciprocal_rank_fusion(
+    rankings: Vec<LeafSearchResponse>,
+    knn_query: &KnnQuery,
+    start_offset: u64,
+    max_hits: u64,
+) -> LeafSearchResponse {
+    let rank_constant = knn_query.rank_constant() as f64;
+    let mut fused_hits: HashMap<(String, u32, u32), (f64, PartialHit)> = HashMap::new();
+    let mut fused_response = LeafSearchResponse::default();
+
+    for ranking in rankings {
+        for (rank, partial_hit) in ranking.partial_hits.into_iter().enumerate() {
+            let key = (
+                partial_hit.split_id.clone(),
+                partial_hit.segment_ord,
+                partial_hit.doc_id,
+            );
+            let rrf_score = 1.0 / (rank_constant + rank as f64 + 1.0);
+            fused_hits.entry(key).or_insert((0.0, partial_hit)).0 += rrf_score;
+        }
+        fused_response.failed_splits.extend(ranking.failed_splits);
+        fused_response.num_attempted_splits += ranking.num_attempted_splits;
+        fused_response.num_successful_splits += ranking.num_successful_splits;
+        fused_response.timed_out |= ranking.timed_out;
+        crate::merge_resource_stats(&ranking.resource_stats, &mut fused_response.resource_stats);
+    }
+    fused_response.num_hits = fused_hits.len() as u64;
+
+    let mut partial_hits: Vec<PartialHit> = fused_hits
+        .into_values()
+        .map(|(rrf_score, mut partial_hit)| {
+            partial_hit.sort_value = Some(SortValue::F64(rrf_score).into());
+            partial_hit.sort_value2 = None;
+            partial_hit
+        })
+        .collect();
+    partial_hits.sort_by(|left, right| {
+        right.sort_value.cmp(&left.sort_value).then_with(|| {
+            (&left.split_id, left.segment_ord, left.doc_id).cmp(&(
+                &right.split_id,
+                right.segment_ord,
+                right.doc_id,
+            ))
+        })
+    });
+    fused_response.partial_hits = partial_hits
+        .into_iter()
+        .skip(start_offset as usize)
+        .take(max_hits as usize)
+        .collect();
+    fused_response
+}
+
+/// The lists of the dense vector index of a split to search for a kNN query.
+pub(crate) struct KnnSplitSearch {
+    dense_vector_index: DenseVectorIndex,
+    query_vector: Vec<f32>,
+    lists: Vec<OwnedBytes>,
+    k: usize,
+}
+
+impl KnnSplitSearch {
+    /// Fetches the dense vector index metadata of the split, then the lists closest to the query
+    /// vector.
+    ///
+    /// Returns `None` if the split has no vector for the field.
+    pub async fn open(index: &Index, knn_query: &KnnQuery) -> crate::Result<Option<Self>> {
+        let Ok(field) = index.schema().get_field(&knn_query.field) else {
+            return Ok(None);
+        };
+        let file_path = dense_vector_index_file_path(field);
+        let file_slice = match index.directory().open_read(&file_path) {
+            Ok(file_slice) => file_slice,
+            Err(OpenReadError::FileDoesNotExist(_)) => return Ok(None),
+            Err(open_read_error) => {
+                return Err(SearchError::Internal(format!(
+                    "failed to open dense vector index: {open_read_error}"
+                )));
+            }
+        };
+        let header_bytes = file_slice
+            .read_bytes_slice_async(0..HEADER_NUM_BYTES)
+            .await
+            .context("failed to read dense vector index header")?;
+        let metadata_num_bytes = DenseVectorIndex::metadata_num_bytes(header_bytes.as_slice())
+            .context("invalid dense vector index header")?;
+        let metadata_bytes = file_slice
+            .read_bytes_slice_async(0..metadata_num_bytes)
+            .await
+            .context("failed to read dense vector index metadata")?;
+        let dense_vector_index = DenseVectorIndex::open(metadata_bytes.as_slice())
+            .context("invalid dense vector index metadata")?;
+
+        // The split may have been indexed with a former doc mapping.
+        if dense_vector_index.dims() != knn_query.query_vector.len() {
+            return Ok(None);
+        }
+        let mut query_vector = knn_query.query_vector.clone();
+        dense_vector_index.similarity().normalize(&mut query_vector);
+
+        let list_futures = dense_vector_index
+            .probe(&query_vector, knn_query.num_candidates())
+            .into_iter()
+            .map(|byte_range| file_slice.read_bytes_slice_async(byte_range));
+        let lists = try_join_all(list_futures)
+            .await
+            .context("failed to read dense vector index lists")?;
+
+        Ok(Some(KnnSplitSearch {
+            dense_vector_index,
+            query_vector,
+            lists,
+            k: knn_query.k as usize,
+        }))
+    }
+
+    /// Returns the nearest neighbors among the documents matching `query`, as partial hits
+    /// sorted by score.
+    pub fn search(
+        &self,
+        searcher: &Searcher,
+        query: &dyn Query,
+        split_id: &str,
+        num_hits_to_return: usize,
+    ) -> tantivy::Result<LeafSearchResponse> {
+        let weight = query.weight(EnableScoring::disabled_from_searcher(searcher))?;
+        let mut matching_docs: Vec<Vec<bool>> =
+            Vec::with_capacity(searcher.segment_readers().len());
+
+        for segment_reader in searcher.segment_readers() {
+            let mut segment_matching_docs = vec![false; segment_reader.max_doc() as usize];
+            let mut scorer = weight.scorer(segment_reader, 1.0)?;
+            let mut doc_id = scorer.doc();
+            let alive_bitset_opt = segment_reader.alive_bitset();
+
+            while doc_id != TERMINATED {
+                let is_alive = alive_bitset_opt
+                    .map(|alive_bitset| alive_bitset.is_alive(doc_id))
+                    .unwrap_or(true);
+                segment_matching_docs[doc_id as usize] = is_alive;
+                doc_id = scorer.advance();
+            }
+            matching_docs.push(segment_matching_docs);
+        }
+        let is_matching = |doc_address: DocAddress| {
+            matching_docs
+                .get(doc_address.segment_ord as usize)
+                .and_then(|segment_matching_docs| {
+                    segment_matching_docs.get(doc_address.doc_id as usize)
+                })
+                .copied()
+                .unwrap_or(false)
+        };
+        let hits =
+            self.dense_vector_index
+                .top_k(&self.query_vector, &self.lists, self.k, is_matching);
+        let partial_hits = hits
+            .iter()
+            .take(num_hits_to_return)
+            .map(|(score, doc_address)| PartialHit {
+                sort_value: Some(SortValue::F64(*score as f64).into()),
+                sort_value2: None,
+                split_id: split_id.to_string(),
+                segment_ord: doc_address.segment_ord,
+                doc_id: doc_address.doc_id,
+                collapse_key: None,
+            })
+            .collect();
+        Ok(LeafSearchResponse {
+            num_hits: hits.len() as u64,
+            partial_hits,
+            num_attempted_splits: 1,
+            num_successful_splits: 1,
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn partial_hit(split_id: &str, doc_id: u32) -> PartialHit {
+        PartialHit {
+            split_id: split_id.to_string(),
+            doc_id,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_knn_query_deserialization() {
+        let knn_query = KnnQuery::parse(
+            r#"{"field": "embedding", "query_vector": [0.5, 1.0], "k": 5, "rrf": {}}"#,
+        )
+        .unwrap();
+        assert_eq!(knn_query.field, "embedding");
+        assert_eq!(knn_query.num_candidates(), 100);
+        assert_eq!(knn_query.rank_constant(), 60);
+
+        let error = KnnQuery::parse(r#"{"field": "embedding", "k": 5}"#).unwrap_err();
+        assert!(matches!(error, SearchError::InvalidArgument(_)));
+    }
+
+    #[test]
+    fn test_filtered_query_ast() {
+        let knn_query = KnnQuery::parse(
+            r#"{"field": "embedding", "query_vector": [1.0], "k": 1, "filter": {"type": "match_all"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            filtered_query_ast(QueryAst::MatchNone, &knn_query),
+            QueryAst::Bool(BoolQuery {
+                must: vec![QueryAst::MatchNone],
+                filter: vec![QueryAst::MatchAll],
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            filtered_query_ast(QueryAst::MatchAll, &knn_query),
+            QueryAst::MatchAll
+        );
+    }
+
+    #[test]
+    fn test_reciprocal_rank_fusion() {
+        let knn_query = KnnQuery::parse(
+            r#"{"field": "embedding", "query_vector": [1.0], "k": 3, "rrf": {"rank_constant": 1}}"#,
+        )
+        .unwrap();
+        let query_hits = LeafSearchResponse {
+            num_hits: 10,
+            partial_hits: vec![partial_hit("split", 1), partial_hit("split", 2)],
+            num_attempted_splits: 1,
+            num_successful_splits: 1,
+            ..Default::default()
+        };
+        let knn_hits = LeafSearchResponse {
+            num_hits: 3,
+            partial_hits: vec![
+                partial_hit("split", 3),
+                partial_hit("split", 2),
+                partial_hit("split", 1),
+            ],
+            num_attempted_splits: 1,
+            num_successful_splits: 1,
+            ..Default::default()
+        };
+        let fused_response = reciprocal_rank_fusion(vec![query_hits, knn_hits], &knn_query, 0, 10);
+        assert_eq!(fused_response.num_hits, 3);
+        assert_eq!(fused_response.num_attempted_splits, 2);
+
+        // doc 1: 1/2 + 1/4, doc 2: 1/3 + 1/3, doc 3: 1/2
+        let doc_ids: Vec<u32> = fused_response
+            .partial_hits
+            .iter()
+            .map(|partial_hit| partial_hit.doc_id)
+            .collect();
+        assert_eq!(doc_ids, [1, 2, 3]);
+        assert_eq!(
+            fused_response.partial_hits[0].sort_value,
+            Some(SortValue::F64(0.75).into())
+        );
+        let fused_response = reciprocal_rank_fusion(
+            vec![LeafSearchResponse::default(), fused_response],
+            &knn_query,
+            1,
+            1,
+        );
+        assert_eq!(fused_response.partial_hits.len(), 1);
+        assert_eq!(fused_response.partial_hits[0].doc_id, 2);
+    }
+}
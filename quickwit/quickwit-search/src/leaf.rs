@@ -16,6 +16,7 @@ use std::collections::{HashMap, HashSet};
 use std::ops::Bound;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Duration, Instant};
 
@@ -32,8 +33,8 @@ use quickwit_proto::search::{
 use quickwit_query::query_ast::{BoolQuery, QueryAst, QueryAstTransformer, RangeQuery, TermQuery};
 use quickwit_query::tokenizers::TokenizerManager;
 use quickwit_storage::{
-    BundleStorage, ByteRangeCache, MemorySizedCache, OwnedBytes, SplitCache, Storage,
-    StorageResolver, TimeoutAndRetryStorage, wrap_storage_with_cache,
+    BundleStorage, ByteCountingStorage, ByteRangeCache, MemorySizedCache, OwnedBytes, SplitCache,
+    Storage, StorageResolver, TimeoutAndRetryStorage, wrap_storage_with_cache,
 };
 use tantivy::aggregation::AggregationLimitsGuard;
 use tantivy::aggregation::agg_req::{AggregationVariants, Aggregations};
@@ -42,11 +43,14 @@ use tantivy::fastfield::FastFieldReaders;
 use tantivy::schema::Field;
 use tantivy::{DateTime, Index, ReloadPolicy, Searcher, TantivyError, Term};
 use tokio::task::JoinError;
+use tokio_util::task::AbortOnDropHandle;
 use tracing::*;
 
 use crate::collector::{IncrementalCollector, make_collector_for_split, make_merge_collector};
+use crate::knn::{KnnQuery, KnnSplitSearch};
 use crate::root::is_metadata_count_request_with_ast;
 use crate::search_permit_provider::{SearchPermit, compute_initial_memory_allocation};
+use crate::search_task::{SearchInterruption, SearchInterruptionCause};
 use crate::service::{SearcherContext, deserialize_doc_mapper};
 use crate::{QuickwitAggregations, SearchError};
 
@@ -381,6 +385,29 @@ async fn warm_up_automatons(
                                 .await
                                 .context("failed to load automaton")
                         }
+                        Automaton::Fuzzy {
+                            path,
+                            value,
+                            distance,
+                            transpositions,
+                        } => {
+                            let levenshtein_automaton =
+                                quickwit_query::query_ast::LevenshteinAutomaton::new(
+                                    value,
+                                    *distance,
+                                    *transpositions,
+                                );
+                            inv_idx_clone
+                                .warm_postings_automaton(
+                                    quickwit_query::query_ast::JsonPathPrefix {
+                                        automaton: levenshtein_automaton.into(),
+                                        prefix: path.clone().unwrap_or_default(),
+                                    },
+                                    cpu_intensive_executor,
+                                )
+                                .await
+                                .context("failed to load automaton")
+                        }
                     }
                 });
             }
@@ -417,11 +444,13 @@ fn get_leaf_resp_from_count(count: u64) -> LeafSearchResponse {
         num_successful_splits: 1,
         intermediate_aggregation_result: None,
         resource_stats: None,
+        timed_out: false,
+        collapse_groups: Vec::new(),
     }
 }
 
 /// Compute the size of the index, store excluded.
-fn compute_index_size(hot_directory: &HotDirectory) -> ByteSize {
+pub(crate) fn compute_index_size(hot_directory: &HotDirectory) -> ByteSize {
     let size_bytes = hot_directory
         .get_file_lengths()
         .iter()
@@ -448,15 +477,26 @@ async fn leaf_search_single_split(
         &split,
         doc_mapper.timestamp_field_name(),
     );
-    if let Some(cached_answer) = searcher_context
+    if let Some(mut cached_answer) = searcher_context
         .leaf_search_cache
         .get(split.clone(), search_request.clone())
     {
+        // The resource stats of the cached answer are those of the search that produced it.
+        cached_answer.resource_stats = Some(ResourceStats {
+            split_num_docs: split.num_docs,
+            num_leaf_search_cache_hits: 1,
+            ..Default::default()
+        });
         return Ok(cached_answer);
     }
 
     let query_ast: QueryAst = serde_json::from_str(search_request.query_ast.as_str())
         .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
+    let knn_query_opt = search_request
+        .knn
+        .as_deref()
+        .map(KnnQuery::parse)
+        .transpose()?;
 
     // CanSplitDoBetter or rewrite_request may have changed the request to be a count only request
     // This may be the case for AllQuery with a sort by date and time filter, where the current
@@ -469,6 +509,12 @@ async fn leaf_search_single_split(
     let split_id = split.split_id.to_string();
     let byte_range_cache =
         ByteRangeCache::with_infinite_capacity(&quickwit_storage::STORAGE_METRICS.shortlived_cache);
+    // Reads served by the searcher caches do not reach this storage.
+    let downloaded_num_bytes = Arc::new(AtomicU64::new(0));
+    let storage = Arc::new(ByteCountingStorage::new(
+        storage,
+        downloaded_num_bytes.clone(),
+    ));
     let (index, hot_directory) = open_index_with_caches(
         searcher_context,
         storage,
@@ -500,10 +546,23 @@ async fn leaf_search_single_split(
     warmup_info.simplify();
 
     let warmup_start = Instant::now();
+    let downloaded_num_bytes_before_warmup = downloaded_num_bytes.load(Ordering::Relaxed);
     warmup(&searcher, &warmup_info).await?;
+    let knn_split_search_opt = if let Some(knn_query) = &knn_query_opt {
+        let Some(knn_split_search) = KnnSplitSearch::open(&index, knn_query).await? else {
+            // The split has no vector for the field.
+            return Ok(get_leaf_resp_from_count(0));
+        };
+        Some(knn_split_search)
+    } else {
+        None
+    };
     let warmup_end = Instant::now();
     let warmup_duration: Duration = warmup_end.duration_since(warmup_start);
     let warmup_size = ByteSize(byte_range_cache.get_num_bytes());
+    let split_downloaded_num_bytes = downloaded_num_bytes.load(Ordering::Relaxed);
+    let warmup_downloaded_num_bytes =
+        split_downloaded_num_bytes - downloaded_num_bytes_before_warmup;
     if warmup_size > search_permit.memory_allocation() {
         warn!(
             memory_usage = ?warmup_size,
@@ -534,7 +593,16 @@ async fn leaf_search_single_split(
                 check_optimize_search_request(&mut search_request, &split, &split_filter);
                 collector.update_search_param(&search_request);
                 let mut leaf_search_response: LeafSearchResponse =
-                    if is_metadata_count_request_with_ast(&query_ast, &search_request) {
+                    if let Some(knn_split_search) = &knn_split_search_opt {
+                        let num_hits_to_return =
+                            (search_request.start_offset + search_request.max_hits) as usize;
+                        knn_split_search.search(
+                            &searcher,
+                            query.as_ref(),
+                            &split.split_id,
+                            num_hits_to_return,
+                        )?
+                    } else if is_metadata_count_request_with_ast(&query_ast, &search_request) {
                         get_leaf_resp_from_count(searcher.num_docs())
                     } else if collector.is_count_only() {
                         let count = query.count(&searcher)? as u64;
@@ -549,6 +617,9 @@ async fn leaf_search_single_split(
                     warmup_microsecs: warmup_duration.as_micros() as u64,
                     cpu_thread_pool_wait_microsecs: cpu_thread_pool_wait_microsecs.as_micros()
                         as u64,
+                    downloaded_num_bytes: split_downloaded_num_bytes,
+                    warmup_downloaded_num_bytes,
+                    num_leaf_search_cache_hits: 0,
                 });
                 Result::<_, TantivyError>::Ok((search_request, leaf_search_response))
             })
@@ -569,7 +640,7 @@ async fn leaf_search_single_split(
 ///
 /// This include things such as sorting result by a field or _score when no document is requested,
 /// or applying date range when the range covers the entire split.
-fn rewrite_request(
+pub(crate) fn rewrite_request(
     search_request: &mut SearchRequest,
     split: &SplitIdAndFooterOffsets,
     timestamp_field: Option<&str>,
@@ -916,10 +987,20 @@ fn is_simple_all_query(search_request: &SearchRequest) -> bool {
         return false;
     }
 
+    if search_request.knn.is_some() {
+        return false;
+    }
+
     if search_request.search_after.is_some() {
         return false;
     }
 
+    // A split holding enough documents does not necessarily hold enough distinct values of the
+    // collapse field.
+    if search_request.collapse.is_some() {
+        return false;
+    }
+
     // TODO: Update the logic to handle start_timestamp end_timestamp ranges
     if search_request.start_timestamp.is_some() || search_request.end_timestamp.is_some() {
         return false;
@@ -944,6 +1025,12 @@ enum CanSplitDoBetter {
 impl CanSplitDoBetter {
     /// Create a CanSplitDoBetter from a SearchRequest
     fn from_request(request: &SearchRequest, timestamp_field_name: Option<&str>) -> Self {
+        // Counting the hits of each group requires collecting the hits of every split.
+        if let Some(collapse) = &request.collapse
+            && collapse.group_size > 0
+        {
+            return CanSplitDoBetter::Uninformative;
+        }
         if request.max_hits == 0
             && let Some(aggregation) = &request.aggregation_request
             && let Ok(crate::QuickwitAggregations::FindTraceIdsAggregation(find_trace_aggregation)) =
@@ -1161,6 +1248,12 @@ pub async fn multi_index_leaf_search(
         .ok_or_else(|| SearchError::Internal("no search request".to_string()))?
         .into();
 
+    let search_task_guard = searcher_context.search_task_registry.register_leaf_search(
+        leaf_search_request.search_task_id,
+        leaf_search_request
+            .timeout_millis
+            .map(Duration::from_millis),
+    );
     let doc_mappers: Vec<Arc<DocMapper>> = leaf_search_request
         .doc_mappers
         .iter()
@@ -1204,6 +1297,7 @@ pub async fn multi_index_leaf_search(
             let searcher_context = searcher_context.clone();
             let search_request = search_request.clone();
             let aggregation_limits = aggregation_limits.clone();
+            let interruption = search_task_guard.interruption().clone();
             async move {
                 let storage = storage_resolver.resolve(&index_uri).await?;
                 single_doc_mapping_leaf_search(
@@ -1213,6 +1307,7 @@ pub async fn multi_index_leaf_search(
                     leaf_search_request_ref.split_offsets,
                     doc_mapper,
                     aggregation_limits,
+                    interruption,
                 )
                 .await
             }
@@ -1233,6 +1328,8 @@ pub async fn multi_index_leaf_search(
             Ok(result) => {
                 incremental_merge_collector.add_result(result)?;
             }
+            // The tenant of the search is over its queue limit: the whole request is rejected.
+            Err(SearchError::TooManyRequests) => return Err(SearchError::TooManyRequests),
             Err(err) => {
                 incremental_merge_collector.add_failed_split(SplitSearchError {
                     split_id: "unknown".to_string(),
@@ -1279,14 +1376,19 @@ fn disable_search_request_hits(search_request: &mut SearchRequest) {
 /// [PartialHit](quickwit_proto::search::PartialHit) candidates. The root will be in
 /// charge to consolidate, identify the actual final top hits to display, and
 /// fetch the actual documents to convert the partial hits into actual Hits.
+///
+/// If the search deadline is reached, the split searches still running are aborted and the
+/// results of the splits searched so far are returned, flagged as `timed_out`. If the search
+/// task is cancelled, the split searches are aborted and an error is returned.
 #[instrument(skip_all, fields(index = ?request.index_id_patterns))]
-pub async fn single_doc_mapping_leaf_search(
+pub(crate) async fn single_doc_mapping_leaf_search(
     searcher_context: Arc<SearcherContext>,
     request: Arc<SearchRequest>,
     index_storage: Arc<dyn Storage>,
     splits: Vec<SplitIdAndFooterOffsets>,
     doc_mapper: Arc<DocMapper>,
     aggregations_limits: AggregationLimitsGuard,
+    interruption: SearchInterruption,
 ) -> Result<LeafSearchResponse, SearchError> {
     let num_docs: u64 = splits.iter().map(|split| split.num_docs).sum();
     let num_splits = splits.len();
@@ -1307,15 +1409,16 @@ pub async fn single_doc_mapping_leaf_search(
 
     let split_filter = Arc::new(RwLock::new(split_filter));
 
-    let mut leaf_search_single_split_join_handles: Vec<(String, tokio::task::JoinHandle<()>)> =
-        Vec::with_capacity(split_with_req.len());
-
     let merge_collector = make_merge_collector(&request, &aggregations_limits)?;
     let incremental_merge_collector = IncrementalCollector::new(merge_collector);
     let incremental_merge_collector = Arc::new(Mutex::new(incremental_merge_collector));
 
     // We acquire all of the leaf search permits to make sure our single split search tasks
-    // do no interleave with other leaf search requests.
+    // do no interleave with other leaf search requests of the same tenant.
+    let tenant_id = request
+        .tenant_id
+        .clone()
+        .unwrap_or_else(|| request.index_id_patterns.join(","));
     let permit_sizes = split_with_req.iter().map(|(split, _)| {
         compute_initial_memory_allocation(
             split,
@@ -1326,60 +1429,82 @@ pub async fn single_doc_mapping_leaf_search(
     });
     let permit_futures = searcher_context
         .search_permit_provider
-        .get_permits(permit_sizes)
-        .await;
-
-    for ((split, mut request), permit_fut) in
-        split_with_req.into_iter().zip(permit_futures.into_iter())
-    {
-        let leaf_split_search_permit = permit_fut
-            .instrument(info_span!("waiting_for_leaf_search_split_semaphore"))
-            .await;
+        .get_permits(&tenant_id, permit_sizes)
+        .await?;
 
-        let can_be_better = check_optimize_search_request(&mut request, &split, &split_filter);
-        if !can_be_better && !run_all_splits {
-            continue;
-        }
+    // Dropping this future aborts the split searches still running and drops the permits not
+    // acquired yet.
+    let split_searches = async {
+        let mut leaf_search_single_split_join_handles: Vec<(String, AbortOnDropHandle<()>)> =
+            Vec::with_capacity(split_with_req.len());
 
-        leaf_search_single_split_join_handles.push((
-            split.split_id.clone(),
-            tokio::spawn(
-                leaf_search_single_split_wrapper(
-                    request,
-                    searcher_context.clone(),
-                    index_storage.clone(),
-                    doc_mapper.clone(),
-                    split,
-                    split_filter.clone(),
-                    incremental_merge_collector.clone(),
-                    leaf_split_search_permit,
-                    aggregations_limits.clone(),
-                )
-                .in_current_span(),
-            ),
-        ));
-    }
-
-    // TODO we could cancel running splits when !run_all_splits and the running split can no
-    // longer give better results after some other split answered.
-    let mut split_search_join_errors: Vec<(String, JoinError)> = Vec::new();
+        for ((split, mut request), permit_fut) in
+            split_with_req.into_iter().zip(permit_futures.into_iter())
+        {
+            let leaf_split_search_permit = permit_fut
+                .instrument(info_span!("waiting_for_leaf_search_split_semaphore"))
+                .await;
 
-    // There is no need to use `join_all`, as these are spawned tasks.
-    for (split, leaf_search_join_handle) in leaf_search_single_split_join_handles {
-        // splits that did not panic were already added to the collector
-        if let Err(join_error) = leaf_search_join_handle.await {
-            if join_error.is_cancelled() {
-                // An explicit task cancellation is not an error.
+            let can_be_better = check_optimize_search_request(&mut request, &split, &split_filter);
+            if !can_be_better && !run_all_splits {
                 continue;
             }
-            if join_error.is_panic() {
-                error!(split=%split, "leaf search task panicked");
-            } else {
-                error!(split=%split, "please report: leaf search was not cancelled, and could not extract panic. this should never happen");
+
+            leaf_search_single_split_join_handles.push((
+                split.split_id.clone(),
+                AbortOnDropHandle::new(tokio::spawn(
+                    leaf_search_single_split_wrapper(
+                        request,
+                        searcher_context.clone(),
+                        index_storage.clone(),
+                        doc_mapper.clone(),
+                        split,
+                        split_filter.clone(),
+                        incremental_merge_collector.clone(),
+                        leaf_split_search_permit,
+                        aggregations_limits.clone(),
+                    )
+                    .in_current_span(),
+                )),
+            ));
+        }
+
+        // TODO we could cancel running splits when !run_all_splits and the running split can no
+        // longer give better results after some other split answered.
+        let mut split_search_join_errors: Vec<(String, JoinError)> = Vec::new();
+
+        // There is no need to use `join_all`, as these are spawned tasks.
+        for (split, leaf_search_join_handle) in leaf_search_single_split_join_handles {
+            // splits that did not panic were already added to the collector
+            if let Err(join_error) = leaf_search_join_handle.await {
+                if join_error.is_cancelled() {
+                    // An explicit task cancellation is not an error.
+                    continue;
+                }
+                if join_error.is_panic() {
+                    error!(split=%split, "leaf search task panicked");
+                } else {
+                    error!(split=%split, "please report: leaf search was not cancelled, and could not extract panic. this should never happen");
+                }
+                split_search_join_errors.push((split, join_error));
             }
-            split_search_join_errors.push((split, join_error));
         }
-    }
+        split_search_join_errors
+    };
+
+    let (split_search_join_errors, timed_out) = tokio::select! {
+        split_search_join_errors = split_searches => (split_search_join_errors, false),
+        interruption_cause = interruption.interrupted() => match interruption_cause {
+            SearchInterruptionCause::TimedOut => {
+                info!("leaf search timed out, returning partial results");
+                (Vec::new(), true)
+            }
+            SearchInterruptionCause::Cancelled => {
+                info!("leaf search cancelled");
+                return Err(SearchError::Cancelled("leaf search was cancelled".to_string()));
+            }
+        },
+    };
 
     // we can't use unwrap_or_clone because mutexes aren't Clone
     let mut incremental_merge_collector = match Arc::try_unwrap(incremental_merge_collector) {
@@ -1394,6 +1519,9 @@ pub async fn single_doc_mapping_leaf_search(
             retryable_error: true,
         });
     }
+    if timed_out {
+        incremental_merge_collector.set_timed_out();
+    }
 
     let leaf_search_response_reresult: Result<Result<LeafSearchResponse, _>, _> =
         crate::search_thread_pool()
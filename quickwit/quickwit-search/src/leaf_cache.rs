@@ -100,6 +100,8 @@ impl CacheKey {
         // it doesn't matter whether or not we count all hits at the scale of a
         // single split: either we did process it and got everything, or we didn't.
         search_request.count_hits = CountHits::CountAll.into();
+        // the timeout only bounds how long we wait for the search, not its result.
+        search_request.timeout_millis = None;
 
         CacheKey {
             split_id: split_info.split_id,
@@ -250,8 +252,11 @@ mod tests {
                 sort_value: Some(SortValue::U64(0u64).into()),
                 sort_value2: None,
                 split_id: "split_1".to_string(),
+                collapse_key: None,
             }],
             resource_stats: None,
+            timed_out: false,
+            collapse_groups: Vec::new(),
         };
 
         assert!(cache.get(split_1.clone(), query_1.clone()).is_none());
@@ -341,8 +346,11 @@ mod tests {
                 sort_value: Some(SortValue::U64(0).into()),
                 sort_value2: None,
                 split_id: "split_1".to_string(),
+                collapse_key: None,
             }],
             resource_stats: Some(ResourceStats::default()),
+            timed_out: false,
+            collapse_groups: Vec::new(),
         };
 
         // for split_1, 1 and 1bis cover different timestamp ranges
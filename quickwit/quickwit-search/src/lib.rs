@@ -17,24 +17,33 @@
 #![allow(clippy::bool_assert_comparison)]
 #![deny(clippy::disallowed_methods)]
 
+mod async_search;
 mod client;
 mod cluster_client;
 mod collector;
+mod composite_aggregation;
 mod error;
+mod export;
 mod fetch_docs;
 mod find_trace_ids_collector;
+mod knn;
 mod leaf;
 mod leaf_cache;
 mod list_fields;
 mod list_fields_cache;
 mod list_terms;
 mod metrics_trackers;
+mod point_in_time;
 mod retry;
 mod root;
+mod root_cache;
 mod scroll_context;
 mod search_job_placer;
+mod search_profile;
 mod search_response_rest;
+mod search_task;
 mod service;
+mod source_filter;
 pub(crate) mod top_k_collector;
 
 mod metrics;
@@ -59,6 +68,7 @@ pub type Result<T> = std::result::Result<T, SearchError>;
 use std::net::{Ipv4Addr, SocketAddr};
 use std::sync::{Arc, OnceLock};
 
+pub use composite_aggregation::CompositeAggregation;
 pub use find_trace_ids_collector::{FindTraceIdsCollector, Span};
 use quickwit_config::SearcherConfig;
 use quickwit_doc_mapper::tag_pruning::TagFilterAst;
@@ -67,7 +77,7 @@ use quickwit_metastore::{
     MetastoreServiceStreamSplitsExt, SplitMetadata, SplitState,
 };
 use quickwit_proto::search::{
-    PartialHit, ResourceStats, SearchRequest, SearchResponse, SplitIdAndFooterOffsets,
+    PartialHit, ResourceStats, SearchRequest, SearchResponse, SourceFilter, SplitIdAndFooterOffsets,
 };
 use quickwit_proto::types::IndexUid;
 use quickwit_storage::StorageResolver;
@@ -79,7 +89,10 @@ pub use crate::client::{
 };
 pub use crate::cluster_client::ClusterClient;
 pub use crate::error::{SearchError, parse_grpc_error};
+pub use crate::export::{ExportCursor, ExportPage, ExportRequest, ExportStream, LeafExportStream};
 use crate::fetch_docs::fetch_docs;
+pub use crate::knn::{KnnQuery, RrfParams};
+use crate::root::resolve_indexes_and_aliases;
 pub use crate::root::{
     IndexMetasForLeafSearch, SearchJob, check_all_index_metadata_found, jobs_to_leaf_request,
     root_search, search_plan,
@@ -88,7 +101,9 @@ pub use crate::search_job_placer::{Job, SearchJobPlacer};
 pub use crate::search_response_rest::{
     AggregationResults, SearchPlanResponseRest, SearchResponseRest,
 };
+pub use crate::search_task::SearchTaskRegistry;
 pub use crate::service::{MockSearchService, SearchService, SearchServiceImpl};
+use crate::source_filter::filter_source;
 
 /// A pool of searcher clients identified by their gRPC socket address.
 pub type SearcherPool = Pool<SocketAddr, SearchServiceClient>;
@@ -215,26 +230,21 @@ pub async fn list_relevant_splits(
 }
 
 /// Resolve index patterns and returns IndexMetadata for found indices.
-/// Patterns follow the elastic search patterns.
+/// Patterns follow the elastic search patterns. Index aliases resolve to their target indexes,
+/// their filters are ignored.
 pub async fn resolve_index_patterns(
     index_id_patterns: &[String],
     metastore: &mut MetastoreServiceClient,
 ) -> crate::Result<Vec<IndexMetadata>> {
-    let list_indexes_metadata_request = if index_id_patterns.is_empty() {
-        ListIndexesMetadataRequest::all()
-    } else {
-        ListIndexesMetadataRequest {
-            index_id_patterns: index_id_patterns.to_owned(),
-        }
-    };
-
-    // Get the index ids from the request
+    if !index_id_patterns.is_empty() {
+        let resolved_indexes = resolve_indexes_and_aliases(metastore, index_id_patterns).await?;
+        return Ok(resolved_indexes.indexes_metadata);
+    }
     let indexes_metadata = metastore
-        .list_indexes_metadata(list_indexes_metadata_request)
+        .list_indexes_metadata(ListIndexesMetadataRequest::all())
         .await?
         .deserialize_indexes_metadata()
         .await?;
-    check_all_index_metadata_found(&indexes_metadata, index_id_patterns)?;
     Ok(indexes_metadata)
 }
 
@@ -246,9 +256,13 @@ pub async fn resolve_index_patterns(
 fn convert_document_to_json_string(
     named_field_doc: NamedFieldDocument,
     doc_mapper: &DocMapper,
+    source_filter_opt: Option<&SourceFilter>,
 ) -> anyhow::Result<String> {
     let NamedFieldDocument(named_field_doc_map) = named_field_doc;
-    let doc_json_map = doc_mapper.doc_to_json(named_field_doc_map)?;
+    let mut doc_json_map = doc_mapper.doc_to_json(named_field_doc_map)?;
+    if let Some(source_filter) = source_filter_opt {
+        filter_source(&mut doc_json_map, source_filter);
+    }
     let content_json =
         serde_json::to_string(&doc_json_map).expect("Json serialization should never fail.");
     Ok(content_json)
@@ -359,6 +373,9 @@ fn merge_resource_stats(
             stat_accs.warmup_microsecs += new_stats.warmup_microsecs;
             stat_accs.cpu_thread_pool_wait_microsecs += new_stats.cpu_thread_pool_wait_microsecs;
             stat_accs.cpu_microsecs += new_stats.cpu_microsecs;
+            stat_accs.downloaded_num_bytes += new_stats.downloaded_num_bytes;
+            stat_accs.warmup_downloaded_num_bytes += new_stats.warmup_downloaded_num_bytes;
+            stat_accs.num_leaf_search_cache_hits += new_stats.num_leaf_search_cache_hits;
         } else {
             *stat_accs_opt = Some(*new_stats);
         }
@@ -382,6 +399,9 @@ mod stats_merge_tests {
             warmup_microsecs: 300,
             cpu_thread_pool_wait_microsecs: 400,
             cpu_microsecs: 500,
+            downloaded_num_bytes: 1000,
+            warmup_downloaded_num_bytes: 500,
+            num_leaf_search_cache_hits: 20,
         });
 
         merge_resource_stats(&stats, &mut acc_stats);
@@ -394,6 +414,9 @@ mod stats_merge_tests {
             warmup_microsecs: 150,
             cpu_thread_pool_wait_microsecs: 200,
             cpu_microsecs: 250,
+            downloaded_num_bytes: 500,
+            warmup_downloaded_num_bytes: 250,
+            num_leaf_search_cache_hits: 10,
         });
 
         merge_resource_stats(&new_stats, &mut acc_stats);
@@ -404,6 +427,9 @@ mod stats_merge_tests {
             warmup_microsecs: 450,
             cpu_thread_pool_wait_microsecs: 600,
             cpu_microsecs: 750,
+            downloaded_num_bytes: 1500,
+            warmup_downloaded_num_bytes: 750,
+            num_leaf_search_cache_hits: 30,
         });
 
         assert_eq!(acc_stats, stats_plus_new_stats);
@@ -424,6 +450,9 @@ mod stats_merge_tests {
             warmup_microsecs: 300,
             cpu_thread_pool_wait_microsecs: 400,
             cpu_microsecs: 500,
+            downloaded_num_bytes: 1000,
+            warmup_downloaded_num_bytes: 500,
+            num_leaf_search_cache_hits: 20,
         });
 
         let merged_stats = merge_resource_stats_it(vec![&None, &stats1, &None]);
@@ -436,6 +465,9 @@ mod stats_merge_tests {
             warmup_microsecs: 150,
             cpu_thread_pool_wait_microsecs: 200,
             cpu_microsecs: 250,
+            downloaded_num_bytes: 500,
+            warmup_downloaded_num_bytes: 250,
+            num_leaf_search_cache_hits: 10,
         });
 
         let stats3 = Some(ResourceStats {
@@ -444,6 +476,9 @@ mod stats_merge_tests {
             warmup_microsecs: 75,
             cpu_thread_pool_wait_microsecs: 100,
             cpu_microsecs: 125,
+            downloaded_num_bytes: 250,
+            warmup_downloaded_num_bytes: 125,
+            num_leaf_search_cache_hits: 5,
         });
 
         let merged_stats = merge_resource_stats_it(vec![&stats1, &stats2, &stats3]);
@@ -456,6 +491,9 @@ mod stats_merge_tests {
                 warmup_microsecs: 525,
                 cpu_thread_pool_wait_microsecs: 700,
                 cpu_microsecs: 875,
+                downloaded_num_bytes: 1750,
+                warmup_downloaded_num_bytes: 875,
+                num_leaf_search_cache_hits: 35,
             })
         );
     }
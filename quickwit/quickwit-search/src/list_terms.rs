@@ -334,8 +334,8 @@ pub async fn leaf_list_terms(
     });
     let permits = searcher_context
         .search_permit_provider
-        .get_permits(permit_sizes)
-        .await;
+        .get_permits(&request.index_id_patterns.join(","), permit_sizes)
+        .await?;
     let leaf_search_single_split_futures: Vec<_> = splits
         .iter()
         .zip(permits.into_iter())
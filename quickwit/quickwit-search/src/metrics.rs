@@ -34,8 +34,11 @@ pub struct SearchMetrics {
     pub job_assigned_total: IntCounterVec<1>,
     pub leaf_search_single_split_tasks_pending: IntGauge,
     pub leaf_search_single_split_tasks_ongoing: IntGauge,
+    pub leaf_search_permit_wait_duration_seconds: HistogramVec<1>,
+    pub leaf_search_permit_requests_rejected_total: IntCounterVec<1>,
     pub leaf_search_single_split_warmup_num_bytes: Histogram,
     pub searcher_local_kv_store_size_bytes: IntGauge,
+    pub searcher_async_search_kv_store_size_bytes: IntGauge,
 }
 
 /// From 0.008s to 131.072s
@@ -140,6 +143,22 @@ impl Default for SearchMetrics {
                 .with_label_values(["ongoing"]),
             leaf_search_single_split_tasks_pending: leaf_search_single_split_tasks
                 .with_label_values(["pending"]),
+            leaf_search_permit_wait_duration_seconds: new_histogram_vec(
+                "leaf_search_permit_wait_duration_seconds",
+                "Time spent by single split search tasks waiting for a search permit, per tenant.",
+                "search",
+                &[],
+                ["tenant"],
+                duration_buckets(),
+            ),
+            leaf_search_permit_requests_rejected_total: new_counter_vec(
+                "leaf_search_permit_requests_rejected_total",
+                "Number of leaf search requests rejected because the queue of their tenant was \
+                 full, per tenant.",
+                "search",
+                &[],
+                ["tenant"],
+            ),
             leaf_search_single_split_warmup_num_bytes: new_histogram(
                 "leaf_search_single_split_warmup_num_bytes",
                 "Size of the short lived cache for a single split once the warmup is done.",
@@ -160,6 +179,12 @@ impl Default for SearchMetrics {
                 "search",
                 &[],
             ),
+            searcher_async_search_kv_store_size_bytes: new_gauge(
+                "searcher_async_search_kv_store_size_bytes",
+                "Size of the searcher kv store holding the states of the async searches in bytes.",
+                "search",
+                &[],
+            ),
         }
     }
 }
@@ -0,0 +1,475 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::Context;
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use quickwit_metastore::SplitMetadata;
+use quickwit_proto::metastore::{
+    DeletePointInTimeLeaseRequest, MetastoreService, MetastoreServiceClient, PointInTimeLease,
+    PutPointInTimeLeaseRequest,
+};
+use quickwit_proto::search::{
+    ClosePointInTimeRequest, ClosePointInTimeResponse, OpenPointInTimeRequest,
+    OpenPointInTimeResponse, PointInTime,
+};
+use quickwit_proto::types::IndexUid;
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::async_search::now_millis;
+use crate::root::resolve_indexes_and_aliases;
+use crate::{ClusterClient, SearchError, list_all_splits};
+
+/// Maximum accepted point in time keep alive.
+///
+/// Unlike scroll contexts, points in time are not bounded by the split deletion grace period: the
+/// janitor defers the deletion of the splits pinned by the point in time leases recorded in the
+/// metastore.
+const MAX_POINT_IN_TIME_KEEP_ALIVE: Duration = Duration::from_secs(24 * 3_600);
+
+/// Lifetime of the empty payload overwriting the context of a closed point in time.
+const CLOSED_POINT_IN_TIME_TTL: Duration = Duration::from_secs(60);
+
+/// Searches only extend the lease of a point in time if it gains at least this fraction of the
+/// requested keep alive, so that paginating through a point in time does not write to the
+/// metastore on every page.
+const LEASE_EXTENSION_MIN_RATIO: u64 = 10;
+
+/// Identifies a point in time.
+///
+/// Besides the key of its context in the KV store, the ID carries the indexes the point in time
+/// was opened on, so that the nodes serving it can extend its lease without loading its context.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct PointInTimeId {
+    pit_ulid: Ulid,
+    index_uids: Vec<IndexUid>,
+}
+
+impl PointInTimeId {
+    fn new(index_uids: Vec<IndexUid>) -> Self {
+        PointInTimeId {
+            pit_ulid: Ulid::new(),
+            index_uids,
+        }
+    }
+
+    /// Returns a short ID, unique to this point in time.
+    fn lease_id(&self) -> String {
+        self.pit_ulid.to_string()
+    }
+
+    /// Returns the Unix timestamp, in seconds, at which the point in time was opened.
+    fn opened_at(&self) -> i64 {
+        (self.pit_ulid.timestamp_ms() / 1_000) as i64
+    }
+
+    /// Returns the lease of the point in time, expiring after `keep_alive`.
+    fn lease(&self, keep_alive: Duration) -> PointInTimeLease {
+        PointInTimeLease {
+            lease_id: self.lease_id(),
+            index_uids: self.index_uids.clone(),
+            opened_at: self.opened_at(),
+            expires_at: now_millis() / 1_000 + keep_alive.as_secs() as i64,
+        }
+    }
+
+    fn pit_key(&self) -> [u8; 16] {
+        u128::from(self.pit_ulid).to_le_bytes()
+    }
+}
+
+impl fmt::Display for PointInTimeId {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let payload =
+            serde_json::to_vec(self).expect("serializing a point in time ID should never fail");
+        let b64_payload = BASE64_STANDARD.encode(payload);
+        write!(formatter, "{b64_payload}")
+    }
+}
+
+impl FromStr for PointInTimeId {
+    type Err = &'static str;
+
+    fn from_str(pit_id_str: &str) -> Result<Self, Self::Err> {
+        let base64_decoded: Vec<u8> = BASE64_STANDARD
+            .decode(pit_id_str)
+            .map_err(|_| "point in time id is invalid base64")?;
+        serde_json::from_slice(&base64_decoded).map_err(|_| "point in time id is malformed")
+    }
+}
+
+/// Splits pinned by a point in time, stored in the searchers KV store.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct PointInTimeContext {
+    pub index_uids: Vec<IndexUid>,
+    pub split_metadatas: Vec<SplitMetadata>,
+    /// Unix timestamp, in seconds, at which the lease recorded in the metastore expires.
+    #[serde(default)]
+    pub lease_expires_at: i64,
+}
+
+impl PointInTimeContext {
+    fn serialize(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap()
+    }
+
+    fn load(payload: &[u8]) -> anyhow::Result<Self> {
+        let point_in_time_context =
+            serde_json::from_slice(payload).context("failed to deserialize context")?;
+        Ok(point_in_time_context)
+    }
+
+    /// Returns the IDs of the indexes the point in time was opened on.
+    pub fn index_ids(&self) -> Vec<String> {
+        self.index_uids
+            .iter()
+            .map(|index_uid| index_uid.index_id.clone())
+            .collect()
+    }
+
+    /// Returns the pinned splits of the given indexes that may contain documents within the time
+    /// range, following the same rules as the metastore when listing splits.
+    pub fn into_relevant_splits(
+        self,
+        index_uids: &[IndexUid],
+        start_timestamp_opt: Option<i64>,
+        end_timestamp_opt: Option<i64>,
+    ) -> Vec<SplitMetadata> {
+        self.split_metadatas
+            .into_iter()
+            .filter(|split_metadata| {
+                if !index_uids.contains(&split_metadata.index_uid) {
+                    return false;
+                }
+                let Some(time_range) = &split_metadata.time_range else {
+                    return true;
+                };
+                if let Some(start_timestamp) = start_timestamp_opt
+                    && *time_range.end() < start_timestamp
+                {
+                    return false;
+                }
+                if let Some(end_timestamp) = end_timestamp_opt
+                    && *time_range.start() >= end_timestamp
+                {
+                    return false;
+                }
+                true
+            })
+            .collect()
+    }
+}
+
+fn get_keep_alive_duration(keep_alive_secs: u32) -> crate::Result<Duration> {
+    let keep_alive = Duration::from_secs(keep_alive_secs as u64);
+    if keep_alive.is_zero() || keep_alive > MAX_POINT_IN_TIME_KEEP_ALIVE {
+        return Err(SearchError::InvalidArgument(format!(
+            "point in time keep alive must be between 1 and {} secs",
+            MAX_POINT_IN_TIME_KEEP_ALIVE.as_secs()
+        )));
+    }
+    Ok(keep_alive)
+}
+
+fn parse_point_in_time_id(pit_id_str: &str) -> crate::Result<PointInTimeId> {
+    PointInTimeId::from_str(pit_id_str).map_err(|msg| SearchError::InvalidArgument(msg.to_string()))
+}
+
+/// Records or extends the lease of a point in time in the metastore. The janitor keeps the splits
+/// pinned by the point in time until the lease expires, regardless of the node that served it.
+///
+/// Returns the expiration timestamp of the lease.
+async fn put_point_in_time_lease(
+    pit_id: &PointInTimeId,
+    keep_alive: Duration,
+    metastore: &mut MetastoreServiceClient,
+) -> crate::Result<i64> {
+    let lease = pit_id.lease(keep_alive);
+    let lease_expires_at = lease.expires_at;
+    let put_lease_request = PutPointInTimeLeaseRequest { lease: Some(lease) };
+    metastore.put_point_in_time_lease(put_lease_request).await?;
+    Ok(lease_expires_at)
+}
+
+/// Returns whether extending a lease expiring at `lease_expires_at` by `keep_alive` from now is
+/// worth a metastore write.
+fn should_extend_lease(lease_expires_at: i64, keep_alive: Duration) -> bool {
+    let new_lease_expires_at = now_millis() / 1_000 + keep_alive.as_secs() as i64;
+    let min_extension_secs = (keep_alive.as_secs() / LEASE_EXTENSION_MIN_RATIO).max(1) as i64;
+    new_lease_expires_at >= lease_expires_at + min_extension_secs
+}
+
+/// Opens a point in time, pinning the splits currently published in the targeted indexes.
+pub(crate) async fn root_open_point_in_time(
+    open_request: OpenPointInTimeRequest,
+    mut metastore: MetastoreServiceClient,
+    cluster_client: &ClusterClient,
+) -> crate::Result<OpenPointInTimeResponse> {
+    if open_request.index_id_patterns.is_empty() {
+        return Err(SearchError::InvalidArgument(
+            "a point in time must target at least one index".to_string(),
+        ));
+    }
+    let keep_alive = get_keep_alive_duration(open_request.keep_alive_secs)?;
+    let resolved_indexes =
+        resolve_indexes_and_aliases(&mut metastore, &open_request.index_id_patterns).await?;
+    // The filters of the aliases are not recorded in the point in time, and subsequent searches
+    // would silently ignore them.
+    if resolved_indexes.alias_filter_opt.is_some() {
+        return Err(SearchError::InvalidArgument(
+            "a point in time cannot be opened on a filtered index alias".to_string(),
+        ));
+    }
+    let index_uids: Vec<IndexUid> = resolved_indexes
+        .indexes_metadata
+        .into_iter()
+        .map(|index_metadata| index_metadata.index_uid)
+        .collect();
+    // The ID, which records the opening time, is created before listing the splits: the pinned
+    // splits that stop being published are therefore all marked for deletion after that time. The
+    // lease is recorded before listing the splits as well, so that the janitor cannot delete any of
+    // them in the meantime.
+    let pit_id = PointInTimeId::new(index_uids.clone());
+    let lease_expires_at = put_point_in_time_lease(&pit_id, keep_alive, &mut metastore).await?;
+    let split_metadatas = list_all_splits(index_uids.clone(), &mut metastore).await?;
+    let point_in_time_context = PointInTimeContext {
+        index_uids,
+        split_metadatas,
+        lease_expires_at,
+    };
+    put_point_in_time_context(&pit_id, &point_in_time_context, keep_alive, cluster_client).await;
+    Ok(OpenPointInTimeResponse {
+        pit_id: pit_id.to_string(),
+    })
+}
+
+/// Opens a point in time pinning splits already listed by the caller, and returns its ID.
+///
+/// The lease is recorded after the splits were listed, so the caller must have listed them
+/// shortly before, well within the split deletion grace period.
+pub(crate) async fn open_point_in_time_on_splits(
+    index_uids: Vec<IndexUid>,
+    split_metadatas: Vec<SplitMetadata>,
+    keep_alive: Duration,
+    metastore: &mut MetastoreServiceClient,
+    cluster_client: &ClusterClient,
+) -> crate::Result<String> {
+    let pit_id = PointInTimeId::new(index_uids.clone());
+    let lease_expires_at = put_point_in_time_lease(&pit_id, keep_alive, metastore).await?;
+    let point_in_time_context = PointInTimeContext {
+        index_uids,
+        split_metadatas,
+        lease_expires_at,
+    };
+    put_point_in_time_context(&pit_id, &point_in_time_context, keep_alive, cluster_client).await;
+    Ok(pit_id.to_string())
+}
+
+async fn put_point_in_time_context(
+    pit_id: &PointInTimeId,
+    point_in_time_context: &PointInTimeContext,
+    keep_alive: Duration,
+    cluster_client: &ClusterClient,
+) {
+    let payload = point_in_time_context.serialize();
+    cluster_client
+        .put_kv(&pit_id.pit_key(), &payload, keep_alive)
+        .await;
+}
+
+/// Loads the context of a point in time, extending its lifetime and its lease if a keep alive is
+/// specified and the lease would be extended significantly.
+pub(crate) async fn load_point_in_time_context(
+    point_in_time: &PointInTime,
+    metastore: &mut MetastoreServiceClient,
+    cluster_client: &ClusterClient,
+) -> crate::Result<PointInTimeContext> {
+    let pit_id = parse_point_in_time_id(&point_in_time.pit_id)?;
+    let keep_alive_opt = point_in_time
+        .keep_alive_secs
+        .map(get_keep_alive_duration)
+        .transpose()?;
+    let pit_key = pit_id.pit_key();
+    let payload = cluster_client
+        .get_kv(&pit_key)
+        .await
+        .filter(|payload| !payload.is_empty())
+        .ok_or_else(|| {
+            SearchError::InvalidArgument("point in time not found or expired".to_string())
+        })?;
+    let mut point_in_time_context = PointInTimeContext::load(&payload)
+        .map_err(|_| SearchError::Internal("corrupted point in time context".to_string()))?;
+    if let Some(keep_alive) = keep_alive_opt
+        && should_extend_lease(point_in_time_context.lease_expires_at, keep_alive)
+    {
+        point_in_time_context.lease_expires_at =
+            put_point_in_time_lease(&pit_id, keep_alive, metastore).await?;
+        put_point_in_time_context(&pit_id, &point_in_time_context, keep_alive, cluster_client)
+            .await;
+    }
+    Ok(point_in_time_context)
+}
+
+/// Closes a point in time and releases its lease.
+///
+/// The KV store has no delete operation, so the context is overwritten with an empty payload that
+/// expires shortly after.
+pub(crate) async fn root_close_point_in_time(
+    close_request: ClosePointInTimeRequest,
+    mut metastore: MetastoreServiceClient,
+    cluster_client: &ClusterClient,
+) -> crate::Result<ClosePointInTimeResponse> {
+    let pit_id = parse_point_in_time_id(&close_request.pit_id)?;
+    let delete_lease_request = DeletePointInTimeLeaseRequest {
+        lease_id: pit_id.lease_id(),
+    };
+    metastore
+        .delete_point_in_time_lease(delete_lease_request)
+        .await?;
+    let pit_key = pit_id.pit_key();
+    let is_live = cluster_client
+        .get_kv(&pit_key)
+        .await
+        .filter(|payload| !payload.is_empty())
+        .is_some();
+    if !is_live {
+        return Ok(ClosePointInTimeResponse { num_freed: 0 });
+    }
+    cluster_client
+        .put_kv(&pit_key, &[], CLOSED_POINT_IN_TIME_TTL)
+        .await;
+    Ok(ClosePointInTimeResponse { num_freed: 1 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_in_time_id_roundtrip() {
+        let index_uids = vec![
+            IndexUid::for_test("index-1", 0),
+            IndexUid::for_test("index-2", 0),
+        ];
+        let pit_id = PointInTimeId::new(index_uids.clone());
+        let pit_id_str = pit_id.to_string();
+        let parsed_pit_id = PointInTimeId::from_str(&pit_id_str).unwrap();
+        assert_eq!(parsed_pit_id, pit_id);
+        assert_eq!(parsed_pit_id.index_uids, index_uids);
+        assert_eq!(parsed_pit_id.lease_id(), pit_id.pit_ulid.to_string());
+
+        assert!(PointInTimeId::from_str("not base64!").is_err());
+        assert!(PointInTimeId::from_str(&BASE64_STANDARD.encode("{}")).is_err());
+    }
+
+    #[test]
+    fn test_point_in_time_lease() {
+        let index_uids = vec![IndexUid::for_test("index-1", 0)];
+        let pit_id = PointInTimeId::new(index_uids.clone());
+        let lease = pit_id.lease(Duration::from_secs(60));
+        assert_eq!(lease.lease_id, pit_id.pit_ulid.to_string());
+        assert_eq!(lease.index_uids, index_uids);
+        assert!(lease.expires_at >= lease.opened_at + 60);
+    }
+
+    #[test]
+    fn test_point_in_time_context_into_relevant_splits() {
+        let index_uid_1 = IndexUid::for_test("index-1", 0);
+        let index_uid_2 = IndexUid::for_test("index-2", 0);
+        let split_metadata = |split_id: &str, index_uid: &IndexUid, time_range| SplitMetadata {
+            split_id: split_id.to_string(),
+            index_uid: index_uid.clone(),
+            time_range,
+            ..Default::default()
+        };
+        let point_in_time_context = || PointInTimeContext {
+            index_uids: vec![index_uid_1.clone(), index_uid_2.clone()],
+            split_metadatas: vec![
+                split_metadata("split-1", &index_uid_1, Some(0..=10)),
+                split_metadata("split-2", &index_uid_1, Some(20..=30)),
+                split_metadata("split-3", &index_uid_1, None),
+                split_metadata("split-4", &index_uid_2, Some(0..=10)),
+            ],
+            lease_expires_at: 0,
+        };
+        let relevant_split_ids = |split_metadatas: Vec<SplitMetadata>| {
+            split_metadatas
+                .into_iter()
+                .map(|split_metadata| split_metadata.split_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            relevant_split_ids(point_in_time_context().into_relevant_splits(
+                &[index_uid_1.clone(), index_uid_2.clone()],
+                None,
+                None
+            )),
+            ["split-1", "split-2", "split-3", "split-4"]
+        );
+        assert_eq!(
+            relevant_split_ids(point_in_time_context().into_relevant_splits(
+                &[index_uid_1.clone()],
+                Some(10),
+                Some(20)
+            )),
+            ["split-1", "split-3"]
+        );
+        assert_eq!(
+            relevant_split_ids(point_in_time_context().into_relevant_splits(
+                &[index_uid_1.clone()],
+                Some(11),
+                None
+            )),
+            ["split-2", "split-3"]
+        );
+    }
+
+    #[test]
+    fn test_point_in_time_context_index_ids() {
+        let point_in_time_context = PointInTimeContext {
+            index_uids: vec![
+                IndexUid::for_test("index-1", 0),
+                IndexUid::for_test("index-2", 0),
+            ],
+            split_metadatas: Vec::new(),
+            lease_expires_at: 0,
+        };
+        assert_eq!(point_in_time_context.index_ids(), ["index-1", "index-2"]);
+    }
+
+    #[test]
+    fn test_should_extend_lease() {
+        let now = now_millis() / 1_000;
+        let keep_alive = Duration::from_secs(600);
+        assert!(should_extend_lease(0, keep_alive));
+        assert!(should_extend_lease(now + 400, keep_alive));
+        assert!(!should_extend_lease(now + 590, keep_alive));
+        assert!(!should_extend_lease(now + 3_600, keep_alive));
+    }
+
+    #[test]
+    fn test_get_keep_alive_duration() {
+        assert_eq!(
+            get_keep_alive_duration(60).unwrap(),
+            Duration::from_secs(60)
+        );
+        assert!(get_keep_alive_duration(0).is_err());
+        assert!(get_keep_alive_duration(24 * 3_600 + 1).is_err());
+    }
+}
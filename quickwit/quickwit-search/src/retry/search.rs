@@ -33,7 +33,8 @@ impl RetryPolicy<LeafSearchRequest, LeafSearchResponse, SearchError> for LeafSea
     ) -> Option<LeafSearchRequest> {
         match response_res {
             Ok(response) => {
-                if response.failed_splits.is_empty() {
+                // Retrying a search that timed out would exceed its deadline.
+                if response.failed_splits.is_empty() || response.timed_out {
                     return None;
                 }
                 let failed_splits_hash_set: HashSet<&str> = response
@@ -54,6 +55,9 @@ impl RetryPolicy<LeafSearchRequest, LeafSearchResponse, SearchError> for LeafSea
                 Some(request)
             }
             Err(SearchError::Timeout(_)) => None, // Don't retry on timeout
+            Err(SearchError::Cancelled(_)) => None,
+            // The tenant is over its queue limit, retrying would only add to the load.
+            Err(SearchError::TooManyRequests) => None,
             Err(_) => Some(request),
         }
     }
@@ -104,6 +108,8 @@ mod tests {
                     },
                 ],
             }],
+            timeout_millis: None,
+            search_task_id: String::new(),
         }
     }
 
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::collections::{HashMap, HashSet};
+use std::ops::Bound;
 use std::sync::OnceLock;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
@@ -23,21 +24,26 @@ use itertools::Itertools;
 use quickwit_common::pretty::PrettySample;
 use quickwit_common::shared_consts;
 use quickwit_common::uri::Uri;
-use quickwit_config::build_doc_mapper;
+use quickwit_config::{IndexAlias, build_doc_mapper};
 use quickwit_doc_mapper::DYNAMIC_FIELD_NAME;
 use quickwit_doc_mapper::tag_pruning::extract_tags_from_query;
-use quickwit_metastore::{IndexMetadata, ListIndexesMetadataResponseExt, SplitMetadata};
+use quickwit_metastore::{
+    IndexMetadata, ListIndexAliasesResponseExt, ListIndexesMetadataResponseExt, SplitMetadata,
+    split_time_range_filter,
+};
 use quickwit_proto::metastore::{
-    ListIndexesMetadataRequest, MetastoreService, MetastoreServiceClient,
+    ListIndexAliasesRequest, ListIndexesMetadataRequest, MetastoreService, MetastoreServiceClient,
 };
 use quickwit_proto::search::{
-    FetchDocsRequest, FetchDocsResponse, Hit, LeafHit, LeafRequestRef, LeafSearchRequest,
-    LeafSearchResponse, PartialHit, SearchPlanResponse, SearchRequest, SearchResponse,
-    SnippetRequest, SortDatetimeFormat, SortField, SortValue, SplitIdAndFooterOffsets,
+    CollapseRequest, CountHits, FetchDocsRequest, FetchDocsResponse, Hit, InnerHits, LeafHit,
+    LeafRequestRef, LeafSearchRequest, LeafSearchResponse, PartialHit, SearchPlanResponse,
+    SearchRequest, SearchResponse, SearchTask, SnippetRequest, SortDatetimeFormat, SortField,
+    SortValue, SourceFilter, SplitIdAndFooterOffsets,
 };
 use quickwit_proto::types::{IndexUid, SplitId};
+use quickwit_query::JsonLiteral;
 use quickwit_query::query_ast::{
-    BoolQuery, QueryAst, QueryAstVisitor, RangeQuery, TermQuery, TermSetQuery,
+    BoolQuery, FieldPresenceQuery, QueryAst, QueryAstVisitor, RangeQuery, TermQuery, TermSetQuery,
 };
 use serde::{Deserialize, Serialize};
 use tantivy::TantivyError;
@@ -46,19 +52,33 @@ use tantivy::aggregation::intermediate_agg_result::IntermediateAggregationResult
 use tantivy::collector::Collector;
 use tantivy::schema::{Field, FieldEntry, FieldType, Schema};
 use tracing::{debug, info_span, instrument};
+use ulid::Ulid;
 
+use crate::async_search::now_millis;
 use crate::cluster_client::ClusterClient;
 use crate::collector::{QuickwitAggregations, make_merge_collector};
+use crate::composite_aggregation::{CompositeAggregation, is_composite_aggregation_request};
+use crate::knn::{
+    KnnQuery, filtered_query_ast, knn_phase_request, reciprocal_rank_fusion,
+    rrf_query_phase_request, rrf_window_size,
+};
 use crate::metrics_trackers::{RootSearchMetricsFuture, RootSearchMetricsStep};
+use crate::point_in_time::{PointInTimeContext, load_point_in_time_context};
+use crate::root_cache::RootSearchCache;
 use crate::scroll_context::{ScrollContext, ScrollKeyAndStartOffset};
 use crate::search_job_placer::{Job, group_by, group_jobs_by_index_id};
+use crate::search_profile::{SearchProfiler, log_slow_query};
 use crate::search_response_rest::StorageRequestCount;
-use crate::service::SearcherContext;
+use crate::search_task::SearchTaskGuard;
+use crate::service::{SearcherContext, deserialize_doc_mapper};
 use crate::{
     SearchError, SearchJobPlacer, SearchPlanResponseRest, SearchServiceClient,
     extract_split_and_footer_offsets, list_relevant_splits,
 };
 
+/// Same limit as Elasticsearch's default `index.max_inner_result_window`.
+const MAX_INNER_HITS_SIZE: u32 = 100;
+
 /// Maximum accepted scroll TTL.
 fn max_scroll_ttl() -> Duration {
     static MAX_SCROLL_TTL_LOCK: OnceLock<Duration> = OnceLock::new();
@@ -167,6 +187,7 @@ struct RequestMetadata {
     query_ast_resolved: QueryAst,
     indexes_meta_for_leaf_search: IndexesMetasForLeafSearch,
     sort_fields_is_datetime: HashMap<String, bool>,
+    knn_query_resolved_opt: Option<KnnQuery>,
 }
 
 /// Validates request against each index's doc mapper and ensures that:
@@ -191,9 +212,12 @@ fn validate_request_and_build_metadata(
         .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
     let mut indexes_meta_for_leaf_search: HashMap<IndexUid, IndexMetasForLeafSearch> =
         HashMap::new();
+    let mut query_ast_parsed_opt: Option<QueryAst> = None;
     let mut query_ast_resolved_opt: Option<QueryAst> = None;
+    let mut first_index_id_opt: Option<&str> = None;
     let mut timestamp_field_opt: Option<String> = None;
     let mut sort_fields_is_datetime: HashMap<String, bool> = HashMap::new();
+    let mut knn_query_resolved_opt: Option<KnnQuery> = None;
 
     for index_metadata in indexes_metadata {
         let doc_mapper = build_doc_mapper(
@@ -203,23 +227,44 @@ fn validate_request_and_build_metadata(
         .map_err(|err| {
             SearchError::Internal(format!("failed to build doc mapper. cause: {err}"))
         })?;
-        let query_ast_resolved_for_index = query_ast
+        let query_rewriter = index_metadata
+            .index_config
+            .search_settings
+            .query_rewrites
+            .query_rewriter()
+            .map_err(|err| {
+                SearchError::Internal(format!("failed to build query rewriter. cause: {err}"))
+            })?;
+        let query_ast_parsed_for_index = query_ast
             .clone()
             .parse_user_query(doc_mapper.default_search_fields())
             // We convert the error to return a 400 to the user (and not a 500).
             .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
+        let query_ast_resolved_for_index =
+            query_rewriter.rewrite(query_ast_parsed_for_index.clone(), &doc_mapper.schema());
 
         // Validate uniqueness of resolved query AST.
         if let Some(query_ast_resolved) = &query_ast_resolved_opt {
-            if query_ast_resolved != &query_ast_resolved_for_index {
+            if query_ast_parsed_opt.as_ref() != Some(&query_ast_parsed_for_index) {
                 return Err(SearchError::InvalidQuery(
                     "resolved query ASTs must be the same across indexes. resolving queries with \
                      different default fields are different between indexes is not supported"
                         .to_string(),
                 ));
             }
+            if query_ast_resolved != &query_ast_resolved_for_index {
+                return Err(SearchError::InvalidQuery(format!(
+                    "the query rewrites (`search_settings.query_rewrites`) of the indexes `{}` \
+                     and `{}` rewrite the query differently. searching indexes with different \
+                     query rewrites is not supported",
+                    first_index_id_opt.unwrap_or_default(),
+                    index_metadata.index_id(),
+                )));
+            }
         } else {
+            query_ast_parsed_opt = Some(query_ast_parsed_for_index);
             query_ast_resolved_opt = Some(query_ast_resolved_for_index.clone());
+            first_index_id_opt = Some(index_metadata.index_id());
         }
 
         // Validate uniqueness of timestamp field if any.
@@ -251,6 +296,21 @@ fn validate_request_and_build_metadata(
         // Validates the query by effectively building it against the current schema.
         doc_mapper.query(doc_mapper.schema(), &query_ast_resolved_for_index, true)?;
 
+        if let Some(knn_json) = &search_request.knn {
+            let mut knn_query = KnnQuery::parse(knn_json)?;
+            knn_query.validate(&doc_mapper, search_request)?;
+
+            if let Some(filter) = knn_query.filter.take() {
+                let filter_parsed = filter
+                    .parse_user_query(doc_mapper.default_search_fields())
+                    .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
+                let filter_resolved = query_rewriter.rewrite(filter_parsed, &doc_mapper.schema());
+                doc_mapper.query(doc_mapper.schema(), &filter_resolved, true)?;
+                knn_query.filter = Some(filter_resolved);
+            }
+            knn_query_resolved_opt = Some(knn_query);
+        }
+
         let index_metadata_for_leaf_search = IndexMetasForLeafSearch {
             index_uri: index_metadata.index_uri().clone(),
             doc_mapper_str: serde_json::to_string(&doc_mapper).map_err(|err| {
@@ -274,6 +334,7 @@ fn validate_request_and_build_metadata(
         query_ast_resolved,
         indexes_meta_for_leaf_search,
         sort_fields_is_datetime,
+        knn_query_resolved_opt,
     })
 }
 
@@ -360,12 +421,22 @@ fn simplify_search_request_for_scroll_api(req: &SearchRequest) -> crate::Result<
         aggregation_request: None,
         // We remove the snippet fields. This feature is not supported for scroll requests.
         snippet_fields: Vec::new(),
+        snippet_options: None,
+        source_filter: req.source_filter.clone(),
+        point_in_time: None,
         // We remove the scroll ttl parameter. It is irrelevant to process later request
         scroll_ttl_secs: None,
         search_after: None,
         // request is simplified after initial query, and we cache the hit count, so we don't need
         // to recompute it afterward.
         count_hits: quickwit_proto::search::CountHits::Underestimate as i32,
+        timeout_millis: None,
+        tenant_id: req.tenant_id.clone(),
+        // Scroll requests cannot collapse hits.
+        collapse: None,
+        profile: false,
+        // kNN searches cannot be scrolled.
+        knn: None,
     })
 }
 
@@ -491,6 +562,47 @@ fn check_is_fast_field(
     Ok(())
 }
 
+fn validate_collapse(
+    schema: &Schema,
+    collapse: &CollapseRequest,
+    search_request: &SearchRequest,
+) -> crate::Result<()> {
+    if search_request.scroll_ttl_secs.is_some() {
+        return Err(SearchError::InvalidArgument(
+            "collapse cannot be used with scroll".to_string(),
+        ));
+    }
+    if search_request.search_after.is_some() {
+        return Err(SearchError::InvalidArgument(
+            "collapse cannot be used with search after".to_string(),
+        ));
+    }
+    if collapse.inner_hits_size > MAX_INNER_HITS_SIZE {
+        return Err(SearchError::InvalidArgument(format!(
+            "max value for collapse inner hits size is {MAX_INNER_HITS_SIZE}, but got {}",
+            collapse.inner_hits_size
+        )));
+    }
+    let dynamic_field = schema.get_field(DYNAMIC_FIELD_NAME).ok();
+    check_is_fast_field(schema, &collapse.field, dynamic_field)?;
+
+    let (field, _path) = schema
+        .find_field_with_default(&collapse.field, dynamic_field)
+        .expect("field should exist");
+    match schema.get_field_entry(field).field_type() {
+        FieldType::Str(_)
+        | FieldType::U64(_)
+        | FieldType::I64(_)
+        | FieldType::F64(_)
+        | FieldType::Bool(_)
+        | FieldType::JsonObject(_) => Ok(()),
+        _ => Err(SearchError::InvalidArgument(format!(
+            "collapse field `{}` must be a text, numeric or boolean field",
+            collapse.field
+        ))),
+    }
+}
+
 fn validate_request(
     schema: &Schema,
     timestamp_field_name: &Option<&str>,
@@ -506,12 +618,23 @@ fn validate_request(
         )));
     }
 
-    validate_requested_snippet_fields(schema, &search_request.snippet_fields)?;
+    let is_lenient_snippet_fields = search_request
+        .snippet_options
+        .as_ref()
+        .map(|snippet_options| snippet_options.lenient_fields)
+        .unwrap_or_default();
+    if !is_lenient_snippet_fields {
+        validate_requested_snippet_fields(schema, &search_request.snippet_fields)?;
+    }
 
     if let Some(agg) = search_request.aggregation_request.as_ref() {
         let aggs: QuickwitAggregations = serde_json::from_str(agg).map_err(|_err| {
-            let err = serde_json::from_str::<tantivy::aggregation::agg_req::Aggregations>(agg)
-                .unwrap_err();
+            let err = if is_composite_aggregation_request(agg) {
+                serde_json::from_str::<CompositeAggregation>(agg).unwrap_err()
+            } else {
+                serde_json::from_str::<tantivy::aggregation::agg_req::Aggregations>(agg)
+                    .unwrap_err()
+            };
             SearchError::InvalidAggregationRequest(err.to_string())
         })?;
 
@@ -523,6 +646,16 @@ fn validate_request(
         }
     };
 
+    if search_request.point_in_time.is_some() && search_request.scroll_ttl_secs.is_some() {
+        return Err(SearchError::InvalidArgument(
+            "scroll cannot be used with a point in time".to_string(),
+        ));
+    }
+
+    if let Some(collapse) = &search_request.collapse {
+        validate_collapse(schema, collapse, search_request)?;
+    }
+
     if search_request.start_offset > 10_000 {
         return Err(SearchError::InvalidArgument(format!(
             "max value for start_offset is 10_000, but got {}",
@@ -562,6 +695,8 @@ async fn search_partial_hits_phase_with_scroll(
     mut search_request: SearchRequest,
     split_metadatas: &[SplitMetadata],
     cluster_client: &ClusterClient,
+    search_task_guard_opt: Option<&SearchTaskGuard>,
+    search_profiler_opt: Option<&SearchProfiler>,
 ) -> crate::Result<(LeafSearchResponse, Option<ScrollKeyAndStartOffset>)> {
     let scroll_ttl_opt = get_scroll_ttl_duration(&search_request)?;
 
@@ -580,6 +715,8 @@ async fn search_partial_hits_phase_with_scroll(
             &search_request,
             split_metadatas,
             cluster_client,
+            search_task_guard_opt,
+            search_profiler_opt,
         )
         .await?;
         let cached_partial_hits = leaf_search_resp.partial_hits.clone();
@@ -625,6 +762,8 @@ async fn search_partial_hits_phase_with_scroll(
             &search_request,
             split_metadatas,
             cluster_client,
+            search_task_guard_opt,
+            search_profiler_opt,
         )
         .await?;
         Ok((leaf_search_resp, None))
@@ -653,6 +792,9 @@ pub fn is_metadata_count_request_with_ast(query_ast: &QueryAst, request: &Search
     if request.max_hits != 0 {
         return false;
     }
+    if request.knn.is_some() {
+        return false;
+    }
 
     // If the start and end timestamp encompass the whole split, it is still a count query.
     // We remove this currently on the leaf level, but not yet on the root level.
@@ -680,6 +822,8 @@ pub fn get_count_from_metadata(split_metadatas: &[SplitMetadata]) -> Vec<LeafSea
             num_successful_splits: 1,
             intermediate_aggregation_result: None,
             resource_stats: None,
+            timed_out: false,
+            collapse_groups: Vec::new(),
         })
         .collect()
 }
@@ -721,6 +865,11 @@ fn is_top_5pct_memory_intensive(num_bytes: u64, split_num_docs: u64) -> bool {
 
 /// If this method fails for some splits, a partial search response is returned, with the list of
 /// faulty splits in the failed_splits field.
+///
+/// When a search task is provided, the leaf requests are tagged with its ID and its remaining
+/// time, so that leaf searches can be cancelled and stop at the deadline.
+///
+/// When a search profiler is provided, the leaf search responses are recorded into it.
 #[instrument(level = "debug", skip_all)]
 pub(crate) async fn search_partial_hits_phase(
     searcher_context: &SearcherContext,
@@ -728,6 +877,8 @@ pub(crate) async fn search_partial_hits_phase(
     search_request: &SearchRequest,
     split_metadatas: &[SplitMetadata],
     cluster_client: &ClusterClient,
+    search_task_guard_opt: Option<&SearchTaskGuard>,
+    search_profiler_opt: Option<&SearchProfiler>,
 ) -> crate::Result<LeafSearchResponse> {
     let leaf_search_responses: Vec<LeafSearchResponse> =
         if is_metadata_count_request(search_request) {
@@ -740,12 +891,34 @@ pub(crate) async fn search_partial_hits_phase(
                 .await?;
             let mut leaf_request_tasks = Vec::new();
             for (client, client_jobs) in assigned_leaf_search_jobs {
-                let leaf_request = jobs_to_leaf_request(
+                let leaf_addr = client.grpc_addr();
+                let split_ids: Vec<String> = client_jobs
+                    .iter()
+                    .map(|job| job.offsets.split_id.clone())
+                    .collect();
+                let mut leaf_request = jobs_to_leaf_request(
                     search_request,
                     indexes_metas_for_leaf_search,
                     client_jobs,
                 )?;
-                leaf_request_tasks.push(cluster_client.leaf_search(leaf_request, client.clone()));
+                if let Some(search_task_guard) = search_task_guard_opt {
+                    search_task_guard.prepare_leaf_request(&mut leaf_request);
+                }
+                let leaf_search_future = cluster_client.leaf_search(leaf_request, client.clone());
+                leaf_request_tasks.push(async move {
+                    let leaf_search_start = Instant::now();
+                    let leaf_search_response = leaf_search_future.await?;
+
+                    if let Some(search_profiler) = search_profiler_opt {
+                        search_profiler.record_leaf_search(
+                            leaf_addr,
+                            &split_ids,
+                            leaf_search_start.elapsed(),
+                            &leaf_search_response,
+                        );
+                    }
+                    crate::Result::Ok(leaf_search_response)
+                });
             }
             try_join_all(leaf_request_tasks).await?
         };
@@ -794,6 +967,116 @@ pub(crate) async fn search_partial_hits_phase(
     Ok(leaf_search_response)
 }
 
+/// Searches the settled splits and the fresh splits separately, so that the merged partial hits
+/// of the settled splits can be served from the root search cache by the next searches. Only the
+/// fresh splits are searched when the partial hits of the settled splits are cached.
+///
+/// The settled splits are cached for the part of the time range aligned on the time granularity
+/// of the cache only, and searched with the exact bounds of the request on the edges of the time
+/// range, so that the searches over a sliding window share their cache entries.
+#[allow(clippy::too_many_arguments)]
+async fn cached_search_partial_hits_phase(
+    root_search_cache: &RootSearchCache,
+    searcher_context: &SearcherContext,
+    indexes_metas_for_leaf_search: &IndexesMetasForLeafSearch,
+    search_request: &SearchRequest,
+    split_metadatas: &[SplitMetadata],
+    cluster_client: &ClusterClient,
+    search_task_guard_opt: Option<&SearchTaskGuard>,
+    search_profiler_opt: Option<&SearchProfiler>,
+) -> crate::Result<LeafSearchResponse> {
+    let (settled_splits, fresh_splits) =
+        root_search_cache.partition_splits(split_metadatas, now_millis() / 1_000);
+    // The partial hits of the settled and fresh splits are merged a second time, so they must
+    // include the hits skipped by the start offset.
+    let mut partial_hits_request = search_request.clone();
+    partial_hits_request.max_hits += partial_hits_request.start_offset;
+    partial_hits_request.start_offset = 0;
+
+    let search_splits = |search_request: SearchRequest, splits: Vec<SplitMetadata>| async move {
+        search_partial_hits_phase(
+            searcher_context,
+            indexes_metas_for_leaf_search,
+            &search_request,
+            &splits,
+            cluster_client,
+            search_task_guard_opt,
+            search_profiler_opt,
+        )
+        .await
+    };
+    let mut leaf_search_responses = Vec::with_capacity(4);
+
+    if !settled_splits.is_empty() {
+        if let Some((aligned_request, edge_requests)) =
+            root_search_cache.split_time_range(&partial_hits_request)
+        {
+            let aligned_splits = splits_in_time_range(&settled_splits, &aligned_request);
+
+            if !aligned_splits.is_empty() {
+                let aligned_response = if let Some(cached_response) =
+                    root_search_cache.get_partial_hits(&aligned_request, &aligned_splits)
+                {
+                    cached_response
+                } else {
+                    let aligned_response =
+                        search_splits(aligned_request.clone(), aligned_splits.clone()).await?;
+                    if aligned_response.failed_splits.is_empty() && !aligned_response.timed_out {
+                        root_search_cache.put_partial_hits(
+                            &aligned_request,
+                            &aligned_splits,
+                            &aligned_response,
+                        );
+                    }
+                    aligned_response
+                };
+                leaf_search_responses.push(aligned_response);
+            }
+            for edge_request in edge_requests {
+                let edge_splits = splits_in_time_range(&settled_splits, &edge_request);
+
+                if !edge_splits.is_empty() {
+                    leaf_search_responses.push(search_splits(edge_request, edge_splits).await?);
+                }
+            }
+        } else {
+            leaf_search_responses
+                .push(search_splits(partial_hits_request.clone(), settled_splits).await?);
+        }
+    }
+    if !fresh_splits.is_empty() || leaf_search_responses.is_empty() {
+        leaf_search_responses.push(search_splits(partial_hits_request, fresh_splits).await?);
+    }
+    let merge_collector =
+        make_merge_collector(search_request, &searcher_context.get_aggregation_limits())?;
+    let leaf_search_results: Vec<tantivy::Result<LeafSearchResponse>> =
+        leaf_search_responses.into_iter().map(Ok).collect_vec();
+    let span = info_span!("merge_fruits");
+    let leaf_search_response = crate::search_thread_pool()
+        .run_cpu_intensive(move || {
+            let _span_guard = span.enter();
+            merge_collector.merge_fruits(leaf_search_results)
+        })
+        .await
+        .context("failed to merge leaf search responses")?
+        .map_err(|error: TantivyError| crate::SearchError::Internal(error.to_string()))?;
+    Ok(leaf_search_response)
+}
+
+/// Returns the splits whose time range overlaps the time range of the search request.
+fn splits_in_time_range(
+    split_metadatas: &[SplitMetadata],
+    search_request: &SearchRequest,
+) -> Vec<SplitMetadata> {
+    let time_range = search_request.start_timestamp.unwrap_or(i64::MIN)
+        ..search_request.end_timestamp.unwrap_or(i64::MAX);
+    split_metadatas
+        .iter()
+        .filter(|split_metadata| split_time_range_filter(split_metadata, Some(&time_range)))
+        .cloned()
+        .collect()
+}
+
 pub(crate) fn get_snippet_request(search_request: &SearchRequest) -> Option<SnippetRequest> {
     if search_request.snippet_fields.is_empty() {
         return None;
@@ -801,6 +1084,7 @@ pub(crate) fn get_snippet_request(search_request: &SearchRequest) -> Option<Snip
     Some(SnippetRequest {
         snippet_fields: search_request.snippet_fields.clone(),
         query_ast_resolved: search_request.query_ast.clone(),
+        snippet_options: search_request.snippet_options.clone(),
     })
 }
 
@@ -811,6 +1095,7 @@ pub(crate) async fn fetch_docs_phase(
     split_metadatas: &[SplitMetadata],
     search_request: &SearchRequest,
     cluster_client: &ClusterClient,
+    search_profiler_opt: Option<&SearchProfiler>,
 ) -> crate::Result<Vec<Hit>> {
     let snippet_request: Option<SnippetRequest> = get_snippet_request(search_request);
     let hit_order: HashMap<(String, u32, u32), usize> = partial_hits
@@ -837,11 +1122,23 @@ pub(crate) async fn fetch_docs_phase(
     for (client, client_jobs) in assigned_fetch_docs_jobs {
         let fetch_jobs_requests = jobs_to_fetch_docs_requests(
             snippet_request.clone(),
+            search_request.source_filter.clone(),
             indexes_metas_for_leaf_search,
             client_jobs,
         )?;
+        let leaf_addr = client.grpc_addr();
+
         for fetch_docs_request in fetch_jobs_requests {
-            fetch_docs_tasks.push(cluster_client.fetch_docs(fetch_docs_request, client.clone()));
+            let fetch_docs_future = cluster_client.fetch_docs(fetch_docs_request, client.clone());
+            fetch_docs_tasks.push(async move {
+                let fetch_docs_start = Instant::now();
+                let fetch_docs_response = fetch_docs_future.await?;
+
+                if let Some(search_profiler) = search_profiler_opt {
+                    search_profiler.record_fetch_docs(leaf_addr, fetch_docs_start.elapsed());
+                }
+                crate::Result::Ok(fetch_docs_response)
+            });
         }
     }
     let fetch_docs_responses: Vec<FetchDocsResponse> = try_join_all(fetch_docs_tasks).await?;
@@ -935,6 +1232,8 @@ fn build_hit_with_position(
             partial_hit: leaf_hit.partial_hit,
             snippet: leaf_hit.leaf_snippet_json,
             index_id,
+            inner_hits: None,
+            source_disabled: leaf_hit.source_disabled,
         },
     ))
 }
@@ -965,29 +1264,93 @@ async fn root_search_aux(
     search_request: SearchRequest,
     split_metadatas: Vec<SplitMetadata>,
     cluster_client: &ClusterClient,
+    search_task_guard_opt: Option<&SearchTaskGuard>,
+    search_profiler_opt: Option<&SearchProfiler>,
 ) -> crate::Result<SearchResponse> {
     debug!(split_metadatas = ?PrettySample::new(&split_metadatas, 5));
+    let root_search_cache_opt = searcher_context
+        .root_search_cache
+        .as_ref()
+        .filter(|_| RootSearchCache::is_cacheable(&search_request));
+    if let Some(root_search_cache) = root_search_cache_opt
+        && let Some(search_response) =
+            root_search_cache.get_response(&search_request, &split_metadatas)
+    {
+        return Ok(search_response);
+    }
+    let knn_query_opt = search_request
+        .knn
+        .as_deref()
+        .map(KnnQuery::parse)
+        .transpose()?;
     let (first_phase_result, scroll_key_and_start_offset_opt): (
         LeafSearchResponse,
         Option<ScrollKeyAndStartOffset>,
-    ) = search_partial_hits_phase_with_scroll(
-        searcher_context,
-        indexes_metas_for_leaf_search,
-        search_request.clone(),
-        &split_metadatas[..],
-        cluster_client,
-    )
-    .await?;
+    ) = if let Some(knn_query) = &knn_query_opt {
+        let knn_phase_result = knn_search_partial_hits_phase(
+            searcher_context,
+            indexes_metas_for_leaf_search,
+            &search_request,
+            knn_query,
+            &split_metadatas[..],
+            cluster_client,
+            search_task_guard_opt,
+            search_profiler_opt,
+        )
+        .await?;
+        (knn_phase_result, None)
+    } else if let Some(root_search_cache) = root_search_cache_opt {
+        let first_phase_result = cached_search_partial_hits_phase(
+            root_search_cache,
+            searcher_context,
+            indexes_metas_for_leaf_search,
+            &search_request,
+            &split_metadatas[..],
+            cluster_client,
+            search_task_guard_opt,
+            search_profiler_opt,
+        )
+        .await?;
+        (first_phase_result, None)
+    } else {
+        search_partial_hits_phase_with_scroll(
+            searcher_context,
+            indexes_metas_for_leaf_search,
+            search_request.clone(),
+            &split_metadatas[..],
+            cluster_client,
+            search_task_guard_opt,
+            search_profiler_opt,
+        )
+        .await?
+    };
 
-    let hits = fetch_docs_phase(
+    let mut hits = fetch_docs_phase(
         indexes_metas_for_leaf_search,
         &first_phase_result.partial_hits,
         &split_metadatas[..],
         &search_request,
         cluster_client,
+        search_profiler_opt,
     )
     .await?;
 
+    if let Some(collapse) = &search_request.collapse
+        && collapse.inner_hits_size > 0
+    {
+        fetch_inner_hits_phase(
+            searcher_context,
+            indexes_metas_for_leaf_search,
+            &mut hits,
+            &split_metadatas[..],
+            &search_request,
+            collapse,
+            cluster_client,
+            search_task_guard_opt,
+        )
+        .await?;
+    }
+
     let mut aggregation_result_postcard_opt = finalize_aggregation_if_any(
         &search_request,
         first_phase_result.intermediate_aggregation_result,
@@ -998,7 +1361,7 @@ async fn root_search_aux(
         aggregation_result_postcard_opt = None;
     }
 
-    Ok(SearchResponse {
+    let search_response = SearchResponse {
         aggregation_postcard: aggregation_result_postcard_opt,
         num_hits: first_phase_result.num_hits,
         hits,
@@ -1009,9 +1372,266 @@ async fn root_search_aux(
             .map(ToString::to_string),
         failed_splits: first_phase_result.failed_splits,
         num_successful_splits: first_phase_result.num_successful_splits,
+        timed_out: first_phase_result.timed_out,
+        profile: None,
+    };
+    if let Some(root_search_cache) = root_search_cache_opt
+        && search_response.failed_splits.is_empty()
+        && !search_response.timed_out
+    {
+        root_search_cache.put_response(&search_request, &split_metadatas, &search_response);
+    }
+    Ok(search_response)
+}
+
+/// Searches the nearest neighbors of a kNN search, or, for a hybrid search, the hits of the query
+/// and the nearest neighbors, fused with reciprocal rank fusion.
+#[allow(clippy::too_many_arguments)]
+async fn knn_search_partial_hits_phase(
+    searcher_context: &SearcherContext,
+    indexes_metas_for_leaf_search: &IndexesMetasForLeafSearch,
+    search_request: &SearchRequest,
+    knn_query: &KnnQuery,
+    split_metadatas: &[SplitMetadata],
+    cluster_client: &ClusterClient,
+    search_task_guard_opt: Option<&SearchTaskGuard>,
+    search_profiler_opt: Option<&SearchProfiler>,
+) -> crate::Result<LeafSearchResponse> {
+    let query_ast: QueryAst = serde_json::from_str(&search_request.query_ast)
+        .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
+
+    if knn_query.rrf.is_none() {
+        let knn_request = knn_phase_request(
+            search_request,
+            knn_query,
+            filtered_query_ast(query_ast, knn_query),
+            search_request.start_offset,
+            search_request.max_hits,
+        )?;
+        let mut knn_phase_result = search_partial_hits_phase(
+            searcher_context,
+            indexes_metas_for_leaf_search,
+            &knn_request,
+            split_metadatas,
+            cluster_client,
+            search_task_guard_opt,
+            search_profiler_opt,
+        )
+        .await?;
+        knn_phase_result.num_hits = knn_phase_result.num_hits.min(knn_query.k);
+        return Ok(knn_phase_result);
+    }
+    let window_size = rrf_window_size(search_request, knn_query);
+    let query_request = rrf_query_phase_request(search_request, window_size);
+    let knn_request = knn_phase_request(
+        search_request,
+        knn_query,
+        knn_query.filter.clone().unwrap_or(QueryAst::MatchAll),
+        0,
+        knn_query.k,
+    )?;
+    let (query_phase_result, knn_phase_result) = futures::try_join!(
+        search_partial_hits_phase(
+            searcher_context,
+            indexes_metas_for_leaf_search,
+            &query_request,
+            split_metadatas,
+            cluster_client,
+            search_task_guard_opt,
+            search_profiler_opt,
+        ),
+        search_partial_hits_phase(
+            searcher_context,
+            indexes_metas_for_leaf_search,
+            &knn_request,
+            split_metadatas,
+            cluster_client,
+            search_task_guard_opt,
+            search_profiler_opt,
+        ),
+    )?;
+    Ok(reciprocal_rank_fusion(
+        vec![query_phase_result, knn_phase_result],
+        knn_query,
+        search_request.start_offset,
+        search_request.max_hits,
+    ))
+}
+
+fn hit_collapse_key(hit: &Hit) -> Option<String> {
+    hit.partial_hit
+        .as_ref()
+        .and_then(|partial_hit| partial_hit.collapse_key.clone())
+}
+
+/// Returns whether the collapse field is a boolean field in one of the searched indexes.
+fn is_bool_collapse_field(
+    indexes_metas_for_leaf_search: &IndexesMetasForLeafSearch,
+    collapse_field: &str,
+) -> crate::Result<bool> {
+    for index_metas in indexes_metas_for_leaf_search.values() {
+        let doc_mapper = deserialize_doc_mapper(&index_metas.doc_mapper_str)?;
+        let schema = doc_mapper.schema();
+
+        if let Ok(field) = schema.get_field(collapse_field)
+            && matches!(
+                schema.get_field_entry(field).field_type(),
+                FieldType::Bool(_)
+            )
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Returns the filter matching the documents of the groups of the given collapse keys.
+///
+/// Documents are matched on the value of the fast field, as the collapse keys are, rather than on
+/// the terms of the inverted index, which may be tokenized differently or not exist at all. Range
+/// queries are not supported on boolean fields, which are not tokenized, so a term query is used
+/// instead.
+fn collapse_keys_filter(
+    collapse: &CollapseRequest,
+    collapse_keys: &[Option<String>],
+    is_bool_field: bool,
+) -> QueryAst {
+    let mut filter = BoolQuery::default();
+
+    for collapse_key_opt in collapse_keys {
+        let group_filter: QueryAst = match collapse_key_opt {
+            Some(collapse_key) if is_bool_field => TermQuery {
+                field: collapse.field.clone(),
+                value: collapse_key.clone(),
+            }
+            .into(),
+            Some(collapse_key) => {
+                let collapse_value = JsonLiteral::String(collapse_key.clone());
+                RangeQuery {
+                    field: collapse.field.clone(),
+                    lower_bound: Bound::Included(collapse_value.clone()),
+                    upper_bound: Bound::Included(collapse_value),
+                }
+                .into()
+            }
+            None => BoolQuery {
+                must: vec![QueryAst::MatchAll],
+                must_not: vec![
+                    FieldPresenceQuery {
+                        field: collapse.field.clone(),
+                    }
+                    .into(),
+                ],
+                ..Default::default()
+            }
+            .into(),
+        };
+        filter.should.push(group_filter);
+    }
+    filter.into()
+}
+
+/// Returns the request searching the best hits of the groups of all the collapsed hits at once.
+/// The request collapses the hits on the same field, keeping the best `inner_hits_size` hits of
+/// each group and counting the hits of each group.
+fn inner_hits_search_request(
+    search_request: &SearchRequest,
+    collapse: &CollapseRequest,
+    collapse_keys: &[Option<String>],
+    is_bool_field: bool,
+) -> crate::Result<SearchRequest> {
+    let query_ast: QueryAst = serde_json::from_str(&search_request.query_ast)
+        .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
+    let inner_hits_query_ast = BoolQuery {
+        must: vec![query_ast],
+        filter: vec![collapse_keys_filter(collapse, collapse_keys, is_bool_field)],
+        ..Default::default()
+    };
+    Ok(SearchRequest {
+        query_ast: serde_json::to_string(&QueryAst::from(inner_hits_query_ast))?,
+        max_hits: collapse_keys.len() as u64 * collapse.inner_hits_size as u64,
+        start_offset: 0,
+        aggregation_request: None,
+        count_hits: CountHits::CountAll as i32,
+        collapse: Some(CollapseRequest {
+            field: collapse.field.clone(),
+            inner_hits_size: 0,
+            group_size: collapse.inner_hits_size,
+        }),
+        ..search_request.clone()
     })
 }
 
+/// Searches the best hits of the group of each collapsed hit with a single search request, and
+/// attaches them to the hit as inner hits.
+#[allow(clippy::too_many_arguments)]
+#[instrument(skip_all, fields(num_hits=hits.len()))]
+async fn fetch_inner_hits_phase(
+    searcher_context: &SearcherContext,
+    indexes_metas_for_leaf_search: &IndexesMetasForLeafSearch,
+    hits: &mut [Hit],
+    split_metadatas: &[SplitMetadata],
+    search_request: &SearchRequest,
+    collapse: &CollapseRequest,
+    cluster_client: &ClusterClient,
+    search_task_guard_opt: Option<&SearchTaskGuard>,
+) -> crate::Result<()> {
+    let collapse_keys: Vec<Option<String>> = hits.iter().map(hit_collapse_key).unique().collect();
+
+    if collapse_keys.is_empty() {
+        return Ok(());
+    }
+    let is_bool_field = is_bool_collapse_field(indexes_metas_for_leaf_search, &collapse.field)?;
+    let inner_hits_request =
+        inner_hits_search_request(search_request, collapse, &collapse_keys, is_bool_field)?;
+
+    let leaf_search_response = search_partial_hits_phase(
+        searcher_context,
+        indexes_metas_for_leaf_search,
+        &inner_hits_request,
+        split_metadatas,
+        cluster_client,
+        search_task_guard_opt,
+        None,
+    )
+    .await?;
+    let inner_hits = fetch_docs_phase(
+        indexes_metas_for_leaf_search,
+        &leaf_search_response.partial_hits,
+        split_metadatas,
+        &inner_hits_request,
+        cluster_client,
+        None,
+    )
+    .await?;
+    let mut inner_hits_per_collapse_key: HashMap<Option<String>, InnerHits> = leaf_search_response
+        .collapse_groups
+        .into_iter()
+        .map(|collapse_group| {
+            let inner_hits = InnerHits {
+                num_hits: collapse_group.num_hits,
+                hits: Vec::new(),
+            };
+            (collapse_group.collapse_key, inner_hits)
+        })
+        .collect();
+    // The inner hits are sorted from best to worst within each group.
+    for inner_hit in inner_hits {
+        inner_hits_per_collapse_key
+            .entry(hit_collapse_key(&inner_hit))
+            .or_default()
+            .hits
+            .push(inner_hit);
+    }
+    for hit in hits.iter_mut() {
+        let inner_hits = inner_hits_per_collapse_key
+            .remove(&hit_collapse_key(hit))
+            .unwrap_or_default();
+        hit.inner_hits = Some(inner_hits);
+    }
+    Ok(())
+}
+
 fn finalize_aggregation(
     intermediate_aggregation_result_bytes_opt: Option<Vec<u8>>,
     aggregations: QuickwitAggregations,
@@ -1022,6 +1642,17 @@ fn finalize_aggregation(
             // The merge collector has already merged the intermediate results.
             return Ok(intermediate_aggregation_result_bytes_opt);
         }
+        QuickwitAggregations::CompositeAggregation(aggregation) => {
+            let intermediate_result = if let Some(intermediate_aggregation_result_bytes) =
+                intermediate_aggregation_result_bytes_opt
+            {
+                postcard::from_bytes(&intermediate_aggregation_result_bytes)?
+            } else {
+                Default::default()
+            };
+            let final_aggregation_proxy = aggregation.finalize(intermediate_result);
+            postcard::to_stdvec(&final_aggregation_proxy)?
+        }
         QuickwitAggregations::TantivyAggregations(aggregations) => {
             let intermediate_aggregation_results =
                 if let Some(intermediate_aggregation_result_bytes) =
@@ -1044,7 +1675,7 @@ fn finalize_aggregation(
     Ok(Some(merge_aggregation_result))
 }
 
-fn finalize_aggregation_if_any(
+pub(crate) fn finalize_aggregation_if_any(
     search_request: &SearchRequest,
     intermediate_aggregation_result_bytes_opt: Option<Vec<u8>>,
     searcher_context: &SearcherContext,
@@ -1073,6 +1704,22 @@ pub fn check_all_index_metadata_found(
     index_metadatas: &[IndexMetadata],
     index_id_patterns: &[String],
 ) -> crate::Result<()> {
+    let missing_index_ids = find_missing_index_ids(index_metadatas, index_id_patterns);
+
+    if !missing_index_ids.is_empty() {
+        return Err(SearchError::IndexesNotFound {
+            index_ids: missing_index_ids,
+        });
+    }
+    Ok(())
+}
+
+/// Returns the index ids, i.e. the patterns that are neither wildcard nor exclusion patterns,
+/// that do not match any of the index metadatas.
+fn find_missing_index_ids(
+    index_metadatas: &[IndexMetadata],
+    index_id_patterns: &[String],
+) -> Vec<String> {
     let mut index_ids: HashSet<&str> = index_id_patterns
         .iter()
         .map(|index_ptn| index_ptn.as_str())
@@ -1081,23 +1728,143 @@ pub fn check_all_index_metadata_found(
 
     if index_ids.is_empty() {
         // All of the patterns are wildcard patterns.
-        return Ok(());
+        return Vec::new();
     }
 
     for index_metadata in index_metadatas {
         index_ids.remove(index_metadata.index_uid.index_id.as_str());
     }
 
-    if !index_ids.is_empty() {
-        let missing_index_ids = index_ids
-            .into_iter()
-            .map(|missing_index_id| missing_index_id.to_string())
-            .collect();
-        return Err(SearchError::IndexesNotFound {
-            index_ids: missing_index_ids,
+    index_ids
+        .into_iter()
+        .map(|missing_index_id| missing_index_id.to_string())
+        .collect()
+}
+
+/// Indexes targeted by a list of index id patterns once the index aliases they reference are
+/// resolved.
+#[derive(Debug)]
+pub(crate) struct ResolvedIndexes {
+    pub indexes_metadata: Vec<IndexMetadata>,
+    /// The index id patterns, with the alias ids replaced by the ids of their target indexes.
+    pub index_id_patterns: Vec<String>,
+    /// Filter to AND with the query when the indexes are targeted through a filtered alias.
+    pub alias_filter_opt: Option<QueryAst>,
+}
+
+/// Lists the indexes matching the index id patterns and resolves the index ids that do not
+/// match any index as index aliases.
+///
+/// Aliases are only resolved from exact ids, wildcard patterns only match indexes. A search
+/// request carries a single query for all the indexes it targets, so all the indexes must be
+/// targeted with the same alias filter (or without any).
+pub(crate) async fn resolve_indexes_and_aliases(
+    metastore: &mut MetastoreServiceClient,
+    index_id_patterns: &[String],
+) -> crate::Result<ResolvedIndexes> {
+    let list_indexes_metadata_request = ListIndexesMetadataRequest {
+        index_id_patterns: index_id_patterns.to_vec(),
+    };
+    let mut indexes_metadata: Vec<IndexMetadata> = metastore
+        .list_indexes_metadata(list_indexes_metadata_request)
+        .await?
+        .deserialize_indexes_metadata()
+        .await?;
+    let missing_index_ids = find_missing_index_ids(&indexes_metadata, index_id_patterns);
+
+    if missing_index_ids.is_empty() {
+        return Ok(ResolvedIndexes {
+            indexes_metadata,
+            index_id_patterns: index_id_patterns.to_vec(),
+            alias_filter_opt: None,
         });
     }
+    let list_index_aliases_request = ListIndexAliasesRequest {
+        alias_ids: missing_index_ids,
+    };
+    let index_aliases: Vec<IndexAlias> = metastore
+        .list_index_aliases(list_index_aliases_request)
+        .await?
+        .deserialize_index_aliases()?;
+
+    let mut resolved_index_id_patterns: Vec<String> = index_id_patterns
+        .iter()
+        .filter(|index_id_pattern| {
+            !index_aliases
+                .iter()
+                .any(|index_alias| &index_alias.alias_id == *index_id_pattern)
+        })
+        .cloned()
+        .collect();
+    let mut target_index_ids: Vec<String> = Vec::new();
+
+    let alias_filter_opt: Option<QueryAst> = {
+        let mut index_filters: HashMap<&str, Option<&QueryAst>> = indexes_metadata
+            .iter()
+            .map(|index_metadata| (index_metadata.index_id(), None))
+            .collect();
+
+        for index_alias in &index_aliases {
+            for target in &index_alias.targets {
+                match index_filters.insert(&target.index_id, target.filter.as_ref()) {
+                    None => target_index_ids.push(target.index_id.clone()),
+                    Some(previous_filter_opt) if previous_filter_opt != target.filter.as_ref() => {
+                        return Err(mixed_alias_filters_error());
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        let mut filters = index_filters.into_values();
+        let alias_filter_opt: Option<QueryAst> = filters.next().flatten().cloned();
+
+        if filters.any(|filter_opt| filter_opt != alias_filter_opt.as_ref()) {
+            return Err(mixed_alias_filters_error());
+        }
+        alias_filter_opt
+    };
+    if !target_index_ids.is_empty() {
+        let list_indexes_metadata_request = ListIndexesMetadataRequest {
+            index_id_patterns: target_index_ids.clone(),
+        };
+        let target_indexes_metadata: Vec<IndexMetadata> = metastore
+            .list_indexes_metadata(list_indexes_metadata_request)
+            .await?
+            .deserialize_indexes_metadata()
+            .await?;
+        indexes_metadata.extend(target_indexes_metadata);
+        resolved_index_id_patterns.extend(target_index_ids);
+    }
+    check_all_index_metadata_found(&indexes_metadata, &resolved_index_id_patterns)?;
+
+    Ok(ResolvedIndexes {
+        indexes_metadata,
+        index_id_patterns: resolved_index_id_patterns,
+        alias_filter_opt,
+    })
+}
+
+fn mixed_alias_filters_error() -> SearchError {
+    SearchError::InvalidQuery(
+        "all the indexes targeted by a search must be targeted with the same alias filter"
+            .to_string(),
+    )
+}
 
+/// ANDs the filter of the alias targeted by the search request with its query.
+fn apply_alias_filter(
+    search_request: &mut SearchRequest,
+    alias_filter: QueryAst,
+) -> crate::Result<()> {
+    let query_ast: QueryAst = serde_json::from_str(&search_request.query_ast)
+        .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
+    let filtered_query_ast: QueryAst = BoolQuery {
+        must: vec![query_ast],
+        filter: vec![alias_filter],
+        ..Default::default()
+    }
+    .into();
+    search_request.query_ast = serde_json::to_string(&filtered_query_ast)?;
     Ok(())
 }
 
@@ -1108,6 +1875,7 @@ async fn refine_and_list_matches(
     query_ast_resolved: QueryAst,
     sort_fields_is_datetime: HashMap<String, bool>,
     timestamp_field_opt: Option<String>,
+    point_in_time_context_opt: Option<PointInTimeContext>,
 ) -> crate::Result<Vec<SplitMetadata>> {
     let index_uids = indexes_metadata
         .iter()
@@ -1118,8 +1886,21 @@ async fn refine_and_list_matches(
     // convert search_after datetime values from input datetime format to nanos.
     convert_search_after_datetime_values(search_request, &sort_fields_is_datetime)?;
 
+    // The kNN hits of a hybrid search do not have to match the query, so the query cannot be used
+    // to prune splits.
+    let is_hybrid_search = matches!(
+        search_request
+            .knn
+            .as_deref()
+            .map(KnnQuery::parse)
+            .transpose()?,
+        Some(KnnQuery { rrf: Some(_), .. })
+    );
+
     // update_search_after_datetime_in_nanos(&mut search_request)?;
-    if let Some(timestamp_field) = &timestamp_field_opt {
+    if let Some(timestamp_field) = &timestamp_field_opt
+        && !is_hybrid_search
+    {
         refine_start_end_timestamp_from_ast(
             &query_ast_resolved,
             timestamp_field,
@@ -1127,7 +1908,19 @@ async fn refine_and_list_matches(
             &mut search_request.end_timestamp,
         );
     }
-    let tag_filter_ast = extract_tags_from_query(query_ast_resolved);
+    if let Some(point_in_time_context) = point_in_time_context_opt {
+        let split_metadatas = point_in_time_context.into_relevant_splits(
+            &index_uids,
+            search_request.start_timestamp,
+            search_request.end_timestamp,
+        );
+        return Ok(split_metadatas);
+    }
+    let tag_filter_ast = if is_hybrid_search {
+        None
+    } else {
+        extract_tags_from_query(query_ast_resolved)
+    };
 
     // TODO if search after is set, we sort by timestamp and we don't want to count all results,
     // we can refine more here. Same if we sort by _shard_doc
@@ -1142,27 +1935,63 @@ async fn refine_and_list_matches(
     Ok(split_metadatas)
 }
 
-/// Fetches the list of splits and their metadata from the metastore
-async fn plan_splits_for_root_search(
+/// Fetches the list of splits and their metadata from the metastore, or from the point in time
+/// context if the request carries one.
+pub(crate) async fn plan_splits_for_root_search(
     search_request: &mut SearchRequest,
     metastore: &mut MetastoreServiceClient,
+    cluster_client: &ClusterClient,
 ) -> crate::Result<(Vec<SplitMetadata>, IndexesMetasForLeafSearch)> {
-    let list_indexes_metadatas_request = ListIndexesMetadataRequest {
-        index_id_patterns: search_request.index_id_patterns.clone(),
+    let point_in_time_context_opt = if let Some(point_in_time) = &search_request.point_in_time {
+        let point_in_time_context =
+            load_point_in_time_context(point_in_time, metastore, cluster_client).await?;
+        // A point in time search targets the indexes the point in time was opened on.
+        search_request.index_id_patterns = point_in_time_context.index_ids();
+        Some(point_in_time_context)
+    } else {
+        None
     };
-    let indexes_metadata: Vec<IndexMetadata> = metastore
-        .list_indexes_metadata(list_indexes_metadatas_request)
-        .await?
-        .deserialize_indexes_metadata()
-        .await?;
+    let indexes_metadata: Vec<IndexMetadata> =
+        if let Some(point_in_time_context) = &point_in_time_context_opt {
+            let list_indexes_metadatas_request = ListIndexesMetadataRequest {
+                index_id_patterns: search_request.index_id_patterns.clone(),
+            };
+            let mut indexes_metadata: Vec<IndexMetadata> = metastore
+                .list_indexes_metadata(list_indexes_metadatas_request)
+                .await?
+                .deserialize_indexes_metadata()
+                .await?;
+            // Indexes deleted and recreated since the point in time was opened are reported as
+            // missing.
+            indexes_metadata.retain(|index_metadata| {
+                point_in_time_context
+                    .index_uids
+                    .contains(&index_metadata.index_uid)
+            });
+            check_all_index_metadata_found(
+                &indexes_metadata[..],
+                &search_request.index_id_patterns[..],
+            )?;
+            indexes_metadata
+        } else {
+            let resolved_indexes =
+                resolve_indexes_and_aliases(metastore, &search_request.index_id_patterns).await?;
+            search_request.index_id_patterns = resolved_indexes.index_id_patterns;
 
-    check_all_index_metadata_found(&indexes_metadata[..], &search_request.index_id_patterns[..])?;
+            if let Some(alias_filter) = resolved_indexes.alias_filter_opt {
+                apply_alias_filter(search_request, alias_filter)?;
+            }
+            resolved_indexes.indexes_metadata
+        };
 
     if indexes_metadata.is_empty() {
         return Ok((Vec::new(), HashMap::default()));
     }
 
     let request_metadata = validate_request_and_build_metadata(&indexes_metadata, search_request)?;
+    if let Some(knn_query_resolved) = &request_metadata.knn_query_resolved_opt {
+        search_request.knn = Some(serde_json::to_string(knn_query_resolved)?);
+    }
     let split_metadatas = refine_and_list_matches(
         metastore,
         search_request,
@@ -1170,6 +1999,7 @@ async fn plan_splits_for_root_search(
         request_metadata.query_ast_resolved,
         request_metadata.sort_fields_is_datetime,
         request_metadata.timestamp_field_opt,
+        point_in_time_context_opt,
     )
     .await?;
     Ok((
@@ -1183,18 +2013,66 @@ async fn plan_splits_for_root_search(
 /// 2. Merges the search results.
 /// 3. Sends fetch docs requests to multiple leaf nodes.
 /// 4. Builds the response with docs and returns.
+///
+/// The search is registered as a search task, which can be listed and cancelled, for its whole
+/// duration.
 #[instrument(skip_all)]
 pub async fn root_search(
+    searcher_context: &SearcherContext,
+    search_request: SearchRequest,
+    metastore: MetastoreServiceClient,
+    cluster_client: &ClusterClient,
+) -> crate::Result<SearchResponse> {
+    let search_task_id = Ulid::new().to_string();
+    let search_task = SearchTask {
+        search_task_id: search_task_id.clone(),
+        index_id_patterns: search_request.index_id_patterns.clone(),
+        query_ast: search_request.query_ast.clone(),
+        start_time_millis: now_millis(),
+        timeout_millis: search_request.timeout_millis,
+        searcher_grpc_addr: String::new(),
+    };
+    let timeout_opt = search_request.timeout_millis.map(Duration::from_millis);
+    let search_task_guard = searcher_context
+        .search_task_registry
+        .register_root_search(search_task, timeout_opt);
+
+    tokio::select! {
+        search_response_result = root_search_with_task(
+            searcher_context,
+            search_request,
+            metastore,
+            cluster_client,
+            &search_task_guard,
+        ) => search_response_result,
+        _ = search_task_guard.interruption().cancelled() => Err(SearchError::Cancelled(
+            format!("search task `{search_task_id}` was cancelled")
+        )),
+    }
+}
+
+async fn root_search_with_task(
     searcher_context: &SearcherContext,
     mut search_request: SearchRequest,
     mut metastore: MetastoreServiceClient,
     cluster_client: &ClusterClient,
+    search_task_guard: &SearchTaskGuard,
 ) -> crate::Result<SearchResponse> {
     let start_instant = Instant::now();
+    // The profile flag is taken out of the request so that it does not alter the leaf search
+    // cache keys.
+    let profile = std::mem::take(&mut search_request.profile);
+    let slow_query_log_threshold_opt = searcher_context.searcher_config.slow_query_log_threshold();
+    let slow_query_context_opt = slow_query_log_threshold_opt.map(|_| {
+        (
+            search_request.index_id_patterns.clone(),
+            search_request.query_ast.clone(),
+        )
+    });
 
     let (split_metadatas, indexes_meta_for_leaf_search) = RootSearchMetricsFuture {
         start: start_instant,
-        tracked: plan_splits_for_root_search(&mut search_request, &mut metastore),
+        tracked: plan_splits_for_root_search(&mut search_request, &mut metastore, cluster_client),
         is_success: None,
         step: RootSearchMetricsStep::Plan,
     }
@@ -1206,6 +2084,9 @@ pub async fn root_search(
     current_span.record("num_docs", num_docs);
     current_span.record("num_splits", num_splits);
 
+    let plan_duration = start_instant.elapsed();
+    let search_profiler = SearchProfiler::default();
+
     let mut search_response_result = RootSearchMetricsFuture {
         start: start_instant,
         tracked: root_search_aux(
@@ -1214,6 +2095,8 @@ pub async fn root_search(
             search_request,
             split_metadatas,
             cluster_client,
+            Some(search_task_guard),
+            Some(&search_profiler),
         ),
         is_success: None,
         step: RootSearchMetricsStep::Exec {
@@ -1222,8 +2105,28 @@ pub async fn root_search(
     }
     .await;
 
+    let elapsed = start_instant.elapsed();
+    let is_slow = matches!(slow_query_log_threshold_opt, Some(threshold) if elapsed >= threshold);
+
+    if profile || is_slow {
+        let search_profile =
+            search_profiler.finish(plan_duration, elapsed - plan_duration, num_splits);
+
+        if is_slow && let Some((index_id_patterns, query_ast)) = &slow_query_context_opt {
+            log_slow_query(
+                index_id_patterns,
+                query_ast,
+                elapsed,
+                &search_profile,
+                search_response_result.as_ref().err(),
+            );
+        }
+        if profile && let Ok(search_response) = &mut search_response_result {
+            search_response.profile = Some(search_profile);
+        }
+    }
     if let Ok(search_response) = &mut search_response_result {
-        search_response.elapsed_time_micros = start_instant.elapsed().as_micros() as u64;
+        search_response.elapsed_time_micros = elapsed.as_micros() as u64;
     }
 
     search_response_result
@@ -1234,16 +2137,14 @@ pub async fn search_plan(
     mut search_request: SearchRequest,
     mut metastore: MetastoreServiceClient,
 ) -> crate::Result<SearchPlanResponse> {
-    let list_indexes_metadatas_request = ListIndexesMetadataRequest {
-        index_id_patterns: search_request.index_id_patterns.clone(),
-    };
-    let indexes_metadata: Vec<IndexMetadata> = metastore
-        .list_indexes_metadata(list_indexes_metadatas_request)
-        .await?
-        .deserialize_indexes_metadata()
-        .await?;
+    let resolved_indexes =
+        resolve_indexes_and_aliases(&mut metastore, &search_request.index_id_patterns).await?;
+    search_request.index_id_patterns = resolved_indexes.index_id_patterns;
 
-    check_all_index_metadata_found(&indexes_metadata[..], &search_request.index_id_patterns[..])?;
+    if let Some(alias_filter) = resolved_indexes.alias_filter_opt {
+        apply_alias_filter(&mut search_request, alias_filter)?;
+    }
+    let indexes_metadata = resolved_indexes.indexes_metadata;
     if indexes_metadata.is_empty() {
         return Ok(SearchPlanResponse {
             result: serde_json::to_string(&SearchPlanResponseRest {
@@ -1268,6 +2169,7 @@ pub async fn search_plan(
         request_metadata.query_ast_resolved.clone(),
         request_metadata.sort_fields_is_datetime,
         request_metadata.timestamp_field_opt,
+        None,
     )
     .await?;
 
@@ -1662,6 +2564,8 @@ pub fn jobs_to_leaf_request(
         leaf_requests: Vec::new(),
         doc_mappers: Vec::new(),
         index_uris: Vec::new(),
+        timeout_millis: None,
+        search_task_id: String::new(),
     };
 
     let mut added_doc_mappers: HashMap<&str, u32> = HashMap::new();
@@ -1709,6 +2613,7 @@ pub fn jobs_to_leaf_request(
 /// Builds a list of [`FetchDocsRequest`], one per index, from a list of [`FetchDocsJob`].
 pub fn jobs_to_fetch_docs_requests(
     snippet_request_opt: Option<SnippetRequest>,
+    source_filter_opt: Option<SourceFilter>,
     indexes_metas_for_leaf_search: &IndexesMetasForLeafSearch,
     jobs: Vec<FetchDocsJob>,
 ) -> crate::Result<Vec<FetchDocsRequest>> {
@@ -1741,6 +2646,7 @@ pub fn jobs_to_fetch_docs_requests(
                 index_uri: index_meta.index_uri.to_string(),
                 snippet_request: snippet_request_opt.clone(),
                 doc_mapper: index_meta.doc_mapper_str.clone(),
+                source_filter: source_filter_opt.clone(),
             };
             fetch_docs_requests.push(fetch_docs_req);
 
@@ -1754,17 +2660,18 @@ pub fn jobs_to_fetch_docs_requests(
 mod tests {
     use std::ops::Range;
     use std::str::FromStr;
-    use std::sync::{Arc, RwLock};
+    use std::sync::{Arc, Mutex, RwLock};
 
     use quickwit_common::ServiceStream;
     use quickwit_common::shared_consts::SCROLL_BATCH_LEN;
     use quickwit_config::{
-        DocMapping, IndexConfig, IndexingSettings, IngestSettings, SearchSettings,
+        DocMapping, IndexConfig, IndexingSettings, IngestSettings, QueryRewrites, SearchSettings,
     };
     use quickwit_indexing::MockSplitBuilder;
     use quickwit_metastore::{IndexMetadata, ListSplitsRequestExt, ListSplitsResponseExt};
     use quickwit_proto::metastore::{
-        ListIndexesMetadataResponse, ListSplitsResponse, MockMetastoreService,
+        ListIndexAliasesResponse, ListIndexesMetadataResponse, ListSplitsResponse,
+        MockMetastoreService,
     };
     use quickwit_proto::search::{
         ScrollRequest, SortByValue, SortOrder, SortValue, SplitSearchError,
@@ -1856,6 +2763,7 @@ mod tests {
         let ingest_settings = IngestSettings::default();
         let search_settings = SearchSettings {
             default_search_fields: vec!["body".to_string()],
+            ..Default::default()
         };
         IndexMetadata::new(IndexConfig {
             index_id: index_id.to_string(),
@@ -1925,6 +2833,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_request_and_build_metadata_applies_query_rewrites() {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("msg:k8s", &[]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let mut index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        index_metadata.index_config.search_settings.query_rewrites = QueryRewrites {
+            synonyms: vec!["k8s, kubernetes".to_string()],
+            field_aliases: [("msg".to_string(), "body".to_string())]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        let request_metadata =
+            validate_request_and_build_metadata(&[index_metadata], &search_request).unwrap();
+        let expected_query_ast: QueryAst = BoolQuery {
+            should: vec![
+                qast_helper("body:k8s", &[]),
+                qast_helper("body:kubernetes", &[]),
+            ],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(request_metadata.query_ast_resolved, expected_query_ast);
+    }
+
     #[test]
     fn test_validate_request_and_build_metadatas_fail_with_different_timestamps() {
         let search_request = quickwit_proto::search::SearchRequest {
@@ -1997,6 +2934,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_request_and_build_metadatas_fail_with_different_query_rewrites() {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index-*".to_string()],
+            query_ast: qast_json_helper("body:k8s", &[]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let index_metadata_1 = IndexMetadata::for_test("test-index-1", "ram:///test-index-1");
+        let mut index_metadata_2 = IndexMetadata::for_test("test-index-2", "ram:///test-index-2");
+        index_metadata_2.index_config.search_settings.query_rewrites = QueryRewrites {
+            synonyms: vec!["k8s, kubernetes".to_string()],
+            ..Default::default()
+        };
+        let search_error = validate_request_and_build_metadata(
+            &[index_metadata_1, index_metadata_2],
+            &search_request,
+        )
+        .unwrap_err();
+        assert_eq!(
+            search_error.to_string(),
+            "the query rewrites (`search_settings.query_rewrites`) of the indexes `test-index-1` \
+             and `test-index-2` rewrite the query differently. searching indexes with different \
+             query rewrites is not supported"
+        );
+    }
+
     fn index_metadata_for_multi_indexes_test_with_incompatible_sort_type(
         index_id: &str,
         index_uri: &str,
@@ -2030,6 +2994,7 @@ mod tests {
         let indexing_settings = IndexingSettings::default();
         let search_settings = SearchSettings {
             default_search_fields: vec!["body".to_string()],
+            ..Default::default()
         };
         IndexMetadata::new(IndexConfig {
             index_id: index_id.to_string(),
@@ -2272,6 +3237,7 @@ mod tests {
             split_id: "".to_string(),
             segment_ord: 0,
             doc_id: 0,
+            collapse_key: None,
         };
         validate_sort_by_fields_and_search_after(&sort_fields, &Some(partial_hit)).unwrap();
     }
@@ -2298,6 +3264,7 @@ mod tests {
             split_id: "split1".to_string(),
             segment_ord: 1,
             doc_id: 1,
+            collapse_key: None,
         };
         validate_sort_by_fields_and_search_after(&sort_fields, &Some(partial_hit)).unwrap();
     }
@@ -2367,6 +3334,7 @@ mod tests {
             split_id: "split1".to_string(),
             segment_ord: 1,
             doc_id: 1,
+            collapse_key: None,
         };
         let error =
             validate_sort_by_fields_and_search_after(&sort_fields, &Some(partial_hit)).unwrap_err();
@@ -2400,6 +3368,7 @@ mod tests {
             split_id: "".to_string(),
             segment_ord: 1,
             doc_id: 1,
+            collapse_key: None,
         };
         let error =
             validate_sort_by_fields_and_search_after(&sort_fields, &Some(partial_hit)).unwrap_err();
@@ -2433,6 +3402,7 @@ mod tests {
             split_id: "split1".to_string(),
             segment_ord: 1,
             doc_id: 1,
+            collapse_key: None,
         };
         let error =
             validate_sort_by_fields_and_search_after(&sort_fields, &Some(partial_hit)).unwrap_err();
@@ -2496,6 +3466,7 @@ mod tests {
             split_id: split_id.to_string(),
             segment_ord: 1,
             doc_id,
+            collapse_key: None,
         }
     }
 
@@ -2510,6 +3481,7 @@ mod tests {
             split_id: split_id.to_string(),
             segment_ord: 1,
             doc_id,
+            collapse_key: None,
         }
     }
 
@@ -2528,6 +3500,7 @@ mod tests {
                 .expect("Json serialization should not fail"),
                 partial_hit: Some(req),
                 leaf_snippet_json: None,
+                source_disabled: false,
             })
             .collect()
     }
@@ -2700,6 +3673,213 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_root_search_cache() -> anyhow::Result<()> {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let mut mock_metastore = MockMetastoreService::new();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::for_test(vec![
+                    index_metadata.clone(),
+                ]))
+            });
+        let num_list_splits_calls = Arc::new(AtomicU64::new(0));
+        mock_metastore
+            .expect_list_splits()
+            .returning(move |_list_splits_request| {
+                let mut splits = vec![
+                    MockSplitBuilder::new("split1")
+                        .with_index_uid(&index_uid)
+                        .build(),
+                ];
+                // A fresh split is published before the third search.
+                if num_list_splits_calls.fetch_add(1, Ordering::Relaxed) >= 2 {
+                    let mut split = MockSplitBuilder::new("split2")
+                        .with_index_uid(&index_uid)
+                        .build();
+                    split.split_metadata.create_timestamp = now_millis() / 1_000;
+                    splits.push(split);
+                }
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().times(2).returning(
+            |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                let split_ids: Vec<&str> = leaf_search_req
+                    .leaf_requests
+                    .iter()
+                    .flat_map(|leaf_request| &leaf_request.split_offsets)
+                    .map(|split_offsets| split_offsets.split_id.as_str())
+                    .collect();
+                let partial_hits = match &split_ids[..] {
+                    ["split1"] => vec![
+                        mock_partial_hit("split1", 3, 1),
+                        mock_partial_hit("split1", 1, 2),
+                    ],
+                    ["split2"] => vec![mock_partial_hit("split2", 2, 1)],
+                    _ => panic!("unexpected splits searched: {split_ids:?}"),
+                };
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: partial_hits.len() as u64,
+                    partial_hits,
+                    failed_splits: Vec::new(),
+                    num_attempted_splits: 1,
+                    ..Default::default()
+                })
+            },
+        );
+        mock_search_service.expect_fetch_docs().returning(
+            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+
+        let searcher_config = quickwit_config::SearcherConfig {
+            root_search_cache: Some(quickwit_config::RootSearchCacheConfig::default()),
+            ..Default::default()
+        };
+        let searcher_context = SearcherContext::new(searcher_config, None);
+        let metastore = MetastoreServiceClient::from_mock(mock_metastore);
+
+        for _ in 0..2 {
+            let search_response = root_search(
+                &searcher_context,
+                search_request.clone(),
+                metastore.clone(),
+                &cluster_client,
+            )
+            .await
+            .unwrap();
+            assert_eq!(search_response.num_hits, 2);
+            assert_eq!(search_response.hits.len(), 2);
+        }
+        // Only the fresh split is searched, the hits of `split1` are served from the cache.
+        let search_response = root_search(
+            &searcher_context,
+            search_request,
+            metastore,
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 3);
+        let split_ids: Vec<&str> = search_response
+            .hits
+            .iter()
+            .map(|hit| hit.partial_hit.as_ref().unwrap().split_id.as_str())
+            .sorted()
+            .collect();
+        assert_eq!(split_ids, ["split1", "split1", "split2"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_root_search_cache_keeps_time_range() -> anyhow::Result<()> {
+        let mut mock_metastore = MockMetastoreService::new();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::for_test(vec![
+                    index_metadata.clone(),
+                ]))
+            });
+        mock_metastore
+            .expect_list_splits()
+            .returning(move |_list_splits_request| {
+                let splits = vec![
+                    MockSplitBuilder::new("split1")
+                        .with_index_uid(&index_uid)
+                        .build(),
+                ];
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        let leaf_time_ranges: Arc<Mutex<Vec<(Option<i64>, Option<i64>)>>> = Default::default();
+        let leaf_time_ranges_clone = leaf_time_ranges.clone();
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().times(5).returning(
+            move |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                let search_request = leaf_search_req.search_request.unwrap();
+                leaf_time_ranges_clone
+                    .lock()
+                    .unwrap()
+                    .push((search_request.start_timestamp, search_request.end_timestamp));
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 1,
+                    num_attempted_splits: 1,
+                    num_successful_splits: 1,
+                    ..Default::default()
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+
+        let searcher_config = quickwit_config::SearcherConfig {
+            root_search_cache: Some(quickwit_config::RootSearchCacheConfig::default()),
+            ..Default::default()
+        };
+        let searcher_context = SearcherContext::new(searcher_config, None);
+        let metastore = MetastoreServiceClient::from_mock(mock_metastore);
+
+        // The two time windows share the same aligned time range, whose hits are cached. Only
+        // the edges of the second window are searched.
+        for (start_timestamp, end_timestamp) in [(121_010, 121_150), (121_015, 121_155)] {
+            let search_request = quickwit_proto::search::SearchRequest {
+                index_id_patterns: vec!["test-index".to_string()],
+                query_ast: qast_json_helper("test", &["body"]),
+                max_hits: 10,
+                start_timestamp: Some(start_timestamp),
+                end_timestamp: Some(end_timestamp),
+                ..Default::default()
+            };
+            let search_response = root_search(
+                &searcher_context,
+                search_request,
+                metastore.clone(),
+                &cluster_client,
+            )
+            .await
+            .unwrap();
+            assert_eq!(search_response.num_hits, 3);
+        }
+        let leaf_time_ranges: Vec<(Option<i64>, Option<i64>)> = leaf_time_ranges
+            .lock()
+            .unwrap()
+            .iter()
+            .copied()
+            .sorted()
+            .collect();
+        assert_eq!(
+            leaf_time_ranges,
+            [
+                (Some(121_010), Some(121_020)),
+                (Some(121_015), Some(121_020)),
+                (Some(121_020), Some(121_140)),
+                (Some(121_140), Some(121_150)),
+                (Some(121_140), Some(121_155)),
+            ]
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_root_search_multiple_splits() -> anyhow::Result<()> {
         let search_request = quickwit_proto::search::SearchRequest {
@@ -2926,6 +4106,7 @@ mod tests {
                             split_id: "split1".to_string(),
                             segment_ord: 0,
                             doc_id: 0,
+                            collapse_key: None,
                         },
                         quickwit_proto::search::PartialHit {
                             sort_value: None,
@@ -2933,6 +4114,7 @@ mod tests {
                             split_id: "split1".to_string(),
                             segment_ord: 0,
                             doc_id: 1,
+                            collapse_key: None,
                         },
                     ],
                     failed_splits: Vec::new(),
@@ -2960,6 +4142,7 @@ mod tests {
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 1,
+                            collapse_key: None,
                         },
                         quickwit_proto::search::PartialHit {
                             sort_value: Some(SortValue::I64(1i64).into()),
@@ -2967,6 +4150,7 @@ mod tests {
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 0,
+                            collapse_key: None,
                         },
                         quickwit_proto::search::PartialHit {
                             sort_value: None,
@@ -2974,6 +4158,7 @@ mod tests {
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 2,
+                            collapse_key: None,
                         },
                     ],
                     failed_splits: Vec::new(),
@@ -3013,6 +4198,7 @@ mod tests {
                 doc_id: 1,
                 sort_value: Some(SortValue::I64(-1i64).into()),
                 sort_value2: None,
+                collapse_key: None,
             }
         );
         assert_eq!(
@@ -3023,6 +4209,7 @@ mod tests {
                 doc_id: 0,
                 sort_value: Some(SortValue::I64(1i64).into()),
                 sort_value2: None,
+                collapse_key: None,
             }
         );
         assert_eq!(
@@ -3033,6 +4220,7 @@ mod tests {
                 doc_id: 0,
                 sort_value: Some(SortValue::U64(2u64).into()),
                 sort_value2: None,
+                collapse_key: None,
             }
         );
         assert_eq!(
@@ -3043,6 +4231,7 @@ mod tests {
                 doc_id: 1,
                 sort_value: None,
                 sort_value2: None,
+                collapse_key: None,
             }
         );
         assert_eq!(
@@ -3053,6 +4242,7 @@ mod tests {
                 doc_id: 2,
                 sort_value: None,
                 sort_value2: None,
+                collapse_key: None,
             }
         );
         Ok(())
@@ -3108,6 +4298,7 @@ mod tests {
                             split_id: "split1".to_string(),
                             segment_ord: 0,
                             doc_id: 0,
+                            collapse_key: None,
                         },
                         quickwit_proto::search::PartialHit {
                             sort_value: None,
@@ -3115,6 +4306,7 @@ mod tests {
                             split_id: "split1".to_string(),
                             segment_ord: 0,
                             doc_id: 1,
+                            collapse_key: None,
                         },
                     ],
                     failed_splits: Vec::new(),
@@ -3142,6 +4334,7 @@ mod tests {
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 0,
+                            collapse_key: None,
                         },
                         quickwit_proto::search::PartialHit {
                             sort_value: Some(SortValue::I64(-1i64).into()),
@@ -3149,6 +4342,7 @@ mod tests {
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 1,
+                            collapse_key: None,
                         },
                         quickwit_proto::search::PartialHit {
                             sort_value: None,
@@ -3156,6 +4350,7 @@ mod tests {
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 2,
+                            collapse_key: None,
                         },
                     ],
                     failed_splits: Vec::new(),
@@ -3195,6 +4390,7 @@ mod tests {
                 doc_id: 0,
                 sort_value: Some(SortValue::U64(2u64).into()),
                 sort_value2: None,
+                collapse_key: None,
             }
         );
         assert_eq!(
@@ -3205,6 +4401,7 @@ mod tests {
                 doc_id: 0,
                 sort_value: Some(SortValue::I64(1i64).into()),
                 sort_value2: None,
+                collapse_key: None,
             }
         );
         assert_eq!(
@@ -3215,6 +4412,7 @@ mod tests {
                 doc_id: 1,
                 sort_value: Some(SortValue::I64(-1i64).into()),
                 sort_value2: None,
+                collapse_key: None,
             }
         );
         assert_eq!(
@@ -3225,6 +4423,7 @@ mod tests {
                 doc_id: 2,
                 sort_value: None,
                 sort_value2: None,
+                collapse_key: None,
             }
         );
         assert_eq!(
@@ -3235,6 +4434,7 @@ mod tests {
                 doc_id: 1,
                 sort_value: None,
                 sort_value2: None,
+                collapse_key: None,
             }
         );
         Ok(())
@@ -5046,4 +6246,93 @@ mod tests {
         assert_eq!(search_response.failed_splits.len(), 1);
         Ok(())
     }
+
+    fn mock_metastore_with_alias(index_alias: IndexAlias) -> MockMetastoreService {
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore.expect_list_indexes_metadata().returning(
+            |list_indexes_metadata_request: ListIndexesMetadataRequest| {
+                let indexes_metadata = list_indexes_metadata_request
+                    .index_id_patterns
+                    .iter()
+                    .filter(|index_id_pattern| index_id_pattern.starts_with("test-index"))
+                    .map(|index_id| {
+                        IndexMetadata::for_test(index_id, &format!("ram:///{index_id}"))
+                    })
+                    .collect();
+                Ok(ListIndexesMetadataResponse::for_test(indexes_metadata))
+            },
+        );
+        mock_metastore.expect_list_index_aliases().returning(
+            move |list_index_aliases_request: ListIndexAliasesRequest| {
+                let index_aliases_json = list_index_aliases_request
+                    .alias_ids
+                    .iter()
+                    .filter(|alias_id| **alias_id == index_alias.alias_id)
+                    .map(|_| serde_json::to_string(&index_alias).unwrap())
+                    .collect();
+                Ok(ListIndexAliasesResponse { index_aliases_json })
+            },
+        );
+        mock_metastore
+    }
+
+    #[tokio::test]
+    async fn test_resolve_indexes_and_aliases() {
+        let mut index_alias = IndexAlias::for_test("logs", &["test-index-1", "test-index-2"]);
+        let alias_filter = qast_helper("tenant:acme", &[]);
+        index_alias.targets[0].filter = Some(alias_filter.clone());
+        index_alias.targets[1].filter = Some(alias_filter.clone());
+        let mut metastore =
+            MetastoreServiceClient::from_mock(mock_metastore_with_alias(index_alias));
+
+        let resolved_indexes =
+            resolve_indexes_and_aliases(&mut metastore, &["test-index-1".to_string()])
+                .await
+                .unwrap();
+        assert_eq!(resolved_indexes.indexes_metadata.len(), 1);
+        assert_eq!(resolved_indexes.index_id_patterns, ["test-index-1"]);
+        assert!(resolved_indexes.alias_filter_opt.is_none());
+
+        let resolved_indexes = resolve_indexes_and_aliases(&mut metastore, &["logs".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(resolved_indexes.indexes_metadata.len(), 2);
+        assert_eq!(
+            resolved_indexes.index_id_patterns,
+            ["test-index-1", "test-index-2"]
+        );
+        assert_eq!(resolved_indexes.alias_filter_opt, Some(alias_filter));
+
+        // The index is targeted both with and without the alias filter.
+        let error = resolve_indexes_and_aliases(
+            &mut metastore,
+            &["test-index-1".to_string(), "logs".to_string()],
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(error, SearchError::InvalidQuery(_)));
+
+        let error = resolve_indexes_and_aliases(&mut metastore, &["unknown".to_string()])
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, SearchError::IndexesNotFound { index_ids } if index_ids == ["unknown"])
+        );
+    }
+
+    #[test]
+    fn test_apply_alias_filter() {
+        let mut search_request = SearchRequest {
+            query_ast: qast_json_helper("body:test", &[]),
+            ..Default::default()
+        };
+        apply_alias_filter(&mut search_request, qast_helper("tenant:acme", &[])).unwrap();
+
+        let query_ast: QueryAst = serde_json::from_str(&search_request.query_ast).unwrap();
+        let QueryAst::Bool(bool_query) = query_ast else {
+            panic!("expected a bool query");
+        };
+        assert_eq!(bool_query.must, [qast_helper("body:test", &[])]);
+        assert_eq!(bool_query.filter, [qast_helper("tenant:acme", &[])]);
+    }
 }
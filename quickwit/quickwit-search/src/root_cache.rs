@@ -0,0 +1,385 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use prost::Message;
+use quickwit_config::RootSearchCacheConfig;
+use quickwit_metastore::SplitMetadata;
+use quickwit_proto::search::{LeafSearchResponse, SearchRequest, SearchResponse};
+use quickwit_proto::types::SplitId;
+use quickwit_storage::{MemorySizedCache, OwnedBytes};
+
+/// A cache to memoize the responses of root searches.
+///
+/// Splits are immutable, so the response to a search only depends on the request and on the set
+/// of splits it targets: a new split being published or splits being merged changes the set of
+/// splits, and therefore the cache key.
+///
+/// Two kinds of entries are stored:
+/// - the full response of a search, served when the same request targets the same splits again;
+/// - the merged partial hits of the settled splits of a search, that is the splits created before
+///   the current time period. When new splits are published, only the splits created during the
+///   current period are searched again and merged with the cached partial hits.
+///
+/// So that the searches over a sliding time window share their entries, the partial hits of the
+/// settled splits are cached for the part of the time range aligned on the time granularity. The
+/// unaligned edges of the time range are searched with the original bounds every time.
+pub struct RootSearchCache {
+    content: MemorySizedCache<CacheKey>,
+    time_granularity_secs: i64,
+}
+
+impl RootSearchCache {
+    pub fn new(root_search_cache_config: &RootSearchCacheConfig) -> RootSearchCache {
+        RootSearchCache {
+            content: MemorySizedCache::with_capacity_in_bytes(
+                root_search_cache_config.capacity.as_u64() as usize,
+                &quickwit_storage::STORAGE_METRICS.root_search_cache,
+            ),
+            time_granularity_secs: root_search_cache_config.time_granularity_secs.get() as i64,
+        }
+    }
+
+    /// Returns whether the responses to a search request can be cached. Scroll searches are not
+    /// cached, as their pages are served from the scroll context.
+    pub(crate) fn is_cacheable(search_request: &SearchRequest) -> bool {
+        search_request.scroll_ttl_secs.is_none()
+    }
+
+    /// Splits the time range of a request into its largest sub-range aligned on the time
+    /// granularity and the unaligned edges around it, and returns a request for each of them.
+    /// Together, the requests cover the exact time range of the original request.
+    ///
+    /// Returns `None` if the aligned sub-range is empty.
+    pub(crate) fn split_time_range(
+        &self,
+        search_request: &SearchRequest,
+    ) -> Option<(SearchRequest, Vec<SearchRequest>)> {
+        let granularity = self.time_granularity_secs;
+        let aligned_start_timestamp_opt = search_request.start_timestamp.map(|start_timestamp| {
+            let aligned_start_timestamp = start_timestamp.div_euclid(granularity) * granularity;
+            if aligned_start_timestamp < start_timestamp {
+                aligned_start_timestamp.saturating_add(granularity)
+            } else {
+                aligned_start_timestamp
+            }
+        });
+        let aligned_end_timestamp_opt = search_request
+            .end_timestamp
+            .map(|end_timestamp| end_timestamp.div_euclid(granularity) * granularity);
+
+        if let (Some(aligned_start_timestamp), Some(aligned_end_timestamp)) =
+            (aligned_start_timestamp_opt, aligned_end_timestamp_opt)
+            && aligned_start_timestamp >= aligned_end_timestamp
+        {
+            return None;
+        }
+        let mut edge_requests = Vec::new();
+
+        if search_request.start_timestamp != aligned_start_timestamp_opt {
+            edge_requests.push(SearchRequest {
+                end_timestamp: aligned_start_timestamp_opt,
+                ..search_request.clone()
+            });
+        }
+        if search_request.end_timestamp != aligned_end_timestamp_opt {
+            edge_requests.push(SearchRequest {
+                start_timestamp: aligned_end_timestamp_opt,
+                ..search_request.clone()
+            });
+        }
+        let aligned_request = SearchRequest {
+            start_timestamp: aligned_start_timestamp_opt,
+            end_timestamp: aligned_end_timestamp_opt,
+            ..search_request.clone()
+        };
+        Some((aligned_request, edge_requests))
+    }
+
+    /// Partitions the splits into the settled splits, created before the current time period,
+    /// and the fresh splits.
+    pub(crate) fn partition_splits(
+        &self,
+        split_metadatas: &[SplitMetadata],
+        now_timestamp: i64,
+    ) -> (Vec<SplitMetadata>, Vec<SplitMetadata>) {
+        let period_start_timestamp =
+            now_timestamp.div_euclid(self.time_granularity_secs) * self.time_granularity_secs;
+        split_metadatas
+            .iter()
+            .cloned()
+            .partition(|split_metadata| split_metadata.create_timestamp < period_start_timestamp)
+    }
+
+    pub(crate) fn get_response(
+        &self,
+        search_request: &SearchRequest,
+        split_metadatas: &[SplitMetadata],
+    ) -> Option<SearchResponse> {
+        let key = CacheKey::new(CacheEntryKind::Response, search_request, split_metadatas);
+        let encoded_response = self.content.get(&key)?;
+        // this should never fail
+        SearchResponse::decode(&*encoded_response).ok()
+    }
+
+    pub(crate) fn put_response(
+        &self,
+        search_request: &SearchRequest,
+        split_metadatas: &[SplitMetadata],
+        search_response: &SearchResponse,
+    ) {
+        let key = CacheKey::new(CacheEntryKind::Response, search_request, split_metadatas);
+        let encoded_response = search_response.encode_to_vec();
+        self.content.put(key, OwnedBytes::new(encoded_response));
+    }
+
+    pub(crate) fn get_partial_hits(
+        &self,
+        search_request: &SearchRequest,
+        split_metadatas: &[SplitMetadata],
+    ) -> Option<LeafSearchResponse> {
+        let key = CacheKey::new(CacheEntryKind::PartialHits, search_request, split_metadatas);
+        let encoded_response = self.content.get(&key)?;
+        // this should never fail
+        LeafSearchResponse::decode(&*encoded_response).ok()
+    }
+
+    pub(crate) fn put_partial_hits(
+        &self,
+        search_request: &SearchRequest,
+        split_metadatas: &[SplitMetadata],
+        leaf_search_response: &LeafSearchResponse,
+    ) {
+        let key = CacheKey::new(CacheEntryKind::PartialHits, search_request, split_metadatas);
+        let encoded_response = leaf_search_response.encode_to_vec();
+        self.content.put(key, OwnedBytes::new(encoded_response));
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+enum CacheEntryKind {
+    Response,
+    PartialHits,
+}
+
+/// A key inside a [`RootSearchCache`].
+#[derive(Debug, Hash, PartialEq, Eq)]
+struct CacheKey {
+    kind: CacheEntryKind,
+    /// The request this matches. The timeout of the request was removed.
+    request: SearchRequest,
+    /// The sorted IDs of the splits searched.
+    split_ids: Vec<SplitId>,
+}
+
+impl CacheKey {
+    fn new(
+        kind: CacheEntryKind,
+        search_request: &SearchRequest,
+        split_metadatas: &[SplitMetadata],
+    ) -> Self {
+        let mut search_request = search_request.clone();
+        // the timeout only bounds how long we wait for the search, not its result.
+        search_request.timeout_millis = None;
+
+        let mut split_ids: Vec<SplitId> = split_metadatas
+            .iter()
+            .map(|split_metadata| split_metadata.split_id.clone())
+            .collect();
+        split_ids.sort_unstable();
+
+        CacheKey {
+            kind,
+            request: search_request,
+            split_ids,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+
+    use bytesize::ByteSize;
+    use quickwit_proto::search::{PartialHit, SortValue};
+
+    use super::*;
+
+    fn root_search_cache_for_test() -> RootSearchCache {
+        RootSearchCache::new(&RootSearchCacheConfig {
+            capacity: ByteSize::mb(64),
+            time_granularity_secs: NonZeroU64::new(60).unwrap(),
+        })
+    }
+
+    fn mock_split_meta(split_id: &str, create_timestamp: i64) -> SplitMetadata {
+        SplitMetadata {
+            split_id: split_id.to_string(),
+            create_timestamp,
+            ..Default::default()
+        }
+    }
+
+    fn time_ranges(search_requests: &[SearchRequest]) -> Vec<(Option<i64>, Option<i64>)> {
+        search_requests
+            .iter()
+            .map(|search_request| (search_request.start_timestamp, search_request.end_timestamp))
+            .collect()
+    }
+
+    #[test]
+    fn test_root_search_cache_split_time_range() {
+        let cache = root_search_cache_for_test();
+
+        let search_request = SearchRequest {
+            start_timestamp: Some(1_000),
+            end_timestamp: Some(1_900),
+            ..Default::default()
+        };
+        let (aligned_request, edge_requests) = cache.split_time_range(&search_request).unwrap();
+        assert_eq!(aligned_request.start_timestamp, Some(1_020));
+        assert_eq!(aligned_request.end_timestamp, Some(1_860));
+        assert_eq!(
+            time_ranges(&edge_requests),
+            [(Some(1_000), Some(1_020)), (Some(1_860), Some(1_900))]
+        );
+
+        let search_request = SearchRequest {
+            start_timestamp: Some(-30),
+            end_timestamp: Some(1_920),
+            ..Default::default()
+        };
+        let (aligned_request, edge_requests) = cache.split_time_range(&search_request).unwrap();
+        assert_eq!(aligned_request.start_timestamp, Some(0));
+        assert_eq!(aligned_request.end_timestamp, Some(1_920));
+        assert_eq!(time_ranges(&edge_requests), [(Some(-30), Some(0))]);
+
+        let search_request = SearchRequest {
+            start_timestamp: Some(1_010),
+            ..Default::default()
+        };
+        let (aligned_request, edge_requests) = cache.split_time_range(&search_request).unwrap();
+        assert_eq!(aligned_request.start_timestamp, Some(1_020));
+        assert_eq!(aligned_request.end_timestamp, None);
+        assert_eq!(time_ranges(&edge_requests), [(Some(1_010), Some(1_020))]);
+
+        let search_request = SearchRequest::default();
+        let (aligned_request, edge_requests) = cache.split_time_range(&search_request).unwrap();
+        assert_eq!(aligned_request, search_request);
+        assert!(edge_requests.is_empty());
+
+        // The time range does not contain a whole period.
+        let search_request = SearchRequest {
+            start_timestamp: Some(1_000),
+            end_timestamp: Some(1_070),
+            ..Default::default()
+        };
+        assert!(cache.split_time_range(&search_request).is_none());
+    }
+
+    #[test]
+    fn test_root_search_cache_partition_splits() {
+        let cache = root_search_cache_for_test();
+        let split_metadatas = vec![
+            mock_split_meta("split_1", 1_000),
+            mock_split_meta("split_2", 1_019),
+            mock_split_meta("split_3", 1_020),
+            mock_split_meta("split_4", 1_050),
+        ];
+        let (settled_splits, fresh_splits) = cache.partition_splits(&split_metadatas, 1_070);
+        let settled_split_ids: Vec<&str> = settled_splits
+            .iter()
+            .map(|split_metadata| split_metadata.split_id())
+            .collect();
+        let fresh_split_ids: Vec<&str> = fresh_splits
+            .iter()
+            .map(|split_metadata| split_metadata.split_id())
+            .collect();
+        assert_eq!(settled_split_ids, ["split_1", "split_2"]);
+        assert_eq!(fresh_split_ids, ["split_3", "split_4"]);
+    }
+
+    #[test]
+    fn test_root_search_cache_keyed_by_split_set() {
+        let cache = root_search_cache_for_test();
+
+        let search_request = SearchRequest {
+            index_id_patterns: vec!["test-idx".to_string()],
+            query_ast: "test".to_string(),
+            max_hits: 10,
+            timeout_millis: Some(1_000),
+            ..Default::default()
+        };
+        let split_metadatas = vec![mock_split_meta("split_1", 0), mock_split_meta("split_2", 0)];
+        let search_response = SearchResponse {
+            num_hits: 1234,
+            num_successful_splits: 2,
+            ..Default::default()
+        };
+        assert!(
+            cache
+                .get_response(&search_request, &split_metadatas)
+                .is_none()
+        );
+        cache.put_response(&search_request, &split_metadatas, &search_response);
+
+        // The order of the splits and the timeout do not matter.
+        let split_metadatas_reversed =
+            vec![mock_split_meta("split_2", 0), mock_split_meta("split_1", 0)];
+        let search_request_without_timeout = SearchRequest {
+            timeout_millis: None,
+            ..search_request.clone()
+        };
+        assert_eq!(
+            cache
+                .get_response(&search_request_without_timeout, &split_metadatas_reversed)
+                .unwrap(),
+            search_response
+        );
+        // A new split invalidates the entry.
+        let mut split_metadatas_with_new_split = split_metadatas.clone();
+        split_metadatas_with_new_split.push(mock_split_meta("split_3", 0));
+        assert!(
+            cache
+                .get_response(&search_request, &split_metadatas_with_new_split)
+                .is_none()
+        );
+        // Responses and partial hits are cached separately.
+        assert!(
+            cache
+                .get_partial_hits(&search_request, &split_metadatas)
+                .is_none()
+        );
+        let leaf_search_response = LeafSearchResponse {
+            num_hits: 1234,
+            partial_hits: vec![PartialHit {
+                doc_id: 1,
+                segment_ord: 0,
+                sort_value: Some(SortValue::U64(0u64).into()),
+                sort_value2: None,
+                split_id: "split_1".to_string(),
+                collapse_key: None,
+            }],
+            num_attempted_splits: 2,
+            num_successful_splits: 2,
+            ..Default::default()
+        };
+        cache.put_partial_hits(&search_request, &split_metadatas, &leaf_search_response);
+        assert_eq!(
+            cache
+                .get_partial_hits(&search_request, &split_metadatas)
+                .unwrap(),
+            leaf_search_response
+        );
+    }
+}
@@ -22,7 +22,7 @@ use std::time::Duration;
 use anyhow::Context;
 use base64::Engine;
 use base64::prelude::BASE64_STANDARD;
-use quickwit_common::metrics::GaugeGuard;
+use quickwit_common::metrics::{GaugeGuard, IntGauge};
 use quickwit_common::shared_consts::SCROLL_BATCH_LEN;
 use quickwit_metastore::SplitMetadata;
 use quickwit_proto::search::{LeafSearchResponse, PartialHit, SearchRequest, SplitSearchError};
@@ -44,6 +44,12 @@ use crate::service::SearcherContext;
 /// amount to up to 1GB.
 const LOCAL_KV_CACHE_SIZE: usize = 1_000;
 
+/// Maximum number of values in the async search KV store.
+///
+/// The states of async searches are kept for up to 5 days, so they are stored apart from the
+/// short-lived scroll contexts.
+const ASYNC_SEARCH_KV_CACHE_SIZE: usize = 10_000;
+
 #[derive(Serialize, Deserialize)]
 pub(crate) struct ScrollContext {
     pub split_metadatas: Vec<SplitMetadata>,
@@ -113,6 +119,8 @@ impl ScrollContext {
             &self.search_request,
             &self.split_metadatas[..],
             cluster_client,
+            None,
+            None,
         )
         .await?;
         self.cached_partial_hits_start_offset = start_offset;
@@ -123,39 +131,73 @@ impl ScrollContext {
 
 struct TrackedValue {
     content: Vec<u8>,
+    version_opt: Option<u64>,
     _total_size_metric_guard: GaugeGuard<'static>,
 }
 
 /// In memory key value store with TTL and limited size.
 ///
-/// Once the capacity [LOCAL_KV_CACHE_SIZE] is reached, the oldest entries are
-/// removed.
+/// Once its capacity is reached, the oldest entries are removed.
 ///
-/// Currently this store is only used for caching scroll contexts. Using it for
-/// other purposes is risky as use cases would compete for its capacity.
+/// Each use case gets its own store, so that use cases do not compete for its capacity: the
+/// default store caches scroll and point in time contexts, while a dedicated store holds the
+/// states of the async searches.
 #[derive(Clone)]
 pub(crate) struct MiniKV {
     ttl_with_cache: Arc<RwLock<TtlCache<Vec<u8>, TrackedValue>>>,
+    size_bytes_gauge: &'static IntGauge,
 }
 
 impl Default for MiniKV {
     fn default() -> MiniKV {
-        MiniKV {
-            ttl_with_cache: Arc::new(RwLock::new(TtlCache::new(LOCAL_KV_CACHE_SIZE))),
-        }
+        MiniKV::new(
+            LOCAL_KV_CACHE_SIZE,
+            &crate::SEARCH_METRICS.searcher_local_kv_store_size_bytes,
+        )
     }
 }
 
 impl MiniKV {
-    pub async fn put(&self, key: Vec<u8>, payload: Vec<u8>, ttl: Duration) {
-        let mut metric_guard =
-            GaugeGuard::from_gauge(&crate::SEARCH_METRICS.searcher_local_kv_store_size_bytes);
-        metric_guard.add(payload.len() as i64);
+    fn new(capacity: usize, size_bytes_gauge: &'static IntGauge) -> MiniKV {
+        MiniKV {
+            ttl_with_cache: Arc::new(RwLock::new(TtlCache::new(capacity))),
+            size_bytes_gauge,
+        }
+    }
+
+    /// Creates the store holding the states of the async searches.
+    pub fn for_async_searches() -> MiniKV {
+        MiniKV::new(
+            ASYNC_SEARCH_KV_CACHE_SIZE,
+            &crate::SEARCH_METRICS.searcher_async_search_kv_store_size_bytes,
+        )
+    }
+
+    /// Stores a value. If a version is specified, a value stored with a higher version is not
+    /// replaced.
+    pub async fn put(
+        &self,
+        key: Vec<u8>,
+        payload: Vec<u8>,
+        ttl: Duration,
+        version_opt: Option<u64>,
+    ) {
         let mut cache_lock = self.ttl_with_cache.write().await;
+
+        if let Some(version) = version_opt
+            && let Some(tracked_value) = cache_lock.get(&key)
+            && let Some(stored_version) = tracked_value.version_opt
+            && stored_version > version
+        {
+            return;
+        }
+        let mut metric_guard = GaugeGuard::from_gauge(self.size_bytes_gauge);
+        metric_guard.add(payload.len() as i64);
         cache_lock.insert(
             key,
             TrackedValue {
                 content: payload,
+                version_opt,
                 _total_size_metric_guard: metric_guard,
             },
             ttl,
@@ -261,10 +303,11 @@ impl FromStr for ScrollKeyAndStartOffset {
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
+    use std::time::Duration;
 
     use quickwit_proto::search::PartialHit;
 
-    use crate::scroll_context::ScrollKeyAndStartOffset;
+    use crate::scroll_context::{MiniKV, ScrollKeyAndStartOffset};
 
     #[test]
     fn test_scroll_id() {
@@ -274,10 +317,39 @@ mod tests {
             split_id: "split".to_string(),
             segment_ord: 1,
             doc_id: 2,
+            collapse_key: None,
         };
         let scroll = ScrollKeyAndStartOffset::new_with_start_offset(10, 100, partial_hit);
         let scroll_str = scroll.to_string();
         let ser_deser_scroll = ScrollKeyAndStartOffset::from_str(&scroll_str).unwrap();
         assert_eq!(scroll, ser_deser_scroll);
     }
+
+    #[tokio::test]
+    async fn test_mini_kv_versioned_put() {
+        let mini_kv = MiniKV::for_async_searches();
+        let ttl = Duration::from_secs(60);
+        mini_kv
+            .put(b"key".to_vec(), b"v1".to_vec(), ttl, Some(1))
+            .await;
+        mini_kv
+            .put(b"key".to_vec(), b"v3".to_vec(), ttl, Some(3))
+            .await;
+        assert_eq!(mini_kv.get(b"key").await.unwrap(), b"v3");
+
+        mini_kv
+            .put(b"key".to_vec(), b"v2".to_vec(), ttl, Some(2))
+            .await;
+        assert_eq!(mini_kv.get(b"key").await.unwrap(), b"v3");
+
+        mini_kv
+            .put(b"key".to_vec(), b"v3-bis".to_vec(), ttl, Some(3))
+            .await;
+        assert_eq!(mini_kv.get(b"key").await.unwrap(), b"v3-bis");
+
+        mini_kv
+            .put(b"key".to_vec(), b"unversioned".to_vec(), ttl, None)
+            .await;
+        assert_eq!(mini_kv.get(b"key").await.unwrap(), b"unversioned");
+    }
 }
@@ -104,6 +104,11 @@ impl SearchJobPlacer {
     pub fn new(searcher_pool: SearcherPool) -> Self {
         Self { searcher_pool }
     }
+
+    /// Returns the clients of all the search nodes.
+    pub fn all_nodes(&self) -> Vec<SearchServiceClient> {
+        self.searcher_pool.values()
+    }
 }
 
 struct SocketAddrAndClient {
@@ -12,24 +12,37 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Instant;
 
 use bytesize::ByteSize;
 use quickwit_common::metrics::GaugeGuard;
+use quickwit_config::{OTHER_SEARCH_TENANT_ID, SearchTenantLimits};
 use quickwit_proto::search::SplitIdAndFooterOffsets;
 #[cfg(test)]
 use tokio::sync::watch;
 use tokio::sync::{mpsc, oneshot};
 
+use crate::SearchError;
+
+/// Virtual time a tenant of weight 1 is charged for each permit it obtains. Tenants with a
+/// weight `w` are charged `1/w` of it.
+const VIRTUAL_TIME_PER_PERMIT: u64 = 1 << 20;
+
 /// Distributor of permits to perform split search operation.
 ///
-/// Requests are served in order. Each permit initially reserves a slot for the
-/// warmup (limit concurrent downloads) and a pessimistic amount of memory. Once
-/// the warmup is completed, the actual memory usage is set and the warmup slot
-/// is released. Once the search is completed and the permit is dropped, the
+/// Requests are queued per tenant. Within a tenant, requests are served in order. Across
+/// tenants, permits are handed out using weighted fair queuing: the next permit goes to the
+/// tenant that obtained the fewest permits relative to its weight, among the tenants that have
+/// not reached their own concurrency and memory limits.
+///
+/// Each permit initially reserves a slot for the warmup (limit concurrent downloads) and a
+/// pessimistic amount of memory. Once the warmup is completed, the actual memory usage is set
+/// and the warmup slot is released. Once the search is completed and the permit is dropped, the
 /// remaining memory is also released.
 #[derive(Clone)]
 pub struct SearchPermitProvider {
@@ -41,14 +54,19 @@ pub struct SearchPermitProvider {
 #[derive(Debug)]
 pub enum SearchPermitMessage {
     Request {
-        permit_sender: oneshot::Sender<Vec<SearchPermitFuture>>,
+        tenant_id: Arc<str>,
+        permit_sender: oneshot::Sender<crate::Result<Vec<SearchPermitFuture>>>,
         permit_sizes: Vec<u64>,
     },
     UpdateMemory {
+        tenant_id: Arc<str>,
         memory_delta: i64,
     },
-    FreeWarmupSlot,
+    FreeWarmupSlot {
+        tenant_id: Arc<str>,
+    },
     Drop {
+        tenant_id: Arc<str>,
         memory_size: u64,
         warmup_slot_freed: bool,
     },
@@ -80,6 +98,23 @@ pub fn compute_initial_memory_allocation(
 
 impl SearchPermitProvider {
     pub fn new(num_download_slots: usize, memory_budget: ByteSize) -> Self {
+        Self::with_tenant_limits(
+            num_download_slots,
+            memory_budget,
+            SearchTenantLimits::default(),
+            HashMap::new(),
+        )
+    }
+
+    /// Creates a permit provider applying `tenant_limits` to the tenants listed in it. The other
+    /// tenants share a single [`OTHER_SEARCH_TENANT_ID`] tenant with `default_tenant_limits`, so
+    /// that arbitrary tenant IDs from clients do not create queues or metric labels.
+    pub fn with_tenant_limits(
+        num_download_slots: usize,
+        memory_budget: ByteSize,
+        default_tenant_limits: SearchTenantLimits,
+        tenant_limits: HashMap<String, SearchTenantLimits>,
+    ) -> Self {
         let (message_sender, message_receiver) = mpsc::unbounded_channel();
         #[cfg(test)]
         let (state_sender, state_receiver) = watch::channel(false);
@@ -88,8 +123,12 @@ impl SearchPermitProvider {
             msg_sender: message_sender.downgrade(),
             num_warmup_slots_available: num_download_slots,
             total_memory_budget: memory_budget.as_u64(),
-            permits_requests: VecDeque::new(),
             total_memory_allocated: 0u64,
+            default_tenant_limits,
+            tenant_limits,
+            tenants: HashMap::new(),
+            virtual_time: 0,
+            next_request_seq: 0,
             #[cfg(test)]
             stopped: state_sender,
         };
@@ -105,17 +144,21 @@ impl SearchPermitProvider {
     ///
     /// The permits returned are guaranteed to be resolved in order. In
     /// addition, the permits are guaranteed to be resolved before permits
-    /// returned by subsequent calls to this function.
+    /// returned by subsequent calls to this function for the same tenant.
     ///
     /// The permit memory size is capped by per_permit_initial_memory_allocation.
+    ///
+    /// Returns a `TooManyRequests` error if the queue of the tenant is full.
     pub async fn get_permits(
         &self,
+        tenant_id: &str,
         splits: impl IntoIterator<Item = ByteSize>,
-    ) -> Vec<SearchPermitFuture> {
+    ) -> crate::Result<Vec<SearchPermitFuture>> {
         let (permit_sender, permit_receiver) = oneshot::channel();
         let permit_sizes = splits.into_iter().map(|size| size.as_u64()).collect();
         self.message_sender
             .send(SearchPermitMessage::Request {
+                tenant_id: Arc::from(tenant_id),
                 permit_sender,
                 permit_sizes,
             })
@@ -126,6 +169,89 @@ impl SearchPermitProvider {
     }
 }
 
+#[derive(Debug)]
+struct PermitRequest {
+    permit_requester_tx: oneshot::Sender<SearchPermit>,
+    permit_size: u64,
+    // Breaks ties between tenants with the same virtual time in favor of the oldest request.
+    seq: u64,
+    enqueued_at: Instant,
+}
+
+struct TenantState {
+    limits: SearchTenantLimits,
+    permits_requests: VecDeque<PermitRequest>,
+    num_permits: usize,
+    num_warmup_slots_used: usize,
+    memory_allocated: u64,
+    virtual_time: u64,
+}
+
+impl TenantState {
+    fn new(limits: SearchTenantLimits) -> Self {
+        Self {
+            limits,
+            permits_requests: VecDeque::new(),
+            num_permits: 0,
+            num_warmup_slots_used: 0,
+            memory_allocated: 0,
+            virtual_time: 0,
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.permits_requests.is_empty() && self.num_permits == 0
+    }
+
+    /// Requests whose future was dropped, for instance because the leaf search was cancelled or
+    /// timed out, should not hold back the requests queued behind them.
+    fn drop_cancelled_requests(&mut self) {
+        while let Some(permit_request) = self.permits_requests.front()
+            && permit_request.permit_requester_tx.is_closed()
+        {
+            self.permits_requests.pop_front();
+        }
+    }
+
+    /// Returns the next request of the tenant if its limits allow serving it. The memory budget
+    /// of the tenant does not apply to its first permit, so that permits larger than the budget
+    /// are eventually served.
+    fn next_serviceable_request(&self) -> Option<&PermitRequest> {
+        let next_permit_request = self.permits_requests.front()?;
+
+        if let Some(max_num_concurrent_split_searches) =
+            self.limits.max_num_concurrent_split_searches
+            && self.num_warmup_slots_used >= max_num_concurrent_split_searches.get()
+        {
+            return None;
+        }
+        if let Some(warmup_memory_budget) = self.limits.warmup_memory_budget
+            && self.memory_allocated > 0
+            && self.memory_allocated + next_permit_request.permit_size
+                > warmup_memory_budget.as_u64()
+        {
+            return None;
+        }
+        Some(next_permit_request)
+    }
+
+    fn update_memory_allocated(&mut self, memory_delta: i64) {
+        if self.memory_allocated as i64 + memory_delta < 0 {
+            panic!("More memory released than allocated, should never happen.")
+        }
+        self.memory_allocated = (self.memory_allocated as i64 + memory_delta) as u64;
+    }
+}
+
+fn live_tenant_mut<'a>(
+    tenants: &'a mut HashMap<Arc<str>, TenantState>,
+    tenant_id: &str,
+) -> &'a mut TenantState {
+    tenants
+        .get_mut(tenant_id)
+        .expect("the tenant of a live permit should be tracked")
+}
+
 struct SearchPermitActor {
     msg_receiver: mpsc::UnboundedReceiver<SearchPermitMessage>,
     msg_sender: mpsc::WeakUnboundedSender<SearchPermitMessage>,
@@ -135,7 +261,14 @@ struct SearchPermitActor {
     /// When it happens, new permits will not be assigned until the memory is freed.
     total_memory_budget: u64,
     total_memory_allocated: u64,
-    permits_requests: VecDeque<(oneshot::Sender<SearchPermit>, u64)>,
+    default_tenant_limits: SearchTenantLimits,
+    tenant_limits: HashMap<String, SearchTenantLimits>,
+    /// Tenants with queued requests or live permits.
+    tenants: HashMap<Arc<str>, TenantState>,
+    /// Virtual time of the last tenant served. Tenants becoming active again start from it, so
+    /// that they do not accumulate credit while idle.
+    virtual_time: u64,
+    next_request_seq: u64,
     #[cfg(test)]
     stopped: watch::Sender<bool>,
 }
@@ -153,38 +286,46 @@ impl SearchPermitActor {
     fn handle_message(&mut self, msg: SearchPermitMessage) {
         match msg {
             SearchPermitMessage::Request {
+                tenant_id,
                 permit_sizes,
                 permit_sender,
             } => {
-                let mut permits = Vec::with_capacity(permit_sizes.len());
-                for permit_size in permit_sizes {
-                    let (tx, rx) = oneshot::channel();
-                    self.permits_requests.push_back((tx, permit_size));
-                    permits.push(SearchPermitFuture(rx));
-                }
+                let permits_res = self.enqueue_permit_requests(tenant_id, permit_sizes);
                 self.assign_available_permits();
                 // The receiver could be dropped in the (unlikely) situation
                 // where the future requesting these permits is cancelled before
                 // this message is processed.
-                let _ = permit_sender.send(permits);
+                let _ = permit_sender.send(permits_res);
             }
-            SearchPermitMessage::UpdateMemory { memory_delta } => {
+            SearchPermitMessage::UpdateMemory {
+                tenant_id,
+                memory_delta,
+            } => {
                 if self.total_memory_allocated as i64 + memory_delta < 0 {
                     panic!("More memory released than allocated, should never happen.")
                 }
                 self.total_memory_allocated =
                     (self.total_memory_allocated as i64 + memory_delta) as u64;
+                live_tenant_mut(&mut self.tenants, &tenant_id)
+                    .update_memory_allocated(memory_delta);
                 self.assign_available_permits();
             }
-            SearchPermitMessage::FreeWarmupSlot => {
+            SearchPermitMessage::FreeWarmupSlot { tenant_id } => {
                 self.num_warmup_slots_available += 1;
+                live_tenant_mut(&mut self.tenants, &tenant_id).num_warmup_slots_used -= 1;
                 self.assign_available_permits();
             }
             SearchPermitMessage::Drop {
+                tenant_id,
                 memory_size,
                 warmup_slot_freed,
             } => {
+                let tenant = live_tenant_mut(&mut self.tenants, &tenant_id);
+                tenant.num_permits -= 1;
+                tenant.update_memory_allocated(-(memory_size as i64));
+
                 if !warmup_slot_freed {
+                    tenant.num_warmup_slots_used -= 1;
                     self.num_warmup_slots_available += 1;
                 }
                 self.total_memory_allocated = self
@@ -196,42 +337,130 @@ impl SearchPermitActor {
         }
     }
 
-    fn pop_next_request_if_serviceable(&mut self) -> Option<(oneshot::Sender<SearchPermit>, u64)> {
+    fn enqueue_permit_requests(
+        &mut self,
+        tenant_id: Arc<str>,
+        permit_sizes: Vec<u64>,
+    ) -> crate::Result<Vec<SearchPermitFuture>> {
+        let (tenant_id, tenant_limits) = match self.tenant_limits.get(&*tenant_id) {
+            Some(tenant_limits) => (tenant_id, tenant_limits),
+            None => (
+                Arc::from(OTHER_SEARCH_TENANT_ID),
+                &self.default_tenant_limits,
+            ),
+        };
+        let tenant = self
+            .tenants
+            .entry(tenant_id.clone())
+            .or_insert_with(|| TenantState::new(tenant_limits.clone()));
+        tenant.drop_cancelled_requests();
+
+        // A request is always admitted when the queue of the tenant is empty, so that requests
+        // targeting more splits than the queue capacity are not rejected forever.
+        if let Some(max_num_pending_split_searches) = tenant.limits.max_num_pending_split_searches
+            && !tenant.permits_requests.is_empty()
+            && tenant.permits_requests.len() + permit_sizes.len()
+                > max_num_pending_split_searches.get()
+        {
+            crate::SEARCH_METRICS
+                .leaf_search_permit_requests_rejected_total
+                .with_label_values([&*tenant_id])
+                .inc();
+            return Err(SearchError::TooManyRequests);
+        }
+        if tenant.permits_requests.is_empty() {
+            tenant.virtual_time = tenant.virtual_time.max(self.virtual_time);
+        }
+        let enqueued_at = Instant::now();
+        let mut permits = Vec::with_capacity(permit_sizes.len());
+
+        for permit_size in permit_sizes {
+            let (tx, rx) = oneshot::channel();
+            tenant.permits_requests.push_back(PermitRequest {
+                permit_requester_tx: tx,
+                permit_size,
+                seq: self.next_request_seq,
+                enqueued_at,
+            });
+            self.next_request_seq += 1;
+            permits.push(SearchPermitFuture(rx));
+        }
+        Ok(permits)
+    }
+
+    /// Pops the next request of the tenant with the lowest virtual time among the tenants that
+    /// can be served. If the memory budget does not allow serving it, no request is popped, so
+    /// that large requests are not starved by smaller ones.
+    fn pop_next_request_if_serviceable(&mut self) -> Option<(Arc<str>, PermitRequest)> {
         if self.num_warmup_slots_available == 0 {
             return None;
         }
-        if let Some((_, next_permit_size)) = self.permits_requests.front()
-            && self.total_memory_allocated + next_permit_size <= self.total_memory_budget
-        {
-            return self.permits_requests.pop_front();
+        let (tenant_id, tenant) = self
+            .tenants
+            .iter_mut()
+            .filter_map(|(tenant_id, tenant)| {
+                let next_permit_request = tenant.next_serviceable_request()?;
+                let priority = (tenant.virtual_time, next_permit_request.seq);
+                Some((priority, tenant_id, tenant))
+            })
+            .min_by_key(|(priority, _, _)| *priority)
+            .map(|(_, tenant_id, tenant)| (tenant_id, tenant))?;
+
+        let next_permit_size = tenant.permits_requests.front()?.permit_size;
+
+        if self.total_memory_allocated + next_permit_size > self.total_memory_budget {
+            return None;
         }
-        None
+        let permit_request = tenant.permits_requests.pop_front()?;
+        tenant.num_permits += 1;
+        tenant.num_warmup_slots_used += 1;
+        tenant.memory_allocated += next_permit_size;
+
+        self.virtual_time = tenant.virtual_time;
+        tenant.virtual_time += (VIRTUAL_TIME_PER_PERMIT / tenant.limits.weight.get() as u64).max(1);
+        Some((tenant_id.clone(), permit_request))
     }
 
     fn assign_available_permits(&mut self) {
-        while let Some((permit_requester_tx, next_permit_size)) =
-            self.pop_next_request_if_serviceable()
-        {
+        for tenant in self.tenants.values_mut() {
+            tenant.drop_cancelled_requests();
+        }
+        self.tenants.retain(|_, tenant| !tenant.is_idle());
+
+        while let Some((tenant_id, permit_request)) = self.pop_next_request_if_serviceable() {
             let mut ongoing_gauge_guard = GaugeGuard::from_gauge(
                 &crate::SEARCH_METRICS.leaf_search_single_split_tasks_ongoing,
             );
             ongoing_gauge_guard.add(1);
-            self.total_memory_allocated += next_permit_size;
+            self.total_memory_allocated += permit_request.permit_size;
             self.num_warmup_slots_available -= 1;
-            permit_requester_tx
+
+            crate::SEARCH_METRICS
+                .leaf_search_permit_wait_duration_seconds
+                .with_label_values([&*tenant_id])
+                .observe(permit_request.enqueued_at.elapsed().as_secs_f64());
+
+            permit_request
+                .permit_requester_tx
                 .send(SearchPermit {
                     _ongoing_gauge_guard: ongoing_gauge_guard,
                     msg_sender: self.msg_sender.clone(),
-                    memory_allocation: next_permit_size,
+                    tenant_id,
+                    memory_allocation: permit_request.permit_size,
                     warmup_slot_freed: false,
                 })
                 // if the requester dropped its receiver, we drop the newly
                 // created SearchPermit which releases the resources
                 .ok();
         }
+        let num_pending_permits: usize = self
+            .tenants
+            .values()
+            .map(|tenant| tenant.permits_requests.len())
+            .sum();
         crate::SEARCH_METRICS
             .leaf_search_single_split_tasks_pending
-            .set(self.permits_requests.len() as i64);
+            .set(num_pending_permits as i64);
     }
 }
 
@@ -239,6 +468,7 @@ impl SearchPermitActor {
 pub struct SearchPermit {
     _ongoing_gauge_guard: GaugeGuard<'static>,
     msg_sender: mpsc::WeakUnboundedSender<SearchPermitMessage>,
+    tenant_id: Arc<str>,
     memory_allocation: u64,
     warmup_slot_freed: bool,
 }
@@ -251,7 +481,10 @@ impl SearchPermit {
         let new_usage_bytes = new_memory_usage.as_u64();
         let memory_delta = new_usage_bytes as i64 - self.memory_allocation as i64;
         self.memory_allocation = new_usage_bytes;
-        self.send_if_still_running(SearchPermitMessage::UpdateMemory { memory_delta });
+        self.send_if_still_running(SearchPermitMessage::UpdateMemory {
+            tenant_id: self.tenant_id.clone(),
+            memory_delta,
+        });
     }
 
     /// Drop the warmup permit, allowing more downloads to be started. Only one
@@ -261,7 +494,9 @@ impl SearchPermit {
             return;
         }
         self.warmup_slot_freed = true;
-        self.send_if_still_running(SearchPermitMessage::FreeWarmupSlot);
+        self.send_if_still_running(SearchPermitMessage::FreeWarmupSlot {
+            tenant_id: self.tenant_id.clone(),
+        });
     }
 
     pub fn memory_allocation(&self) -> ByteSize {
@@ -282,6 +517,7 @@ impl SearchPermit {
 impl Drop for SearchPermit {
     fn drop(&mut self) {
         self.send_if_still_running(SearchPermitMessage::Drop {
+            tenant_id: self.tenant_id.clone(),
             memory_size: self.memory_allocation,
             warmup_slot_freed: self.warmup_slot_freed,
         });
@@ -307,6 +543,7 @@ impl Future for SearchPermitFuture {
 #[cfg(test)]
 mod tests {
     use std::iter::repeat_n;
+    use std::num::{NonZeroU32, NonZeroUsize};
     use std::time::Duration;
 
     use futures::StreamExt;
@@ -320,8 +557,9 @@ mod tests {
         let permit_provider = SearchPermitProvider::new(1, ByteSize::mb(100));
         let mut all_futures = Vec::new();
         let first_batch_of_permits = permit_provider
-            .get_permits(repeat_n(ByteSize::mb(10), 10))
-            .await;
+            .get_permits("my-tenant", repeat_n(ByteSize::mb(10), 10))
+            .await
+            .unwrap();
         assert_eq!(first_batch_of_permits.len(), 10);
         all_futures.extend(
             first_batch_of_permits
@@ -331,8 +569,9 @@ mod tests {
         );
 
         let second_batch_of_permits = permit_provider
-            .get_permits(repeat_n(ByteSize::mb(10), 10))
-            .await;
+            .get_permits("my-tenant", repeat_n(ByteSize::mb(10), 10))
+            .await
+            .unwrap();
         assert_eq!(second_batch_of_permits.len(), 10);
         all_futures.extend(
             second_batch_of_permits
@@ -369,14 +608,16 @@ mod tests {
     async fn test_search_permit_early_drops() {
         let permit_provider = SearchPermitProvider::new(1, ByteSize::mb(100));
         let permit_fut1 = permit_provider
-            .get_permits(vec![ByteSize::mb(10)])
+            .get_permits("my-tenant", vec![ByteSize::mb(10)])
             .await
+            .unwrap()
             .into_iter()
             .next()
             .unwrap();
         let permit_fut2 = permit_provider
-            .get_permits([ByteSize::mb(10)])
+            .get_permits("my-tenant", [ByteSize::mb(10)])
             .await
+            .unwrap()
             .into_iter()
             .next()
             .unwrap();
@@ -386,8 +627,9 @@ mod tests {
         assert_eq!(*permit_provider.actor_stopped.borrow(), false);
 
         let _permit_fut3 = permit_provider
-            .get_permits([ByteSize::mb(10)])
+            .get_permits("my-tenant", [ByteSize::mb(10)])
             .await
+            .unwrap()
             .into_iter()
             .next()
             .unwrap();
@@ -399,6 +641,213 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_search_permit_dropped_requests_are_skipped() {
+        let permit_provider = SearchPermitProvider::new(10, ByteSize::mb(100));
+        let _permit = permit_provider
+            .get_permits("my-tenant", [ByteSize::mb(60)])
+            .await
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap()
+            .await;
+        // This request does not fit in the memory budget until the first permit is released.
+        let permit_fut = permit_provider
+            .get_permits("my-tenant", [ByteSize::mb(60)])
+            .await
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap();
+        drop(permit_fut);
+
+        let permit_fut = permit_provider
+            .get_permits("my-tenant", [ByteSize::mb(30)])
+            .await
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap();
+        let permit = tokio::time::timeout(Duration::from_secs(1), permit_fut)
+            .await
+            .expect("the dropped request should not hold back the next one");
+        assert_eq!(permit.memory_allocation, ByteSize::mb(30).as_u64());
+    }
+
+    #[tokio::test]
+    async fn test_search_permit_weighted_fair_queuing() {
+        let tenant_limits = HashMap::from_iter([(
+            "tenant-b".to_string(),
+            SearchTenantLimits {
+                weight: NonZeroU32::new(2).unwrap(),
+                ..Default::default()
+            },
+        )]);
+        let permit_provider = SearchPermitProvider::with_tenant_limits(
+            1,
+            ByteSize::mb(100),
+            SearchTenantLimits::default(),
+            tenant_limits,
+        );
+        let permit_futs_a = permit_provider
+            .get_permits("tenant-a", repeat_n(ByteSize::mb(1), 6))
+            .await
+            .unwrap();
+        let permit_futs_b = permit_provider
+            .get_permits("tenant-b", repeat_n(ByteSize::mb(1), 6))
+            .await
+            .unwrap();
+
+        // There is a single warmup slot, so the permits are granted one at a time, each one
+        // after the previous one is dropped.
+        let (order_tx, mut order_rx) = mpsc::unbounded_channel();
+        let mut join_set = JoinSet::new();
+        for (tenant, permit_futs) in [("a", permit_futs_a), ("b", permit_futs_b)] {
+            for permit_fut in permit_futs {
+                let order_tx = order_tx.clone();
+                join_set.spawn(async move {
+                    let _permit = permit_fut.await;
+                    order_tx.send(tenant).unwrap();
+                });
+            }
+        }
+        drop(order_tx);
+        join_set.join_all().await;
+
+        let mut order = String::new();
+        while let Some(tenant) = order_rx.recv().await {
+            order.push_str(tenant);
+        }
+        assert_eq!(order, "abbabbabbaaa");
+    }
+
+    #[tokio::test]
+    async fn test_search_permit_tenant_limits() {
+        let tenant_limits = HashMap::from_iter([
+            (
+                "tenant-a".to_string(),
+                SearchTenantLimits {
+                    max_num_concurrent_split_searches: Some(NonZeroUsize::MIN),
+                    ..Default::default()
+                },
+            ),
+            (
+                "tenant-b".to_string(),
+                SearchTenantLimits {
+                    warmup_memory_budget: Some(ByteSize::mb(15)),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        let permit_provider = SearchPermitProvider::with_tenant_limits(
+            10,
+            ByteSize::mb(100),
+            SearchTenantLimits::default(),
+            tenant_limits,
+        );
+        let mut permit_futs_a = permit_provider
+            .get_permits("tenant-a", repeat_n(ByteSize::mb(10), 2))
+            .await
+            .unwrap()
+            .into_iter();
+        let mut permit_a = try_get(permit_futs_a.next().unwrap()).await.unwrap();
+        // tenant A is capped to one concurrent split search...
+        try_get(permit_futs_a.next().unwrap()).await.unwrap_err();
+
+        let mut permit_futs_b = permit_provider
+            .get_permits("tenant-b", repeat_n(ByteSize::mb(10), 2))
+            .await
+            .unwrap()
+            .into_iter();
+        // ... which does not prevent tenant B from obtaining a permit
+        let _permit_b = try_get(permit_futs_b.next().unwrap()).await.unwrap();
+        // tenant B is capped by its memory budget
+        try_get(permit_futs_b.next().unwrap()).await.unwrap_err();
+
+        let mut permit_futs_a = permit_provider
+            .get_permits("tenant-a", [ByteSize::mb(10)])
+            .await
+            .unwrap()
+            .into_iter();
+        // freeing the warmup slot of tenant A lets it start another split search
+        permit_a.free_warmup_slot();
+        try_get(permit_futs_a.next().unwrap()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_search_permit_tenant_queue_limit() {
+        let tenant_limits = HashMap::from_iter([(
+            "tenant-a".to_string(),
+            SearchTenantLimits {
+                max_num_pending_split_searches: Some(NonZeroUsize::new(2).unwrap()),
+                ..Default::default()
+            },
+        )]);
+        let permit_provider = SearchPermitProvider::with_tenant_limits(
+            1,
+            ByteSize::mb(100),
+            SearchTenantLimits::default(),
+            tenant_limits,
+        );
+        let _permit = permit_provider
+            .get_permits("tenant-a", [ByteSize::mb(10)])
+            .await
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap()
+            .await;
+        // A request is admitted when the queue is empty, even if it exceeds the queue limit.
+        let _permit_futs = permit_provider
+            .get_permits("tenant-a", repeat_n(ByteSize::mb(10), 3))
+            .await
+            .unwrap();
+        let search_error = permit_provider
+            .get_permits("tenant-a", [ByteSize::mb(10)])
+            .await
+            .unwrap_err();
+        assert!(matches!(search_error, SearchError::TooManyRequests));
+        // The queues of the other tenants are not affected.
+        permit_provider
+            .get_permits("tenant-b", [ByteSize::mb(10)])
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_search_permit_unknown_tenants_share_other_tenant() {
+        let default_tenant_limits = SearchTenantLimits {
+            max_num_concurrent_split_searches: Some(NonZeroUsize::MIN),
+            ..Default::default()
+        };
+        let permit_provider = SearchPermitProvider::with_tenant_limits(
+            10,
+            ByteSize::mb(100),
+            default_tenant_limits,
+            HashMap::new(),
+        );
+        let permit_fut_x = permit_provider
+            .get_permits("tenant-x", [ByteSize::mb(10)])
+            .await
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap();
+        let permit_x = try_get(permit_fut_x).await.unwrap();
+        assert_eq!(&*permit_x.tenant_id, OTHER_SEARCH_TENANT_ID);
+
+        // Tenants without limits of their own are capped together.
+        let permit_fut_y = permit_provider
+            .get_permits("tenant-y", [ByteSize::mb(10)])
+            .await
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap();
+        try_get(permit_fut_y).await.unwrap_err();
+    }
+
     /// Tries to wait for a permit
     async fn try_get(permit_fut: SearchPermitFuture) -> anyhow::Result<SearchPermit> {
         // using a short timeout is a bit flaky, but it should be enough for these tests
@@ -410,8 +859,9 @@ mod tests {
     async fn test_memory_budget() {
         let permit_provider = SearchPermitProvider::new(100, ByteSize::mb(100));
         let mut permit_futs = permit_provider
-            .get_permits(repeat_n(ByteSize::mb(10), 14))
-            .await;
+            .get_permits("my-tenant", repeat_n(ByteSize::mb(10), 14))
+            .await
+            .unwrap();
         let mut remaining_permit_futs = permit_futs.split_off(10).into_iter();
         assert_eq!(remaining_permit_futs.len(), 4);
         // we should be able to obtain 10 permits right away (100MB / 10MB)
@@ -440,8 +890,9 @@ mod tests {
     async fn test_warmup_slot() {
         let permit_provider = SearchPermitProvider::new(10, ByteSize::mb(100));
         let mut permit_futs = permit_provider
-            .get_permits(repeat_n(ByteSize::mb(1), 16))
-            .await;
+            .get_permits("my-tenant", repeat_n(ByteSize::mb(1), 16))
+            .await
+            .unwrap();
         let mut remaining_permit_futs = permit_futs.split_off(10).into_iter();
         assert_eq!(remaining_permit_futs.len(), 6);
         // we should be able to obtain 10 permits right away
@@ -0,0 +1,248 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use quickwit_proto::search::{LeafSearchProfile, LeafSearchResponse, ResourceStats, SearchProfile};
+use tracing::warn;
+
+use crate::merge_resource_stats;
+
+/// Tracing target of the slow query log.
+const SLOW_QUERY_LOG_TARGET: &str = "quickwit_search::slow_query_log";
+
+/// Collects the execution breakdown of a root search, searcher by searcher.
+///
+/// It is fed by the leaf search and fetch docs phases, and turned into a [`SearchProfile`] once
+/// the search is over.
+#[derive(Default)]
+pub(crate) struct SearchProfiler {
+    inner: Mutex<InnerSearchProfiler>,
+}
+
+#[derive(Default)]
+struct InnerSearchProfiler {
+    leaves: BTreeMap<SocketAddr, LeafStats>,
+    /// Splits sent to at least one searcher. The other targeted splits were pruned by the root.
+    searched_split_ids: HashSet<String>,
+}
+
+#[derive(Default)]
+struct LeafStats {
+    num_splits: u64,
+    num_attempted_splits: u64,
+    num_failed_splits: u64,
+    leaf_search_duration: Duration,
+    fetch_docs_duration: Duration,
+    resource_stats: Option<ResourceStats>,
+}
+
+impl SearchProfiler {
+    pub fn record_leaf_search(
+        &self,
+        leaf_addr: SocketAddr,
+        split_ids: &[String],
+        elapsed: Duration,
+        leaf_search_response: &LeafSearchResponse,
+    ) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.searched_split_ids.extend(split_ids.iter().cloned());
+        let leaf_stats = inner.leaves.entry(leaf_addr).or_default();
+        leaf_stats.num_splits += split_ids.len() as u64;
+        leaf_stats.num_attempted_splits += leaf_search_response.num_attempted_splits;
+        leaf_stats.num_failed_splits += leaf_search_response.failed_splits.len() as u64;
+        leaf_stats.leaf_search_duration += elapsed;
+        merge_resource_stats(
+            &leaf_search_response.resource_stats,
+            &mut leaf_stats.resource_stats,
+        );
+    }
+
+    /// Records a fetch docs request. The requests sent to a searcher run concurrently, so the
+    /// fetch docs duration of a searcher is that of its slowest request.
+    pub fn record_fetch_docs(&self, leaf_addr: SocketAddr, elapsed: Duration) {
+        let mut inner = self.inner.lock().unwrap();
+        let leaf_stats = inner.leaves.entry(leaf_addr).or_default();
+        leaf_stats.fetch_docs_duration = leaf_stats.fetch_docs_duration.max(elapsed);
+    }
+
+    pub fn finish(
+        &self,
+        plan_duration: Duration,
+        exec_duration: Duration,
+        num_targeted_splits: usize,
+    ) -> SearchProfile {
+        let inner = self.inner.lock().unwrap();
+        // Splits answered from the split metadata or the root search cache are never sent to the
+        // searchers.
+        let mut num_pruned_splits =
+            (num_targeted_splits as u64).saturating_sub(inner.searched_split_ids.len() as u64);
+        let mut leaf_profiles = Vec::with_capacity(inner.leaves.len());
+
+        for (leaf_addr, leaf_stats) in inner.leaves.iter() {
+            num_pruned_splits += leaf_stats
+                .num_splits
+                .saturating_sub(leaf_stats.num_attempted_splits + leaf_stats.num_failed_splits);
+            let resource_stats = leaf_stats.resource_stats.unwrap_or_default();
+            let leaf_profile = LeafSearchProfile {
+                leaf_addr: leaf_addr.to_string(),
+                num_splits: leaf_stats.num_splits,
+                leaf_search_micros: leaf_stats.leaf_search_duration.as_micros() as u64,
+                warmup_micros: resource_stats.warmup_microsecs,
+                cpu_thread_pool_wait_micros: resource_stats.cpu_thread_pool_wait_microsecs,
+                search_micros: resource_stats.cpu_microsecs,
+                fetch_docs_micros: leaf_stats.fetch_docs_duration.as_micros() as u64,
+                downloaded_num_bytes: resource_stats.downloaded_num_bytes,
+                leaf_search_cache_hit_ratio: ratio(
+                    resource_stats.num_leaf_search_cache_hits,
+                    leaf_stats.num_attempted_splits,
+                ),
+                storage_cache_hit_ratio: cache_hit_ratio(
+                    resource_stats.warmup_downloaded_num_bytes,
+                    resource_stats.short_lived_cache_num_bytes,
+                ),
+            };
+            leaf_profiles.push(leaf_profile);
+        }
+        SearchProfile {
+            plan_micros: plan_duration.as_micros() as u64,
+            num_targeted_splits: num_targeted_splits as u64,
+            // A split sent several times, e.g. by a hybrid search, may be counted twice.
+            num_pruned_splits: num_pruned_splits.min(num_targeted_splits as u64),
+            exec_micros: exec_duration.as_micros() as u64,
+            leaves: leaf_profiles,
+        }
+    }
+}
+
+/// Returns `numerator / denominator`, capped to 1, or 0 if the denominator is 0.
+fn ratio(numerator: u64, denominator: u64) -> f64 {
+    if denominator == 0 {
+        return 0.0;
+    }
+    (numerator as f64 / denominator as f64).min(1.0)
+}
+
+/// Returns the share of the bytes read that were not downloaded, or 0 if nothing was read.
+fn cache_hit_ratio(num_downloaded_bytes: u64, num_read_bytes: u64) -> f64 {
+    if num_read_bytes == 0 {
+        return 0.0;
+    }
+    1.0 - ratio(num_downloaded_bytes, num_read_bytes)
+}
+
+/// Logs a search that took longer than the slow query log threshold.
+pub(crate) fn log_slow_query(
+    index_id_patterns: &[String],
+    query_ast: &str,
+    elapsed: Duration,
+    search_profile: &SearchProfile,
+    error_opt: Option<&crate::SearchError>,
+) {
+    let leaves_json = serde_json::to_string(&search_profile.leaves).unwrap_or_default();
+    warn!(
+        target: SLOW_QUERY_LOG_TARGET,
+        elapsed_millis = elapsed.as_millis() as u64,
+        index_id_patterns = ?index_id_patterns,
+        query_ast,
+        plan_micros = search_profile.plan_micros,
+        exec_micros = search_profile.exec_micros,
+        num_targeted_splits = search_profile.num_targeted_splits,
+        num_pruned_splits = search_profile.num_pruned_splits,
+        leaves = leaves_json,
+        error = error_opt.map(tracing::field::display),
+        "slow query"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_proto::search::SplitSearchError;
+
+    use super::*;
+
+    #[test]
+    fn test_search_profiler() {
+        let search_profiler = SearchProfiler::default();
+        let leaf_addr_1: SocketAddr = "127.0.0.1:1000".parse().unwrap();
+        let leaf_addr_2: SocketAddr = "127.0.0.1:2000".parse().unwrap();
+
+        let leaf_search_response = LeafSearchResponse {
+            num_attempted_splits: 4,
+            failed_splits: vec![SplitSearchError::default()],
+            resource_stats: Some(ResourceStats {
+                short_lived_cache_num_bytes: 1_000,
+                warmup_microsecs: 300,
+                cpu_thread_pool_wait_microsecs: 20,
+                cpu_microsecs: 500,
+                downloaded_num_bytes: 1_500,
+                warmup_downloaded_num_bytes: 250,
+                num_leaf_search_cache_hits: 1,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let split_ids: Vec<String> = (0..7)
+            .map(|split_ord| format!("split-{split_ord}"))
+            .collect();
+        search_profiler.record_leaf_search(
+            leaf_addr_2,
+            &split_ids[..6],
+            Duration::from_millis(2),
+            &leaf_search_response,
+        );
+        search_profiler.record_leaf_search(
+            leaf_addr_1,
+            &split_ids[6..],
+            Duration::from_millis(1),
+            &LeafSearchResponse {
+                num_attempted_splits: 1,
+                ..Default::default()
+            },
+        );
+        search_profiler.record_fetch_docs(leaf_addr_2, Duration::from_millis(3));
+        search_profiler.record_fetch_docs(leaf_addr_2, Duration::from_millis(1));
+
+        let search_profile =
+            search_profiler.finish(Duration::from_millis(10), Duration::from_millis(20), 9);
+        assert_eq!(search_profile.plan_micros, 10_000);
+        assert_eq!(search_profile.exec_micros, 20_000);
+        assert_eq!(search_profile.num_targeted_splits, 9);
+        // 2 splits pruned by the root and 1 split pruned by a searcher.
+        assert_eq!(search_profile.num_pruned_splits, 3);
+        assert_eq!(search_profile.leaves.len(), 2);
+
+        let leaf_profile_1 = &search_profile.leaves[0];
+        assert_eq!(leaf_profile_1.leaf_addr, "127.0.0.1:1000");
+        assert_eq!(leaf_profile_1.num_splits, 1);
+        assert_eq!(leaf_profile_1.downloaded_num_bytes, 0);
+        // Nothing was read, so nothing was served by the caches.
+        assert_eq!(leaf_profile_1.storage_cache_hit_ratio, 0.0);
+
+        let leaf_profile_2 = &search_profile.leaves[1];
+        assert_eq!(leaf_profile_2.leaf_addr, "127.0.0.1:2000");
+        assert_eq!(leaf_profile_2.num_splits, 6);
+        assert_eq!(leaf_profile_2.leaf_search_micros, 2_000);
+        assert_eq!(leaf_profile_2.warmup_micros, 300);
+        assert_eq!(leaf_profile_2.cpu_thread_pool_wait_micros, 20);
+        assert_eq!(leaf_profile_2.search_micros, 500);
+        assert_eq!(leaf_profile_2.fetch_docs_micros, 3_000);
+        assert_eq!(leaf_profile_2.downloaded_num_bytes, 1_500);
+        assert_eq!(leaf_profile_2.leaf_search_cache_hit_ratio, 0.25);
+        assert_eq!(leaf_profile_2.storage_cache_hit_ratio, 0.75);
+    }
+}
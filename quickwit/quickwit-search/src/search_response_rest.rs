@@ -12,14 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::convert::TryFrom;
 
 use quickwit_common::truncate_str;
-use quickwit_proto::search::SearchResponse;
-use quickwit_query::aggregations::AggregationResults as AggregationResultsProxy;
+use quickwit_proto::search::{SearchProfile, SearchResponse};
+use quickwit_query::aggregations::{
+    AggregationResults as AggregationResultsProxy, CompositeKey, CompositeKeyValue, CompositeResult,
+};
 use quickwit_query::query_ast::QueryAst;
 use serde::{Deserialize, Serialize};
-use serde_json::Value as JsonValue;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use tantivy::aggregation::agg_result::{
+    AggregationResult as TantivyAggregationResult, AggregationResults as TantivyAggregationResults,
+};
 
 use crate::error::SearchError;
 
@@ -27,16 +33,90 @@ use crate::error::SearchError;
 // TODO previously, we were using zero-copy when possible, which we are no longer doing:
 // is that problematic? How can we return to zero/low-copy without it being painful?
 #[derive(Serialize, PartialEq, Debug)]
-pub struct AggregationResults(tantivy::aggregation::agg_result::AggregationResults);
+pub struct AggregationResults(HashMap<String, AggregationResult>);
 
 impl AggregationResults {
     /// Parse an ES aggregation result ast from our non-ambiguous postcard format
     pub fn from_postcard(postcard_bytes: &[u8]) -> anyhow::Result<Self> {
-        let aggregation_result: AggregationResultsProxy = postcard::from_bytes(postcard_bytes)?;
-        Ok(AggregationResults(aggregation_result.into()))
+        let aggregation_results: AggregationResultsProxy = postcard::from_bytes(postcard_bytes)?;
+        let aggregation_results = aggregation_results
+            .0
+            .into_iter()
+            .map(|(name, aggregation_result)| {
+                let aggregation_result =
+                    match TantivyAggregationResult::try_from(aggregation_result) {
+                        Ok(tantivy_aggregation_result) => {
+                            AggregationResult::Tantivy(tantivy_aggregation_result)
+                        }
+                        Err(composite_result) => {
+                            AggregationResult::Composite(composite_result.into())
+                        }
+                    };
+                (name, aggregation_result)
+            })
+            .collect();
+        Ok(AggregationResults(aggregation_results))
     }
 }
 
+#[derive(Serialize, PartialEq, Debug)]
+#[serde(untagged)]
+enum AggregationResult {
+    Tantivy(TantivyAggregationResult),
+    Composite(CompositeAggregationResult),
+}
+
+/// Composite aggregation result, in the Elasticsearch format.
+#[derive(Serialize, PartialEq, Debug)]
+struct CompositeAggregationResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    after_key: Option<JsonMap<String, JsonValue>>,
+    buckets: Vec<CompositeBucketEntry>,
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+struct CompositeBucketEntry {
+    key: JsonMap<String, JsonValue>,
+    doc_count: u64,
+    #[serde(flatten)]
+    sub_aggregation: TantivyAggregationResults,
+}
+
+impl From<CompositeResult> for CompositeAggregationResult {
+    fn from(composite_result: CompositeResult) -> Self {
+        let buckets = composite_result
+            .buckets
+            .into_iter()
+            .map(|bucket| CompositeBucketEntry {
+                key: composite_key_to_json(bucket.key),
+                doc_count: bucket.doc_count,
+                sub_aggregation: bucket.sub_aggregation.into(),
+            })
+            .collect();
+        CompositeAggregationResult {
+            after_key: composite_result.after_key.map(composite_key_to_json),
+            buckets,
+        }
+    }
+}
+
+fn composite_key_to_json(composite_key: CompositeKey) -> JsonMap<String, JsonValue> {
+    composite_key
+        .into_iter()
+        .map(|(source_name, value_opt)| {
+            let value_json = match value_opt {
+                Some(CompositeKeyValue::Bool(value)) => JsonValue::from(value),
+                Some(CompositeKeyValue::U64(value)) => JsonValue::from(value),
+                Some(CompositeKeyValue::I64(value)) => JsonValue::from(value),
+                Some(CompositeKeyValue::F64(value)) => JsonValue::from(value),
+                Some(CompositeKeyValue::Str(value)) => JsonValue::from(value),
+                None => JsonValue::Null,
+            };
+            (source_name, value_json)
+        })
+        .collect()
+}
+
 /// SearchResponseRest represents the response returned by the REST search API
 /// and is meant to be serialized into JSON.
 #[derive(Serialize, PartialEq, Debug, utoipa::ToSchema)]
@@ -58,6 +138,13 @@ pub struct SearchResponseRest {
     #[schema(value_type = Object)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub aggregations: Option<AggregationResults>,
+    /// Whether the search timed out, in which case the results only cover the splits searched
+    /// before the timeout.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+    /// Breakdown of the execution of the search, if requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<SearchProfile>,
 }
 
 impl TryFrom<SearchResponse> for SearchResponseRest {
@@ -109,6 +196,8 @@ impl TryFrom<SearchResponse> for SearchResponseRest {
             elapsed_time_micros: search_response.elapsed_time_micros,
             errors: search_response.errors,
             aggregations: aggregations_opt,
+            timed_out: search_response.timed_out,
+            profile: search_response.profile,
         })
     }
 }
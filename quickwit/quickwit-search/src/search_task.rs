@@ -0,0 +1,364 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use quickwit_proto::search::{CancelSearchTaskResponse, LeafSearchRequest, SearchTask};
+use tokio_util::sync::CancellationToken;
+
+/// How long we remember that a search task was cancelled, so that the leaf requests of that task
+/// that reach the searcher after the cancellation are cancelled right away.
+const CANCELLED_SEARCH_TASK_RETENTION: Duration = Duration::from_secs(60);
+
+/// Reason why a search was interrupted before completion.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum SearchInterruptionCause {
+    /// The search deadline was reached.
+    TimedOut,
+    /// The search task was cancelled.
+    Cancelled,
+}
+
+/// Signals that a search should stop, either because its deadline was reached or because its
+/// task was cancelled.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SearchInterruption {
+    cancellation_token: CancellationToken,
+    deadline_opt: Option<tokio::time::Instant>,
+}
+
+impl SearchInterruption {
+    pub fn with_timeout(timeout_opt: Option<Duration>) -> Self {
+        Self {
+            cancellation_token: CancellationToken::new(),
+            deadline_opt: timeout_opt.map(|timeout| tokio::time::Instant::now() + timeout),
+        }
+    }
+
+    /// Returns the time left before the deadline, if any.
+    pub fn remaining_timeout(&self) -> Option<Duration> {
+        self.deadline_opt
+            .map(|deadline| deadline.saturating_duration_since(tokio::time::Instant::now()))
+    }
+
+    /// Resolves when the search task is cancelled.
+    pub async fn cancelled(&self) {
+        self.cancellation_token.cancelled().await
+    }
+
+    /// Resolves when the search task is cancelled or its deadline is reached, whichever comes
+    /// first.
+    pub async fn interrupted(&self) -> SearchInterruptionCause {
+        let deadline_reached = async {
+            match self.deadline_opt {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            biased;
+            _ = self.cancellation_token.cancelled() => SearchInterruptionCause::Cancelled,
+            _ = deadline_reached => SearchInterruptionCause::TimedOut,
+        }
+    }
+}
+
+struct SearchTaskRegistration {
+    search_task_id: String,
+    // Only set for root searches, which are the ones listed by the tasks API.
+    search_task_opt: Option<SearchTask>,
+    cancellation_token: CancellationToken,
+}
+
+#[derive(Default)]
+struct InnerSearchTaskRegistry {
+    next_registration_id: u64,
+    registrations: HashMap<u64, SearchTaskRegistration>,
+    cancelled_search_task_ids: HashMap<String, Instant>,
+}
+
+impl InnerSearchTaskRegistry {
+    fn gc_cancelled_search_task_ids(&mut self) {
+        self.cancelled_search_task_ids
+            .retain(|_, cancelled_at| cancelled_at.elapsed() < CANCELLED_SEARCH_TASK_RETENTION);
+    }
+}
+
+/// Keeps track of the root and leaf searches running on a searcher so that they can be listed and
+/// cancelled.
+///
+/// Root and leaf searches belonging to the same search share the same search task ID. Cancelling
+/// a search task cancels all of them.
+#[derive(Clone, Default)]
+pub struct SearchTaskRegistry {
+    inner: Arc<Mutex<InnerSearchTaskRegistry>>,
+}
+
+impl SearchTaskRegistry {
+    /// Registers a root search. The registration is removed when the returned guard is dropped.
+    pub(crate) fn register_root_search(
+        &self,
+        search_task: SearchTask,
+        timeout_opt: Option<Duration>,
+    ) -> SearchTaskGuard {
+        let search_task_id = search_task.search_task_id.clone();
+        self.register(search_task_id, Some(search_task), timeout_opt)
+    }
+
+    /// Registers a leaf search. The registration is removed when the returned guard is dropped.
+    ///
+    /// Leaf searches without a search task ID are not registered and cannot be cancelled.
+    pub(crate) fn register_leaf_search(
+        &self,
+        search_task_id: String,
+        timeout_opt: Option<Duration>,
+    ) -> SearchTaskGuard {
+        if search_task_id.is_empty() {
+            return SearchTaskGuard {
+                registry: self.clone(),
+                registration_id_opt: None,
+                search_task_id,
+                interruption: SearchInterruption::with_timeout(timeout_opt),
+            };
+        }
+        self.register(search_task_id, None, timeout_opt)
+    }
+
+    fn register(
+        &self,
+        search_task_id: String,
+        search_task_opt: Option<SearchTask>,
+        timeout_opt: Option<Duration>,
+    ) -> SearchTaskGuard {
+        let interruption = SearchInterruption::with_timeout(timeout_opt);
+        let mut inner = self.inner.lock().unwrap();
+
+        if inner
+            .cancelled_search_task_ids
+            .contains_key(&search_task_id)
+        {
+            interruption.cancellation_token.cancel();
+        }
+        let registration_id = inner.next_registration_id;
+        inner.next_registration_id += 1;
+
+        let registration = SearchTaskRegistration {
+            search_task_id: search_task_id.clone(),
+            search_task_opt,
+            cancellation_token: interruption.cancellation_token.clone(),
+        };
+        inner.registrations.insert(registration_id, registration);
+
+        SearchTaskGuard {
+            registry: self.clone(),
+            registration_id_opt: Some(registration_id),
+            search_task_id,
+            interruption,
+        }
+    }
+
+    /// Returns the root searches running on this searcher, oldest first.
+    pub(crate) fn list_root_search_tasks(&self) -> Vec<SearchTask> {
+        let inner = self.inner.lock().unwrap();
+        let mut search_tasks: Vec<SearchTask> = inner
+            .registrations
+            .values()
+            .filter_map(|registration| registration.search_task_opt.clone())
+            .collect();
+        search_tasks.sort_by_key(|search_task| search_task.start_time_millis);
+        search_tasks
+    }
+
+    /// Cancels the root and leaf searches of a search task running on this searcher, as well as
+    /// the leaf searches of that task received in the near future.
+    pub(crate) fn cancel(&self, search_task_id: &str) -> CancelSearchTaskResponse {
+        if search_task_id.is_empty() {
+            return CancelSearchTaskResponse::default();
+        }
+        let mut inner = self.inner.lock().unwrap();
+        inner.gc_cancelled_search_task_ids();
+        inner
+            .cancelled_search_task_ids
+            .insert(search_task_id.to_string(), Instant::now());
+
+        let mut cancel_search_task_response = CancelSearchTaskResponse::default();
+
+        for registration in inner.registrations.values() {
+            if registration.search_task_id != search_task_id {
+                continue;
+            }
+            registration.cancellation_token.cancel();
+
+            if let Some(search_task) = &registration.search_task_opt {
+                cancel_search_task_response
+                    .cancelled_search_tasks
+                    .push(search_task.clone());
+            } else {
+                cancel_search_task_response.num_cancelled_leaf_searches += 1;
+            }
+        }
+        cancel_search_task_response
+    }
+}
+
+/// Removes the registration of a search from the [`SearchTaskRegistry`] when dropped.
+pub(crate) struct SearchTaskGuard {
+    registry: SearchTaskRegistry,
+    registration_id_opt: Option<u64>,
+    search_task_id: String,
+    interruption: SearchInterruption,
+}
+
+impl SearchTaskGuard {
+    pub fn interruption(&self) -> &SearchInterruption {
+        &self.interruption
+    }
+
+    /// Tags a leaf request with the search task ID and the time left before the deadline.
+    pub fn prepare_leaf_request(&self, leaf_search_request: &mut LeafSearchRequest) {
+        leaf_search_request.search_task_id = self.search_task_id.clone();
+        leaf_search_request.timeout_millis = self
+            .interruption
+            .remaining_timeout()
+            .map(|remaining_timeout| remaining_timeout.as_millis() as u64);
+    }
+}
+
+impl Drop for SearchTaskGuard {
+    fn drop(&mut self) {
+        let Some(registration_id) = self.registration_id_opt else {
+            return;
+        };
+        self.registry
+            .inner
+            .lock()
+            .unwrap()
+            .registrations
+            .remove(&registration_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search_task_for_test(search_task_id: &str, start_time_millis: i64) -> SearchTask {
+        SearchTask {
+            search_task_id: search_task_id.to_string(),
+            start_time_millis,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_task_registry_list_and_cancel() {
+        let registry = SearchTaskRegistry::default();
+        let root_guard_1 = registry.register_root_search(search_task_for_test("task-1", 2), None);
+        let root_guard_2 = registry.register_root_search(search_task_for_test("task-2", 1), None);
+        let leaf_guard_1 = registry.register_leaf_search("task-1".to_string(), None);
+
+        let search_task_ids: Vec<String> = registry
+            .list_root_search_tasks()
+            .into_iter()
+            .map(|search_task| search_task.search_task_id)
+            .collect();
+        assert_eq!(search_task_ids, ["task-2", "task-1"]);
+
+        let cancel_search_task_response = registry.cancel("task-1");
+        assert_eq!(cancel_search_task_response.cancelled_search_tasks.len(), 1);
+        assert_eq!(cancel_search_task_response.num_cancelled_leaf_searches, 1);
+
+        assert_eq!(
+            root_guard_1.interruption().interrupted().await,
+            SearchInterruptionCause::Cancelled
+        );
+        leaf_guard_1.interruption().cancelled().await;
+        assert!(
+            !root_guard_2
+                .interruption()
+                .cancellation_token
+                .is_cancelled()
+        );
+
+        // Leaf searches of a cancelled task received after the cancellation are cancelled too.
+        let leaf_guard_2 = registry.register_leaf_search("task-1".to_string(), None);
+        assert!(
+            leaf_guard_2
+                .interruption()
+                .cancellation_token
+                .is_cancelled()
+        );
+
+        drop(root_guard_1);
+        drop(root_guard_2);
+        drop(leaf_guard_1);
+        drop(leaf_guard_2);
+        assert!(registry.list_root_search_tasks().is_empty());
+        assert!(registry.inner.lock().unwrap().registrations.is_empty());
+    }
+
+    #[test]
+    fn test_search_task_registry_ignores_empty_search_task_id() {
+        let registry = SearchTaskRegistry::default();
+        let leaf_guard_1 = registry.register_leaf_search(String::new(), None);
+        assert!(registry.inner.lock().unwrap().registrations.is_empty());
+
+        let cancel_search_task_response = registry.cancel("");
+        assert_eq!(cancel_search_task_response.num_cancelled_leaf_searches, 0);
+        assert!(
+            !leaf_guard_1
+                .interruption()
+                .cancellation_token
+                .is_cancelled()
+        );
+        let leaf_guard_2 = registry.register_leaf_search(String::new(), None);
+        assert!(
+            !leaf_guard_2
+                .interruption()
+                .cancellation_token
+                .is_cancelled()
+        );
+        assert!(
+            registry
+                .inner
+                .lock()
+                .unwrap()
+                .cancelled_search_task_ids
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_task_timeout() {
+        let registry = SearchTaskRegistry::default();
+        let search_task_guard = registry.register_root_search(
+            search_task_for_test("task", 0),
+            Some(Duration::from_millis(50)),
+        );
+        let mut leaf_search_request = LeafSearchRequest::default();
+        search_task_guard.prepare_leaf_request(&mut leaf_search_request);
+        assert_eq!(leaf_search_request.search_task_id, "task");
+        assert!(leaf_search_request.timeout_millis.unwrap() <= 50);
+
+        assert_eq!(
+            search_task_guard.interruption().interrupted().await,
+            SearchInterruptionCause::TimedOut
+        );
+        let mut leaf_search_request = LeafSearchRequest::default();
+        search_task_guard.prepare_leaf_request(&mut leaf_search_request);
+        assert_eq!(leaf_search_request.timeout_millis, Some(0));
+    }
+}
@@ -22,26 +22,37 @@ use quickwit_config::SearcherConfig;
 use quickwit_doc_mapper::DocMapper;
 use quickwit_proto::metastore::MetastoreServiceClient;
 use quickwit_proto::search::{
-    FetchDocsRequest, FetchDocsResponse, GetKvRequest, Hit, LeafListFieldsRequest,
-    LeafListTermsRequest, LeafListTermsResponse, LeafSearchRequest, LeafSearchResponse,
-    ListFieldsRequest, ListFieldsResponse, ListTermsRequest, ListTermsResponse, PutKvRequest,
+    AsyncSearchResponse, CancelSearchTaskRequest, CancelSearchTaskResponse,
+    ClosePointInTimeRequest, ClosePointInTimeResponse, DeleteAsyncSearchRequest,
+    DeleteAsyncSearchResponse, FetchDocsRequest, FetchDocsResponse, GetAsyncSearchRequest,
+    GetKvRequest, Hit, KvStore, LeafExportRequest, LeafListFieldsRequest, LeafListTermsRequest,
+    LeafListTermsResponse, LeafSearchRequest, LeafSearchResponse, ListFieldsRequest,
+    ListFieldsResponse, ListSearchTasksRequest, ListSearchTasksResponse, ListTermsRequest,
+    ListTermsResponse, OpenPointInTimeRequest, OpenPointInTimeResponse, PutKvRequest,
     ReportSplitsRequest, ReportSplitsResponse, ScrollRequest, SearchPlanResponse, SearchRequest,
-    SearchResponse, SnippetRequest,
+    SearchResponse, SnippetRequest, SubmitAsyncSearchRequest,
 };
 use quickwit_storage::{
     MemorySizedCache, QuickwitCache, SplitCache, StorageCache, StorageResolver,
 };
 use tantivy::aggregation::AggregationLimitsGuard;
 
+use crate::async_search::{
+    root_delete_async_search, root_get_async_search, root_submit_async_search,
+};
+use crate::export::{ExportRequest, ExportStream, LeafExportStream, leaf_export, root_export};
 use crate::leaf::multi_index_leaf_search;
 use crate::leaf_cache::LeafSearchCache;
 use crate::list_fields::{leaf_list_fields, root_list_fields};
 use crate::list_fields_cache::ListFieldsCache;
 use crate::list_terms::{leaf_list_terms, root_list_terms};
 use crate::metrics_trackers::LeafSearchMetricsFuture;
+use crate::point_in_time::{root_close_point_in_time, root_open_point_in_time};
 use crate::root::fetch_docs_phase;
+use crate::root_cache::RootSearchCache;
 use crate::scroll_context::{MiniKV, ScrollContext, ScrollKeyAndStartOffset};
 use crate::search_permit_provider::SearchPermitProvider;
+use crate::search_task::SearchTaskRegistry;
 use crate::{ClusterClient, SearchError, fetch_docs, root_search, search_plan};
 
 #[derive(Clone)]
@@ -52,6 +63,7 @@ pub struct SearchServiceImpl {
     cluster_client: ClusterClient,
     searcher_context: Arc<SearcherContext>,
     local_kv_store: MiniKV,
+    async_search_kv_store: MiniKV,
 }
 
 /// Trait representing a search service.
@@ -105,6 +117,63 @@ pub trait SearchService: 'static + Send + Sync {
     /// Performs a scroll request.
     async fn scroll(&self, scroll_request: ScrollRequest) -> crate::Result<SearchResponse>;
 
+    /// Opens a point in time, pinning the splits currently published in the targeted indexes.
+    /// Search requests carrying the returned point in time ID run on these splits only.
+    async fn open_point_in_time(
+        &self,
+        open_request: OpenPointInTimeRequest,
+    ) -> crate::Result<OpenPointInTimeResponse>;
+
+    /// Closes a point in time, releasing its context.
+    async fn close_point_in_time(
+        &self,
+        close_request: ClosePointInTimeRequest,
+    ) -> crate::Result<ClosePointInTimeResponse>;
+
+    /// Submits a search running in the background on this searcher. Its progress and results
+    /// are stored in the cluster KV store so that any searcher can serve them.
+    async fn submit_async_search(
+        &self,
+        submit_request: SubmitAsyncSearchRequest,
+    ) -> crate::Result<AsyncSearchResponse>;
+
+    /// Returns the progress or the results of an async search.
+    async fn get_async_search(
+        &self,
+        get_request: GetAsyncSearchRequest,
+    ) -> crate::Result<AsyncSearchResponse>;
+
+    /// Deletes an async search, cancelling it if it is still running.
+    async fn delete_async_search(
+        &self,
+        delete_request: DeleteAsyncSearchRequest,
+    ) -> crate::Result<DeleteAsyncSearchResponse>;
+
+    /// Lists the root searches running in the cluster.
+    async fn root_list_search_tasks(
+        &self,
+        list_request: ListSearchTasksRequest,
+    ) -> crate::Result<ListSearchTasksResponse>;
+
+    /// Lists the root searches running on this searcher.
+    async fn leaf_list_search_tasks(
+        &self,
+        list_request: ListSearchTasksRequest,
+    ) -> crate::Result<ListSearchTasksResponse>;
+
+    /// Cancels a search task on all the searchers of the cluster, aborting its root and leaf
+    /// searches.
+    async fn root_cancel_search_task(
+        &self,
+        cancel_request: CancelSearchTaskRequest,
+    ) -> crate::Result<CancelSearchTaskResponse>;
+
+    /// Cancels the root and leaf searches of a search task running on this searcher.
+    async fn leaf_cancel_search_task(
+        &self,
+        cancel_request: CancelSearchTaskRequest,
+    ) -> crate::Result<CancelSearchTaskResponse>;
+
     /// Stores a Key value in the local cache.
     /// This operation is not distributed. The distribution logic lives in
     /// the `ClusterClient`.
@@ -132,6 +201,17 @@ pub trait SearchService: 'static + Send + Sync {
 
     /// Describe how a search would be processed.
     async fn search_plan(&self, request: SearchRequest) -> crate::Result<SearchPlanResponse>;
+
+    /// Root export API.
+    /// Exports all the documents matching a search request as a stream of pages, dispatching
+    /// the calls to `LeafExport` split by split.
+    async fn root_export(&self, export_request: ExportRequest) -> crate::Result<ExportStream>;
+
+    /// Streams the documents of a split matching a query, page by page.
+    async fn leaf_export(
+        &self,
+        leaf_export_request: LeafExportRequest,
+    ) -> crate::Result<LeafExportStream>;
 }
 
 impl SearchServiceImpl {
@@ -148,6 +228,14 @@ impl SearchServiceImpl {
             cluster_client,
             searcher_context,
             local_kv_store: MiniKV::default(),
+            async_search_kv_store: MiniKV::for_async_searches(),
+        }
+    }
+
+    fn kv_store(&self, store: KvStore) -> &MiniKV {
+        match store {
+            KvStore::SearchContext => &self.local_kv_store,
+            KvStore::AsyncSearch => &self.async_search_kv_store,
         }
     }
 }
@@ -215,6 +303,7 @@ impl SearchService for SearchServiceImpl {
             &fetch_docs_request.split_offsets,
             doc_mapper,
             snippet_request_opt,
+            fetch_docs_request.source_filter.as_ref(),
         )
         .await?;
 
@@ -261,15 +350,114 @@ impl SearchService for SearchServiceImpl {
         scroll(scroll_request, &self.cluster_client, &self.searcher_context).await
     }
 
+    async fn open_point_in_time(
+        &self,
+        open_request: OpenPointInTimeRequest,
+    ) -> crate::Result<OpenPointInTimeResponse> {
+        root_open_point_in_time(open_request, self.metastore.clone(), &self.cluster_client).await
+    }
+
+    async fn close_point_in_time(
+        &self,
+        close_request: ClosePointInTimeRequest,
+    ) -> crate::Result<ClosePointInTimeResponse> {
+        root_close_point_in_time(close_request, self.metastore.clone(), &self.cluster_client).await
+    }
+
+    async fn submit_async_search(
+        &self,
+        submit_request: SubmitAsyncSearchRequest,
+    ) -> crate::Result<AsyncSearchResponse> {
+        root_submit_async_search(
+            submit_request,
+            self.searcher_context.clone(),
+            self.metastore.clone(),
+            &self.cluster_client,
+        )
+        .await
+    }
+
+    async fn get_async_search(
+        &self,
+        get_request: GetAsyncSearchRequest,
+    ) -> crate::Result<AsyncSearchResponse> {
+        root_get_async_search(get_request, &self.cluster_client).await
+    }
+
+    async fn delete_async_search(
+        &self,
+        delete_request: DeleteAsyncSearchRequest,
+    ) -> crate::Result<DeleteAsyncSearchResponse> {
+        root_delete_async_search(delete_request, &self.cluster_client).await
+    }
+
+    async fn root_list_search_tasks(
+        &self,
+        _list_request: ListSearchTasksRequest,
+    ) -> crate::Result<ListSearchTasksResponse> {
+        Ok(self.cluster_client.list_search_tasks().await)
+    }
+
+    async fn leaf_list_search_tasks(
+        &self,
+        _list_request: ListSearchTasksRequest,
+    ) -> crate::Result<ListSearchTasksResponse> {
+        let search_tasks = self
+            .searcher_context
+            .search_task_registry
+            .list_root_search_tasks();
+        Ok(ListSearchTasksResponse { search_tasks })
+    }
+
+    async fn root_cancel_search_task(
+        &self,
+        cancel_request: CancelSearchTaskRequest,
+    ) -> crate::Result<CancelSearchTaskResponse> {
+        if cancel_request.search_task_id.is_empty() {
+            return Err(SearchError::InvalidArgument(
+                "search task ID must not be empty".to_string(),
+            ));
+        }
+        let cancel_response = self
+            .cluster_client
+            .cancel_search_task(&cancel_request.search_task_id)
+            .await;
+        Ok(cancel_response)
+    }
+
+    async fn leaf_cancel_search_task(
+        &self,
+        cancel_request: CancelSearchTaskRequest,
+    ) -> crate::Result<CancelSearchTaskResponse> {
+        if cancel_request.search_task_id.is_empty() {
+            return Err(SearchError::InvalidArgument(
+                "search task ID must not be empty".to_string(),
+            ));
+        }
+        let cancel_response = self
+            .searcher_context
+            .search_task_registry
+            .cancel(&cancel_request.search_task_id);
+        Ok(cancel_response)
+    }
+
     async fn put_kv(&self, put_request: PutKvRequest) {
         let ttl = Duration::from_secs(put_request.ttl_secs as u64);
-        self.local_kv_store
-            .put(put_request.key, put_request.payload, ttl)
+        self.kv_store(put_request.store())
+            .put(
+                put_request.key,
+                put_request.payload,
+                ttl,
+                put_request.version,
+            )
             .await;
     }
 
     async fn get_kv(&self, get_request: GetKvRequest) -> Option<Vec<u8>> {
-        let payload: Vec<u8> = self.local_kv_store.get(&get_request.key).await?;
+        let payload: Vec<u8> = self
+            .kv_store(get_request.store())
+            .get(&get_request.key)
+            .await?;
         Some(payload)
     }
 
@@ -317,6 +505,26 @@ impl SearchService for SearchServiceImpl {
         let search_plan = search_plan(search_request, self.metastore.clone()).await?;
         Ok(search_plan)
     }
+
+    async fn root_export(&self, export_request: ExportRequest) -> crate::Result<ExportStream> {
+        root_export(export_request, self.metastore.clone(), &self.cluster_client).await
+    }
+
+    async fn leaf_export(
+        &self,
+        leaf_export_request: LeafExportRequest,
+    ) -> crate::Result<LeafExportStream> {
+        let index_uri = Uri::from_str(&leaf_export_request.index_uri)?;
+        let storage = self.storage_resolver.resolve(&index_uri).await?;
+        let doc_mapper = deserialize_doc_mapper(&leaf_export_request.doc_mapper)?;
+        leaf_export(
+            self.searcher_context.clone(),
+            leaf_export_request,
+            storage,
+            doc_mapper,
+        )
+        .await
+    }
 }
 
 pub(crate) async fn scroll(
@@ -363,6 +571,7 @@ pub(crate) async fn scroll(
         &scroll_context.split_metadatas[..],
         &scroll_context.search_request,
         cluster_client,
+        None,
     )
     .await?;
 
@@ -391,6 +600,8 @@ pub(crate) async fn scroll(
         aggregation_postcard: None,
         failed_splits: scroll_context.failed_splits,
         num_successful_splits: scroll_context.num_successful_splits,
+        timed_out: false,
+        profile: None,
     })
 }
 /// [`SearcherContext`] provides a common set of variables
@@ -407,12 +618,16 @@ pub struct SearcherContext {
     pub split_footer_cache: MemorySizedCache<String>,
     /// Recent sub-query cache.
     pub leaf_search_cache: LeafSearchCache,
+    /// Root search response cache. `None` if no root search cache is configured.
+    pub root_search_cache: Option<RootSearchCache>,
     /// Search split cache. `None` if no split cache is configured.
     pub split_cache_opt: Option<Arc<SplitCache>>,
     /// List fields cache. Caches the list fields response for a given split.
     pub list_fields_cache: ListFieldsCache,
     /// The aggregation limits are passed to limit the memory usage.
     pub aggregation_limit: AggregationLimitsGuard,
+    /// Root and leaf searches in flight, which can be listed and cancelled.
+    pub search_task_registry: SearchTaskRegistry,
 }
 
 impl std::fmt::Debug for SearcherContext {
@@ -438,14 +653,20 @@ impl SearcherContext {
             capacity_in_bytes,
             &quickwit_storage::STORAGE_METRICS.split_footer_cache,
         );
-        let leaf_search_split_semaphore = SearchPermitProvider::new(
+        let leaf_search_split_semaphore = SearchPermitProvider::with_tenant_limits(
             searcher_config.max_num_concurrent_split_searches,
             searcher_config.warmup_memory_budget,
+            searcher_config.default_tenant_limits.clone(),
+            searcher_config.tenant_limits.clone(),
         );
         let fast_field_cache_capacity = searcher_config.fast_field_cache_capacity.as_u64() as usize;
         let storage_long_term_cache = Arc::new(QuickwitCache::new(fast_field_cache_capacity));
         let leaf_search_cache =
             LeafSearchCache::new(searcher_config.partial_request_cache_capacity.as_u64() as usize);
+        let root_search_cache = searcher_config
+            .root_search_cache
+            .as_ref()
+            .map(RootSearchCache::new);
         let list_fields_cache =
             ListFieldsCache::new(searcher_config.partial_request_cache_capacity.as_u64() as usize);
         let aggregation_limit = AggregationLimitsGuard::new(
@@ -459,9 +680,11 @@ impl SearcherContext {
             search_permit_provider: leaf_search_split_semaphore,
             split_footer_cache: global_split_footer_cache,
             leaf_search_cache,
+            root_search_cache,
             list_fields_cache,
             split_cache_opt,
             aggregation_limit,
+            search_task_registry: SearchTaskRegistry::default(),
         }
     }
 
@@ -0,0 +1,214 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use quickwit_proto::search::SourceFilter;
+use serde_json::{Map as JsonObject, Value as JsonValue};
+
+/// Removes the fields of a document that are not selected by the source filter.
+///
+/// Fields are identified by their full path, with the object keys joined by `.`. A field matching
+/// an include pattern is kept along with all its children, unless they match an exclude pattern.
+/// Objects that end up empty because none of their children are included are removed.
+pub(crate) fn filter_source(
+    doc_json: &mut JsonObject<String, JsonValue>,
+    source_filter: &SourceFilter,
+) {
+    if source_filter.includes.is_empty() && source_filter.excludes.is_empty() {
+        return;
+    }
+    filter_json_object(
+        doc_json,
+        "",
+        &source_filter.includes,
+        &source_filter.excludes,
+    );
+}
+
+fn filter_json_object(
+    json_obj: &mut JsonObject<String, JsonValue>,
+    path_prefix: &str,
+    includes: &[String],
+    excludes: &[String],
+) {
+    json_obj.retain(|key, value| {
+        let path = if path_prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{path_prefix}.{key}")
+        };
+        if matches_any(excludes, &path) {
+            return false;
+        }
+        let is_included = includes.is_empty() || matches_any(includes, &path);
+        // Once a field is included, all its children are included as well.
+        let children_includes: &[String] = if is_included { &[] } else { includes };
+        filter_json_value(value, &path, children_includes, excludes, is_included)
+    });
+}
+
+/// Filters the children of a JSON value. Returns `false` if the value should be removed.
+fn filter_json_value(
+    value: &mut JsonValue,
+    path: &str,
+    includes: &[String],
+    excludes: &[String],
+    is_included: bool,
+) -> bool {
+    match value {
+        JsonValue::Object(json_obj) => {
+            filter_json_object(json_obj, path, includes, excludes);
+            is_included || !json_obj.is_empty()
+        }
+        JsonValue::Array(values) => {
+            values.retain_mut(|value| {
+                filter_json_value(value, path, includes, excludes, is_included)
+            });
+            is_included || !values.is_empty()
+        }
+        _ => is_included,
+    }
+}
+
+fn matches_any(patterns: &[String], path: &str) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| wildcard_match(pattern.as_bytes(), path.as_bytes()))
+}
+
+/// Matches a pattern in which `*` stands for any sequence of characters, including `.`.
+pub(crate) fn wildcard_match(pattern: &[u8], text: &[u8]) -> bool {
+    let mut pattern_pos = 0;
+    let mut text_pos = 0;
+    // Position of the last `*` in the pattern and of the text position it was matched against.
+    let mut backtrack_opt: Option<(usize, usize)> = None;
+
+    while text_pos < text.len() {
+        if pattern_pos < pattern.len() && pattern[pattern_pos] == b'*' {
+            backtrack_opt = Some((pattern_pos, text_pos));
+            pattern_pos += 1;
+        } else if pattern_pos < pattern.len() && pattern[pattern_pos] == text[text_pos] {
+            pattern_pos += 1;
+            text_pos += 1;
+        } else if let Some((star_pos, star_text_pos)) = backtrack_opt {
+            // Let the last `*` absorb one more character.
+            pattern_pos = star_pos + 1;
+            text_pos = star_text_pos + 1;
+            backtrack_opt = Some((star_pos, text_pos));
+        } else {
+            return false;
+        }
+    }
+    pattern[pattern_pos..].iter().all(|byte| *byte == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn test_filter_source_aux(includes: &[&str], excludes: &[&str], expected: JsonValue) {
+        let JsonValue::Object(mut doc_json) = json!({
+            "timestamp": 1,
+            "severity": "ERROR",
+            "app": {
+                "id": 123,
+                "name": "foo",
+                "tags": [{"key": "env", "value": "prod"}]
+            },
+            "attributes": {"host": "bar"}
+        }) else {
+            unreachable!()
+        };
+        let source_filter = SourceFilter {
+            includes: includes.iter().map(|include| include.to_string()).collect(),
+            excludes: excludes.iter().map(|exclude| exclude.to_string()).collect(),
+            disabled: false,
+        };
+        filter_source(&mut doc_json, &source_filter);
+        assert_eq!(JsonValue::Object(doc_json), expected);
+    }
+
+    #[test]
+    fn test_filter_source_includes() {
+        test_filter_source_aux(
+            &["timestamp", "app.name"],
+            &[],
+            json!({"timestamp": 1, "app": {"name": "foo"}}),
+        );
+        test_filter_source_aux(
+            &["app"],
+            &[],
+            json!({
+                "app": {"id": 123, "name": "foo", "tags": [{"key": "env", "value": "prod"}]}
+            }),
+        );
+        test_filter_source_aux(
+            &["app.tags.key"],
+            &[],
+            json!({"app": {"tags": [{"key": "env"}]}}),
+        );
+        test_filter_source_aux(&["does_not_exist"], &[], json!({}));
+    }
+
+    #[test]
+    fn test_filter_source_includes_wildcards() {
+        test_filter_source_aux(
+            &["*.name", "sev*"],
+            &[],
+            json!({"severity": "ERROR", "app": {"name": "foo"}}),
+        );
+        test_filter_source_aux(
+            &["a*.*"],
+            &[],
+            json!({
+                "app": {"id": 123, "name": "foo", "tags": [{"key": "env", "value": "prod"}]},
+                "attributes": {"host": "bar"}
+            }),
+        );
+    }
+
+    #[test]
+    fn test_filter_source_excludes() {
+        test_filter_source_aux(
+            &[],
+            &["app", "attributes.*"],
+            json!({"timestamp": 1, "severity": "ERROR", "attributes": {}}),
+        );
+        test_filter_source_aux(&[], &["*"], json!({}));
+    }
+
+    #[test]
+    fn test_filter_source_includes_and_excludes() {
+        test_filter_source_aux(
+            &["app"],
+            &["app.tags", "app.id"],
+            json!({"app": {"name": "foo"}}),
+        );
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match(b"", b""));
+        assert!(wildcard_match(b"*", b""));
+        assert!(wildcard_match(b"*", b"app.name"));
+        assert!(wildcard_match(b"app.name", b"app.name"));
+        assert!(wildcard_match(b"app.*", b"app.name"));
+        assert!(wildcard_match(b"*name", b"app.name"));
+        assert!(wildcard_match(b"a*n*e", b"app.name"));
+        assert!(!wildcard_match(b"app", b"app.name"));
+        assert!(!wildcard_match(b"app.*", b"app"));
+        assert!(!wildcard_match(b"*.id", b"app.name"));
+    }
+}
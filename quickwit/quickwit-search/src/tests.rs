@@ -21,8 +21,8 @@ use quickwit_doc_mapper::tag_pruning::extract_tags_from_query;
 use quickwit_indexing::TestSandbox;
 use quickwit_opentelemetry::otlp::TraceId;
 use quickwit_proto::search::{
-    LeafListTermsResponse, ListTermsRequest, SearchRequest, SortByValue, SortField, SortOrder,
-    SortValue,
+    CollapseRequest, Hit, LeafListTermsResponse, ListTermsRequest, SearchRequest, SnippetOptions,
+    SortByValue, SortField, SortOrder, SortValue,
 };
 use quickwit_query::query_ast::{
     QueryAst, qast_helper, qast_json_helper, query_ast_from_user_text,
@@ -36,6 +36,7 @@ use self::leaf::single_doc_mapping_leaf_search;
 use super::*;
 use crate::find_trace_ids_collector::Span;
 use crate::list_terms::leaf_list_terms;
+use crate::search_task::SearchInterruption;
 use crate::service::SearcherContext;
 use crate::single_node_search;
 
@@ -82,6 +83,246 @@ async fn test_single_node_simple() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_single_node_search_profile() -> anyhow::Result<()> {
+    let index_id = "single-node-search-profile";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+    test_sandbox
+        .add_documents(vec![json!({"body": "hello"}), json!({"body": "world"})])
+        .await?;
+    test_sandbox
+        .add_documents(vec![json!({"body": "hello, world"})])
+        .await?;
+
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: qast_json_helper("hello", &["body"]),
+        max_hits: 10,
+        ..Default::default()
+    };
+    let search_response = single_node_search(
+        search_request.clone(),
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    assert!(search_response.profile.is_none());
+
+    let search_response = single_node_search(
+        SearchRequest {
+            profile: true,
+            ..search_request
+        },
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    assert_eq!(search_response.num_hits, 2);
+
+    let search_profile = search_response.profile.unwrap();
+    assert_eq!(search_profile.num_targeted_splits, 2);
+    assert_eq!(search_profile.num_pruned_splits, 0);
+    assert_eq!(search_profile.leaves.len(), 1);
+
+    let leaf_profile = &search_profile.leaves[0];
+    assert_eq!(leaf_profile.num_splits, 2);
+    assert!(leaf_profile.leaf_search_micros > 0);
+    assert!(leaf_profile.fetch_docs_micros > 0);
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_single_node_collapse_inner_hits() -> anyhow::Result<()> {
+    let index_id = "single-node-collapse-inner-hits";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: host
+                type: text
+                tokenizer: raw
+                fast: true
+              - name: rank
+                type: u64
+                fast: true
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &[]).await?;
+    test_sandbox
+        .add_documents(vec![
+            json!({"host": "host-a", "rank": 1}),
+            json!({"host": "host-a", "rank": 5}),
+            json!({"host": "host-b", "rank": 4}),
+        ])
+        .await?;
+    test_sandbox
+        .add_documents(vec![
+            json!({"host": "host-a", "rank": 3}),
+            json!({"host": "host-b", "rank": 2}),
+            json!({"rank": 6}),
+        ])
+        .await?;
+
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: serde_json::to_string(&QueryAst::MatchAll)?,
+        max_hits: 10,
+        sort_fields: vec![SortField {
+            field_name: "rank".to_string(),
+            sort_order: SortOrder::Desc as i32,
+            sort_datetime_format: None,
+        }],
+        collapse: Some(CollapseRequest {
+            field: "host".to_string(),
+            inner_hits_size: 2,
+            group_size: 0,
+        }),
+        ..Default::default()
+    };
+    let search_response = single_node_search(
+        search_request,
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    assert_eq!(search_response.num_hits, 6);
+
+    let hit_rank = |hit: &Hit| -> u64 {
+        let doc: JsonValue = serde_json::from_str(&hit.json).unwrap();
+        doc["rank"].as_u64().unwrap()
+    };
+    let hits_and_inner_hits: Vec<(u64, u64, Vec<u64>)> = search_response
+        .hits
+        .iter()
+        .map(|hit| {
+            let inner_hits = hit.inner_hits.as_ref().unwrap();
+            let inner_hit_ranks = inner_hits.hits.iter().map(hit_rank).collect();
+            (hit_rank(hit), inner_hits.num_hits, inner_hit_ranks)
+        })
+        .collect();
+    assert_eq!(
+        hits_and_inner_hits,
+        [(6, 1, vec![6]), (5, 3, vec![5, 3]), (4, 2, vec![4, 2])]
+    );
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_single_node_knn_search() -> anyhow::Result<()> {
+    let index_id = "single-node-knn-search";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+              - name: embedding
+                type: dense_vector
+                dims: 2
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+    test_sandbox
+        .add_documents(vec![
+            json!({"body": "printer jam", "embedding": [1.0, 0.0]}),
+            json!({"body": "login issue", "embedding": [0.0, 1.0]}),
+        ])
+        .await?;
+    test_sandbox
+        .add_documents(vec![
+            json!({"body": "printer offline", "embedding": [0.8, 0.6]}),
+            json!({"body": "printer without vector"}),
+        ])
+        .await?;
+
+    let hit_bodies = |search_response: &SearchResponse| -> Vec<String> {
+        search_response
+            .hits
+            .iter()
+            .map(|hit| {
+                let doc: JsonValue = serde_json::from_str(&hit.json).unwrap();
+                doc["body"].as_str().unwrap().to_string()
+            })
+            .collect()
+    };
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: serde_json::to_string(&QueryAst::MatchAll)?,
+        max_hits: 10,
+        knn: Some(json!({"field": "embedding", "query_vector": [1.0, 0.0], "k": 2}).to_string()),
+        ..Default::default()
+    };
+    let search_response = single_node_search(
+        search_request.clone(),
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    assert_eq!(search_response.num_hits, 2);
+    assert_eq!(
+        hit_bodies(&search_response),
+        ["printer jam", "printer offline"]
+    );
+
+    // The kNN filter is resolved against the default search fields.
+    let knn = json!({
+        "field": "embedding",
+        "query_vector": [1.0, 0.0],
+        "k": 2,
+        "filter": query_ast_from_user_text("login", None),
+    });
+    let search_response = single_node_search(
+        SearchRequest {
+            knn: Some(knn.to_string()),
+            ..search_request.clone()
+        },
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    assert_eq!(hit_bodies(&search_response), ["login issue"]);
+
+    let knn = json!({"field": "embedding", "query_vector": [0.0, 1.0], "k": 1, "rrf": {}});
+    let search_response = single_node_search(
+        SearchRequest {
+            query_ast: qast_json_helper("printer", &["body"]),
+            knn: Some(knn.to_string()),
+            ..search_request.clone()
+        },
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    assert_eq!(search_response.num_hits, 4);
+    let mut bodies = hit_bodies(&search_response);
+    bodies.sort();
+    assert_eq!(
+        bodies,
+        [
+            "login issue",
+            "printer jam",
+            "printer offline",
+            "printer without vector"
+        ]
+    );
+
+    let knn = json!({"field": "body", "query_vector": [1.0, 0.0], "k": 2});
+    let search_error = single_node_search(
+        SearchRequest {
+            knn: Some(knn.to_string()),
+            ..search_request
+        },
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(search_error, SearchError::InvalidArgument(_)));
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_single_node_termset() -> anyhow::Result<()> {
     let index_id = "single-node-termset-1";
@@ -178,6 +419,195 @@ async fn test_single_search_with_snippet() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_single_search_with_snippet_options() -> anyhow::Result<()> {
+    let index_id = "single-node-with-snippet-options";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: title
+                type: text
+              - name: body
+                type: text
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+    let docs = vec![
+        json!({"title": "beagle", "body": "The beagle is a breed of small scent hound."}),
+        json!({"title": "lisa", "body": "Lisa is a character in `The Simpsons` animated tv series."}),
+    ];
+    test_sandbox.add_documents(docs.clone()).await?;
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: qast_json_helper("beagle", &["title", "body"]),
+        snippet_fields: vec!["title".to_string(), "body".to_string()],
+        snippet_options: Some(SnippetOptions {
+            pre_tag: Some("<em>".to_string()),
+            post_tag: Some("</em>".to_string()),
+            fragment_size: Some(20),
+            number_of_fragments: None,
+            lenient_fields: false,
+        }),
+        max_hits: 2,
+        ..Default::default()
+    };
+    let single_node_result = single_node_search(
+        search_request,
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    assert_eq!(single_node_result.num_hits, 1);
+
+    let highlight_json: JsonValue =
+        serde_json::from_str(single_node_result.hits[0].snippet.as_ref().unwrap())?;
+    let expected_json: JsonValue = json!({
+        "title": ["<em>beagle</em>"],
+        "body": ["The <em>beagle</em> is a"]
+    });
+    assert_json_eq!(highlight_json, expected_json);
+
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_single_search_with_lenient_snippet_fields() -> anyhow::Result<()> {
+    let index_id = "single-node-with-lenient-snippet-fields";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: title
+                type: text
+              - name: body
+                type: text
+              - name: count
+                type: u64
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+    let docs = vec![json!({"title": "beagle", "body": "The beagle is a hound.", "count": 1})];
+    test_sandbox.add_documents(docs.clone()).await?;
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: qast_json_helper("beagle", &["title", "body"]),
+        // The pattern is expanded, while the non-text and unknown fields are ignored.
+        snippet_fields: vec![
+            "tit*".to_string(),
+            "count".to_string(),
+            "doesnotexist".to_string(),
+        ],
+        snippet_options: Some(SnippetOptions {
+            pre_tag: None,
+            post_tag: None,
+            fragment_size: None,
+            number_of_fragments: None,
+            lenient_fields: true,
+        }),
+        max_hits: 1,
+        ..Default::default()
+    };
+    let single_node_result = single_node_search(
+        search_request.clone(),
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    assert_eq!(single_node_result.num_hits, 1);
+
+    let highlight_json: JsonValue =
+        serde_json::from_str(single_node_result.hits[0].snippet.as_ref().unwrap())?;
+    let expected_json: JsonValue = json!({"title": ["<b>beagle</b>"]});
+    assert_json_eq!(highlight_json, expected_json);
+
+    // Without `lenient_fields`, the field pattern fails the search.
+    let mut strict_search_request = search_request;
+    strict_search_request
+        .snippet_options
+        .as_mut()
+        .unwrap()
+        .lenient_fields = false;
+    let search_error = single_node_search(
+        strict_search_request,
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await
+    .unwrap_err();
+    assert!(search_error.to_string().contains("tit*"));
+
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_single_search_with_number_of_fragments() -> anyhow::Result<()> {
+    let index_id = "single-node-with-number-of-fragments";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+    let docs = vec![json!({
+        "body": ["a beagle is a hound and another beagle is a dog", "my beagle barks"]
+    })];
+    test_sandbox.add_documents(docs.clone()).await?;
+
+    let search_with_number_of_fragments = |number_of_fragments: u32| {
+        let search_request = SearchRequest {
+            index_id_patterns: vec![index_id.to_string()],
+            query_ast: qast_json_helper("beagle", &["body"]),
+            snippet_fields: vec!["body".to_string()],
+            snippet_options: Some(SnippetOptions {
+                pre_tag: None,
+                post_tag: None,
+                fragment_size: Some(20),
+                number_of_fragments: Some(number_of_fragments),
+                lenient_fields: false,
+            }),
+            max_hits: 1,
+            ..Default::default()
+        };
+        single_node_search(
+            search_request,
+            test_sandbox.metastore(),
+            test_sandbox.storage_resolver(),
+        )
+    };
+    // The limit applies to the fragments, not to the field values.
+    let single_node_result = search_with_number_of_fragments(2).await?;
+    let highlight_json: JsonValue =
+        serde_json::from_str(single_node_result.hits[0].snippet.as_ref().unwrap())?;
+    let expected_json: JsonValue = json!({
+        "body": ["a <b>beagle</b> is a hound", "and another <b>beagle</b>"]
+    });
+    assert_json_eq!(highlight_json, expected_json);
+
+    let single_node_result = search_with_number_of_fragments(3).await?;
+    let highlight_json: JsonValue =
+        serde_json::from_str(single_node_result.hits[0].snippet.as_ref().unwrap())?;
+    let expected_json: JsonValue = json!({
+        "body": [
+            "a <b>beagle</b> is a hound",
+            "and another <b>beagle</b>",
+            "my <b>beagle</b> barks"
+        ]
+    });
+    assert_json_eq!(highlight_json, expected_json);
+
+    // A `number_of_fragments` of 0 highlights the whole values.
+    let single_node_result = search_with_number_of_fragments(0).await?;
+    let highlight_json: JsonValue =
+        serde_json::from_str(single_node_result.hits[0].snippet.as_ref().unwrap())?;
+    let expected_json: JsonValue = json!({
+        "body": [
+            "a <b>beagle</b> is a hound and another <b>beagle</b> is a dog",
+            "my <b>beagle</b> barks"
+        ]
+    });
+    assert_json_eq!(highlight_json, expected_json);
+
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
 async fn slop_search_and_check(
     test_sandbox: &TestSandbox,
     index_id: &str,
@@ -1058,6 +1488,7 @@ async fn test_search_util(test_sandbox: &TestSandbox, query: &str) -> Vec<u32> {
         splits_offsets,
         test_sandbox.doc_mapper(),
         agg_limits,
+        SearchInterruption::default(),
     )
     .await
     .unwrap();
@@ -1202,7 +1633,7 @@ fn test_convert_leaf_hit_aux(
     let default_doc_mapper: DocMapper = serde_json::from_value(default_doc_mapper_json).unwrap();
     let named_field_doc = json_to_named_field_doc(document_json);
     let hit_json_str =
-        convert_document_to_json_string(named_field_doc, &default_doc_mapper).unwrap();
+        convert_document_to_json_string(named_field_doc, &default_doc_mapper, None).unwrap();
     let hit_json: JsonValue = serde_json::from_str(&hit_json_str).unwrap();
     assert_eq!(hit_json, expected_hit_json);
 }
@@ -13,23 +13,30 @@
 // limitations under the License.
 
 use std::cmp::{Ordering, Reverse};
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 
 use quickwit_common::binary_heap::TopK;
-use quickwit_proto::search::{PartialHit, SortOrder};
+use quickwit_proto::search::{CollapseGroup, CollapseRequest, PartialHit, SortOrder};
 use quickwit_proto::types::SplitId;
-use tantivy::{DocId, Score};
+use tantivy::columnar::{ColumnType, MonotonicallyMappableToU64, StrColumn};
+use tantivy::fastfield::Column;
+use tantivy::{DocId, Score, SegmentReader};
 
 use crate::collector::{
-    HitSortingMapper, SegmentPartialHit, SegmentPartialHitSortingKey,
-    SortingFieldExtractorComponent, SortingFieldExtractorPair,
+    HitSortingMapper, SegmentPartialHit, SegmentPartialHitSortingKey, SortFieldType,
+    SortingFieldExtractorComponent, SortingFieldExtractorPair, TopKGroups, collapse_group_size,
 };
 
 pub trait QuickwitSegmentTopKCollector {
     fn collect_top_k_block(&mut self, docs: &[DocId]);
     fn collect_top_k(&mut self, doc_id: DocId, score: Score);
     fn get_top_k(&self) -> Vec<PartialHit>;
+    /// Returns the number of hits of each group, if the collector collapses hits and counts them.
+    fn get_collapse_groups(&self) -> Vec<CollapseGroup> {
+        Vec::new()
+    }
 }
 
 trait IntoOptionU64 {
@@ -817,6 +824,214 @@ impl QuickwitSegmentTopKCollector for GenericQuickwitSegmentTopKCollector {
     }
 }
 
+/// Fast field column on which hits are collapsed.
+enum CollapseColumn {
+    /// Hits are grouped by term ordinal, which is only converted back to a string for the best
+    /// hits.
+    Str(StrColumn),
+    Numeric {
+        column: Column<u64>,
+        field_type: SortFieldType,
+    },
+}
+
+impl CollapseColumn {
+    fn open(field_name: &str, segment_reader: &SegmentReader) -> tantivy::Result<Self> {
+        if let Some(str_column) = segment_reader.fast_fields().str(field_name)? {
+            return Ok(CollapseColumn::Str(str_column));
+        }
+        let column_opt: Option<(Column<u64>, ColumnType)> =
+            segment_reader.fast_fields().u64_lenient(field_name)?;
+        let (column, column_type) = column_opt.unwrap_or_else(|| {
+            (
+                Column::build_empty_column(segment_reader.max_doc()),
+                ColumnType::U64,
+            )
+        });
+        let field_type = SortFieldType::try_from(column_type)?;
+        Ok(CollapseColumn::Numeric { column, field_type })
+    }
+
+    /// Returns the group of the document, or `None` if it has no value for the collapse field.
+    #[inline]
+    fn group(&self, doc_id: DocId) -> Option<u64> {
+        match self {
+            CollapseColumn::Str(str_column) => str_column.ords().first(doc_id),
+            CollapseColumn::Numeric { column, .. } => column.first(doc_id),
+        }
+    }
+
+    /// Converts a group to the collapse key, which identifies the group across splits.
+    fn collapse_key(&self, group: u64) -> Option<String> {
+        match self {
+            CollapseColumn::Str(str_column) => {
+                let mut collapse_key = String::new();
+                match str_column.ord_to_str(group, &mut collapse_key) {
+                    Ok(true) => Some(collapse_key),
+                    _ => None,
+                }
+            }
+            CollapseColumn::Numeric { field_type, .. } => {
+                let collapse_key = match field_type {
+                    SortFieldType::U64 => group.to_string(),
+                    SortFieldType::I64 | SortFieldType::DateTime => {
+                        i64::from_u64(group).to_string()
+                    }
+                    SortFieldType::F64 => f64::from_u64(group).to_string(),
+                    SortFieldType::Bool => (group != 0).to_string(),
+                };
+                Some(collapse_key)
+            }
+        }
+    }
+}
+
+/// Segment collector keeping the top-k groups of hits sharing the same value for the collapse
+/// field, with only the best hits of each group. If the collapse request has a group size, the
+/// hits of each group are also counted.
+///
+/// Collapsing is not supported with search after, so unlike
+/// [`GenericQuickwitSegmentTopKCollector`], this collector ignores it.
+pub(crate) struct CollapseSegmentTopKCollector {
+    split_id: SplitId,
+    score_extractor: SortingFieldExtractorPair,
+    collapse_column: CollapseColumn,
+    top_k_groups:
+        TopKGroups<Option<u64>, SegmentPartialHit, SegmentPartialHitSortingKey, HitSortingMapper>,
+    num_hits_per_group_opt: Option<HashMap<Option<u64>, u64>>,
+    segment_ord: u32,
+    sort_values1: Box<[Option<u64>; COLLECT_BLOCK_BUFFER_LEN]>,
+    sort_values2: Box<[Option<u64>; COLLECT_BLOCK_BUFFER_LEN]>,
+}
+
+impl CollapseSegmentTopKCollector {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        split_id: SplitId,
+        score_extractor: SortingFieldExtractorPair,
+        collapse: &CollapseRequest,
+        segment_reader: &SegmentReader,
+        leaf_max_hits: usize,
+        segment_ord: u32,
+        order1: SortOrder,
+        order2: SortOrder,
+    ) -> tantivy::Result<Self> {
+        let sort_key_mapper = HitSortingMapper { order1, order2 };
+        let collapse_column = CollapseColumn::open(&collapse.field, segment_reader)?;
+        let top_k_groups = TopKGroups::new(
+            leaf_max_hits,
+            collapse_group_size(collapse),
+            sort_key_mapper,
+        );
+        let num_hits_per_group_opt = (collapse.group_size > 0).then(HashMap::new);
+
+        Ok(CollapseSegmentTopKCollector {
+            split_id,
+            score_extractor,
+            collapse_column,
+            top_k_groups,
+            num_hits_per_group_opt,
+            segment_ord,
+            sort_values1: vec![None; COLLECT_BLOCK_BUFFER_LEN]
+                .into_boxed_slice()
+                .try_into()
+                .unwrap(),
+            sort_values2: vec![None; COLLECT_BLOCK_BUFFER_LEN]
+                .into_boxed_slice()
+                .try_into()
+                .unwrap(),
+        })
+    }
+
+    #[inline]
+    fn add_hit(&mut self, hit: SegmentPartialHit) {
+        let group = self.collapse_column.group(hit.doc_id);
+
+        if let Some(num_hits_per_group) = &mut self.num_hits_per_group_opt {
+            *num_hits_per_group.entry(group).or_default() += 1;
+        }
+        self.top_k_groups.add_entry(group, hit);
+    }
+}
+
+impl QuickwitSegmentTopKCollector for CollapseSegmentTopKCollector {
+    fn collect_top_k_block(&mut self, docs: &[DocId]) {
+        self.score_extractor.extract_typed_sort_values(
+            docs,
+            &mut self.sort_values1[..],
+            &mut self.sort_values2[..],
+        );
+        let has_first_sort = self.score_extractor.first.is_fast_field();
+        let has_second_sort = self
+            .score_extractor
+            .second
+            .as_ref()
+            .map(|extr| extr.is_fast_field())
+            .unwrap_or(false);
+
+        for (idx, doc_id) in docs.iter().cloned().enumerate() {
+            let hit = SegmentPartialHit {
+                sort_value: if has_first_sort {
+                    self.sort_values1[idx]
+                } else {
+                    None
+                },
+                sort_value2: if has_second_sort {
+                    self.sort_values2[idx]
+                } else {
+                    None
+                },
+                doc_id,
+            };
+            self.add_hit(hit);
+        }
+    }
+
+    #[inline]
+    fn collect_top_k(&mut self, doc_id: DocId, score: Score) {
+        let (sort_value, sort_value2): (Option<u64>, Option<u64>) =
+            self.score_extractor.extract_typed_sort_value(doc_id, score);
+        let hit = SegmentPartialHit {
+            sort_value,
+            sort_value2,
+            doc_id,
+        };
+        self.add_hit(hit);
+    }
+
+    fn get_top_k(&self) -> Vec<PartialHit> {
+        self.top_k_groups
+            .clone()
+            .finalize()
+            .into_iter()
+            .map(|(group, segment_partial_hit)| {
+                let mut partial_hit = segment_partial_hit.into_partial_hit(
+                    self.split_id.clone(),
+                    self.segment_ord,
+                    &self.score_extractor.first,
+                    &self.score_extractor.second,
+                );
+                partial_hit.collapse_key =
+                    group.and_then(|group| self.collapse_column.collapse_key(group));
+                partial_hit
+            })
+            .collect()
+    }
+
+    fn get_collapse_groups(&self) -> Vec<CollapseGroup> {
+        let Some(num_hits_per_group) = &self.num_hits_per_group_opt else {
+            return Vec::new();
+        };
+        num_hits_per_group
+            .iter()
+            .map(|(group, num_hits)| CollapseGroup {
+                collapse_key: group.and_then(|group| self.collapse_column.collapse_key(group)),
+                num_hits: *num_hits,
+            })
+            .collect()
+    }
+}
+
 /// Search After, but the sort values are converted to the u64 fast field representation.
 pub(crate) struct SearchAfterSegment {
     sort_value: Option<u64>,
@@ -0,0 +1,792 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::StreamExt;
+use quickwit_common::uri::Uri;
+use quickwit_config::{DeadLetterConfig, SourceInputFormat, build_doc_mapper};
+use quickwit_doc_mapper::DocMapper;
+use quickwit_index_management::{IndexService as IndexManager, IndexServiceError};
+use quickwit_indexing::models::{
+    DeadLetter, dead_letters_prefix, deserialize_dead_letters, serialize_dead_letters,
+};
+use quickwit_ingest::{IngestServiceClient, IngestServiceError};
+use quickwit_janitor::error::JanitorError;
+use quickwit_metastore::{
+    IndexMetadataResponseExt, ListSplitsQuery, ListSplitsRequestExt,
+    MetastoreServiceStreamSplitsExt, SplitState,
+};
+use quickwit_proto::ingest::router::IngestRouterServiceClient;
+use quickwit_proto::metastore::{
+    EntityKind, IndexMetadataRequest, ListDeleteTasksRequest, ListSplitsRequest, MetastoreError,
+    MetastoreService, MetastoreServiceClient,
+};
+use quickwit_proto::search::SearchRequest;
+use quickwit_proto::types::{IndexId, IndexUid, SourceId};
+use quickwit_proto::{ServiceError, ServiceErrorCode};
+use quickwit_query::query_ast::{QueryAst, TermSetQuery, query_ast_from_user_text};
+use quickwit_search::{ExportRequest, SearchError, SearchService};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use warp::{Filter, Rejection};
+
+use crate::decompression::get_ingest_load_shield;
+use crate::delete_task_api::submit_delete_query;
+use crate::format::extract_format_from_qs;
+use crate::ingest_api::{IngestOptions, ingest};
+use crate::rest_api_response::into_rest_api_response;
+use crate::{Body, with_arg};
+
+/// Field of the dead letters targeted by the delete tasks of the replays.
+const DEAD_LETTER_ID_FIELD_NAME: &str = "id";
+
+#[derive(utoipa::OpenApi)]
+#[openapi(
+    paths(replay_dead_letters),
+    components(schemas(ReplayDeadLettersResponse))
+)]
+pub struct DeadLetterApi;
+
+/// Query parameters of the dead letters replay endpoint.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReplayDeadLettersQueryParams {
+    /// If set, only replays the documents rejected at or after this timestamp, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_timestamp: Option<i64>,
+    /// If set, only replays the documents rejected before this timestamp, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_timestamp: Option<i64>,
+}
+
+impl ReplayDeadLettersQueryParams {
+    fn contains(&self, timestamp: i64) -> bool {
+        if let Some(start_timestamp) = self.start_timestamp
+            && timestamp < start_timestamp
+        {
+            return false;
+        }
+        if let Some(end_timestamp) = self.end_timestamp
+            && timestamp >= end_timestamp
+        {
+            return false;
+        }
+        true
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct ReplayDeadLettersResponse {
+    /// Number of dead letters sent back to the ingest API.
+    pub num_replayed_docs: u64,
+    /// Number of documents rejected again, by the doc mapping of the index or by the ingest API.
+    pub num_rejected_docs: u64,
+    /// Number of dead letters kept in the dead-letter destination because their document was
+    /// rejected again, or because their batch had documents rejected by the ingest API. They are
+    /// replayed again by the next replay.
+    #[serde(default)]
+    pub num_kept_docs: u64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeadLetterApiError {
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("failed to read dead letters: {0}")]
+    Internal(String),
+    #[error(transparent)]
+    IndexService(#[from] IndexServiceError),
+    #[error(transparent)]
+    Ingest(#[from] IngestServiceError),
+    #[error(transparent)]
+    Janitor(#[from] JanitorError),
+    #[error(transparent)]
+    Metastore(#[from] MetastoreError),
+    #[error(transparent)]
+    Search(#[from] SearchError),
+}
+
+impl ServiceError for DeadLetterApiError {
+    fn error_code(&self) -> ServiceErrorCode {
+        match self {
+            DeadLetterApiError::BadRequest(_) => ServiceErrorCode::BadRequest,
+            DeadLetterApiError::Internal(_) => ServiceErrorCode::Internal,
+            DeadLetterApiError::IndexService(error) => error.error_code(),
+            DeadLetterApiError::Ingest(error) => error.error_code(),
+            DeadLetterApiError::Janitor(error) => error.error_code(),
+            DeadLetterApiError::Metastore(error) => error.error_code(),
+            DeadLetterApiError::Search(error) => error.error_code(),
+        }
+    }
+}
+
+/// Reads the dead letters of a source and re-ingests their raw documents into the index of the
+/// source.
+#[derive(Clone)]
+pub(crate) struct DeadLetterReplayer {
+    pub index_manager: IndexManager,
+    pub search_service: Arc<dyn SearchService>,
+    pub ingest_router: IngestRouterServiceClient,
+    pub ingest_service: IngestServiceClient,
+    pub metastore: MetastoreServiceClient,
+    pub enable_ingest_v1: bool,
+    pub enable_ingest_v2: bool,
+}
+
+impl DeadLetterReplayer {
+    async fn replay(
+        &self,
+        index_id: &IndexId,
+        source_id: &SourceId,
+        query_params: &ReplayDeadLettersQueryParams,
+    ) -> Result<ReplayDeadLettersResponse, DeadLetterApiError> {
+        let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.clone());
+        let index_metadata = self
+            .metastore
+            .index_metadata(index_metadata_request)
+            .await?
+            .deserialize_index_metadata()?;
+        let Some(source_config) = index_metadata.sources.get(source_id) else {
+            return Err(MetastoreError::NotFound(EntityKind::Source {
+                index_id: index_id.clone(),
+                source_id: source_id.clone(),
+            })
+            .into());
+        };
+        let Some(dead_letter_config) = source_config.dead_letter.clone() else {
+            return Err(DeadLetterApiError::BadRequest(format!(
+                "source `{source_id}` of index `{index_id}` has no dead-letter destination"
+            )));
+        };
+        // The ingest API indexes the documents as they are, so the documents of sources that
+        // reshape them before indexing would be indexed differently.
+        if source_config.input_format != SourceInputFormat::Json
+            || source_config.transform_config.is_some()
+        {
+            return Err(DeadLetterApiError::BadRequest(format!(
+                "the dead letters of source `{source_id}` of index `{index_id}` cannot be \
+                 replayed: only the dead letters of sources with the `json` input format and no \
+                 transform can be replayed"
+            )));
+        }
+        let index_config = &index_metadata.index_config;
+        let doc_mapper = build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)
+            .map_err(|error| DeadLetterApiError::Internal(error.to_string()))?;
+        let replayer = BatchReplayer {
+            dead_letter_replayer: self,
+            index_id,
+            doc_mapper: &doc_mapper,
+        };
+        let mut response = ReplayDeadLettersResponse::default();
+
+        match dead_letter_config {
+            DeadLetterConfig {
+                index_id: Some(dead_letter_index_id),
+                storage_uri: None,
+            } => {
+                self.replay_from_index(
+                    &replayer,
+                    index_id,
+                    source_id,
+                    &dead_letter_index_id,
+                    query_params,
+                    &mut response,
+                )
+                .await?;
+            }
+            DeadLetterConfig {
+                index_id: None,
+                storage_uri: Some(storage_uri),
+            } => {
+                self.replay_from_storage(
+                    &replayer,
+                    index_id,
+                    source_id,
+                    &storage_uri,
+                    query_params,
+                    &mut response,
+                )
+                .await?;
+            }
+            _ => {
+                return Err(DeadLetterApiError::BadRequest(format!(
+                    "invalid dead-letter destination for source `{source_id}` of index \
+                     `{index_id}`"
+                )));
+            }
+        }
+        info!(
+            index_id=%index_id,
+            source_id=%source_id,
+            num_replayed_docs=response.num_replayed_docs,
+            num_kept_docs=response.num_kept_docs,
+            "replayed dead letters"
+        );
+        Ok(response)
+    }
+
+    /// Replays the dead letters of a dead-letter index page by page. Once a page is replayed, a
+    /// delete task removes its replayed dead letters from the dead-letter index, and dead letters
+    /// whose delete task is still pending are skipped, so each dead letter is replayed once.
+    async fn replay_from_index(
+        &self,
+        replayer: &BatchReplayer<'_>,
+        index_id: &IndexId,
+        source_id: &SourceId,
+        dead_letter_index_id: &IndexId,
+        query_params: &ReplayDeadLettersQueryParams,
+        response: &mut ReplayDeadLettersResponse,
+    ) -> Result<(), DeadLetterApiError> {
+        let index_metadata_request =
+            IndexMetadataRequest::for_index_id(dead_letter_index_id.clone());
+        let dead_letter_index_metadata = self
+            .metastore
+            .index_metadata(index_metadata_request)
+            .await?
+            .deserialize_index_metadata()?;
+        let replayed_dead_letter_ids = self
+            .pending_replayed_dead_letter_ids(&dead_letter_index_metadata.index_uid)
+            .await?;
+
+        let query = format!("index_id:\"{index_id}\" AND source_id:\"{source_id}\"");
+        let query_ast = query_ast_from_user_text(&query, None);
+        let search_request = SearchRequest {
+            index_id_patterns: vec![dead_letter_index_id.clone()],
+            query_ast: serde_json::to_string(&query_ast)
+                .expect("query AST should be JSON serializable"),
+            ..Default::default()
+        };
+        let export_request = ExportRequest {
+            search_request,
+            cursor_opt: None,
+        };
+        let mut export_stream = self.search_service.root_export(export_request).await?;
+
+        while let Some(page_result) = export_stream.next().await {
+            let mut dead_letters = page_result?
+                .docs
+                .iter()
+                .map(|doc| serde_json::from_str::<DeadLetter>(&doc.json))
+                .collect::<Result<Vec<DeadLetter>, _>>()
+                .map_err(|error| DeadLetterApiError::Internal(error.to_string()))?;
+            dead_letters.retain(|dead_letter| {
+                query_params.contains(dead_letter.timestamp)
+                    && !replayed_dead_letter_ids.contains(&dead_letter.id)
+            });
+            if dead_letters.is_empty() {
+                continue;
+            }
+            let (replayed_dead_letters, _kept_dead_letters) =
+                replayer.replay_batch(dead_letters, response).await?;
+
+            if replayed_dead_letters.is_empty() {
+                continue;
+            }
+            let dead_letter_ids: BTreeSet<String> = replayed_dead_letters
+                .into_iter()
+                .map(|dead_letter| dead_letter.id)
+                .collect();
+            let delete_query_ast: QueryAst = TermSetQuery {
+                terms_per_field: HashMap::from([(
+                    DEAD_LETTER_ID_FIELD_NAME.to_string(),
+                    dead_letter_ids,
+                )]),
+            }
+            .into();
+            submit_delete_query(
+                &self.metastore,
+                dead_letter_index_metadata.clone(),
+                delete_query_ast,
+                None,
+                None,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the IDs of the dead letters deleted by the delete tasks of previous replays that
+    /// have not been applied to all the splits of the dead-letter index yet.
+    async fn pending_replayed_dead_letter_ids(
+        &self,
+        dead_letter_index_uid: &IndexUid,
+    ) -> Result<HashSet<String>, DeadLetterApiError> {
+        // A split has been cleaned up by all the delete tasks up to its delete opstamp, so only
+        // the delete tasks past the delete opstamp of the stalest split may still be pending.
+        let list_splits_query = ListSplitsQuery::for_index(dead_letter_index_uid.clone())
+            .with_split_state(SplitState::Published)
+            .sort_by_staleness()
+            .with_limit(1);
+        let list_splits_request =
+            ListSplitsRequest::try_from_list_splits_query(&list_splits_query)?;
+        let stalest_splits = self
+            .metastore
+            .list_splits(list_splits_request)
+            .await?
+            .collect_splits()
+            .await?;
+        let Some(stalest_split) = stalest_splits.first() else {
+            return Ok(HashSet::new());
+        };
+        let list_delete_tasks_request = ListDeleteTasksRequest::new(
+            dead_letter_index_uid.clone(),
+            stalest_split.split_metadata.delete_opstamp,
+        );
+        let delete_tasks = self
+            .metastore
+            .list_delete_tasks(list_delete_tasks_request)
+            .await?
+            .delete_tasks;
+        let mut dead_letter_ids = HashSet::new();
+
+        for delete_task in delete_tasks {
+            let Some(delete_query) = delete_task.delete_query else {
+                continue;
+            };
+            if let Ok(QueryAst::TermSet(term_set_query)) =
+                serde_json::from_str::<QueryAst>(&delete_query.query_ast)
+                && term_set_query.terms_per_field.len() == 1
+                && let Some(ids) = term_set_query
+                    .terms_per_field
+                    .get(DEAD_LETTER_ID_FIELD_NAME)
+            {
+                dead_letter_ids.extend(ids.iter().cloned());
+            }
+        }
+        Ok(dead_letter_ids)
+    }
+
+    /// Replays the dead letters of a dead-letter storage file by file. Once a file is replayed,
+    /// it is deleted, or rewritten with the dead letters that were not replayed, so each dead
+    /// letter is replayed once.
+    async fn replay_from_storage(
+        &self,
+        replayer: &BatchReplayer<'_>,
+        index_id: &IndexId,
+        source_id: &SourceId,
+        storage_uri: &Uri,
+        query_params: &ReplayDeadLettersQueryParams,
+        response: &mut ReplayDeadLettersResponse,
+    ) -> Result<(), DeadLetterApiError> {
+        let storage = self
+            .index_manager
+            .storage_resolver()
+            .resolve(storage_uri)
+            .await
+            .map_err(|error| DeadLetterApiError::Internal(error.to_string()))?;
+        // The trailing separator keeps the dead letters of `source-10` out of the dead
+        // letters of `source-1`.
+        let prefix = dead_letters_prefix(index_id, source_id).join("");
+        let filepaths = storage
+            .list_files_with_prefix(&prefix)
+            .await
+            .map_err(|error| DeadLetterApiError::Internal(error.to_string()))?;
+
+        for filepath in filepaths {
+            let payload = storage
+                .get_all(&filepath)
+                .await
+                .map_err(|error| DeadLetterApiError::Internal(error.to_string()))?;
+            let (dead_letters, mut remaining_dead_letters): (Vec<DeadLetter>, Vec<DeadLetter>) =
+                deserialize_dead_letters(&payload)
+                    .map_err(|error| DeadLetterApiError::Internal(error.to_string()))?
+                    .into_iter()
+                    .partition(|dead_letter| query_params.contains(dead_letter.timestamp));
+
+            if dead_letters.is_empty() {
+                continue;
+            }
+            let (replayed_dead_letters, kept_dead_letters) =
+                replayer.replay_batch(dead_letters, response).await?;
+
+            if replayed_dead_letters.is_empty() {
+                continue;
+            }
+            remaining_dead_letters.extend(kept_dead_letters);
+
+            let result = if remaining_dead_letters.is_empty() {
+                storage.delete(&filepath).await
+            } else {
+                let payload = serialize_dead_letters(&remaining_dead_letters);
+                storage.put(&filepath, Box::new(payload)).await
+            };
+            result.map_err(|error| DeadLetterApiError::Internal(error.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Re-ingests batches of dead letters into the index of their source.
+struct BatchReplayer<'a> {
+    dead_letter_replayer: &'a DeadLetterReplayer,
+    index_id: &'a IndexId,
+    doc_mapper: &'a DocMapper,
+}
+
+impl BatchReplayer<'_> {
+    /// Sends the raw documents of the dead letters that the doc mapping of the index now accepts
+    /// to the ingest API of the index. Returns the dead letters that were re-ingested, which can
+    /// be removed from the dead-letter destination, and the ones that must be kept.
+    async fn replay_batch(
+        &self,
+        dead_letters: Vec<DeadLetter>,
+        response: &mut ReplayDeadLettersResponse,
+    ) -> Result<(Vec<DeadLetter>, Vec<DeadLetter>), DeadLetterApiError> {
+        let mut valid_dead_letters = Vec::with_capacity(dead_letters.len());
+        let mut kept_dead_letters = Vec::new();
+
+        for dead_letter in dead_letters {
+            let raw_doc = dead_letter
+                .raw_doc_bytes()
+                .map_err(|error| DeadLetterApiError::Internal(error.to_string()))?;
+            // The ingest API would accept the documents that the doc processor rejects again, and
+            // they would be lost.
+            if self.doc_mapper.validate_json_bytes(&raw_doc).is_ok()
+                && self.doc_mapper.doc_from_json_bytes(&raw_doc).is_ok()
+            {
+                valid_dead_letters.push(dead_letter);
+            } else {
+                kept_dead_letters.push(dead_letter);
+            }
+        }
+        response.num_rejected_docs += kept_dead_letters.len() as u64;
+        response.num_kept_docs += kept_dead_letters.len() as u64;
+
+        if valid_dead_letters.is_empty() {
+            return Ok((valid_dead_letters, kept_dead_letters));
+        }
+        let dead_letter_replayer = self.dead_letter_replayer;
+        let content = dead_letters_to_ndjson(&valid_dead_letters)?;
+        let load_shield_permit = get_ingest_load_shield()
+            .acquire_permit()
+            .await
+            .map_err(|_| {
+                IngestServiceError::Unavailable("too many in-flight ingest requests".to_string())
+            })?;
+        let body = Body::new(content, load_shield_permit);
+        let ingest_response = ingest(
+            self.index_id.clone(),
+            body,
+            IngestOptions::default(),
+            dead_letter_replayer.ingest_router.clone(),
+            dead_letter_replayer.ingest_service.clone(),
+            dead_letter_replayer.metastore.clone(),
+            dead_letter_replayer.enable_ingest_v1,
+            dead_letter_replayer.enable_ingest_v2,
+        )
+        .await?;
+        let num_rejected_docs = ingest_response.num_rejected_docs.unwrap_or(0);
+        response.num_replayed_docs += ingest_response.num_docs_for_processing;
+        response.num_rejected_docs += num_rejected_docs;
+
+        // The rejected documents cannot be told apart from the accepted ones, so the whole batch
+        // is kept.
+        if num_rejected_docs > 0 {
+            response.num_kept_docs += valid_dead_letters.len() as u64;
+            kept_dead_letters.extend(valid_dead_letters);
+            return Ok((Vec::new(), kept_dead_letters));
+        }
+        Ok((valid_dead_letters, kept_dead_letters))
+    }
+}
+
+/// Builds the NDJSON payload of the raw documents of the dead letters.
+fn dead_letters_to_ndjson(dead_letters: &[DeadLetter]) -> Result<Bytes, DeadLetterApiError> {
+    let mut content = Vec::new();
+
+    for dead_letter in dead_letters {
+        let raw_doc = dead_letter
+            .raw_doc_bytes()
+            .map_err(|error| DeadLetterApiError::Internal(error.to_string()))?;
+        // Documents spanning several lines would be split by the ingest API, so valid JSON
+        // documents are compacted.
+        if raw_doc.contains(&b'\n')
+            && let Ok(json_doc) = serde_json::from_slice::<serde_json::Value>(&raw_doc)
+        {
+            serde_json::to_writer(&mut content, &json_doc)
+                .expect("JSON value should be serializable");
+        } else {
+            content.extend_from_slice(&raw_doc);
+        }
+        content.push(b'\n');
+    }
+    Ok(Bytes::from(content))
+}
+
+pub(crate) fn dead_letter_api_handlers(
+    dead_letter_replayer: DeadLetterReplayer,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "sources" / String / "dead-letters" / "replay")
+        .and(warp::post())
+        .and(warp::query::<ReplayDeadLettersQueryParams>())
+        .and(with_arg(dead_letter_replayer))
+        .then(replay_dead_letters)
+        .and(extract_format_from_qs())
+        .map(into_rest_api_response)
+        .boxed()
+}
+
+#[utoipa::path(
+    post,
+    tag = "Sources",
+    path = "/indexes/{index_id}/sources/{source_id}/dead-letters/replay",
+    responses(
+        (status = 200, description = "Successfully replayed dead letters.", body = ReplayDeadLettersResponse)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID of the source."),
+        ("source_id" = String, Path, description = "The source ID whose dead letters are replayed."),
+        ReplayDeadLettersQueryParams,
+    )
+)]
+/// Replay Dead Letters
+///
+/// Re-ingests the documents rejected by the doc processor of a source into the index of the
+/// source. Replayed dead letters are removed from the dead-letter destination, so a replay that
+/// failed midway can simply be run again.
+async fn replay_dead_letters(
+    index_id: IndexId,
+    source_id: SourceId,
+    query_params: ReplayDeadLettersQueryParams,
+    dead_letter_replayer: DeadLetterReplayer,
+) -> Result<ReplayDeadLettersResponse, DeadLetterApiError> {
+    dead_letter_replayer
+        .replay(&index_id, &source_id, &query_params)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use quickwit_config::{SourceConfig, SourceParams, TransformConfig};
+    use quickwit_ingest::{IngestResponse, MockIngestService};
+    use quickwit_metastore::IndexMetadata;
+    use quickwit_metastore::checkpoint::SourceCheckpointDelta;
+    use quickwit_proto::metastore::{IndexMetadataResponse, MockMetastoreService};
+    use quickwit_search::MockSearchService;
+    use quickwit_storage::StorageResolver;
+
+    use super::*;
+
+    fn dead_letter_for_test(timestamp: i64, raw_doc: &'static [u8]) -> DeadLetter {
+        DeadLetter::new(
+            timestamp,
+            "test-index".to_string(),
+            "test-source".to_string(),
+            "doc_mapper_error",
+            "doc mapper parse error".to_string(),
+            &SourceCheckpointDelta::from_range(0..1),
+            Bytes::from_static(raw_doc),
+        )
+    }
+
+    #[test]
+    fn test_replay_dead_letters_query_params_contains() {
+        let query_params = ReplayDeadLettersQueryParams::default();
+        assert!(query_params.contains(10));
+
+        let query_params = ReplayDeadLettersQueryParams {
+            start_timestamp: Some(20),
+            end_timestamp: Some(30),
+        };
+        assert!(!query_params.contains(10));
+        assert!(query_params.contains(20));
+        assert!(!query_params.contains(30));
+    }
+
+    #[test]
+    fn test_dead_letters_to_ndjson() {
+        let dead_letters = vec![
+            dead_letter_for_test(10, br#"{"id": 1}"#),
+            dead_letter_for_test(20, b"{\n  \"id\": 2\n}"),
+            dead_letter_for_test(30, br#"{"id": 3}"#),
+        ];
+        let content = dead_letters_to_ndjson(&dead_letters).unwrap();
+        assert_eq!(content, "{\"id\": 1}\n{\"id\":2}\n{\"id\": 3}\n");
+    }
+
+    #[tokio::test]
+    async fn test_replay_dead_letters_from_storage_once() {
+        let mut index_metadata = IndexMetadata::for_test("test-index", "ram:///indexes/test-index");
+        let mut source_config = SourceConfig::for_test("test-source", SourceParams::void());
+        source_config.dead_letter = Some(DeadLetterConfig {
+            index_id: None,
+            storage_uri: Some(Uri::for_test("ram:///dead-letters")),
+        });
+        index_metadata.add_source(source_config).unwrap();
+
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore.expect_index_metadata().returning(move |_| {
+            Ok(IndexMetadataResponse::try_from_index_metadata(&index_metadata).unwrap())
+        });
+        let metastore = MetastoreServiceClient::from_mock(mock_metastore);
+
+        let mut mock_ingest_service = MockIngestService::new();
+        mock_ingest_service
+            .expect_ingest()
+            .times(3)
+            .returning(|ingest_request| {
+                assert_eq!(ingest_request.doc_batches[0].index_id, "test-index");
+                Ok(IngestResponse {
+                    num_docs_for_processing: ingest_request.doc_batches[0].num_docs() as u64,
+                })
+            });
+        let storage_resolver = StorageResolver::for_test();
+        let dead_letter_replayer = DeadLetterReplayer {
+            index_manager: IndexManager::new(metastore.clone(), storage_resolver.clone()),
+            search_service: Arc::new(MockSearchService::new()),
+            ingest_router: IngestRouterServiceClient::mocked(),
+            ingest_service: IngestServiceClient::from_mock(mock_ingest_service),
+            metastore,
+            enable_ingest_v1: true,
+            enable_ingest_v2: false,
+        };
+        let storage = storage_resolver
+            .resolve(&Uri::for_test("ram:///dead-letters"))
+            .await
+            .unwrap();
+        let dead_letters_0 = [
+            dead_letter_for_test(10, br#"{"id": 1, "timestamp": 10}"#),
+            dead_letter_for_test(30, br#"{"id": 3, "timestamp": 30}"#),
+        ];
+        storage
+            .put(
+                Path::new("test-index/test-source/0.ndjson"),
+                Box::new(serialize_dead_letters(&dead_letters_0)),
+            )
+            .await
+            .unwrap();
+        // The dead letter without timestamp is still rejected by the doc mapping.
+        let dead_letters_1 = [
+            dead_letter_for_test(15, br#"{"id": 4}"#),
+            dead_letter_for_test(20, br#"{"id": 2, "timestamp": 20}"#),
+        ];
+        storage
+            .put(
+                Path::new("test-index/test-source/1.ndjson"),
+                Box::new(serialize_dead_letters(&dead_letters_1)),
+            )
+            .await
+            .unwrap();
+        let index_id = "test-index".to_string();
+        let source_id = "test-source".to_string();
+
+        let query_params = ReplayDeadLettersQueryParams {
+            start_timestamp: None,
+            end_timestamp: Some(25),
+        };
+        let response = dead_letter_replayer
+            .replay(&index_id, &source_id, &query_params)
+            .await
+            .unwrap();
+        assert_eq!(response.num_replayed_docs, 2);
+        assert_eq!(response.num_rejected_docs, 1);
+        assert_eq!(response.num_kept_docs, 1);
+
+        let mut filepaths = storage
+            .list_files_with_prefix(Path::new("test-index/test-source/"))
+            .await
+            .unwrap();
+        filepaths.sort();
+        assert_eq!(
+            filepaths,
+            [
+                Path::new("test-index/test-source/0.ndjson"),
+                Path::new("test-index/test-source/1.ndjson")
+            ]
+        );
+        let payload = storage.get_all(&filepaths[0]).await.unwrap();
+        let remaining_dead_letters = deserialize_dead_letters(&payload).unwrap();
+        assert_eq!(remaining_dead_letters, dead_letters_0[1..]);
+
+        let payload = storage.get_all(&filepaths[1]).await.unwrap();
+        let remaining_dead_letters = deserialize_dead_letters(&payload).unwrap();
+        assert_eq!(remaining_dead_letters, dead_letters_1[..1]);
+
+        // The replayed dead letters are not replayed twice.
+        let response = dead_letter_replayer
+            .replay(&index_id, &source_id, &query_params)
+            .await
+            .unwrap();
+        assert_eq!(response.num_replayed_docs, 0);
+
+        let response = dead_letter_replayer
+            .replay(
+                &index_id,
+                &source_id,
+                &ReplayDeadLettersQueryParams::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.num_replayed_docs, 1);
+        assert_eq!(response.num_kept_docs, 1);
+
+        let filepaths = storage
+            .list_files_with_prefix(Path::new("test-index/test-source/"))
+            .await
+            .unwrap();
+        assert_eq!(filepaths, [Path::new("test-index/test-source/1.ndjson")]);
+    }
+
+    #[tokio::test]
+    async fn test_replay_dead_letters_rejects_processed_sources() {
+        let mut index_metadata = IndexMetadata::for_test("test-index", "ram:///indexes/test-index");
+        let dead_letter_config = DeadLetterConfig {
+            index_id: None,
+            storage_uri: Some(Uri::for_test("ram:///dead-letters")),
+        };
+        let mut source_config = SourceConfig::for_test("otlp-source", SourceParams::void());
+        source_config.input_format = SourceInputFormat::OtlpLogsJson;
+        source_config.dead_letter = Some(dead_letter_config.clone());
+        index_metadata.add_source(source_config).unwrap();
+
+        let mut source_config = SourceConfig::for_test("transform-source", SourceParams::void());
+        source_config.transform_config = Some(TransformConfig::for_test(".body = \"hello\""));
+        source_config.dead_letter = Some(dead_letter_config);
+        index_metadata.add_source(source_config).unwrap();
+
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore.expect_index_metadata().returning(move |_| {
+            Ok(IndexMetadataResponse::try_from_index_metadata(&index_metadata).unwrap())
+        });
+        let metastore = MetastoreServiceClient::from_mock(mock_metastore);
+        let storage_resolver = StorageResolver::for_test();
+        let dead_letter_replayer = DeadLetterReplayer {
+            index_manager: IndexManager::new(metastore.clone(), storage_resolver),
+            search_service: Arc::new(MockSearchService::new()),
+            ingest_router: IngestRouterServiceClient::mocked(),
+            ingest_service: IngestServiceClient::from_mock(MockIngestService::new()),
+            metastore,
+            enable_ingest_v1: true,
+            enable_ingest_v2: false,
+        };
+        let index_id = "test-index".to_string();
+
+        for source_id in ["otlp-source", "transform-source"] {
+            let error = dead_letter_replayer
+                .replay(
+                    &index_id,
+                    &source_id.to_string(),
+                    &ReplayDeadLettersQueryParams::default(),
+                )
+                .await
+                .unwrap_err();
+            assert!(matches!(error, DeadLetterApiError::BadRequest(_)));
+        }
+    }
+}
@@ -25,7 +25,7 @@ use warp::reject::Reject;
 
 use crate::load_shield::{LoadShield, LoadShieldPermit};
 
-fn get_ingest_load_shield() -> &'static LoadShield {
+pub(crate) fn get_ingest_load_shield() -> &'static LoadShield {
     static LOAD_SHIELD: OnceLock<LoadShield> = OnceLock::new();
     LOAD_SHIELD.get_or_init(|| LoadShield::new("ingest"))
 }
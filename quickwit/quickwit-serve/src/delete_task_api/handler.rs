@@ -14,7 +14,7 @@
 
 use quickwit_config::build_doc_mapper;
 use quickwit_janitor::error::JanitorError;
-use quickwit_metastore::IndexMetadataResponseExt;
+use quickwit_metastore::{IndexMetadata, IndexMetadataResponseExt};
 use quickwit_proto::metastore::{
     DeleteQuery, DeleteTask, IndexMetadataRequest, ListDeleteTasksRequest, MetastoreResult,
     MetastoreService, MetastoreServiceClient,
@@ -149,20 +149,39 @@ pub async fn post_delete_request(
         .index_metadata(index_metadata_request)
         .await?
         .deserialize_index_metadata()?;
-    let index_uid: IndexUid = metadata.index_uid.clone();
     let query_ast = query_ast_from_user_text(&delete_request.query, delete_request.search_fields)
         .parse_user_query(&metadata.index_config.search_settings.default_search_fields)
         .map_err(|err| JanitorError::InvalidDeleteQuery(err.to_string()))?;
+    submit_delete_query(
+        &metastore,
+        metadata,
+        query_ast,
+        delete_request.start_timestamp,
+        delete_request.end_timestamp,
+    )
+    .await
+}
+
+/// Validates a delete query against the doc mapping of the index and, if valid, registers it as a
+/// new delete task in the metastore.
+pub(crate) async fn submit_delete_query(
+    metastore: &MetastoreServiceClient,
+    index_metadata: IndexMetadata,
+    query_ast: QueryAst,
+    start_timestamp: Option<i64>,
+    end_timestamp: Option<i64>,
+) -> Result<DeleteTask, JanitorError> {
+    let index_uid: IndexUid = index_metadata.index_uid.clone();
     let query_ast_json = serde_json::to_string(&query_ast).map_err(|_err| {
         JanitorError::Internal("failed to serialized delete query ast".to_string())
     })?;
     let delete_query = DeleteQuery {
         index_uid: Some(index_uid),
-        start_timestamp: delete_request.start_timestamp,
-        end_timestamp: delete_request.end_timestamp,
+        start_timestamp,
+        end_timestamp,
         query_ast: query_ast_json,
     };
-    let index_config = metadata.into_index_config();
+    let index_config = index_metadata.into_index_config();
     // TODO should it be something else than a JanitorError?
     let doc_mapper = build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)
         .map_err(|error| JanitorError::Internal(error.to_string()))?;
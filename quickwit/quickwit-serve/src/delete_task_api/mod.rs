@@ -14,4 +14,5 @@
 
 mod handler;
 
+pub(crate) use handler::submit_delete_query;
 pub use handler::{DeleteTaskApi, delete_task_api_handlers};
@@ -20,14 +20,20 @@ use quickwit_ingest::{
     CommitType, DocBatchBuilder, IngestRequest, IngestService, IngestServiceClient,
 };
 use quickwit_proto::ingest::router::IngestRouterServiceClient;
+use quickwit_proto::metastore::MetastoreServiceClient;
 use quickwit_proto::types::IndexId;
 use warp::http::StatusCode;
 use warp::{Filter, Rejection};
 
-use super::bulk_v2::{ElasticBulkResponse, elastic_bulk_ingest_v2};
+use super::bulk_v2::{
+    BulkDelete, ElasticBulkAction, ElasticBulkResponse, IndexedDocIds, UNSUPPORTED_UPDATE_REASON,
+    elastic_bulk_ingest_v2, submit_bulk_deletes,
+};
 use crate::elasticsearch_api::filter::{elastic_bulk_filter, elastic_index_bulk_filter};
 use crate::elasticsearch_api::make_elastic_api_response;
-use crate::elasticsearch_api::model::{BulkAction, ElasticBulkOptions, ElasticsearchError};
+use crate::elasticsearch_api::model::{
+    BulkAction, ElasticBulkOptions, ElasticException, ElasticsearchError,
+};
 use crate::format::extract_format_from_qs;
 use crate::ingest_api::lines;
 use crate::rest::recover_fn;
@@ -37,6 +43,7 @@ use crate::{Body, with_arg};
 pub fn es_compat_bulk_handler(
     ingest_service: IngestServiceClient,
     ingest_router: IngestRouterServiceClient,
+    metastore: MetastoreServiceClient,
     content_length_limit: ByteSize,
     enable_ingest_v1: bool,
     enable_ingest_v2: bool,
@@ -44,17 +51,21 @@ pub fn es_compat_bulk_handler(
     elastic_bulk_filter(content_length_limit)
         .and(with_arg(ingest_service))
         .and(with_arg(ingest_router))
-        .then(move |body, bulk_options, ingest_service, ingest_router| {
-            elastic_ingest_bulk(
-                None,
-                body,
-                bulk_options,
-                ingest_service,
-                ingest_router,
-                enable_ingest_v1,
-                enable_ingest_v2,
-            )
-        })
+        .and(with_arg(metastore))
+        .then(
+            move |body, bulk_options, ingest_service, ingest_router, metastore| {
+                elastic_ingest_bulk(
+                    None,
+                    body,
+                    bulk_options,
+                    ingest_service,
+                    ingest_router,
+                    metastore,
+                    enable_ingest_v1,
+                    enable_ingest_v2,
+                )
+            },
+        )
         .and(extract_format_from_qs())
         .map(make_elastic_api_response)
         .recover(recover_fn)
@@ -64,6 +75,7 @@ pub fn es_compat_bulk_handler(
 pub fn es_compat_index_bulk_handler(
     ingest_service: IngestServiceClient,
     ingest_router: IngestRouterServiceClient,
+    metastore: MetastoreServiceClient,
     content_length_limit: ByteSize,
     enable_ingest_v1: bool,
     enable_ingest_v2: bool,
@@ -71,14 +83,16 @@ pub fn es_compat_index_bulk_handler(
     elastic_index_bulk_filter(content_length_limit)
         .and(with_arg(ingest_service))
         .and(with_arg(ingest_router))
+        .and(with_arg(metastore))
         .then(
-            move |index_id, body, bulk_options, ingest_service, ingest_router| {
+            move |index_id, body, bulk_options, ingest_service, ingest_router, metastore| {
                 elastic_ingest_bulk(
                     Some(index_id),
                     body,
                     bulk_options,
                     ingest_service,
                     ingest_router,
+                    metastore,
                     enable_ingest_v1,
                     enable_ingest_v2,
                 )
@@ -90,17 +104,26 @@ pub fn es_compat_index_bulk_handler(
         .boxed()
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn elastic_ingest_bulk(
     default_index_id: Option<IndexId>,
     body: Body,
     bulk_options: ElasticBulkOptions,
     ingest_service: IngestServiceClient,
     ingest_router: IngestRouterServiceClient,
+    metastore: MetastoreServiceClient,
     enable_ingest_v1: bool,
     enable_ingest_v2: bool,
 ) -> Result<ElasticBulkResponse, ElasticsearchError> {
     if enable_ingest_v2 && !bulk_options.use_legacy_ingest {
-        return elastic_bulk_ingest_v2(default_index_id, body, bulk_options, ingest_router).await;
+        return elastic_bulk_ingest_v2(
+            default_index_id,
+            body,
+            bulk_options,
+            ingest_router,
+            metastore,
+        )
+        .await;
     }
     if !enable_ingest_v1 {
         return Err(ElasticsearchError::new(
@@ -111,6 +134,8 @@ async fn elastic_ingest_bulk(
     }
     let now = Instant::now();
     let mut doc_batch_builders = HashMap::new();
+    let mut bulk_deletes = Vec::new();
+    let mut indexed_es_doc_ids = IndexedDocIds::default();
     let mut lines = lines(&body.content).enumerate();
 
     while let Some((line_number, line)) = lines.next() {
@@ -121,18 +146,25 @@ async fn elastic_ingest_bulk(
                 None,
             )
         })?;
-        let (_, source) = lines.next().ok_or_else(|| {
-            ElasticsearchError::new(
-                StatusCode::BAD_REQUEST,
-                "expected source for the action".to_string(),
-                None,
-            )
-        })?;
+        let source_opt = if action.has_source() {
+            let (_, source) = lines.next().ok_or_else(|| {
+                ElasticsearchError::new(
+                    StatusCode::BAD_REQUEST,
+                    "expected source for the action".to_string(),
+                    None,
+                )
+            })?;
+            Some(source)
+        } else {
+            None
+        };
+        let is_update = matches!(action, BulkAction::Update(_));
+        let meta = action.into_meta();
         // when ingesting on /my-index/_bulk, if _index: is set to something else than my-index,
         // ES honors it and create the doc in the requested index. That is, `my-index` is a default
         // value in case _index: is missing, but not a constraint on each sub-action.
-        let index_id = action
-            .into_index_id()
+        let index_id = meta
+            .index_id
             .or_else(|| default_index_id.clone())
             .ok_or_else(|| {
                 ElasticsearchError::new(
@@ -141,10 +173,27 @@ async fn elastic_ingest_bulk(
                     None,
                 )
             })?;
+        let Some(source) = source_opt else {
+            let is_indexed_in_request = indexed_es_doc_ids.contains(&index_id, &meta.es_doc_id);
+            bulk_deletes.push(BulkDelete {
+                position: bulk_deletes.len(),
+                index_id,
+                es_doc_id: meta.es_doc_id,
+                is_indexed_in_request,
+            });
+            continue;
+        };
+        if is_update {
+            return Err(ElasticsearchError::new(
+                StatusCode::BAD_REQUEST,
+                format!("invalid action in the line [#{line_number}]: {UNSUPPORTED_UPDATE_REASON}"),
+                Some(ElasticException::IllegalArgument),
+            ));
+        }
+        indexed_es_doc_ids.insert(&index_id, &meta.es_doc_id);
         let doc_batch_builder = doc_batch_builders
             .entry(index_id.clone())
             .or_insert(DocBatchBuilder::new(index_id));
-
         doc_batch_builder.ingest_doc(source);
     }
     let doc_batches = doc_batch_builders
@@ -158,12 +207,20 @@ async fn elastic_ingest_bulk(
     };
     ingest_service.ingest(ingest_request).await?;
 
+    // The legacy ingest API does not report per-document items, only the items of the deletes.
+    let mut positioned_delete_actions = submit_bulk_deletes(&metastore, bulk_deletes).await;
+    positioned_delete_actions.sort_unstable_by_key(|(position, _)| *position);
+    let actions: Vec<ElasticBulkAction> = positioned_delete_actions
+        .into_iter()
+        .map(|(_, action)| action)
+        .collect();
+    let errors = actions.iter().any(ElasticBulkAction::is_error);
+
     let took_millis = now.elapsed().as_millis() as u64;
-    let errors = false;
     let bulk_response = ElasticBulkResponse {
         took_millis,
         errors,
-        actions: Vec::new(),
+        actions,
     };
     Ok(bulk_response)
 }
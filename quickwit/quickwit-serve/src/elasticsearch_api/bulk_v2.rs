@@ -12,24 +12,29 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::time::Instant;
 
 use quickwit_common::rate_limited_error;
 use quickwit_config::{INGEST_V2_SOURCE_ID, validate_identifier};
 use quickwit_ingest::IngestRequestV2Builder;
+use quickwit_metastore::IndexMetadataResponseExt;
 use quickwit_proto::ingest::CommitTypeV2;
 use quickwit_proto::ingest::router::{
-    IngestFailureReason, IngestResponseV2, IngestRouterService, IngestRouterServiceClient,
+    IngestFailureReason, IngestResponseV2, IngestRouterServiceClient,
 };
+use quickwit_proto::metastore::{IndexMetadataRequest, MetastoreService, MetastoreServiceClient};
 use quickwit_proto::types::{DocUid, IndexId};
+use quickwit_query::ID_FIELD_NAME;
+use quickwit_query::query_ast::TermSetQuery;
 use serde::{Deserialize, Serialize};
 use warp::hyper::StatusCode;
 
 use super::model::ElasticException;
 use crate::Body;
+use crate::delete_task_api::submit_delete_query;
 use crate::elasticsearch_api::model::{BulkAction, ElasticBulkOptions, ElasticsearchError};
-use crate::ingest_api::lines;
+use crate::ingest_api::{ingest_v2_with_index_aliases, lines};
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub(crate) struct ElasticBulkResponse {
@@ -46,6 +51,25 @@ pub(crate) enum ElasticBulkAction {
     Create(ElasticBulkItem),
     #[serde(rename = "index")]
     Index(ElasticBulkItem),
+    #[serde(rename = "update")]
+    Update(ElasticBulkItem),
+    #[serde(rename = "delete")]
+    Delete(ElasticBulkItem),
+}
+
+impl ElasticBulkAction {
+    fn item(&self) -> &ElasticBulkItem {
+        match self {
+            ElasticBulkAction::Create(item) => item,
+            ElasticBulkAction::Index(item) => item,
+            ElasticBulkAction::Update(item) => item,
+            ElasticBulkAction::Delete(item) => item,
+        }
+    }
+
+    pub(crate) fn is_error(&self) -> bool {
+        self.item().error.is_some()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,6 +94,11 @@ pub(crate) struct ElasticBulkError {
 
 type ElasticDocId = String;
 
+/// Documents cannot be modified once indexed, and emulating updates with a delete task followed
+/// by an index would delete the updated document as well if it is published before the task.
+pub(crate) const UNSUPPORTED_UPDATE_REASON: &str =
+    "update actions are not supported: documents cannot be modified once indexed";
+
 #[derive(Debug)]
 struct DocHandle {
     doc_position: usize,
@@ -80,18 +109,31 @@ struct DocHandle {
     is_parse_failure: bool,
 }
 
+/// A `delete` action of a bulk request.
+#[derive(Debug)]
+pub(crate) struct BulkDelete {
+    pub position: usize,
+    pub index_id: IndexId,
+    pub es_doc_id: Option<ElasticDocId>,
+    // Whether a document with the same `_id` is indexed earlier in the same bulk request.
+    pub is_indexed_in_request: bool,
+}
+
 pub(crate) async fn elastic_bulk_ingest_v2(
     default_index_id: Option<IndexId>,
     body: Body,
     bulk_options: ElasticBulkOptions,
     ingest_router: IngestRouterServiceClient,
+    metastore: MetastoreServiceClient,
 ) -> Result<ElasticBulkResponse, ElasticsearchError> {
     let now = Instant::now();
     let mut ingest_request_builder = IngestRequestV2Builder::default();
     let mut lines = lines(&body.content).enumerate();
     let mut per_subrequest_doc_handles: HashMap<u32, Vec<DocHandle>> = HashMap::new();
     let mut action_count = 0;
-    let mut invalid_index_id_items = Vec::new();
+    let mut bulk_deletes = Vec::new();
+    let mut non_ingested_actions = Vec::new();
+    let mut indexed_es_doc_ids = IndexedDocIds::default();
     while let Some((line_no, line)) = lines.next() {
         let action = serde_json::from_slice::<BulkAction>(line).map_err(|error| {
             ElasticsearchError::new(
@@ -100,13 +142,19 @@ pub(crate) async fn elastic_bulk_ingest_v2(
                 Some(ElasticException::IllegalArgument),
             )
         })?;
-        let (_, doc) = lines.next().ok_or_else(|| {
-            ElasticsearchError::new(
-                StatusCode::BAD_REQUEST,
-                "Validation Failed: 1: no requests added;".to_string(),
-                Some(ElasticException::ActionRequestValidation),
-            )
-        })?;
+        let source_opt = if action.has_source() {
+            let (_, source) = lines.next().ok_or_else(|| {
+                ElasticsearchError::new(
+                    StatusCode::BAD_REQUEST,
+                    "Validation Failed: 1: no requests added;".to_string(),
+                    Some(ElasticException::ActionRequestValidation),
+                )
+            })?;
+            Some(source)
+        } else {
+            None
+        };
+        let is_update = matches!(action, BulkAction::Update(_));
         let meta = action.into_meta();
         // When ingesting into `/my-index/_bulk`, if `_index` is set to something other than
         // `my-index`, ES honors it and creates the doc for the requested index. That is,
@@ -127,12 +175,34 @@ pub(crate) async fn elastic_bulk_ingest_v2(
         // from deeper ingest layers is harder
         if validate_identifier("", &index_id).is_err() {
             let invalid_item = make_invalid_index_id_item(index_id.clone(), meta.es_doc_id);
-            invalid_index_id_items.push((action_count, invalid_item));
+            let invalid_action = if source_opt.is_some() {
+                ElasticBulkAction::Index(invalid_item)
+            } else {
+                ElasticBulkAction::Delete(invalid_item)
+            };
+            non_ingested_actions.push((action_count, invalid_action));
             action_count += 1;
             continue;
         }
-
-        let (subrequest_id, doc_uid) = ingest_request_builder.add_doc(index_id, doc);
+        let Some(source) = source_opt else {
+            let is_indexed_in_request = indexed_es_doc_ids.contains(&index_id, &meta.es_doc_id);
+            bulk_deletes.push(BulkDelete {
+                position: action_count,
+                index_id,
+                es_doc_id: meta.es_doc_id,
+                is_indexed_in_request,
+            });
+            action_count += 1;
+            continue;
+        };
+        if is_update {
+            let unsupported_item = make_unsupported_update_item(index_id, meta.es_doc_id);
+            non_ingested_actions.push((action_count, ElasticBulkAction::Update(unsupported_item)));
+            action_count += 1;
+            continue;
+        }
+        indexed_es_doc_ids.insert(&index_id, &meta.es_doc_id);
+        let (subrequest_id, doc_uid) = ingest_request_builder.add_doc(index_id, source);
 
         let doc_handle = DocHandle {
             doc_position: action_count,
@@ -150,29 +220,169 @@ pub(crate) async fn elastic_bulk_ingest_v2(
 
     let ingest_request_opt = ingest_request_builder.build(INGEST_V2_SOURCE_ID, commit_type);
 
-    let Some(ingest_request) = ingest_request_opt else {
+    let ingest_response = if let Some(ingest_request) = ingest_request_opt {
+        ingest_v2_with_index_aliases(&ingest_router, &metastore, ingest_request)
+            .await
+            .map_err(|err| {
+                rate_limited_error!(limit_per_min=6, err=?err, "router error");
+                err
+            })?
+    } else if bulk_deletes.is_empty() && non_ingested_actions.is_empty() {
         return Ok(ElasticBulkResponse::default());
+    } else {
+        IngestResponseV2::default()
     };
-    let ingest_response = ingest_router.ingest(ingest_request).await.map_err(|err| {
-        rate_limited_error!(limit_per_min=6, err=?err, "router error");
-        err
-    })?;
+    // A delete task only applies to the splits published before it is created, so it never covers
+    // the documents of this request, which are published later on. This is why the deletes of
+    // documents indexed earlier in the request are rejected.
+    let delete_actions = submit_bulk_deletes(&metastore, bulk_deletes).await;
+    non_ingested_actions.extend(delete_actions);
+
     make_elastic_bulk_response_v2(
         ingest_response,
         per_subrequest_doc_handles,
         now,
         action_count,
-        invalid_index_id_items,
+        non_ingested_actions,
     )
 }
 
+/// Tracks the `_id`s of the documents indexed by a bulk request, per index.
+#[derive(Default)]
+pub(crate) struct IndexedDocIds(HashMap<IndexId, HashSet<ElasticDocId>>);
+
+impl IndexedDocIds {
+    pub fn insert(&mut self, index_id: &IndexId, es_doc_id_opt: &Option<ElasticDocId>) {
+        if let Some(es_doc_id) = es_doc_id_opt {
+            self.0
+                .entry(index_id.clone())
+                .or_default()
+                .insert(es_doc_id.clone());
+        }
+    }
+
+    pub fn contains(&self, index_id: &IndexId, es_doc_id_opt: &Option<ElasticDocId>) -> bool {
+        match (self.0.get(index_id), es_doc_id_opt) {
+            (Some(es_doc_ids), Some(es_doc_id)) => es_doc_ids.contains(es_doc_id),
+            _ => false,
+        }
+    }
+}
+
+/// Deletes are not applied synchronously: the deletes targeting the same index are grouped into a
+/// single delete task matching their `_id`s, which the janitor applies to the splits of the index
+/// in the background. As a consequence, deleting documents requires the index to define an
+/// `id_field`.
+pub(crate) async fn submit_bulk_deletes(
+    metastore: &MetastoreServiceClient,
+    bulk_deletes: Vec<BulkDelete>,
+) -> Vec<(usize, ElasticBulkAction)> {
+    let mut positioned_actions = Vec::with_capacity(bulk_deletes.len());
+    let mut per_index_deletes: BTreeMap<IndexId, Vec<(usize, ElasticDocId)>> = BTreeMap::new();
+
+    for bulk_delete in bulk_deletes {
+        let Some(es_doc_id) = bulk_delete.es_doc_id else {
+            let error = ElasticBulkError {
+                index_id: Some(bulk_delete.index_id.clone()),
+                exception: ElasticException::ActionRequestValidation,
+                reason: "Validation Failed: 1: id is missing;".to_string(),
+            };
+            let item = ElasticBulkItem {
+                index_id: bulk_delete.index_id,
+                es_doc_id: None,
+                status: StatusCode::BAD_REQUEST,
+                error: Some(error),
+            };
+            positioned_actions.push((bulk_delete.position, ElasticBulkAction::Delete(item)));
+            continue;
+        };
+        if bulk_delete.is_indexed_in_request {
+            let error = ElasticBulkError {
+                index_id: Some(bulk_delete.index_id.clone()),
+                exception: ElasticException::IllegalArgument,
+                reason: format!(
+                    "document [{es_doc_id}] is indexed earlier in the same bulk request: deletes \
+                     only apply to documents published before the request"
+                ),
+            };
+            let item = ElasticBulkItem {
+                index_id: bulk_delete.index_id,
+                es_doc_id: Some(es_doc_id),
+                status: StatusCode::BAD_REQUEST,
+                error: Some(error),
+            };
+            positioned_actions.push((bulk_delete.position, ElasticBulkAction::Delete(item)));
+            continue;
+        }
+        per_index_deletes
+            .entry(bulk_delete.index_id)
+            .or_default()
+            .push((bulk_delete.position, es_doc_id));
+    }
+    for (index_id, deletes) in per_index_deletes {
+        let es_doc_ids: BTreeSet<ElasticDocId> = deletes
+            .iter()
+            .map(|(_, es_doc_id)| es_doc_id.clone())
+            .collect();
+        let delete_result = submit_delete_by_ids(metastore, index_id.clone(), es_doc_ids).await;
+
+        for (position, es_doc_id) in deletes {
+            let item = match &delete_result {
+                Ok(()) => ElasticBulkItem {
+                    index_id: index_id.clone(),
+                    es_doc_id: Some(es_doc_id),
+                    status: StatusCode::OK,
+                    error: None,
+                },
+                Err(delete_error) => {
+                    let exception = match delete_error.status {
+                        StatusCode::NOT_FOUND => ElasticException::IndexNotFound,
+                        StatusCode::BAD_REQUEST => ElasticException::IllegalArgument,
+                        _ => ElasticException::Internal,
+                    };
+                    let error = ElasticBulkError {
+                        index_id: Some(index_id.clone()),
+                        exception,
+                        reason: delete_error.error.reason.clone().unwrap_or_default(),
+                    };
+                    ElasticBulkItem {
+                        index_id: index_id.clone(),
+                        es_doc_id: Some(es_doc_id),
+                        status: delete_error.status,
+                        error: Some(error),
+                    }
+                }
+            };
+            positioned_actions.push((position, ElasticBulkAction::Delete(item)));
+        }
+    }
+    positioned_actions
+}
+
+async fn submit_delete_by_ids(
+    metastore: &MetastoreServiceClient,
+    index_id: IndexId,
+    es_doc_ids: BTreeSet<ElasticDocId>,
+) -> Result<(), ElasticsearchError> {
+    let index_metadata_request = IndexMetadataRequest::for_index_id(index_id);
+    let index_metadata = metastore
+        .index_metadata(index_metadata_request)
+        .await?
+        .deserialize_index_metadata()?;
+    let term_set_query = TermSetQuery {
+        terms_per_field: HashMap::from([(ID_FIELD_NAME.to_string(), es_doc_ids)]),
+    };
+    submit_delete_query(metastore, index_metadata, term_set_query.into(), None, None).await?;
+    Ok(())
+}
+
 #[allow(clippy::result_large_err)]
 fn make_elastic_bulk_response_v2(
     ingest_response_v2: IngestResponseV2,
     mut per_subrequest_doc_handles: HashMap<u32, Vec<DocHandle>>,
     now: Instant,
     action_count: usize,
-    invalid_index_id_items: Vec<(usize, ElasticBulkItem)>,
+    non_ingested_actions: Vec<(usize, ElasticBulkAction)>,
 ) -> Result<ElasticBulkResponse, ElasticsearchError> {
     let mut positioned_actions: Vec<(usize, ElasticBulkAction)> = Vec::with_capacity(action_count);
     let mut errors = false;
@@ -322,9 +532,8 @@ fn make_elastic_bulk_response_v2(
         "doc handles should be empty"
     );
 
-    for (position, item) in invalid_index_id_items {
-        errors = true;
-        let action = ElasticBulkAction::Index(item);
+    for (position, action) in non_ingested_actions {
+        errors |= action.is_error();
         positioned_actions.push((position, action));
     }
 
@@ -379,14 +588,32 @@ fn make_invalid_index_id_item(index_id: String, es_doc_id: Option<String>) -> El
     }
 }
 
+fn make_unsupported_update_item(index_id: String, es_doc_id: Option<String>) -> ElasticBulkItem {
+    let error = ElasticBulkError {
+        index_id: Some(index_id.clone()),
+        exception: ElasticException::IllegalArgument,
+        reason: UNSUPPORTED_UPDATE_REASON.to_string(),
+    };
+    ElasticBulkItem {
+        index_id,
+        es_doc_id,
+        status: StatusCode::BAD_REQUEST,
+        error: Some(error),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use bytesize::ByteSize;
+    use quickwit_indexing::TestSandbox;
     use quickwit_proto::ingest::router::{
         IngestFailure, IngestFailureReason, IngestResponseV2, IngestSuccess,
         MockIngestRouterService,
     };
     use quickwit_proto::ingest::{ParseFailure, ParseFailureReason};
+    use quickwit_proto::metastore::{
+        ListDeleteTasksRequest, ListIndexAliasesResponse, MockMetastoreService,
+    };
     use quickwit_proto::types::{IndexUid, Position, ShardId};
     use warp::{Filter, Rejection, Reply};
 
@@ -400,42 +627,43 @@ mod tests {
 
     impl ElasticBulkAction {
         fn index_id(&self) -> &IndexId {
-            match self {
-                ElasticBulkAction::Create(item) => &item.index_id,
-                ElasticBulkAction::Index(item) => &item.index_id,
-            }
+            &self.item().index_id
         }
 
         fn es_doc_id(&self) -> Option<&str> {
-            match self {
-                ElasticBulkAction::Create(item) => item.es_doc_id.as_deref(),
-                ElasticBulkAction::Index(item) => item.es_doc_id.as_deref(),
-            }
+            self.item().es_doc_id.as_deref()
         }
 
         fn status(&self) -> StatusCode {
-            match self {
-                ElasticBulkAction::Create(item) => item.status,
-                ElasticBulkAction::Index(item) => item.status,
-            }
+            self.item().status
         }
 
         fn error(&self) -> Option<&ElasticBulkError> {
-            match self {
-                ElasticBulkAction::Create(item) => item.error.as_ref(),
-                ElasticBulkAction::Index(item) => item.error.as_ref(),
-            }
+            self.item().error.as_ref()
         }
     }
 
     fn es_compat_bulk_handler_v2(
         ingest_router: IngestRouterServiceClient,
         content_length_limit: ByteSize,
+    ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+        es_compat_bulk_handler_v2_with_metastore(
+            ingest_router,
+            MetastoreServiceClient::mocked(),
+            content_length_limit,
+        )
+    }
+
+    fn es_compat_bulk_handler_v2_with_metastore(
+        ingest_router: IngestRouterServiceClient,
+        metastore: MetastoreServiceClient,
+        content_length_limit: ByteSize,
     ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
         elastic_bulk_filter(content_length_limit)
             .and(with_arg(ingest_router))
-            .then(|body, bulk_options, ingest_router| {
-                elastic_bulk_ingest_v2(None, body, bulk_options, ingest_router)
+            .and(with_arg(metastore))
+            .then(|body, bulk_options, ingest_router, metastore| {
+                elastic_bulk_ingest_v2(None, body, bulk_options, ingest_router, metastore)
             })
             .and(extract_format_from_qs())
             .map(make_elastic_api_response)
@@ -518,6 +746,8 @@ mod tests {
             .map(|action| match action {
                 ElasticBulkAction::Create(item) => item,
                 ElasticBulkAction::Index(item) => item,
+                ElasticBulkAction::Update(item) => item,
+                ElasticBulkAction::Delete(item) => item,
             })
             .collect::<Vec<_>>();
         assert_eq!(items.len(), 3);
@@ -540,6 +770,119 @@ mod tests {
         assert_eq!(items[2].status, StatusCode::CREATED);
     }
 
+    #[tokio::test]
+    async fn test_bulk_api_update_and_delete() {
+        let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: id
+                type: text
+                tokenizer: raw
+                fast: true
+              - name: message
+                type: text
+            id_field: id
+        "#;
+        let test_sandbox = TestSandbox::create("my-index-1", doc_mapping_yaml, "", &["message"])
+            .await
+            .unwrap();
+        let mut mock_ingest_router = MockIngestRouterService::new();
+        mock_ingest_router
+            .expect_ingest()
+            .once()
+            .returning(|ingest_request| {
+                assert_eq!(ingest_request.subrequests.len(), 1);
+                let doc_batch = ingest_request.subrequests[0].doc_batch.as_ref().unwrap();
+                assert_eq!(doc_batch.num_docs(), 1);
+
+                Ok(IngestResponseV2 {
+                    successes: vec![IngestSuccess {
+                        subrequest_id: 0,
+                        index_uid: Some(IndexUid::for_test("my-index-1", 0)),
+                        source_id: INGEST_V2_SOURCE_ID.to_string(),
+                        shard_id: Some(ShardId::from(1)),
+                        replication_position_inclusive: Some(Position::offset(0u64)),
+                        num_ingested_docs: 1,
+                        parse_failures: Vec::new(),
+                    }],
+                    failures: Vec::new(),
+                })
+            });
+        let ingest_router = IngestRouterServiceClient::from_mock(mock_ingest_router);
+        let handler = es_compat_bulk_handler_v2_with_metastore(
+            ingest_router,
+            test_sandbox.metastore(),
+            ByteSize::mb(10),
+        );
+        let payload = r#"
+            {"update": {"_index": "my-index-1", "_id": "1"}}
+            {"doc": {"id": "1", "message": "my-message-1"}, "doc_as_upsert": true}
+            {"index": {"_index": "my-index-1", "_id": "2"}}
+            {"id": "2", "message": "my-message-2"}
+            {"delete": {"_index": "my-index-1", "_id": "3"}}
+            {"delete": {"_index": "my-index-1"}}
+            {"delete": {"_index": "my-index-2", "_id": "1"}}
+            {"delete": {"_index": "my-index-1", "_id": "2"}}
+        "#;
+        let response = warp::test::request()
+            .path("/_elastic/_bulk")
+            .method("POST")
+            .body(payload)
+            .reply(&handler)
+            .await;
+        assert_eq!(response.status(), 200);
+
+        let bulk_response: ElasticBulkResponse = serde_json::from_slice(response.body()).unwrap();
+        assert!(bulk_response.errors);
+
+        let actions = bulk_response.actions;
+        assert_eq!(actions.len(), 6);
+
+        assert!(matches!(actions[0], ElasticBulkAction::Update(_)));
+        assert_eq!(actions[0].es_doc_id(), Some("1"));
+        assert_eq!(actions[0].status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            actions[0].error().unwrap().reason,
+            UNSUPPORTED_UPDATE_REASON
+        );
+
+        assert!(matches!(actions[1], ElasticBulkAction::Index(_)));
+        assert_eq!(actions[1].es_doc_id(), Some("2"));
+        assert_eq!(actions[1].status(), StatusCode::CREATED);
+
+        assert!(matches!(actions[2], ElasticBulkAction::Delete(_)));
+        assert_eq!(actions[2].es_doc_id(), Some("3"));
+        assert_eq!(actions[2].status(), StatusCode::OK);
+
+        assert!(matches!(actions[3], ElasticBulkAction::Delete(_)));
+        assert_eq!(actions[3].status(), StatusCode::BAD_REQUEST);
+
+        assert!(matches!(actions[4], ElasticBulkAction::Delete(_)));
+        assert_eq!(actions[4].index_id(), "my-index-2");
+        assert_eq!(actions[4].status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            actions[4].error().unwrap().exception,
+            ElasticException::IndexNotFound
+        );
+
+        assert!(matches!(actions[5], ElasticBulkAction::Delete(_)));
+        assert_eq!(actions[5].es_doc_id(), Some("2"));
+        assert_eq!(actions[5].status(), StatusCode::BAD_REQUEST);
+
+        let delete_tasks = test_sandbox
+            .metastore()
+            .list_delete_tasks(ListDeleteTasksRequest::new(test_sandbox.index_uid(), 0))
+            .await
+            .unwrap()
+            .delete_tasks;
+        assert_eq!(delete_tasks.len(), 1);
+        let delete_query = delete_tasks[0].delete_query.as_ref().unwrap();
+        assert_eq!(
+            delete_query.query_ast,
+            r#"{"type":"term_set","terms_per_field":{"_id":["3"]}}"#
+        );
+        test_sandbox.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_bulk_api_accepts_empty_requests() {
         let ingest_router = IngestRouterServiceClient::mocked();
@@ -712,7 +1055,15 @@ mod tests {
                 })
             });
         let ingest_router = IngestRouterServiceClient::from_mock(mock_ingest_router);
-        let handler = es_compat_bulk_handler_v2(ingest_router, ByteSize::mb(10));
+        // The indexes are not aliases either.
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore
+            .expect_list_index_aliases()
+            .once()
+            .returning(|_| Ok(ListIndexAliasesResponse::default()));
+        let metastore = MetastoreServiceClient::from_mock(mock_metastore);
+        let handler =
+            es_compat_bulk_handler_v2_with_metastore(ingest_router, metastore, ByteSize::mb(10));
 
         let payload = r#"
             {"index": {"_index": "my-index-1", "_id" : "1"}}
@@ -931,6 +1282,8 @@ mod tests {
             .map(|action| match action {
                 ElasticBulkAction::Create(item) => item,
                 ElasticBulkAction::Index(item) => item,
+                ElasticBulkAction::Update(item) => item,
+                ElasticBulkAction::Delete(item) => item,
             })
             .collect::<Vec<_>>();
         assert_eq!(items.len(), 3);
@@ -19,15 +19,20 @@ use warp::reject::LengthRequired;
 use warp::{Filter, Rejection};
 
 use super::model::{
-    CatIndexQueryParams, DeleteQueryParams, FieldCapabilityQueryParams, FieldCapabilityRequestBody,
-    MultiSearchQueryParams, SearchQueryParamsCount,
+    CatIndexQueryParams, ClosePointInTimeBody, DeleteByQueryBody, DeleteQueryParams,
+    FieldCapabilityQueryParams, FieldCapabilityRequestBody, GetAsyncSearchQueryParams,
+    GetDocumentQueryParams, MultiGetBody, MultiSearchQueryParams, OpenPointInTimeQueryParams,
+    PutAliasBody, SearchQueryParamsCount, UpdateAliasesBody,
 };
 use crate::Body;
 use crate::decompression::get_body_bytes;
 use crate::elasticsearch_api::model::{
     ElasticBulkOptions, ScrollQueryParams, SearchBody, SearchQueryParams,
 };
-use crate::search_api::{extract_index_id_patterns, extract_index_id_patterns_default};
+use crate::search_api::{
+    extract_index_id_patterns, extract_index_id_patterns_default, tenant_id_header,
+};
+use crate::sql_api::{SqlQueryParams, SqlRequest};
 
 const BODY_LENGTH_LIMIT: ByteSize = ByteSize::mib(1);
 
@@ -47,10 +52,11 @@ pub(crate) fn elastic_cluster_info_filter() -> impl Filter<Extract = (), Error =
 
 #[utoipa::path(get, tag = "Search", path = "/_search")]
 pub(crate) fn elasticsearch_filter()
--> impl Filter<Extract = (SearchQueryParams,), Error = Rejection> + Clone {
+-> impl Filter<Extract = (SearchQueryParams, SearchBody), Error = Rejection> + Clone {
     warp::path!("_elastic" / "_search")
         .and(warp::get().or(warp::post()).unify())
-        .and(warp::query())
+        .and(search_query_params())
+        .and(json_or_empty())
 }
 
 #[utoipa::path(
@@ -124,6 +130,16 @@ fn json_or_empty<T: DeserializeOwned + Send + Default>()
         .unify()
 }
 
+/// Extracts the search query parameters, along with the tenant ID header.
+fn search_query_params() -> impl Filter<Extract = (SearchQueryParams,), Error = Rejection> + Clone {
+    warp::query().and(tenant_id_header()).map(
+        |mut search_params: SearchQueryParams, tenant_id_opt: Option<String>| {
+            search_params.tenant_id = tenant_id_opt;
+            search_params
+        },
+    )
+}
+
 #[utoipa::path(get, tag = "Metadata", path = "/{index}/_field_caps")]
 pub(crate) fn elastic_index_field_capabilities_filter() -> impl Filter<
     Extract = (
@@ -156,6 +172,24 @@ pub(crate) fn elastic_field_capabilities_filter() -> impl Filter<
         .and(json_or_empty())
 }
 
+#[utoipa::path(get, tag = "Metadata", path = "/{index}/_mapping")]
+pub(crate) fn elastic_index_mapping_filter()
+-> impl Filter<Extract = (Vec<String>,), Error = Rejection> + Clone {
+    warp::path!("_elastic" / String / "_mapping")
+        .or(warp::path!("_elastic" / String / "_mappings"))
+        .unify()
+        .and_then(extract_index_id_patterns)
+        .and(warp::get())
+}
+
+#[utoipa::path(get, tag = "Metadata", path = "/_mapping")]
+pub(crate) fn elastic_mapping_filter() -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::path!("_elastic" / "_mapping")
+        .or(warp::path!("_elastic" / "_mappings"))
+        .unify()
+        .and(warp::get())
+}
+
 #[utoipa::path(get, tag = "Metadata", path = "/_resolve/index/{index}")]
 pub(crate) fn elastic_resolve_index_filter()
 -> impl Filter<Extract = (Vec<String>,), Error = Rejection> + Clone {
@@ -171,7 +205,12 @@ pub(crate) fn elastic_index_count_filter()
     warp::path!("_elastic" / String / "_count")
         .and_then(extract_index_id_patterns)
         .and(warp::get().or(warp::post()).unify())
-        .and(warp::query())
+        .and(warp::query().and(tenant_id_header()).map(
+            |mut search_params: SearchQueryParamsCount, tenant_id_opt: Option<String>| {
+                search_params.tenant_id = tenant_id_opt;
+                search_params
+            },
+        ))
         .and(json_or_empty())
 }
 
@@ -184,6 +223,170 @@ pub(crate) fn elastic_delete_index_filter()
         .and(warp::query())
 }
 
+#[utoipa::path(post, tag = "Delete Tasks", path = "/{index}/_delete_by_query")]
+pub(crate) fn elastic_delete_by_query_filter()
+-> impl Filter<Extract = (String, DeleteByQueryBody), Error = Rejection> + Clone {
+    warp::path!("_elastic" / String / "_delete_by_query")
+        .and(warp::post())
+        .and(json_or_empty())
+}
+
+#[utoipa::path(post, tag = "Indexes", path = "/_aliases")]
+pub(crate) fn elastic_update_aliases_filter()
+-> impl Filter<Extract = (UpdateAliasesBody,), Error = Rejection> + Clone {
+    warp::path!("_elastic" / "_aliases")
+        .and(warp::post())
+        .and(json_or_empty())
+}
+
+async fn extract_index_and_alias_id_patterns(
+    comma_separated_index_id_patterns_opt: Option<String>,
+    comma_separated_alias_id_patterns_opt: Option<String>,
+) -> Result<(Vec<String>, Vec<String>), Rejection> {
+    let index_id_patterns = match comma_separated_index_id_patterns_opt {
+        Some(comma_separated_index_id_patterns) => {
+            extract_index_id_patterns(comma_separated_index_id_patterns).await?
+        }
+        None => Vec::new(),
+    };
+    let alias_id_patterns = match comma_separated_alias_id_patterns_opt {
+        Some(comma_separated_alias_id_patterns) => {
+            extract_index_id_patterns(comma_separated_alias_id_patterns).await?
+        }
+        None => Vec::new(),
+    };
+    Ok((index_id_patterns, alias_id_patterns))
+}
+
+#[utoipa::path(get, tag = "Indexes", path = "/{index}/_alias/{alias}")]
+pub(crate) fn elastic_get_aliases_filter()
+-> impl Filter<Extract = (Vec<String>, Vec<String>), Error = Rejection> + Clone {
+    warp::path!("_elastic" / "_alias")
+        .map(|| (None::<String>, None::<String>))
+        .or(warp::path!("_elastic" / "_alias" / String).map(|alias: String| (None, Some(alias))))
+        .unify()
+        .or(warp::path!("_elastic" / String / "_alias").map(|index: String| (Some(index), None)))
+        .unify()
+        .or(warp::path!("_elastic" / String / "_alias" / String)
+            .map(|index: String, alias: String| (Some(index), Some(alias))))
+        .unify()
+        .untuple_one()
+        .and(warp::get())
+        .and_then(extract_index_and_alias_id_patterns)
+        .untuple_one()
+}
+
+#[utoipa::path(put, tag = "Indexes", path = "/{index}/_alias/{alias}")]
+pub(crate) fn elastic_put_alias_filter()
+-> impl Filter<Extract = (String, String, PutAliasBody), Error = Rejection> + Clone {
+    warp::path!("_elastic" / String / "_alias" / String)
+        .and(warp::put().or(warp::post()).unify())
+        .and(json_or_empty())
+}
+
+#[utoipa::path(delete, tag = "Indexes", path = "/{index}/_alias/{alias}")]
+pub(crate) fn elastic_delete_alias_filter()
+-> impl Filter<Extract = (Vec<String>, Vec<String>), Error = Rejection> + Clone {
+    warp::path!("_elastic" / String / "_alias" / String)
+        .and(warp::delete())
+        .and_then(|index: String, alias: String| {
+            extract_index_and_alias_id_patterns(Some(index), Some(alias))
+        })
+        .untuple_one()
+}
+
+#[utoipa::path(get, tag = "Search", path = "/{index}/_doc/{id}")]
+pub(crate) fn elastic_get_document_filter()
+-> impl Filter<Extract = (String, String, GetDocumentQueryParams), Error = Rejection> + Clone {
+    warp::path!("_elastic" / String / "_doc" / String)
+        .and(warp::get())
+        .and(warp::query())
+}
+
+#[utoipa::path(post, tag = "Search", path = "/_mget")]
+pub(crate) fn elastic_multi_get_filter()
+-> impl Filter<Extract = (Option<String>, GetDocumentQueryParams, MultiGetBody), Error = Rejection>
++ Clone {
+    warp::path!("_elastic" / "_mget")
+        .map(|| None)
+        .or(warp::path!("_elastic" / String / "_mget").map(Some))
+        .unify()
+        .and(warp::get().or(warp::post()).unify())
+        .and(warp::query())
+        .and(json_or_empty())
+}
+
+#[utoipa::path(post, tag = "Search", path = "/{index}/_pit")]
+pub(crate) fn elastic_open_point_in_time_filter()
+-> impl Filter<Extract = (Vec<String>, OpenPointInTimeQueryParams), Error = Rejection> + Clone {
+    warp::path!("_elastic" / String / "_pit")
+        .and_then(extract_index_id_patterns)
+        .and(warp::post())
+        .and(warp::query())
+}
+
+#[utoipa::path(delete, tag = "Search", path = "/_pit")]
+pub(crate) fn elastic_close_point_in_time_filter()
+-> impl Filter<Extract = (ClosePointInTimeBody,), Error = Rejection> + Clone {
+    warp::path!("_elastic" / "_pit")
+        .and(warp::delete())
+        .and(json_or_empty())
+}
+
+#[utoipa::path(post, tag = "Search", path = "/{index}/_async_search")]
+pub(crate) fn elastic_submit_async_search_filter()
+-> impl Filter<Extract = (Vec<String>, SearchQueryParams, SearchBody), Error = Rejection> + Clone {
+    warp::path!("_elastic" / String / "_async_search")
+        .and_then(extract_index_id_patterns)
+        .and(warp::post())
+        .and(search_query_params())
+        .and(json_or_empty())
+}
+
+#[utoipa::path(get, tag = "Search", path = "/_async_search/{id}")]
+pub(crate) fn elastic_get_async_search_filter()
+-> impl Filter<Extract = (String, GetAsyncSearchQueryParams), Error = Rejection> + Clone {
+    warp::path!("_elastic" / "_async_search" / String)
+        .and(warp::get())
+        .and(warp::query())
+}
+
+#[utoipa::path(delete, tag = "Search", path = "/_async_search/{id}")]
+pub(crate) fn elastic_delete_async_search_filter()
+-> impl Filter<Extract = (String,), Error = Rejection> + Clone {
+    warp::path!("_elastic" / "_async_search" / String).and(warp::delete())
+}
+
+#[utoipa::path(post, tag = "Search", path = "/_sql")]
+pub(crate) fn elastic_sql_filter()
+-> impl Filter<Extract = (SqlQueryParams, SqlRequest), Error = Rejection> + Clone {
+    warp::path!("_elastic" / "_sql")
+        .and(warp::get().or(warp::post()).unify())
+        .and(warp::query())
+        .and(warp::body::content_length_limit(BODY_LENGTH_LIMIT.as_u64()))
+        .and(warp::body::json())
+}
+
+#[utoipa::path(get, tag = "Delete Tasks", path = "/_tasks/{task_id}")]
+pub(crate) fn elastic_get_task_filter()
+-> impl Filter<Extract = (String,), Error = Rejection> + Clone {
+    warp::path!("_elastic" / "_tasks" / String).and(warp::get())
+}
+
+#[utoipa::path(get, tag = "Search", path = "/_tasks")]
+pub(crate) fn elastic_list_tasks_filter() -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::path!("_elastic" / "_tasks").and(warp::get())
+}
+
+#[utoipa::path(post, tag = "Search", path = "/_tasks/{task_id}/_cancel")]
+pub(crate) fn elastic_cancel_task_filter()
+-> impl Filter<Extract = (String,), Error = Rejection> + Clone {
+    warp::path!("_elastic" / "_tasks" / String / "_cancel")
+        .and(warp::post())
+        .or(warp::path!("_elastic" / "_tasks" / String).and(warp::delete()))
+        .unify()
+}
+
 // No support for any query parameters for now.
 #[utoipa::path(get, tag = "Search", path = "/{index}/_stats")]
 pub(crate) fn elastic_index_stats_filter()
@@ -227,7 +430,7 @@ pub(crate) fn elastic_index_search_filter()
     warp::path!("_elastic" / String / "_search")
         .and_then(extract_index_id_patterns)
         .and(warp::get().or(warp::post()).unify())
-        .and(warp::query())
+        .and(search_query_params())
         .and(json_or_empty())
 }
 
@@ -238,7 +441,12 @@ pub(crate) fn elastic_multi_search_filter()
         .and(warp::body::content_length_limit(BODY_LENGTH_LIMIT.as_u64()))
         .and(warp::body::bytes())
         .and(warp::post())
-        .and(warp::query())
+        .and(warp::query().and(tenant_id_header()).map(
+            |mut multi_search_params: MultiSearchQueryParams, tenant_id_opt: Option<String>| {
+                multi_search_params.tenant_id = tenant_id_opt;
+                multi_search_params
+            },
+        ))
 }
 
 fn merge_scroll_body_params(
@@ -31,11 +31,19 @@ use quickwit_proto::metastore::MetastoreServiceClient;
 use quickwit_search::SearchService;
 use rest_handler::es_compat_cluster_health_handler;
 pub use rest_handler::{
-    es_compat_cat_indices_handler, es_compat_cluster_info_handler, es_compat_delete_index_handler,
+    es_compat_cancel_task_handler, es_compat_cat_indices_handler,
+    es_compat_close_point_in_time_handler, es_compat_cluster_info_handler,
+    es_compat_delete_alias_handler, es_compat_delete_async_search_handler,
+    es_compat_delete_by_query_handler, es_compat_delete_index_handler,
+    es_compat_get_aliases_handler, es_compat_get_async_search_handler,
+    es_compat_get_document_handler, es_compat_get_task_handler,
     es_compat_index_cat_indices_handler, es_compat_index_count_handler,
     es_compat_index_field_capabilities_handler, es_compat_index_multi_search_handler,
-    es_compat_index_search_handler, es_compat_index_stats_handler, es_compat_resolve_index_handler,
-    es_compat_scroll_handler, es_compat_search_handler, es_compat_stats_handler,
+    es_compat_index_search_handler, es_compat_index_stats_handler, es_compat_list_tasks_handler,
+    es_compat_mapping_handler, es_compat_multi_get_handler, es_compat_open_point_in_time_handler,
+    es_compat_put_alias_handler, es_compat_resolve_index_handler, es_compat_scroll_handler,
+    es_compat_search_handler, es_compat_sql_handler, es_compat_stats_handler,
+    es_compat_submit_async_search_handler, es_compat_update_aliases_handler,
 };
 use serde::{Deserialize, Serialize};
 use warp::hyper::StatusCode;
@@ -68,6 +76,7 @@ pub fn elastic_api_handlers(
         .or(es_compat_bulk_handler(
             ingest_service.clone(),
             ingest_router.clone(),
+            metastore.clone(),
             ingest_content_length_limit,
             enable_ingest_v1,
             enable_ingest_v2,
@@ -76,6 +85,7 @@ pub fn elastic_api_handlers(
         .or(es_compat_index_bulk_handler(
             ingest_service,
             ingest_router,
+            metastore.clone(),
             ingest_content_length_limit,
             enable_ingest_v1,
             enable_ingest_v2,
@@ -91,10 +101,48 @@ pub fn elastic_api_handlers(
         .or(es_compat_index_stats_handler(metastore.clone()))
         .or(es_compat_delete_index_handler(index_service))
         .or(es_compat_stats_handler(metastore.clone()))
-        .or(es_compat_cluster_health_handler(cluster))
+        .or(es_compat_cluster_health_handler(cluster.clone()))
         .or(es_compat_index_cat_indices_handler(metastore.clone()))
         .or(es_compat_cat_indices_handler(metastore.clone()))
         .or(es_compat_resolve_index_handler(metastore.clone()))
+        .boxed()
+        .or(es_compat_delete_by_query_handler(metastore.clone()))
+        .or(es_compat_get_task_handler(
+            metastore.clone(),
+            search_service.clone(),
+            cluster.clone(),
+        ))
+        .or(es_compat_mapping_handler(metastore.clone()))
+        .boxed()
+        .or(es_compat_update_aliases_handler(metastore.clone()))
+        .or(es_compat_get_aliases_handler(metastore.clone()))
+        .or(es_compat_put_alias_handler(metastore.clone()))
+        .or(es_compat_delete_alias_handler(metastore.clone()))
+        .boxed()
+        .or(es_compat_open_point_in_time_handler(search_service.clone()))
+        .or(es_compat_close_point_in_time_handler(
+            search_service.clone(),
+        ))
+        .or(es_compat_get_document_handler(search_service.clone()))
+        .or(es_compat_multi_get_handler(search_service.clone()))
+        .boxed()
+        .or(es_compat_submit_async_search_handler(
+            search_service.clone(),
+        ))
+        .or(es_compat_get_async_search_handler(search_service.clone()))
+        .or(es_compat_delete_async_search_handler(
+            search_service.clone(),
+        ))
+        .boxed()
+        .or(es_compat_list_tasks_handler(
+            search_service.clone(),
+            cluster.clone(),
+        ))
+        .or(es_compat_cancel_task_handler(
+            search_service.clone(),
+            cluster,
+        ))
+        .or(es_compat_sql_handler(search_service))
         .recover(recover_fn)
         .boxed()
     // Register newly created handlers here.
@@ -145,14 +193,22 @@ mod tests {
     use std::sync::Arc;
 
     use assert_json_diff::assert_json_include;
+    use base64::prelude::{BASE64_STANDARD, Engine};
     use mockall::predicate;
     use quickwit_cluster::{ChannelTransport, Cluster, create_cluster_for_test};
     use quickwit_config::NodeConfig;
     use quickwit_index_management::IndexService;
+    use quickwit_indexing::TestSandbox;
     use quickwit_ingest::{IngestApiService, IngestServiceClient};
     use quickwit_metastore::metastore_for_test;
     use quickwit_proto::ingest::router::IngestRouterServiceClient;
     use quickwit_proto::metastore::MetastoreServiceClient;
+    use quickwit_proto::search::{
+        AsyncSearchResponse, CancelSearchTaskResponse, ClosePointInTimeResponse,
+        DeleteAsyncSearchResponse, ListSearchTasksResponse, OpenPointInTimeResponse,
+        SearchResponse, SearchTask,
+    };
+    use quickwit_proto::types::IndexUid;
     use quickwit_search::MockSearchService;
     use quickwit_storage::StorageResolver;
     use serde_json::Value as JsonValue;
@@ -161,7 +217,16 @@ mod tests {
     use super::elastic_api_handlers;
     use super::model::ElasticsearchError;
     use crate::BuildInfo;
-    use crate::elasticsearch_api::rest_handler::es_compat_cluster_info_handler;
+    use crate::elasticsearch_api::rest_handler::{
+        es_compat_cancel_task_handler, es_compat_close_point_in_time_handler,
+        es_compat_cluster_info_handler, es_compat_delete_alias_handler,
+        es_compat_delete_async_search_handler, es_compat_delete_by_query_handler,
+        es_compat_get_aliases_handler, es_compat_get_async_search_handler,
+        es_compat_get_document_handler, es_compat_get_task_handler, es_compat_list_tasks_handler,
+        es_compat_multi_get_handler, es_compat_open_point_in_time_handler,
+        es_compat_put_alias_handler, es_compat_search_handler,
+        es_compat_submit_async_search_handler, es_compat_update_aliases_handler,
+    };
     use crate::rest::recover_fn;
 
     fn ingest_service_client() -> IngestServiceClient {
@@ -539,4 +604,591 @@ mod tests {
             .await;
         assert_eq!(resp.status(), 200);
     }
+
+    #[tokio::test]
+    async fn test_es_compat_delete_by_query_and_get_task() {
+        let index_id = "test-es-delete-by-query";
+        let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+            mode: lenient
+        "#;
+        let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "", &["body"])
+            .await
+            .unwrap();
+        let metastore = test_sandbox.metastore();
+        let search_service = Arc::new(MockSearchService::new());
+        let cluster = mock_cluster().await;
+        let handler = es_compat_delete_by_query_handler(metastore.clone())
+            .or(es_compat_get_task_handler(
+                metastore,
+                search_service,
+                cluster,
+            ))
+            .recover(recover_fn);
+
+        let resp = warp::test::request()
+            .path("/_elastic/test-es-delete-by-query/_delete_by_query")
+            .method("POST")
+            .body(r#"{"query": {"term": {"body": {"value": "myterm"}}}}"#)
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let task_id = resp_json["task"].as_str().unwrap();
+        assert_eq!(task_id, format!("{}:1", test_sandbox.index_uid()));
+
+        let resp = warp::test::request()
+            .path(&format!("/_elastic/_tasks/{task_id}"))
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!({
+            "completed": true,
+            "task": {
+                "id": 1,
+                "action": "indices:data/write/delete/byquery",
+                "status": {"total": 0, "remaining": 0},
+            }
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+
+        let resp = warp::test::request()
+            .path(&format!("/_elastic/_tasks/{}:2", test_sandbox.index_uid()))
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        // A delete by query without query is rejected.
+        let resp = warp::test::request()
+            .path("/_elastic/test-es-delete-by-query/_delete_by_query")
+            .method("POST")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+
+        // A delete by query on an unknown field is rejected.
+        let resp = warp::test::request()
+            .path("/_elastic/test-es-delete-by-query/_delete_by_query")
+            .method("POST")
+            .body(r#"{"query": {"term": {"unknown": {"value": "myterm"}}}}"#)
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+
+        test_sandbox.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_es_compat_aliases() {
+        let index_id = "test-es-aliases";
+        let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: tenant
+                type: text
+                tokenizer: raw
+            mode: lenient
+        "#;
+        let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "", &["tenant"])
+            .await
+            .unwrap();
+        let metastore = test_sandbox.metastore();
+        let handler = es_compat_update_aliases_handler(metastore.clone())
+            .or(es_compat_get_aliases_handler(metastore.clone()))
+            .or(es_compat_put_alias_handler(metastore.clone()))
+            .or(es_compat_delete_alias_handler(metastore))
+            .recover(recover_fn);
+
+        let resp = warp::test::request()
+            .path("/_elastic/test-es-aliases/_alias/logs")
+            .method("PUT")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json, serde_json::json!({"acknowledged": true}));
+
+        let resp = warp::test::request()
+            .path("/_elastic/_aliases")
+            .method("POST")
+            .json(&serde_json::json!({
+                "actions": [
+                    {
+                        "add": {
+                            "index": "test-es-aliases",
+                            "alias": "acme-logs",
+                            "filter": {"term": {"tenant": "acme"}},
+                            "is_write_index": true
+                        }
+                    }
+                ]
+            }))
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let resp = warp::test::request()
+            .path("/_elastic/test-es-aliases/_alias")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let aliases_json = &resp_json["test-es-aliases"]["aliases"];
+        assert_eq!(aliases_json["logs"], serde_json::json!({}));
+        assert_eq!(aliases_json["acme-logs"]["is_write_index"], true);
+        assert_eq!(aliases_json["acme-logs"]["filter"]["type"], "term");
+
+        // The actions are applied atomically: the failed removal cancels the addition.
+        let resp = warp::test::request()
+            .path("/_elastic/_aliases")
+            .method("POST")
+            .json(&serde_json::json!({
+                "actions": [
+                    {"add": {"index": "test-es-aliases", "alias": "metrics"}},
+                    {"remove": {"index": "test-es-aliases", "alias": "does-not-exist"}}
+                ]
+            }))
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        let resp = warp::test::request()
+            .path("/_elastic/_alias/metrics")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        // Aliases must target existing indexes.
+        let resp = warp::test::request()
+            .path("/_elastic/test-es-aliases-does-not-exist/_alias/logs")
+            .method("PUT")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        let resp = warp::test::request()
+            .path("/_elastic/test-es-aliases/_alias/logs")
+            .method("DELETE")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let resp = warp::test::request()
+            .path("/_elastic/_alias/logs")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        let resp = warp::test::request()
+            .path("/_elastic/test-es-aliases/_alias/logs")
+            .method("DELETE")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        test_sandbox.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_es_compat_point_in_time() {
+        let pit_id_json = serde_json::json!({
+            "pit_ulid": "01HAV29D4XY3D462FS3D8K5Q2H",
+            "index_uids": [IndexUid::for_test("index-1", 0)],
+        });
+        let pit_id = BASE64_STANDARD.encode(serde_json::to_vec(&pit_id_json).unwrap());
+
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_open_point_in_time()
+            .withf(|open_request| {
+                open_request.index_id_patterns == ["index-1"] && open_request.keep_alive_secs == 60
+            })
+            .return_once({
+                let pit_id = pit_id.clone();
+                move |_| Ok(OpenPointInTimeResponse { pit_id })
+            });
+        mock_search_service
+            .expect_root_search()
+            .withf({
+                let pit_id = pit_id.clone();
+                move |search_request| {
+                    let point_in_time = search_request.point_in_time.as_ref().unwrap();
+                    search_request.index_id_patterns.is_empty()
+                        && point_in_time.pit_id == pit_id
+                        && point_in_time.keep_alive_secs == Some(120)
+                }
+            })
+            .returning(|_| Ok(Default::default()));
+        mock_search_service
+            .expect_close_point_in_time()
+            .return_once(|_| Ok(ClosePointInTimeResponse { num_freed: 1 }));
+        let search_service = Arc::new(mock_search_service);
+        let handler = es_compat_open_point_in_time_handler(search_service.clone())
+            .or(es_compat_close_point_in_time_handler(
+                search_service.clone(),
+            ))
+            .or(es_compat_search_handler(search_service))
+            .recover(recover_fn);
+
+        let resp = warp::test::request()
+            .path("/_elastic/index-1/_pit?keep_alive=1m")
+            .method("POST")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json, serde_json::json!({"id": pit_id}));
+
+        let search_body = serde_json::json!({"pit": {"id": pit_id, "keep_alive": "2m"}});
+        let resp = warp::test::request()
+            .path("/_elastic/_search")
+            .method("POST")
+            .json(&search_body)
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json["pit_id"], pit_id);
+
+        // Searching without a point in time requires targeting some indexes.
+        let resp = warp::test::request()
+            .path("/_elastic/_search")
+            .method("POST")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 501);
+
+        let resp = warp::test::request()
+            .path("/_elastic/_pit")
+            .method("DELETE")
+            .json(&serde_json::json!({"id": pit_id}))
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            resp_json,
+            serde_json::json!({"succeeded": true, "num_freed": 1})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_es_compat_async_search() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_submit_async_search()
+            .withf(|submit_request| {
+                let search_request = submit_request.search_request.as_ref().unwrap();
+                search_request.index_id_patterns == ["index-1"]
+                    && submit_request.keep_alive_secs == 3_600
+                    && submit_request.wait_for_completion_timeout_millis == 100
+            })
+            .return_once(|_| {
+                Ok(AsyncSearchResponse {
+                    async_search_id: "01HAV29D4XY3D462FS3D8K5Q2H".to_string(),
+                    is_running: true,
+                    is_partial: true,
+                    start_time_millis: 1_000,
+                    expiration_time_millis: 3_601_000,
+                    num_targeted_splits: 2,
+                    response: Some(SearchResponse {
+                        num_hits: 3,
+                        num_successful_splits: 1,
+                        ..Default::default()
+                    }),
+                    error: None,
+                })
+            });
+        mock_search_service
+            .expect_get_async_search()
+            .withf(|get_request| {
+                get_request.async_search_id == "01HAV29D4XY3D462FS3D8K5Q2H"
+                    && get_request.keep_alive_secs == Some(120)
+            })
+            .return_once(|_| {
+                Ok(AsyncSearchResponse {
+                    async_search_id: "01HAV29D4XY3D462FS3D8K5Q2H".to_string(),
+                    is_running: false,
+                    is_partial: false,
+                    start_time_millis: 1_000,
+                    expiration_time_millis: 121_000,
+                    num_targeted_splits: 2,
+                    response: Some(SearchResponse {
+                        num_hits: 6,
+                        num_successful_splits: 2,
+                        ..Default::default()
+                    }),
+                    error: None,
+                })
+            });
+        mock_search_service
+            .expect_delete_async_search()
+            .times(2)
+            .returning({
+                let mut num_freed = 2;
+                move |_| {
+                    num_freed -= 1;
+                    Ok(DeleteAsyncSearchResponse { num_freed })
+                }
+            });
+        let search_service = Arc::new(mock_search_service);
+        let handler = es_compat_submit_async_search_handler(search_service.clone())
+            .or(es_compat_get_async_search_handler(search_service.clone()))
+            .or(es_compat_delete_async_search_handler(search_service))
+            .recover(recover_fn);
+
+        let resp = warp::test::request()
+            .path("/_elastic/index-1/_async_search?keep_alive=1h&wait_for_completion_timeout=100ms")
+            .method("POST")
+            .json(&serde_json::json!({"query": {"match_all": {}}}))
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_json_include!(
+            actual: resp_json,
+            expected: serde_json::json!({
+                "id": "01HAV29D4XY3D462FS3D8K5Q2H",
+                "is_partial": true,
+                "is_running": true,
+                "start_time_in_millis": 1_000,
+                "expiration_time_in_millis": 3_601_000,
+                "response": {"hits": {"total": {"value": 3}}},
+            })
+        );
+
+        let resp = warp::test::request()
+            .path("/_elastic/_async_search/01HAV29D4XY3D462FS3D8K5Q2H?keep_alive=2m")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_json_include!(
+            actual: resp_json,
+            expected: serde_json::json!({
+                "is_partial": false,
+                "is_running": false,
+                "response": {"hits": {"total": {"value": 6}}},
+            })
+        );
+
+        let resp = warp::test::request()
+            .path("/_elastic/_async_search/01HAV29D4XY3D462FS3D8K5Q2H")
+            .method("DELETE")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json, serde_json::json!({"acknowledged": true}));
+
+        let resp = warp::test::request()
+            .path("/_elastic/_async_search/01HAV29D4XY3D462FS3D8K5Q2H")
+            .method("DELETE")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_es_compat_list_and_cancel_tasks() {
+        let search_task = SearchTask {
+            search_task_id: "01HAV29D4XY3D462FS3D8K5Q2H".to_string(),
+            index_id_patterns: vec!["index-1".to_string()],
+            start_time_millis: 1_000,
+            searcher_grpc_addr: "127.0.0.1:7281".to_string(),
+            ..Default::default()
+        };
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_list_search_tasks()
+            .times(3)
+            .returning({
+                let search_task = search_task.clone();
+                move |_| {
+                    Ok(ListSearchTasksResponse {
+                        search_tasks: vec![search_task.clone()],
+                    })
+                }
+            });
+        mock_search_service
+            .expect_root_cancel_search_task()
+            .times(2)
+            .returning(move |cancel_request| {
+                let cancelled_search_tasks =
+                    if cancel_request.search_task_id == search_task.search_task_id {
+                        vec![search_task.clone()]
+                    } else {
+                        Vec::new()
+                    };
+                Ok(CancelSearchTaskResponse {
+                    cancelled_search_tasks,
+                    num_cancelled_leaf_searches: 1,
+                })
+            });
+        let search_service = Arc::new(mock_search_service);
+        let cluster = mock_cluster().await;
+        let handler = es_compat_list_tasks_handler(search_service.clone(), cluster.clone())
+            .or(es_compat_get_task_handler(
+                MetastoreServiceClient::mocked(),
+                search_service.clone(),
+                cluster.clone(),
+            ))
+            .or(es_compat_cancel_task_handler(search_service, cluster))
+            .recover(recover_fn);
+
+        let resp = warp::test::request()
+            .path("/_elastic/_tasks")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_json_include!(
+            actual: resp_json,
+            expected: serde_json::json!({
+                "nodes": {
+                    "127.0.0.1:7281": {
+                        "tasks": {
+                            "127.0.0.1:7281:01HAV29D4XY3D462FS3D8K5Q2H": {
+                                "id": "01HAV29D4XY3D462FS3D8K5Q2H",
+                                "action": "indices:data/read/search",
+                                "cancellable": true,
+                                "cancelled": false,
+                            }
+                        }
+                    }
+                }
+            })
+        );
+
+        let resp = warp::test::request()
+            .path("/_elastic/_tasks/127.0.0.1:7281:01HAV29D4XY3D462FS3D8K5Q2H")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_json_include!(
+            actual: resp_json,
+            expected: serde_json::json!({
+                "completed": false,
+                "task": {
+                    "node": "127.0.0.1:7281",
+                    "id": "01HAV29D4XY3D462FS3D8K5Q2H",
+                    "action": "indices:data/read/search",
+                    "cancellable": true,
+                }
+            })
+        );
+
+        let resp = warp::test::request()
+            .path("/_elastic/_tasks/127.0.0.1:7281:01HAV29D4XY3D462FS3D8K5Q2J")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        let resp = warp::test::request()
+            .path("/_elastic/_tasks/127.0.0.1:7281:01HAV29D4XY3D462FS3D8K5Q2H/_cancel")
+            .method("POST")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_json_include!(
+            actual: resp_json,
+            expected: serde_json::json!({
+                "nodes": {
+                    "127.0.0.1:7281": {
+                        "tasks": {
+                            "127.0.0.1:7281:01HAV29D4XY3D462FS3D8K5Q2H": {"cancelled": true}
+                        }
+                    }
+                }
+            })
+        );
+
+        let resp = warp::test::request()
+            .path("/_elastic/_tasks/unknown-task")
+            .method("DELETE")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_es_compat_get_document() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .returning(|search_request| {
+                let query_ast: JsonValue = serde_json::from_str(&search_request.query_ast).unwrap();
+                assert_eq!(query_ast["field"], "_id");
+                assert_eq!(search_request.max_hits, 1);
+                let hits = if query_ast["value"] == "event-1" {
+                    vec![quickwit_proto::search::Hit {
+                        json: r#"{"event_id": "event-1", "body": "hello"}"#.to_string(),
+                        index_id: "index-1".to_string(),
+                        ..Default::default()
+                    }]
+                } else {
+                    Vec::new()
+                };
+                Ok(quickwit_proto::search::SearchResponse {
+                    num_hits: hits.len() as u64,
+                    hits,
+                    ..Default::default()
+                })
+            });
+        let search_service = Arc::new(mock_search_service);
+        let handler = es_compat_get_document_handler(search_service.clone())
+            .or(es_compat_multi_get_handler(search_service))
+            .recover(recover_fn);
+
+        let resp = warp::test::request()
+            .path("/_elastic/index-1/_doc/event-1?_source_includes=body")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            resp_json,
+            serde_json::json!({
+                "_index": "index-1",
+                "_id": "event-1",
+                "found": true,
+                "_source": {"body": "hello"}
+            })
+        );
+
+        let resp = warp::test::request()
+            .path("/_elastic/index-1/_doc/event-2")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            resp_json,
+            serde_json::json!({"_index": "index-1", "_id": "event-2", "found": false})
+        );
+
+        let resp = warp::test::request()
+            .path("/_elastic/index-1/_mget")
+            .method("POST")
+            .json(&serde_json::json!({"ids": ["event-1", "event-2"]}))
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json["docs"][0]["found"], true);
+        assert_eq!(resp_json["docs"][0]["_source"]["event_id"], "event-1");
+        assert_eq!(resp_json["docs"][1]["found"], false);
+
+        let resp = warp::test::request()
+            .path("/_elastic/_mget")
+            .method("POST")
+            .json(&serde_json::json!({"docs": [{"_id": "event-1"}]}))
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
 }
@@ -0,0 +1,269 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use glob::Pattern as GlobPattern;
+use quickwit_config::{IndexAlias, IndexAliasAction, IndexAliasTarget};
+use quickwit_query::ElasticQueryDsl;
+use quickwit_query::query_ast::QueryAst;
+use serde::{Deserialize, Serialize};
+use warp::hyper::StatusCode;
+
+use super::{ElasticException, ElasticsearchError};
+
+/// Body of the `_aliases` API.
+#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateAliasesBody {
+    pub actions: Vec<AliasAction>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AliasAction {
+    Add(AddAliasAction),
+    Remove(RemoveAliasAction),
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AddAliasAction {
+    pub index: String,
+    pub alias: String,
+    #[serde(default)]
+    pub filter: Option<ElasticQueryDsl>,
+    #[serde(default)]
+    pub is_write_index: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RemoveAliasAction {
+    pub index: String,
+    pub alias: String,
+}
+
+/// Body of the `PUT {index}/_alias/{alias}` API.
+#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PutAliasBody {
+    #[serde(default)]
+    pub filter: Option<ElasticQueryDsl>,
+    #[serde(default)]
+    pub is_write_index: bool,
+}
+
+impl AliasAction {
+    /// Converts the action into an [`IndexAliasAction`], parsing its filter into a query AST.
+    #[allow(clippy::result_large_err)]
+    pub fn into_index_alias_action(self) -> Result<IndexAliasAction, ElasticsearchError> {
+        match self {
+            AliasAction::Add(add_action) => {
+                let filter: Option<QueryAst> = add_action
+                    .filter
+                    .map(QueryAst::try_from)
+                    .transpose()
+                    .map_err(|error| {
+                        ElasticsearchError::new(
+                            StatusCode::BAD_REQUEST,
+                            format!("invalid alias filter: {error}"),
+                            Some(ElasticException::IllegalArgument),
+                        )
+                    })?;
+                let target = IndexAliasTarget {
+                    index_id: add_action.index,
+                    filter,
+                    is_write_index: add_action.is_write_index,
+                };
+                Ok(IndexAliasAction::Add {
+                    alias_id: add_action.alias,
+                    target,
+                })
+            }
+            AliasAction::Remove(remove_action) => Ok(IndexAliasAction::Remove {
+                alias_id: remove_action.alias,
+                index_id: remove_action.index,
+            }),
+        }
+    }
+}
+
+/// Returns JSON in the format:
+///
+/// {
+///   "my-index": {
+///     "aliases": {
+///       "my-alias": {
+///         "filter": {"type": "term", "field": "tenant", "value": "acme"},
+///         "is_write_index": true
+///       }
+///     }
+///   }
+/// }
+///
+/// Filters are returned as query ASTs, not in the Elasticsearch query DSL they were created with.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(transparent)]
+pub struct ElasticsearchAliasesResponse {
+    pub indices: BTreeMap<String, ElasticsearchIndexAliases>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ElasticsearchIndexAliases {
+    pub aliases: BTreeMap<String, ElasticsearchAliasProperties>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ElasticsearchAliasProperties {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<QueryAst>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_write_index: bool,
+}
+
+fn matches_any_pattern(patterns: &[GlobPattern], name: &str) -> bool {
+    patterns.is_empty() || patterns.iter().any(|pattern| pattern.matches(name))
+}
+
+/// Builds the `_alias` response listing the aliases matching the alias patterns and targeting
+/// the indexes matching the index patterns. Empty pattern lists match everything.
+#[allow(clippy::result_large_err)]
+pub fn convert_to_es_aliases_response(
+    index_aliases: Vec<IndexAlias>,
+    index_id_patterns: &[String],
+    alias_id_patterns: &[String],
+) -> Result<ElasticsearchAliasesResponse, ElasticsearchError> {
+    let parse_patterns = |patterns: &[String]| {
+        patterns
+            .iter()
+            .filter(|pattern| *pattern != "_all")
+            .map(|pattern| {
+                GlobPattern::new(pattern).map_err(|error| {
+                    ElasticsearchError::new(
+                        StatusCode::BAD_REQUEST,
+                        format!("invalid pattern `{pattern}`: {error}"),
+                        Some(ElasticException::IllegalArgument),
+                    )
+                })
+            })
+            .collect::<Result<Vec<GlobPattern>, ElasticsearchError>>()
+    };
+    let index_id_patterns = parse_patterns(index_id_patterns)?;
+    let alias_id_patterns = parse_patterns(alias_id_patterns)?;
+
+    let mut response = ElasticsearchAliasesResponse::default();
+
+    for index_alias in index_aliases {
+        if !matches_any_pattern(&alias_id_patterns, &index_alias.alias_id) {
+            continue;
+        }
+        for target in index_alias.targets {
+            if !matches_any_pattern(&index_id_patterns, &target.index_id) {
+                continue;
+            }
+            let alias_properties = ElasticsearchAliasProperties {
+                filter: target.filter,
+                is_write_index: target.is_write_index,
+            };
+            response
+                .indices
+                .entry(target.index_id)
+                .or_default()
+                .aliases
+                .insert(index_alias.alias_id.clone(), alias_properties);
+        }
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alias_action_into_index_alias_action() {
+        let body: UpdateAliasesBody = serde_json::from_str(
+            r#"{
+                "actions": [
+                    {"add": {"index": "logs-2026-10", "alias": "logs", "is_write_index": true}},
+                    {"add": {"index": "logs-2026-10", "alias": "acme-logs", "filter": {"term": {"tenant": "acme"}}}},
+                    {"remove": {"index": "logs-2026-09", "alias": "logs"}}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let index_alias_actions: Vec<IndexAliasAction> = body
+            .actions
+            .into_iter()
+            .map(|action| action.into_index_alias_action().unwrap())
+            .collect();
+        assert_eq!(index_alias_actions.len(), 3);
+
+        let IndexAliasAction::Add { alias_id, target } = &index_alias_actions[0] else {
+            panic!("expected add action");
+        };
+        assert_eq!(alias_id, "logs");
+        assert_eq!(target.index_id, "logs-2026-10");
+        assert!(target.filter.is_none());
+        assert!(target.is_write_index);
+
+        let IndexAliasAction::Add { alias_id, target } = &index_alias_actions[1] else {
+            panic!("expected add action");
+        };
+        assert_eq!(alias_id, "acme-logs");
+        assert!(matches!(target.filter, Some(QueryAst::Term(_))));
+        assert!(!target.is_write_index);
+
+        assert_eq!(
+            index_alias_actions[2],
+            IndexAliasAction::Remove {
+                alias_id: "logs".to_string(),
+                index_id: "logs-2026-09".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_convert_to_es_aliases_response() {
+        let mut logs_alias = IndexAlias::for_test("logs", &["logs-2026-09", "logs-2026-10"]);
+        logs_alias.targets[1].is_write_index = true;
+        let metrics_alias = IndexAlias::for_test("metrics", &["metrics-2026-10"]);
+        let index_aliases = vec![logs_alias, metrics_alias];
+
+        let response = convert_to_es_aliases_response(index_aliases.clone(), &[], &[]).unwrap();
+        let response_json = serde_json::to_value(&response).unwrap();
+        let expected_response_json = serde_json::json!({
+            "logs-2026-09": {"aliases": {"logs": {}}},
+            "logs-2026-10": {"aliases": {"logs": {"is_write_index": true}}},
+            "metrics-2026-10": {"aliases": {"metrics": {}}}
+        });
+        assert_eq!(response_json, expected_response_json);
+
+        let response = convert_to_es_aliases_response(
+            index_aliases.clone(),
+            &["logs-*".to_string()],
+            &["_all".to_string()],
+        )
+        .unwrap();
+        assert_eq!(response.indices.len(), 2);
+
+        let response =
+            convert_to_es_aliases_response(index_aliases, &[], &["metrics".to_string()]).unwrap();
+        assert_eq!(response.indices.len(), 1);
+        assert!(response.indices.contains_key("metrics-2026-10"));
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use elasticsearch_dsl::search::ErrorCause;
+use quickwit_search::SearchError;
+use serde::{Deserialize, Serialize};
+
+use super::ElasticsearchResponse;
+
+/// Same defaults as Elasticsearch.
+const DEFAULT_WAIT_FOR_COMPLETION_TIMEOUT: Duration = Duration::from_secs(1);
+const DEFAULT_KEEP_ALIVE: Duration = Duration::from_secs(5 * 24 * 3_600);
+
+fn parse_duration(param_name: &str, duration_str: &str) -> Result<Duration, SearchError> {
+    humantime::parse_duration(duration_str).map_err(|_err| {
+        SearchError::InvalidArgument(format!("invalid {param_name} duration: `{duration_str}`"))
+    })
+}
+
+/// Returns the keep alive of an async search, in seconds, defaulting to 5 days.
+pub fn parse_keep_alive_secs(keep_alive_opt: Option<&str>) -> Result<u32, SearchError> {
+    let keep_alive = match keep_alive_opt {
+        Some(keep_alive_str) => parse_duration("keep alive", keep_alive_str)?,
+        None => DEFAULT_KEEP_ALIVE,
+    };
+    Ok(keep_alive.as_secs() as u32)
+}
+
+/// Returns the duration to wait for an async search to complete, defaulting to 1 second.
+pub fn parse_wait_for_completion_timeout(
+    wait_for_completion_timeout_opt: Option<&str>,
+) -> Result<Duration, SearchError> {
+    match wait_for_completion_timeout_opt {
+        Some(wait_for_completion_timeout_str) => parse_duration(
+            "wait for completion timeout",
+            wait_for_completion_timeout_str,
+        ),
+        None => Ok(DEFAULT_WAIT_FOR_COMPLETION_TIMEOUT),
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GetAsyncSearchQueryParams {
+    #[serde(default)]
+    pub keep_alive: Option<String>,
+    #[serde(default)]
+    pub typed_keys: Option<bool>,
+    #[serde(default)]
+    pub wait_for_completion_timeout: Option<String>,
+}
+
+/// Returns JSON in the format below. The `id` is omitted when the search completed before the wait
+/// for completion timeout and `keep_on_completion` was not set.
+///
+/// {
+///   "id": "01HAV29D4XY3D462FS3D8K5Q2H",
+///   "is_partial": true,
+///   "is_running": true,
+///   "start_time_in_millis": 1712000000000,
+///   "expiration_time_in_millis": 1712432000000,
+///   "response": {"took": 1042, "hits": {...}, "aggregations": {...}}
+/// }
+#[derive(Debug, Serialize)]
+pub struct ElasticsearchAsyncSearchResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub is_partial: bool,
+    pub is_running: bool,
+    pub start_time_in_millis: i64,
+    pub expiration_time_in_millis: i64,
+    pub response: ElasticsearchResponse,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorCause>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_async_search_durations() {
+        assert_eq!(parse_keep_alive_secs(None).unwrap(), 5 * 24 * 3_600);
+        assert_eq!(parse_keep_alive_secs(Some("1h")).unwrap(), 3_600);
+        assert!(parse_keep_alive_secs(Some("forever")).is_err());
+
+        assert_eq!(
+            parse_wait_for_completion_timeout(None).unwrap(),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            parse_wait_for_completion_timeout(Some("500ms")).unwrap(),
+            Duration::from_millis(500)
+        );
+        assert!(parse_wait_for_completion_timeout(Some("soon")).is_err());
+    }
+}
@@ -20,20 +20,27 @@ use serde::Deserialize;
 pub enum BulkAction {
     Create(BulkActionMeta),
     Index(BulkActionMeta),
+    Update(BulkActionMeta),
+    Delete(BulkActionMeta),
 }
 
 impl BulkAction {
+    /// Returns whether the action line is followed by a source line. `delete` actions are the only
+    /// ones without a source.
+    pub fn has_source(&self) -> bool {
+        !matches!(self, BulkAction::Delete(_))
+    }
+
     pub fn into_index_id(self) -> Option<IndexId> {
-        match self {
-            BulkAction::Index(meta) => meta.index_id,
-            BulkAction::Create(meta) => meta.index_id,
-        }
+        self.into_meta().index_id
     }
 
     pub fn into_meta(self) -> BulkActionMeta {
         match self {
             BulkAction::Create(meta) => meta,
             BulkAction::Index(meta) => meta,
+            BulkAction::Update(meta) => meta,
+            BulkAction::Delete(meta) => meta,
         }
     }
 }
@@ -108,6 +115,38 @@ mod tests {
                     "_id": "2"
                 }
             }"#;
+            let bulk_action = serde_json::from_str::<BulkAction>(bulk_action_json).unwrap();
+            assert!(!bulk_action.has_source());
+            assert_eq!(
+                bulk_action,
+                BulkAction::Delete(BulkActionMeta {
+                    index_id: Some("test".to_string()),
+                    es_doc_id: Some("2".to_string()),
+                })
+            );
+        }
+        {
+            let bulk_action_json = r#"{
+                "update": {
+                    "_id": "2"
+                }
+            }"#;
+            let bulk_action = serde_json::from_str::<BulkAction>(bulk_action_json).unwrap();
+            assert!(bulk_action.has_source());
+            assert_eq!(
+                bulk_action,
+                BulkAction::Update(BulkActionMeta {
+                    index_id: None,
+                    es_doc_id: Some("2".to_string()),
+                })
+            );
+        }
+        {
+            let bulk_action_json = r#"{
+                "upsert": {
+                    "_id": "2"
+                }
+            }"#;
             serde_json::from_str::<BulkAction>(bulk_action_json).unwrap_err();
         }
     }
@@ -0,0 +1,169 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::str::FromStr;
+
+use quickwit_proto::metastore::DeleteTask;
+use quickwit_proto::types::IndexUid;
+use quickwit_query::ElasticQueryDsl;
+use serde::{Deserialize, Serialize};
+
+pub(crate) const DELETE_BY_QUERY_ACTION: &str = "indices:data/write/delete/byquery";
+
+#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DeleteByQueryBody {
+    #[serde(default)]
+    pub query: Option<ElasticQueryDsl>,
+}
+
+/// Returns JSON in the format:
+///
+/// {
+///   "task": "my-index:01HAV29D4XY3D462FS3D8K5Q2H:3"
+/// }
+///
+/// This mirrors the response returned by Elasticsearch when `wait_for_completion=false`: delete
+/// tasks are always applied asynchronously by the janitor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElasticsearchDeleteByQueryResponse {
+    pub task: String,
+}
+
+/// Identifies a delete task. Elasticsearch task ids have the form `{node_id}:{task_number}`, ours
+/// have the form `{index_uid}:{opstamp}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElasticTaskId {
+    pub index_uid: IndexUid,
+    pub opstamp: u64,
+}
+
+impl fmt::Display for ElasticTaskId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.index_uid, self.opstamp)
+    }
+}
+
+impl FromStr for ElasticTaskId {
+    type Err = String;
+
+    fn from_str(task_id: &str) -> Result<Self, Self::Err> {
+        let invalid_task_id = || format!("invalid task id `{task_id}`");
+        let (index_uid_str, opstamp_str) = task_id.rsplit_once(':').ok_or_else(invalid_task_id)?;
+        let index_uid: IndexUid = index_uid_str.parse().map_err(|_| invalid_task_id())?;
+        let opstamp: u64 = opstamp_str.parse().map_err(|_| invalid_task_id())?;
+        Ok(ElasticTaskId { index_uid, opstamp })
+    }
+}
+
+/// Returns JSON in the format:
+///
+/// {
+///   "completed": false,
+///   "task": {
+///     "node": "my-index:01HAV29D4XY3D462FS3D8K5Q2H",
+///     "id": 3,
+///     "type": "delete_task",
+///     "action": "indices:data/write/delete/byquery",
+///     "status": {"total": 12, "remaining": 5},
+///     "description": "delete-by-query [my-index]",
+///     "start_time_in_millis": 1696000000000,
+///     "cancellable": false
+///   }
+/// }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElasticsearchTaskResponse {
+    pub completed: bool,
+    pub task: ElasticsearchTaskInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElasticsearchTaskInfo {
+    pub node: String,
+    pub id: u64,
+    #[serde(rename = "type")]
+    pub task_type: String,
+    pub action: String,
+    pub status: ElasticsearchTaskStatus,
+    pub description: String,
+    pub start_time_in_millis: i64,
+    pub cancellable: bool,
+}
+
+/// Progress of a delete task, expressed in number of splits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElasticsearchTaskStatus {
+    /// Number of published splits in the index.
+    pub total: usize,
+    /// Number of published splits the delete task has not been applied to yet.
+    pub remaining: usize,
+}
+
+impl ElasticsearchTaskResponse {
+    pub fn from_delete_task(
+        index_id: &str,
+        delete_task: &DeleteTask,
+        num_splits: usize,
+        num_stale_splits: usize,
+    ) -> Self {
+        let index_uid = delete_task
+            .delete_query
+            .as_ref()
+            .and_then(|delete_query| delete_query.index_uid.clone())
+            .unwrap_or_default();
+        ElasticsearchTaskResponse {
+            completed: num_stale_splits == 0,
+            task: ElasticsearchTaskInfo {
+                node: index_uid.to_string(),
+                id: delete_task.opstamp,
+                task_type: "delete_task".to_string(),
+                action: DELETE_BY_QUERY_ACTION.to_string(),
+                status: ElasticsearchTaskStatus {
+                    total: num_splits,
+                    remaining: num_stale_splits,
+                },
+                description: format!("delete-by-query [{index_id}]"),
+                start_time_in_millis: delete_task.create_timestamp * 1_000,
+                cancellable: false,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elastic_task_id_roundtrip() {
+        let index_uid = IndexUid::for_test("my-index", 0);
+        let task_id = ElasticTaskId {
+            index_uid: index_uid.clone(),
+            opstamp: 42,
+        };
+        let task_id_str = task_id.to_string();
+        assert_eq!(task_id_str, format!("{index_uid}:42"));
+        let parsed_task_id: ElasticTaskId = task_id_str.parse().unwrap();
+        assert_eq!(parsed_task_id, task_id);
+    }
+
+    #[test]
+    fn test_elastic_task_id_invalid() {
+        assert!("".parse::<ElasticTaskId>().is_err());
+        assert!("my-index".parse::<ElasticTaskId>().is_err());
+        assert!("my-index:foo".parse::<ElasticTaskId>().is_err());
+        assert!("node:12".parse::<ElasticTaskId>().is_err());
+    }
+}
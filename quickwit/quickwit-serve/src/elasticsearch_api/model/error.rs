@@ -16,8 +16,10 @@ use elasticsearch_dsl::search::ErrorCause;
 use quickwit_common::{rate_limited_debug, rate_limited_error};
 use quickwit_index_management::IndexServiceError;
 use quickwit_ingest::IngestServiceError;
+use quickwit_janitor::error::JanitorError;
 use quickwit_proto::ServiceError;
 use quickwit_proto::ingest::IngestV2Error;
+use quickwit_proto::metastore::MetastoreError;
 use quickwit_search::SearchError;
 use serde::{Deserialize, Serialize};
 use warp::hyper::StatusCode;
@@ -135,10 +137,52 @@ impl From<IndexServiceError> for ElasticsearchError {
     }
 }
 
+impl From<MetastoreError> for ElasticsearchError {
+    fn from(metastore_error: MetastoreError) -> Self {
+        let status = metastore_error.error_code().http_status_code();
+
+        let reason = ErrorCause {
+            reason: Some(metastore_error.to_string()),
+            caused_by: None,
+            root_cause: Vec::new(),
+            stack_trace: None,
+            suppressed: Vec::new(),
+            ty: None,
+            additional_details: Default::default(),
+        };
+        ElasticsearchError {
+            status,
+            error: reason,
+        }
+    }
+}
+
+impl From<JanitorError> for ElasticsearchError {
+    fn from(janitor_error: JanitorError) -> Self {
+        let status = janitor_error.error_code().http_status_code();
+
+        let reason = ErrorCause {
+            reason: Some(janitor_error.to_string()),
+            caused_by: None,
+            root_cause: Vec::new(),
+            stack_trace: None,
+            suppressed: Vec::new(),
+            ty: None,
+            additional_details: Default::default(),
+        };
+        ElasticsearchError {
+            status,
+            error: reason,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ElasticException {
     #[serde(rename = "action_request_validation_exception")]
     ActionRequestValidation,
+    #[serde(rename = "aliases_not_found_exception")]
+    AliasesNotFound,
     #[serde(rename = "document_parsing_exception")]
     DocumentParsing,
     // This is an exception proper to Quickwit.
@@ -151,6 +195,8 @@ pub enum ElasticException {
     // This is an exception proper to Quickwit.
     #[serde(rename = "rate_limited_exception")]
     RateLimited,
+    #[serde(rename = "resource_not_found_exception")]
+    ResourceNotFound,
     // This is an exception proper to Quickwit.
     #[serde(rename = "source_not_found_exception")]
     SourceNotFound,
@@ -162,11 +208,13 @@ impl ElasticException {
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::ActionRequestValidation => "action_request_validation_exception",
+            Self::AliasesNotFound => "aliases_not_found_exception",
             Self::DocumentParsing => "document_parsing_exception",
             Self::Internal => "internal_exception",
             Self::RateLimited => "rate_limited_exception",
             Self::IllegalArgument => "illegal_argument_exception",
             Self::IndexNotFound => "index_not_found_exception",
+            Self::ResourceNotFound => "resource_not_found_exception",
             Self::SourceNotFound => "source_not_found_exception",
             Self::Timeout => "timeout_exception",
         }
@@ -0,0 +1,133 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use elasticsearch_dsl::ErrorCause;
+use serde::{Deserialize, Serialize};
+
+use super::ElasticsearchError;
+use crate::simple_list::from_simple_list;
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GetDocumentQueryParams {
+    #[serde(deserialize_with = "from_simple_list")]
+    #[serde(default)]
+    pub _source_excludes: Option<Vec<String>>,
+    #[serde(deserialize_with = "from_simple_list")]
+    #[serde(default)]
+    pub _source_includes: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MultiGetDocumentBody {
+    #[serde(rename = "_index")]
+    #[serde(default)]
+    pub index: Option<String>,
+    #[serde(rename = "_id")]
+    pub id: String,
+}
+
+/// Body of an `_mget` request. Documents are either listed in `docs`, or listed by id in `ids`
+/// when the index is set in the path.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MultiGetBody {
+    #[serde(default)]
+    pub docs: Vec<MultiGetDocumentBody>,
+    #[serde(default)]
+    pub ids: Vec<String>,
+}
+
+/// Returns JSON in the format:
+///
+/// {
+///   "_index": "my-index",
+///   "_id": "0",
+///   "found": true,
+///   "_source": {
+///     "message": "hello"
+///   }
+/// }
+#[derive(Debug, Clone, Serialize)]
+pub struct ElasticsearchGetDocumentResponse {
+    #[serde(rename = "_index")]
+    pub index: String,
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub found: bool,
+    #[serde(rename = "_source")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorCause>,
+}
+
+impl ElasticsearchGetDocumentResponse {
+    pub fn not_found(index: String, id: String) -> Self {
+        ElasticsearchGetDocumentResponse {
+            index,
+            id,
+            found: false,
+            source: None,
+            error: None,
+        }
+    }
+
+    pub fn from_error(index: String, id: String, error: ElasticsearchError) -> Self {
+        ElasticsearchGetDocumentResponse {
+            index,
+            id,
+            found: false,
+            source: None,
+            error: Some(error.error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ElasticsearchMultiGetResponse {
+    pub docs: Vec<ElasticsearchGetDocumentResponse>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_get_body_deserialization() {
+        let multi_get_body: MultiGetBody =
+            serde_json::from_str(r#"{"docs": [{"_index": "my-index", "_id": "1"}, {"_id": "2"}]}"#)
+                .unwrap();
+        assert_eq!(
+            multi_get_body.docs,
+            [
+                MultiGetDocumentBody {
+                    index: Some("my-index".to_string()),
+                    id: "1".to_string(),
+                },
+                MultiGetDocumentBody {
+                    index: None,
+                    id: "2".to_string(),
+                },
+            ]
+        );
+        assert!(multi_get_body.ids.is_empty());
+
+        let multi_get_body: MultiGetBody = serde_json::from_str(r#"{"ids": ["1", "2"]}"#).unwrap();
+        assert_eq!(multi_get_body.ids, ["1", "2"]);
+
+        serde_json::from_str::<MultiGetBody>(r#"{"docs": [{"_id": "1", "routing": "a"}]}"#)
+            .unwrap_err();
+    }
+}
@@ -0,0 +1,313 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, HashMap};
+
+use itertools::Itertools;
+use quickwit_doc_mapper::{
+    DocMapping, FieldMappingEntry, FieldMappingType, Mode, VectorSimilarity,
+};
+use quickwit_metastore::IndexMetadata;
+use serde::{Deserialize, Serialize};
+
+const RAW_TOKENIZER_NAME: &str = "raw";
+
+/// Returns JSON in the format:
+///
+/// {
+///   "my-index": {
+///     "mappings": {
+///       "dynamic": "true",
+///       "properties": {
+///         "timestamp": {"type": "date", "format": "strict_date_optional_time"},
+///         "severity": {"type": "keyword"},
+///         "body": {"type": "text"}
+///       }
+///     }
+///   }
+/// }
+pub type ElasticsearchMappingsResponse = HashMap<String, ElasticsearchIndexMappings>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ElasticsearchIndexMappings {
+    pub mappings: ElasticsearchMappings,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ElasticsearchMappings {
+    pub dynamic: String,
+    pub properties: BTreeMap<String, ElasticsearchFieldMapping>,
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ElasticsearchFieldMapping {
+    /// Elasticsearch omits the type of object fields that have properties.
+    #[serde(rename = "type")]
+    pub field_type: Option<String>,
+    /// Date formats, separated by `||`.
+    pub format: Option<String>,
+    /// Only set when the field is not indexed.
+    pub index: Option<bool>,
+    /// Only set when the field is not a fast field.
+    pub doc_values: Option<bool>,
+    /// Number of dimensions of dense vector fields.
+    pub dims: Option<usize>,
+    /// Similarity metric of dense vector fields.
+    pub similarity: Option<String>,
+    pub properties: Option<BTreeMap<String, ElasticsearchFieldMapping>>,
+}
+
+impl ElasticsearchFieldMapping {
+    fn with_type(field_type: &str) -> Self {
+        ElasticsearchFieldMapping {
+            field_type: Some(field_type.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn with_index_and_doc_values(mut self, indexed: bool, fast: bool) -> Self {
+        if !indexed {
+            self.index = Some(false);
+        }
+        if !fast {
+            self.doc_values = Some(false);
+        }
+        self
+    }
+}
+
+pub fn convert_to_es_mappings_response(
+    indexes_metadata: Vec<IndexMetadata>,
+) -> ElasticsearchMappingsResponse {
+    indexes_metadata
+        .into_iter()
+        .map(|index_metadata| {
+            let index_id = index_metadata.index_id().to_string();
+            let mappings = convert_to_es_mappings(&index_metadata.index_config.doc_mapping);
+            (index_id, ElasticsearchIndexMappings { mappings })
+        })
+        .collect()
+}
+
+fn convert_to_es_mappings(doc_mapping: &DocMapping) -> ElasticsearchMappings {
+    let dynamic = match doc_mapping.mode {
+        Mode::Lenient => "false",
+        Mode::Strict => "strict",
+        Mode::Dynamic(_) => "true",
+    };
+    ElasticsearchMappings {
+        dynamic: dynamic.to_string(),
+        properties: convert_field_mappings(&doc_mapping.field_mappings),
+    }
+}
+
+fn convert_field_mappings(
+    field_mappings: &[FieldMappingEntry],
+) -> BTreeMap<String, ElasticsearchFieldMapping> {
+    field_mappings
+        .iter()
+        .map(|field_mapping| {
+            (
+                field_mapping.name.clone(),
+                convert_field_mapping_type(&field_mapping.mapping_type),
+            )
+        })
+        .collect()
+}
+
+fn text_field_type(tokenizer_name_opt: Option<&str>) -> &'static str {
+    if tokenizer_name_opt == Some(RAW_TOKENIZER_NAME) {
+        "keyword"
+    } else {
+        "text"
+    }
+}
+
+fn convert_field_mapping_type(mapping_type: &FieldMappingType) -> ElasticsearchFieldMapping {
+    match mapping_type {
+        FieldMappingType::Text(text_options, _) => {
+            let tokenizer_name_opt = text_options
+                .indexing_options
+                .as_ref()
+                .map(|indexing_options| indexing_options.tokenizer.name());
+            ElasticsearchFieldMapping::with_type(text_field_type(tokenizer_name_opt))
+                .with_index_and_doc_values(
+                    text_options.indexing_options.is_some(),
+                    text_options.fast.is_enabled(),
+                )
+        }
+        FieldMappingType::I64(numeric_options, _) => ElasticsearchFieldMapping::with_type("long")
+            .with_index_and_doc_values(numeric_options.indexed, numeric_options.fast),
+        FieldMappingType::U64(numeric_options, _) => {
+            ElasticsearchFieldMapping::with_type("unsigned_long")
+                .with_index_and_doc_values(numeric_options.indexed, numeric_options.fast)
+        }
+        FieldMappingType::F64(numeric_options, _) => ElasticsearchFieldMapping::with_type("double")
+            .with_index_and_doc_values(numeric_options.indexed, numeric_options.fast),
+        FieldMappingType::Bool(bool_options, _) => ElasticsearchFieldMapping::with_type("boolean")
+            .with_index_and_doc_values(bool_options.indexed, bool_options.fast),
+        FieldMappingType::IpAddr(ip_options, _) => ElasticsearchFieldMapping::with_type("ip")
+            .with_index_and_doc_values(ip_options.indexed, ip_options.fast),
+        FieldMappingType::DateTime(date_time_options, _) => {
+            // Only the formats with an Elasticsearch equivalent are reported: strptime and RFC 2822
+            // formats cannot be expressed with the Java date syntax.
+            let es_formats: Vec<&str> = date_time_options
+                .input_formats
+                .iter()
+                .flat_map(|input_format| match input_format.as_str() {
+                    "rfc3339" | "iso8601" => &["strict_date_optional_time"][..],
+                    "unix_timestamp" => &["epoch_second", "epoch_millis"][..],
+                    _ => &[][..],
+                })
+                .copied()
+                .unique()
+                .collect();
+            let mut field_mapping = ElasticsearchFieldMapping::with_type("date")
+                .with_index_and_doc_values(date_time_options.indexed, date_time_options.fast);
+            if !es_formats.is_empty() {
+                field_mapping.format = Some(es_formats.join("||"));
+            }
+            field_mapping
+        }
+        FieldMappingType::Bytes(bytes_options, _) => ElasticsearchFieldMapping::with_type("binary")
+            .with_index_and_doc_values(bytes_options.indexed, bytes_options.fast),
+        FieldMappingType::Json(json_options, _) => {
+            // JSON fields tokenized with the raw tokenizer behave like Elasticsearch `flattened`
+            // fields: all their leaves are indexed as keywords.
+            let tokenizer_name_opt = json_options
+                .indexing_options
+                .as_ref()
+                .map(|indexing_options| indexing_options.tokenizer.name());
+            let field_type = if tokenizer_name_opt == Some(RAW_TOKENIZER_NAME) {
+                "flattened"
+            } else {
+                "object"
+            };
+            ElasticsearchFieldMapping::with_type(field_type).with_index_and_doc_values(
+                json_options.indexing_options.is_some(),
+                json_options.fast.is_enabled(),
+            )
+        }
+        FieldMappingType::Object(object_options) => ElasticsearchFieldMapping {
+            properties: Some(convert_field_mappings(&object_options.field_mappings)),
+            ..Default::default()
+        },
+        FieldMappingType::Concatenate(concatenate_options) => {
+            // Concatenate fields have no Elasticsearch equivalent. They are exposed as the
+            // searchable text field they are.
+            let tokenizer_name = concatenate_options.indexing_options.tokenizer.name();
+            ElasticsearchFieldMapping::with_type(text_field_type(Some(tokenizer_name)))
+                .with_index_and_doc_values(true, false)
+        }
+        FieldMappingType::DenseVector(dense_vector_options) => {
+            let similarity = match dense_vector_options.similarity {
+                VectorSimilarity::Cosine => "cosine",
+                VectorSimilarity::DotProduct => "dot_product",
+                VectorSimilarity::L2Norm => "l2_norm",
+            };
+            ElasticsearchFieldMapping {
+                dims: Some(dense_vector_options.dims),
+                similarity: Some(similarity.to_string()),
+                ..ElasticsearchFieldMapping::with_type("dense_vector")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_convert_to_es_mappings() {
+        let doc_mapping: DocMapping = serde_json::from_value(json!({
+            "mode": "strict",
+            "field_mappings": [
+                {"name": "body", "type": "text"},
+                {"name": "severity", "type": "text", "tokenizer": "raw", "fast": true},
+                {"name": "count", "type": "i64", "fast": true},
+                {"name": "size", "type": "u64", "indexed": false},
+                {"name": "ratio", "type": "f64", "fast": true},
+                {"name": "is_error", "type": "bool", "fast": true},
+                {"name": "host_ip", "type": "ip", "fast": true},
+                {
+                    "name": "timestamp",
+                    "type": "datetime",
+                    "input_formats": ["rfc3339", "unix_timestamp"],
+                    "fast": true
+                },
+                {
+                    "name": "day",
+                    "type": "datetime",
+                    "input_formats": ["%Y-%m-%d", "iso8601"],
+                    "fast": true
+                },
+                {
+                    "name": "legacy_date",
+                    "type": "datetime",
+                    "input_formats": ["rfc2822", "%d/%m/%Y"]
+                },
+                {"name": "attributes", "type": "json", "tokenizer": "raw", "fast": true},
+                {"name": "payload", "type": "json", "tokenizer": "default"},
+                {"name": "embedding", "type": "dense_vector", "dims": 3},
+                {
+                    "name": "user",
+                    "type": "object",
+                    "field_mappings": [{"name": "id", "type": "u64", "fast": true}]
+                }
+            ]
+        }))
+        .unwrap();
+        let mappings = convert_to_es_mappings(&doc_mapping);
+        let mappings_json = serde_json::to_value(&mappings).unwrap();
+        let expected_mappings_json = json!({
+            "dynamic": "strict",
+            "properties": {
+                "body": {"type": "text", "doc_values": false},
+                "severity": {"type": "keyword"},
+                "count": {"type": "long"},
+                "size": {"type": "unsigned_long", "index": false, "doc_values": false},
+                "ratio": {"type": "double"},
+                "is_error": {"type": "boolean"},
+                "host_ip": {"type": "ip"},
+                "timestamp": {
+                    "type": "date",
+                    "format": "strict_date_optional_time||epoch_second||epoch_millis"
+                },
+                "day": {"type": "date", "format": "strict_date_optional_time"},
+                "legacy_date": {"type": "date", "doc_values": false},
+                "attributes": {"type": "flattened"},
+                "payload": {"type": "object", "doc_values": false},
+                "embedding": {"type": "dense_vector", "dims": 3, "similarity": "cosine"},
+                "user": {
+                    "properties": {
+                        "id": {"type": "unsigned_long"}
+                    }
+                }
+            }
+        });
+        assert_eq!(mappings_json, expected_mappings_json);
+    }
+
+    #[test]
+    fn test_convert_to_es_mappings_dynamic_mode() {
+        let doc_mapping: DocMapping = serde_json::from_value(json!({})).unwrap();
+        let mappings = convert_to_es_mappings(&doc_mapping);
+        assert_eq!(mappings.dynamic, "true");
+        assert!(mappings.properties.is_empty());
+    }
+}
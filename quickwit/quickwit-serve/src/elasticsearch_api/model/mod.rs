@@ -12,37 +12,69 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod alias;
+mod async_search;
 mod bulk_body;
 mod bulk_query_params;
 mod cat_indices;
+mod delete_by_query;
 mod error;
 mod field_capability;
+mod get_document;
+mod mapping;
 mod multi_search;
+mod point_in_time;
 mod scroll;
 mod search_body;
 mod search_query_params;
 mod search_response;
+mod search_task;
 mod stats;
 
+pub use alias::{
+    AddAliasAction, AliasAction, ElasticsearchAliasesResponse, PutAliasBody, RemoveAliasAction,
+    UpdateAliasesBody, convert_to_es_aliases_response,
+};
+pub use async_search::{
+    ElasticsearchAsyncSearchResponse, GetAsyncSearchQueryParams, parse_keep_alive_secs,
+    parse_wait_for_completion_timeout,
+};
 pub use bulk_body::BulkAction;
 pub use bulk_query_params::ElasticBulkOptions;
 pub use cat_indices::{
     CatIndexQueryParams, ElasticsearchCatIndexResponse, ElasticsearchResolveIndexEntryResponse,
     ElasticsearchResolveIndexResponse,
 };
+pub use delete_by_query::{
+    DeleteByQueryBody, ElasticTaskId, ElasticsearchDeleteByQueryResponse, ElasticsearchTaskResponse,
+};
 pub use error::{ElasticException, ElasticsearchError};
 pub use field_capability::{
     FieldCapabilityQueryParams, FieldCapabilityRequestBody, FieldCapabilityResponse,
     build_list_field_request_for_es_api, convert_to_es_field_capabilities_response,
 };
+pub use get_document::{
+    ElasticsearchGetDocumentResponse, ElasticsearchMultiGetResponse, GetDocumentQueryParams,
+    MultiGetBody,
+};
+pub use mapping::{ElasticsearchMappingsResponse, convert_to_es_mappings_response};
 pub use multi_search::{
     MultiSearchHeader, MultiSearchQueryParams, MultiSearchResponse, MultiSearchSingleResponse,
 };
+pub use point_in_time::{
+    ClosePointInTimeBody, ElasticsearchClosePointInTimeResponse,
+    ElasticsearchOpenPointInTimeResponse, OpenPointInTimeQueryParams, PointInTimeBody,
+};
 use quickwit_proto::search::{SortDatetimeFormat, SortOrder};
 pub use scroll::ScrollQueryParams;
-pub use search_body::SearchBody;
+pub use search_body::{
+    CollapseBody, CollapseInnerHitsBody, HighlightBody, KnnBody, RankBody, SearchBody, SourceBody,
+};
 pub use search_query_params::{DeleteQueryParams, SearchQueryParams, SearchQueryParamsCount};
 pub use search_response::ElasticsearchResponse;
+pub use search_task::{
+    ElasticsearchListTasksResponse, ElasticsearchSearchTaskResponse, parse_search_task_id,
+};
 use serde::{Deserialize, Serialize};
 pub use stats::{ElasticsearchStatsResponse, StatsResponseEntry};
 
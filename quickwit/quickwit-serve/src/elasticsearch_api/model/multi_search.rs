@@ -76,6 +76,11 @@ pub struct MultiSearchQueryParams {
     pub _source_includes: Option<Vec<String>>,
     #[serde(default)]
     pub typed_keys: Option<bool>,
+    /// This is not part of the official Elasticsearch API.
+    /// Tenant the search is accounted to by the searcher admission control, read from the
+    /// `x-quickwit-tenant-id` header.
+    #[serde(skip)]
+    pub tenant_id: Option<String>,
 }
 
 #[serde_as]
@@ -0,0 +1,103 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use quickwit_search::SearchError;
+use serde::{Deserialize, Serialize};
+
+fn parse_keep_alive_secs(keep_alive_str: &str) -> Result<u32, SearchError> {
+    let keep_alive = humantime::parse_duration(keep_alive_str).map_err(|_err| {
+        SearchError::InvalidArgument(format!("invalid keep alive duration: `{keep_alive_str}`"))
+    })?;
+    Ok(keep_alive.as_secs() as u32)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OpenPointInTimeQueryParams {
+    pub keep_alive: String,
+}
+
+impl OpenPointInTimeQueryParams {
+    pub fn keep_alive_secs(&self) -> Result<u32, SearchError> {
+        parse_keep_alive_secs(&self.keep_alive)
+    }
+}
+
+/// The `pit` parameter of a search request.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PointInTimeBody {
+    pub id: String,
+    #[serde(default)]
+    pub keep_alive: Option<String>,
+}
+
+impl PointInTimeBody {
+    /// Returns the new keep alive of the point in time, if it should be extended.
+    pub fn keep_alive_secs(&self) -> Result<Option<u32>, SearchError> {
+        self.keep_alive
+            .as_deref()
+            .map(parse_keep_alive_secs)
+            .transpose()
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClosePointInTimeBody {
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+/// Returns JSON in the format:
+///
+/// {
+///   "id": "eyJwaXRfdWxpZCI6IjAxSEFWMjlENFhZM0Q0NjJGUzNEOEs1UTJIIiwiaW5kZXhfdWlkcyI6W119"
+/// }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElasticsearchOpenPointInTimeResponse {
+    pub id: String,
+}
+
+/// Returns JSON in the format:
+///
+/// {
+///   "succeeded": true,
+///   "num_freed": 1
+/// }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElasticsearchClosePointInTimeResponse {
+    pub succeeded: bool,
+    pub num_freed: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_in_time_body_keep_alive_secs() {
+        let pit_body: PointInTimeBody =
+            serde_json::from_str(r#"{"id": "pit-id", "keep_alive": "2m"}"#).unwrap();
+        assert_eq!(pit_body.id, "pit-id");
+        assert_eq!(pit_body.keep_alive_secs().unwrap(), Some(120));
+
+        let pit_body: PointInTimeBody = serde_json::from_str(r#"{"id": "pit-id"}"#).unwrap();
+        assert_eq!(pit_body.keep_alive_secs().unwrap(), None);
+
+        let pit_body: PointInTimeBody =
+            serde_json::from_str(r#"{"id": "pit-id", "keep_alive": "forever"}"#).unwrap();
+        assert!(pit_body.keep_alive_secs().is_err());
+    }
+}
@@ -12,17 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 
-use quickwit_proto::search::SortOrder;
+use quickwit_proto::search::{SortOrder, SourceFilter};
 use quickwit_query::{ElasticQueryDsl, OneFieldMap};
 use serde::de::{MapAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize};
+use serde_with::formats::PreferMany;
+use serde_with::{OneOrMany, serde_as};
 
 use super::ElasticDateFormat;
 use crate::elasticsearch_api::TrackTotalHits;
-use crate::elasticsearch_api::model::{SortField, default_elasticsearch_sort_order};
+use crate::elasticsearch_api::model::{
+    PointInTimeBody, SortField, default_elasticsearch_sort_order,
+};
 
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(untagged)]
@@ -81,20 +85,162 @@ pub struct SearchBody {
     pub stored_fields: Option<BTreeSet<String>>,
     #[serde(default)]
     pub search_after: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub highlight: Option<HighlightBody>,
+    #[serde(default)]
+    pub _source: Option<SourceBody>,
+    #[serde(default)]
+    pub pit: Option<PointInTimeBody>,
+    #[serde(default)]
+    pub timeout: Option<String>,
+    #[serde(default)]
+    pub collapse: Option<CollapseBody>,
+    #[serde(default)]
+    pub knn: Option<KnnBody>,
+    #[serde(default)]
+    pub rank: Option<RankBody>,
+    #[serde(default)]
+    pub profile: bool,
 
     // Ignored values, only here for compatibility with OpenSearch Dashboards.
     #[serde(default)]
-    pub _source: serde::de::IgnoredAny,
-    #[serde(default)]
     pub docvalue_fields: serde::de::IgnoredAny,
     #[serde(default)]
     pub script_fields: serde::de::IgnoredAny,
     #[serde(default)]
-    pub highlight: serde::de::IgnoredAny,
-    #[serde(default)]
     pub version: serde::de::IgnoredAny,
 }
 
+/// The `_source` parameter of a search request. It can be a boolean, one or several field
+/// patterns to include, or an object with patterns to include and exclude.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum SourceBody {
+    Enabled(bool),
+    Includes(#[serde_as(deserialize_as = "OneOrMany<_, PreferMany>")] Vec<String>),
+    IncludesExcludes {
+        #[serde_as(deserialize_as = "OneOrMany<_, PreferMany>")]
+        #[serde(default)]
+        includes: Vec<String>,
+        #[serde_as(deserialize_as = "OneOrMany<_, PreferMany>")]
+        #[serde(default)]
+        excludes: Vec<String>,
+    },
+}
+
+impl SourceBody {
+    /// Returns the source filter to apply to the hits, or `None` if the whole documents should be
+    /// returned.
+    pub fn into_source_filter(self) -> Option<SourceFilter> {
+        let source_filter = match self {
+            SourceBody::Enabled(true) => return None,
+            SourceBody::Enabled(false) => SourceFilter {
+                includes: Vec::new(),
+                excludes: Vec::new(),
+                disabled: true,
+            },
+            SourceBody::Includes(includes) => SourceFilter {
+                includes,
+                excludes: Vec::new(),
+                disabled: false,
+            },
+            SourceBody::IncludesExcludes { includes, excludes } => SourceFilter {
+                includes,
+                excludes,
+                disabled: false,
+            },
+        };
+        Some(source_filter)
+    }
+}
+
+/// Highlighting options of a search request. Per-field options and highlighter types are accepted
+/// but ignored: all the fields are highlighted with the top-level options.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct HighlightBody {
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_highlight_fields")]
+    pub fields: Vec<String>,
+    #[serde(default)]
+    pub pre_tags: Vec<String>,
+    #[serde(default)]
+    pub post_tags: Vec<String>,
+    #[serde(default)]
+    pub fragment_size: Option<u32>,
+    #[serde(default)]
+    pub number_of_fragments: Option<u32>,
+}
+
+/// Field collapsing options of a search request: only the best hit of each value of `field` is
+/// returned, optionally with the best hits of its group as inner hits.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CollapseBody {
+    pub field: String,
+    #[serde(default)]
+    pub inner_hits: Option<CollapseInnerHitsBody>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct CollapseInnerHitsBody {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub size: Option<u32>,
+}
+
+/// k-nearest neighbors search over a dense vector field.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KnnBody {
+    pub field: String,
+    pub query_vector: Vec<f32>,
+    pub k: u64,
+    #[serde(default)]
+    pub num_candidates: Option<u64>,
+    #[serde(default)]
+    pub filter: Option<ElasticQueryDsl>,
+}
+
+/// Ranking of the hits of a hybrid search. Only reciprocal rank fusion is supported.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RankBody {
+    pub rrf: RrfBody,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RrfBody {
+    #[serde(default)]
+    #[serde(alias = "window_size")]
+    pub rank_window_size: Option<u64>,
+    #[serde(default)]
+    pub rank_constant: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HighlightFieldsForDeser {
+    Object(BTreeMap<String, serde::de::IgnoredAny>),
+    Array(Vec<BTreeMap<String, serde::de::IgnoredAny>>),
+}
+
+/// ES accepts the highlighted fields either as an object or, to preserve their order, as an array
+/// of single-field objects.
+fn deserialize_highlight_fields<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where D: Deserializer<'de> {
+    let field_names = match HighlightFieldsForDeser::deserialize(deserializer)? {
+        HighlightFieldsForDeser::Object(fields) => fields.into_keys().collect(),
+        HighlightFieldsForDeser::Array(fields) => fields
+            .into_iter()
+            .flat_map(|field| field.into_keys())
+            .collect(),
+    };
+    Ok(field_names)
+}
+
 struct FieldSortVecVisitor;
 
 #[derive(Deserialize)]
@@ -256,6 +402,122 @@ mod tests {
         assert_eq!(field_sorts[3].order, SortOrder::Asc);
     }
 
+    #[test]
+    fn test_source_body() {
+        let source_filter_from_json = |json: &str| {
+            let search_body: SearchBody = serde_json::from_str(json).unwrap();
+            search_body._source.and_then(SourceBody::into_source_filter)
+        };
+        assert_eq!(source_filter_from_json(r#"{}"#), None);
+        assert_eq!(source_filter_from_json(r#"{"_source": true}"#), None);
+        assert_eq!(
+            source_filter_from_json(r#"{"_source": false}"#),
+            Some(SourceFilter {
+                includes: Vec::new(),
+                excludes: Vec::new(),
+                disabled: true,
+            })
+        );
+        assert_eq!(
+            source_filter_from_json(r#"{"_source": "app.*"}"#),
+            Some(SourceFilter {
+                includes: vec!["app.*".to_string()],
+                excludes: Vec::new(),
+                disabled: false,
+            })
+        );
+        assert_eq!(
+            source_filter_from_json(r#"{"_source": ["timestamp", "app.*"]}"#),
+            Some(SourceFilter {
+                includes: vec!["timestamp".to_string(), "app.*".to_string()],
+                excludes: Vec::new(),
+                disabled: false,
+            })
+        );
+        assert_eq!(
+            source_filter_from_json(
+                r#"{"_source": {"includes": ["app.*"], "excludes": "app.id"}}"#
+            ),
+            Some(SourceFilter {
+                includes: vec!["app.*".to_string()],
+                excludes: vec!["app.id".to_string()],
+                disabled: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_highlight_fields_obj() {
+        let json = r#"
+        {
+            "highlight": {
+                "pre_tags": ["<mark>"],
+                "post_tags": ["</mark>"],
+                "fragment_size": 50,
+                "number_of_fragments": 2,
+                "require_field_match": false,
+                "fields": {
+                    "title": {},
+                    "body": { "type": "plain" }
+                }
+            }
+        }
+        "#;
+        let search_body: SearchBody = serde_json::from_str(json).unwrap();
+        let highlight = search_body.highlight.unwrap();
+        assert_eq!(highlight.fields, ["body", "title"]);
+        assert_eq!(highlight.pre_tags, ["<mark>"]);
+        assert_eq!(highlight.post_tags, ["</mark>"]);
+        assert_eq!(highlight.fragment_size, Some(50));
+        assert_eq!(highlight.number_of_fragments, Some(2));
+    }
+
+    #[test]
+    fn test_highlight_fields_array() {
+        let json = r#"
+        {
+            "highlight": {
+                "fields": [
+                    { "title": {} },
+                    { "body": {} }
+                ]
+            }
+        }
+        "#;
+        let search_body: SearchBody = serde_json::from_str(json).unwrap();
+        let highlight = search_body.highlight.unwrap();
+        assert_eq!(highlight.fields, ["title", "body"]);
+        assert!(highlight.pre_tags.is_empty());
+        assert_eq!(highlight.fragment_size, None);
+    }
+
+    #[test]
+    fn test_knn_body() {
+        let json = r#"
+        {
+            "knn": {
+                "field": "embedding",
+                "query_vector": [0.5, -1.0],
+                "k": 5,
+                "num_candidates": 50,
+                "filter": { "term": { "status": "open" } }
+            },
+            "rank": { "rrf": { "window_size": 20 } }
+        }
+        "#;
+        let search_body: SearchBody = serde_json::from_str(json).unwrap();
+        let knn = search_body.knn.unwrap();
+        assert_eq!(knn.field, "embedding");
+        assert_eq!(knn.query_vector, [0.5, -1.0]);
+        assert_eq!(knn.k, 5);
+        assert_eq!(knn.num_candidates, Some(50));
+        assert!(knn.filter.is_some());
+
+        let rrf = search_body.rank.unwrap().rrf;
+        assert_eq!(rrf.rank_window_size, Some(20));
+        assert_eq!(rrf.rank_constant, None);
+    }
+
     #[test]
     fn test_unknown_field_behaviour() {
         let json = r#"
@@ -90,6 +90,12 @@ pub struct SearchQueryParams {
     pub ignore_throttled: Option<bool>,
     #[serde(default)]
     pub ignore_unavailable: Option<bool>,
+    /// Only used by the `_async_search` API.
+    #[serde(default)]
+    pub keep_alive: Option<String>,
+    /// Only used by the `_async_search` API.
+    #[serde(default)]
+    pub keep_on_completion: Option<bool>,
     #[serde(default)]
     pub lenient: Option<bool>,
     #[serde(default)]
@@ -148,6 +154,14 @@ pub struct SearchQueryParams {
     pub typed_keys: Option<bool>,
     #[serde(default)]
     pub version: Option<bool>,
+    /// Only used by the `_async_search` API.
+    #[serde(default)]
+    pub wait_for_completion_timeout: Option<String>,
+    /// This is not part of the official Elasticsearch API.
+    /// Tenant the search is accounted to by the searcher admission control, read from the
+    /// `x-quickwit-tenant-id` header.
+    #[serde(skip)]
+    pub tenant_id: Option<String>,
 }
 
 #[serde_with::skip_serializing_none]
@@ -186,6 +200,11 @@ pub struct SearchQueryParamsCount {
     #[serde(deserialize_with = "from_simple_list")]
     #[serde(default)]
     pub routing: Option<Vec<String>>,
+    /// This is not part of the official Elasticsearch API.
+    /// Tenant the search is accounted to by the searcher admission control, read from the
+    /// `x-quickwit-tenant-id` header.
+    #[serde(skip)]
+    pub tenant_id: Option<String>,
 }
 impl From<SearchQueryParamsCount> for SearchQueryParams {
     fn from(value: SearchQueryParamsCount) -> Self {
@@ -203,6 +222,7 @@ impl From<SearchQueryParamsCount> for SearchQueryParams {
             request_cache: value.request_cache,
             routing: value.routing,
             size: Some(0),
+            tenant_id: value.tenant_id,
             ..Default::default()
         }
     }
@@ -287,6 +307,20 @@ impl SearchQueryParams {
         Ok(Some(duration))
     }
 
+    /// Returns the search timeout supplied by the user, in the query string or in the search body.
+    pub fn parse_timeout(
+        &self,
+        search_body_timeout_opt: Option<&str>,
+    ) -> Result<Option<Duration>, SearchError> {
+        let Some(timeout_str) = self.timeout.as_deref().or(search_body_timeout_opt) else {
+            return Ok(None);
+        };
+        let timeout: Duration = humantime::parse_duration(timeout_str).map_err(|_err| {
+            SearchError::InvalidArgument(format!("invalid timeout: `{timeout_str}`"))
+        })?;
+        Ok(Some(timeout))
+    }
+
     pub fn allow_partial_search_results(&self) -> bool {
         // By default, elastic search allows partial results.
         self.allow_partial_search_results.unwrap_or(true)
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use elasticsearch_dsl::{ClusterStatistics, HitsMetadata, ShardStatistics, Suggest};
+use quickwit_proto::search::SearchProfile;
 use quickwit_search::AggregationResults;
 use serde::Serialize;
 
@@ -75,4 +76,8 @@ pub struct ElasticsearchResponse {
     #[serde(skip_serializing_if = "Map::is_empty", default)]
     /// Suggest response
     pub suggest: Map<String, Vec<Suggest>>,
+
+    /// Execution breakdown of the search, in the Quickwit search profile format
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<SearchProfile>,
 }
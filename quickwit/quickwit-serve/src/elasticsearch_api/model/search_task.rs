@@ -0,0 +1,268 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, HashMap};
+
+use itertools::Itertools;
+use quickwit_proto::search::SearchTask;
+use serde::Serialize;
+
+const SEARCH_ACTION: &str = "indices:data/read/search";
+
+/// Returns the search task ID of a task ID of the tasks API. Elasticsearch task IDs are prefixed
+/// with the ID of the node running the task, which is optional here since search tasks are
+/// cancelled on all the nodes.
+pub fn parse_search_task_id(task_id: &str) -> &str {
+    match task_id.rsplit_once(':') {
+        Some((_node_id, search_task_id)) => search_task_id,
+        None => task_id,
+    }
+}
+
+/// Returns JSON in the format below, grouping the search tasks by node:
+///
+/// {
+///   "nodes": {
+///     "searcher-1": {
+///       "name": "searcher-1",
+///       "transport_address": "10.0.0.1:7281",
+///       "tasks": {
+///         "searcher-1:01HAV29D4XY3D462FS3D8K5Q2H": {
+///           "node": "searcher-1",
+///           "id": "01HAV29D4XY3D462FS3D8K5Q2H",
+///           "type": "transport",
+///           "action": "indices:data/read/search",
+///           "description": "indices[my-index], query[...]",
+///           "start_time_in_millis": 1712000000000,
+///           "running_time_in_nanos": 1042000000,
+///           "cancellable": true,
+///           "cancelled": false
+///         }
+///       }
+///     }
+///   }
+/// }
+#[derive(Debug, Default, Serialize)]
+pub struct ElasticsearchListTasksResponse {
+    pub nodes: BTreeMap<String, ElasticsearchNodeTasks>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ElasticsearchNodeTasks {
+    pub name: String,
+    pub transport_address: String,
+    pub tasks: BTreeMap<String, ElasticsearchTask>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ElasticsearchTask {
+    pub node: String,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub task_type: &'static str,
+    pub action: &'static str,
+    pub description: String,
+    pub start_time_in_millis: i64,
+    pub running_time_in_nanos: u64,
+    pub cancellable: bool,
+    pub cancelled: bool,
+}
+
+impl ElasticsearchTask {
+    fn from_search_task(
+        search_task: &SearchTask,
+        node_id: String,
+        now_millis: i64,
+        cancelled: bool,
+    ) -> Self {
+        let running_time_millis = (now_millis - search_task.start_time_millis).max(0) as u64;
+        let description = format!(
+            "indices[{}], query[{}]",
+            search_task.index_id_patterns.iter().join(","),
+            search_task.query_ast
+        );
+        ElasticsearchTask {
+            node: node_id,
+            id: search_task.search_task_id.clone(),
+            task_type: "transport",
+            action: SEARCH_ACTION,
+            description,
+            start_time_in_millis: search_task.start_time_millis,
+            running_time_in_nanos: running_time_millis * 1_000_000,
+            cancellable: true,
+            cancelled,
+        }
+    }
+}
+
+/// Returns the ID of the node running the search task, falling back to its gRPC address.
+fn search_task_node_id(search_task: &SearchTask, node_ids: &HashMap<String, String>) -> String {
+    node_ids
+        .get(&search_task.searcher_grpc_addr)
+        .cloned()
+        .unwrap_or_else(|| search_task.searcher_grpc_addr.clone())
+}
+
+/// Returns JSON in the format of the response of `GET _tasks/{task_id}`:
+///
+/// {
+///   "completed": false,
+///   "task": {
+///     "node": "searcher-1",
+///     "id": "01HAV29D4XY3D462FS3D8K5Q2H",
+///     "type": "transport",
+///     "action": "indices:data/read/search",
+///     ...
+///   }
+/// }
+///
+/// Only running searches are tracked, so the task is never completed.
+#[derive(Debug, Serialize)]
+pub struct ElasticsearchSearchTaskResponse {
+    pub completed: bool,
+    pub task: ElasticsearchTask,
+}
+
+impl ElasticsearchSearchTaskResponse {
+    pub fn from_search_task(
+        search_task: &SearchTask,
+        node_ids: &HashMap<String, String>,
+        now_millis: i64,
+    ) -> Self {
+        let node_id = search_task_node_id(search_task, node_ids);
+        ElasticsearchSearchTaskResponse {
+            completed: false,
+            task: ElasticsearchTask::from_search_task(search_task, node_id, now_millis, false),
+        }
+    }
+}
+
+impl ElasticsearchListTasksResponse {
+    /// Builds the response from the search tasks running in the cluster. `node_ids` maps the gRPC
+    /// address of the searchers to their node ID. Searchers missing from it are identified by
+    /// their gRPC address.
+    pub fn from_search_tasks(
+        search_tasks: Vec<SearchTask>,
+        node_ids: &HashMap<String, String>,
+        now_millis: i64,
+        cancelled: bool,
+    ) -> Self {
+        let mut list_tasks_response = ElasticsearchListTasksResponse::default();
+
+        for search_task in search_tasks {
+            let node_id = search_task_node_id(&search_task, node_ids);
+            let task = ElasticsearchTask::from_search_task(
+                &search_task,
+                node_id.clone(),
+                now_millis,
+                cancelled,
+            );
+            let node_tasks = list_tasks_response
+                .nodes
+                .entry(node_id.clone())
+                .or_insert_with(|| ElasticsearchNodeTasks {
+                    name: node_id.clone(),
+                    transport_address: search_task.searcher_grpc_addr.clone(),
+                    tasks: BTreeMap::new(),
+                });
+            let task_id = format!("{node_id}:{}", search_task.search_task_id);
+            node_tasks.tasks.insert(task_id, task);
+        }
+        list_tasks_response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_search_task_id() {
+        assert_eq!(
+            parse_search_task_id("searcher-1:01HAV29D4XY3D462FS3D8K5Q2H"),
+            "01HAV29D4XY3D462FS3D8K5Q2H"
+        );
+        assert_eq!(
+            parse_search_task_id("01HAV29D4XY3D462FS3D8K5Q2H"),
+            "01HAV29D4XY3D462FS3D8K5Q2H"
+        );
+    }
+
+    #[test]
+    fn test_list_tasks_response_from_search_tasks() {
+        let search_tasks = vec![
+            SearchTask {
+                search_task_id: "task-1".to_string(),
+                index_id_patterns: vec!["my-index".to_string()],
+                query_ast: r#"{"type":"match_all"}"#.to_string(),
+                start_time_millis: 1_000,
+                timeout_millis: None,
+                searcher_grpc_addr: "127.0.0.1:7281".to_string(),
+            },
+            SearchTask {
+                search_task_id: "task-2".to_string(),
+                start_time_millis: 2_000,
+                searcher_grpc_addr: "127.0.0.2:7281".to_string(),
+                ..Default::default()
+            },
+        ];
+        let node_ids = HashMap::from_iter([("127.0.0.1:7281".to_string(), "node-1".to_string())]);
+        let list_tasks_response = ElasticsearchListTasksResponse::from_search_tasks(
+            search_tasks,
+            &node_ids,
+            3_000,
+            false,
+        );
+        let list_tasks_response_json = serde_json::to_value(&list_tasks_response).unwrap();
+        let expected_list_tasks_response_json = serde_json::json!({
+            "nodes": {
+                "node-1": {
+                    "name": "node-1",
+                    "transport_address": "127.0.0.1:7281",
+                    "tasks": {
+                        "node-1:task-1": {
+                            "node": "node-1",
+                            "id": "task-1",
+                            "type": "transport",
+                            "action": "indices:data/read/search",
+                            "description": r#"indices[my-index], query[{"type":"match_all"}]"#,
+                            "start_time_in_millis": 1_000,
+                            "running_time_in_nanos": 2_000_000_000u64,
+                            "cancellable": true,
+                            "cancelled": false,
+                        }
+                    }
+                },
+                "127.0.0.2:7281": {
+                    "name": "127.0.0.2:7281",
+                    "transport_address": "127.0.0.2:7281",
+                    "tasks": {
+                        "127.0.0.2:7281:task-2": {
+                            "node": "127.0.0.2:7281",
+                            "id": "task-2",
+                            "type": "transport",
+                            "action": "indices:data/read/search",
+                            "description": "indices[], query[]",
+                            "start_time_in_millis": 2_000,
+                            "running_time_in_nanos": 1_000_000_000u64,
+                            "cancellable": true,
+                            "cancelled": false,
+                        }
+                    }
+                }
+            }
+        });
+        assert_eq!(list_tasks_response_json, expected_list_tasks_response_json);
+    }
+}
@@ -18,53 +18,82 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use bytes::Bytes;
-use elasticsearch_dsl::search::Hit as ElasticHit;
+use elasticsearch_dsl::search::{ErrorCause, Hit as ElasticHit, InnerHitsResult};
 use elasticsearch_dsl::{HitsMetadata, ShardStatistics, Source, TotalHits, TotalHitsRelation};
 use futures_util::StreamExt;
 use itertools::Itertools;
+use percent_encoding::percent_decode_str;
 use quickwit_cluster::Cluster;
 use quickwit_common::truncate_str;
-use quickwit_config::{NodeConfig, validate_index_id_pattern};
+use quickwit_config::{IndexAliasAction, NodeConfig, validate_index_id_pattern};
 use quickwit_index_management::IndexService;
 use quickwit_metastore::*;
-use quickwit_proto::metastore::MetastoreServiceClient;
+use quickwit_proto::metastore::{
+    EntityKind, IndexMetadataRequest, ListDeleteTasksRequest, ListIndexAliasesRequest,
+    MetastoreError, MetastoreService, MetastoreServiceClient, UpdateIndexAliasesRequest,
+};
 use quickwit_proto::search::{
-    CountHits, ListFieldsResponse, PartialHit, ScrollRequest, SearchResponse, SortByValue,
-    SortDatetimeFormat,
+    AsyncSearchResponse, CancelSearchTaskRequest, ClosePointInTimeRequest, CollapseRequest,
+    CountHits, DeleteAsyncSearchRequest, GetAsyncSearchRequest, ListFieldsResponse,
+    ListSearchTasksRequest, OpenPointInTimeRequest, PartialHit, PointInTime, ScrollRequest,
+    SearchResponse, SnippetOptions, SortByValue, SortDatetimeFormat, SubmitAsyncSearchRequest,
 };
 use quickwit_proto::types::IndexUid;
-use quickwit_query::BooleanOperand;
-use quickwit_query::query_ast::{BoolQuery, QueryAst, UserInputQuery};
+use quickwit_query::query_ast::{BoolQuery, QueryAst, TermQuery, UserInputQuery};
+use quickwit_query::{BooleanOperand, ID_FIELD_NAME};
 use quickwit_search::{
-    AggregationResults, SearchError, SearchService, list_all_splits, resolve_index_patterns,
+    AggregationResults, KnnQuery, RrfParams, SearchError, SearchService, list_all_splits,
+    resolve_index_patterns,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use time::OffsetDateTime;
 use warp::hyper::StatusCode;
 use warp::reply::with_status;
-use warp::{Filter, Rejection};
+use warp::{Filter, Rejection, Reply};
 
 use super::filter::{
-    elastic_cat_indices_filter, elastic_cluster_health_filter, elastic_cluster_info_filter,
-    elastic_delete_index_filter, elastic_field_capabilities_filter,
+    elastic_cancel_task_filter, elastic_cat_indices_filter, elastic_close_point_in_time_filter,
+    elastic_cluster_health_filter, elastic_cluster_info_filter, elastic_delete_alias_filter,
+    elastic_delete_async_search_filter, elastic_delete_by_query_filter,
+    elastic_delete_index_filter, elastic_field_capabilities_filter, elastic_get_aliases_filter,
+    elastic_get_async_search_filter, elastic_get_document_filter, elastic_get_task_filter,
     elastic_index_cat_indices_filter, elastic_index_count_filter,
-    elastic_index_field_capabilities_filter, elastic_index_search_filter,
-    elastic_index_stats_filter, elastic_multi_search_filter, elastic_resolve_index_filter,
-    elastic_scroll_filter, elastic_stats_filter, elasticsearch_filter,
+    elastic_index_field_capabilities_filter, elastic_index_mapping_filter,
+    elastic_index_search_filter, elastic_index_stats_filter, elastic_list_tasks_filter,
+    elastic_mapping_filter, elastic_multi_get_filter, elastic_multi_search_filter,
+    elastic_open_point_in_time_filter, elastic_put_alias_filter, elastic_resolve_index_filter,
+    elastic_scroll_filter, elastic_sql_filter, elastic_stats_filter,
+    elastic_submit_async_search_filter, elastic_update_aliases_filter, elasticsearch_filter,
 };
 use super::model::{
-    CatIndexQueryParams, DeleteQueryParams, ElasticsearchCatIndexResponse, ElasticsearchError,
-    ElasticsearchResolveIndexEntryResponse, ElasticsearchResolveIndexResponse,
-    ElasticsearchResponse, ElasticsearchStatsResponse, FieldCapabilityQueryParams,
-    FieldCapabilityRequestBody, FieldCapabilityResponse, MultiSearchHeader, MultiSearchQueryParams,
-    MultiSearchResponse, MultiSearchSingleResponse, ScrollQueryParams, SearchBody,
-    SearchQueryParams, SearchQueryParamsCount, StatsResponseEntry,
-    build_list_field_request_for_es_api, convert_to_es_field_capabilities_response,
+    AddAliasAction, AliasAction, CatIndexQueryParams, ClosePointInTimeBody, CollapseBody,
+    DeleteByQueryBody, DeleteQueryParams, ElasticException, ElasticTaskId,
+    ElasticsearchAliasesResponse, ElasticsearchAsyncSearchResponse, ElasticsearchCatIndexResponse,
+    ElasticsearchClosePointInTimeResponse, ElasticsearchDeleteByQueryResponse, ElasticsearchError,
+    ElasticsearchGetDocumentResponse, ElasticsearchListTasksResponse,
+    ElasticsearchMappingsResponse, ElasticsearchMultiGetResponse,
+    ElasticsearchOpenPointInTimeResponse, ElasticsearchResolveIndexEntryResponse,
+    ElasticsearchResolveIndexResponse, ElasticsearchResponse, ElasticsearchSearchTaskResponse,
+    ElasticsearchStatsResponse, ElasticsearchTaskResponse, FieldCapabilityQueryParams,
+    FieldCapabilityRequestBody, FieldCapabilityResponse, GetAsyncSearchQueryParams,
+    GetDocumentQueryParams, HighlightBody, KnnBody, MultiGetBody, MultiSearchHeader,
+    MultiSearchQueryParams, MultiSearchResponse, MultiSearchSingleResponse,
+    OpenPointInTimeQueryParams, PutAliasBody, RankBody, RemoveAliasAction, ScrollQueryParams,
+    SearchBody, SearchQueryParams, SearchQueryParamsCount, SourceBody, StatsResponseEntry,
+    UpdateAliasesBody, build_list_field_request_for_es_api, convert_to_es_aliases_response,
+    convert_to_es_field_capabilities_response, convert_to_es_mappings_response,
+    parse_keep_alive_secs, parse_search_task_id, parse_wait_for_completion_timeout,
 };
 use super::{TrackTotalHits, make_elastic_api_response};
+use crate::delete_task_api::submit_delete_query;
 use crate::format::BodyFormat;
 use crate::rest::recover_fn;
-use crate::rest_api_response::{RestApiError, RestApiResponse};
+use crate::rest_api_response::RestApiResponse;
+use crate::search_api::tenant_id_header;
+use crate::sql_api::{
+    SqlQueryParams, SqlRequest, SqlResponseFormat, into_csv_response, sql_endpoint,
+};
 use crate::{BuildInfo, with_arg};
 
 /// Elastic compatible cluster info handler.
@@ -94,24 +123,74 @@ pub fn es_compat_cluster_info_handler(
 
 /// GET or POST _elastic/_search
 pub fn es_compat_search_handler(
-    _search_service: Arc<dyn SearchService>,
+    search_service: Arc<dyn SearchService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     elasticsearch_filter()
-        .then(|_params: SearchQueryParams| async move {
-            // TODO
-            let api_error = RestApiError {
-                status_code: StatusCode::NOT_IMPLEMENTED,
-                message: "_elastic/_search is not supported yet. Please try the index search \
-                          endpoint (_elastic/{index}/search)"
-                    .to_string(),
-            };
-            RestApiResponse::new::<(), _>(
-                &Err(api_error),
-                StatusCode::NOT_IMPLEMENTED,
-                BodyFormat::default(),
-            )
-        })
+        .and(with_arg(search_service))
+        .then(es_compat_search)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+        .recover(recover_fn)
+        .boxed()
+}
+
+/// POST _elastic/{index}/_pit
+pub fn es_compat_open_point_in_time_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_open_point_in_time_filter()
+        .and(with_arg(search_service))
+        .then(es_compat_open_point_in_time)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+        .recover(recover_fn)
+        .boxed()
+}
+
+/// DELETE _elastic/_pit
+pub fn es_compat_close_point_in_time_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_close_point_in_time_filter()
+        .and(with_arg(search_service))
+        .then(es_compat_close_point_in_time)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+        .recover(recover_fn)
+        .boxed()
+}
+
+/// POST _elastic/{index}/_async_search
+pub fn es_compat_submit_async_search_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_submit_async_search_filter()
+        .and(with_arg(search_service))
+        .then(es_compat_submit_async_search)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+        .recover(recover_fn)
+        .boxed()
+}
+
+/// GET _elastic/_async_search/{id}
+pub fn es_compat_get_async_search_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_get_async_search_filter()
+        .and(with_arg(search_service))
+        .then(es_compat_get_async_search)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+        .recover(recover_fn)
+        .boxed()
+}
+
+/// DELETE _elastic/_async_search/{id}
+pub fn es_compat_delete_async_search_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_delete_async_search_filter()
+        .and(with_arg(search_service))
+        .then(es_compat_delete_async_search)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
         .recover(recover_fn)
+        .boxed()
 }
 
 /// GET or POST _elastic/{index}/_field_caps
@@ -138,6 +217,74 @@ pub fn es_compat_delete_index_handler(
         .boxed()
 }
 
+/// POST _elastic/{index}/_delete_by_query
+pub fn es_compat_delete_by_query_handler(
+    metastore_service: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_delete_by_query_filter()
+        .and(with_arg(metastore_service))
+        .then(es_compat_delete_by_query)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+        .recover(recover_fn)
+        .boxed()
+}
+
+/// GET _elastic/_tasks/{task_id}
+pub fn es_compat_get_task_handler(
+    metastore_service: MetastoreServiceClient,
+    search_service: Arc<dyn SearchService>,
+    cluster: Cluster,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_get_task_filter()
+        .and(with_arg(metastore_service))
+        .and(with_arg(search_service))
+        .and(with_arg(cluster))
+        .then(es_compat_get_task)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+        .recover(recover_fn)
+        .boxed()
+}
+
+/// GET _elastic/_tasks
+pub fn es_compat_list_tasks_handler(
+    search_service: Arc<dyn SearchService>,
+    cluster: Cluster,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_list_tasks_filter()
+        .and(with_arg(search_service))
+        .and(with_arg(cluster))
+        .then(es_compat_list_tasks)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+        .recover(recover_fn)
+        .boxed()
+}
+
+/// POST _elastic/_tasks/{task_id}/_cancel or DELETE _elastic/_tasks/{task_id}
+pub fn es_compat_cancel_task_handler(
+    search_service: Arc<dyn SearchService>,
+    cluster: Cluster,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_cancel_task_filter()
+        .and(with_arg(search_service))
+        .and(with_arg(cluster))
+        .then(es_compat_cancel_task)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+        .recover(recover_fn)
+        .boxed()
+}
+
+/// POST _elastic/_sql
+pub fn es_compat_sql_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_sql_filter()
+        .and(tenant_id_header())
+        .and(with_arg(search_service))
+        .then(es_compat_sql)
+        .recover(recover_fn)
+        .boxed()
+}
+
 /// GET _elastic/_stats
 pub fn es_compat_stats_handler(
     metastore_service: MetastoreServiceClient,
@@ -237,6 +384,20 @@ pub fn es_compat_index_cat_indices_handler(
         .boxed()
 }
 
+/// GET _elastic/_mapping or _elastic/{index}/_mapping
+pub fn es_compat_mapping_handler(
+    metastore_service: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_index_mapping_filter()
+        .or(elastic_mapping_filter().map(|| vec!["*".to_string()]))
+        .unify()
+        .and(with_arg(metastore_service))
+        .then(es_compat_mapping)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+        .recover(recover_fn)
+        .boxed()
+}
+
 /// GET  _elastic/_resolve/index/{index}
 pub fn es_compat_resolve_index_handler(
     metastore_service: MetastoreServiceClient,
@@ -248,6 +409,55 @@ pub fn es_compat_resolve_index_handler(
         .boxed()
 }
 
+/// POST _elastic/_aliases
+pub fn es_compat_update_aliases_handler(
+    metastore_service: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_update_aliases_filter()
+        .and(with_arg(metastore_service))
+        .then(es_compat_update_aliases)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+        .recover(recover_fn)
+        .boxed()
+}
+
+/// GET _elastic/_alias, _elastic/_alias/{alias}, _elastic/{index}/_alias or
+/// _elastic/{index}/_alias/{alias}
+pub fn es_compat_get_aliases_handler(
+    metastore_service: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_get_aliases_filter()
+        .and(with_arg(metastore_service))
+        .then(es_compat_get_aliases)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+        .recover(recover_fn)
+        .boxed()
+}
+
+/// PUT or POST _elastic/{index}/_alias/{alias}
+pub fn es_compat_put_alias_handler(
+    metastore_service: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_put_alias_filter()
+        .and(with_arg(metastore_service))
+        .then(es_compat_put_alias)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+        .recover(recover_fn)
+        .boxed()
+}
+
+/// DELETE _elastic/{index}/_alias/{alias}
+pub fn es_compat_delete_alias_handler(
+    metastore_service: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_delete_alias_filter()
+        .and(with_arg(metastore_service))
+        .then(es_compat_delete_alias)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+        .recover(recover_fn)
+        .boxed()
+}
+
 /// GET or POST _elastic/{index}/_search
 pub fn es_compat_index_search_handler(
     search_service: Arc<dyn SearchService>,
@@ -302,12 +512,59 @@ pub fn es_compat_scroll_handler(
         .boxed()
 }
 
+/// GET _elastic/{index}/_doc/{id}
+pub fn es_compat_get_document_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_get_document_filter()
+        .and(with_arg(search_service))
+        .then(es_compat_get_document)
+        .map(
+            |result: Result<ElasticsearchGetDocumentResponse, ElasticsearchError>| {
+                let status_code = match &result {
+                    Ok(get_document_response) if !get_document_response.found => {
+                        StatusCode::NOT_FOUND
+                    }
+                    Ok(_) => StatusCode::OK,
+                    Err(err) => err.status,
+                };
+                RestApiResponse::new(&result, status_code, BodyFormat::default())
+            },
+        )
+        .recover(recover_fn)
+        .boxed()
+}
+
+/// GET or POST _elastic/_mget
+pub fn es_compat_multi_get_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_multi_get_filter()
+        .and(with_arg(search_service))
+        .then(es_compat_multi_get)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+        .recover(recover_fn)
+        .boxed()
+}
+
+/// Same defaults as Elasticsearch for the inner hits of collapsed hits.
+const DEFAULT_INNER_HITS_SIZE: u32 = 3;
+const DEFAULT_INNER_HITS_NAME: &str = "inner_hits";
+
 #[allow(clippy::result_large_err)]
 fn build_request_for_es_api(
     index_id_patterns: Vec<String>,
     search_params: SearchQueryParams,
     search_body: SearchBody,
 ) -> Result<(quickwit_proto::search::SearchRequest, bool), ElasticsearchError> {
+    // A point in time search targets the indexes the point in time was opened on.
+    if search_body.pit.is_some() && !index_id_patterns.is_empty() {
+        return Err(ElasticsearchError::new(
+            StatusCode::BAD_REQUEST,
+            "[indices] cannot be used with point in time".to_string(),
+            Some(ElasticException::ActionRequestValidation),
+        ));
+    }
     let default_operator = search_params.default_operator.unwrap_or(BooleanOperand::Or);
     // The query string, if present, takes priority over what can be in the request
     // body.
@@ -392,10 +649,46 @@ fn build_request_for_es_api(
 
     let scroll_duration: Option<Duration> = search_params.parse_scroll_ttl()?;
     let scroll_ttl_secs: Option<u32> = scroll_duration.map(|duration| duration.as_secs() as u32);
+    let timeout_millis: Option<u64> = search_params
+        .parse_timeout(search_body.timeout.as_deref())?
+        .map(|timeout| timeout.as_millis() as u64);
 
     let has_doc_id_field = sort_fields.iter().any(is_doc_field);
     let search_after = partial_hit_from_search_after_param(search_body.search_after, &sort_fields)?;
 
+    let (snippet_fields, snippet_options) = search_body
+        .highlight
+        .map(snippet_fields_and_options_from_highlight)
+        .unwrap_or_default();
+    let source_filter = search_body._source.and_then(SourceBody::into_source_filter);
+    let point_in_time = search_body
+        .pit
+        .map(|pit_body| {
+            let keep_alive_secs = pit_body.keep_alive_secs()?;
+            Ok::<_, SearchError>(PointInTime {
+                pit_id: pit_body.id,
+                keep_alive_secs,
+            })
+        })
+        .transpose()?;
+    let collapse = search_body.collapse.map(|collapse_body| CollapseRequest {
+        field: collapse_body.field,
+        inner_hits_size: collapse_body
+            .inner_hits
+            .map(|inner_hits| inner_hits.size.unwrap_or(DEFAULT_INNER_HITS_SIZE))
+            .unwrap_or(0),
+        group_size: 0,
+    });
+    let knn = match (search_body.knn, search_body.rank) {
+        (Some(knn_body), rank_opt) => Some(knn_from_body(knn_body, rank_opt)?),
+        (None, Some(_)) => {
+            return Err(ElasticsearchError::from(SearchError::InvalidArgument(
+                "`rank` can only be used with `knn`".to_string(),
+            )));
+        }
+        (None, None) => None,
+    };
+
     Ok((
         quickwit_proto::search::SearchRequest {
             index_id_patterns,
@@ -406,15 +699,75 @@ fn build_request_for_es_api(
             sort_fields,
             start_timestamp: None,
             end_timestamp: None,
-            snippet_fields: Vec::new(),
+            snippet_fields,
+            snippet_options,
+            source_filter,
+            point_in_time,
             scroll_ttl_secs,
             search_after,
             count_hits,
+            timeout_millis,
+            tenant_id: search_params.tenant_id,
+            collapse,
+            profile: search_body.profile,
+            knn,
         },
         has_doc_id_field,
     ))
 }
 
+/// Converts the `knn` and `rank` clauses into a serialized [`KnnQuery`].
+fn knn_from_body(knn_body: KnnBody, rank_opt: Option<RankBody>) -> Result<String, SearchError> {
+    let filter = knn_body
+        .filter
+        .map(QueryAst::try_from)
+        .transpose()
+        .map_err(|err: anyhow::Error| SearchError::InvalidQuery(err.to_string()))?;
+    let rrf = rank_opt.map(|rank| RrfParams {
+        window_size: rank.rrf.rank_window_size,
+        rank_constant: rank.rrf.rank_constant,
+    });
+    let knn_query = KnnQuery {
+        field: knn_body.field,
+        query_vector: knn_body.query_vector,
+        k: knn_body.k,
+        num_candidates: knn_body.num_candidates,
+        filter,
+        rrf,
+    };
+    Ok(serde_json::to_string(&knn_query).expect("Failed to serialize KnnQuery"))
+}
+
+/// Converts the `highlight` clause into snippet fields and options. Only the first pre and post
+/// tags are used. Field patterns are expanded and the fields that cannot be highlighted are
+/// ignored by the searchers, as in Elasticsearch.
+fn snippet_fields_and_options_from_highlight(
+    highlight: HighlightBody,
+) -> (Vec<String>, Option<SnippetOptions>) {
+    let snippet_fields: Vec<String> = highlight.fields;
+    if snippet_fields.is_empty() {
+        return (Vec::new(), None);
+    }
+    let pre_tag = highlight
+        .pre_tags
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| "<em>".to_string());
+    let post_tag = highlight
+        .post_tags
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| "</em>".to_string());
+    let snippet_options = SnippetOptions {
+        pre_tag: Some(pre_tag),
+        post_tag: Some(post_tag),
+        fragment_size: highlight.fragment_size,
+        number_of_fragments: highlight.number_of_fragments,
+        lenient_fields: true,
+    };
+    (snippet_fields, Some(snippet_options))
+}
+
 fn is_doc_field(field: &quickwit_proto::search::SortField) -> bool {
     field.field_name == "_shard_doc" || field.field_name == "_doc"
 }
@@ -515,6 +868,8 @@ async fn es_compat_index_search(
     let _source_includes = search_params._source_includes.clone();
     let start_instant = Instant::now();
     let allow_partial_search_results = search_params.allow_partial_search_results();
+    let pit_id_opt = search_body.pit.as_ref().map(|pit_body| pit_body.id.clone());
+    let collapse_opt = search_body.collapse.clone();
     let (search_request, append_shard_doc) =
         build_request_for_es_api(index_id_patterns, search_params, search_body)?;
     let search_response: SearchResponse = search_service.root_search(search_request).await?;
@@ -524,19 +879,333 @@ async fn es_compat_index_search(
         append_shard_doc,
         _source_excludes,
         _source_includes,
+        collapse_opt.as_ref(),
         allow_partial_search_results,
     )?;
     search_response_rest.took = elapsed.as_millis() as u32;
+    search_response_rest.pit_id = pit_id_opt;
     Ok(search_response_rest)
 }
 
+/// Only point in time searches, which carry their indexes in the point in time ID, can be
+/// performed without specifying the indexes in the path.
+async fn es_compat_search(
+    search_params: SearchQueryParams,
+    search_body: SearchBody,
+    search_service: Arc<dyn SearchService>,
+) -> Result<ElasticsearchResponse, ElasticsearchError> {
+    if search_body.pit.is_none() {
+        return Err(ElasticsearchError::new(
+            StatusCode::NOT_IMPLEMENTED,
+            "_elastic/_search is only supported with a point in time. Please try the index search \
+             endpoint (_elastic/{index}/_search)"
+                .to_string(),
+            None,
+        ));
+    }
+    es_compat_index_search(Vec::new(), search_params, search_body, search_service).await
+}
+
+async fn es_compat_open_point_in_time(
+    index_id_patterns: Vec<String>,
+    query_params: OpenPointInTimeQueryParams,
+    search_service: Arc<dyn SearchService>,
+) -> Result<ElasticsearchOpenPointInTimeResponse, ElasticsearchError> {
+    let keep_alive_secs = query_params.keep_alive_secs()?;
+    let open_request = OpenPointInTimeRequest {
+        index_id_patterns,
+        keep_alive_secs,
+    };
+    let open_response = search_service.open_point_in_time(open_request).await?;
+    Ok(ElasticsearchOpenPointInTimeResponse {
+        id: open_response.pit_id,
+    })
+}
+
+async fn es_compat_close_point_in_time(
+    close_body: ClosePointInTimeBody,
+    search_service: Arc<dyn SearchService>,
+) -> Result<ElasticsearchClosePointInTimeResponse, ElasticsearchError> {
+    let Some(pit_id) = close_body.id else {
+        return Err(ElasticsearchError::new(
+            StatusCode::BAD_REQUEST,
+            "request body must contain a point in time id".to_string(),
+            Some(ElasticException::ActionRequestValidation),
+        ));
+    };
+    let close_request = ClosePointInTimeRequest { pit_id };
+    let close_response = search_service.close_point_in_time(close_request).await?;
+    Ok(ElasticsearchClosePointInTimeResponse {
+        succeeded: true,
+        num_freed: close_response.num_freed,
+    })
+}
+
+/// Partial responses carry the number of hits and the aggregations merged so far, but no hits.
+fn convert_to_es_async_search_response(
+    async_search_response: AsyncSearchResponse,
+    append_shard_doc: bool,
+    _source_excludes: Option<Vec<String>>,
+    _source_includes: Option<Vec<String>>,
+    collapse_opt: Option<&CollapseBody>,
+    allow_partial_search_results: bool,
+    is_stored: bool,
+) -> ElasticsearchAsyncSearchResponse {
+    let mut error_opt = async_search_response.error.map(|error| ErrorCause {
+        reason: Some(error),
+        caused_by: None,
+        root_cause: Vec::new(),
+        stack_trace: None,
+        suppressed: Vec::new(),
+        ty: None,
+        additional_details: Default::default(),
+    });
+    let search_response = async_search_response.response.unwrap_or_default();
+    let took_millis = (search_response.elapsed_time_micros / 1_000) as u32;
+    let response = match convert_to_es_search_response(
+        search_response,
+        append_shard_doc,
+        _source_excludes,
+        _source_includes,
+        collapse_opt,
+        allow_partial_search_results || async_search_response.is_running,
+    ) {
+        Ok(mut response) => {
+            response.took = took_millis;
+            response
+        }
+        Err(error) => {
+            error_opt.get_or_insert(error.error);
+            ElasticsearchResponse::default()
+        }
+    };
+    ElasticsearchAsyncSearchResponse {
+        id: is_stored.then_some(async_search_response.async_search_id),
+        is_partial: async_search_response.is_partial,
+        is_running: async_search_response.is_running,
+        start_time_in_millis: async_search_response.start_time_millis,
+        expiration_time_in_millis: async_search_response.expiration_time_millis,
+        response,
+        error: error_opt,
+    }
+}
+
+async fn es_compat_submit_async_search(
+    index_id_patterns: Vec<String>,
+    search_params: SearchQueryParams,
+    search_body: SearchBody,
+    search_service: Arc<dyn SearchService>,
+) -> Result<ElasticsearchAsyncSearchResponse, ElasticsearchError> {
+    if search_params.scroll.is_some() {
+        return Err(ElasticsearchError::from(SearchError::InvalidArgument(
+            "scroll is not supported by the async search API".to_string(),
+        )));
+    }
+    let keep_alive_secs = parse_keep_alive_secs(search_params.keep_alive.as_deref())?;
+    let wait_for_completion_timeout =
+        parse_wait_for_completion_timeout(search_params.wait_for_completion_timeout.as_deref())?;
+    let keep_on_completion = search_params.keep_on_completion.unwrap_or(false);
+    let _source_excludes = search_params._source_excludes.clone();
+    let _source_includes = search_params._source_includes.clone();
+    let allow_partial_search_results = search_params.allow_partial_search_results();
+    let collapse_opt = search_body.collapse.clone();
+    let (search_request, append_shard_doc) =
+        build_request_for_es_api(index_id_patterns, search_params, search_body)?;
+    let submit_request = SubmitAsyncSearchRequest {
+        search_request: Some(search_request),
+        keep_alive_secs,
+        wait_for_completion_timeout_millis: wait_for_completion_timeout.as_millis() as u64,
+    };
+    let async_search_response = search_service.submit_async_search(submit_request).await?;
+
+    // Like Elasticsearch, searches that complete within the wait for completion timeout are only
+    // kept if explicitly requested.
+    let is_stored = async_search_response.is_running || keep_on_completion;
+    if !is_stored {
+        let delete_request = DeleteAsyncSearchRequest {
+            async_search_id: async_search_response.async_search_id.clone(),
+        };
+        search_service.delete_async_search(delete_request).await?;
+    }
+    Ok(convert_to_es_async_search_response(
+        async_search_response,
+        append_shard_doc,
+        _source_excludes,
+        _source_includes,
+        collapse_opt.as_ref(),
+        allow_partial_search_results,
+        is_stored,
+    ))
+}
+
+async fn es_compat_get_async_search(
+    async_search_id: String,
+    query_params: GetAsyncSearchQueryParams,
+    search_service: Arc<dyn SearchService>,
+) -> Result<ElasticsearchAsyncSearchResponse, ElasticsearchError> {
+    let keep_alive_secs = query_params
+        .keep_alive
+        .as_deref()
+        .map(|keep_alive| parse_keep_alive_secs(Some(keep_alive)))
+        .transpose()?;
+    let wait_for_completion_timeout =
+        parse_wait_for_completion_timeout(query_params.wait_for_completion_timeout.as_deref())?;
+    let get_request = GetAsyncSearchRequest {
+        async_search_id,
+        keep_alive_secs,
+        wait_for_completion_timeout_millis: wait_for_completion_timeout.as_millis() as u64,
+    };
+    let async_search_response = search_service.get_async_search(get_request).await?;
+    // The source filtering, shard doc and collapse options of the submit request are not
+    // persisted.
+    Ok(convert_to_es_async_search_response(
+        async_search_response,
+        false,
+        None,
+        None,
+        None,
+        true,
+        true,
+    ))
+}
+
+async fn es_compat_delete_async_search(
+    async_search_id: String,
+    search_service: Arc<dyn SearchService>,
+) -> Result<ElasticsearchAcknowledgedResponse, ElasticsearchError> {
+    let delete_request = DeleteAsyncSearchRequest {
+        async_search_id: async_search_id.clone(),
+    };
+    let delete_response = search_service.delete_async_search(delete_request).await?;
+
+    if delete_response.num_freed == 0 {
+        return Err(ElasticsearchError::new(
+            StatusCode::NOT_FOUND,
+            format!("async search `{async_search_id}` not found"),
+            Some(ElasticException::ResourceNotFound),
+        ));
+    }
+    Ok(ElasticsearchAcknowledgedResponse { acknowledged: true })
+}
+
+/// Fetches a document by id, i.e. by the value of the `id_field` of the doc mapping.
+async fn get_document(
+    index_id_pattern: String,
+    doc_id: String,
+    query_params: &GetDocumentQueryParams,
+    search_service: &dyn SearchService,
+) -> Result<ElasticsearchGetDocumentResponse, ElasticsearchError> {
+    let query_ast: QueryAst = TermQuery {
+        field: ID_FIELD_NAME.to_string(),
+        value: doc_id.clone(),
+    }
+    .into();
+    let search_request = quickwit_proto::search::SearchRequest {
+        index_id_patterns: vec![index_id_pattern.clone()],
+        query_ast: serde_json::to_string(&query_ast).expect("Failed to serialize QueryAst"),
+        max_hits: 1,
+        count_hits: CountHits::Underestimate.into(),
+        ..Default::default()
+    };
+    let search_response = search_service.root_search(search_request).await?;
+    let Some(hit) = search_response.hits.into_iter().next() else {
+        return Ok(ElasticsearchGetDocumentResponse::not_found(
+            index_id_pattern,
+            doc_id,
+        ));
+    };
+    let mut source: serde_json::Value = serde_json::from_str(&hit.json).unwrap_or(json!({}));
+    filter_source(
+        &mut source,
+        &query_params._source_excludes,
+        &query_params._source_includes,
+    );
+    Ok(ElasticsearchGetDocumentResponse {
+        index: hit.index_id,
+        id: doc_id,
+        found: true,
+        source: Some(source),
+        error: None,
+    })
+}
+
+async fn es_compat_get_document(
+    index_id_pattern: String,
+    doc_id: String,
+    query_params: GetDocumentQueryParams,
+    search_service: Arc<dyn SearchService>,
+) -> Result<ElasticsearchGetDocumentResponse, ElasticsearchError> {
+    let doc_id = percent_decode_str(&doc_id).decode_utf8_lossy().to_string();
+    get_document(index_id_pattern, doc_id, &query_params, &*search_service).await
+}
+
+async fn es_compat_multi_get(
+    index_id_pattern_opt: Option<String>,
+    query_params: GetDocumentQueryParams,
+    multi_get_body: MultiGetBody,
+    search_service: Arc<dyn SearchService>,
+) -> Result<ElasticsearchMultiGetResponse, ElasticsearchError> {
+    let mut docs: Vec<(String, String)> =
+        Vec::with_capacity(multi_get_body.docs.len() + multi_get_body.ids.len());
+    for doc in multi_get_body.docs {
+        let Some(index_id_pattern) = doc.index.or_else(|| index_id_pattern_opt.clone()) else {
+            return Err(ElasticsearchError::new(
+                StatusCode::BAD_REQUEST,
+                format!("index is missing for doc [{}]", doc.id),
+                Some(ElasticException::ActionRequestValidation),
+            ));
+        };
+        docs.push((index_id_pattern, doc.id));
+    }
+    if !multi_get_body.ids.is_empty() {
+        let Some(index_id_pattern) = &index_id_pattern_opt else {
+            return Err(ElasticsearchError::new(
+                StatusCode::BAD_REQUEST,
+                "index is missing for `ids`".to_string(),
+                Some(ElasticException::ActionRequestValidation),
+            ));
+        };
+        for doc_id in multi_get_body.ids {
+            docs.push((index_id_pattern.clone(), doc_id));
+        }
+    }
+    if docs.is_empty() {
+        return Err(ElasticsearchError::new(
+            StatusCode::BAD_REQUEST,
+            "no documents to get".to_string(),
+            Some(ElasticException::ActionRequestValidation),
+        ));
+    }
+    let futures = docs.into_iter().map(|(index_id_pattern, doc_id)| {
+        let search_service = &*search_service;
+        let query_params = &query_params;
+        async move {
+            get_document(
+                index_id_pattern.clone(),
+                doc_id.clone(),
+                query_params,
+                search_service,
+            )
+            .await
+            .unwrap_or_else(|error| {
+                ElasticsearchGetDocumentResponse::from_error(index_id_pattern, doc_id, error)
+            })
+        }
+    });
+    let docs = futures::stream::iter(futures)
+        .buffered(10)
+        .collect::<Vec<_>>()
+        .await;
+    Ok(ElasticsearchMultiGetResponse { docs })
+}
+
 /// Returns JSON in the format:
 ///
 /// {
 ///   "acknowledged": true
 /// }
 #[derive(Clone, Serialize, Deserialize, Debug)]
-pub struct ElasticsearchDeleteResponse {
+pub struct ElasticsearchAcknowledgedResponse {
     pub acknowledged: bool,
 }
 
@@ -544,7 +1213,7 @@ async fn es_compat_delete_index(
     index_id_patterns: Vec<String>,
     query_params: DeleteQueryParams,
     index_service: IndexService,
-) -> Result<ElasticsearchDeleteResponse, ElasticsearchError> {
+) -> Result<ElasticsearchAcknowledgedResponse, ElasticsearchError> {
     index_service
         .delete_indexes(
             index_id_patterns,
@@ -552,7 +1221,206 @@ async fn es_compat_delete_index(
             false,
         )
         .await?;
-    Ok(ElasticsearchDeleteResponse { acknowledged: true })
+    Ok(ElasticsearchAcknowledgedResponse { acknowledged: true })
+}
+
+/// Delete by query is not applied synchronously: the query is registered as a delete task
+/// that the janitor applies to the splits of the index in the background. The progress of the
+/// returned task can be polled with `GET _elastic/_tasks/{task_id}`.
+async fn es_compat_delete_by_query(
+    index_id: String,
+    delete_by_query_body: DeleteByQueryBody,
+    metastore: MetastoreServiceClient,
+) -> Result<ElasticsearchDeleteByQueryResponse, ElasticsearchError> {
+    let Some(query_dsl) = delete_by_query_body.query else {
+        return Err(ElasticsearchError::new(
+            StatusCode::BAD_REQUEST,
+            "request body must contain a query".to_string(),
+            Some(ElasticException::ActionRequestValidation),
+        ));
+    };
+    let query_ast: QueryAst = query_dsl
+        .try_into()
+        .map_err(|err: anyhow::Error| SearchError::InvalidQuery(err.to_string()))?;
+    let index_metadata_request = IndexMetadataRequest::for_index_id(index_id);
+    let index_metadata = metastore
+        .index_metadata(index_metadata_request)
+        .await?
+        .deserialize_index_metadata()?;
+    let index_uid = index_metadata.index_uid.clone();
+    let delete_task =
+        submit_delete_query(&metastore, index_metadata, query_ast, None, None).await?;
+    let task_id = ElasticTaskId {
+        index_uid,
+        opstamp: delete_task.opstamp,
+    };
+    Ok(ElasticsearchDeleteByQueryResponse {
+        task: task_id.to_string(),
+    })
+}
+
+/// Returns JSON in the format of either a delete task or a search task.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum ElasticsearchGetTaskResponse {
+    DeleteTask(ElasticsearchTaskResponse),
+    SearchTask(ElasticsearchSearchTaskResponse),
+}
+
+/// Task IDs of delete tasks are formatted as `<index_uid>:<opstamp>`. Any other task ID is
+/// resolved as the ID of a search running in the cluster, optionally prefixed by the node ID,
+/// as returned by `GET _elastic/_tasks`.
+async fn es_compat_get_task(
+    task_id: String,
+    metastore: MetastoreServiceClient,
+    search_service: Arc<dyn SearchService>,
+    cluster: Cluster,
+) -> Result<ElasticsearchGetTaskResponse, ElasticsearchError> {
+    let task_id = percent_decode_str(&task_id).decode_utf8_lossy().to_string();
+
+    if let Ok(delete_task_id) = task_id.parse::<ElasticTaskId>() {
+        let delete_task_response = get_delete_task(delete_task_id, metastore).await?;
+        return Ok(ElasticsearchGetTaskResponse::DeleteTask(
+            delete_task_response,
+        ));
+    }
+    let search_task_id = parse_search_task_id(&task_id);
+    let list_response = search_service
+        .root_list_search_tasks(ListSearchTasksRequest {})
+        .await?;
+    let Some(search_task) = list_response
+        .search_tasks
+        .iter()
+        .find(|search_task| search_task.search_task_id == search_task_id)
+    else {
+        return Err(ElasticsearchError::new(
+            StatusCode::NOT_FOUND,
+            format!("task [{task_id}] not found"),
+            Some(ElasticException::ResourceNotFound),
+        ));
+    };
+    let node_ids = node_ids_by_grpc_addr(&cluster).await;
+    let now_millis = OffsetDateTime::now_utc().unix_timestamp_nanos() as i64 / 1_000_000;
+    Ok(ElasticsearchGetTaskResponse::SearchTask(
+        ElasticsearchSearchTaskResponse::from_search_task(search_task, &node_ids, now_millis),
+    ))
+}
+
+async fn get_delete_task(
+    task_id: ElasticTaskId,
+    mut metastore: MetastoreServiceClient,
+) -> Result<ElasticsearchTaskResponse, ElasticsearchError> {
+    // `ListDeleteTasks` returns the tasks with an opstamp strictly greater than `opstamp_start`.
+    let list_delete_tasks_request =
+        ListDeleteTasksRequest::new(task_id.index_uid.clone(), task_id.opstamp.saturating_sub(1));
+    let delete_task = metastore
+        .list_delete_tasks(list_delete_tasks_request)
+        .await?
+        .delete_tasks
+        .into_iter()
+        .find(|delete_task| delete_task.opstamp == task_id.opstamp)
+        .ok_or_else(|| {
+            ElasticsearchError::new(
+                StatusCode::NOT_FOUND,
+                format!("task [{task_id}] not found"),
+                Some(ElasticException::ResourceNotFound),
+            )
+        })?;
+    // The janitor bumps the `delete_opstamp` of every split once the task has been applied to it,
+    // regardless of whether the split contained matching documents or not.
+    let splits_metadata = list_all_splits(vec![task_id.index_uid.clone()], &mut metastore).await?;
+    let num_stale_splits = splits_metadata
+        .iter()
+        .filter(|split_metadata| split_metadata.delete_opstamp < task_id.opstamp)
+        .count();
+    Ok(ElasticsearchTaskResponse::from_delete_task(
+        &task_id.index_uid.index_id,
+        &delete_task,
+        splits_metadata.len(),
+        num_stale_splits,
+    ))
+}
+
+/// Maps the gRPC address of the nodes of the cluster to their node ID.
+async fn node_ids_by_grpc_addr(cluster: &Cluster) -> HashMap<String, String> {
+    cluster
+        .ready_members()
+        .await
+        .into_iter()
+        .map(|member| {
+            (
+                member.grpc_advertise_addr.to_string(),
+                member.node_id.to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Lists the searches running in the cluster. Other kinds of tasks are not listed.
+async fn es_compat_list_tasks(
+    search_service: Arc<dyn SearchService>,
+    cluster: Cluster,
+) -> Result<ElasticsearchListTasksResponse, ElasticsearchError> {
+    let list_response = search_service
+        .root_list_search_tasks(ListSearchTasksRequest {})
+        .await?;
+    let node_ids = node_ids_by_grpc_addr(&cluster).await;
+    let now_millis = OffsetDateTime::now_utc().unix_timestamp_nanos() as i64 / 1_000_000;
+    Ok(ElasticsearchListTasksResponse::from_search_tasks(
+        list_response.search_tasks,
+        &node_ids,
+        now_millis,
+        false,
+    ))
+}
+
+/// Cancels a search running in the cluster, aborting its leaf searches on all the nodes.
+async fn es_compat_cancel_task(
+    task_id: String,
+    search_service: Arc<dyn SearchService>,
+    cluster: Cluster,
+) -> Result<ElasticsearchListTasksResponse, ElasticsearchError> {
+    let task_id = percent_decode_str(&task_id).decode_utf8_lossy();
+    let cancel_request = CancelSearchTaskRequest {
+        search_task_id: parse_search_task_id(&task_id).to_string(),
+    };
+    let cancel_response = search_service
+        .root_cancel_search_task(cancel_request)
+        .await?;
+
+    if cancel_response.cancelled_search_tasks.is_empty() {
+        return Err(ElasticsearchError::new(
+            StatusCode::NOT_FOUND,
+            format!("task [{task_id}] is not found"),
+            Some(ElasticException::ResourceNotFound),
+        ));
+    }
+    let node_ids = node_ids_by_grpc_addr(&cluster).await;
+    let now_millis = OffsetDateTime::now_utc().unix_timestamp_nanos() as i64 / 1_000_000;
+    Ok(ElasticsearchListTasksResponse::from_search_tasks(
+        cancel_response.cancelled_search_tasks,
+        &node_ids,
+        now_millis,
+        true,
+    ))
+}
+
+async fn es_compat_sql(
+    sql_query_params: SqlQueryParams,
+    sql_request: SqlRequest,
+    tenant_id_opt: Option<String>,
+    search_service: Arc<dyn SearchService>,
+) -> warp::reply::Response {
+    let result = sql_endpoint(sql_request, tenant_id_opt, &*search_service).await;
+
+    match (result, sql_query_params.format) {
+        (Ok(sql_response), SqlResponseFormat::Csv) => into_csv_response(&sql_response),
+        (result, _) => make_elastic_api_response(
+            result.map_err(ElasticsearchError::from),
+            BodyFormat::default(),
+        )
+        .into_response(),
+    }
 }
 
 async fn es_compat_stats(
@@ -658,6 +1526,111 @@ async fn es_compat_resolve_index(
     })
 }
 
+/// Applies the alias actions atomically: either all of them succeed or none of them is applied.
+async fn update_index_aliases(
+    actions: Vec<AliasAction>,
+    mut metastore: MetastoreServiceClient,
+) -> Result<ElasticsearchAcknowledgedResponse, ElasticsearchError> {
+    if actions.is_empty() {
+        return Err(ElasticsearchError::new(
+            StatusCode::BAD_REQUEST,
+            "[actions] must contain at least one action".to_string(),
+            Some(ElasticException::ActionRequestValidation),
+        ));
+    }
+    let alias_actions: Vec<IndexAliasAction> = actions
+        .into_iter()
+        .map(AliasAction::into_index_alias_action)
+        .collect::<Result<_, _>>()?;
+    let update_index_aliases_request =
+        UpdateIndexAliasesRequest::try_from_alias_actions(&alias_actions)?;
+
+    match metastore
+        .update_index_aliases(update_index_aliases_request)
+        .await
+    {
+        Ok(_) => Ok(ElasticsearchAcknowledgedResponse { acknowledged: true }),
+        Err(MetastoreError::NotFound(EntityKind::IndexAlias { alias_id })) => {
+            Err(ElasticsearchError::new(
+                StatusCode::NOT_FOUND,
+                format!("aliases [{alias_id}] missing"),
+                Some(ElasticException::AliasesNotFound),
+            ))
+        }
+        Err(metastore_error) => Err(metastore_error.into()),
+    }
+}
+
+async fn es_compat_update_aliases(
+    update_aliases_body: UpdateAliasesBody,
+    metastore: MetastoreServiceClient,
+) -> Result<ElasticsearchAcknowledgedResponse, ElasticsearchError> {
+    update_index_aliases(update_aliases_body.actions, metastore).await
+}
+
+async fn es_compat_put_alias(
+    index_id: String,
+    alias_id: String,
+    put_alias_body: PutAliasBody,
+    metastore: MetastoreServiceClient,
+) -> Result<ElasticsearchAcknowledgedResponse, ElasticsearchError> {
+    let add_action = AliasAction::Add(AddAliasAction {
+        index: index_id,
+        alias: alias_id,
+        filter: put_alias_body.filter,
+        is_write_index: put_alias_body.is_write_index,
+    });
+    update_index_aliases(vec![add_action], metastore).await
+}
+
+async fn es_compat_delete_alias(
+    index_ids: Vec<String>,
+    alias_ids: Vec<String>,
+    metastore: MetastoreServiceClient,
+) -> Result<ElasticsearchAcknowledgedResponse, ElasticsearchError> {
+    let remove_actions: Vec<AliasAction> = index_ids
+        .iter()
+        .cartesian_product(&alias_ids)
+        .map(|(index_id, alias_id)| {
+            AliasAction::Remove(RemoveAliasAction {
+                index: index_id.clone(),
+                alias: alias_id.clone(),
+            })
+        })
+        .collect();
+    update_index_aliases(remove_actions, metastore).await
+}
+
+async fn es_compat_get_aliases(
+    index_id_patterns: Vec<String>,
+    alias_id_patterns: Vec<String>,
+    mut metastore: MetastoreServiceClient,
+) -> Result<ElasticsearchAliasesResponse, ElasticsearchError> {
+    let index_aliases = metastore
+        .list_index_aliases(ListIndexAliasesRequest::default())
+        .await?
+        .deserialize_index_aliases()?;
+    let aliases_response =
+        convert_to_es_aliases_response(index_aliases, &index_id_patterns, &alias_id_patterns)?;
+
+    if !alias_id_patterns.is_empty() && aliases_response.indices.is_empty() {
+        return Err(ElasticsearchError::new(
+            StatusCode::NOT_FOUND,
+            format!("alias [{}] missing", alias_id_patterns.join(",")),
+            Some(ElasticException::AliasesNotFound),
+        ));
+    }
+    Ok(aliases_response)
+}
+
+async fn es_compat_mapping(
+    index_id_patterns: Vec<String>,
+    mut metastore: MetastoreServiceClient,
+) -> Result<ElasticsearchMappingsResponse, ElasticsearchError> {
+    let indexes_metadata = resolve_index_patterns(&index_id_patterns, &mut metastore).await?;
+    Ok(convert_to_es_mappings_response(indexes_metadata))
+}
+
 async fn es_compat_index_field_capabilities(
     index_id_patterns: Vec<String>,
     search_params: FieldCapabilityQueryParams,
@@ -766,15 +1739,26 @@ fn convert_hit(
     append_shard_doc: bool,
     _source_excludes: &Option<Vec<String>>,
     _source_includes: &Option<Vec<String>>,
+    collapse_opt: Option<&CollapseBody>,
 ) -> ElasticHit {
-    let mut json: serde_json::Value = serde_json::from_str(&hit.json).unwrap_or(json!({}));
-    filter_source(&mut json, _source_excludes, _source_includes);
-    let source =
+    // Like Elasticsearch, we omit the `_source` key altogether when the source is disabled.
+    let source = if hit.source_disabled {
+        Source::default()
+    } else {
+        let mut json: serde_json::Value = serde_json::from_str(&hit.json).unwrap_or(json!({}));
+        filter_source(&mut json, _source_excludes, _source_includes);
         Source::from_string(serde_json::to_string(&json).unwrap_or_else(|_| "{}".to_string()))
-            .unwrap_or_else(|_| Source::from_string("{}".to_string()).unwrap());
+            .unwrap_or_else(|_| Source::from_string("{}".to_string()).unwrap())
+    };
 
     let mut sort = Vec::new();
+    let mut fields = serde_json::Map::new();
     if let Some(partial_hit) = hit.partial_hit {
+        if let Some(collapse) = collapse_opt
+            && let Some(collapse_key) = &partial_hit.collapse_key
+        {
+            fields.insert(collapse.field.clone(), json!([collapse_key]));
+        }
         if let Some(sort_value) = partial_hit.sort_value {
             sort.push(sort_value.into_json());
         }
@@ -788,16 +1772,55 @@ fn convert_hit(
         }
     }
 
+    let highlight = hit
+        .snippet
+        .and_then(|snippet_json| {
+            serde_json::from_str::<HashMap<String, Vec<String>>>(&snippet_json).ok()
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(_, snippets)| !snippets.is_empty())
+        .collect();
+
+    let inner_hits = hit
+        .inner_hits
+        .map(|inner_hits| {
+            let inner_hits_name = collapse_opt
+                .and_then(|collapse| collapse.inner_hits.as_ref())
+                .and_then(|inner_hits| inner_hits.name.clone())
+                .unwrap_or_else(|| DEFAULT_INNER_HITS_NAME.to_string());
+            let hits = inner_hits
+                .hits
+                .into_iter()
+                .map(|inner_hit| {
+                    convert_hit(inner_hit, false, _source_excludes, _source_includes, None)
+                })
+                .collect();
+            let inner_hits_result = InnerHitsResult {
+                hits: HitsMetadata {
+                    total: Some(TotalHits {
+                        value: inner_hits.num_hits,
+                        relation: TotalHitsRelation::Equal,
+                    }),
+                    max_score: None,
+                    hits,
+                },
+            };
+            (inner_hits_name, inner_hits_result)
+        })
+        .into_iter()
+        .collect();
+
     ElasticHit {
-        fields: Default::default(),
+        fields: fields.into_iter().collect(),
         explanation: None,
         index: hit.index_id,
         id: "".to_string(),
         score: None,
         nested: None,
         source,
-        highlight: Default::default(),
-        inner_hits: Default::default(),
+        highlight,
+        inner_hits,
         matched_queries: Vec::default(),
         sort,
     }
@@ -858,36 +1881,42 @@ async fn es_compat_index_multi_search(
         if let Some(extra_filters) = &multi_search_params.extra_filters {
             search_query_params.extra_filters = Some(extra_filters.to_vec());
         }
-        let es_request =
+        search_query_params
+            .tenant_id
+            .clone_from(&multi_search_params.tenant_id);
+        let collapse_opt = search_body.collapse.clone();
+        let (search_request, append_shard_doc) =
             build_request_for_es_api(index_ids_patterns, search_query_params, search_body)?;
-        search_requests.push(es_request);
+        search_requests.push((search_request, append_shard_doc, collapse_opt));
     }
 
     // TODO: forced to do weird referencing to work around https://github.com/rust-lang/rust/issues/100905
     // otherwise append_shard_doc is captured by ref, and we get lifetime issues
-    let futures = search_requests
-        .into_iter()
-        .map(|(search_request, append_shard_doc)| {
-            let search_service = &search_service;
-            let _source_excludes = multi_search_params._source_excludes.clone();
-            let _source_includes = multi_search_params._source_includes.clone();
-            async move {
-                let start_instant = Instant::now();
-                let search_response: SearchResponse =
-                    search_service.clone().root_search(search_request).await?;
-                let elapsed = start_instant.elapsed();
-                let mut search_response_rest: ElasticsearchResponse =
-                    convert_to_es_search_response(
-                        search_response,
-                        append_shard_doc,
-                        _source_excludes,
-                        _source_includes,
-                        true, //< allow_partial_results. Set to true to match ES's behavior.
-                    )?;
-                search_response_rest.took = elapsed.as_millis() as u32;
-                Ok::<_, ElasticsearchError>(search_response_rest)
-            }
-        });
+    let futures =
+        search_requests
+            .into_iter()
+            .map(|(search_request, append_shard_doc, collapse_opt)| {
+                let search_service = &search_service;
+                let _source_excludes = multi_search_params._source_excludes.clone();
+                let _source_includes = multi_search_params._source_includes.clone();
+                async move {
+                    let start_instant = Instant::now();
+                    let search_response: SearchResponse =
+                        search_service.clone().root_search(search_request).await?;
+                    let elapsed = start_instant.elapsed();
+                    let mut search_response_rest: ElasticsearchResponse =
+                        convert_to_es_search_response(
+                            search_response,
+                            append_shard_doc,
+                            _source_excludes,
+                            _source_includes,
+                            collapse_opt.as_ref(),
+                            true, //< allow_partial_results. Set to true to match ES's behavior.
+                        )?;
+                    search_response_rest.took = elapsed.as_millis() as u32;
+                    Ok::<_, ElasticsearchError>(search_response_rest)
+                }
+            });
     let max_concurrent_searches =
         multi_search_params.max_concurrent_searches.unwrap_or(10) as usize;
     let search_responses = futures::stream::iter(futures)
@@ -931,8 +1960,14 @@ async fn es_scroll(
     // However, passing that parameter is cumbersome, so we cut some corner and forbid the
     // use of scroll requests in combination with allow_partial_results set to false.
     let allow_failed_splits = true;
-    let mut search_response_rest: ElasticsearchResponse =
-        convert_to_es_search_response(search_response, false, None, None, allow_failed_splits)?;
+    let mut search_response_rest: ElasticsearchResponse = convert_to_es_search_response(
+        search_response,
+        false,
+        None,
+        None,
+        None,
+        allow_failed_splits,
+    )?;
     search_response_rest.took = start_instant.elapsed().as_millis() as u32;
     Ok(search_response_rest)
 }
@@ -998,6 +2033,7 @@ fn convert_to_es_search_response(
     append_shard_doc: bool,
     _source_excludes: Option<Vec<String>>,
     _source_includes: Option<Vec<String>>,
+    collapse_opt: Option<&CollapseBody>,
     allow_partial_results: bool,
 ) -> Result<ElasticsearchResponse, ElasticsearchError> {
     if (!allow_partial_results || resp.num_successful_splits == 0)
@@ -1008,7 +2044,15 @@ fn convert_to_es_search_response(
     let hits: Vec<ElasticHit> = resp
         .hits
         .into_iter()
-        .map(|hit| convert_hit(hit, append_shard_doc, &_source_excludes, &_source_includes))
+        .map(|hit| {
+            convert_hit(
+                hit,
+                append_shard_doc,
+                &_source_excludes,
+                &_source_includes,
+                collapse_opt,
+            )
+        })
         .collect();
     let aggregations: Option<AggregationResults> =
         if let Some(aggregation_postcard) = resp.aggregation_postcard {
@@ -1028,7 +2072,7 @@ fn convert_to_es_search_response(
     let num_successful_splits = resp.num_successful_splits as u32;
     let num_total_splits = num_successful_splits + num_failed_splits;
     Ok(ElasticsearchResponse {
-        timed_out: false,
+        timed_out: resp.timed_out,
         hits: HitsMetadata {
             total: Some(TotalHits {
                 value: resp.num_hits,
@@ -1039,6 +2083,7 @@ fn convert_to_es_search_response(
         },
         aggregations,
         scroll_id: resp.scroll_id,
+        profile: resp.profile,
         // There is no concept of shards here, but use this to convey split search failures.
         shards: ShardStatistics {
             total: num_total_splits,
@@ -1255,7 +2300,8 @@ mod tests {
                 failed_splits: vec![split_error.clone()],
                 ..Default::default()
             };
-            convert_to_es_search_response(search_response, false, None, None, false).unwrap_err();
+            convert_to_es_search_response(search_response, false, None, None, None, false)
+                .unwrap_err();
         }
         {
             let search_response = SearchResponse {
@@ -1266,7 +2312,8 @@ mod tests {
             // if we allow partial search results, this should not fail, but we report the presence
             // of failed splits in the fail shard response.
             let es_search_resp =
-                convert_to_es_search_response(search_response, false, None, None, true).unwrap();
+                convert_to_es_search_response(search_response, false, None, None, None, true)
+                    .unwrap();
             assert_eq!(es_search_resp.shards.failed, 1);
         }
         {
@@ -1276,7 +2323,8 @@ mod tests {
             };
             // Event if we allow partial search results, with a fail and no success, we have a
             // failure.
-            convert_to_es_search_response(search_response, false, None, None, true).unwrap_err();
+            convert_to_es_search_response(search_response, false, None, None, None, true)
+                .unwrap_err();
         }
         {
             // Not having any splits (no failure + no success) is not considered a failure.
@@ -1287,6 +2335,7 @@ mod tests {
                     false,
                     None,
                     None,
+                    None,
                     allow_partial,
                 )
                 .unwrap();
@@ -1294,4 +2343,139 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_build_request_for_es_api_with_highlight() {
+        let search_body: SearchBody = serde_json::from_value(json!({
+            "highlight": {
+                "pre_tags": ["<mark>"],
+                "fragment_size": 80,
+                "fields": {"title": {}, "*": {}}
+            }
+        }))
+        .unwrap();
+        let (search_request, _) = build_request_for_es_api(
+            vec!["my-index".to_string()],
+            SearchQueryParams::default(),
+            search_body,
+        )
+        .unwrap();
+        assert_eq!(search_request.snippet_fields, ["*", "title"]);
+        let expected_snippet_options = SnippetOptions {
+            pre_tag: Some("<mark>".to_string()),
+            post_tag: Some("</em>".to_string()),
+            fragment_size: Some(80),
+            number_of_fragments: None,
+            lenient_fields: true,
+        };
+        assert_eq!(
+            search_request.snippet_options,
+            Some(expected_snippet_options)
+        );
+    }
+
+    #[test]
+    fn test_convert_hit_with_highlight() {
+        let hit = quickwit_proto::search::Hit {
+            json: r#"{"title": "beagle"}"#.to_string(),
+            snippet: Some(r#"{"title": ["<em>beagle</em>"], "body": []}"#.to_string()),
+            index_id: "my-index".to_string(),
+            ..Default::default()
+        };
+        let es_hit = convert_hit(hit, false, &None, &None, None);
+        assert_eq!(es_hit.highlight.len(), 1);
+        assert_eq!(es_hit.highlight["title"], ["<em>beagle</em>"]);
+    }
+
+    #[test]
+    fn test_convert_hit_with_source_disabled() {
+        let hit = quickwit_proto::search::Hit {
+            json: String::new(),
+            index_id: "my-index".to_string(),
+            source_disabled: true,
+            ..Default::default()
+        };
+        let es_hit = convert_hit(hit, false, &None, &None, None);
+        let es_hit_json = serde_json::to_value(&es_hit).unwrap();
+        assert!(es_hit_json.get("_source").is_none());
+        assert_eq!(es_hit_json["_index"], "my-index");
+    }
+
+    #[test]
+    fn test_es_search_with_profile() {
+        let search_body: SearchBody = serde_json::from_value(json!({"profile": true})).unwrap();
+        let (search_request, _) = build_request_for_es_api(
+            vec!["my-index".to_string()],
+            SearchQueryParams::default(),
+            search_body,
+        )
+        .unwrap();
+        assert!(search_request.profile);
+
+        let search_response = SearchResponse {
+            profile: Some(quickwit_proto::search::SearchProfile {
+                num_targeted_splits: 3,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let es_search_response =
+            convert_to_es_search_response(search_response, false, None, None, None, true).unwrap();
+        let es_search_response_json = serde_json::to_value(&es_search_response).unwrap();
+        assert_eq!(es_search_response_json["profile"]["num_targeted_splits"], 3);
+    }
+
+    #[test]
+    fn test_build_request_for_es_api_with_collapse() {
+        let search_body: SearchBody = serde_json::from_value(json!({
+            "collapse": {
+                "field": "host",
+                "inner_hits": {"name": "latest"}
+            }
+        }))
+        .unwrap();
+        let (search_request, _) = build_request_for_es_api(
+            vec!["my-index".to_string()],
+            SearchQueryParams::default(),
+            search_body,
+        )
+        .unwrap();
+        let expected_collapse = CollapseRequest {
+            field: "host".to_string(),
+            inner_hits_size: 3,
+            group_size: 0,
+        };
+        assert_eq!(search_request.collapse, Some(expected_collapse));
+    }
+
+    #[test]
+    fn test_convert_hit_with_collapse() {
+        let collapse: CollapseBody = serde_json::from_value(json!({
+            "field": "host",
+            "inner_hits": {"name": "latest", "size": 1}
+        }))
+        .unwrap();
+        let hit = quickwit_proto::search::Hit {
+            json: r#"{"host": "host-1", "message": "last"}"#.to_string(),
+            partial_hit: Some(PartialHit {
+                collapse_key: Some("host-1".to_string()),
+                ..Default::default()
+            }),
+            index_id: "my-index".to_string(),
+            inner_hits: Some(quickwit_proto::search::InnerHits {
+                num_hits: 2,
+                hits: vec![quickwit_proto::search::Hit {
+                    json: r#"{"host": "host-1", "message": "last"}"#.to_string(),
+                    index_id: "my-index".to_string(),
+                    ..Default::default()
+                }],
+            }),
+            ..Default::default()
+        };
+        let es_hit = convert_hit(hit, false, &None, &None, Some(&collapse));
+        assert_eq!(es_hit.fields["host"], json!(["host-1"]));
+        let inner_hits = &es_hit.inner_hits["latest"].hits;
+        assert_eq!(inner_hits.total.as_ref().unwrap().value, 2);
+        assert_eq!(inner_hits.hits.len(), 1);
+    }
 }
@@ -0,0 +1,372 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use quickwit_proto::search::ExportedDoc;
+use quickwit_search::{ExportPage, SearchError};
+use serde_json::{Map as JsonObject, Value as JsonValue};
+
+use super::ExportFormat;
+use super::parquet::ParquetWriter;
+use crate::sql_api::{lookup_field, push_csv_record};
+
+/// Name of the field, or column, holding the cursor of the exported documents.
+const CURSOR_FIELD_NAME: &str = "_cursor";
+
+/// Number of rows of the Parquet row groups. Rows are buffered until a row group is complete.
+const PARQUET_ROW_GROUP_NUM_ROWS: usize = 10_000;
+
+/// Maximum number of rows of a Parquet file. A Parquet file cannot be read without its footer,
+/// so an export is cut into self-contained files rather than streamed as a single file: an
+/// interrupted export only loses the file being received.
+const PARQUET_FILE_MAX_NUM_ROWS: usize = 100_000;
+
+/// Key of the Parquet metadata holding the cursor resuming the export after a full file.
+pub(crate) const PARQUET_CURSOR_METADATA_KEY: &str = "quickwit.export.cursor";
+
+/// Encodes the pages of documents of an export into chunks of the response body.
+pub(crate) struct ExportEncoder {
+    format: ExportFormat,
+    fields: Vec<String>,
+    include_cursor: bool,
+    parquet_writer_opt: Option<ParquetWriter>,
+    pending_rows: Vec<Vec<Option<String>>>,
+    parquet_file_max_num_rows: usize,
+    num_parquet_rows: usize,
+    last_parquet_cursor_opt: Option<String>,
+}
+
+impl ExportEncoder {
+    pub fn new(
+        format: ExportFormat,
+        fields: Vec<String>,
+        include_cursor: bool,
+    ) -> Result<Self, SearchError> {
+        if fields.is_empty() && format != ExportFormat::Ndjson {
+            return Err(SearchError::InvalidArgument(format!(
+                "the fields to export must be set with the `{}` format",
+                format.as_str()
+            )));
+        }
+        let parquet_writer_opt = if format == ExportFormat::Parquet {
+            let mut column_names = fields.clone();
+            if include_cursor {
+                column_names.push(CURSOR_FIELD_NAME.to_string());
+            }
+            let parquet_writer = ParquetWriter::new(&column_names).map_err(|parquet_error| {
+                SearchError::Internal(format!("failed to create parquet writer: {parquet_error}"))
+            })?;
+            Some(parquet_writer)
+        } else {
+            None
+        };
+        Ok(ExportEncoder {
+            format,
+            fields,
+            include_cursor,
+            parquet_writer_opt,
+            pending_rows: Vec::new(),
+            parquet_file_max_num_rows: PARQUET_FILE_MAX_NUM_ROWS,
+            num_parquet_rows: 0,
+            last_parquet_cursor_opt: None,
+        })
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self.format {
+            ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+
+    /// Returns the beginning of the body, sent before the first document.
+    pub fn header(&mut self) -> Vec<u8> {
+        match self.format {
+            ExportFormat::Ndjson => Vec::new(),
+            ExportFormat::Csv => {
+                let mut csv = String::new();
+                let header = self
+                    .fields
+                    .iter()
+                    .cloned()
+                    .chain(self.include_cursor.then(|| CURSOR_FIELD_NAME.to_string()));
+                push_csv_record(&mut csv, header);
+                csv.into_bytes()
+            }
+            ExportFormat::Parquet => self.parquet_writer().take_written_bytes(),
+        }
+    }
+
+    /// Returns whether the body cannot hold more documents. This only happens with the Parquet
+    /// format, once the file reaches its maximum number of rows.
+    pub fn is_full(&self) -> bool {
+        self.num_parquet_rows == self.parquet_file_max_num_rows
+    }
+
+    /// Encodes the documents of the page, up to the capacity of the body.
+    pub fn encode_page(&mut self, page: &ExportPage) -> Result<Vec<u8>, SearchError> {
+        let mut chunk = Vec::new();
+
+        for doc in &page.docs {
+            if self.is_full() {
+                break;
+            }
+            let cursor_opt = self
+                .include_cursor
+                .then(|| page.doc_cursor(doc).to_string());
+
+            match self.format {
+                ExportFormat::Ndjson => {
+                    if let Some(cursor) = cursor_opt {
+                        let mut doc_json: JsonObject<String, JsonValue> =
+                            serde_json::from_str(&doc.json)?;
+                        doc_json.insert(CURSOR_FIELD_NAME.to_string(), cursor.into());
+                        serde_json::to_writer(&mut chunk, &doc_json)?;
+                    } else {
+                        chunk.extend_from_slice(doc.json.as_bytes());
+                    }
+                    chunk.push(b'\n');
+                }
+                ExportFormat::Csv => {
+                    let mut csv = String::new();
+                    let record = self
+                        .row(doc, cursor_opt)?
+                        .into_iter()
+                        .map(Option::unwrap_or_default);
+                    push_csv_record(&mut csv, record);
+                    chunk.extend_from_slice(csv.as_bytes());
+                }
+                ExportFormat::Parquet => {
+                    let row = self.row(doc, cursor_opt)?;
+                    self.pending_rows.push(row);
+                    self.num_parquet_rows += 1;
+                    self.last_parquet_cursor_opt = Some(page.doc_cursor(doc).to_string());
+
+                    if self.pending_rows.len() == PARQUET_ROW_GROUP_NUM_ROWS {
+                        chunk.extend(self.flush_pending_rows()?);
+                    }
+                }
+            }
+        }
+        Ok(chunk)
+    }
+
+    /// Returns the end of the body, sent after the last document.
+    ///
+    /// The footer of a full Parquet file records the cursor of its last row, from which the
+    /// export must be resumed to get the next file.
+    pub fn finish(&mut self) -> Result<Vec<u8>, SearchError> {
+        match self.format {
+            ExportFormat::Ndjson | ExportFormat::Csv => Ok(Vec::new()),
+            ExportFormat::Parquet => {
+                let mut chunk = self.flush_pending_rows()?;
+                let mut key_value_metadata = Vec::new();
+
+                if self.is_full()
+                    && let Some(last_cursor) = self.last_parquet_cursor_opt.take()
+                {
+                    key_value_metadata.push((PARQUET_CURSOR_METADATA_KEY.to_string(), last_cursor));
+                }
+                let parquet_writer = self
+                    .parquet_writer_opt
+                    .take()
+                    .expect("the parquet writer should be set with the parquet format");
+                let footer = parquet_writer
+                    .finish(key_value_metadata)
+                    .map_err(parquet_write_error)?;
+                chunk.extend(footer);
+                Ok(chunk)
+            }
+        }
+    }
+
+    fn row(
+        &self,
+        doc: &ExportedDoc,
+        cursor_opt: Option<String>,
+    ) -> Result<Vec<Option<String>>, SearchError> {
+        let doc_json: JsonValue = serde_json::from_str(&doc.json)?;
+        let mut row: Vec<Option<String>> = self
+            .fields
+            .iter()
+            .map(|field| match lookup_field(&doc_json, field)? {
+                JsonValue::Null => None,
+                JsonValue::String(text) => Some(text.clone()),
+                other => Some(other.to_string()),
+            })
+            .collect();
+        if self.include_cursor {
+            row.push(cursor_opt);
+        }
+        Ok(row)
+    }
+
+    fn flush_pending_rows(&mut self) -> Result<Vec<u8>, SearchError> {
+        if self.pending_rows.is_empty() {
+            return Ok(Vec::new());
+        }
+        let pending_rows = std::mem::take(&mut self.pending_rows);
+        self.parquet_writer()
+            .write_row_group(&pending_rows)
+            .map_err(parquet_write_error)
+    }
+
+    fn parquet_writer(&mut self) -> &mut ParquetWriter {
+        self.parquet_writer_opt
+            .as_mut()
+            .expect("the parquet writer should be set with the parquet format")
+    }
+}
+
+fn parquet_write_error(parquet_error: parquet::errors::ParquetError) -> SearchError {
+    SearchError::Internal(format!("failed to write parquet file: {parquet_error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bytes::Bytes;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::Field;
+    use serde_json::json;
+
+    use super::*;
+
+    fn page() -> ExportPage {
+        let docs = [
+            json!({"level": "INFO", "message": "hello, world", "attributes": {"status": 200}}),
+            json!({"level": "WARN"}),
+        ];
+        ExportPage {
+            pit_id: "pit".to_string(),
+            split_id: "split-1".to_string(),
+            docs: docs
+                .iter()
+                .enumerate()
+                .map(|(doc_id, doc)| ExportedDoc {
+                    json: doc.to_string(),
+                    segment_ord: 0,
+                    doc_id: doc_id as u32,
+                })
+                .collect(),
+        }
+    }
+
+    /// Reads a Parquet file, returning the values of its rows and its key-value metadata.
+    fn read_parquet(parquet: Vec<u8>) -> (Vec<Vec<Field>>, HashMap<String, String>) {
+        let reader = SerializedFileReader::new(Bytes::from(parquet)).unwrap();
+        let key_value_metadata = reader
+            .metadata()
+            .file_metadata()
+            .key_value_metadata()
+            .into_iter()
+            .flatten()
+            .map(|key_value| (key_value.key.clone(), key_value.value.clone().unwrap()))
+            .collect();
+        let rows = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| {
+                row.unwrap()
+                    .get_column_iter()
+                    .map(|(_, field)| field.clone())
+                    .collect()
+            })
+            .collect();
+        (rows, key_value_metadata)
+    }
+
+    #[test]
+    fn test_export_encoder_ndjson() {
+        let mut encoder = ExportEncoder::new(ExportFormat::Ndjson, Vec::new(), true).unwrap();
+        assert!(encoder.header().is_empty());
+        let chunk = String::from_utf8(encoder.encode_page(&page()).unwrap()).unwrap();
+        let lines: Vec<JsonValue> = chunk
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["message"], "hello, world");
+        assert_eq!(lines[0]["_cursor"], "pit:split-1:0:0");
+        assert_eq!(lines[1]["_cursor"], "pit:split-1:0:1");
+        assert!(encoder.finish().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_export_encoder_csv() {
+        let fields = vec!["message".to_string(), "attributes.status".to_string()];
+        let mut encoder = ExportEncoder::new(ExportFormat::Csv, fields, false).unwrap();
+        let mut csv = encoder.header();
+        csv.extend(encoder.encode_page(&page()).unwrap());
+        csv.extend(encoder.finish().unwrap());
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "message,attributes.status\r\n\"hello, world\",200\r\n,\r\n"
+        );
+    }
+
+    #[test]
+    fn test_export_encoder_parquet() {
+        let fields = vec!["level".to_string()];
+        let mut encoder = ExportEncoder::new(ExportFormat::Parquet, fields, true).unwrap();
+        let mut parquet = encoder.header();
+        // Rows are buffered until the row group is complete.
+        assert!(encoder.encode_page(&page()).unwrap().is_empty());
+        assert!(!encoder.is_full());
+        parquet.extend(encoder.finish().unwrap());
+
+        let (rows, key_value_metadata) = read_parquet(parquet);
+        let string_field = |value: &str| Field::Str(value.to_string());
+        assert_eq!(
+            rows,
+            [
+                [string_field("INFO"), string_field("pit:split-1:0:0")],
+                [string_field("WARN"), string_field("pit:split-1:0:1")],
+            ]
+        );
+        assert!(!key_value_metadata.contains_key(PARQUET_CURSOR_METADATA_KEY));
+    }
+
+    #[test]
+    fn test_export_encoder_parquet_full_file() {
+        let fields = vec!["level".to_string()];
+        let mut encoder = ExportEncoder::new(ExportFormat::Parquet, fields, false).unwrap();
+        encoder.parquet_file_max_num_rows = 3;
+        let mut parquet = encoder.header();
+        parquet.extend(encoder.encode_page(&page()).unwrap());
+        assert!(!encoder.is_full());
+
+        let mut next_page = page();
+        next_page.split_id = "split-2".to_string();
+        parquet.extend(encoder.encode_page(&next_page).unwrap());
+        assert!(encoder.is_full());
+        parquet.extend(encoder.finish().unwrap());
+
+        let (rows, key_value_metadata) = read_parquet(parquet);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            key_value_metadata[PARQUET_CURSOR_METADATA_KEY],
+            "pit:split-2:0:0"
+        );
+    }
+
+    #[test]
+    fn test_export_encoder_requires_fields() {
+        for format in [ExportFormat::Csv, ExportFormat::Parquet] {
+            let error = ExportEncoder::new(format, Vec::new(), false).err().unwrap();
+            assert!(matches!(error, SearchError::InvalidArgument(_)));
+        }
+    }
+}
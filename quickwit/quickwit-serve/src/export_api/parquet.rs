@@ -0,0 +1,177 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Streaming Parquet writer.
+//!
+//! All the columns are optional UTF-8 strings. The row groups are written as soon as they are
+//! complete, and the bytes written so far are handed over to the caller after each row group, so
+//! that only the row group being written is buffered.
+
+use std::sync::Arc;
+
+use parquet::basic::{LogicalType, Repetition, Type as PhysicalType};
+use parquet::data_type::{ByteArray, ByteArrayType};
+use parquet::errors::ParquetError;
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::types::Type;
+
+const CREATED_BY: &str = concat!("quickwit version ", env!("CARGO_PKG_VERSION"));
+
+pub(crate) struct ParquetWriter {
+    file_writer: SerializedFileWriter<Vec<u8>>,
+}
+
+impl ParquetWriter {
+    pub fn new(column_names: &[String]) -> Result<Self, ParquetError> {
+        let fields = column_names
+            .iter()
+            .map(|column_name| {
+                let field = Type::primitive_type_builder(column_name, PhysicalType::BYTE_ARRAY)
+                    .with_repetition(Repetition::OPTIONAL)
+                    .with_logical_type(Some(LogicalType::String))
+                    .build()?;
+                Ok(Arc::new(field))
+            })
+            .collect::<Result<Vec<_>, ParquetError>>()?;
+        let schema = Type::group_type_builder("schema")
+            .with_fields(fields)
+            .build()?;
+        let properties = WriterProperties::builder()
+            .set_created_by(CREATED_BY.to_string())
+            .build();
+        let file_writer =
+            SerializedFileWriter::new(Vec::new(), Arc::new(schema), Arc::new(properties))?;
+        Ok(ParquetWriter { file_writer })
+    }
+
+    /// Returns the bytes of the file written since the previous call.
+    pub fn take_written_bytes(&mut self) -> Vec<u8> {
+        std::mem::take(self.file_writer.inner_mut())
+    }
+
+    /// Writes a row group and returns the bytes of the file written since the previous call.
+    pub fn write_row_group(
+        &mut self,
+        rows: &[Vec<Option<String>>],
+    ) -> Result<Vec<u8>, ParquetError> {
+        let mut row_group_writer = self.file_writer.next_row_group()?;
+        let mut column_idx = 0;
+
+        while let Some(mut column_writer) = row_group_writer.next_column()? {
+            let mut values: Vec<ByteArray> = Vec::with_capacity(rows.len());
+            let mut definition_levels: Vec<i16> = Vec::with_capacity(rows.len());
+
+            for row in rows {
+                if let Some(value) = &row[column_idx] {
+                    values.push(ByteArray::from(value.as_str()));
+                    definition_levels.push(1);
+                } else {
+                    definition_levels.push(0);
+                }
+            }
+            column_writer.typed::<ByteArrayType>().write_batch(
+                &values,
+                Some(&definition_levels),
+                None,
+            )?;
+            column_writer.close()?;
+            column_idx += 1;
+        }
+        row_group_writer.close()?;
+        Ok(self.take_written_bytes())
+    }
+
+    /// Writes the footer of the file, with the given key-value metadata, and returns the last
+    /// bytes of the file.
+    pub fn finish(
+        mut self,
+        key_value_metadata: Vec<(String, String)>,
+    ) -> Result<Vec<u8>, ParquetError> {
+        for (key, value) in key_value_metadata {
+            self.file_writer
+                .append_key_value_metadata(KeyValue::new(key, value));
+        }
+        self.file_writer.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::Field;
+
+    use super::*;
+
+    #[test]
+    fn test_parquet_writer_read_back() {
+        let column_names = vec!["level".to_string(), "attributes.status".to_string()];
+        let mut parquet_writer = ParquetWriter::new(&column_names).unwrap();
+        let mut parquet = parquet_writer.take_written_bytes();
+
+        let row_groups = [
+            vec![
+                vec![Some("INFO".to_string()), Some("200".to_string())],
+                vec![Some("WARN".to_string()), None],
+            ],
+            vec![vec![None, Some("500".to_string())]],
+        ];
+        for rows in &row_groups {
+            parquet.extend(parquet_writer.write_row_group(rows).unwrap());
+        }
+        let key_value_metadata = vec![("quickwit.test".to_string(), "value".to_string())];
+        parquet.extend(parquet_writer.finish(key_value_metadata).unwrap());
+
+        let reader = SerializedFileReader::new(Bytes::from(parquet)).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.num_row_groups(), 2);
+
+        let file_metadata = metadata.file_metadata();
+        assert_eq!(file_metadata.num_rows(), 3);
+        let key_value = &file_metadata.key_value_metadata().unwrap()[0];
+        assert_eq!(key_value.key, "quickwit.test");
+        assert_eq!(key_value.value.as_deref(), Some("value"));
+
+        let rows: Vec<Vec<(String, Field)>> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| {
+                row.unwrap()
+                    .get_column_iter()
+                    .map(|(column_name, field)| (column_name.clone(), field.clone()))
+                    .collect()
+            })
+            .collect();
+        let string_field = |value: &str| Field::Str(value.to_string());
+        assert_eq!(
+            rows,
+            [
+                vec![
+                    ("level".to_string(), string_field("INFO")),
+                    ("attributes.status".to_string(), string_field("200")),
+                ],
+                vec![
+                    ("level".to_string(), string_field("WARN")),
+                    ("attributes.status".to_string(), Field::Null),
+                ],
+                vec![
+                    ("level".to_string(), Field::Null),
+                    ("attributes.status".to_string(), string_field("500")),
+                ],
+            ]
+        );
+    }
+}
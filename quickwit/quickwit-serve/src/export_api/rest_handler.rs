@@ -0,0 +1,451 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::{StreamExt, TryStreamExt};
+use http_body::Frame;
+use http_body_util::StreamBody;
+use quickwit_proto::search::{SearchRequest, SourceFilter};
+use quickwit_query::query_ast::query_ast_from_user_text;
+use quickwit_search::{ExportCursor, ExportRequest, ExportStream, SearchError, SearchService};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{Instrument, error, info};
+use warp::hyper::header::CONTENT_TYPE;
+use warp::hyper::http::HeaderValue;
+use warp::{Filter, Rejection, Reply};
+
+use super::encoder::ExportEncoder;
+use crate::rest_api_response::into_rest_api_response;
+use crate::search_api::{extract_index_id_patterns, tenant_id_header};
+use crate::{BodyFormat, with_arg};
+
+#[derive(utoipa::OpenApi)]
+#[openapi(
+    paths(export_handler),
+    components(schemas(ExportRequestBody, ExportFormat))
+)]
+pub struct ExportApi;
+
+/// Body of an export request.
+#[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ExportRequestBody {
+    /// Query text. The query language is that of tantivy. Defaults to `*`.
+    #[serde(default = "default_query")]
+    pub query: String,
+    /// The fields to search on.
+    #[serde(default)]
+    pub search_fields: Option<Vec<String>>,
+    /// If set, restricts the export to documents with a `timestamp >= start_timestamp`.
+    #[serde(default)]
+    pub start_timestamp: Option<i64>,
+    /// If set, restricts the export to documents with a `timestamp < end_timestamp`.
+    #[serde(default)]
+    pub end_timestamp: Option<i64>,
+    /// Fields to export. Required with the `csv` and `parquet` formats, which have one column
+    /// per field. With the `ndjson` format, restricts the fields of the exported documents.
+    #[serde(default)]
+    pub fields: Vec<String>,
+    /// Cursor of the last document received, to resume an interrupted export after it.
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// Adds the cursor of each document to the export, as a `_cursor` field or column.
+    #[serde(default)]
+    pub include_cursor: bool,
+}
+
+fn default_query() -> String {
+    "*".to_string()
+}
+
+/// Output format of the export API.
+#[derive(Debug, Default, Clone, Copy, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ExportFormat {
+    #[default]
+    Ndjson,
+    Csv,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ExportQueryParams {
+    /// The output format: `ndjson` (default), `csv`, or `parquet`.
+    #[serde(default)]
+    format: ExportFormat,
+}
+
+/// Plans the export and returns its stream of pages of documents, along with the encoder of the
+/// response body.
+async fn export_endpoint(
+    index_id_patterns: Vec<String>,
+    format: ExportFormat,
+    export_request_body: ExportRequestBody,
+    tenant_id_opt: Option<String>,
+    search_service: &dyn SearchService,
+) -> Result<(ExportStream, ExportEncoder), SearchError> {
+    let export_encoder = ExportEncoder::new(
+        format,
+        export_request_body.fields.clone(),
+        export_request_body.include_cursor,
+    )?;
+    let cursor_opt = export_request_body
+        .cursor
+        .as_deref()
+        .map(ExportCursor::from_str)
+        .transpose()?;
+    let query_ast = query_ast_from_user_text(
+        &export_request_body.query,
+        export_request_body.search_fields,
+    );
+    let source_filter_opt = if export_request_body.fields.is_empty() {
+        None
+    } else {
+        Some(SourceFilter {
+            includes: export_request_body.fields,
+            excludes: Vec::new(),
+            disabled: false,
+        })
+    };
+    let search_request = SearchRequest {
+        index_id_patterns,
+        query_ast: serde_json::to_string(&query_ast)?,
+        start_timestamp: export_request_body.start_timestamp,
+        end_timestamp: export_request_body.end_timestamp,
+        source_filter: source_filter_opt,
+        tenant_id: tenant_id_opt,
+        ..Default::default()
+    };
+    let export_request = ExportRequest {
+        search_request,
+        cursor_opt,
+    };
+    let export_stream = search_service.root_export(export_request).await?;
+    Ok((export_stream, export_encoder))
+}
+
+/// Streams the export as a chunked response.
+///
+/// The pages of documents are encoded and sent one at a time by a background task. The channel
+/// between this task and the response body holds a single chunk, so a slow client slows down the
+/// export rather than making it buffer documents. The export stops early once the body is full,
+/// which only happens with the Parquet format.
+fn into_chunked_response(
+    mut export_stream: ExportStream,
+    mut export_encoder: ExportEncoder,
+) -> warp::reply::Response {
+    let content_type = export_encoder.content_type();
+    let (chunk_tx, chunk_rx) = mpsc::channel::<io::Result<Bytes>>(1);
+
+    let export_future = async move {
+        if !send_chunk(&chunk_tx, export_encoder.header()).await {
+            return;
+        }
+        while let Some(page_result) = export_stream.next().await {
+            let chunk_result = page_result.and_then(|page| export_encoder.encode_page(&page));
+            match chunk_result {
+                Ok(chunk) => {
+                    if !send_chunk(&chunk_tx, chunk).await {
+                        return;
+                    }
+                }
+                Err(search_error) => {
+                    fail_body(&chunk_tx, search_error).await;
+                    return;
+                }
+            }
+            if export_encoder.is_full() {
+                break;
+            }
+        }
+        match export_encoder.finish() {
+            Ok(chunk) => {
+                send_chunk(&chunk_tx, chunk).await;
+            }
+            Err(search_error) => fail_body(&chunk_tx, search_error).await,
+        }
+    };
+    tokio::spawn(export_future.in_current_span());
+
+    let chunk_stream = ReceiverStream::new(chunk_rx).map_ok(Frame::data);
+    let mut response = warp::hyper::Response::new(StreamBody::new(chunk_stream));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+    response.into_response()
+}
+
+/// Aborts the response body rather than terminating it, so that the client can tell the export
+/// is incomplete and resume it.
+async fn fail_body(chunk_tx: &mpsc::Sender<io::Result<Bytes>>, search_error: SearchError) {
+    error!(error=%search_error, "export failed");
+    let _ = chunk_tx
+        .send(Err(io::Error::other(search_error.to_string())))
+        .await;
+}
+
+/// Sends a chunk of the response body. Returns `false` if the client went away.
+async fn send_chunk(chunk_tx: &mpsc::Sender<io::Result<Bytes>>, chunk: Vec<u8>) -> bool {
+    if chunk.is_empty() {
+        return true;
+    }
+    chunk_tx.send(Ok(Bytes::from(chunk))).await.is_ok()
+}
+
+async fn export(
+    index_id_patterns: Vec<String>,
+    export_query_params: ExportQueryParams,
+    export_request_body: ExportRequestBody,
+    tenant_id_opt: Option<String>,
+    search_service: Arc<dyn SearchService>,
+) -> warp::reply::Response {
+    info!(index_id_patterns=?index_id_patterns, query=%export_request_body.query, format=?export_query_params.format, "export");
+    let export_result = export_endpoint(
+        index_id_patterns,
+        export_query_params.format,
+        export_request_body,
+        tenant_id_opt,
+        &*search_service,
+    )
+    .await;
+
+    match export_result {
+        Ok((export_stream, export_encoder)) => into_chunked_response(export_stream, export_encoder),
+        Err(search_error) => {
+            into_rest_api_response::<(), _>(Err(search_error), BodyFormat::default())
+                .into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    tag = "Search",
+    path = "/{index_id}/export",
+    request_body = ExportRequestBody,
+    responses(
+        (status = 200, description = "Streams the documents matching the query.")
+    ),
+    params(
+        ExportQueryParams,
+        ("index_id" = String, Path, description = "The index ID to export documents from."),
+    )
+)]
+/// Export Documents
+///
+/// Streams all the documents matching a query as NDJSON, CSV, or Parquet. The documents are
+/// streamed split by split, in no particular order, and an interrupted export can be resumed
+/// from the cursor of the last document received.
+pub fn export_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!(String / "export")
+        .and_then(extract_index_id_patterns)
+        .and(warp::post())
+        .and(warp::query())
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::body::json())
+        .and(tenant_id_header())
+        .and(with_arg(search_service))
+        .then(export)
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate;
+    use quickwit_proto::search::ExportedDoc;
+    use quickwit_search::{ExportPage, MockSearchService};
+    use serde_json::{Value as JsonValue, json};
+
+    use super::*;
+    use crate::recover_fn;
+
+    fn export_handler_for_test(
+        mock_search_service: MockSearchService,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+        export_handler(Arc::new(mock_search_service)).recover(recover_fn)
+    }
+
+    fn export_page(split_id: &str, docs: &[JsonValue]) -> ExportPage {
+        ExportPage {
+            pit_id: "pit".to_string(),
+            split_id: split_id.to_string(),
+            docs: docs
+                .iter()
+                .enumerate()
+                .map(|(doc_id, doc)| ExportedDoc {
+                    json: doc.to_string(),
+                    segment_ord: 0,
+                    doc_id: doc_id as u32,
+                })
+                .collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_api_ndjson() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_export()
+            .with(predicate::function(|export_request: &ExportRequest| {
+                let search_request = &export_request.search_request;
+                search_request.index_id_patterns == ["logs"]
+                    && search_request.start_timestamp == Some(10)
+                    && search_request.source_filter.is_none()
+                    && search_request.tenant_id.as_deref() == Some("tenant-a")
+                    && export_request.cursor_opt
+                        == Some(ExportCursor {
+                            pit_id: "pit".to_string(),
+                            split_id: "split-1".to_string(),
+                            segment_ord: 0,
+                            doc_id: 3,
+                        })
+            }))
+            .returning(|_| {
+                let pages = vec![
+                    Ok(export_page("split-1", &[json!({"message": "a"})])),
+                    Ok(export_page("split-2", &[json!({"message": "b"})])),
+                ];
+                Ok(futures::stream::iter(pages).boxed())
+            });
+        let export_handler = export_handler_for_test(mock_search_service);
+        let response = warp::test::request()
+            .path("/logs/export")
+            .method("POST")
+            .header("x-quickwit-tenant-id", "tenant-a")
+            .json(&json!({
+                "query": "level:ERROR",
+                "start_timestamp": 10,
+                "cursor": "pit:split-1:0:3",
+                "include_cursor": true,
+            }))
+            .reply(&export_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "application/x-ndjson"
+        );
+        let lines: Vec<JsonValue> = std::str::from_utf8(response.body())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                json!({"message": "a", "_cursor": "pit:split-1:0:0"}),
+                json!({"message": "b", "_cursor": "pit:split-2:0:0"}),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_api_csv() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_export()
+            .with(predicate::function(|export_request: &ExportRequest| {
+                let source_filter = export_request.search_request.source_filter.as_ref();
+                source_filter.unwrap().includes == ["level", "message"]
+            }))
+            .returning(|_| {
+                let pages = vec![Ok(export_page(
+                    "split-1",
+                    &[
+                        json!({"level": "INFO", "message": "hello, world"}),
+                        json!({"message": "no level"}),
+                    ],
+                ))];
+                Ok(futures::stream::iter(pages).boxed())
+            });
+        let export_handler = export_handler_for_test(mock_search_service);
+        let response = warp::test::request()
+            .path("/logs/export?format=csv")
+            .method("POST")
+            .json(&json!({"fields": ["level", "message"]}))
+            .reply(&export_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "text/csv; charset=utf-8"
+        );
+        assert_eq!(
+            response.body(),
+            "level,message\r\nINFO,\"hello, world\"\r\n,no level\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_api_parquet() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_root_export().returning(|_| {
+            let pages = vec![Ok(export_page("split-1", &[json!({"level": "INFO"})]))];
+            Ok(futures::stream::iter(pages).boxed())
+        });
+        let export_handler = export_handler_for_test(mock_search_service);
+        let response = warp::test::request()
+            .path("/logs/export?format=parquet")
+            .method("POST")
+            .json(&json!({"fields": ["level"]}))
+            .reply(&export_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+        assert!(response.body().starts_with(b"PAR1"));
+        assert!(response.body().ends_with(b"PAR1"));
+    }
+
+    #[tokio::test]
+    async fn test_export_api_invalid_requests() {
+        let export_handler = export_handler_for_test(MockSearchService::new());
+        let response = warp::test::request()
+            .path("/logs/export?format=csv")
+            .method("POST")
+            .json(&json!({}))
+            .reply(&export_handler)
+            .await;
+        assert_eq!(response.status(), 400);
+
+        let response = warp::test::request()
+            .path("/logs/export")
+            .method("POST")
+            .json(&json!({"cursor": "not-a-cursor"}))
+            .reply(&export_handler)
+            .await;
+        assert_eq!(response.status(), 400);
+        let response_json: JsonValue = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(
+            response_json["message"],
+            "Invalid argument: invalid export cursor `not-a-cursor`"
+        );
+    }
+}
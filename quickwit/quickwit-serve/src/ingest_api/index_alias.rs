@@ -0,0 +1,208 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, HashSet};
+
+use quickwit_metastore::ListIndexAliasesResponseExt;
+use quickwit_proto::ingest::IngestV2Result;
+use quickwit_proto::ingest::router::{
+    IngestFailureReason, IngestRequestV2, IngestResponseV2, IngestRouterService,
+    IngestRouterServiceClient,
+};
+use quickwit_proto::metastore::{
+    ListIndexAliasesRequest, MetastoreService, MetastoreServiceClient,
+};
+use quickwit_proto::types::IndexId;
+use tracing::warn;
+
+/// Ingests the documents of the request, then sends the subrequests that failed because their
+/// index does not exist to the write index of the alias with the same ID, if any.
+///
+/// Aliases are resolved lazily so that ingesting into an index directly does not cost an extra
+/// metastore round trip.
+pub(crate) async fn ingest_v2_with_index_aliases(
+    ingest_router: &IngestRouterServiceClient,
+    metastore: &MetastoreServiceClient,
+    ingest_request: IngestRequestV2,
+) -> IngestV2Result<IngestResponseV2> {
+    let commit_type = ingest_request.commit_type;
+    // Doc batches are backed by `Bytes`: cloning the subrequests does not copy the documents.
+    let mut subrequests = ingest_request.subrequests.clone();
+    let mut ingest_response = ingest_router.ingest(ingest_request).await?;
+
+    let index_not_found_subrequest_ids: HashSet<u32> = ingest_response
+        .failures
+        .iter()
+        .filter(|failure| failure.reason() == IngestFailureReason::IndexNotFound)
+        .map(|failure| failure.subrequest_id)
+        .collect();
+
+    if index_not_found_subrequest_ids.is_empty() {
+        return Ok(ingest_response);
+    }
+    subrequests
+        .retain(|subrequest| index_not_found_subrequest_ids.contains(&subrequest.subrequest_id));
+
+    let list_index_aliases_request = ListIndexAliasesRequest {
+        alias_ids: subrequests
+            .iter()
+            .map(|subrequest| subrequest.index_id.clone())
+            .collect(),
+    };
+    let index_aliases = match metastore
+        .list_index_aliases(list_index_aliases_request)
+        .await
+        .and_then(|response| response.deserialize_index_aliases())
+    {
+        Ok(index_aliases) => index_aliases,
+        Err(error) => {
+            warn!(%error, "failed to list index aliases");
+            return Ok(ingest_response);
+        }
+    };
+    let write_index_ids: HashMap<&str, &IndexId> = index_aliases
+        .iter()
+        .filter_map(|index_alias| {
+            let write_index_id = index_alias.write_index_id()?;
+            Some((index_alias.alias_id.as_str(), write_index_id))
+        })
+        .collect();
+
+    subrequests.retain_mut(|subrequest| {
+        let Some(write_index_id) = write_index_ids.get(subrequest.index_id.as_str()) else {
+            return false;
+        };
+        subrequest.index_id = (*write_index_id).clone();
+        true
+    });
+    if subrequests.is_empty() {
+        return Ok(ingest_response);
+    }
+    let routed_subrequest_ids: HashSet<u32> = subrequests
+        .iter()
+        .map(|subrequest| subrequest.subrequest_id)
+        .collect();
+    let routed_ingest_request = IngestRequestV2 {
+        subrequests,
+        commit_type,
+    };
+    let routed_ingest_response = ingest_router.ingest(routed_ingest_request).await?;
+
+    ingest_response
+        .failures
+        .retain(|failure| !routed_subrequest_ids.contains(&failure.subrequest_id));
+    ingest_response
+        .successes
+        .extend(routed_ingest_response.successes);
+    ingest_response
+        .failures
+        .extend(routed_ingest_response.failures);
+    Ok(ingest_response)
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_config::IndexAlias;
+    use quickwit_proto::ingest::DocBatchV2;
+    use quickwit_proto::ingest::router::{
+        IngestFailure, IngestSubrequest, IngestSuccess, MockIngestRouterService,
+    };
+    use quickwit_proto::metastore::{ListIndexAliasesResponse, MockMetastoreService};
+    use quickwit_proto::types::IndexUid;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ingest_v2_with_index_aliases() {
+        let mut mock_ingest_router = MockIngestRouterService::new();
+        mock_ingest_router
+            .expect_ingest()
+            .withf(|ingest_request| ingest_request.subrequests.len() == 3)
+            .once()
+            .returning(|ingest_request| {
+                let failures = ingest_request.subrequests[1..]
+                    .iter()
+                    .map(|subrequest| IngestFailure {
+                        subrequest_id: subrequest.subrequest_id,
+                        index_id: subrequest.index_id.clone(),
+                        source_id: subrequest.source_id.clone(),
+                        reason: IngestFailureReason::IndexNotFound as i32,
+                    })
+                    .collect();
+                Ok(IngestResponseV2 {
+                    successes: vec![IngestSuccess {
+                        subrequest_id: 0,
+                        index_uid: Some(IndexUid::for_test("my-index", 0)),
+                        ..Default::default()
+                    }],
+                    failures,
+                })
+            });
+        mock_ingest_router
+            .expect_ingest()
+            .withf(|ingest_request| ingest_request.subrequests.len() == 1)
+            .once()
+            .returning(|ingest_request| {
+                let subrequest = &ingest_request.subrequests[0];
+                assert_eq!(subrequest.subrequest_id, 1);
+                assert_eq!(subrequest.index_id, "my-write-index");
+                Ok(IngestResponseV2 {
+                    successes: vec![IngestSuccess {
+                        subrequest_id: 1,
+                        index_uid: Some(IndexUid::for_test("my-write-index", 0)),
+                        ..Default::default()
+                    }],
+                    failures: Vec::new(),
+                })
+            });
+        let mut mock_metastore = MockMetastoreService::new();
+        mock_metastore
+            .expect_list_index_aliases()
+            .once()
+            .returning(|list_index_aliases_request| {
+                assert_eq!(
+                    list_index_aliases_request.alias_ids,
+                    ["my-alias", "my-missing-index"]
+                );
+                let index_alias = IndexAlias::for_test("my-alias", &["my-write-index"]);
+                Ok(ListIndexAliasesResponse {
+                    index_aliases_json: vec![serde_json::to_string(&index_alias).unwrap()],
+                })
+            });
+        let ingest_router = IngestRouterServiceClient::from_mock(mock_ingest_router);
+        let metastore = MetastoreServiceClient::from_mock(mock_metastore);
+
+        let subrequests = ["my-index", "my-alias", "my-missing-index"]
+            .iter()
+            .enumerate()
+            .map(|(subrequest_id, index_id)| IngestSubrequest {
+                subrequest_id: subrequest_id as u32,
+                index_id: index_id.to_string(),
+                source_id: "my-source".to_string(),
+                doc_batch: Some(DocBatchV2::for_test(["{}"])),
+            })
+            .collect();
+        let ingest_request = IngestRequestV2 {
+            subrequests,
+            commit_type: 0,
+        };
+        let ingest_response =
+            ingest_v2_with_index_aliases(&ingest_router, &metastore, ingest_request)
+                .await
+                .unwrap();
+        assert_eq!(ingest_response.successes.len(), 2);
+        assert_eq!(ingest_response.failures.len(), 1);
+        assert_eq!(ingest_response.failures[0].index_id, "my-missing-index");
+    }
+}
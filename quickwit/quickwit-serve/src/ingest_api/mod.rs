@@ -12,11 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod index_alias;
 mod response;
 mod rest_handler;
 
+pub(crate) use index_alias::ingest_v2_with_index_aliases;
 pub use response::{RestIngestResponse, RestParseFailure};
 #[cfg(test)]
 pub(crate) use rest_handler::tests::setup_ingest_v1_service;
 pub use rest_handler::{IngestApi, IngestApiSchemas};
-pub(crate) use rest_handler::{ingest_api_handlers, lines};
+pub(crate) use rest_handler::{IngestOptions, ingest, ingest_api_handlers, lines};
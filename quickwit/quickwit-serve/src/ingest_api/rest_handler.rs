@@ -20,13 +20,14 @@ use quickwit_ingest::{
 };
 use quickwit_proto::ingest::CommitTypeV2;
 use quickwit_proto::ingest::router::{
-    IngestRequestV2, IngestRouterService, IngestRouterServiceClient, IngestSubrequest,
+    IngestRequestV2, IngestRouterServiceClient, IngestSubrequest,
 };
+use quickwit_proto::metastore::MetastoreServiceClient;
 use quickwit_proto::types::{DocUidGenerator, IndexId};
 use serde::Deserialize;
 use warp::{Filter, Rejection};
 
-use super::RestIngestResponse;
+use super::{RestIngestResponse, ingest_v2_with_index_aliases};
 use crate::decompression::get_body_bytes;
 use crate::format::extract_format_from_qs;
 use crate::rest_api_response::into_rest_api_response;
@@ -46,7 +47,7 @@ pub struct IngestApi;
 pub struct IngestApiSchemas;
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
-struct IngestOptions {
+pub(crate) struct IngestOptions {
     #[serde(alias = "commit", default = "IngestOptions::default_commit_type")]
     commit_type: CommitTypeV2,
     #[serde(default)]
@@ -55,6 +56,16 @@ struct IngestOptions {
     detailed_response: bool,
 }
 
+impl Default for IngestOptions {
+    fn default() -> Self {
+        IngestOptions {
+            commit_type: IngestOptions::default_commit_type(),
+            use_legacy_ingest: false,
+            detailed_response: false,
+        }
+    }
+}
+
 impl IngestOptions {
     // This default implementation is necessary because `CommitTypeV2::default()` is
     // `CommitTypeV2::Unspecified`.
@@ -74,6 +85,7 @@ impl IngestOptions {
 pub(crate) fn ingest_api_handlers(
     ingest_router: IngestRouterServiceClient,
     ingest_service: IngestServiceClient,
+    metastore: MetastoreServiceClient,
     config: IngestApiConfig,
     enable_ingest_v1: bool,
     enable_ingest_v2: bool,
@@ -81,6 +93,7 @@ pub(crate) fn ingest_api_handlers(
     ingest_handler(
         ingest_router,
         ingest_service.clone(),
+        metastore,
         config,
         enable_ingest_v1,
         enable_ingest_v2,
@@ -104,6 +117,7 @@ fn ingest_filter(
 fn ingest_handler(
     ingest_router: IngestRouterServiceClient,
     ingest_service: IngestServiceClient,
+    metastore: MetastoreServiceClient,
     config: IngestApiConfig,
     enable_ingest_v1: bool,
     enable_ingest_v2: bool,
@@ -111,14 +125,16 @@ fn ingest_handler(
     ingest_filter(config)
         .and(with_arg(ingest_router))
         .and(with_arg(ingest_service))
+        .and(with_arg(metastore))
         .then(
-            move |index_id, body, ingest_options, ingest_router, ingest_service| {
+            move |index_id, body, ingest_options, ingest_router, ingest_service, metastore| {
                 ingest(
                     index_id,
                     body,
                     ingest_options,
                     ingest_router,
                     ingest_service,
+                    metastore,
                     enable_ingest_v1,
                     enable_ingest_v2,
                 )
@@ -142,17 +158,18 @@ fn ingest_handler(
     )
 )]
 /// Ingest documents
-async fn ingest(
+pub(crate) async fn ingest(
     index_id: IndexId,
     body: Body,
     ingest_options: IngestOptions,
     ingest_router: IngestRouterServiceClient,
     ingest_service: IngestServiceClient,
+    metastore: MetastoreServiceClient,
     enable_ingest_v1: bool,
     enable_ingest_v2: bool,
 ) -> Result<RestIngestResponse, IngestServiceError> {
     if enable_ingest_v2 && !ingest_options.use_legacy_ingest {
-        return ingest_v2(index_id, body, ingest_options, ingest_router).await;
+        return ingest_v2(index_id, body, ingest_options, ingest_router, metastore).await;
     }
     if !enable_ingest_v1 {
         let message = "ingest v1 is disabled: environment variable `QW_DISABLE_INGEST_V1` is set";
@@ -192,6 +209,7 @@ async fn ingest_v2(
     body: Body,
     ingest_options: IngestOptions,
     ingest_router: IngestRouterServiceClient,
+    metastore: MetastoreServiceClient,
 ) -> Result<RestIngestResponse, IngestServiceError> {
     let mut doc_batch_builder = DocBatchV2Builder::default();
     let mut doc_uid_generator = DocUidGenerator::default();
@@ -231,7 +249,7 @@ async fn ingest_v2(
         commit_type: ingest_options.commit_type as i32,
         subrequests: vec![subrequest],
     };
-    let response = ingest_router.ingest(request).await?;
+    let response = ingest_v2_with_index_aliases(&ingest_router, &metastore, request).await?;
     RestIngestResponse::from_ingest_v2(
         response,
         doc_batch_clone_opt.as_ref(),
@@ -297,6 +315,7 @@ pub(crate) mod tests {
         IngestServiceClient, QUEUES_DIR_NAME, SuggestTruncateRequest, init_ingest_api,
     };
     use quickwit_proto::ingest::router::IngestRouterServiceClient;
+    use quickwit_proto::metastore::MetastoreServiceClient;
 
     use super::{RestIngestResponse, ingest_api_handlers};
     use crate::ingest_api::lines;
@@ -355,6 +374,7 @@ pub(crate) mod tests {
         let ingest_api_handlers = ingest_api_handlers(
             ingest_router,
             ingest_service,
+            MetastoreServiceClient::mocked(),
             IngestApiConfig::default(),
             true,
             false,
@@ -396,6 +416,7 @@ pub(crate) mod tests {
         let ingest_api_handlers = ingest_api_handlers(
             ingest_router,
             ingest_service,
+            MetastoreServiceClient::mocked(),
             IngestApiConfig::default(),
             true,
             false,
@@ -427,6 +448,7 @@ pub(crate) mod tests {
         let ingest_api_handlers = ingest_api_handlers(
             ingest_router,
             ingest_service,
+            MetastoreServiceClient::mocked(),
             IngestApiConfig::default(),
             true,
             false,
@@ -449,8 +471,14 @@ pub(crate) mod tests {
         let (universe, _temp_dir, ingest_service, _) =
             setup_ingest_v1_service(&["my-index"], &IngestApiConfig::default()).await;
         let ingest_router = IngestRouterServiceClient::mocked();
-        let ingest_api_handlers =
-            ingest_api_handlers(ingest_router, ingest_service, config.clone(), true, false);
+        let ingest_api_handlers = ingest_api_handlers(
+            ingest_router,
+            ingest_service,
+            MetastoreServiceClient::mocked(),
+            config.clone(),
+            true,
+            false,
+        );
         let resp = warp::test::request()
             .path("/my-index/ingest")
             .method("POST")
@@ -470,6 +498,7 @@ pub(crate) mod tests {
         let ingest_api_handlers = ingest_api_handlers(
             ingest_router,
             ingest_service_client,
+            MetastoreServiceClient::mocked(),
             IngestApiConfig::default(),
             true,
             false,
@@ -521,6 +550,7 @@ pub(crate) mod tests {
         let ingest_api_handlers = ingest_api_handlers(
             ingest_router,
             ingest_service_client,
+            MetastoreServiceClient::mocked(),
             IngestApiConfig::default(),
             true,
             false,
@@ -572,6 +602,7 @@ pub(crate) mod tests {
         let ingest_api_handlers = ingest_api_handlers(
             ingest_router,
             ingest_service,
+            MetastoreServiceClient::mocked(),
             IngestApiConfig::default(),
             true,
             false,
@@ -16,10 +16,12 @@
 
 mod build_info;
 mod cluster_api;
+mod dead_letter_api;
 mod decompression;
 mod delete_task_api;
 mod developer_api;
 mod elasticsearch_api;
+mod export_api;
 mod format;
 mod grpc;
 mod health_check_api;
@@ -38,6 +40,7 @@ mod rest;
 mod rest_api_response;
 mod search_api;
 pub(crate) mod simple_list;
+mod sql_api;
 pub mod tcp_listener;
 mod template_api;
 mod ui_handler;
@@ -120,6 +123,7 @@ use tracing::{debug, error, info, warn};
 use warp::{Filter, Rejection};
 
 pub use crate::build_info::{BuildInfo, RuntimeInfo};
+pub use crate::dead_letter_api::{ReplayDeadLettersQueryParams, ReplayDeadLettersResponse};
 pub use crate::index_api::{ListSplitsQueryParams, ListSplitsResponse};
 pub use crate::ingest_api::{RestIngestResponse, RestParseFailure};
 pub use crate::metrics::SERVE_METRICS;
@@ -23,9 +23,11 @@ use utoipa::OpenApi;
 use utoipa::openapi::Tag;
 
 use crate::cluster_api::ClusterApi;
+use crate::dead_letter_api::DeadLetterApi;
 use crate::delete_task_api::DeleteTaskApi;
 use crate::developer_api::DeveloperApi;
 use crate::elasticsearch_api::ElasticCompatibleApi;
+use crate::export_api::ExportApi;
 use crate::health_check_api::HealthCheckApi;
 use crate::index_api::IndexApi;
 use crate::indexing_api::IndexingApi;
@@ -35,6 +37,7 @@ use crate::metrics_api::MetricsApi;
 use crate::node_info_handler::NodeInfoApi;
 use crate::otlp_api::OtlpApi;
 use crate::search_api::SearchApi;
+use crate::sql_api::SqlApi;
 use crate::template_api::IndexTemplateApi;
 
 /// Builds the OpenApi docs structure using the registered/merged docs.
@@ -81,6 +84,7 @@ pub fn build_docs() -> utoipa::openapi::OpenApi {
 
     // Routing
     docs_base.merge_components_and_paths(ClusterApi::openapi().with_path_prefix("/api/v1"));
+    docs_base.merge_components_and_paths(DeadLetterApi::openapi().with_path_prefix("/api/v1"));
     docs_base.merge_components_and_paths(DeleteTaskApi::openapi().with_path_prefix("/api/v1"));
     docs_base
         .merge_components_and_paths(DeveloperApi::openapi().with_path_prefix("/api/developer"));
@@ -96,6 +100,8 @@ pub fn build_docs() -> utoipa::openapi::OpenApi {
     docs_base.merge_components_and_paths(MetricsApi::openapi().with_path_prefix("/metrics"));
     docs_base.merge_components_and_paths(NodeInfoApi::openapi().with_path_prefix("/api/v1"));
     docs_base.merge_components_and_paths(SearchApi::openapi().with_path_prefix("/api/v1"));
+    docs_base.merge_components_and_paths(SqlApi::openapi().with_path_prefix("/api/v1"));
+    docs_base.merge_components_and_paths(ExportApi::openapi().with_path_prefix("/api/v1"));
 
     // Schemas
     docs_base.merge_components_and_paths(MetastoreApiSchemas::openapi());
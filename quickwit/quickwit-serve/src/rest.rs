@@ -37,10 +37,12 @@ use warp::hyper::{Method, StatusCode, http};
 use warp::{Filter, Rejection, Reply, redirect};
 
 use crate::cluster_api::cluster_handler;
+use crate::dead_letter_api::{DeadLetterReplayer, dead_letter_api_handlers};
 use crate::decompression::{CorruptedData, UnsupportedEncoding};
 use crate::delete_task_api::delete_task_api_handlers;
 use crate::developer_api::developer_api_routes;
 use crate::elasticsearch_api::elastic_api_handlers;
+use crate::export_api::export_handler;
 use crate::health_check_api::health_check_handlers;
 use crate::index_api::index_management_handlers;
 use crate::indexing_api::indexing_get_handler;
@@ -53,6 +55,7 @@ use crate::rest_api_response::{RestApiError, RestApiResponse};
 use crate::search_api::{
     search_get_handler, search_plan_get_handler, search_plan_post_handler, search_post_handler,
 };
+use crate::sql_api::sql_handler;
 use crate::template_api::index_template_api_handlers;
 use crate::ui_handler::ui_handler;
 use crate::{BodyFormat, BuildInfo, QuickwitServices, RuntimeInfo};
@@ -317,9 +320,14 @@ fn api_v1_routes(
         .boxed()
         .or(search_routes(quickwit_services.search_service.clone()))
         .boxed()
+        .or(sql_handler(quickwit_services.search_service.clone()))
+        .boxed()
+        .or(export_handler(quickwit_services.search_service.clone()))
+        .boxed()
         .or(ingest_api_handlers(
             quickwit_services.ingest_router_service.clone(),
             quickwit_services.ingest_service.clone(),
+            quickwit_services.metastore_client.clone(),
             quickwit_services.node_config.ingest_api_config.clone(),
             !disable_ingest_v1(),
             enable_ingest_v2(),
@@ -339,6 +347,16 @@ fn api_v1_routes(
             quickwit_services.metastore_client.clone(),
         ))
         .boxed()
+        .or(dead_letter_api_handlers(DeadLetterReplayer {
+            index_manager: quickwit_services.index_manager.clone(),
+            search_service: quickwit_services.search_service.clone(),
+            ingest_router: quickwit_services.ingest_router_service.clone(),
+            ingest_service: quickwit_services.ingest_service.clone(),
+            metastore: quickwit_services.metastore_client.clone(),
+            enable_ingest_v1: !disable_ingest_v1(),
+            enable_ingest_v2: enable_ingest_v2(),
+        }))
+        .boxed()
         .or(jaeger_api_handlers(
             quickwit_services.jaeger_service_opt.clone(),
         ))
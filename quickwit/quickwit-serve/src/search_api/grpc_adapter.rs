@@ -15,10 +15,14 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use quickwit_proto::error::convert_to_grpc_result;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use quickwit_proto::error::{GrpcServiceError, convert_to_grpc_result};
 use quickwit_proto::search::{
-    GetKvRequest, GetKvResponse, LeafListFieldsRequest, ListFieldsRequest, ListFieldsResponse,
-    ReportSplitsRequest, ReportSplitsResponse, search_service_server as grpc,
+    CancelSearchTaskRequest, CancelSearchTaskResponse, GetKvRequest, GetKvResponse,
+    LeafExportRequest, LeafExportResponse, LeafListFieldsRequest, ListFieldsRequest,
+    ListFieldsResponse, ListSearchTasksRequest, ListSearchTasksResponse, ReportSplitsRequest,
+    ReportSplitsResponse, search_service_server as grpc,
 };
 use quickwit_proto::{set_parent_span_from_request_metadata, tonic};
 use quickwit_search::SearchService;
@@ -164,4 +168,47 @@ impl grpc::SearchService for GrpcSearchAdapter {
         let search_result = self.0.search_plan(search_request).await;
         convert_to_grpc_result(search_result)
     }
+
+    #[instrument(skip(self, request))]
+    async fn leaf_list_search_tasks(
+        &self,
+        request: tonic::Request<ListSearchTasksRequest>,
+    ) -> Result<tonic::Response<ListSearchTasksResponse>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let list_request = request.into_inner();
+        let list_result = self.0.leaf_list_search_tasks(list_request).await;
+        convert_to_grpc_result(list_result)
+    }
+
+    #[instrument(skip(self, request))]
+    async fn leaf_cancel_search_task(
+        &self,
+        request: tonic::Request<CancelSearchTaskRequest>,
+    ) -> Result<tonic::Response<CancelSearchTaskResponse>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let cancel_request = request.into_inner();
+        let cancel_result = self.0.leaf_cancel_search_task(cancel_request).await;
+        convert_to_grpc_result(cancel_result)
+    }
+
+    type LeafExportStream = BoxStream<'static, Result<LeafExportResponse, tonic::Status>>;
+
+    #[instrument(skip(self, request))]
+    async fn leaf_export(
+        &self,
+        request: tonic::Request<LeafExportRequest>,
+    ) -> Result<tonic::Response<Self::LeafExportStream>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let leaf_export_request = request.into_inner();
+        let leaf_export_result =
+            self.0
+                .leaf_export(leaf_export_request)
+                .await
+                .map(|export_stream| {
+                    export_stream
+                        .map_err(|search_error| search_error.into_grpc_status())
+                        .boxed()
+                });
+        convert_to_grpc_result(leaf_export_result)
+    }
 }
@@ -20,4 +20,6 @@ pub use self::rest_handler::{
     SearchApi, SearchRequestQueryString, SortBy, search_get_handler, search_plan_get_handler,
     search_plan_post_handler, search_post_handler, search_request_from_api_request,
 };
-pub(crate) use self::rest_handler::{extract_index_id_patterns, extract_index_id_patterns_default};
+pub(crate) use self::rest_handler::{
+    extract_index_id_patterns, extract_index_id_patterns_default, tenant_id_header,
+};
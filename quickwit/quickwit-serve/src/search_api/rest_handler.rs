@@ -17,7 +17,7 @@ use std::sync::Arc;
 
 use percent_encoding::percent_decode_str;
 use quickwit_config::validate_index_id_pattern;
-use quickwit_proto::search::{CountHits, SortField, SortOrder};
+use quickwit_proto::search::{CountHits, SortField, SortOrder, SourceFilter};
 use quickwit_query::query_ast::query_ast_from_user_text;
 use quickwit_search::{SearchError, SearchPlanResponseRest, SearchResponseRest, SearchService};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -49,6 +49,15 @@ use crate::{BodyFormat, with_arg};
 )]
 pub struct SearchApi;
 
+/// Header identifying the tenant a search is accounted to by the searcher admission control.
+/// Searches without this header are accounted to the index ID patterns they target.
+const TENANT_ID_HEADER: &str = "x-quickwit-tenant-id";
+
+pub(crate) fn tenant_id_header()
+-> impl Filter<Extract = (Option<String>,), Error = Rejection> + Clone {
+    warp::header::optional::<String>(TENANT_ID_HEADER)
+}
+
 pub(crate) async fn extract_index_id_patterns_default() -> Result<Vec<String>, Rejection> {
     let index_id_patterns = Vec::new();
     Ok(index_id_patterns)
@@ -173,6 +182,13 @@ pub struct SearchRequestQueryString {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(serialize_with = "to_simple_list")]
     pub snippet_fields: Option<Vec<String>>,
+    /// Fields of the documents to return in the hits. Field paths may contain `*` wildcards.
+    /// Fields prefixed with `-` are excluded.
+    #[serde(default)]
+    #[serde(deserialize_with = "from_simple_list")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "to_simple_list")]
+    pub source_fields: Option<Vec<String>>,
     /// If set, restrict search to documents with a `timestamp >= start_timestamp`.
     /// This timestamp is expressed in seconds.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -210,6 +226,23 @@ pub struct SearchRequestQueryString {
     #[schema(value_type = bool)]
     #[serde(default)]
     pub allow_failed_splits: bool,
+    /// Maximum duration of the search (e.g. `10s`). Once it is reached, the results of the splits
+    /// searched so far are returned and the response is flagged as `timed_out`.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+    /// If set, the response includes a breakdown of the execution of the search: time spent per
+    /// phase and per searcher, splits pruned, bytes downloaded, and cache hit ratios.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub profile: bool,
+    #[param(value_type = Object)]
+    #[schema(value_type = Object)]
+    /// The k-nearest neighbors search JSON object, searching the documents whose dense vectors are
+    /// the most similar to `query_vector`.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub knn: Option<JsonValue>,
 }
 
 mod count_hits_from_bool {
@@ -240,6 +273,18 @@ mod count_hits_from_bool {
     }
 }
 
+fn source_filter_from_source_fields(source_fields: Vec<String>) -> SourceFilter {
+    let mut source_filter = SourceFilter::default();
+    for source_field in source_fields {
+        if let Some(excluded_field) = source_field.strip_prefix('-') {
+            source_filter.excludes.push(excluded_field.to_string());
+        } else {
+            source_filter.includes.push(source_field);
+        }
+    }
+    source_filter
+}
+
 pub fn search_request_from_api_request(
     index_id_patterns: Vec<String>,
     search_request: SearchRequestQueryString,
@@ -249,10 +294,25 @@ pub fn search_request_from_api_request(
     // the user of the docmapper default fields (which we do not have at this point).
     let query_ast = query_ast_from_user_text(&search_request.query, search_request.search_fields);
     let query_ast_json = serde_json::to_string(&query_ast)?;
+    let timeout_millis: Option<u64> = search_request
+        .timeout
+        .as_deref()
+        .map(|timeout_str| {
+            humantime::parse_duration(timeout_str)
+                .map(|timeout| timeout.as_millis() as u64)
+                .map_err(|_err| {
+                    SearchError::InvalidArgument(format!("invalid timeout: `{timeout_str}`"))
+                })
+        })
+        .transpose()?;
     let search_request = quickwit_proto::search::SearchRequest {
         index_id_patterns,
         query_ast: query_ast_json,
         snippet_fields: search_request.snippet_fields.unwrap_or_default(),
+        snippet_options: None,
+        source_filter: search_request
+            .source_fields
+            .map(source_filter_from_source_fields),
         start_timestamp: search_request.start_timestamp,
         end_timestamp: search_request.end_timestamp,
         max_hits: search_request.max_hits,
@@ -264,6 +324,14 @@ pub fn search_request_from_api_request(
         scroll_ttl_secs: None,
         search_after: None,
         count_hits: search_request.count_all.into(),
+        point_in_time: None,
+        timeout_millis,
+        tenant_id: None,
+        collapse: None,
+        profile: search_request.profile,
+        knn: search_request
+            .knn
+            .map(|knn| serde_json::to_string(&knn).expect("could not serialize JsonValue")),
     };
     Ok(search_request)
 }
@@ -271,10 +339,12 @@ pub fn search_request_from_api_request(
 async fn search_endpoint(
     index_id_patterns: Vec<String>,
     search_request: SearchRequestQueryString,
+    tenant_id_opt: Option<String>,
     search_service: &dyn SearchService,
 ) -> Result<SearchResponseRest, SearchError> {
     let allow_failed_splits = search_request.allow_failed_splits;
-    let search_request = search_request_from_api_request(index_id_patterns, search_request)?;
+    let mut search_request = search_request_from_api_request(index_id_patterns, search_request)?;
+    search_request.tenant_id = tenant_id_opt;
     let search_response =
         search_service
             .root_search(search_request)
@@ -329,11 +399,18 @@ fn search_plan_post_filter()
 async fn search(
     index_id_patterns: Vec<String>,
     search_request: SearchRequestQueryString,
+    tenant_id_opt: Option<String>,
     search_service: Arc<dyn SearchService>,
 ) -> impl warp::Reply {
     info!(request =? search_request, "search");
     let body_format = search_request.format;
-    let result = search_endpoint(index_id_patterns, search_request, &*search_service).await;
+    let result = search_endpoint(
+        index_id_patterns,
+        search_request,
+        tenant_id_opt,
+        &*search_service,
+    )
+    .await;
     into_rest_api_response(result, body_format)
 }
 
@@ -372,6 +449,7 @@ pub fn search_get_handler(
     search_service: Arc<dyn SearchService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     search_get_filter()
+        .and(tenant_id_header())
         .and(with_arg(search_service))
         .then(search)
 }
@@ -397,6 +475,7 @@ pub fn search_post_handler(
     search_service: Arc<dyn SearchService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     search_post_filter()
+        .and(tenant_id_header())
         .and(with_arg(search_service))
         .then(search)
 }
@@ -500,6 +579,8 @@ mod tests {
             elapsed_time_micros: 0u64,
             errors: Vec::new(),
             aggregations: None,
+            timed_out: false,
+            profile: None,
         };
         let search_response_json: JsonValue = serde_json::to_value(search_response)?;
         let expected_search_response_json: JsonValue = json!({
@@ -665,6 +746,30 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_rest_search_api_route_source_fields() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .with(predicate::function(
+                |search_request: &quickwit_proto::search::SearchRequest| {
+                    search_request.source_filter
+                        == Some(SourceFilter {
+                            includes: vec!["timestamp".to_string(), "app.*".to_string()],
+                            excludes: vec!["app.id".to_string()],
+                            disabled: false,
+                        })
+                },
+            ))
+            .returning(|_| Ok(Default::default()));
+        let rest_search_api_handler = search_handler(mock_search_service);
+        let resp = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*&source_fields=timestamp,app.*,-app.id")
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+    }
+
     #[tokio::test]
     async fn test_rest_search_api_route_simple_default_num_hits_default_offset() {
         let rest_search_api_filter = search_get_filter();
@@ -1000,6 +1105,8 @@ mod tests {
                     partial_hit: None,
                     snippet: Some(r#"{"title": [], "body": ["foo <em>bar</em> baz"]}"#.to_string()),
                     index_id: "quickwit-demo-index".to_string(),
+                    inner_hits: None,
+                    source_disabled: false,
                 }],
                 num_hits: 1,
                 elapsed_time_micros: 16,
@@ -0,0 +1,1127 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tokenizer and recursive descent parser for the subset of SQL supported by the `_sql` endpoint:
+//!
+//! ```text
+//! SELECT <select_item> [, ...] FROM <index_id_patterns>
+//! [WHERE <condition>]
+//! [GROUP BY <column> [, ...]]
+//! [ORDER BY <column | aggregate> [ASC | DESC] [, ...]]
+//! [LIMIT <n>]
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use quickwit_search::SearchError;
+
+/// Keywords that cannot be used as bare identifiers.
+const RESERVED_KEYWORDS: &[&str] = &[
+    "AND", "AS", "ASC", "BETWEEN", "BY", "DESC", "FALSE", "FROM", "GROUP", "IN", "IS", "LIKE",
+    "LIMIT", "NOT", "NULL", "OR", "ORDER", "SELECT", "TRUE", "WHERE",
+];
+
+/// Maximum nesting depth of the parentheses and `NOT` operators of a `WHERE` clause, so that
+/// deeply nested conditions do not overflow the stack of the parser and planner.
+const MAX_CONDITION_DEPTH: usize = 32;
+
+/// Keywords of SQL constructs that are not supported. Hitting one of them yields an error naming
+/// the construct rather than a generic syntax error.
+const UNSUPPORTED_KEYWORDS: &[&str] = &[
+    "ALL",
+    "ALTER",
+    "CASE",
+    "CAST",
+    "CREATE",
+    "CROSS",
+    "DELETE",
+    "DISTINCT",
+    "DROP",
+    "ESCAPE",
+    "EXCEPT",
+    "EXISTS",
+    "FETCH",
+    "FULL",
+    "HAVING",
+    "INNER",
+    "INSERT",
+    "INTERSECT",
+    "JOIN",
+    "LEFT",
+    "NATURAL",
+    "OFFSET",
+    "ON",
+    "OUTER",
+    "OVER",
+    "PIVOT",
+    "RIGHT",
+    "UNION",
+    "UPDATE",
+    "USING",
+    "WINDOW",
+    "WITH",
+];
+
+/// Error raised when a SQL statement cannot be parsed or planned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SqlError {
+    message: String,
+}
+
+impl SqlError {
+    /// Builds an error pointing at the byte offset `position` of the statement `sql`.
+    pub fn at(sql: &str, position: usize, message: impl fmt::Display) -> Self {
+        let prefix = &sql[..position.min(sql.len())];
+        let line = prefix.matches('\n').count() + 1;
+        let column = prefix
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count()
+            + 1;
+        SqlError {
+            message: format!("{message} at line {line}, column {column}"),
+        }
+    }
+}
+
+impl From<SqlError> for SearchError {
+    fn from(sql_error: SqlError) -> Self {
+        SearchError::InvalidQuery(sql_error.message)
+    }
+}
+
+impl fmt::Display for SqlError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.message.fmt(formatter)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    /// Bare identifier or keyword.
+    Word(String),
+    /// Identifier between double quotes or backticks.
+    QuotedIdentifier(String),
+    String(String),
+    Number(String),
+    Comma,
+    LeftParen,
+    RightParen,
+    Star,
+    Semicolon,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    Eof,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// Byte offsets of the token in the statement.
+    start: usize,
+    end: usize,
+}
+
+fn is_identifier_start(character: char) -> bool {
+    character.is_alphabetic() || character == '_' || character == '@'
+}
+
+fn is_identifier_part(character: char) -> bool {
+    character.is_alphanumeric() || matches!(character, '_' | '@' | '.' | '-' | '*')
+}
+
+fn tokenize(sql: &str) -> Result<Vec<Token>, SqlError> {
+    let mut tokens = Vec::new();
+    let mut chars = sql.char_indices().peekable();
+
+    while let Some((start, character)) = chars.next() {
+        let kind = match character {
+            character if character.is_whitespace() => continue,
+            '-' if chars.peek().map(|(_, next)| *next) == Some('-') => {
+                // Line comment.
+                for (_, next) in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+                continue;
+            }
+            '\'' => TokenKind::String(read_quoted(sql, start, '\'', &mut chars)?),
+            '"' | '`' => {
+                TokenKind::QuotedIdentifier(read_quoted(sql, start, character, &mut chars)?)
+            }
+            '0'..='9' => read_number(character, &mut chars),
+            '-' if matches!(chars.peek(), Some((_, next)) if next.is_ascii_digit()) => {
+                read_number(character, &mut chars)
+            }
+            ',' => TokenKind::Comma,
+            '(' => TokenKind::LeftParen,
+            ')' => TokenKind::RightParen,
+            '*' => TokenKind::Star,
+            ';' => TokenKind::Semicolon,
+            '=' => TokenKind::Eq,
+            '!' if chars.next_if(|(_, next)| *next == '=').is_some() => TokenKind::NotEq,
+            '<' => {
+                if chars.next_if(|(_, next)| *next == '=').is_some() {
+                    TokenKind::LtEq
+                } else if chars.next_if(|(_, next)| *next == '>').is_some() {
+                    TokenKind::NotEq
+                } else {
+                    TokenKind::Lt
+                }
+            }
+            '>' => {
+                if chars.next_if(|(_, next)| *next == '=').is_some() {
+                    TokenKind::GtEq
+                } else {
+                    TokenKind::Gt
+                }
+            }
+            character if is_identifier_start(character) => {
+                while chars
+                    .next_if(|(_, next)| is_identifier_part(*next))
+                    .is_some()
+                {}
+                let end = chars.peek().map(|(end, _)| *end).unwrap_or(sql.len());
+                TokenKind::Word(sql[start..end].to_string())
+            }
+            _ => {
+                return Err(SqlError::at(
+                    sql,
+                    start,
+                    format!("unexpected character `{character}`"),
+                ));
+            }
+        };
+        let end = chars.peek().map(|(end, _)| *end).unwrap_or(sql.len());
+        tokens.push(Token { kind, start, end });
+    }
+    tokens.push(Token {
+        kind: TokenKind::Eof,
+        start: sql.len(),
+        end: sql.len(),
+    });
+    Ok(tokens)
+}
+
+/// Reads a string or quoted identifier whose opening quote was already consumed. The quote
+/// character is escaped by doubling it.
+fn read_quoted(
+    sql: &str,
+    start: usize,
+    quote: char,
+    chars: &mut std::iter::Peekable<std::str::CharIndices>,
+) -> Result<String, SqlError> {
+    let mut value = String::new();
+    while let Some((_, character)) = chars.next() {
+        if character == quote {
+            if chars.next_if(|(_, next)| *next == quote).is_some() {
+                value.push(quote);
+                continue;
+            }
+            return Ok(value);
+        }
+        value.push(character);
+    }
+    Err(SqlError::at(sql, start, format!("unterminated `{quote}`")))
+}
+
+fn read_number(
+    first_character: char,
+    chars: &mut std::iter::Peekable<std::str::CharIndices>,
+) -> TokenKind {
+    let mut number = first_character.to_string();
+    let mut previous = first_character;
+    while let Some((_, character)) = chars.next_if(|(_, next)| {
+        next.is_ascii_digit()
+            || matches!(next, '.' | 'e' | 'E')
+            || (matches!(next, '+' | '-') && matches!(previous, 'e' | 'E'))
+    }) {
+        number.push(character);
+        previous = character;
+    }
+    TokenKind::Number(number)
+}
+
+/// A column referenced in the statement.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Column {
+    pub name: String,
+    pub position: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+    Percentile,
+}
+
+impl AggregateFunction {
+    fn from_name(name: &str) -> Option<Self> {
+        let aggregate_function = match name.to_ascii_uppercase().as_str() {
+            "COUNT" => AggregateFunction::Count,
+            "SUM" => AggregateFunction::Sum,
+            "AVG" => AggregateFunction::Avg,
+            "MIN" => AggregateFunction::Min,
+            "MAX" => AggregateFunction::Max,
+            "PERCENTILE" => AggregateFunction::Percentile,
+            _ => return None,
+        };
+        Some(aggregate_function)
+    }
+}
+
+/// A call to an aggregate function, such as `COUNT(*)` or `PERCENTILE(latency, 99)`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Aggregate {
+    pub function: AggregateFunction,
+    /// `None` stands for `*`.
+    pub column_opt: Option<Column>,
+    pub percent_opt: Option<f64>,
+    /// Text of the call as written in the statement.
+    pub text: String,
+    pub position: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Expr {
+    Column(Column),
+    Aggregate(Aggregate),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SelectItem {
+    Wildcard {
+        position: usize,
+    },
+    Expr {
+        expr: Expr,
+        alias_opt: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Literal {
+    String(String),
+    Number(serde_json::Number),
+    Bool(bool),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ComparisonOperator {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+/// A `WHERE` clause condition.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Condition {
+    /// Chains of `AND` operators are flattened into a single condition.
+    And(Vec<Condition>),
+    /// Chains of `OR` operators are flattened into a single condition.
+    Or(Vec<Condition>),
+    Not(Box<Condition>),
+    Comparison {
+        column: Column,
+        operator: ComparisonOperator,
+        value: Literal,
+    },
+    Between {
+        column: Column,
+        lower: Literal,
+        upper: Literal,
+    },
+    In {
+        column: Column,
+        values: Vec<Literal>,
+    },
+    IsNull {
+        column: Column,
+    },
+    Like {
+        column: Column,
+        pattern: String,
+    },
+    /// `MATCH(column, 'text')`: full-text search of the text in the column.
+    Match {
+        column: Column,
+        text: String,
+    },
+    /// `QUERY('text')`: query expressed in the Quickwit query language.
+    QueryString {
+        text: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct OrderByItem {
+    pub expr: Expr,
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SelectStatement {
+    pub select_items: Vec<SelectItem>,
+    pub index_id_patterns: Vec<String>,
+    pub where_opt: Option<Condition>,
+    pub group_by: Vec<Column>,
+    pub order_by: Vec<OrderByItem>,
+    pub limit_opt: Option<u64>,
+}
+
+/// Parses a `SELECT` statement.
+pub(crate) fn parse_sql(sql: &str) -> Result<SelectStatement, SqlError> {
+    let tokens = tokenize(sql)?;
+    let mut parser = Parser {
+        sql,
+        tokens,
+        cursor: 0,
+        condition_depth: 0,
+    };
+    parser.parse_statement()
+}
+
+struct Parser<'a> {
+    sql: &'a str,
+    tokens: Vec<Token>,
+    cursor: usize,
+    condition_depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.cursor]
+    }
+
+    fn peek_nth(&self, offset: usize) -> &Token {
+        let index = (self.cursor + offset).min(self.tokens.len() - 1);
+        &self.tokens[index]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.cursor].clone();
+        if token.kind != TokenKind::Eof {
+            self.cursor += 1;
+        }
+        token
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.peek().kind, TokenKind::Word(word) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn consume_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek_keyword(keyword) {
+            self.cursor += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), SqlError> {
+        if self.consume_keyword(keyword) {
+            return Ok(());
+        }
+        Err(self.unexpected(self.peek(), &format!("`{keyword}`")))
+    }
+
+    fn consume(&mut self, kind: &TokenKind) -> bool {
+        if &self.peek().kind == kind {
+            self.cursor += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, kind: &TokenKind, expected: &str) -> Result<Token, SqlError> {
+        if &self.peek().kind == kind {
+            return Ok(self.next());
+        }
+        Err(self.unexpected(self.peek(), expected))
+    }
+
+    fn unexpected(&self, token: &Token, expected: &str) -> SqlError {
+        let message = match &token.kind {
+            TokenKind::Eof => format!("unexpected end of statement, expected {expected}"),
+            TokenKind::Word(word) if is_unsupported_keyword(word) => {
+                format!("`{}` is not supported", word.to_ascii_uppercase())
+            }
+            _ => format!(
+                "unexpected `{}`, expected {expected}",
+                &self.sql[token.start..token.end]
+            ),
+        };
+        SqlError::at(self.sql, token.start, message)
+    }
+
+    fn error_at(&self, position: usize, message: impl fmt::Display) -> SqlError {
+        SqlError::at(self.sql, position, message)
+    }
+
+    fn parse_statement(&mut self) -> Result<SelectStatement, SqlError> {
+        self.expect_keyword("SELECT")?;
+        let select_items = self.parse_comma_separated(Self::parse_select_item)?;
+        self.expect_keyword("FROM")?;
+        let index_id_patterns = self.parse_index_id_patterns()?;
+
+        let where_opt = if self.consume_keyword("WHERE") {
+            Some(self.parse_or()?)
+        } else {
+            None
+        };
+        let group_by = if self.consume_keyword("GROUP") {
+            self.expect_keyword("BY")?;
+            self.parse_comma_separated(Self::parse_column)?
+        } else {
+            Vec::new()
+        };
+        let order_by = if self.consume_keyword("ORDER") {
+            self.expect_keyword("BY")?;
+            self.parse_comma_separated(Self::parse_order_by_item)?
+        } else {
+            Vec::new()
+        };
+        let limit_opt = if self.consume_keyword("LIMIT") {
+            let token = self.next();
+            match &token.kind {
+                TokenKind::Number(number) => Some(number.parse::<u64>().map_err(|_| {
+                    self.error_at(token.start, "LIMIT expects a non-negative integer")
+                })?),
+                _ => return Err(self.unexpected(&token, "a non-negative integer")),
+            }
+        } else {
+            None
+        };
+        self.consume(&TokenKind::Semicolon);
+
+        if self.peek().kind != TokenKind::Eof {
+            return Err(self.unexpected(self.peek(), "end of statement"));
+        }
+        Ok(SelectStatement {
+            select_items,
+            index_id_patterns,
+            where_opt,
+            group_by,
+            order_by,
+            limit_opt,
+        })
+    }
+
+    fn parse_comma_separated<T>(
+        &mut self,
+        parse_fn: fn(&mut Self) -> Result<T, SqlError>,
+    ) -> Result<Vec<T>, SqlError> {
+        let mut items = vec![parse_fn(self)?];
+        while self.consume(&TokenKind::Comma) {
+            items.push(parse_fn(self)?);
+        }
+        Ok(items)
+    }
+
+    /// Parses an identifier, which is either a bare word that is not a keyword or a quoted
+    /// identifier.
+    fn parse_identifier(&mut self, expected: &str) -> Result<(String, usize), SqlError> {
+        let token = self.peek().clone();
+        match token.kind {
+            TokenKind::Word(word) if !is_reserved_keyword(&word) => {
+                self.cursor += 1;
+                Ok((word, token.start))
+            }
+            TokenKind::QuotedIdentifier(identifier) => {
+                self.cursor += 1;
+                Ok((identifier, token.start))
+            }
+            _ => Err(self.unexpected(&token, expected)),
+        }
+    }
+
+    fn parse_column(&mut self) -> Result<Column, SqlError> {
+        let (name, position) = self.parse_identifier("a column name")?;
+        Ok(Column { name, position })
+    }
+
+    fn parse_index_id_patterns(&mut self) -> Result<Vec<String>, SqlError> {
+        if self.peek().kind == TokenKind::LeftParen {
+            return Err(self.error_at(self.peek().start, "subqueries are not supported"));
+        }
+        let (index_id_patterns, _) = self.parse_identifier("an index ID")?;
+        Ok(index_id_patterns
+            .split(',')
+            .map(|index_id_pattern| index_id_pattern.trim().to_string())
+            .collect())
+    }
+
+    fn parse_select_item(&mut self) -> Result<SelectItem, SqlError> {
+        if self.peek().kind == TokenKind::Star {
+            let position = self.next().start;
+            return Ok(SelectItem::Wildcard { position });
+        }
+        let expr = self.parse_expr()?;
+        let alias_opt = if self.consume_keyword("AS") {
+            Some(self.parse_identifier("an alias")?.0)
+        } else if matches!(
+            &self.peek().kind,
+            TokenKind::Word(word) if !is_reserved_keyword(word)
+        ) || matches!(self.peek().kind, TokenKind::QuotedIdentifier(_))
+        {
+            Some(self.parse_identifier("an alias")?.0)
+        } else {
+            None
+        };
+        Ok(SelectItem::Expr { expr, alias_opt })
+    }
+
+    fn parse_order_by_item(&mut self) -> Result<OrderByItem, SqlError> {
+        let expr = self.parse_expr()?;
+        let descending = if self.consume_keyword("DESC") {
+            true
+        } else {
+            self.consume_keyword("ASC");
+            false
+        };
+        Ok(OrderByItem { expr, descending })
+    }
+
+    /// Parses a column or a call to an aggregate function.
+    fn parse_expr(&mut self) -> Result<Expr, SqlError> {
+        if self.peek_nth(1).kind != TokenKind::LeftParen {
+            return self.parse_column().map(Expr::Column);
+        }
+        let token = self.next();
+        let TokenKind::Word(function_name) = &token.kind else {
+            return Err(self.unexpected(&token, "a column name or an aggregate function"));
+        };
+        let Some(function) = AggregateFunction::from_name(function_name) else {
+            return Err(self.error_at(
+                token.start,
+                format!(
+                    "unsupported function `{}`",
+                    function_name.to_ascii_uppercase()
+                ),
+            ));
+        };
+        self.expect(&TokenKind::LeftParen, "`(`")?;
+
+        let column_opt = if self.peek().kind == TokenKind::Star {
+            if function != AggregateFunction::Count {
+                return Err(self.error_at(
+                    self.peek().start,
+                    format!(
+                        "`*` is only supported by COUNT, not by {}",
+                        function_name.to_ascii_uppercase()
+                    ),
+                ));
+            }
+            self.cursor += 1;
+            None
+        } else {
+            Some(self.parse_column()?)
+        };
+        let percent_opt = if function == AggregateFunction::Percentile {
+            self.expect(&TokenKind::Comma, "`,` followed by a percentile")?;
+            let token = self.next();
+            let percent = match &token.kind {
+                TokenKind::Number(number) => number.parse::<f64>().ok(),
+                _ => None,
+            }
+            .filter(|percent| (0.0..=100.0).contains(percent))
+            .ok_or_else(|| self.error_at(token.start, "expected a percentile between 0 and 100"))?;
+            Some(percent)
+        } else {
+            None
+        };
+        let right_paren = self.expect(&TokenKind::RightParen, "`)`")?;
+
+        Ok(Expr::Aggregate(Aggregate {
+            function,
+            column_opt,
+            percent_opt,
+            text: self.sql[token.start..right_paren.end].to_string(),
+            position: token.start,
+        }))
+    }
+
+    fn parse_or(&mut self) -> Result<Condition, SqlError> {
+        let mut conditions = Vec::new();
+        loop {
+            match self.parse_and()? {
+                Condition::Or(operands) => conditions.extend(operands),
+                condition => conditions.push(condition),
+            }
+            if !self.consume_keyword("OR") {
+                break;
+            }
+        }
+        if conditions.len() == 1 {
+            return Ok(conditions.pop().unwrap());
+        }
+        Ok(Condition::Or(conditions))
+    }
+
+    fn parse_and(&mut self) -> Result<Condition, SqlError> {
+        let mut conditions = Vec::new();
+        loop {
+            match self.parse_not()? {
+                Condition::And(operands) => conditions.extend(operands),
+                condition => conditions.push(condition),
+            }
+            if !self.consume_keyword("AND") {
+                break;
+            }
+        }
+        if conditions.len() == 1 {
+            return Ok(conditions.pop().unwrap());
+        }
+        Ok(Condition::And(conditions))
+    }
+
+    fn parse_not(&mut self) -> Result<Condition, SqlError> {
+        let position = self.peek().start;
+
+        if self.consume_keyword("NOT") {
+            let condition = self.parse_nested_condition(position, Self::parse_not)?;
+            return Ok(Condition::Not(Box::new(condition)));
+        }
+        self.parse_predicate()
+    }
+
+    /// Parses a condition nested in a parenthesis or a `NOT` operator located at `position`.
+    fn parse_nested_condition(
+        &mut self,
+        position: usize,
+        parse_fn: fn(&mut Self) -> Result<Condition, SqlError>,
+    ) -> Result<Condition, SqlError> {
+        if self.condition_depth >= MAX_CONDITION_DEPTH {
+            return Err(self.error_at(
+                position,
+                format!("conditions cannot be nested more than {MAX_CONDITION_DEPTH} levels deep"),
+            ));
+        }
+        self.condition_depth += 1;
+        let condition_res = parse_fn(self);
+        self.condition_depth -= 1;
+        condition_res
+    }
+
+    fn parse_predicate(&mut self) -> Result<Condition, SqlError> {
+        let position = self.peek().start;
+
+        if self.consume(&TokenKind::LeftParen) {
+            let condition = self.parse_nested_condition(position, Self::parse_or)?;
+            self.expect(&TokenKind::RightParen, "`)`")?;
+            return Ok(condition);
+        }
+        if self.peek_nth(1).kind == TokenKind::LeftParen {
+            return self.parse_function_predicate();
+        }
+        let column = self.parse_column()?;
+
+        let operator_token = self.peek().clone();
+        let operator_opt = match operator_token.kind {
+            TokenKind::Eq => Some(ComparisonOperator::Eq),
+            TokenKind::NotEq => Some(ComparisonOperator::NotEq),
+            TokenKind::Lt => Some(ComparisonOperator::Lt),
+            TokenKind::LtEq => Some(ComparisonOperator::LtEq),
+            TokenKind::Gt => Some(ComparisonOperator::Gt),
+            TokenKind::GtEq => Some(ComparisonOperator::GtEq),
+            _ => None,
+        };
+        if let Some(operator) = operator_opt {
+            self.cursor += 1;
+            let value = self.parse_literal()?;
+            return Ok(Condition::Comparison {
+                column,
+                operator,
+                value,
+            });
+        }
+        if self.consume_keyword("IS") {
+            let negated = self.consume_keyword("NOT");
+            self.expect_keyword("NULL")?;
+            let condition = Condition::IsNull { column };
+            return Ok(negate_if(condition, negated));
+        }
+        let negated = self.consume_keyword("NOT");
+
+        let condition = if self.consume_keyword("BETWEEN") {
+            let lower = self.parse_literal()?;
+            self.expect_keyword("AND")?;
+            let upper = self.parse_literal()?;
+            Condition::Between {
+                column,
+                lower,
+                upper,
+            }
+        } else if self.consume_keyword("IN") {
+            self.expect(&TokenKind::LeftParen, "`(`")?;
+            let values = self.parse_comma_separated(Self::parse_literal)?;
+            self.expect(&TokenKind::RightParen, "`)`")?;
+            Condition::In { column, values }
+        } else if self.consume_keyword("LIKE") {
+            let token = self.next();
+            let TokenKind::String(pattern) = token.kind else {
+                return Err(self.unexpected(&token, "a string pattern"));
+            };
+            if self.peek_keyword("ESCAPE") {
+                return Err(self.unexpected(self.peek(), "end of predicate"));
+            }
+            Condition::Like { column, pattern }
+        } else {
+            let expected = if negated {
+                "`BETWEEN`, `IN`, or `LIKE`"
+            } else {
+                "a comparison operator, `BETWEEN`, `IN`, `IS`, or `LIKE`"
+            };
+            return Err(self.unexpected(self.peek(), expected));
+        };
+        Ok(negate_if(condition, negated))
+    }
+
+    /// Parses the `MATCH(column, 'text')` and `QUERY('text')` predicates.
+    fn parse_function_predicate(&mut self) -> Result<Condition, SqlError> {
+        let token = self.next();
+        let function_name = match &token.kind {
+            TokenKind::Word(word) => word.to_ascii_uppercase(),
+            _ => return Err(self.unexpected(&token, "a condition")),
+        };
+        let condition = match function_name.as_str() {
+            "MATCH" => {
+                self.expect(&TokenKind::LeftParen, "`(`")?;
+                let column = self.parse_column()?;
+                self.expect(&TokenKind::Comma, "`,`")?;
+                let text = self.parse_string()?;
+                Condition::Match { column, text }
+            }
+            "QUERY" => {
+                self.expect(&TokenKind::LeftParen, "`(`")?;
+                let text = self.parse_string()?;
+                Condition::QueryString { text }
+            }
+            _ if is_unsupported_keyword(&function_name) => {
+                return Err(self.unexpected(&token, "a condition"));
+            }
+            _ => {
+                return Err(self.error_at(
+                    token.start,
+                    format!("unsupported function `{function_name}`"),
+                ));
+            }
+        };
+        self.expect(&TokenKind::RightParen, "`)`")?;
+        Ok(condition)
+    }
+
+    fn parse_string(&mut self) -> Result<String, SqlError> {
+        let token = self.next();
+        match token.kind {
+            TokenKind::String(value) => Ok(value),
+            _ => Err(self.unexpected(&token, "a string")),
+        }
+    }
+
+    fn parse_literal(&mut self) -> Result<Literal, SqlError> {
+        let token = self.next();
+        match &token.kind {
+            TokenKind::String(value) => Ok(Literal::String(value.clone())),
+            TokenKind::Number(number) => serde_json::Number::from_str(number)
+                .map(Literal::Number)
+                .map_err(|_| self.error_at(token.start, format!("invalid number `{number}`"))),
+            TokenKind::Word(word) if word.eq_ignore_ascii_case("TRUE") => Ok(Literal::Bool(true)),
+            TokenKind::Word(word) if word.eq_ignore_ascii_case("FALSE") => Ok(Literal::Bool(false)),
+            TokenKind::Word(word) if word.eq_ignore_ascii_case("NULL") => Err(self.error_at(
+                token.start,
+                "comparisons with NULL are not supported, use `IS NULL` or `IS NOT NULL` instead",
+            )),
+            _ => Err(self.unexpected(&token, "a literal value")),
+        }
+    }
+}
+
+fn negate_if(condition: Condition, negated: bool) -> Condition {
+    if negated {
+        Condition::Not(Box::new(condition))
+    } else {
+        condition
+    }
+}
+
+fn is_reserved_keyword(word: &str) -> bool {
+    RESERVED_KEYWORDS
+        .iter()
+        .any(|keyword| keyword.eq_ignore_ascii_case(word))
+        || is_unsupported_keyword(word)
+}
+
+fn is_unsupported_keyword(word: &str) -> bool {
+    UNSUPPORTED_KEYWORDS
+        .iter()
+        .any(|keyword| keyword.eq_ignore_ascii_case(word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, position: usize) -> Column {
+        Column {
+            name: name.to_string(),
+            position,
+        }
+    }
+
+    fn parse_error(sql: &str) -> String {
+        parse_sql(sql).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_parse_select_statement() {
+        let sql = "SELECT service, COUNT(*) AS total, percentile(latency, 99) p99 FROM \"logs-*\" \
+                   WHERE level = 'ERROR' GROUP BY service ORDER BY total DESC, service LIMIT 10;";
+        let statement = parse_sql(sql).unwrap();
+        assert_eq!(statement.index_id_patterns, ["logs-*"]);
+        assert_eq!(statement.select_items.len(), 3);
+        assert_eq!(
+            statement.select_items[0],
+            SelectItem::Expr {
+                expr: Expr::Column(column("service", 7)),
+                alias_opt: None,
+            }
+        );
+        let SelectItem::Expr {
+            expr: Expr::Aggregate(count),
+            alias_opt,
+        } = &statement.select_items[1]
+        else {
+            panic!("expected an aggregate");
+        };
+        assert_eq!(count.function, AggregateFunction::Count);
+        assert_eq!(count.column_opt, None);
+        assert_eq!(count.text, "COUNT(*)");
+        assert_eq!(alias_opt.as_deref(), Some("total"));
+
+        let SelectItem::Expr {
+            expr: Expr::Aggregate(percentile),
+            alias_opt,
+        } = &statement.select_items[2]
+        else {
+            panic!("expected an aggregate");
+        };
+        assert_eq!(percentile.function, AggregateFunction::Percentile);
+        assert_eq!(percentile.column_opt.as_ref().unwrap().name, "latency");
+        assert_eq!(percentile.percent_opt, Some(99.0));
+        assert_eq!(percentile.text, "percentile(latency, 99)");
+        assert_eq!(alias_opt.as_deref(), Some("p99"));
+
+        assert_eq!(
+            statement.where_opt,
+            Some(Condition::Comparison {
+                column: column("level", 83),
+                operator: ComparisonOperator::Eq,
+                value: Literal::String("ERROR".to_string()),
+            })
+        );
+        assert_eq!(statement.group_by, [column("service", 108)]);
+        assert_eq!(statement.order_by.len(), 2);
+        assert!(statement.order_by[0].descending);
+        assert!(!statement.order_by[1].descending);
+        assert_eq!(statement.limit_opt, Some(10));
+    }
+
+    #[test]
+    fn test_parse_where_conditions() {
+        let sql = "SELECT * FROM logs WHERE NOT a = 1 OR b BETWEEN -1.5 AND 2e3 AND c NOT IN \
+                   ('x', 'it''s') OR d IS NOT NULL OR `e f` LIKE 'ab%' OR MATCH(g, 'foo') OR \
+                   QUERY('h:bar') OR (i <> true AND j >= 3)";
+        let statement = parse_sql(sql).unwrap();
+        let Some(Condition::Or(conditions)) = statement.where_opt else {
+            panic!("expected OR");
+        };
+        // The trailing parenthesized AND is not merged into the OR chain.
+        assert_eq!(conditions.len(), 7);
+
+        assert!(matches!(&conditions[0], Condition::Not(condition)
+            if matches!(**condition, Condition::Comparison { .. })));
+        let Condition::And(and_conditions) = &conditions[1] else {
+            panic!("expected AND");
+        };
+        let [between, not_in] = &and_conditions[..] else {
+            panic!("expected two operands");
+        };
+        assert_eq!(
+            *between,
+            Condition::Between {
+                column: column("b", 38),
+                lower: Literal::Number(serde_json::Number::from_f64(-1.5).unwrap()),
+                upper: Literal::Number(serde_json::Number::from_f64(2000.0).unwrap()),
+            }
+        );
+        assert_eq!(
+            *not_in,
+            Condition::Not(Box::new(Condition::In {
+                column: column("c", 65),
+                values: vec![
+                    Literal::String("x".to_string()),
+                    Literal::String("it's".to_string()),
+                ],
+            }))
+        );
+        assert!(matches!(&conditions[2], Condition::Not(condition)
+            if matches!(**condition, Condition::IsNull { .. })));
+        assert!(matches!(&conditions[3], Condition::Like { column, pattern }
+            if column.name == "e f" && pattern == "ab%"));
+        assert!(matches!(&conditions[4], Condition::Match { column, text }
+            if column.name == "g" && text == "foo"));
+        assert_eq!(
+            conditions[5],
+            Condition::QueryString {
+                text: "h:bar".to_string()
+            }
+        );
+        assert!(matches!(&conditions[6], Condition::And(conditions) if conditions.len() == 2));
+    }
+
+    #[test]
+    fn test_parse_flattens_condition_chains() {
+        let sql = "SELECT * FROM logs WHERE a = 1 AND (b = 2 AND c = 3) AND d = 4 OR e = 5 OR (f \
+                   = 6 OR g = 7)";
+        let statement = parse_sql(sql).unwrap();
+        let Some(Condition::Or(conditions)) = statement.where_opt else {
+            panic!("expected OR");
+        };
+        assert_eq!(conditions.len(), 4);
+        assert!(matches!(&conditions[0], Condition::And(conditions) if conditions.len() == 4));
+
+        let long_chain = vec!["a = 1"; 100_000].join(" AND ");
+        let sql = format!("SELECT * FROM logs WHERE {long_chain}");
+        let statement = parse_sql(&sql).unwrap();
+        assert!(
+            matches!(statement.where_opt, Some(Condition::And(conditions))
+            if conditions.len() == 100_000)
+        );
+    }
+
+    #[test]
+    fn test_parse_deeply_nested_conditions() {
+        let sql = format!(
+            "SELECT * FROM logs WHERE {}a = 1{}",
+            "(".repeat(MAX_CONDITION_DEPTH),
+            ")".repeat(MAX_CONDITION_DEPTH)
+        );
+        parse_sql(&sql).unwrap();
+
+        let sql = format!(
+            "SELECT * FROM logs WHERE {}a = 1{}",
+            "(".repeat(100_000),
+            ")".repeat(100_000)
+        );
+        assert_eq!(
+            parse_error(&sql),
+            "conditions cannot be nested more than 32 levels deep at line 1, column 58"
+        );
+        let sql = format!("SELECT * FROM logs WHERE {}a = 1", "NOT ".repeat(100_000));
+        assert_eq!(
+            parse_error(&sql),
+            "conditions cannot be nested more than 32 levels deep at line 1, column 154"
+        );
+        let sql = format!(
+            "SELECT * FROM logs WHERE {}a = 1{}",
+            "NOT (".repeat(100_000),
+            ")".repeat(100_000)
+        );
+        assert_eq!(
+            parse_error(&sql),
+            "conditions cannot be nested more than 32 levels deep at line 1, column 106"
+        );
+    }
+
+    #[test]
+    fn test_parse_sql_errors() {
+        assert_eq!(
+            parse_error("SELECT a FROM logs\nLEFT JOIN traces"),
+            "`LEFT` is not supported at line 2, column 1"
+        );
+        assert_eq!(
+            parse_error("SELECT DISTINCT a FROM logs"),
+            "`DISTINCT` is not supported at line 1, column 8"
+        );
+        assert_eq!(
+            parse_error("SELECT a FROM logs GROUP BY a HAVING COUNT(*) > 1"),
+            "`HAVING` is not supported at line 1, column 31"
+        );
+        assert_eq!(
+            parse_error("SELECT a FROM logs LIMIT 10 OFFSET 5"),
+            "`OFFSET` is not supported at line 1, column 29"
+        );
+        assert_eq!(
+            parse_error("SELECT UPPER(a) FROM logs"),
+            "unsupported function `UPPER` at line 1, column 8"
+        );
+        assert_eq!(
+            parse_error("SELECT SUM(*) FROM logs"),
+            "`*` is only supported by COUNT, not by SUM at line 1, column 12"
+        );
+        assert_eq!(
+            parse_error("SELECT a FROM (SELECT b FROM logs)"),
+            "subqueries are not supported at line 1, column 15"
+        );
+        assert_eq!(
+            parse_error("SELECT a FROM logs WHERE a = NULL"),
+            "comparisons with NULL are not supported, use `IS NULL` or `IS NOT NULL` instead at \
+             line 1, column 30"
+        );
+        assert_eq!(
+            parse_error("SELECT a FROM logs WHERE 1 = a"),
+            "unexpected `1`, expected a column name at line 1, column 26"
+        );
+        assert_eq!(
+            parse_error("SELECT a FROM logs WHERE a = 'b"),
+            "unterminated `'` at line 1, column 30"
+        );
+        assert_eq!(
+            parse_error("SELECT a FROM logs WHERE"),
+            "unexpected end of statement, expected a column name at line 1, column 25"
+        );
+        assert_eq!(
+            parse_error("SELECT a, FROM logs"),
+            "unexpected `FROM`, expected a column name at line 1, column 11"
+        );
+        assert_eq!(
+            parse_error("SELECT PERCENTILE(a, 101) FROM logs"),
+            "expected a percentile between 0 and 100 at line 1, column 22"
+        );
+        assert_eq!(
+            parse_error("SELECT a FROM logs WHERE a ~ 1"),
+            "unexpected character `~` at line 1, column 28"
+        );
+        assert_eq!(
+            parse_error("UPDATE logs SET a = 1"),
+            "`UPDATE` is not supported at line 1, column 1"
+        );
+    }
+}
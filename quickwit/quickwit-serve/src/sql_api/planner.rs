@@ -0,0 +1,934 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Bound;
+
+use quickwit_proto::search::{CountHits, SearchRequest, SortField, SortOrder};
+use quickwit_query::query_ast::{
+    BoolQuery, FieldPresenceQuery, FullTextParams, FullTextQuery, QueryAst, RangeQuery, TermQuery,
+    TermSetQuery, WildcardQuery, query_ast_from_user_text,
+};
+use quickwit_query::{BooleanOperand, JsonLiteral, MatchAllOrNone};
+use quickwit_search::{SearchError, SearchResponseRest};
+use serde_json::{Map as JsonMap, Value as JsonValue, json};
+
+use super::parser::{
+    Aggregate, AggregateFunction, Column, ComparisonOperator, Condition, Expr, Literal, SelectItem,
+    SelectStatement, SqlError,
+};
+use super::response::SqlResponse;
+
+/// Maximum number of rows returned when the statement has no `LIMIT` clause.
+const DEFAULT_LIMIT: u64 = 1_000;
+
+/// Maximum number of distinct values of a `GROUP BY` column.
+const MAX_GROUPS: u32 = 10_000;
+
+/// A SQL statement compiled to a search request, along with how to turn the search response into
+/// a table.
+pub(crate) struct SqlPlan {
+    pub search_request: SearchRequest,
+    table_plan: TablePlan,
+}
+
+enum TablePlan {
+    /// The rows are the documents matching the query.
+    Hits(HitsPlan),
+    /// The rows are the groups computed by the aggregations.
+    Aggregation(AggregationPlan),
+}
+
+struct HitsPlan {
+    /// Names of the columns and the fields they are read from. `None` stands for `*`.
+    columns: Vec<Option<(String, String)>>,
+}
+
+/// Where the value of a column is read from in an aggregation row.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Slot {
+    Group(usize),
+    DocCount,
+    Metric(usize),
+}
+
+#[derive(Debug, PartialEq)]
+struct Metric {
+    function: AggregateFunction,
+    field: String,
+    percent_opt: Option<f64>,
+}
+
+struct AggregationPlan {
+    group_by: Vec<String>,
+    metrics: Vec<Metric>,
+    columns: Vec<(String, Slot)>,
+    order_by: Vec<(Slot, bool)>,
+    limit: usize,
+}
+
+struct AggregationRow {
+    keys: Vec<JsonValue>,
+    doc_count: JsonValue,
+    metric_values: Vec<JsonValue>,
+}
+
+impl AggregationRow {
+    fn value(&self, slot: Slot) -> &JsonValue {
+        match slot {
+            Slot::Group(group_idx) => &self.keys[group_idx],
+            Slot::DocCount => &self.doc_count,
+            Slot::Metric(metric_idx) => &self.metric_values[metric_idx],
+        }
+    }
+}
+
+impl SqlPlan {
+    /// Compiles a statement parsed from `sql`.
+    ///
+    /// Statements with aggregate functions or a `GROUP BY` clause are compiled to an
+    /// aggregation request. The others return the matching documents.
+    pub fn build(sql: &str, statement: SelectStatement) -> Result<Self, SqlError> {
+        let query_ast = match &statement.where_opt {
+            Some(condition) => condition_to_query_ast(condition),
+            None => QueryAst::MatchAll,
+        };
+        let search_request = SearchRequest {
+            index_id_patterns: statement.index_id_patterns.clone(),
+            query_ast: serde_json::to_string(&query_ast)
+                .expect("query AST should be JSON serializable"),
+            ..Default::default()
+        };
+        let has_aggregates = statement.select_items.iter().any(|select_item| {
+            matches!(
+                select_item,
+                SelectItem::Expr {
+                    expr: Expr::Aggregate(_),
+                    ..
+                }
+            )
+        }) || statement
+            .order_by
+            .iter()
+            .any(|order_by_item| matches!(order_by_item.expr, Expr::Aggregate(_)));
+
+        if has_aggregates || !statement.group_by.is_empty() {
+            build_aggregation_plan(sql, statement, search_request)
+        } else {
+            build_hits_plan(sql, statement, search_request)
+        }
+    }
+
+    /// Turns the response of the search request into a table.
+    pub fn build_response(
+        &self,
+        search_response: SearchResponseRest,
+    ) -> Result<SqlResponse, SearchError> {
+        match &self.table_plan {
+            TablePlan::Hits(hits_plan) => Ok(hits_plan.build_response(search_response)),
+            TablePlan::Aggregation(aggregation_plan) => {
+                let aggregations = match search_response.aggregations {
+                    Some(aggregations) => serde_json::to_value(aggregations)?,
+                    None => JsonValue::Null,
+                };
+                aggregation_plan.build_response(search_response.num_hits, &aggregations)
+            }
+        }
+    }
+}
+
+fn build_hits_plan(
+    sql: &str,
+    statement: SelectStatement,
+    mut search_request: SearchRequest,
+) -> Result<SqlPlan, SqlError> {
+    let mut columns = Vec::with_capacity(statement.select_items.len());
+    let mut aliases: HashMap<String, String> = HashMap::new();
+
+    for select_item in statement.select_items {
+        match select_item {
+            SelectItem::Wildcard { .. } => columns.push(None),
+            SelectItem::Expr {
+                expr: Expr::Column(column),
+                alias_opt,
+            } => {
+                let name = alias_opt.unwrap_or_else(|| column.name.clone());
+                aliases.insert(name.clone(), column.name.clone());
+                columns.push(Some((name, column.name)));
+            }
+            SelectItem::Expr {
+                expr: Expr::Aggregate(aggregate),
+                ..
+            } => {
+                return Err(SqlError::at(
+                    sql,
+                    aggregate.position,
+                    "unexpected aggregate function",
+                ));
+            }
+        }
+    }
+    for order_by_item in statement.order_by {
+        let column = match order_by_item.expr {
+            Expr::Column(column) => column,
+            Expr::Aggregate(aggregate) => {
+                return Err(SqlError::at(
+                    sql,
+                    aggregate.position,
+                    "unexpected aggregate function",
+                ));
+            }
+        };
+        let field_name = aliases.get(&column.name).cloned().unwrap_or(column.name);
+        let sort_order = if order_by_item.descending {
+            SortOrder::Desc
+        } else {
+            SortOrder::Asc
+        };
+        search_request.sort_fields.push(SortField {
+            field_name,
+            sort_order: sort_order as i32,
+            sort_datetime_format: None,
+        });
+    }
+    search_request.max_hits = statement.limit_opt.unwrap_or(DEFAULT_LIMIT);
+    search_request.count_hits = CountHits::Underestimate as i32;
+
+    Ok(SqlPlan {
+        search_request,
+        table_plan: TablePlan::Hits(HitsPlan { columns }),
+    })
+}
+
+fn build_aggregation_plan(
+    sql: &str,
+    statement: SelectStatement,
+    mut search_request: SearchRequest,
+) -> Result<SqlPlan, SqlError> {
+    let group_by: Vec<String> = statement
+        .group_by
+        .iter()
+        .map(|column| column.name.clone())
+        .collect();
+    let mut metrics = Vec::new();
+    let mut columns = Vec::with_capacity(statement.select_items.len());
+
+    for select_item in statement.select_items {
+        let (expr, alias_opt) = match select_item {
+            SelectItem::Wildcard { position } => {
+                return Err(SqlError::at(
+                    sql,
+                    position,
+                    "`*` cannot be selected along with aggregate functions or a GROUP BY clause",
+                ));
+            }
+            SelectItem::Expr { expr, alias_opt } => (expr, alias_opt),
+        };
+        let column = match expr {
+            Expr::Column(column) => {
+                let slot = group_slot(sql, &group_by, &column)?;
+                (alias_opt.unwrap_or(column.name), slot)
+            }
+            Expr::Aggregate(aggregate) => {
+                let slot = aggregate_slot(&mut metrics, &aggregate);
+                (alias_opt.unwrap_or(aggregate.text), slot)
+            }
+        };
+        columns.push(column);
+    }
+    let mut order_by = Vec::with_capacity(statement.order_by.len());
+
+    for order_by_item in statement.order_by {
+        let slot = match &order_by_item.expr {
+            Expr::Column(column) => {
+                if let Some((_, slot)) = columns.iter().find(|(name, _)| *name == column.name) {
+                    *slot
+                } else {
+                    group_slot(sql, &group_by, column)?
+                }
+            }
+            Expr::Aggregate(aggregate) => aggregate_slot(&mut metrics, aggregate),
+        };
+        order_by.push((slot, order_by_item.descending));
+    }
+    let mut aggregations = JsonMap::new();
+
+    for (metric_idx, metric) in metrics.iter().enumerate() {
+        aggregations.insert(format!("m{metric_idx}"), metric.aggregation());
+    }
+    for (group_idx, field) in group_by.iter().enumerate().rev() {
+        let mut terms_aggregation = json!({
+            "terms": {
+                "field": field,
+                "size": MAX_GROUPS,
+            }
+        });
+        let sub_aggregations = std::mem::take(&mut aggregations);
+        if !sub_aggregations.is_empty() {
+            terms_aggregation["aggs"] = JsonValue::Object(sub_aggregations);
+        }
+        aggregations.insert(format!("g{group_idx}"), terms_aggregation);
+    }
+    if !aggregations.is_empty() {
+        search_request.aggregation_request = Some(JsonValue::Object(aggregations).to_string());
+    }
+    search_request.max_hits = 0;
+
+    let aggregation_plan = AggregationPlan {
+        group_by,
+        metrics,
+        columns,
+        order_by,
+        limit: statement.limit_opt.unwrap_or(DEFAULT_LIMIT) as usize,
+    };
+    Ok(SqlPlan {
+        search_request,
+        table_plan: TablePlan::Aggregation(aggregation_plan),
+    })
+}
+
+fn group_slot(sql: &str, group_by: &[String], column: &Column) -> Result<Slot, SqlError> {
+    group_by
+        .iter()
+        .position(|group_by_column| *group_by_column == column.name)
+        .map(Slot::Group)
+        .ok_or_else(|| {
+            SqlError::at(
+                sql,
+                column.position,
+                format!(
+                    "column `{}` must appear in the GROUP BY clause or be used in an aggregate \
+                     function",
+                    column.name
+                ),
+            )
+        })
+}
+
+/// Returns the slot of the value of an aggregate function, registering a new metric if needed.
+fn aggregate_slot(metrics: &mut Vec<Metric>, aggregate: &Aggregate) -> Slot {
+    let Some(column) = &aggregate.column_opt else {
+        // `COUNT(*)` is the document count of the group.
+        return Slot::DocCount;
+    };
+    let metric = Metric {
+        function: aggregate.function,
+        field: column.name.clone(),
+        percent_opt: aggregate.percent_opt,
+    };
+    if let Some(metric_idx) = metrics.iter().position(|other| *other == metric) {
+        return Slot::Metric(metric_idx);
+    }
+    metrics.push(metric);
+    Slot::Metric(metrics.len() - 1)
+}
+
+impl Metric {
+    fn aggregation(&self) -> JsonValue {
+        let field = &self.field;
+        match self.function {
+            AggregateFunction::Count => json!({"value_count": {"field": field}}),
+            AggregateFunction::Sum => json!({"sum": {"field": field}}),
+            AggregateFunction::Avg => json!({"avg": {"field": field}}),
+            AggregateFunction::Min => json!({"min": {"field": field}}),
+            AggregateFunction::Max => json!({"max": {"field": field}}),
+            AggregateFunction::Percentile => json!({
+                "percentiles": {
+                    "field": field,
+                    "percents": [self.percent_opt.unwrap_or(50.0)],
+                    "keyed": false,
+                }
+            }),
+        }
+    }
+
+    fn extract_value(&self, metric_result: &JsonValue) -> JsonValue {
+        match self.function {
+            AggregateFunction::Count => {
+                let count = metric_result["value"].as_f64().unwrap_or(0.0) as u64;
+                JsonValue::from(count)
+            }
+            AggregateFunction::Percentile => match &metric_result["values"] {
+                JsonValue::Array(entries) => entries
+                    .first()
+                    .map(|entry| entry["value"].clone())
+                    .unwrap_or_default(),
+                JsonValue::Object(entries) => entries.values().next().cloned().unwrap_or_default(),
+                _ => JsonValue::Null,
+            },
+            _ => metric_result["value"].clone(),
+        }
+    }
+}
+
+impl HitsPlan {
+    fn build_response(&self, search_response: SearchResponseRest) -> SqlResponse {
+        let mut column_names = Vec::new();
+        let mut fields = Vec::new();
+
+        for column in &self.columns {
+            match column {
+                Some((name, field)) => {
+                    column_names.push(name.clone());
+                    fields.push(field.clone());
+                }
+                None => {
+                    let field_names: BTreeSet<&String> = search_response
+                        .hits
+                        .iter()
+                        .filter_map(JsonValue::as_object)
+                        .flat_map(|document| document.keys())
+                        .collect();
+                    for field_name in field_names {
+                        column_names.push(field_name.clone());
+                        fields.push(field_name.clone());
+                    }
+                }
+            }
+        }
+        let rows = search_response
+            .hits
+            .iter()
+            .map(|document| {
+                fields
+                    .iter()
+                    .map(|field| lookup_field(document, field).cloned().unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+        SqlResponse::new(column_names, rows)
+    }
+}
+
+impl AggregationPlan {
+    /// Builds the table from the aggregation results, rendered as in the search API.
+    fn build_response(
+        &self,
+        num_hits: u64,
+        aggregations: &JsonValue,
+    ) -> Result<SqlResponse, SearchError> {
+        let mut rows = Vec::new();
+        self.collect_rows(aggregations, &mut Vec::new(), num_hits, &mut rows)?;
+
+        rows.sort_by(|left, right| {
+            self.order_by
+                .iter()
+                .map(|(slot, descending)| {
+                    compare_values(left.value(*slot), right.value(*slot), *descending)
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+        rows.truncate(self.limit);
+
+        let column_names = self.columns.iter().map(|(name, _)| name.clone()).collect();
+        let rows = rows
+            .iter()
+            .map(|row| {
+                self.columns
+                    .iter()
+                    .map(|(_, slot)| row.value(*slot).clone())
+                    .collect()
+            })
+            .collect();
+        Ok(SqlResponse::new(column_names, rows))
+    }
+
+    /// Walks down the nested `terms` aggregations, emitting one row per leaf bucket.
+    fn collect_rows(
+        &self,
+        aggregations: &JsonValue,
+        keys: &mut Vec<JsonValue>,
+        doc_count: u64,
+        rows: &mut Vec<AggregationRow>,
+    ) -> Result<(), SearchError> {
+        let group_idx = keys.len();
+
+        if group_idx == self.group_by.len() {
+            let metric_values = self
+                .metrics
+                .iter()
+                .enumerate()
+                .map(|(metric_idx, metric)| {
+                    metric.extract_value(&aggregations[format!("m{metric_idx}")])
+                })
+                .collect();
+            rows.push(AggregationRow {
+                keys: keys.clone(),
+                doc_count: JsonValue::from(doc_count),
+                metric_values,
+            });
+            return Ok(());
+        }
+        let terms_result = &aggregations[format!("g{group_idx}")];
+
+        if terms_result["sum_other_doc_count"].as_u64().unwrap_or(0) > 0 {
+            return Err(SearchError::InvalidQuery(format!(
+                "GROUP BY column `{}` has more than {MAX_GROUPS} distinct values",
+                self.group_by[group_idx]
+            )));
+        }
+        for bucket in terms_result["buckets"].as_array().into_iter().flatten() {
+            let key = bucket
+                .get("key_as_string")
+                .or_else(|| bucket.get("key"))
+                .cloned()
+                .unwrap_or_default();
+            let bucket_doc_count = bucket["doc_count"].as_u64().unwrap_or(0);
+            keys.push(key);
+            self.collect_rows(bucket, keys, bucket_doc_count, rows)?;
+            keys.pop();
+        }
+        Ok(())
+    }
+}
+
+/// Looks up a field in a document, following dotted paths into nested objects.
+pub(crate) fn lookup_field<'a>(document: &'a JsonValue, field: &str) -> Option<&'a JsonValue> {
+    if let Some(value) = document.get(field) {
+        return Some(value);
+    }
+    let (head, tail) = field.split_once('.')?;
+    lookup_field(document.get(head)?, tail)
+}
+
+/// Compares two values of a column. Null values always come last.
+fn compare_values(left: &JsonValue, right: &JsonValue, descending: bool) -> Ordering {
+    let ordering = match (left, right) {
+        (JsonValue::Null, JsonValue::Null) => return Ordering::Equal,
+        (JsonValue::Null, _) => return Ordering::Greater,
+        (_, JsonValue::Null) => return Ordering::Less,
+        (JsonValue::Number(left), JsonValue::Number(right)) => left
+            .as_f64()
+            .partial_cmp(&right.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (JsonValue::String(left), JsonValue::String(right)) => left.cmp(right),
+        (JsonValue::Bool(left), JsonValue::Bool(right)) => left.cmp(right),
+        _ => left.to_string().cmp(&right.to_string()),
+    };
+    if descending {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
+fn condition_to_query_ast(condition: &Condition) -> QueryAst {
+    match condition {
+        Condition::And(conditions) => BoolQuery {
+            must: conditions.iter().map(condition_to_query_ast).collect(),
+            ..Default::default()
+        }
+        .into(),
+        Condition::Or(conditions) => BoolQuery {
+            should: conditions.iter().map(condition_to_query_ast).collect(),
+            ..Default::default()
+        }
+        .into(),
+        Condition::Not(condition) => negate(condition_to_query_ast(condition)),
+        Condition::Comparison {
+            column,
+            operator,
+            value,
+        } => {
+            let field = column.name.clone();
+            let (lower_bound, upper_bound) = match operator {
+                ComparisonOperator::Eq | ComparisonOperator::NotEq => {
+                    let term_query: QueryAst = TermQuery {
+                        field,
+                        value: literal_to_term(value),
+                    }
+                    .into();
+                    if *operator == ComparisonOperator::NotEq {
+                        return negate(term_query);
+                    }
+                    return term_query;
+                }
+                ComparisonOperator::Lt => {
+                    (Bound::Unbounded, Bound::Excluded(literal_to_json(value)))
+                }
+                ComparisonOperator::LtEq => {
+                    (Bound::Unbounded, Bound::Included(literal_to_json(value)))
+                }
+                ComparisonOperator::Gt => {
+                    (Bound::Excluded(literal_to_json(value)), Bound::Unbounded)
+                }
+                ComparisonOperator::GtEq => {
+                    (Bound::Included(literal_to_json(value)), Bound::Unbounded)
+                }
+            };
+            RangeQuery {
+                field,
+                lower_bound,
+                upper_bound,
+            }
+            .into()
+        }
+        Condition::Between {
+            column,
+            lower,
+            upper,
+        } => RangeQuery {
+            field: column.name.clone(),
+            lower_bound: Bound::Included(literal_to_json(lower)),
+            upper_bound: Bound::Included(literal_to_json(upper)),
+        }
+        .into(),
+        Condition::In { column, values } => {
+            let terms = values.iter().map(literal_to_term).collect();
+            TermSetQuery {
+                terms_per_field: HashMap::from([(column.name.clone(), terms)]),
+            }
+            .into()
+        }
+        Condition::IsNull { column } => negate(
+            FieldPresenceQuery {
+                field: column.name.clone(),
+            }
+            .into(),
+        ),
+        Condition::Like { column, pattern } => WildcardQuery {
+            field: column.name.clone(),
+            value: like_pattern_to_wildcard(pattern),
+            case_insensitive: false,
+            lenient: false,
+        }
+        .into(),
+        Condition::Match { column, text } => FullTextQuery {
+            field: column.name.clone(),
+            text: text.clone(),
+            params: FullTextParams {
+                tokenizer: None,
+                mode: BooleanOperand::Or.into(),
+                zero_terms_query: MatchAllOrNone::MatchNone,
+            },
+            lenient: false,
+        }
+        .into(),
+        Condition::QueryString { text } => query_ast_from_user_text(text, None),
+    }
+}
+
+fn negate(query_ast: QueryAst) -> QueryAst {
+    BoolQuery {
+        must_not: vec![query_ast],
+        ..Default::default()
+    }
+    .into()
+}
+
+fn literal_to_term(literal: &Literal) -> String {
+    match literal {
+        Literal::String(value) => value.clone(),
+        Literal::Number(number) => number.to_string(),
+        Literal::Bool(value) => value.to_string(),
+    }
+}
+
+fn literal_to_json(literal: &Literal) -> JsonLiteral {
+    match literal {
+        Literal::String(value) => JsonLiteral::String(value.clone()),
+        Literal::Number(number) => JsonLiteral::Number(number.clone()),
+        Literal::Bool(value) => JsonLiteral::Bool(*value),
+    }
+}
+
+/// Converts a `LIKE` pattern to a wildcard pattern: `%` matches any sequence of characters and
+/// `_` matches a single character.
+fn like_pattern_to_wildcard(pattern: &str) -> String {
+    let mut wildcard = String::with_capacity(pattern.len());
+    for character in pattern.chars() {
+        match character {
+            '%' => wildcard.push('*'),
+            '_' => wildcard.push('?'),
+            '*' | '?' | '\\' => {
+                wildcard.push('\\');
+                wildcard.push(character);
+            }
+            _ => wildcard.push(character),
+        }
+    }
+    wildcard
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql_api::parser::parse_sql;
+
+    fn build_plan(sql: &str) -> Result<SqlPlan, SqlError> {
+        let statement = parse_sql(sql)?;
+        SqlPlan::build(sql, statement)
+    }
+
+    fn aggregation_plan(sql_plan: &SqlPlan) -> &AggregationPlan {
+        match &sql_plan.table_plan {
+            TablePlan::Aggregation(aggregation_plan) => aggregation_plan,
+            TablePlan::Hits(_) => panic!("expected an aggregation plan"),
+        }
+    }
+
+    #[test]
+    fn test_plan_where_clause() {
+        let sql_plan = build_plan(
+            "SELECT a FROM logs WHERE a != 'x' AND (b > 1 OR c LIKE 'a_b%*') AND d IS NULL",
+        )
+        .unwrap();
+        let query_ast: QueryAst = serde_json::from_str(&sql_plan.search_request.query_ast).unwrap();
+        // The AND chain is compiled to a single boolean query.
+        let expected_query_ast: QueryAst = BoolQuery {
+            must: vec![
+                negate(
+                    TermQuery {
+                        field: "a".to_string(),
+                        value: "x".to_string(),
+                    }
+                    .into(),
+                ),
+                BoolQuery {
+                    should: vec![
+                        RangeQuery {
+                            field: "b".to_string(),
+                            lower_bound: Bound::Excluded(JsonLiteral::Number(1.into())),
+                            upper_bound: Bound::Unbounded,
+                        }
+                        .into(),
+                        WildcardQuery {
+                            field: "c".to_string(),
+                            value: "a?b*\\*".to_string(),
+                            case_insensitive: false,
+                            lenient: false,
+                        }
+                        .into(),
+                    ],
+                    ..Default::default()
+                }
+                .into(),
+                negate(
+                    FieldPresenceQuery {
+                        field: "d".to_string(),
+                    }
+                    .into(),
+                ),
+            ],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(query_ast, expected_query_ast);
+        assert_eq!(sql_plan.search_request.max_hits, DEFAULT_LIMIT);
+    }
+
+    #[test]
+    fn test_plan_deeply_nested_where_clause() {
+        let long_chain = vec!["a = 1"; 100_000].join(" OR ");
+        let sql_plan = build_plan(&format!("SELECT a FROM logs WHERE {long_chain}")).unwrap();
+        let query_ast: QueryAst = serde_json::from_str(&sql_plan.search_request.query_ast).unwrap();
+        let QueryAst::Bool(bool_query) = query_ast else {
+            panic!("expected a boolean query");
+        };
+        assert_eq!(bool_query.should.len(), 100_000);
+
+        let sql = format!(
+            "SELECT a FROM logs WHERE {}a = 1{}",
+            "NOT (".repeat(16),
+            ")".repeat(16)
+        );
+        let sql_plan = build_plan(&sql).unwrap();
+        serde_json::from_str::<QueryAst>(&sql_plan.search_request.query_ast).unwrap();
+
+        let sql = format!(
+            "SELECT a FROM logs WHERE {}a = 1{}",
+            "(".repeat(100_000),
+            ")".repeat(100_000)
+        );
+        build_plan(&sql).unwrap_err();
+    }
+
+    #[test]
+    fn test_plan_aggregation_request() {
+        let sql_plan = build_plan(
+            "SELECT service, host, COUNT(*), AVG(latency) AS avg_latency FROM logs GROUP BY \
+             service, host ORDER BY MAX(latency) DESC, avg_latency",
+        )
+        .unwrap();
+        assert_eq!(sql_plan.search_request.max_hits, 0);
+
+        let aggregation_request: JsonValue = serde_json::from_str(
+            sql_plan
+                .search_request
+                .aggregation_request
+                .as_ref()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            aggregation_request,
+            json!({
+                "g0": {
+                    "terms": {"field": "service", "size": MAX_GROUPS},
+                    "aggs": {
+                        "g1": {
+                            "terms": {"field": "host", "size": MAX_GROUPS},
+                            "aggs": {
+                                "m0": {"avg": {"field": "latency"}},
+                                "m1": {"max": {"field": "latency"}},
+                            }
+                        }
+                    }
+                }
+            })
+        );
+        let aggregation_plan = aggregation_plan(&sql_plan);
+        assert_eq!(
+            aggregation_plan.columns,
+            [
+                ("service".to_string(), Slot::Group(0)),
+                ("host".to_string(), Slot::Group(1)),
+                ("COUNT(*)".to_string(), Slot::DocCount),
+                ("avg_latency".to_string(), Slot::Metric(0)),
+            ]
+        );
+        assert_eq!(
+            aggregation_plan.order_by,
+            [(Slot::Metric(1), true), (Slot::Metric(0), false)]
+        );
+    }
+
+    #[test]
+    fn test_plan_count_without_group_by() {
+        let sql_plan = build_plan("SELECT COUNT(*) FROM logs WHERE level = 'ERROR'").unwrap();
+        assert_eq!(sql_plan.search_request.max_hits, 0);
+        assert!(sql_plan.search_request.aggregation_request.is_none());
+
+        let sql_response = aggregation_plan(&sql_plan)
+            .build_response(42, &JsonValue::Null)
+            .unwrap();
+        assert_eq!(sql_response.rows, [[json!(42)]]);
+    }
+
+    #[test]
+    fn test_plan_aggregation_errors() {
+        let error = build_plan("SELECT service, host FROM logs GROUP BY service")
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "column `host` must appear in the GROUP BY clause or be used in an aggregate function \
+             at line 1, column 17"
+        );
+        let error = build_plan("SELECT *, COUNT(*) FROM logs").err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "`*` cannot be selected along with aggregate functions or a GROUP BY clause at line \
+             1, column 8"
+        );
+    }
+
+    #[test]
+    fn test_aggregation_response() {
+        let sql_plan = build_plan(
+            "SELECT service, COUNT(*) AS total, COUNT(latency), PERCENTILE(latency, 95) FROM logs \
+             GROUP BY service ORDER BY total DESC, service LIMIT 2",
+        )
+        .unwrap();
+        let aggregations = json!({
+            "g0": {
+                "buckets": [
+                    {
+                        "key": "api",
+                        "doc_count": 3,
+                        "m0": {"value": 3.0},
+                        "m1": {"values": [{"key": 95.0, "value": 120.0}]},
+                    },
+                    {
+                        "key": "db",
+                        "doc_count": 5,
+                        "m0": {"value": 4.0},
+                        "m1": {"values": [{"key": 95.0, "value": 80.5}]},
+                    },
+                    {
+                        "key": "auth",
+                        "doc_count": 3,
+                        "m0": {"value": 0.0},
+                        "m1": {"values": [{"key": 95.0, "value": null}]},
+                    },
+                ],
+                "sum_other_doc_count": 0,
+            }
+        });
+        let sql_response = aggregation_plan(&sql_plan)
+            .build_response(11, &aggregations)
+            .unwrap();
+        let column_names: Vec<&str> = sql_response
+            .columns
+            .iter()
+            .map(|column| column.name.as_str())
+            .collect();
+        assert_eq!(
+            column_names,
+            [
+                "service",
+                "total",
+                "COUNT(latency)",
+                "PERCENTILE(latency, 95)"
+            ]
+        );
+        assert_eq!(
+            sql_response.rows,
+            [
+                [json!("db"), json!(5), json!(4), json!(80.5)],
+                [json!("api"), json!(3), json!(3), json!(120.0)],
+            ]
+        );
+
+        let too_many_groups = json!({"g0": {"buckets": [], "sum_other_doc_count": 10}});
+        let error = aggregation_plan(&sql_plan)
+            .build_response(11, &too_many_groups)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "GROUP BY column `service` has more than 10000 distinct values"
+        );
+    }
+
+    #[test]
+    fn test_hits_response() {
+        let sql_plan = build_plan("SELECT a.b, * FROM logs").unwrap();
+        let search_response = SearchResponseRest {
+            num_hits: 2,
+            hits: vec![json!({"a": {"b": 1}, "c": "x"}), json!({"a.b": 2})],
+            snippets: None,
+            elapsed_time_micros: 0,
+            errors: Vec::new(),
+            aggregations: None,
+            timed_out: false,
+            profile: None,
+        };
+        let sql_response = sql_plan.build_response(search_response).unwrap();
+        let column_names: Vec<&str> = sql_response
+            .columns
+            .iter()
+            .map(|column| column.name.as_str())
+            .collect();
+        assert_eq!(column_names, ["a.b", "a", "a.b", "c"]);
+        assert_eq!(
+            sql_response.rows,
+            [
+                [json!(1), json!({"b": 1}), json!(1), json!("x")],
+                [json!(2), JsonValue::Null, json!(2), JsonValue::Null],
+            ]
+        );
+    }
+}
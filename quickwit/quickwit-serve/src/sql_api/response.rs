@@ -0,0 +1,151 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+/// Tabular result of a SQL statement, in the format of the Elasticsearch SQL API.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct SqlResponse {
+    pub columns: Vec<SqlColumn>,
+    #[schema(value_type = Vec<Vec<Object>>)]
+    pub rows: Vec<Vec<JsonValue>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct SqlColumn {
+    pub name: String,
+    /// Type of the values of the column: `keyword`, `long`, `double`, `boolean`, `object`, or
+    /// `null` if the column only contains null values.
+    #[serde(rename = "type")]
+    #[schema(value_type = String)]
+    pub column_type: &'static str,
+}
+
+impl SqlResponse {
+    /// Builds a response, inferring the type of the columns from their values.
+    pub(crate) fn new(column_names: Vec<String>, rows: Vec<Vec<JsonValue>>) -> Self {
+        let columns = column_names
+            .into_iter()
+            .enumerate()
+            .map(|(column_idx, name)| SqlColumn {
+                name,
+                column_type: infer_column_type(&rows, column_idx),
+            })
+            .collect();
+        SqlResponse { columns, rows }
+    }
+
+    /// Renders the response as CSV (RFC 4180), starting with a header row.
+    pub(crate) fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        let header = self.columns.iter().map(|column| column.name.clone());
+        push_csv_record(&mut csv, header);
+
+        for row in &self.rows {
+            let record = row.iter().map(|value| match value {
+                JsonValue::Null => String::new(),
+                JsonValue::String(text) => text.clone(),
+                other => other.to_string(),
+            });
+            push_csv_record(&mut csv, record);
+        }
+        csv
+    }
+}
+
+fn infer_column_type(rows: &[Vec<JsonValue>], column_idx: usize) -> &'static str {
+    let mut column_type = "null";
+
+    for value in rows.iter().filter_map(|row| row.get(column_idx)) {
+        let value_type = match value {
+            JsonValue::Null => continue,
+            JsonValue::Bool(_) => "boolean",
+            JsonValue::Number(number) if number.is_f64() => "double",
+            JsonValue::Number(_) => "long",
+            JsonValue::String(_) => "keyword",
+            JsonValue::Array(_) | JsonValue::Object(_) => "object",
+        };
+        column_type = match (column_type, value_type) {
+            ("null", _) => value_type,
+            ("long", "double") | ("double", "long") => "double",
+            (current, new) if current == new => current,
+            // Columns mixing values of different types are reported as objects.
+            _ => "object",
+        };
+    }
+    column_type
+}
+
+/// Appends a CSV record, quoting the fields that need it.
+pub(crate) fn push_csv_record(csv: &mut String, fields: impl Iterator<Item = String>) {
+    for (field_idx, field) in fields.enumerate() {
+        if field_idx > 0 {
+            csv.push(',');
+        }
+        if field.contains([',', '"', '\r', '\n']) {
+            csv.push('"');
+            csv.push_str(&field.replace('"', "\"\""));
+            csv.push('"');
+        } else {
+            csv.push_str(&field);
+        }
+    }
+    csv.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_sql_response_column_types() {
+        let sql_response = SqlResponse::new(
+            vec![
+                "keyword".to_string(),
+                "number".to_string(),
+                "empty".to_string(),
+                "mixed".to_string(),
+            ],
+            vec![
+                vec![json!("a"), json!(1), JsonValue::Null, json!(true)],
+                vec![json!("b"), json!(1.5), JsonValue::Null, json!("c")],
+            ],
+        );
+        let column_types: Vec<&str> = sql_response
+            .columns
+            .iter()
+            .map(|column| column.column_type)
+            .collect();
+        assert_eq!(column_types, ["keyword", "double", "null", "object"]);
+    }
+
+    #[test]
+    fn test_sql_response_to_csv() {
+        let sql_response = SqlResponse::new(
+            vec!["service".to_string(), "count, total".to_string()],
+            vec![
+                vec![json!("api"), json!(3)],
+                vec![json!("say \"hi\""), JsonValue::Null],
+                vec![json!({"a": 1}), json!(1.5)],
+            ],
+        );
+        assert_eq!(
+            sql_response.to_csv(),
+            "service,\"count, total\"\r\napi,3\r\n\"say \"\"hi\"\"\",\r\n\"{\"\"a\"\":1}\",1.5\r\n"
+        );
+    }
+}
@@ -0,0 +1,251 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use quickwit_search::{SearchError, SearchResponseRest, SearchService};
+use serde::Deserialize;
+use tracing::info;
+use warp::hyper::header::CONTENT_TYPE;
+use warp::{Filter, Rejection, Reply};
+
+use super::parser::parse_sql;
+use super::planner::SqlPlan;
+use super::response::{SqlColumn, SqlResponse};
+use crate::rest_api_response::into_rest_api_response;
+use crate::search_api::tenant_id_header;
+use crate::{BodyFormat, with_arg};
+
+#[derive(utoipa::OpenApi)]
+#[openapi(
+    paths(sql_handler),
+    components(schemas(SqlRequest, SqlResponse, SqlColumn, SqlResponseFormat))
+)]
+pub struct SqlApi;
+
+/// Body of a SQL request.
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct SqlRequest {
+    /// The `SELECT` statement to run.
+    pub query: String,
+}
+
+/// Output format of the SQL API.
+#[derive(Debug, Default, Clone, Copy, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SqlResponseFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct SqlQueryParams {
+    /// The output format: `json` (default) or `csv`.
+    #[serde(default)]
+    pub format: SqlResponseFormat,
+}
+
+/// Parses a SQL statement, runs it, and returns the resulting table.
+pub(crate) async fn sql_endpoint(
+    sql_request: SqlRequest,
+    tenant_id_opt: Option<String>,
+    search_service: &dyn SearchService,
+) -> Result<SqlResponse, SearchError> {
+    let sql = &sql_request.query;
+    let statement = parse_sql(sql)?;
+    let sql_plan = SqlPlan::build(sql, statement)?;
+
+    let mut search_request = sql_plan.search_request.clone();
+    search_request.tenant_id = tenant_id_opt;
+    let search_response = search_service.root_search(search_request).await?;
+
+    if let Some(search_error) = SearchError::from_split_errors(&search_response.failed_splits[..]) {
+        return Err(search_error);
+    }
+    let search_response_rest = SearchResponseRest::try_from(search_response)?;
+    sql_plan.build_response(search_response_rest)
+}
+
+/// Renders a table as a CSV response.
+pub(crate) fn into_csv_response(sql_response: &SqlResponse) -> warp::reply::Response {
+    warp::reply::with_header(
+        sql_response.to_csv(),
+        CONTENT_TYPE,
+        "text/csv; charset=utf-8",
+    )
+    .into_response()
+}
+
+async fn sql(
+    sql_query_params: SqlQueryParams,
+    sql_request: SqlRequest,
+    tenant_id_opt: Option<String>,
+    search_service: Arc<dyn SearchService>,
+) -> warp::reply::Response {
+    info!(query=%sql_request.query, "sql");
+    let result = sql_endpoint(sql_request, tenant_id_opt, &*search_service).await;
+
+    match (result, sql_query_params.format) {
+        (Ok(sql_response), SqlResponseFormat::Csv) => into_csv_response(&sql_response),
+        (result, _) => into_rest_api_response(result, BodyFormat::default()).into_response(),
+    }
+}
+
+#[utoipa::path(
+    post,
+    tag = "Search",
+    path = "/_sql",
+    request_body = SqlRequest,
+    responses(
+        (status = 200, description = "Successfully executed the SQL statement.", body = SqlResponse)
+    ),
+    params(SqlQueryParams)
+)]
+/// Run SQL Query
+///
+/// Runs a `SELECT` statement over one or several indexes. Supports projections, `WHERE`
+/// conditions, `GROUP BY` over fast fields, the `COUNT`, `SUM`, `AVG`, `MIN`, `MAX`, and
+/// `PERCENTILE` aggregate functions, `ORDER BY`, and `LIMIT`.
+pub fn sql_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("_sql")
+        .and(warp::post())
+        .and(warp::query())
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::body::json())
+        .and(tenant_id_header())
+        .and(with_arg(search_service))
+        .then(sql)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_json_diff::assert_json_eq;
+    use mockall::predicate;
+    use quickwit_proto::search::{Hit, SearchRequest, SearchResponse, SortOrder};
+    use quickwit_search::MockSearchService;
+    use serde_json::{Value as JsonValue, json};
+
+    use super::*;
+    use crate::recover_fn;
+
+    fn sql_handler_for_test(
+        mock_search_service: MockSearchService,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+        sql_handler(Arc::new(mock_search_service)).recover(recover_fn)
+    }
+
+    fn hit(json: JsonValue) -> Hit {
+        Hit {
+            json: json.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sql_api_select_hits() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .with(predicate::function(|search_request: &SearchRequest| {
+                search_request.index_id_patterns == ["logs"]
+                    && search_request.max_hits == 2
+                    && search_request.sort_fields[0].field_name == "timestamp"
+                    && search_request.sort_fields[0].sort_order == SortOrder::Desc as i32
+                    && search_request.aggregation_request.is_none()
+                    && search_request.tenant_id.as_deref() == Some("tenant-a")
+            }))
+            .returning(|_| {
+                Ok(SearchResponse {
+                    num_hits: 2,
+                    hits: vec![
+                        hit(json!({"service": "api", "attributes": {"status": 200}})),
+                        hit(json!({"service": "db"})),
+                    ],
+                    ..Default::default()
+                })
+            });
+        let sql_handler = sql_handler_for_test(mock_search_service);
+        let response = warp::test::request()
+            .path("/_sql")
+            .method("POST")
+            .header("x-quickwit-tenant-id", "tenant-a")
+            .json(&json!({
+                "query": "SELECT service AS svc, attributes.status FROM logs \
+                          WHERE service IN ('api', 'db') ORDER BY timestamp DESC LIMIT 2"
+            }))
+            .reply(&sql_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+        let response_json: JsonValue = serde_json::from_slice(response.body()).unwrap();
+        assert_json_eq!(
+            response_json,
+            json!({
+                "columns": [
+                    {"name": "svc", "type": "keyword"},
+                    {"name": "attributes.status", "type": "long"},
+                ],
+                "rows": [["api", 200], ["db", null]],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sql_api_csv_format() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_root_search().returning(|_| {
+            Ok(SearchResponse {
+                num_hits: 1,
+                hits: vec![hit(json!({"message": "hello, world", "level": "INFO"}))],
+                ..Default::default()
+            })
+        });
+        let sql_handler = sql_handler_for_test(mock_search_service);
+        let response = warp::test::request()
+            .path("/_sql?format=csv")
+            .method("POST")
+            .json(&json!({"query": "SELECT * FROM logs"}))
+            .reply(&sql_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "text/csv; charset=utf-8"
+        );
+        assert_eq!(
+            response.body(),
+            "level,message\r\nINFO,\"hello, world\"\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sql_api_invalid_statement() {
+        let sql_handler = sql_handler_for_test(MockSearchService::new());
+        let response = warp::test::request()
+            .path("/_sql")
+            .method("POST")
+            .json(&json!({"query": "SELECT a FROM logs JOIN traces"}))
+            .reply(&sql_handler)
+            .await;
+        assert_eq!(response.status(), 400);
+        let response_json: JsonValue = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(
+            response_json["message"],
+            "`JOIN` is not supported at line 1, column 20"
+        );
+    }
+}
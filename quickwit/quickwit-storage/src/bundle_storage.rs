@@ -282,6 +282,19 @@ impl Storage for BundleStorage {
         Ok(file_range.end - file_range.start)
     }
 
+    async fn list_files_with_prefix(&self, prefix: &Path) -> StorageResult<Vec<PathBuf>> {
+        let prefix_str = prefix.to_string_lossy();
+        let mut paths: Vec<PathBuf> = self
+            .metadata
+            .files
+            .keys()
+            .filter(|path| path.to_string_lossy().starts_with(&*prefix_str))
+            .cloned()
+            .collect();
+        paths.sort();
+        Ok(paths)
+    }
+
     fn uri(&self) -> &Uri {
         self.storage.uri()
     }
@@ -0,0 +1,153 @@
+// Copyright 2021-Present Datadog, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use quickwit_common::uri::Uri;
+use tantivy::directory::OwnedBytes;
+use tokio::io::AsyncRead;
+
+use crate::storage::SendableAsync;
+use crate::{BulkDeleteError, PutPayload, Storage, StorageResult};
+
+/// Storage proxy that counts the number of bytes read with `get_slice` and `get_all`.
+///
+/// This is used to measure the number of bytes a search downloads from the underlying storage.
+/// Streamed reads and copies are not counted.
+#[derive(Clone, Debug)]
+pub struct ByteCountingStorage {
+    underlying: Arc<dyn Storage>,
+    num_bytes_read: Arc<AtomicU64>,
+}
+
+impl ByteCountingStorage {
+    /// Creates a new `ByteCountingStorage` adding the number of bytes read to `num_bytes_read`.
+    pub fn new(storage: Arc<dyn Storage>, num_bytes_read: Arc<AtomicU64>) -> Self {
+        ByteCountingStorage {
+            underlying: storage,
+            num_bytes_read,
+        }
+    }
+
+    fn record_read(&self, bytes: &OwnedBytes) {
+        self.num_bytes_read
+            .fetch_add(bytes.len() as u64, Ordering::Relaxed);
+    }
+}
+
+#[async_trait]
+impl Storage for ByteCountingStorage {
+    async fn check_connectivity(&self) -> anyhow::Result<()> {
+        self.underlying.check_connectivity().await
+    }
+
+    async fn put(&self, path: &Path, payload: Box<dyn PutPayload>) -> StorageResult<()> {
+        self.underlying.put(path, payload).await
+    }
+
+    async fn copy_to(&self, path: &Path, output: &mut dyn SendableAsync) -> StorageResult<()> {
+        self.underlying.copy_to(path, output).await
+    }
+
+    async fn copy_to_file(&self, path: &Path, output_path: &Path) -> StorageResult<u64> {
+        self.underlying.copy_to_file(path, output_path).await
+    }
+
+    async fn get_slice(&self, path: &Path, range: Range<usize>) -> StorageResult<OwnedBytes> {
+        let bytes = self.underlying.get_slice(path, range).await?;
+        self.record_read(&bytes);
+        Ok(bytes)
+    }
+
+    async fn get_slice_stream(
+        &self,
+        path: &Path,
+        range: Range<usize>,
+    ) -> StorageResult<Box<dyn AsyncRead + Send + Unpin>> {
+        self.underlying.get_slice_stream(path, range).await
+    }
+
+    async fn get_all(&self, path: &Path) -> StorageResult<OwnedBytes> {
+        let bytes = self.underlying.get_all(path).await?;
+        self.record_read(&bytes);
+        Ok(bytes)
+    }
+
+    async fn delete(&self, path: &Path) -> StorageResult<()> {
+        self.underlying.delete(path).await
+    }
+
+    async fn bulk_delete<'a>(&self, paths: &[&'a Path]) -> Result<(), BulkDeleteError> {
+        self.underlying.bulk_delete(paths).await
+    }
+
+    async fn exists(&self, path: &Path) -> StorageResult<bool> {
+        self.underlying.exists(path).await
+    }
+
+    async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64> {
+        self.underlying.file_num_bytes(path).await
+    }
+
+    async fn list_files_with_prefix(&self, prefix: &Path) -> StorageResult<Vec<PathBuf>> {
+        self.underlying.list_files_with_prefix(prefix).await
+    }
+
+    async fn list_files_with_prefix_after(
+        &self,
+        prefix: &Path,
+        start_after: &Path,
+    ) -> StorageResult<Vec<PathBuf>> {
+        self.underlying
+            .list_files_with_prefix_after(prefix, start_after)
+            .await
+    }
+
+    fn uri(&self) -> &Uri {
+        self.underlying.uri()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RamStorage;
+
+    #[tokio::test]
+    async fn test_byte_counting_storage() {
+        let ram_storage = RamStorage::builder()
+            .put("split.split", b"hello, world")
+            .build();
+        let num_bytes_read = Arc::new(AtomicU64::new(0));
+        let storage = ByteCountingStorage::new(Arc::new(ram_storage), num_bytes_read.clone());
+        let path = Path::new("split.split");
+
+        let bytes = storage.get_slice(path, 0..5).await.unwrap();
+        assert_eq!(bytes.as_slice(), b"hello");
+        assert_eq!(num_bytes_read.load(Ordering::Relaxed), 5);
+
+        storage.get_all(path).await.unwrap();
+        assert_eq!(num_bytes_read.load(Ordering::Relaxed), 17);
+
+        storage
+            .get_slice(Path::new("missing"), 0..5)
+            .await
+            .unwrap_err();
+        assert_eq!(num_bytes_read.load(Ordering::Relaxed), 17);
+    }
+}
@@ -14,7 +14,7 @@
 
 use std::fmt;
 use std::ops::Range;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -104,6 +104,20 @@ impl Storage for StorageWithCache {
         self.storage.file_num_bytes(path).await
     }
 
+    async fn list_files_with_prefix(&self, prefix: &Path) -> StorageResult<Vec<PathBuf>> {
+        self.storage.list_files_with_prefix(prefix).await
+    }
+
+    async fn list_files_with_prefix_after(
+        &self,
+        prefix: &Path,
+        start_after: &Path,
+    ) -> StorageResult<Vec<PathBuf>> {
+        self.storage
+            .list_files_with_prefix_after(prefix, start_after)
+            .await
+    }
+
     fn uri(&self) -> &Uri {
         self.storage.uri()
     }
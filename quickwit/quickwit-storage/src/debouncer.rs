@@ -194,6 +194,20 @@ impl<T: Storage> Storage for DebouncedStorage<T> {
     async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64> {
         self.underlying.file_num_bytes(path).await
     }
+
+    async fn list_files_with_prefix(&self, prefix: &Path) -> StorageResult<Vec<PathBuf>> {
+        self.underlying.list_files_with_prefix(prefix).await
+    }
+
+    async fn list_files_with_prefix_after(
+        &self,
+        prefix: &Path,
+        start_after: &Path,
+    ) -> StorageResult<Vec<PathBuf>> {
+        self.underlying
+            .list_files_with_prefix_after(prefix, start_after)
+            .await
+    }
 }
 
 #[cfg(test)]
@@ -25,6 +25,7 @@
 //! - etc.
 //!
 //! The `BundleStorage` bundles together multiple files into a single file.
+mod byte_counting_storage;
 mod cache;
 mod debouncer;
 mod file_descriptor_cache;
@@ -60,6 +61,7 @@ pub use tantivy::directory::OwnedBytes;
 pub use versioned_component::VersionedComponent;
 
 pub use self::bundle_storage::{BundleStorage, BundleStorageFileOffsets};
+pub use self::byte_counting_storage::ByteCountingStorage;
 #[cfg(any(test, feature = "testsuite"))]
 pub use self::cache::MockStorageCache;
 pub use self::cache::{
@@ -162,7 +164,7 @@ mod tests {
 #[cfg(any(test, feature = "integration-testsuite"))]
 pub(crate) mod test_suite {
 
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
 
     use anyhow::Context;
     use tokio::io::AsyncReadExt;
@@ -318,6 +320,56 @@ pub(crate) mod test_suite {
         Ok(())
     }
 
+    async fn test_list_files_with_prefix(storage: &mut dyn Storage) -> anyhow::Result<()> {
+        let test_paths = [
+            Path::new("list_files/app/2026/01.json"),
+            Path::new("list_files/app/2026/02.json"),
+            Path::new("list_files/app.json"),
+            Path::new("list_files/other/01.json"),
+        ];
+        for test_path in test_paths {
+            storage.put(test_path, Box::new(b"123".to_vec())).await?;
+        }
+        let paths = storage
+            .list_files_with_prefix(Path::new("list_files/app/"))
+            .await?;
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from("list_files/app/2026/01.json"),
+                PathBuf::from("list_files/app/2026/02.json"),
+            ]
+        );
+        let paths = storage
+            .list_files_with_prefix(Path::new("list_files/app"))
+            .await?;
+        assert_eq!(paths.len(), 3);
+
+        let paths = storage
+            .list_files_with_prefix(Path::new("list_files/missing"))
+            .await?;
+        assert!(paths.is_empty());
+
+        let paths = storage
+            .list_files_with_prefix_after(
+                Path::new("list_files/app/"),
+                Path::new("list_files/app/2026/01.json"),
+            )
+            .await?;
+        assert_eq!(paths, [PathBuf::from("list_files/app/2026/02.json")]);
+
+        let paths = storage
+            .list_files_with_prefix_after(
+                Path::new("list_files/app/"),
+                Path::new("list_files/app/2026/02.json"),
+            )
+            .await?;
+        assert!(paths.is_empty());
+
+        storage.bulk_delete(&test_paths).await?;
+        Ok(())
+    }
+
     /// Generic test suite for a storage.
     pub async fn storage_test_suite(storage: &mut dyn Storage) -> anyhow::Result<()> {
         test_get_inexistent_file(storage)
@@ -347,6 +399,9 @@ pub(crate) mod test_suite {
         test_delete_missing_file(storage)
             .await
             .context("delete_missing_file")?;
+        test_list_files_with_prefix(storage)
+            .await
+            .context("list_files_with_prefix")?;
         Ok(())
     }
 
@@ -116,6 +116,45 @@ fn ensure_valid_relative_path(path: &Path) -> StorageResult<()> {
     Ok(())
 }
 
+/// Lists the files under `root` whose path relative to `root` starts with `prefix`.
+fn list_files_with_prefix(root: &Path, prefix: &Path) -> StorageResult<Vec<PathBuf>> {
+    let prefix_str = prefix.to_string_lossy();
+    // Unless it ends with a separator, the prefix may end with a partial file or directory name,
+    // in which case the candidates live in its parent directory.
+    let start_dir = if prefix_str.is_empty() || prefix_str.ends_with(std::path::MAIN_SEPARATOR) {
+        root.join(prefix)
+    } else {
+        root.join(prefix.parent().unwrap_or(Path::new("")))
+    };
+    let mut dirs_to_visit = vec![start_dir];
+    let mut paths = Vec::new();
+
+    while let Some(dir) = dirs_to_visit.pop() {
+        let dir_entries = match std::fs::read_dir(&dir) {
+            Ok(dir_entries) => dir_entries,
+            Err(error) if error.kind() == ErrorKind::NotFound => continue,
+            Err(error) => return Err(error.into()),
+        };
+        for dir_entry_res in dir_entries {
+            let dir_entry = dir_entry_res?;
+            let path = dir_entry.path();
+
+            if dir_entry.file_type()?.is_dir() {
+                dirs_to_visit.push(path);
+                continue;
+            }
+            let Ok(relative_path) = path.strip_prefix(root) else {
+                continue;
+            };
+            if relative_path.to_string_lossy().starts_with(&*prefix_str) {
+                paths.push(relative_path.to_path_buf());
+            }
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
 /// Delete empty directories starting from `{root}/{path}` directory and stopping at `{root}`
 /// directory. Note that the `{root}` directory is not deleted.
 fn delete_all_dirs_if_empty<'a>(
@@ -322,6 +361,17 @@ impl Storage for LocalFileStorage {
         &self.uri
     }
 
+    async fn list_files_with_prefix(&self, prefix: &Path) -> StorageResult<Vec<PathBuf>> {
+        ensure_valid_relative_path(prefix)?;
+        let root = self.root.clone();
+        let prefix = prefix.to_path_buf();
+        tokio::task::spawn_blocking(move || list_files_with_prefix(&root, &prefix))
+            .await
+            .map_err(|_| {
+                StorageErrorKind::Internal.with_error(anyhow::anyhow!("listing files panicked"))
+            })?
+    }
+
     async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64> {
         let full_path = self.full_path(path)?;
         match tokio::fs::metadata(full_path).await {
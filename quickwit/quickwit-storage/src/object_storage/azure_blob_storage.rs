@@ -522,6 +522,29 @@ impl Storage for AzureBlobStorage {
         }
     }
 
+    async fn list_files_with_prefix(&self, prefix: &Path) -> StorageResult<Vec<PathBuf>> {
+        let blob_name_prefix = self.blob_name(prefix);
+        let mut list_blobs_stream = self
+            .container_client
+            .list_blobs()
+            .prefix(blob_name_prefix)
+            .into_stream();
+        let mut paths = Vec::new();
+
+        while let Some(list_blobs_result) = list_blobs_stream.next().await {
+            let list_blobs_response = list_blobs_result
+                .map_err(|err| StorageError::from(AzureErrorWrapper::from(err)))?;
+
+            for blob in list_blobs_response.blobs.blobs() {
+                if let Ok(path) = Path::new(&blob.name).strip_prefix(&self.prefix) {
+                    paths.push(path.to_path_buf());
+                }
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
     fn uri(&self) -> &Uri {
         &self.uri
     }
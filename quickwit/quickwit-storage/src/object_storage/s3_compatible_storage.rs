@@ -713,6 +713,48 @@ impl S3CompatibleObjectStorage {
             unattempted,
         })
     }
+
+    async fn list_objects(
+        &self,
+        prefix: &Path,
+        start_after_opt: Option<&Path>,
+    ) -> StorageResult<Vec<PathBuf>> {
+        let bucket = self.bucket.clone();
+        let key_prefix = self.key(prefix);
+        let start_after_key_opt = start_after_opt.map(|start_after| self.key(start_after));
+        let mut paths = Vec::new();
+        let mut continuation_token_opt: Option<String> = None;
+
+        loop {
+            let _permit = REQUEST_SEMAPHORE.acquire().await;
+            let list_objects_output = aws_retry(&self.retry_params, || async {
+                self.s3_client
+                    .list_objects_v2()
+                    .bucket(&bucket)
+                    .prefix(&key_prefix)
+                    .set_start_after(start_after_key_opt.clone())
+                    .set_continuation_token(continuation_token_opt.clone())
+                    .send()
+                    .await
+            })
+            .await?;
+
+            for object in list_objects_output.contents() {
+                if let Some(key) = object.key() {
+                    paths.push(self.relative_path(key));
+                }
+            }
+            continuation_token_opt = list_objects_output
+                .next_continuation_token()
+                .map(|continuation_token| continuation_token.to_string());
+
+            if continuation_token_opt.is_none() {
+                break;
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
 }
 
 async fn download_all(byte_stream: ByteStream, output: &mut Vec<u8>) -> io::Result<()> {
@@ -876,6 +918,18 @@ impl Storage for S3CompatibleObjectStorage {
         Ok(head_object_output.content_length().unwrap_or(0) as u64)
     }
 
+    async fn list_files_with_prefix(&self, prefix: &Path) -> StorageResult<Vec<PathBuf>> {
+        self.list_objects(prefix, None).await
+    }
+
+    async fn list_files_with_prefix_after(
+        &self,
+        prefix: &Path,
+        start_after: &Path,
+    ) -> StorageResult<Vec<PathBuf>> {
+        self.list_objects(prefix, Some(start_after)).await
+    }
+
     fn uri(&self) -> &Uri {
         &self.uri
     }
@@ -14,7 +14,7 @@
 
 use std::fmt;
 use std::ops::Range;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
 use futures::AsyncWriteExt as FuturesAsyncWriteExt;
@@ -229,6 +229,18 @@ impl Storage for OpendalStorage {
         Ok(meta.content_length())
     }
 
+    async fn list_files_with_prefix(&self, prefix: &Path) -> StorageResult<Vec<PathBuf>> {
+        let prefix = prefix.as_os_str().to_string_lossy();
+        let entries = self.op.list_with(&prefix).recursive(true).await?;
+        let mut paths: Vec<PathBuf> = entries
+            .into_iter()
+            .filter(|entry| entry.metadata().is_file())
+            .map(|entry| PathBuf::from(entry.path()))
+            .collect();
+        paths.sort();
+        Ok(paths)
+    }
+
     fn uri(&self) -> &Uri {
         &self.uri
     }
@@ -114,6 +114,39 @@ impl Storage for PrefixStorage {
     async fn file_num_bytes(&self, path: &Path) -> crate::StorageResult<u64> {
         self.storage.file_num_bytes(&self.prefix.join(path)).await
     }
+
+    async fn list_files_with_prefix(&self, prefix: &Path) -> crate::StorageResult<Vec<PathBuf>> {
+        let prefixed_paths = self
+            .storage
+            .list_files_with_prefix(&self.prefix.join(prefix))
+            .await?;
+        Ok(self.strip_prefix_from_paths(prefixed_paths))
+    }
+
+    async fn list_files_with_prefix_after(
+        &self,
+        prefix: &Path,
+        start_after: &Path,
+    ) -> crate::StorageResult<Vec<PathBuf>> {
+        let prefixed_paths = self
+            .storage
+            .list_files_with_prefix_after(&self.prefix.join(prefix), &self.prefix.join(start_after))
+            .await?;
+        Ok(self.strip_prefix_from_paths(prefixed_paths))
+    }
+}
+
+impl PrefixStorage {
+    fn strip_prefix_from_paths(&self, prefixed_paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        prefixed_paths
+            .into_iter()
+            .filter_map(|path| {
+                path.strip_prefix(&self.prefix)
+                    .ok()
+                    .map(|path| path.to_path_buf())
+            })
+            .collect()
+    }
 }
 
 /// Creates a [`PrefixStorage`] using an underlying storage and a prefix.
@@ -135,6 +135,20 @@ impl Storage for RamStorage {
         Ok(())
     }
 
+    async fn list_files_with_prefix(&self, prefix: &Path) -> StorageResult<Vec<PathBuf>> {
+        let prefix_str = prefix.to_string_lossy();
+        let mut paths: Vec<PathBuf> = self
+            .files
+            .read()
+            .await
+            .keys()
+            .filter(|path| path.to_string_lossy().starts_with(&*prefix_str))
+            .cloned()
+            .collect();
+        paths.sort();
+        Ok(paths)
+    }
+
     async fn get_all(&self, path: &Path) -> StorageResult<OwnedBytes> {
         let payload_bytes = self.get_data(path).await.ok_or_else(|| {
             StorageErrorKind::NotFound
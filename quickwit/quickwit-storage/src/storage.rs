@@ -135,6 +135,27 @@ pub trait Storage: fmt::Debug + Send + Sync + 'static {
     /// Returns a file size.
     async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64>;
 
+    /// Lists the files whose path starts with `prefix`, sorted by path.
+    ///
+    /// As for object storages, the prefix is matched against the entire path, so the prefix
+    /// `logs/app` matches both `logs/app/file.json` and `logs/app.json`.
+    async fn list_files_with_prefix(&self, prefix: &Path) -> StorageResult<Vec<PathBuf>>;
+
+    /// Lists the files whose path starts with `prefix` and is strictly greater than `start_after`
+    /// in byte order, sorted by path.
+    ///
+    /// Object storages that support it only list the keys after `start_after`, which keeps
+    /// repeated listings of a growing prefix cheap.
+    async fn list_files_with_prefix_after(
+        &self,
+        prefix: &Path,
+        start_after: &Path,
+    ) -> StorageResult<Vec<PathBuf>> {
+        let mut paths = self.list_files_with_prefix(prefix).await?;
+        paths.retain(|path| path.as_os_str() > start_after.as_os_str());
+        Ok(paths)
+    }
+
     /// Returns an URI identifying the storage
     fn uri(&self) -> &Uri;
 }
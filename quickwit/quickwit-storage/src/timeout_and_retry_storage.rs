@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use std::ops::Range;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -146,6 +146,20 @@ impl Storage for TimeoutAndRetryStorage {
         self.underlying.file_num_bytes(path).await
     }
 
+    async fn list_files_with_prefix(&self, prefix: &Path) -> StorageResult<Vec<PathBuf>> {
+        self.underlying.list_files_with_prefix(prefix).await
+    }
+
+    async fn list_files_with_prefix_after(
+        &self,
+        prefix: &Path,
+        start_after: &Path,
+    ) -> StorageResult<Vec<PathBuf>> {
+        self.underlying
+            .list_files_with_prefix_after(prefix, start_after)
+            .await
+    }
+
     fn uri(&self) -> &Uri {
         self.underlying.uri()
     }
@@ -240,6 +254,10 @@ mod tests {
         async fn file_num_bytes(&self, _path: &Path) -> StorageResult<u64> {
             todo!();
         }
+
+        async fn list_files_with_prefix(&self, _prefix: &Path) -> StorageResult<Vec<PathBuf>> {
+            todo!();
+        }
     }
 
     #[tokio::test]